@@ -381,6 +381,19 @@ impl pallet_bridge_relayers::Config for Runtime {
 	type Reward = Balance;
 	type PaymentProcedure =
 		bp_relayers::PayLaneRewardFromAccount<pallet_balances::Pallet<Runtime>, AccountId>;
+	type MaxLanesPerClaim = ConstU32<16>;
+	type RewardDecayPerBlock = ();
+	type Currency = Balances;
+	type RequiredStake = frame_support::traits::ConstU64<1_000_000>;
+	type StakeSlashFraction = ();
+	type SlashBeneficiary = ();
+	type RewardEraLength = ();
+	type RewardPayoutDelay = ();
+	type LaneOwnerOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureRoot<AccountId>>;
+	type MaxAllowlistedRelayers = ConstU32<16>;
+	type LowRewardsPotThreshold = ();
+	type OnLowRewardsPot = ();
 	type WeightInfo = ();
 }
 
@@ -415,10 +428,14 @@ parameter_types! {
 parameter_types! {
 	/// Maximal number of authorities at Westend.
 	pub const MaxAuthoritiesAtWestend: u32 = bp_westend::MAX_AUTHORITIES_COUNT;
+	/// Maximal size of the SCALE-encoded GRANDPA justification.
+	pub const MaxFinalityProofSize: u32 = 1024 * 1024;
+	pub const AuthoritySetsToKeep: u32 = 4;
 }
 
 pub type RialtoGrandpaInstance = ();
 impl pallet_bridge_grandpa::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
 	type BridgedChain = bp_rialto::Rialto;
 	// This is a pretty unscientific cap.
 	//
@@ -427,16 +444,33 @@ impl pallet_bridge_grandpa::Config for Runtime {
 	type MaxRequests = ConstU32<50>;
 	type HeadersToKeep = HeadersToKeep;
 	type MaxBridgedAuthorities = MaxAuthoritiesAtRialto;
+	type MaxFinalityProofSize = MaxFinalityProofSize;
+	type AuthoritySetsToKeep = AuthoritySetsToKeep;
+	type TrustedCheckpoint = frame_support::traits::GetDefault;
+	type FreeHeadersInterval = frame_support::traits::GetDefault;
+	type MaxFinalizedHeadersJump = frame_support::traits::GetDefault;
+	type OnEquivocation = ();
+	type MaxStallPeriod = frame_support::traits::GetDefault;
+	type OnBridgeStalled = ();
 
 	type WeightInfo = pallet_bridge_grandpa::weights::BridgeWeight<Runtime>;
 }
 
 pub type WestendGrandpaInstance = pallet_bridge_grandpa::Instance1;
 impl pallet_bridge_grandpa::Config<WestendGrandpaInstance> for Runtime {
+	type RuntimeEvent = RuntimeEvent;
 	type BridgedChain = bp_westend::Westend;
 	type MaxRequests = ConstU32<50>;
 	type HeadersToKeep = HeadersToKeep;
 	type MaxBridgedAuthorities = MaxAuthoritiesAtWestend;
+	type MaxFinalityProofSize = MaxFinalityProofSize;
+	type AuthoritySetsToKeep = AuthoritySetsToKeep;
+	type TrustedCheckpoint = frame_support::traits::GetDefault;
+	type FreeHeadersInterval = frame_support::traits::GetDefault;
+	type MaxFinalizedHeadersJump = frame_support::traits::GetDefault;
+	type OnEquivocation = ();
+	type MaxStallPeriod = frame_support::traits::GetDefault;
+	type OnBridgeStalled = ();
 
 	type WeightInfo = pallet_bridge_grandpa::weights::BridgeWeight<Runtime>;
 }
@@ -450,6 +484,8 @@ parameter_types! {
 	pub const MaxUnconfirmedMessagesAtInboundLane: bp_messages::MessageNonce =
 		bp_rialto::MAX_UNCONFIRMED_MESSAGES_IN_CONFIRMATION_TX;
 	pub const RootAccountForPayments: Option<AccountId> = None;
+	pub MaxImmediateDispatchWeight: Weight =
+		<bp_millau::Millau as bp_runtime::Chain>::max_extrinsic_weight() / 2;
 	pub const RialtoChainId: bp_runtime::ChainId = bp_runtime::RIALTO_CHAIN_ID;
 	pub const RialtoParachainChainId: bp_runtime::ChainId = bp_runtime::RIALTO_PARACHAIN_CHAIN_ID;
 	pub RialtoActiveOutboundLanes: &'static [bp_messages::LaneId] = &[rialto_messages::XCM_LANE];
@@ -463,10 +499,20 @@ impl pallet_bridge_messages::Config<WithRialtoMessagesInstance> for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_bridge_messages::weights::BridgeWeight<Runtime>;
 	type ActiveOutboundLanes = RialtoActiveOutboundLanes;
+	type MaxDynamicOutboundLanes = ConstU32<8>;
+	type LaneOwnerOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureRoot<AccountId>>;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type UnorderedInboundLanes = frame_support::traits::GetDefault;
+	type MaxImmediateDispatchWeight = MaxImmediateDispatchWeight;
+	type MaxParkedMessages = ConstU32<64>;
+	type MaxParkedMessagesPerLane = ConstU32<8>;
+	type MaxInboundLanesToPrunePerBlock = ConstU32<4>;
 
 	type MaximalOutboundPayloadSize = crate::rialto_messages::ToRialtoMaximalOutboundPayloadSize;
+	type MaxMessagesPerLanePerBlock = frame_support::traits::ConstU64<1_024>;
+	type MaxBytesPerLanePerBlock = ConstU32<16_777_216>;
 	type OutboundPayload = crate::rialto_messages::ToRialtoMessagePayload;
 
 	type InboundPayload = crate::rialto_messages::FromRialtoMessagePayload;
@@ -475,11 +521,16 @@ impl pallet_bridge_messages::Config<WithRialtoMessagesInstance> for Runtime {
 
 	type TargetHeaderChain = crate::rialto_messages::RialtoAsTargetHeaderChain;
 	type LaneMessageVerifier = crate::rialto_messages::ToRialtoMessageVerifier;
+	type DeliveryFeeCalculator = crate::rialto_messages::ToRialtoMessageDeliveryFeeCalculator;
 	type DeliveryConfirmationPayments = pallet_bridge_relayers::DeliveryConfirmationPaymentsAdapter<
 		Runtime,
 		frame_support::traits::ConstU64<100_000>,
 		frame_support::traits::ConstU64<10_000>,
 	>;
+	type MessageTtl = frame_support::traits::GetDefault;
+	type OnMessageExpired = ();
+	type OnMessageCancelled = ();
+	type OnDeliveryConfirmed = ();
 
 	type SourceHeaderChain = crate::rialto_messages::RialtoAsSourceHeaderChain;
 	type MessageDispatch = crate::rialto_messages::FromRialtoMessageDispatch;
@@ -493,11 +544,21 @@ impl pallet_bridge_messages::Config<WithRialtoParachainMessagesInstance> for Run
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_bridge_messages::weights::BridgeWeight<Runtime>;
 	type ActiveOutboundLanes = RialtoParachainActiveOutboundLanes;
+	type MaxDynamicOutboundLanes = ConstU32<8>;
+	type LaneOwnerOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureRoot<AccountId>>;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type UnorderedInboundLanes = frame_support::traits::GetDefault;
+	type MaxImmediateDispatchWeight = MaxImmediateDispatchWeight;
+	type MaxParkedMessages = ConstU32<64>;
+	type MaxParkedMessagesPerLane = ConstU32<8>;
+	type MaxInboundLanesToPrunePerBlock = ConstU32<4>;
 
 	type MaximalOutboundPayloadSize =
 		crate::rialto_parachain_messages::ToRialtoParachainMaximalOutboundPayloadSize;
+	type MaxMessagesPerLanePerBlock = frame_support::traits::ConstU64<1_024>;
+	type MaxBytesPerLanePerBlock = ConstU32<16_777_216>;
 	type OutboundPayload = crate::rialto_parachain_messages::ToRialtoParachainMessagePayload;
 
 	type InboundPayload = crate::rialto_parachain_messages::FromRialtoParachainMessagePayload;
@@ -506,11 +567,17 @@ impl pallet_bridge_messages::Config<WithRialtoParachainMessagesInstance> for Run
 
 	type TargetHeaderChain = crate::rialto_parachain_messages::RialtoParachainAsTargetHeaderChain;
 	type LaneMessageVerifier = crate::rialto_parachain_messages::ToRialtoParachainMessageVerifier;
+	type DeliveryFeeCalculator =
+		crate::rialto_parachain_messages::ToRialtoParachainMessageDeliveryFeeCalculator;
 	type DeliveryConfirmationPayments = pallet_bridge_relayers::DeliveryConfirmationPaymentsAdapter<
 		Runtime,
 		frame_support::traits::ConstU64<100_000>,
 		frame_support::traits::ConstU64<10_000>,
 	>;
+	type MessageTtl = frame_support::traits::GetDefault;
+	type OnMessageExpired = ();
+	type OnMessageCancelled = ();
+	type OnDeliveryConfirmed = ();
 
 	type SourceHeaderChain = crate::rialto_parachain_messages::RialtoParachainAsSourceHeaderChain;
 	type MessageDispatch = crate::rialto_parachain_messages::FromRialtoParachainMessageDispatch;
@@ -519,7 +586,7 @@ impl pallet_bridge_messages::Config<WithRialtoParachainMessagesInstance> for Run
 
 parameter_types! {
 	pub const RialtoParachainMessagesLane: bp_messages::LaneId = rialto_parachain_messages::XCM_LANE;
-	pub const RialtoParachainId: u32 = bp_rialto_parachain::RIALTO_PARACHAIN_ID;
+	pub RialtoParachainIds: Vec<u32> = vec![bp_rialto_parachain::RIALTO_PARACHAIN_ID];
 	pub const RialtoParasPalletName: &'static str = bp_rialto::PARAS_PALLET_NAME;
 	pub const WestendParasPalletName: &'static str = bp_westend::PARAS_PALLET_NAME;
 	pub const MaxRialtoParaHeadDataSize: u32 = bp_rialto::MAX_NESTED_PARACHAIN_HEAD_DATA_SIZE;
@@ -532,12 +599,19 @@ pub type WithRialtoParachainsInstance = ();
 impl pallet_bridge_parachains::Config<WithRialtoParachainsInstance> for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_bridge_parachains::weights::BridgeWeight<Runtime>;
-	type BridgesGrandpaPalletInstance = RialtoGrandpaInstance;
+	type BridgedRelayChain = bp_rialto::Rialto;
+	type RelayChainHeaders =
+		pallet_bridge_grandpa::GrandpaChainHeaders<Runtime, RialtoGrandpaInstance>;
 	type ParasPalletName = RialtoParasPalletName;
 	type ParaStoredHeaderDataBuilder =
 		SingleParaStoredHeaderDataBuilder<bp_rialto_parachain::RialtoParachain>;
 	type HeadsToKeep = HeadersToKeep;
 	type MaxParaHeadDataSize = MaxRialtoParaHeadDataSize;
+	type OnlyRegisteredParachains = frame_support::traits::ConstBool<false>;
+	type MaxHeadsReorgDepth = frame_support::traits::GetDefault;
+	type FreeHeadsInterval = frame_support::traits::GetDefault;
+	type Currency = Balances;
+	type ParachainRegistrationDeposit = frame_support::traits::GetDefault;
 }
 
 /// Instance of the with-Westend parachains pallet.
@@ -546,11 +620,18 @@ pub type WithWestendParachainsInstance = pallet_bridge_parachains::Instance1;
 impl pallet_bridge_parachains::Config<WithWestendParachainsInstance> for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_bridge_parachains::weights::BridgeWeight<Runtime>;
-	type BridgesGrandpaPalletInstance = WestendGrandpaInstance;
+	type BridgedRelayChain = bp_westend::Westend;
+	type RelayChainHeaders =
+		pallet_bridge_grandpa::GrandpaChainHeaders<Runtime, WestendGrandpaInstance>;
 	type ParasPalletName = WestendParasPalletName;
 	type ParaStoredHeaderDataBuilder = SingleParaStoredHeaderDataBuilder<bp_westend::Westmint>;
 	type HeadsToKeep = HeadersToKeep;
 	type MaxParaHeadDataSize = MaxWestendParaHeadDataSize;
+	type OnlyRegisteredParachains = frame_support::traits::ConstBool<false>;
+	type MaxHeadsReorgDepth = frame_support::traits::GetDefault;
+	type FreeHeadsInterval = frame_support::traits::GetDefault;
+	type Currency = Balances;
+	type ParachainRegistrationDeposit = frame_support::traits::GetDefault;
 }
 
 impl pallet_utility::Config for Runtime {
@@ -590,11 +671,11 @@ construct_runtime!(
 
 		// Rialto bridge modules.
 		BridgeRelayers: pallet_bridge_relayers::{Pallet, Call, Storage, Event<T>},
-		BridgeRialtoGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage},
+		BridgeRialtoGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage, Event<T>},
 		BridgeRialtoMessages: pallet_bridge_messages::{Pallet, Call, Storage, Event<T>, Config<T>},
 
 		// Westend bridge modules.
-		BridgeWestendGrandpa: pallet_bridge_grandpa::<Instance1>::{Pallet, Call, Config<T>, Storage},
+		BridgeWestendGrandpa: pallet_bridge_grandpa::<Instance1>::{Pallet, Call, Config<T>, Storage, Event<T>},
 		BridgeWestendParachains: pallet_bridge_parachains::<Instance1>::{Pallet, Call, Storage, Event<T>},
 
 		// RialtoParachain bridge modules.
@@ -623,10 +704,14 @@ pub type BridgeRefundRialtoParachainRelayers =
 		RialtoGrandpaInstance,
 		WithRialtoParachainsInstance,
 		WithRialtoParachainMessagesInstance,
+		bridge_runtime_common::refund_relayer_extension::UtilityBatchCallUnpacker<Runtime>,
 		BridgeRejectObsoleteHeadersAndMessages,
-		RialtoParachainId,
+		RialtoParachainIds,
 		RialtoParachainMessagesLane,
 		Runtime,
+		(),
+		(),
+		(),
 	>;
 
 /// The address format for describing accounts.
@@ -666,8 +751,16 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+/// Migrations that are executed on runtime upgrade.
+pub type Migrations = (
+	pallet_bridge_relayers::migration::MigrateToV1<Runtime>,
+	pallet_bridge_grandpa::migration::MigrateToV1<Runtime, RialtoGrandpaInstance>,
+	pallet_bridge_grandpa::migration::MigrateToV1<Runtime, WestendGrandpaInstance>,
+);
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -863,12 +956,38 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl bp_rialto::RialtoHeaderAncestryApi<Block> for Runtime {
+		fn is_finalized_ancestor(hash: bp_rialto::Hash) -> bool {
+			BridgeRialtoGrandpa::is_finalized_ancestor(hash)
+		}
+	}
+
+	impl bp_rialto::RialtoBridgedHeaderApi<Block> for Runtime {
+		fn best_finalized_with_state_root(
+		) -> Option<(HeaderId<bp_rialto::Hash, bp_rialto::BlockNumber>, bp_rialto::Hash)> {
+			BridgeRialtoGrandpa::best_finalized_with_state_root()
+		}
+	}
+
 	impl bp_westend::WestendFinalityApi<Block> for Runtime {
 		fn best_finalized() -> Option<HeaderId<bp_westend::Hash, bp_westend::BlockNumber>> {
 			BridgeWestendGrandpa::best_finalized()
 		}
 	}
 
+	impl bp_westend::WestendHeaderAncestryApi<Block> for Runtime {
+		fn is_finalized_ancestor(hash: bp_westend::Hash) -> bool {
+			BridgeWestendGrandpa::is_finalized_ancestor(hash)
+		}
+	}
+
+	impl bp_westend::WestendBridgedHeaderApi<Block> for Runtime {
+		fn best_finalized_with_state_root(
+		) -> Option<(HeaderId<bp_westend::Hash, bp_westend::BlockNumber>, bp_westend::Hash)> {
+			BridgeWestendGrandpa::best_finalized_with_state_root()
+		}
+	}
+
 	impl bp_westend::WestmintFinalityApi<Block> for Runtime {
 		fn best_finalized() -> Option<HeaderId<bp_westend::Hash, bp_westend::BlockNumber>> {
 			pallet_bridge_parachains::Pallet::<
@@ -878,6 +997,16 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl bp_westend::WestmintBridgedHeaderApi<Block> for Runtime {
+		fn best_finalized_with_state_root(
+		) -> Option<(HeaderId<bp_westend::Hash, bp_westend::BlockNumber>, bp_westend::Hash)> {
+			pallet_bridge_parachains::Pallet::<
+				Runtime,
+				WithWestendParachainsInstance,
+			>::best_parachain_head_id_with_state_root::<bp_westend::Westmint>().unwrap_or(None)
+		}
+	}
+
 	impl bp_rialto_parachain::RialtoParachainFinalityApi<Block> for Runtime {
 		fn best_finalized() -> Option<HeaderId<bp_rialto::Hash, bp_rialto::BlockNumber>> {
 			pallet_bridge_parachains::Pallet::<
@@ -887,6 +1016,17 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl bp_rialto_parachain::RialtoParachainBridgedHeaderApi<Block> for Runtime {
+		fn best_finalized_with_state_root(
+		) -> Option<(HeaderId<bp_rialto::Hash, bp_rialto::BlockNumber>, bp_rialto::Hash)> {
+			pallet_bridge_parachains::Pallet::<
+				Runtime,
+				WithRialtoParachainsInstance,
+			>::best_parachain_head_id_with_state_root::<bp_rialto_parachain::RialtoParachain>()
+				.unwrap_or(None)
+		}
+	}
+
 	impl bp_rialto::ToRialtoOutboundLaneApi<Block> for Runtime {
 		fn message_details(
 			lane: bp_messages::LaneId,
@@ -898,6 +1038,13 @@ impl_runtime_apis! {
 				WithRialtoMessagesInstance,
 			>(lane, begin, end)
 		}
+
+		fn lane_diagnostics(lane: bp_messages::LaneId) -> bp_messages::OutboundLaneDiagnostics {
+			bridge_runtime_common::messages_api::outbound_lane_diagnostics::<
+				Runtime,
+				WithRialtoMessagesInstance,
+			>(lane)
+		}
 	}
 
 	impl bp_rialto::FromRialtoInboundLaneApi<Block> for Runtime {
@@ -910,6 +1057,25 @@ impl_runtime_apis! {
 				WithRialtoMessagesInstance,
 			>(lane, messages)
 		}
+
+		fn message_delivery_proof_keys(
+			lane: bp_messages::LaneId,
+			nonce: bp_messages::MessageNonce,
+		) -> Option<bp_messages::InboundMessageDeliveryProofKeys> {
+			bridge_runtime_common::messages_api::message_delivery_proof_keys::<
+				Runtime,
+				WithRialtoMessagesInstance,
+			>(lane, nonce)
+		}
+
+		fn lane_diagnostics(
+			lane: bp_messages::LaneId,
+		) -> bp_messages::InboundLaneDiagnostics<bp_rialto::AccountId> {
+			bridge_runtime_common::messages_api::inbound_lane_diagnostics::<
+				Runtime,
+				WithRialtoMessagesInstance,
+			>(lane)
+		}
 	}
 
 	impl bp_rialto_parachain::ToRialtoParachainOutboundLaneApi<Block> for Runtime {
@@ -923,6 +1089,13 @@ impl_runtime_apis! {
 				WithRialtoParachainMessagesInstance,
 			>(lane, begin, end)
 		}
+
+		fn lane_diagnostics(lane: bp_messages::LaneId) -> bp_messages::OutboundLaneDiagnostics {
+			bridge_runtime_common::messages_api::outbound_lane_diagnostics::<
+				Runtime,
+				WithRialtoParachainMessagesInstance,
+			>(lane)
+		}
 	}
 
 	impl bp_rialto_parachain::FromRialtoParachainInboundLaneApi<Block> for Runtime {
@@ -935,6 +1108,52 @@ impl_runtime_apis! {
 				WithRialtoParachainMessagesInstance,
 			>(lane, messages)
 		}
+
+		fn message_delivery_proof_keys(
+			lane: bp_messages::LaneId,
+			nonce: bp_messages::MessageNonce,
+		) -> Option<bp_messages::InboundMessageDeliveryProofKeys> {
+			bridge_runtime_common::messages_api::message_delivery_proof_keys::<
+				Runtime,
+				WithRialtoParachainMessagesInstance,
+			>(lane, nonce)
+		}
+
+		fn lane_diagnostics(
+			lane: bp_messages::LaneId,
+		) -> bp_messages::InboundLaneDiagnostics<bp_rialto_parachain::AccountId> {
+			bridge_runtime_common::messages_api::inbound_lane_diagnostics::<
+				Runtime,
+				WithRialtoParachainMessagesInstance,
+			>(lane)
+		}
+	}
+
+	impl bp_parachains::BridgedParachainsApi<Block> for Runtime {
+		fn best_parachain_heads() -> Vec<(
+			bp_polkadot_core::parachains::ParaId,
+			bp_polkadot_core::parachains::ParaHash,
+			bp_polkadot_core::BlockNumber,
+		)> {
+			pallet_bridge_parachains::Pallet::<
+				Runtime,
+				WithRialtoParachainsInstance,
+			>::best_parachain_heads()
+		}
+	}
+
+	impl bp_relayers::BridgeRelayersApi<Block, AccountId, Balance> for Runtime {
+		fn invalid_submissions(relayer: AccountId) -> u32 {
+			BridgeRelayers::invalid_submissions(relayer)
+		}
+
+		fn relayer_rewards(relayer: AccountId) -> Vec<(bp_relayers::RewardsAccountParams, Balance)> {
+			BridgeRelayers::relayer_rewards(&relayer)
+		}
+
+		fn relayer_statistics(relayer: AccountId) -> bp_relayers::RelayerStatistics<Balance> {
+			BridgeRelayers::relayer_statistics(relayer)
+		}
 	}
 
 	#[cfg(feature = "runtime-benchmarks")]
@@ -949,6 +1168,7 @@ impl_runtime_apis! {
 			use pallet_bridge_messages::benchmarking::Pallet as MessagesBench;
 			use pallet_bridge_parachains::benchmarking::Pallet as ParachainsBench;
 			use pallet_bridge_relayers::benchmarking::Pallet as RelayersBench;
+			use bridge_runtime_common::refund_relayer_extension_benchmarking::Pallet as RefundExtensionBench;
 
 			let mut list = Vec::<BenchmarkList>::new();
 
@@ -956,6 +1176,7 @@ impl_runtime_apis! {
 			list_benchmark!(list, extra, pallet_bridge_grandpa, BridgeRialtoGrandpa);
 			list_benchmark!(list, extra, pallet_bridge_parachains, ParachainsBench::<Runtime, WithRialtoMessagesInstance>);
 			list_benchmark!(list, extra, pallet_bridge_relayers, RelayersBench::<Runtime>);
+			list_benchmark!(list, extra, refund_relayer_extension, RefundExtensionBench::<Runtime>);
 
 			let storage_info = AllPalletsWithSystem::storage_info();
 
@@ -998,6 +1219,10 @@ impl_runtime_apis! {
 				Pallet as RelayersBench,
 				Config as RelayersConfig,
 			};
+			use bridge_runtime_common::refund_relayer_extension_benchmarking::{
+				Pallet as RefundExtensionBench,
+				Config as RefundExtensionConfig,
+			};
 			use rialto_messages::WithRialtoMessageBridge;
 
 			impl MessagesConfig<WithRialtoMessagesInstance> for Runtime {
@@ -1018,7 +1243,13 @@ impl_runtime_apis! {
 				}
 
 				fn is_relayer_rewarded(relayer: &Self::AccountId) -> bool {
-					pallet_bridge_relayers::Pallet::<Runtime>::relayer_reward(relayer, &Self::bench_lane_id()).is_some()
+					pallet_bridge_relayers::Pallet::<Runtime>::relayer_reward(
+						relayer,
+						&bp_relayers::RewardsAccountParams::new(
+							Self::bench_lane_id(),
+							bp_relayers::RewardKind::MessageDelivery,
+						),
+					).is_some()
 				}
 			}
 
@@ -1038,7 +1269,7 @@ impl_runtime_apis! {
 					bp_polkadot_core::parachains::ParaHeadsProof,
 					Vec<(bp_polkadot_core::parachains::ParaId, bp_polkadot_core::parachains::ParaHash)>,
 				) {
-					bridge_runtime_common::parachains_benchmarking::prepare_parachain_heads_proof::<Runtime, WithRialtoParachainsInstance>(
+					bridge_runtime_common::parachains_benchmarking::prepare_parachain_heads_proof::<Runtime, RialtoGrandpaInstance, WithRialtoParachainsInstance>(
 						parachains,
 						parachain_head_size,
 						proof_size,
@@ -1048,18 +1279,20 @@ impl_runtime_apis! {
 
 			impl RelayersConfig for Runtime {
 				fn prepare_environment(
-					lane: bp_messages::LaneId,
+					rewards_account_params: bp_relayers::RewardsAccountParams,
 					reward: Balance,
 				) {
 					use frame_support::traits::fungible::Mutate;
 					let lane_rewards_account = bp_relayers::PayLaneRewardFromAccount::<
 						Balances,
 						AccountId
-					>::lane_rewards_account(lane);
+					>::lane_rewards_account(rewards_account_params.lane_id);
 					Balances::mint_into(&lane_rewards_account, reward).unwrap();
 				}
 			}
 
+			impl RefundExtensionConfig for Runtime {}
+
 			add_benchmark!(
 				params,
 				batches,
@@ -1074,6 +1307,12 @@ impl_runtime_apis! {
 				ParachainsBench::<Runtime, WithRialtoParachainsInstance>
 			);
 			add_benchmark!(params, batches, pallet_bridge_relayers, RelayersBench::<Runtime>);
+			add_benchmark!(
+				params,
+				batches,
+				refund_relayer_extension,
+				RefundExtensionBench::<Runtime>
+			);
 
 			Ok(batches)
 		}
@@ -47,6 +47,18 @@ pub type ToRialtoMessagePayload = messages::source::FromThisChainMessagePayload;
 pub type ToRialtoMessageVerifier =
 	messages::source::FromThisChainMessageVerifier<WithRialtoMessageBridge>;
 
+/// Delivery fee calculator for Millau -> Rialto messages.
+///
+/// Signed senders pay `TransactionByteFee` for every byte of the message payload. The fee grows
+/// when the lane is congested. Collected fees are burned.
+pub type ToRialtoMessageDeliveryFeeCalculator = messages::source::PayloadSizeDeliveryFeeCalculator<
+	WithRialtoMessageBridge,
+	crate::Balances,
+	frame_support::traits::GetDefault,
+	crate::TransactionByteFee,
+	(),
+>;
+
 /// Message payload for Rialto -> Millau messages.
 pub type FromRialtoMessagePayload = messages::target::FromBridgedChainMessagePayload<RuntimeCall>;
 
@@ -121,6 +133,14 @@ impl messages::BridgedChainWithMessages for Rialto {
 	fn verify_dispatch_weight(_message_payload: &[u8]) -> bool {
 		true
 	}
+
+	fn maximal_unconfirmed_messages_at_inbound_lane() -> MessageNonce {
+		bp_millau::MAX_UNCONFIRMED_MESSAGES_IN_CONFIRMATION_TX
+	}
+
+	fn maximal_unrewarded_relayer_entries_at_inbound_lane() -> MessageNonce {
+		bp_millau::MAX_UNREWARDED_RELAYERS_IN_CONFIRMATION_TX
+	}
 }
 
 #[cfg(test)]
@@ -47,6 +47,19 @@ pub type ToRialtoParachainMessagePayload = messages::source::FromThisChainMessag
 pub type ToRialtoParachainMessageVerifier =
 	messages::source::FromThisChainMessageVerifier<WithRialtoParachainMessageBridge>;
 
+/// Delivery fee calculator for Millau -> RialtoParachain messages.
+///
+/// Signed senders pay `TransactionByteFee` for every byte of the message payload. The fee grows
+/// when the lane is congested. Collected fees are burned.
+pub type ToRialtoParachainMessageDeliveryFeeCalculator =
+	messages::source::PayloadSizeDeliveryFeeCalculator<
+		WithRialtoParachainMessageBridge,
+		crate::Balances,
+		frame_support::traits::GetDefault,
+		crate::TransactionByteFee,
+		(),
+	>;
+
 /// Message payload for RialtoParachain -> Millau messages.
 pub type FromRialtoParachainMessagePayload =
 	messages::target::FromBridgedChainMessagePayload<RuntimeCall>;
@@ -120,4 +133,12 @@ impl messages::BridgedChainWithMessages for RialtoParachain {
 	fn verify_dispatch_weight(_message_payload: &[u8]) -> bool {
 		true
 	}
+
+	fn maximal_unconfirmed_messages_at_inbound_lane() -> MessageNonce {
+		bp_millau::MAX_UNCONFIRMED_MESSAGES_IN_CONFIRMATION_TX
+	}
+
+	fn maximal_unrewarded_relayer_entries_at_inbound_lane() -> MessageNonce {
+		bp_millau::MAX_UNREWARDED_RELAYERS_IN_CONFIRMATION_TX
+	}
 }
@@ -391,6 +391,19 @@ impl pallet_bridge_relayers::Config for Runtime {
 	type Reward = Balance;
 	type PaymentProcedure =
 		bp_relayers::PayLaneRewardFromAccount<pallet_balances::Pallet<Runtime>, AccountId>;
+	type MaxLanesPerClaim = ConstU32<16>;
+	type RewardDecayPerBlock = ();
+	type Currency = Balances;
+	type RequiredStake = frame_support::traits::ConstU128<1_000_000>;
+	type StakeSlashFraction = ();
+	type SlashBeneficiary = ();
+	type RewardEraLength = ();
+	type RewardPayoutDelay = ();
+	type LaneOwnerOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureRoot<AccountId>>;
+	type MaxAllowlistedRelayers = ConstU32<16>;
+	type LowRewardsPotThreshold = ();
+	type OnLowRewardsPot = ();
 	type WeightInfo = ();
 }
 
@@ -403,10 +416,14 @@ parameter_types! {
 
 	/// Maximal number of authorities at Millau.
 	pub const MaxAuthoritiesAtMillau: u32 = bp_millau::MAX_AUTHORITIES_COUNT;
+	/// Maximal size of the SCALE-encoded GRANDPA justification.
+	pub const MaxFinalityProofSize: u32 = 1024 * 1024;
+	pub const AuthoritySetsToKeep: u32 = 4;
 }
 
 pub type MillauGrandpaInstance = ();
 impl pallet_bridge_grandpa::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
 	type BridgedChain = bp_millau::Millau;
 	/// This is a pretty unscientific cap.
 	///
@@ -415,6 +432,14 @@ impl pallet_bridge_grandpa::Config for Runtime {
 	type MaxRequests = ConstU32<50>;
 	type HeadersToKeep = HeadersToKeep;
 	type MaxBridgedAuthorities = MaxAuthoritiesAtMillau;
+	type MaxFinalityProofSize = MaxFinalityProofSize;
+	type AuthoritySetsToKeep = AuthoritySetsToKeep;
+	type TrustedCheckpoint = frame_support::traits::GetDefault;
+	type FreeHeadersInterval = frame_support::traits::GetDefault;
+	type MaxFinalizedHeadersJump = frame_support::traits::GetDefault;
+	type OnEquivocation = ();
+	type MaxStallPeriod = frame_support::traits::GetDefault;
+	type OnBridgeStalled = ();
 	type WeightInfo = pallet_bridge_grandpa::weights::BridgeWeight<Runtime>;
 }
 
@@ -427,6 +452,8 @@ parameter_types! {
 	pub const MaxUnconfirmedMessagesAtInboundLane: bp_messages::MessageNonce =
 		bp_millau::MAX_UNCONFIRMED_MESSAGES_IN_CONFIRMATION_TX;
 	pub const RootAccountForPayments: Option<AccountId> = None;
+	pub MaxImmediateDispatchWeight: Weight =
+		<bp_rialto::Rialto as bp_runtime::Chain>::max_extrinsic_weight() / 2;
 	pub const BridgedChainId: bp_runtime::ChainId = bp_runtime::MILLAU_CHAIN_ID;
 	pub ActiveOutboundLanes: &'static [bp_messages::LaneId] = &[millau_messages::XCM_LANE];
 }
@@ -438,10 +465,20 @@ impl pallet_bridge_messages::Config<WithMillauMessagesInstance> for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_bridge_messages::weights::BridgeWeight<Runtime>;
 	type ActiveOutboundLanes = ActiveOutboundLanes;
+	type MaxDynamicOutboundLanes = ConstU32<8>;
+	type LaneOwnerOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureRoot<AccountId>>;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type UnorderedInboundLanes = frame_support::traits::GetDefault;
+	type MaxImmediateDispatchWeight = MaxImmediateDispatchWeight;
+	type MaxParkedMessages = ConstU32<64>;
+	type MaxParkedMessagesPerLane = ConstU32<8>;
+	type MaxInboundLanesToPrunePerBlock = ConstU32<4>;
 
 	type MaximalOutboundPayloadSize = crate::millau_messages::ToMillauMaximalOutboundPayloadSize;
+	type MaxMessagesPerLanePerBlock = frame_support::traits::ConstU64<1_024>;
+	type MaxBytesPerLanePerBlock = ConstU32<16_777_216>;
 	type OutboundPayload = crate::millau_messages::ToMillauMessagePayload;
 
 	type InboundPayload = crate::millau_messages::FromMillauMessagePayload;
@@ -450,11 +487,16 @@ impl pallet_bridge_messages::Config<WithMillauMessagesInstance> for Runtime {
 
 	type TargetHeaderChain = crate::millau_messages::MillauAsTargetHeaderChain;
 	type LaneMessageVerifier = crate::millau_messages::ToMillauMessageVerifier;
+	type DeliveryFeeCalculator = crate::millau_messages::ToMillauMessageDeliveryFeeCalculator;
 	type DeliveryConfirmationPayments = pallet_bridge_relayers::DeliveryConfirmationPaymentsAdapter<
 		Runtime,
 		frame_support::traits::ConstU128<100_000>,
 		frame_support::traits::ConstU128<100_000>,
 	>;
+	type MessageTtl = frame_support::traits::GetDefault;
+	type OnMessageExpired = ();
+	type OnMessageCancelled = ();
+	type OnDeliveryConfirmed = ();
 
 	type SourceHeaderChain = crate::millau_messages::MillauAsSourceHeaderChain;
 	type MessageDispatch = crate::millau_messages::FromMillauMessageDispatch;
@@ -465,7 +507,11 @@ pub type MillauBeefyInstance = ();
 impl pallet_bridge_beefy::Config<MillauBeefyInstance> for Runtime {
 	type MaxRequests = frame_support::traits::ConstU32<16>;
 	type CommitmentsToKeep = frame_support::traits::ConstU32<8>;
+	type HeadersToKeep = frame_support::traits::ConstU32<8>;
 	type BridgedChain = bp_millau::Millau;
+	// TODO: use `pallet_bridge_beefy::weights::BridgeWeight<Runtime>` once weights are generated by
+	// the `pallet_bridge_beefy` benchmarks
+	type WeightInfo = ();
 }
 
 construct_runtime!(
@@ -497,7 +543,7 @@ construct_runtime!(
 
 		// Millau bridge modules.
 		BridgeRelayers: pallet_bridge_relayers::{Pallet, Call, Storage, Event<T>},
-		BridgeMillauGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage},
+		BridgeMillauGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage, Event<T>},
 		BridgeMillauMessages: pallet_bridge_messages::{Pallet, Call, Storage, Event<T>, Config<T>},
 
 		// Millau bridge modules (BEEFY based).
@@ -565,8 +611,15 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+/// Migrations that are executed on runtime upgrade.
+pub type Migrations = (
+	pallet_bridge_relayers::migration::MigrateToV1<Runtime>,
+	pallet_bridge_grandpa::migration::MigrateToV1<Runtime, MillauGrandpaInstance>,
+);
+
 /// MMR helper types.
 mod mmr {
 	use super::Runtime;
@@ -687,6 +740,19 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl bp_millau::MillauHeaderAncestryApi<Block> for Runtime {
+		fn is_finalized_ancestor(hash: bp_millau::Hash) -> bool {
+			BridgeMillauGrandpa::is_finalized_ancestor(hash)
+		}
+	}
+
+	impl bp_millau::MillauBridgedHeaderApi<Block> for Runtime {
+		fn best_finalized_with_state_root(
+		) -> Option<(HeaderId<bp_millau::Hash, bp_millau::BlockNumber>, bp_millau::Hash)> {
+			BridgeMillauGrandpa::best_finalized_with_state_root()
+		}
+	}
+
 	impl sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block> for Runtime {
 		fn validate_transaction(
 			source: TransactionSource,
@@ -924,6 +990,13 @@ impl_runtime_apis! {
 				WithMillauMessagesInstance,
 			>(lane, begin, end)
 		}
+
+		fn lane_diagnostics(lane: bp_messages::LaneId) -> bp_messages::OutboundLaneDiagnostics {
+			bridge_runtime_common::messages_api::outbound_lane_diagnostics::<
+				Runtime,
+				WithMillauMessagesInstance,
+			>(lane)
+		}
 	}
 
 	impl bp_millau::FromMillauInboundLaneApi<Block> for Runtime {
@@ -936,6 +1009,70 @@ impl_runtime_apis! {
 				WithMillauMessagesInstance,
 			>(lane, messages)
 		}
+
+		fn message_delivery_proof_keys(
+			lane: bp_messages::LaneId,
+			nonce: bp_messages::MessageNonce,
+		) -> Option<bp_messages::InboundMessageDeliveryProofKeys> {
+			bridge_runtime_common::messages_api::message_delivery_proof_keys::<
+				Runtime,
+				WithMillauMessagesInstance,
+			>(lane, nonce)
+		}
+
+		fn lane_diagnostics(
+			lane: bp_messages::LaneId,
+		) -> bp_messages::InboundLaneDiagnostics<bp_millau::AccountId> {
+			bridge_runtime_common::messages_api::inbound_lane_diagnostics::<
+				Runtime,
+				WithMillauMessagesInstance,
+			>(lane)
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	impl frame_benchmarking::Benchmark<Block> for Runtime {
+		fn benchmark_metadata(extra: bool) -> (
+			Vec<frame_benchmarking::BenchmarkList>,
+			Vec<frame_support::traits::StorageInfo>,
+		) {
+			use frame_benchmarking::{list_benchmark, Benchmarking, BenchmarkList};
+			use frame_support::traits::StorageInfoTrait;
+
+			let mut list = Vec::<BenchmarkList>::new();
+
+			list_benchmark!(list, extra, pallet_bridge_beefy, BridgeMillauBeefy);
+
+			let storage_info = AllPalletsWithSystem::storage_info();
+
+			return (list, storage_info)
+		}
+
+		fn dispatch_benchmark(
+			config: frame_benchmarking::BenchmarkConfig,
+		) -> Result<Vec<frame_benchmarking::BenchmarkBatch>, sp_runtime::RuntimeString> {
+			use frame_benchmarking::{Benchmarking, BenchmarkBatch, TrackedStorageKey, add_benchmark};
+
+			let whitelist: Vec<TrackedStorageKey> = vec![
+				// Block Number
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef702a5c1b19ab7a04f536c519aca4983ac").to_vec().into(),
+				// Execution Phase
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7ff553b5a9862a516939d82b3d3d8661a").to_vec().into(),
+				// Event Count
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef70a98fdbe9ce6c55837576c60c7af3850").to_vec().into(),
+				// System Events
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef780d41e5e16056765bc8461851072c9d7").to_vec().into(),
+				// Caller 0 Account
+				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da946c154ffd9992e395af90b5b13cc6f295c77033fce8a9045824a6690bbf99c6db269502f0a8d1d2a008542d5690a0749").to_vec().into(),
+			];
+
+			let mut batches = Vec::<BenchmarkBatch>::new();
+			let params = (&config, &whitelist);
+
+			add_benchmark!(params, batches, pallet_bridge_beefy, BridgeMillauBeefy);
+
+			Ok(batches)
+		}
 	}
 }
 
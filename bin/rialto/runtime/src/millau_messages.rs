@@ -47,6 +47,18 @@ pub type ToMillauMessagePayload = messages::source::FromThisChainMessagePayload;
 pub type ToMillauMessageVerifier =
 	messages::source::FromThisChainMessageVerifier<WithMillauMessageBridge>;
 
+/// Delivery fee calculator for Rialto -> Millau messages.
+///
+/// Signed senders pay `TransactionByteFee` for every byte of the message payload. The fee grows
+/// when the lane is congested. Collected fees are burned.
+pub type ToMillauMessageDeliveryFeeCalculator = messages::source::PayloadSizeDeliveryFeeCalculator<
+	WithMillauMessageBridge,
+	crate::Balances,
+	frame_support::traits::GetDefault,
+	crate::TransactionByteFee,
+	(),
+>;
+
 /// Message payload for Millau -> Rialto messages.
 pub type FromMillauMessagePayload = messages::target::FromBridgedChainMessagePayload<RuntimeCall>;
 
@@ -121,6 +133,14 @@ impl messages::BridgedChainWithMessages for Millau {
 	fn verify_dispatch_weight(_message_payload: &[u8]) -> bool {
 		true
 	}
+
+	fn maximal_unconfirmed_messages_at_inbound_lane() -> MessageNonce {
+		bp_rialto::MAX_UNCONFIRMED_MESSAGES_IN_CONFIRMATION_TX
+	}
+
+	fn maximal_unrewarded_relayer_entries_at_inbound_lane() -> MessageNonce {
+		bp_rialto::MAX_UNREWARDED_RELAYERS_IN_CONFIRMATION_TX
+	}
 }
 
 #[cfg(test)]
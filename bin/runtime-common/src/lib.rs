@@ -19,7 +19,16 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use bp_runtime::FilterCall;
-use sp_runtime::transaction_validity::TransactionValidity;
+use codec::{Decode, Encode};
+use frame_support::{
+	CloneNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, SignedExtension},
+	transaction_validity::{TransactionValidity, TransactionValidityError, ValidTransaction},
+};
+use sp_std::marker::PhantomData;
 use xcm::v3::NetworkId;
 
 pub mod messages;
@@ -28,6 +37,8 @@ pub mod messages_benchmarking;
 pub mod messages_extension;
 pub mod parachains_benchmarking;
 pub mod refund_relayer_extension;
+pub mod refund_relayer_extension_benchmarking;
+pub mod refund_relayer_extension_weights;
 
 mod messages_generation;
 mod mock;
@@ -39,11 +50,41 @@ pub mod integrity;
 ///
 /// We need this trait in order to be able to implement it for the messages pallet,
 /// since the implementation is done outside of the pallet crate.
+///
+/// It is implemented by bridge GRANDPA, parachains and messages pallets to reject obsolete bridge
+/// transactions. It is also implemented for tuples (up to 8 elements) of filters - the call is
+/// valid if it is accepted by all filters of the tuple.
 pub trait BridgeRuntimeFilterCall<Call> {
 	/// Checks if a runtime call is valid.
 	fn validate(call: &Call) -> TransactionValidity;
 }
 
+macro_rules! impl_bridge_runtime_filter_call_for_tuple {
+	($($filter_call:ident),+) => {
+		impl<Call, $($filter_call),+> BridgeRuntimeFilterCall<Call> for ($($filter_call,)+)
+		where
+			$($filter_call: BridgeRuntimeFilterCall<Call>,)+
+		{
+			fn validate(call: &Call) -> TransactionValidity {
+				let valid = ValidTransaction::default();
+				$(
+					let valid = valid.combine_with($filter_call::validate(call)?);
+				)+
+				Ok(valid)
+			}
+		}
+	};
+}
+
+impl_bridge_runtime_filter_call_for_tuple!(F1);
+impl_bridge_runtime_filter_call_for_tuple!(F1, F2);
+impl_bridge_runtime_filter_call_for_tuple!(F1, F2, F3);
+impl_bridge_runtime_filter_call_for_tuple!(F1, F2, F3, F4);
+impl_bridge_runtime_filter_call_for_tuple!(F1, F2, F3, F4, F5);
+impl_bridge_runtime_filter_call_for_tuple!(F1, F2, F3, F4, F5, F6);
+impl_bridge_runtime_filter_call_for_tuple!(F1, F2, F3, F4, F5, F6, F7);
+impl_bridge_runtime_filter_call_for_tuple!(F1, F2, F3, F4, F5, F6, F7, F8);
+
 impl<Call, T, I> BridgeRuntimeFilterCall<Call> for pallet_bridge_grandpa::Pallet<T, I>
 where
 	pallet_bridge_grandpa::Pallet<T, I>: FilterCall<Call>,
@@ -77,11 +118,25 @@ where
 /// The goal of this extension is to avoid "mining" transactions that provide outdated bridged
 /// headers and messages. Without that extension, even honest relayers may lose their funds if
 /// there are multiple relays running and submitting the same information.
+///
+/// The generated extension also implements the `BridgeRuntimeFilterCall`, so it may be used as
+/// the `BE` parameter of the `RefundRelayerForMessagesFromParachain`. New runtimes may prefer the
+/// generic `RejectObsoleteBridgeTransactions` extension.
 #[macro_export]
 macro_rules! generate_bridge_reject_obsolete_headers_and_messages {
 	($call:ty, $account_id:ty, $($filter_call:ty),*) => {
 		#[derive(Clone, codec::Decode, Default, codec::Encode, Eq, PartialEq, frame_support::RuntimeDebug, scale_info::TypeInfo)]
 		pub struct BridgeRejectObsoleteHeadersAndMessages;
+		impl $crate::BridgeRuntimeFilterCall<$call> for BridgeRejectObsoleteHeadersAndMessages {
+			fn validate(call: &$call) -> sp_runtime::transaction_validity::TransactionValidity {
+				let valid = sp_runtime::transaction_validity::ValidTransaction::default();
+				$(
+					let valid = valid
+						.combine_with(<$filter_call as $crate::BridgeRuntimeFilterCall<$call>>::validate(call)?);
+				)*
+				Ok(valid)
+			}
+		}
 		impl sp_runtime::traits::SignedExtension for BridgeRejectObsoleteHeadersAndMessages {
 			const IDENTIFIER: &'static str = "BridgeRejectObsoleteHeadersAndMessages";
 			type AccountId = $account_id;
@@ -103,12 +158,7 @@ macro_rules! generate_bridge_reject_obsolete_headers_and_messages {
 				_info: &sp_runtime::traits::DispatchInfoOf<Self::Call>,
 				_len: usize,
 			) -> sp_runtime::transaction_validity::TransactionValidity {
-				let valid = sp_runtime::transaction_validity::ValidTransaction::default();
-				$(
-					let valid = valid
-						.combine_with(<$filter_call as $crate::BridgeRuntimeFilterCall<$call>>::validate(call)?);
-				)*
-				Ok(valid)
+				<Self as $crate::BridgeRuntimeFilterCall<$call>>::validate(call)
 			}
 
 			fn pre_dispatch(
@@ -124,6 +174,63 @@ macro_rules! generate_bridge_reject_obsolete_headers_and_messages {
 	};
 }
 
+/// Signed extension that rejects obsolete bridge transactions.
+///
+/// It is a generic version of the extension, generated by the
+/// `generate_bridge_reject_obsolete_headers_and_messages` macro. The `F` is the
+/// `BridgeRuntimeFilterCall` implementation (e.g. tuple of bridge pallets) that is used to check
+/// transactions. It has the same identifier and encoding as the generated extension, so it may
+/// replace the latter without breaking transactions format.
+#[derive(
+	CloneNoBound,
+	Decode,
+	DefaultNoBound,
+	Encode,
+	EqNoBound,
+	PartialEqNoBound,
+	RuntimeDebugNoBound,
+	TypeInfo,
+)]
+#[scale_info(skip_type_params(Call, AccountId, F))]
+pub struct RejectObsoleteBridgeTransactions<Call, AccountId, F>(PhantomData<(Call, AccountId, F)>);
+
+impl<Call, AccountId, F> SignedExtension for RejectObsoleteBridgeTransactions<Call, AccountId, F>
+where
+	Call: 'static + Send + Sync + Dispatchable,
+	AccountId: 'static + Clone + Send + Sync + Eq + sp_std::fmt::Debug + Encode + Decode + TypeInfo,
+	F: 'static + Send + Sync + BridgeRuntimeFilterCall<Call>,
+{
+	const IDENTIFIER: &'static str = "BridgeRejectObsoleteHeadersAndMessages";
+	type AccountId = AccountId;
+	type Call = Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		F::validate(call)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(drop)
+	}
+}
+
 /// A mapping over `NetworkId`.
 /// Since `NetworkId` doesn't include `Millau`, `Rialto` and `RialtoParachain`, we create some
 /// synthetic associations between these chains and `NetworkId` chains.
@@ -148,7 +255,7 @@ impl CustomNetworkId {
 
 #[cfg(test)]
 mod tests {
-	use crate::BridgeRuntimeFilterCall;
+	use crate::{BridgeRuntimeFilterCall, RejectObsoleteBridgeTransactions};
 	use frame_support::{assert_err, assert_ok};
 	use sp_runtime::{
 		traits::SignedExtension,
@@ -219,4 +326,31 @@ mod tests {
 			ValidTransaction { priority: 3, ..Default::default() }
 		)
 	}
+
+	#[test]
+	fn reject_obsolete_bridge_transactions_uses_all_filters() {
+		type Extension =
+			RejectObsoleteBridgeTransactions<MockCall, (), (FirstFilterCall, SecondFilterCall)>;
+
+		assert_err!(
+			Extension::default().validate(&(), &MockCall { data: 1 }, &(), 0),
+			InvalidTransaction::Custom(1)
+		);
+
+		assert_err!(
+			Extension::default().validate(&(), &MockCall { data: 2 }, &(), 0),
+			InvalidTransaction::Custom(2)
+		);
+
+		assert_ok!(
+			Extension::default().validate(&(), &MockCall { data: 3 }, &(), 0),
+			ValidTransaction { priority: 3, ..Default::default() }
+		);
+		assert_ok!(
+			<(FirstFilterCall, SecondFilterCall) as BridgeRuntimeFilterCall<MockCall>>::validate(
+				&MockCall { data: 3 }
+			),
+			ValidTransaction { priority: 3, ..Default::default() }
+		)
+	}
 }
@@ -22,20 +22,29 @@
 
 use bp_header_chain::{HeaderChain, HeaderChainError};
 use bp_messages::{
-	source_chain::{LaneMessageVerifier, TargetHeaderChain},
+	source_chain::{LaneMessageVerifier, MessageDeliveryFeeCalculator, TargetHeaderChain},
 	target_chain::{
 		DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages, SourceHeaderChain,
 	},
-	InboundLaneData, LaneId, Message, MessageKey, MessageNonce, MessagePayload, OutboundLaneData,
+	DeliveredMessageDispatchResult, InboundLaneData, LaneId, Message, MessageKey, MessageNonce,
+	MessagePayload, OutboundLaneData,
 };
 use bp_runtime::{messages::MessageDispatchResult, Chain, ChainId, Size, StorageProofChecker};
 pub use bp_runtime::{UnderlyingChainOf, UnderlyingChainProvider};
 use codec::{Decode, DecodeLimit, Encode};
-use frame_support::{traits::Get, weights::Weight, RuntimeDebug};
+use frame_support::{
+	traits::{Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReasons},
+	weights::Weight,
+	RuntimeDebug,
+};
 use hash_db::Hasher;
 use scale_info::TypeInfo;
-use sp_std::{convert::TryFrom, fmt::Debug, marker::PhantomData, vec::Vec};
-use sp_trie::StorageProof;
+use sp_runtime::{
+	traits::{Saturating, UniqueSaturatedFrom},
+	FixedPointNumber, FixedU128,
+};
+use sp_std::{convert::TryFrom, fmt::Debug, marker::PhantomData, ops::RangeInclusive, vec::Vec};
+use sp_trie::{CompactProof, StorageProof};
 use xcm::latest::prelude::*;
 
 /// Bidirectional message bridge.
@@ -78,6 +87,17 @@ pub trait BridgedChainWithMessages: UnderlyingChainProvider {
 	/// Returns `true` if message dispatch weight is withing expected limits. `false` means
 	/// that the message is too heavy to be sent over the bridge and shall be rejected.
 	fn verify_dispatch_weight(message_payload: &[u8]) -> bool;
+
+	/// Maximal number of unconfirmed messages at the Bridged chain inbound lane.
+	///
+	/// It must be the `MaxUnconfirmedMessagesAtInboundLane` of the Bridged chain messages pallet.
+	fn maximal_unconfirmed_messages_at_inbound_lane() -> MessageNonce;
+
+	/// Maximal number of unrewarded relayer entries at the Bridged chain inbound lane.
+	///
+	/// It must be the `MaxUnrewardedRelayerEntriesAtInboundLane` of the Bridged chain messages
+	/// pallet.
+	fn maximal_unrewarded_relayer_entries_at_inbound_lane() -> MessageNonce;
 }
 
 /// This chain in context of message bridge.
@@ -119,8 +139,9 @@ pub mod source {
 	/// Messages delivery proof from bridged chain:
 	///
 	/// - hash of finalized header;
-	/// - storage proof of inbound lane state;
-	/// - lane id.
+	/// - storage proof of inbound lane state and (optionally) dispatch results of messages;
+	/// - lane id;
+	/// - nonces of messages, which dispatch results are included in the proof.
 	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
 	pub struct FromBridgedChainMessagesDeliveryProof<BridgedHeaderHash> {
 		/// Hash of the bridge header the proof is for.
@@ -129,6 +150,8 @@ pub mod source {
 		pub storage_proof: RawStorageProof,
 		/// Lane id of which messages were delivered and the proof is for.
 		pub lane: LaneId,
+		/// Nonces of messages, which dispatch results are included in the storage proof.
+		pub dispatch_result_nonces: Vec<MessageNonce>,
 	}
 
 	impl<BridgedHeaderHash> Size for FromBridgedChainMessagesDeliveryProof<BridgedHeaderHash> {
@@ -142,9 +165,9 @@ pub mod source {
 		}
 	}
 
-	/// 'Parsed' message delivery proof - inbound lane id and its state.
+	/// 'Parsed' message delivery proof - inbound lane id, its state and proved dispatch results.
 	pub type ParsedMessagesDeliveryProofFromBridgedChain<B> =
-		(LaneId, InboundLaneData<AccountIdOf<ThisChain<B>>>);
+		(LaneId, InboundLaneData<AccountIdOf<ThisChain<B>>>, Vec<DeliveredMessageDispatchResult>);
 
 	/// Message verifier that is doing all basic checks.
 	///
@@ -166,8 +189,8 @@ pub mod source {
 	/// The error message returned from `LaneMessageVerifier` when outbound lane is disabled.
 	pub const MESSAGE_REJECTED_BY_OUTBOUND_LANE: &str =
 		"The outbound message lane has rejected the message.";
-	/// The error message returned from `LaneMessageVerifier` when too many pending messages at the
-	/// lane.
+	/// The error message returned from `LaneMessageVerifier` and `MessageDeliveryFeeCalculator`
+	/// when too many pending messages at the lane.
 	pub const TOO_MANY_PENDING_MESSAGES: &str = "Too many pending messages at the lane.";
 	/// The error message returned from `LaneMessageVerifier` when call origin is mismatch.
 	pub const BAD_ORIGIN: &str = "Unable to match the source origin to expected target origin.";
@@ -207,6 +230,93 @@ pub mod source {
 		}
 	}
 
+	/// The error message returned from `MessageDeliveryFeeCalculator` when the sender is unable to
+	/// pay the delivery fee.
+	pub const FAILED_TO_PAY_DELIVERY_FEE: &str = "Failed to pay message delivery fee.";
+
+	/// Return maximal number of pending messages at This -> Bridged chain outbound lane.
+	///
+	/// Pending messages are messages that have been sent, but their delivery is not yet confirmed.
+	/// Every such message occupies a slot at the Bridged chain inbound lane. If messages have been
+	/// delivered by different transactions, every message may also occupy its own unrewarded
+	/// relayer entry there. So the limit is the minimal of both inbound lane limits.
+	pub fn maximal_pending_messages<B: MessageBridge>() -> MessageNonce {
+		sp_std::cmp::min(
+			BridgedChain::<B>::maximal_unconfirmed_messages_at_inbound_lane(),
+			BridgedChain::<B>::maximal_unrewarded_relayer_entries_at_inbound_lane(),
+		)
+	}
+
+	/// Message delivery fee calculator that charges sender for every byte of the message payload.
+	///
+	/// The fee is `(BaseFee + ByteFee * encoded_payload_size) * fee_factor`, where `fee_factor`
+	/// reflects congestion of the outbound lane. It is withdrawn from the sender account and then
+	/// passed to the `OnFee` handler. Messages that are sent by non-signed origins are not charged.
+	///
+	/// The fee also depends on the number of pending messages at the lane (see
+	/// `maximal_pending_messages`). Once more than half of the limit is used, every extra pending
+	/// message adds the regular fee to the fee of the new message. When the limit is reached, all
+	/// new messages are rejected until delivery confirmations arrive. This way the lane is
+	/// throttled before it is blocked by limits of the Bridged chain inbound lane.
+	#[derive(RuntimeDebug)]
+	pub struct PayloadSizeDeliveryFeeCalculator<B, C, BaseFee, ByteFee, OnFee>(
+		PhantomData<(B, C, BaseFee, ByteFee, OnFee)>,
+	);
+
+	impl<B, C, BaseFee, ByteFee, OnFee> MessageDeliveryFeeCalculator<OriginOf<ThisChain<B>>>
+		for PayloadSizeDeliveryFeeCalculator<B, C, BaseFee, ByteFee, OnFee>
+	where
+		B: MessageBridge,
+		// matches requirements from the `frame_system::Config::Origin`
+		OriginOf<ThisChain<B>>: Clone
+			+ Into<Result<frame_system::RawOrigin<AccountIdOf<ThisChain<B>>>, OriginOf<ThisChain<B>>>>,
+		C: Currency<AccountIdOf<ThisChain<B>>>,
+		BaseFee: Get<C::Balance>,
+		ByteFee: Get<C::Balance>,
+		OnFee: OnUnbalanced<C::NegativeImbalance>,
+	{
+		type Error = &'static str;
+
+		fn pay_delivery_fee(
+			submitter: &OriginOf<ThisChain<B>>,
+			_lane: &LaneId,
+			outbound_data: &OutboundLaneData,
+			encoded_payload_size: u32,
+			fee_factor: FixedU128,
+		) -> Result<(), Self::Error> {
+			// reject message if there are too many pending messages at this lane
+			let max_pending_messages = maximal_pending_messages::<B>();
+			let pending_messages = outbound_data
+				.latest_generated_nonce
+				.saturating_sub(outbound_data.latest_received_nonce);
+			if pending_messages >= max_pending_messages {
+				return Err(TOO_MANY_PENDING_MESSAGES)
+			}
+
+			let sender = match submitter.clone().into() {
+				Ok(frame_system::RawOrigin::Signed(sender)) => sender,
+				_ => return Ok(()),
+			};
+
+			let excess_messages = pending_messages.saturating_sub(max_pending_messages / 2);
+			let fee = fee_factor
+				.saturating_mul_int(
+					ByteFee::get()
+						.saturating_mul(encoded_payload_size.into())
+						.saturating_add(BaseFee::get()),
+				)
+				.saturating_mul(C::Balance::unique_saturated_from(
+					excess_messages.saturating_add(1),
+				));
+			let imbalance =
+				C::withdraw(&sender, fee, WithdrawReasons::FEE, ExistenceRequirement::KeepAlive)
+					.map_err(|_| FAILED_TO_PAY_DELIVERY_FEE)?;
+			OnFee::on_unbalanced(imbalance);
+
+			Ok(())
+		}
+	}
+
 	/// Return maximal message size of This -> Bridged chain message.
 	pub fn maximal_message_size<B: MessageBridge>() -> u32 {
 		super::target::maximal_incoming_message_size(
@@ -229,7 +339,7 @@ pub mod source {
 
 		fn verify_messages_delivery_proof(
 			proof: Self::MessagesDeliveryProof,
-		) -> Result<(LaneId, InboundLaneData<AccountIdOf<ThisChain<B>>>), Self::Error> {
+		) -> Result<ParsedMessagesDeliveryProofFromBridgedChain<B>, Self::Error> {
 			verify_messages_delivery_proof::<B>(proof)
 		}
 	}
@@ -270,14 +380,18 @@ pub mod source {
 	pub fn verify_messages_delivery_proof<B: MessageBridge>(
 		proof: FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<B>>>,
 	) -> Result<ParsedMessagesDeliveryProofFromBridgedChain<B>, &'static str> {
-		let FromBridgedChainMessagesDeliveryProof { bridged_header_hash, storage_proof, lane } =
-			proof;
+		let FromBridgedChainMessagesDeliveryProof {
+			bridged_header_hash,
+			storage_proof,
+			lane,
+			dispatch_result_nonces,
+		} = proof;
 		B::BridgedHeaderChain::parse_finalized_storage_proof(
 			bridged_header_hash,
 			StorageProof::new(storage_proof),
 			|storage| {
-				// Messages delivery proof is just proof of single storage key read => any error
-				// is fatal.
+				// Messages delivery proof is proof of inbound lane state and (optionally) dispatch
+				// results of some messages => any error is fatal.
 				let storage_inbound_lane_data_key =
 					bp_messages::storage_keys::inbound_lane_data_key(
 						B::BRIDGED_MESSAGES_PALLET_NAME,
@@ -290,12 +404,87 @@ pub mod source {
 				let inbound_lane_data = InboundLaneData::decode(&mut &raw_inbound_lane_data[..])
 					.map_err(|_| "Failed to decode inbound lane state from the proof")?;
 
-				Ok((lane, inbound_lane_data))
+				// dispatch results are only stored for the latest messages, so the proof of
+				// missing dispatch result is not an error
+				let mut dispatch_results = Vec::with_capacity(dispatch_result_nonces.len());
+				for nonce in dispatch_result_nonces {
+					let storage_dispatch_result_key =
+						bp_messages::storage_keys::inbound_message_dispatch_result_key(
+							B::BRIDGED_MESSAGES_PALLET_NAME,
+							&lane,
+							nonce,
+						);
+					let raw_dispatch_result = match storage
+						.read_value(storage_dispatch_result_key.0.as_ref())
+						.map_err(|_| "Failed to read message dispatch result from storage proof")?
+					{
+						Some(raw_dispatch_result) => raw_dispatch_result,
+						None => continue,
+					};
+					dispatch_results.push(decode_delivered_message_dispatch_result(
+						nonce,
+						&raw_dispatch_result,
+					)?);
+				}
+
+				Ok((lane, inbound_lane_data, dispatch_results))
 			},
 		)
 		.map_err(<&'static str>::from)?
 	}
 
+	/// Verify proof of This -> Bridged chain message delivery and dispatch.
+	///
+	/// The storage proof must contain value of the key, returned by the
+	/// `From<ThisChain>InboundLaneApi::message_delivery_proof_keys` runtime API of the Bridged
+	/// chain. The `DispatchLevelResult` must be the same type that is used by the message
+	/// dispatcher at the Bridged chain.
+	pub fn verify_message_dispatch_proof<B: MessageBridge, DispatchLevelResult: Decode>(
+		proof: FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<B>>>,
+		nonce: MessageNonce,
+	) -> Result<MessageDispatchResult<DispatchLevelResult>, &'static str> {
+		let FromBridgedChainMessagesDeliveryProof {
+			bridged_header_hash, storage_proof, lane, ..
+		} = proof;
+		B::BridgedHeaderChain::parse_finalized_storage_proof(
+			bridged_header_hash,
+			StorageProof::new(storage_proof),
+			|storage| {
+				let storage_dispatch_result_key =
+					bp_messages::storage_keys::inbound_message_dispatch_result_key(
+						B::BRIDGED_MESSAGES_PALLET_NAME,
+						&lane,
+						nonce,
+					);
+				let raw_dispatch_result = storage
+					.read_value(storage_dispatch_result_key.0.as_ref())
+					.map_err(|_| "Failed to read message dispatch result from storage proof")?
+					.ok_or("Message dispatch result is missing from the proof")?;
+				MessageDispatchResult::decode(&mut &raw_dispatch_result[..])
+					.map_err(|_| "Failed to decode message dispatch result from the proof")
+			},
+		)
+		.map_err(<&'static str>::from)?
+	}
+
+	/// Decode dispatch result of This -> Bridged chain message without knowing type of the
+	/// dispatch-level result at the Bridged chain.
+	fn decode_delivered_message_dispatch_result(
+		nonce: MessageNonce,
+		raw_dispatch_result: &[u8],
+	) -> Result<DeliveredMessageDispatchResult, &'static str> {
+		// the encoded `MessageDispatchResult` starts with the unspent weight, followed by the
+		// encoded dispatch-level result
+		let mut raw_dispatch_result = raw_dispatch_result;
+		let unspent_weight = Weight::decode(&mut raw_dispatch_result)
+			.map_err(|_| "Failed to decode message dispatch result from the proof")?;
+		Ok(DeliveredMessageDispatchResult {
+			nonce,
+			unspent_weight,
+			encoded_dispatch_level_result: raw_dispatch_result.to_vec(),
+		})
+	}
+
 	/// XCM bridge.
 	pub trait XcmBridge {
 		/// Runtime message bridge configuration.
@@ -418,34 +607,215 @@ pub mod target {
 		}
 	}
 
+	/// Storage proof of messages, in one of supported encodings.
+	///
+	/// The variant index acts as a version byte of the encoded proof, so the relayer may select
+	/// the encoding that results in the smallest delivery transaction.
+	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum MessagesStorageProof {
+		/// Compact trie proof. Nodes that may be computed by the verifier are omitted.
+		#[codec(index = 0)]
+		Compact(CompactProof),
+		/// Zstd-compressed SCALE-encoded compact trie proof.
+		#[codec(index = 1)]
+		CompressedCompact {
+			/// Size of the SCALE-encoded compact proof before compression.
+			uncompressed_size: u32,
+			/// Compressed SCALE-encoded compact proof.
+			compressed: Vec<u8>,
+		},
+	}
+
+	impl MessagesStorageProof {
+		/// Creates compact proof from the raw storage proof of the storage with given root.
+		pub fn compact<H: Hasher>(
+			storage_proof: RawStorageProof,
+			root: H::Out,
+		) -> Result<Self, &'static str> {
+			StorageProof::new(storage_proof)
+				.into_compact_proof::<H>(root)
+				.map(MessagesStorageProof::Compact)
+				.map_err(|_| "Failed to convert storage proof into compact proof")
+		}
+
+		/// Converts proof into the storage proof that may be verified against the storage root.
+		pub fn into_storage_proof<H: Hasher>(self) -> Result<StorageProof, MessageProofError> {
+			let compact_proof = match self {
+				MessagesStorageProof::Compact(compact_proof) => compact_proof,
+				MessagesStorageProof::CompressedCompact { uncompressed_size, compressed } => {
+					let encoded_compact_proof = decompress(&compressed, uncompressed_size as _)?;
+					CompactProof::decode(&mut &encoded_compact_proof[..])
+						.map_err(|_| MessageProofError::FailedToDecodeStorageProof)?
+				},
+			};
+
+			// the root is checked later, when the proof is verified against the header
+			compact_proof
+				.to_storage_proof::<H>(None)
+				.map(|(storage_proof, _)| storage_proof)
+				.map_err(|_| MessageProofError::FailedToDecodeStorageProof)
+		}
+	}
+
+	impl Size for MessagesStorageProof {
+		fn size(&self) -> u32 {
+			match *self {
+				MessagesStorageProof::Compact(ref compact_proof) => u32::try_from(
+					compact_proof
+						.encoded_nodes
+						.iter()
+						.fold(0usize, |sum, node| sum.saturating_add(node.len())),
+				)
+				.unwrap_or(u32::MAX),
+				MessagesStorageProof::CompressedCompact { uncompressed_size, .. } =>
+					uncompressed_size,
+			}
+		}
+	}
+
+	/// Decompresses zstd-compressed blob.
+	///
+	/// Fails if the size of decompressed blob is not exactly `expected_size` bytes. The decoder
+	/// allocates its window buffer using the size from the frame header, so we reject frames
+	/// that declare window or content size larger than `expected_size` before the decoder is
+	/// created. The decompression is also aborted as soon as `expected_size` is exceeded, so we
+	/// never allocate more than the relayer has declared (and paid for).
+	fn decompress(compressed: &[u8], expected_size: usize) -> Result<Vec<u8>, MessageProofError> {
+		use ruzstd::io::Read;
+
+		ensure_zstd_frame_fits(compressed, expected_size)?;
+
+		let mut decoder = ruzstd::StreamingDecoder::new(compressed)
+			.map_err(|_| MessageProofError::FailedToDecompressStorageProof)?;
+		let mut decompressed = Vec::new();
+		let mut chunk = [0u8; 1024];
+		loop {
+			let read = decoder
+				.read(&mut chunk)
+				.map_err(|_| MessageProofError::FailedToDecompressStorageProof)?;
+			if read == 0 {
+				break
+			}
+			if decompressed.len().saturating_add(read) > expected_size {
+				return Err(MessageProofError::FailedToDecompressStorageProof)
+			}
+			decompressed.extend_from_slice(&chunk[..read]);
+		}
+
+		if decompressed.len() != expected_size {
+			return Err(MessageProofError::FailedToDecompressStorageProof)
+		}
+
+		Ok(decompressed)
+	}
+
+	/// Ensures that the window size and the content size (if present), declared by the header of
+	/// the zstd frame, are not larger than `max_size`.
+	///
+	/// See <https://github.com/facebook/zstd/blob/dev/doc/zstd_compression_format.md#frame_header>
+	/// for the frame header format.
+	fn ensure_zstd_frame_fits(compressed: &[u8], max_size: usize) -> Result<(), MessageProofError> {
+		const ZSTD_MAGIC_NUMBER: u32 = 0xFD2FB528;
+
+		let read_le = |bytes: Option<&[u8]>| -> Result<u64, MessageProofError> {
+			let bytes = bytes.ok_or(MessageProofError::FailedToDecompressStorageProof)?;
+			Ok(bytes.iter().rev().fold(0u64, |value, byte| (value << 8) | *byte as u64))
+		};
+
+		let magic_number = read_le(compressed.get(0..4))?;
+		if magic_number != ZSTD_MAGIC_NUMBER as u64 {
+			return Err(MessageProofError::FailedToDecompressStorageProof)
+		}
+
+		let descriptor = read_le(compressed.get(4..5))? as u8;
+		let is_single_segment = descriptor & 0b0010_0000 != 0;
+		let dictionary_id_size = [0, 1, 2, 4][(descriptor & 0b11) as usize];
+		let content_size_size = match descriptor >> 6 {
+			0 if is_single_segment => 1,
+			0 => 0,
+			1 => 2,
+			2 => 4,
+			_ => 8,
+		};
+
+		let mut offset = 5;
+		let window_size = if is_single_segment {
+			None
+		} else {
+			let window_descriptor = read_le(compressed.get(offset..offset + 1))?;
+			offset += 1;
+			let window_base = 1u64 << (10 + (window_descriptor >> 3));
+			Some(window_base + (window_base / 8) * (window_descriptor & 0b111))
+		};
+		offset += dictionary_id_size;
+		let content_size = match content_size_size {
+			0 => None,
+			2 => Some(read_le(compressed.get(offset..offset + 2))? + 256),
+			size => Some(read_le(compressed.get(offset..offset + size))?),
+		};
+
+		// window size of single segment frame is equal to its content size
+		let max_size = max_size as u64;
+		let is_window_size_too_large = window_size.or(content_size).map_or(false, |s| s > max_size);
+		let is_content_size_too_large = content_size.map_or(false, |s| s > max_size);
+		if is_window_size_too_large || is_content_size_too_large {
+			return Err(MessageProofError::FailedToDecompressStorageProof)
+		}
+
+		Ok(())
+	}
+
 	/// Messages proof from bridged chain:
 	///
 	/// - hash of finalized header;
 	/// - storage proof of messages and (optionally) outbound lane state;
 	/// - lane id;
-	/// - nonces (inclusive range) of messages which are included in this proof.
+	/// - nonces (inclusive range) of messages which are included in this proof;
+	/// - (optionally) lane ids and nonces of messages of other lanes, proved by the same storage
+	///   proof.
 	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
 	pub struct FromBridgedChainMessagesProof<BridgedHeaderHash> {
 		/// Hash of the finalized bridged header the proof is for.
 		pub bridged_header_hash: BridgedHeaderHash,
 		/// A storage trie proof of messages being delivered.
-		pub storage_proof: RawStorageProof,
+		pub storage_proof: MessagesStorageProof,
 		/// Messages in this proof are sent over this lane.
 		pub lane: LaneId,
 		/// Nonce of the first message being delivered.
 		pub nonces_start: MessageNonce,
 		/// Nonce of the last message being delivered.
 		pub nonces_end: MessageNonce,
+		/// Messages of other lanes, that are proved by the same `storage_proof`. Every lane may
+		/// only be mentioned once in the proof.
+		pub additional_lanes: Vec<FromBridgedChainLaneMessages>,
 	}
 
-	impl<BridgedHeaderHash> Size for FromBridgedChainMessagesProof<BridgedHeaderHash> {
-		fn size(&self) -> u32 {
-			u32::try_from(
-				self.storage_proof
+	impl<BridgedHeaderHash> FromBridgedChainMessagesProof<BridgedHeaderHash> {
+		/// Returns all lanes and nonces (inclusive range) of messages that are included in this
+		/// proof.
+		pub fn lanes(&self) -> impl Iterator<Item = (LaneId, RangeInclusive<MessageNonce>)> + '_ {
+			sp_std::iter::once((self.lane, self.nonces_start..=self.nonces_end)).chain(
+				self.additional_lanes
 					.iter()
-					.fold(0usize, |sum, node| sum.saturating_add(node.len())),
+					.map(|lane| (lane.lane, lane.nonces_start..=lane.nonces_end)),
 			)
-			.unwrap_or(u32::MAX)
+		}
+	}
+
+	/// Messages of the additional lane, included in the `FromBridgedChainMessagesProof`.
+	#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct FromBridgedChainLaneMessages {
+		/// Messages are sent over this lane.
+		pub lane: LaneId,
+		/// Nonce of the first message being delivered.
+		pub nonces_start: MessageNonce,
+		/// Nonce of the last message being delivered.
+		pub nonces_end: MessageNonce,
+	}
+
+	impl<BridgedHeaderHash> Size for FromBridgedChainMessagesProof<BridgedHeaderHash> {
+		fn size(&self) -> u32 {
+			self.storage_proof.size()
 		}
 	}
 
@@ -594,76 +964,81 @@ pub mod target {
 	/// The `messages_count` argument verification (sane limits) is supposed to be made
 	/// outside of this function. This function only verifies that the proof declares exactly
 	/// `messages_count` messages.
+	///
+	/// The proof may contain messages of multiple lanes. The storage proof is verified only
+	/// once for all of them.
 	pub fn verify_messages_proof<B: MessageBridge>(
 		proof: FromBridgedChainMessagesProof<HashOf<BridgedChain<B>>>,
 		messages_count: u32,
 	) -> Result<ProvedMessages<Message>, MessageProofError> {
-		let FromBridgedChainMessagesProof {
-			bridged_header_hash,
-			storage_proof,
-			lane,
-			nonces_start,
-			nonces_end,
-		} = proof;
+		let lanes = proof.lanes().collect::<Vec<_>>();
+		let FromBridgedChainMessagesProof { bridged_header_hash, storage_proof, .. } = proof;
+		let storage_proof = storage_proof.into_storage_proof::<HasherOf<BridgedChain<B>>>()?;
 
 		B::BridgedHeaderChain::parse_finalized_storage_proof(
 			bridged_header_hash,
-			StorageProof::new(storage_proof),
+			storage_proof,
 			|storage| {
 				let parser =
 					StorageProofCheckerAdapter::<_, B> { storage, _dummy: Default::default() };
 
 				// receiving proofs where end < begin is ok (if proof includes outbound lane state)
 				let messages_in_the_proof =
-					if let Some(nonces_difference) = nonces_end.checked_sub(nonces_start) {
-						// let's check that the user (relayer) has passed correct `messages_count`
-						// (this bounds maximal capacity of messages vec below)
-						let messages_in_the_proof = nonces_difference.saturating_add(1);
-						if messages_in_the_proof != MessageNonce::from(messages_count) {
-							return Err(MessageProofError::MessagesCountMismatch)
-						}
-
-						messages_in_the_proof
-					} else {
-						0
-					};
-
-				// Read messages first. All messages that are claimed to be in the proof must
-				// be in the proof. So any error in `read_value`, or even missing value is fatal.
-				//
-				// Mind that we allow proofs with no messages if outbound lane state is proved.
-				let mut messages = Vec::with_capacity(messages_in_the_proof as _);
-				for nonce in nonces_start..=nonces_end {
-					let message_key = MessageKey { lane_id: lane, nonce };
-					let raw_message_data = parser
-						.read_raw_message(&message_key)
-						.ok_or(MessageProofError::MissingRequiredMessage)?;
-					let payload = MessagePayload::decode(&mut &raw_message_data[..])
-						.map_err(|_| MessageProofError::FailedToDecodeMessage)?;
-					messages.push(Message { key: message_key, payload });
-				}
-
-				// Now let's check if proof contains outbound lane state proof. It is optional, so
-				// we simply ignore `read_value` errors and missing value.
-				let mut proved_lane_messages = ProvedLaneMessages { lane_state: None, messages };
-				let raw_outbound_lane_data = parser.read_raw_outbound_lane_data(&lane);
-				if let Some(raw_outbound_lane_data) = raw_outbound_lane_data {
-					proved_lane_messages.lane_state = Some(
-						OutboundLaneData::decode(&mut &raw_outbound_lane_data[..])
-							.map_err(|_| MessageProofError::FailedToDecodeOutboundLaneState)?,
-					);
-				}
-
-				// Now we may actually check if the proof is empty or not.
-				if proved_lane_messages.lane_state.is_none() &&
-					proved_lane_messages.messages.is_empty()
+					lanes.iter().fold(0, |total: MessageNonce, (_, nonces)| {
+						total.saturating_add(messages_in_range(nonces))
+					});
+				// let's check that the user (relayer) has passed correct `messages_count`
+				// (this bounds maximal capacity of messages vecs below)
+				if messages_in_the_proof != 0 &&
+					messages_in_the_proof != MessageNonce::from(messages_count)
 				{
-					return Err(MessageProofError::Empty)
+					return Err(MessageProofError::MessagesCountMismatch)
 				}
 
-				// We only support single lane messages in this generated_schema
 				let mut proved_messages = ProvedMessages::new();
-				proved_messages.insert(lane, proved_lane_messages);
+				for (lane, nonces) in lanes {
+					// every lane may only be mentioned once in the proof
+					if proved_messages.contains_key(&lane) {
+						return Err(MessageProofError::DuplicateLane)
+					}
+
+					// Read messages first. All messages that are claimed to be in the proof must
+					// be in the proof. So any error in `read_value`, or even missing value is
+					// fatal.
+					//
+					// Mind that we allow proofs with no messages if outbound lane state is proved.
+					let mut messages = Vec::with_capacity(messages_in_range(&nonces) as _);
+					for nonce in nonces {
+						let message_key = MessageKey { lane_id: lane, nonce };
+						let raw_message_data = parser
+							.read_raw_message(&message_key)
+							.ok_or(MessageProofError::MissingRequiredMessage)?;
+						let payload = MessagePayload::decode(&mut &raw_message_data[..])
+							.map_err(|_| MessageProofError::FailedToDecodeMessage)?;
+						messages.push(Message { key: message_key, payload });
+					}
+
+					// Now let's check if proof contains outbound lane state proof. It is optional,
+					// so we simply ignore `read_value` errors and missing value.
+					let mut proved_lane_messages =
+						ProvedLaneMessages { lane_state: None, messages };
+					let raw_outbound_lane_data = parser.read_raw_outbound_lane_data(&lane);
+					if let Some(raw_outbound_lane_data) = raw_outbound_lane_data {
+						proved_lane_messages.lane_state = Some(
+							OutboundLaneData::decode(&mut &raw_outbound_lane_data[..])
+								.map_err(|_| MessageProofError::FailedToDecodeOutboundLaneState)?,
+						);
+					}
+
+					// Now we may actually check if the proof is empty or not.
+					if proved_lane_messages.lane_state.is_none() &&
+						proved_lane_messages.messages.is_empty()
+					{
+						return Err(MessageProofError::Empty)
+					}
+
+					proved_messages.insert(lane, proved_lane_messages);
+				}
 
 				Ok(proved_messages)
 			},
@@ -671,6 +1046,15 @@ pub mod target {
 		.map_err(MessageProofError::HeaderChain)?
 	}
 
+	/// Returns number of messages in the inclusive nonces range.
+	fn messages_in_range(nonces: &RangeInclusive<MessageNonce>) -> MessageNonce {
+		nonces
+			.end()
+			.checked_sub(*nonces.start())
+			.map(|difference| difference.saturating_add(1))
+			.unwrap_or(0)
+	}
+
 	/// Error that happens during message proof verification.
 	#[derive(Debug, PartialEq, Eq)]
 	pub enum MessageProofError {
@@ -686,6 +1070,12 @@ pub mod target {
 		FailedToDecodeMessage,
 		/// Failed to decode outbound lane data from the proof.
 		FailedToDecodeOutboundLaneState,
+		/// The same lane is mentioned more than once in the proof.
+		DuplicateLane,
+		/// Failed to decompress storage proof.
+		FailedToDecompressStorageProof,
+		/// Failed to decode storage proof.
+		FailedToDecodeStorageProof,
 	}
 
 	impl From<MessageProofError> for &'static str {
@@ -700,6 +1090,11 @@ pub mod target {
 					"Failed to decode message from the proof",
 				MessageProofError::FailedToDecodeOutboundLaneState =>
 					"Failed to decode outbound lane data from the proof",
+				MessageProofError::DuplicateLane =>
+					"The same lane is mentioned more than once in the proof",
+				MessageProofError::FailedToDecompressStorageProof =>
+					"Failed to decompress storage proof",
+				MessageProofError::FailedToDecodeStorageProof => "Failed to decode storage proof",
 			}
 		}
 	}
@@ -749,7 +1144,7 @@ mod tests {
 	use bp_runtime::HeaderId;
 	use codec::Encode;
 	use sp_core::H256;
-	use sp_runtime::traits::Header as _;
+	use sp_runtime::traits::{ConstU64, Header as _, One};
 
 	fn test_lane_outbound_data() -> OutboundLaneData {
 		OutboundLaneData::default()
@@ -831,6 +1226,130 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn payload_size_delivery_fee_calculator_works() {
+		type Calculator = source::PayloadSizeDeliveryFeeCalculator<
+			OnThisChainBridge,
+			Balances,
+			ConstU64<100>,
+			ConstU64<10>,
+			(),
+		>;
+
+		sp_io::TestExternalities::new(Default::default()).execute_with(|| {
+			let _ = Balances::deposit_creating(&1, 10_000);
+
+			// (100 + 10 * 50) * 1.5 = 900
+			assert_eq!(
+				Calculator::pay_delivery_fee(
+					&RuntimeOrigin::signed(1),
+					&TEST_LANE_ID,
+					&test_lane_outbound_data(),
+					50,
+					FixedU128::saturating_from_rational(3, 2),
+				),
+				Ok(()),
+			);
+			assert_eq!(Balances::free_balance(1), 9_100);
+
+			// messages that are sent by non-signed origins are not charged
+			assert_eq!(
+				Calculator::pay_delivery_fee(
+					&RuntimeOrigin::root(),
+					&TEST_LANE_ID,
+					&test_lane_outbound_data(),
+					50,
+					FixedU128::one(),
+				),
+				Ok(()),
+			);
+
+			// sender can't pay the fee
+			assert_eq!(
+				Calculator::pay_delivery_fee(
+					&RuntimeOrigin::signed(1),
+					&TEST_LANE_ID,
+					&test_lane_outbound_data(),
+					1_000,
+					FixedU128::one(),
+				),
+				Err(source::FAILED_TO_PAY_DELIVERY_FEE),
+			);
+			assert_eq!(Balances::free_balance(1), 9_100);
+		});
+	}
+
+	#[test]
+	fn payload_size_delivery_fee_calculator_charges_more_when_lane_has_many_pending_messages() {
+		type Calculator = source::PayloadSizeDeliveryFeeCalculator<
+			OnThisChainBridge,
+			Balances,
+			ConstU64<100>,
+			ConstU64<10>,
+			(),
+		>;
+
+		sp_io::TestExternalities::new(Default::default()).execute_with(|| {
+			let _ = Balances::deposit_creating(&1, 10_000);
+			let max_pending_messages = source::maximal_pending_messages::<OnThisChainBridge>();
+			assert_eq!(max_pending_messages, BRIDGED_CHAIN_MAX_UNREWARDED_RELAYERS_AT_INBOUND_LANE);
+			let outbound_data = |pending_messages| OutboundLaneData {
+				latest_generated_nonce: 100 + pending_messages,
+				latest_received_nonce: 100,
+				..Default::default()
+			};
+
+			// regular fee is paid while at most half of the limit is used
+			assert_eq!(
+				Calculator::pay_delivery_fee(
+					&RuntimeOrigin::signed(1),
+					&TEST_LANE_ID,
+					&outbound_data(max_pending_messages / 2),
+					10,
+					FixedU128::one(),
+				),
+				Ok(()),
+			);
+			assert_eq!(Balances::free_balance(1), 9_800);
+
+			// every extra pending message adds the regular fee
+			assert_eq!(
+				Calculator::pay_delivery_fee(
+					&RuntimeOrigin::signed(1),
+					&TEST_LANE_ID,
+					&outbound_data(max_pending_messages / 2 + 2),
+					10,
+					FixedU128::one(),
+				),
+				Ok(()),
+			);
+			assert_eq!(Balances::free_balance(1), 9_200);
+
+			// when the limit is reached, new messages are rejected, even if they're not charged
+			assert_eq!(
+				Calculator::pay_delivery_fee(
+					&RuntimeOrigin::signed(1),
+					&TEST_LANE_ID,
+					&outbound_data(max_pending_messages),
+					10,
+					FixedU128::one(),
+				),
+				Err(source::TOO_MANY_PENDING_MESSAGES),
+			);
+			assert_eq!(
+				Calculator::pay_delivery_fee(
+					&RuntimeOrigin::root(),
+					&TEST_LANE_ID,
+					&outbound_data(max_pending_messages),
+					10,
+					FixedU128::one(),
+				),
+				Err(source::TOO_MANY_PENDING_MESSAGES),
+			);
+			assert_eq!(Balances::free_balance(1), 9_200);
+		});
+	}
+
 	fn using_messages_proof<R>(
 		nonces_end: MessageNonce,
 		outbound_lane_data: Option<OutboundLaneData>,
@@ -868,10 +1387,14 @@ mod tests {
 			);
 			test(target::FromBridgedChainMessagesProof {
 				bridged_header_hash,
-				storage_proof,
+				storage_proof: target::MessagesStorageProof::compact::<
+					HasherOf<BridgedChain<OnThisChainBridge>>,
+				>(storage_proof, state_root)
+				.unwrap(),
 				lane: TEST_LANE_ID,
 				nonces_start: 1,
 				nonces_end,
+				additional_lanes: Vec::new(),
 			})
 		})
 	}
@@ -1071,4 +1594,253 @@ mod tests {
 			Err(target::MessageProofError::MessagesCountMismatch),
 		);
 	}
+
+	#[test]
+	fn messages_proof_is_rejected_if_declared_messages_count_does_not_include_additional_lanes() {
+		assert_eq!(
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.additional_lanes.push(target::FromBridgedChainLaneMessages {
+					lane: LaneId([0, 0, 0, 2]),
+					nonces_start: 1,
+					nonces_end: 5,
+				});
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 10)
+			}),
+			Err(target::MessageProofError::MessagesCountMismatch),
+		);
+	}
+
+	#[test]
+	fn messages_proof_is_rejected_if_lane_is_mentioned_twice() {
+		assert_eq!(
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.additional_lanes.push(target::FromBridgedChainLaneMessages {
+					lane: TEST_LANE_ID,
+					nonces_start: 11,
+					nonces_end: 10,
+				});
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 10)
+			}),
+			Err(target::MessageProofError::DuplicateLane),
+		);
+	}
+
+	#[test]
+	fn messages_proof_is_rejected_if_messages_of_additional_lane_are_missing() {
+		assert_eq!(
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.additional_lanes.push(target::FromBridgedChainLaneMessages {
+					lane: LaneId([0, 0, 0, 2]),
+					nonces_start: 1,
+					nonces_end: 5,
+				});
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 15)
+			}),
+			Err(target::MessageProofError::MissingRequiredMessage),
+		);
+	}
+
+	#[test]
+	fn messages_proof_is_rejected_if_additional_lane_is_empty() {
+		assert_eq!(
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.additional_lanes.push(target::FromBridgedChainLaneMessages {
+					lane: LaneId([0, 0, 0, 2]),
+					nonces_start: 1,
+					nonces_end: 0,
+				});
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 10)
+			}),
+			Err(target::MessageProofError::Empty),
+		);
+	}
+
+	fn compress_storage_proof(
+		storage_proof: &target::MessagesStorageProof,
+		uncompressed_size_delta: i32,
+	) -> target::MessagesStorageProof {
+		let compact_proof = match *storage_proof {
+			target::MessagesStorageProof::Compact(ref compact_proof) => compact_proof.encode(),
+			_ => unreachable!("test proofs are always compact"),
+		};
+		target::MessagesStorageProof::CompressedCompact {
+			uncompressed_size: (compact_proof.len() as i32 + uncompressed_size_delta) as u32,
+			compressed: zstd::bulk::compress(&compact_proof, 0).unwrap(),
+		}
+	}
+
+	#[test]
+	fn compressed_message_proof_is_accepted() {
+		using_messages_proof(10, None, encode_all_messages, encode_lane_data, |proof| {
+			let mut compressed_proof = proof.clone();
+			compressed_proof.storage_proof = compress_storage_proof(&proof.storage_proof, 0);
+
+			let proved_messages = target::verify_messages_proof::<OnThisChainBridge>(proof, 10);
+			assert!(proved_messages.is_ok());
+			assert_eq!(
+				target::verify_messages_proof::<OnThisChainBridge>(compressed_proof, 10),
+				proved_messages,
+			);
+		});
+	}
+
+	#[test]
+	fn compressed_message_proof_is_rejected_if_uncompressed_size_is_wrong() {
+		assert_eq!(
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.storage_proof = compress_storage_proof(&proof.storage_proof, -1);
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 10)
+			}),
+			Err(target::MessageProofError::FailedToDecompressStorageProof),
+		);
+		assert_eq!(
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.storage_proof = compress_storage_proof(&proof.storage_proof, 1);
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 10)
+			}),
+			Err(target::MessageProofError::FailedToDecompressStorageProof),
+		);
+	}
+
+	#[test]
+	fn compressed_message_proof_is_rejected_if_it_is_malformed() {
+		assert_eq!(
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.storage_proof = target::MessagesStorageProof::CompressedCompact {
+					uncompressed_size: 1024,
+					compressed: vec![42; 1024],
+				};
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 10)
+			}),
+			Err(target::MessageProofError::FailedToDecompressStorageProof),
+		);
+	}
+
+	#[test]
+	fn compressed_message_proof_is_rejected_if_frame_header_declares_too_large_sizes() {
+		let zstd_frame = |header: &[u8]| {
+			let mut frame = vec![0x28, 0xB5, 0x2F, 0xFD];
+			frame.extend_from_slice(header);
+			// last raw block with 1024 bytes of content
+			frame.extend_from_slice(&[0x01, 0x20, 0x00]);
+			frame.extend_from_slice(&[1; 1024]);
+			frame
+		};
+		let verify_compressed_proof = |compressed: Vec<u8>| {
+			using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+				proof.storage_proof = target::MessagesStorageProof::CompressedCompact {
+					uncompressed_size: 1024,
+					compressed: compressed.clone(),
+				};
+				target::verify_messages_proof::<OnThisChainBridge>(proof, 10)
+			})
+		};
+
+		// frame with 1KiB window is decompressed, but it isn't a valid compact proof
+		assert_eq!(
+			verify_compressed_proof(zstd_frame(&[0x00, 0x00])),
+			Err(target::MessageProofError::FailedToDecodeStorageProof),
+		);
+		// frame that declares 64MiB window is rejected before decompression
+		assert_eq!(
+			verify_compressed_proof(zstd_frame(&[0x00, 0x80])),
+			Err(target::MessageProofError::FailedToDecompressStorageProof),
+		);
+		// frame that declares 256MiB content is rejected before decompression
+		assert_eq!(
+			verify_compressed_proof(zstd_frame(&[0x80, 0x00, 0x00, 0x00, 0x00, 0x10])),
+			Err(target::MessageProofError::FailedToDecompressStorageProof),
+		);
+	}
+
+	#[test]
+	fn compressed_message_proof_size_is_uncompressed_size() {
+		using_messages_proof(10, None, encode_all_messages, encode_lane_data, |mut proof| {
+			let compact_proof_size = proof.size();
+			proof.storage_proof = compress_storage_proof(&proof.storage_proof, 0);
+			assert!(proof.size() >= compact_proof_size);
+		});
+	}
+
+	#[test]
+	fn messages_delivery_proof_contains_dispatch_results() {
+		use sp_trie::{trie_types::TrieDBMutBuilderV1, LayoutV1, MemoryDB, Recorder, TrieMut};
+
+		// prepare Bridged chain storage with inbound lane state and dispatch results of
+		// messages 1 and 2
+		let inbound_lane_data = InboundLaneData::<ThisChainAccountId>::default();
+		let dispatch_result = |unspent_weight, dispatch_level_result: u8| MessageDispatchResult {
+			unspent_weight: Weight::from_ref_time(unspent_weight),
+			dispatch_level_result,
+		};
+		let mut root = Default::default();
+		let mut mdb = MemoryDB::default();
+		{
+			let mut trie =
+				TrieDBMutBuilderV1::<BridgedChainHasher>::new(&mut mdb, &mut root).build();
+			let storage_key = bp_messages::storage_keys::inbound_lane_data_key(
+				OnThisChainBridge::BRIDGED_MESSAGES_PALLET_NAME,
+				&TEST_LANE_ID,
+			);
+			trie.insert(&storage_key.0, &inbound_lane_data.encode()).unwrap();
+			for (nonce, result) in [(1, dispatch_result(10, 1)), (2, dispatch_result(20, 2))] {
+				let storage_key = bp_messages::storage_keys::inbound_message_dispatch_result_key(
+					OnThisChainBridge::BRIDGED_MESSAGES_PALLET_NAME,
+					&TEST_LANE_ID,
+					nonce,
+				);
+				trie.insert(&storage_key.0, &result.encode()).unwrap();
+			}
+		}
+		let mut proof_recorder = Recorder::<LayoutV1<BridgedChainHasher>>::new();
+		bp_runtime::record_all_trie_keys::<LayoutV1<BridgedChainHasher>, _>(
+			&mdb,
+			&root,
+			&mut proof_recorder,
+		)
+		.unwrap();
+		let storage_proof = proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect();
+
+		sp_io::TestExternalities::new(Default::default()).execute_with(move || {
+			let bridged_header = BridgedChainHeader::new(
+				0,
+				Default::default(),
+				root,
+				Default::default(),
+				Default::default(),
+			);
+			let bridged_header_hash = bridged_header.hash();
+			pallet_bridge_grandpa::BestFinalized::<TestRuntime>::put(HeaderId(
+				0,
+				bridged_header_hash,
+			));
+			pallet_bridge_grandpa::ImportedHeaders::<TestRuntime>::insert(
+				bridged_header_hash,
+				bridged_header.build(),
+			);
+
+			// dispatch result of message 3 is missing from the storage, so it is ignored
+			let (lane, lane_data, dispatch_results) =
+				source::verify_messages_delivery_proof::<OnThisChainBridge>(
+					source::FromBridgedChainMessagesDeliveryProof {
+						bridged_header_hash,
+						storage_proof,
+						lane: TEST_LANE_ID,
+						dispatch_result_nonces: vec![2, 3],
+					},
+				)
+				.unwrap();
+			assert_eq!(lane, TEST_LANE_ID);
+			assert_eq!(lane_data, inbound_lane_data);
+			assert_eq!(
+				dispatch_results,
+				vec![DeliveredMessageDispatchResult {
+					nonce: 2,
+					unspent_weight: Weight::from_ref_time(20),
+					encoded_dispatch_level_result: vec![2],
+				}],
+			);
+			assert_eq!(dispatch_results[0].dispatch_level_result::<u8>(), Some(2));
+		});
+	}
 }
@@ -17,7 +17,8 @@
 //! Helpers for implementing various message-related runtime API mthods.
 
 use bp_messages::{
-	InboundMessageDetails, LaneId, MessageNonce, MessagePayload, OutboundMessageDetails,
+	InboundLaneDiagnostics, InboundMessageDeliveryProofKeys, InboundMessageDetails, LaneId,
+	MessageNonce, MessagePayload, OutboundLaneDiagnostics, OutboundMessageDetails,
 };
 use sp_std::vec::Vec;
 
@@ -46,6 +47,19 @@ where
 		.collect()
 }
 
+/// Implementation of the `To*OutboundLaneApi::lane_diagnostics`.
+pub fn outbound_lane_diagnostics<Runtime, MessagesPalletInstance>(
+	lane: LaneId,
+) -> OutboundLaneDiagnostics
+where
+	Runtime: pallet_bridge_messages::Config<MessagesPalletInstance>,
+	MessagesPalletInstance: 'static,
+{
+	pallet_bridge_messages::Pallet::<Runtime, MessagesPalletInstance>::outbound_lane_diagnostics(
+		lane,
+	)
+}
+
 /// Implementation of the `To*InboundLaneApi::message_details`.
 pub fn inbound_message_details<Runtime, MessagesPalletInstance>(
 	lane: LaneId,
@@ -64,3 +78,30 @@ where
 		})
 		.collect()
 }
+
+/// Implementation of the `From*InboundLaneApi::message_delivery_proof_keys`.
+pub fn message_delivery_proof_keys<Runtime, MessagesPalletInstance>(
+	lane: LaneId,
+	nonce: MessageNonce,
+) -> Option<InboundMessageDeliveryProofKeys>
+where
+	Runtime: pallet_bridge_messages::Config<MessagesPalletInstance>,
+	MessagesPalletInstance: 'static,
+{
+	pallet_bridge_messages::Pallet::<Runtime, MessagesPalletInstance>::inbound_message_delivery_proof_keys(
+		lane, nonce,
+	)
+}
+
+/// Implementation of the `From*InboundLaneApi::lane_diagnostics`.
+pub fn inbound_lane_diagnostics<Runtime, MessagesPalletInstance>(
+	lane: LaneId,
+) -> InboundLaneDiagnostics<Runtime::InboundRelayer>
+where
+	Runtime: pallet_bridge_messages::Config<MessagesPalletInstance>,
+	MessagesPalletInstance: 'static,
+{
+	pallet_bridge_messages::Pallet::<Runtime, MessagesPalletInstance>::inbound_lane_diagnostics(
+		lane,
+	)
+}
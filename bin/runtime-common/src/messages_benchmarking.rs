@@ -21,8 +21,9 @@
 
 use crate::{
 	messages::{
-		source::FromBridgedChainMessagesDeliveryProof, target::FromBridgedChainMessagesProof,
-		AccountIdOf, BalanceOf, BridgedChain, CallOf, HashOf, MessageBridge, ThisChain,
+		source::FromBridgedChainMessagesDeliveryProof,
+		target::{FromBridgedChainMessagesProof, MessagesStorageProof},
+		AccountIdOf, BalanceOf, BridgedChain, CallOf, HashOf, HasherOf, MessageBridge, ThisChain,
 	},
 	messages_generation::{
 		encode_all_messages, encode_lane_data, grow_trie, prepare_messages_storage_proof,
@@ -81,10 +82,15 @@ where
 	(
 		FromBridgedChainMessagesProof {
 			bridged_header_hash,
-			storage_proof,
+			storage_proof: MessagesStorageProof::compact::<HasherOf<BridgedChain<B>>>(
+				storage_proof,
+				state_root,
+			)
+			.expect("prepared storage proof is valid; qed"),
 			lane: params.lane,
 			nonces_start: *params.message_nonces.start(),
 			nonces_end: *params.message_nonces.end(),
+			additional_lanes: Vec::new(),
 		},
 		Weight::zero(),
 	)
@@ -130,6 +136,7 @@ where
 		bridged_header_hash: bridged_header_hash.into(),
 		storage_proof,
 		lane: params.lane,
+		dispatch_result_nonces: Vec::new(),
 	}
 }
 
@@ -50,16 +50,19 @@ impl<
 				ref proof,
 				..
 			}) => {
-				let inbound_lane_data =
-					pallet_bridge_messages::InboundLanes::<T, I>::get(proof.lane);
-				if proof.nonces_end <= inbound_lane_data.last_delivered_nonce() {
+				// the transaction is obsolete only if it brings nothing new to any of proved lanes
+				let is_obsolete = proof.lanes().all(|(lane, nonces)| {
+					let inbound_lane_data = pallet_bridge_messages::InboundLanes::<T, I>::get(lane);
+					*nonces.end() <= inbound_lane_data.last_delivered_nonce()
+				});
+				if is_obsolete {
 					log::trace!(
 						target: pallet_bridge_messages::LOG_TARGET,
 						"Rejecting obsolete messages delivery transaction: \
-                            lane {:?}, bundled {:?}, best {:?}",
+                            lane {:?}, bundled {:?}, additional lanes {:?}",
 						proof.lane,
 						proof.nonces_end,
-						inbound_lane_data.last_delivered_nonce(),
+						proof.additional_lanes.len(),
 					);
 
 					return sp_runtime::transaction_validity::InvalidTransaction::Stale.into()
@@ -98,12 +101,16 @@ impl<
 mod tests {
 	use crate::{
 		messages::{
-			source::FromBridgedChainMessagesDeliveryProof, target::FromBridgedChainMessagesProof,
+			source::FromBridgedChainMessagesDeliveryProof,
+			target::{
+				FromBridgedChainLaneMessages, FromBridgedChainMessagesProof, MessagesStorageProof,
+			},
 		},
 		mock::{TestRuntime, ThisChainRuntimeCall},
 		BridgeRuntimeFilterCall,
 	};
 	use bp_messages::UnrewardedRelayersState;
+	use sp_trie::CompactProof;
 
 	fn deliver_message_10() {
 		pallet_bridge_messages::InboundLanes::<TestRuntime>::insert(
@@ -115,6 +122,14 @@ mod tests {
 	fn validate_message_delivery(
 		nonces_start: bp_messages::MessageNonce,
 		nonces_end: bp_messages::MessageNonce,
+	) -> bool {
+		validate_multi_lane_message_delivery(nonces_start, nonces_end, Vec::new())
+	}
+
+	fn validate_multi_lane_message_delivery(
+		nonces_start: bp_messages::MessageNonce,
+		nonces_end: bp_messages::MessageNonce,
+		additional_lanes: Vec<FromBridgedChainLaneMessages>,
 	) -> bool {
 		pallet_bridge_messages::Pallet::<TestRuntime>::validate(
 			&ThisChainRuntimeCall::BridgeMessages(
@@ -124,10 +139,13 @@ mod tests {
 					dispatch_weight: frame_support::weights::Weight::zero(),
 					proof: FromBridgedChainMessagesProof {
 						bridged_header_hash: Default::default(),
-						storage_proof: vec![],
+						storage_proof: MessagesStorageProof::Compact(CompactProof {
+							encoded_nodes: vec![],
+						}),
 						lane: bp_messages::LaneId([0, 0, 0, 0]),
 						nonces_start,
 						nonces_end,
+						additional_lanes,
 					},
 				},
 			),
@@ -165,6 +183,41 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn extension_rejects_obsolete_messages_of_all_lanes() {
+		sp_io::TestExternalities::new(Default::default()).execute_with(|| {
+			// when all lanes of the proof are bringing nothing new => tx is rejected
+			deliver_message_10();
+			assert!(!validate_multi_lane_message_delivery(
+				8,
+				10,
+				vec![FromBridgedChainLaneMessages {
+					lane: bp_messages::LaneId([0, 0, 0, 1]),
+					nonces_start: 1,
+					nonces_end: 0,
+				}],
+			));
+		});
+	}
+
+	#[test]
+	fn extension_accepts_new_messages_of_additional_lane() {
+		sp_io::TestExternalities::new(Default::default()).execute_with(|| {
+			// when messages of the first lane are obsolete, but additional lane brings new
+			// messages => tx is accepted
+			deliver_message_10();
+			assert!(validate_multi_lane_message_delivery(
+				8,
+				10,
+				vec![FromBridgedChainLaneMessages {
+					lane: bp_messages::LaneId([0, 0, 0, 1]),
+					nonces_start: 1,
+					nonces_end: 1,
+				}],
+			));
+		});
+	}
+
 	fn confirm_message_10() {
 		pallet_bridge_messages::OutboundLanes::<TestRuntime>::insert(
 			bp_messages::LaneId([0, 0, 0, 0]),
@@ -184,6 +237,7 @@ mod tests {
 						bridged_header_hash: Default::default(),
 						storage_proof: Vec::new(),
 						lane: bp_messages::LaneId([0, 0, 0, 0]),
+						dispatch_result_nonces: Vec::new(),
 					},
 					relayers_state: UnrewardedRelayersState {
 						last_delivered_nonce,
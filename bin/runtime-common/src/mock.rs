@@ -35,7 +35,7 @@ use crate::messages::{
 use bp_header_chain::HeaderChain;
 use bp_messages::{target_chain::ForbidInboundMessages, LaneId, MessageNonce};
 use bp_parachains::SingleParaStoredHeaderDataBuilder;
-use bp_runtime::{Chain, ChainId, Parachain, UnderlyingChainProvider};
+use bp_runtime::{BlockNumberOf, Chain, ChainId, Parachain, UnderlyingChainProvider};
 use codec::{Decode, Encode};
 use frame_support::{
 	parameter_types,
@@ -45,7 +45,7 @@ use pallet_transaction_payment::Multiplier;
 use sp_runtime::{
 	testing::H256,
 	traits::{BlakeTwo256, ConstU32, ConstU64, ConstU8, IdentityLookup},
-	FixedPointNumber, Perquintill,
+	FixedPointNumber, Perbill, Perquintill,
 };
 
 /// Account identifier at `ThisChain`.
@@ -93,6 +93,10 @@ pub const BRIDGED_CHAIN_MIN_EXTRINSIC_WEIGHT: usize = 5;
 pub const BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT: usize = 2048;
 /// Maximal extrinsic size at the `BridgedChain`.
 pub const BRIDGED_CHAIN_MAX_EXTRINSIC_SIZE: u32 = 1024;
+/// Maximal number of unconfirmed messages at the `BridgedChain` inbound lane.
+pub const BRIDGED_CHAIN_MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE: MessageNonce = 16;
+/// Maximal number of unrewarded relayer entries at the `BridgedChain` inbound lane.
+pub const BRIDGED_CHAIN_MAX_UNREWARDED_RELAYERS_AT_INBOUND_LANE: MessageNonce = 8;
 
 frame_support::construct_runtime! {
 	pub enum TestRuntime where
@@ -105,7 +109,7 @@ frame_support::construct_runtime! {
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage, Event<T>},
 		BridgeRelayers: pallet_bridge_relayers::{Pallet, Call, Storage, Event<T>},
-		BridgeGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage},
+		BridgeGrandpa: pallet_bridge_grandpa::{Pallet, Call, Storage, Event<T>},
 		BridgeParachains: pallet_bridge_parachains::{Pallet, Call, Storage, Event<T>},
 		BridgeMessages: pallet_bridge_messages::{Pallet, Call, Storage, Event<T>, Config<T>},
 	}
@@ -118,6 +122,7 @@ crate::generate_bridge_reject_obsolete_headers_and_messages! {
 
 parameter_types! {
 	pub const ActiveOutboundLanes: &'static [LaneId] = &[TEST_LANE_ID];
+	pub const MaxImmediateDispatchWeight: Weight = Weight::MAX;
 	pub const BridgedChainId: ChainId = *b"brdg";
 	pub const BridgedParasPalletName: &'static str = "Paras";
 	pub const ExistentialDeposit: ThisChainBalance = 500;
@@ -125,6 +130,7 @@ parameter_types! {
 	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
 	pub const TransactionBaseFee: ThisChainBalance = 0;
 	pub const TransactionByteFee: ThisChainBalance = 1;
+	pub const RelayerStakeSlashFraction: Perbill = Perbill::from_percent(50);
 	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(3, 100_000);
 	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 1_000_000u128);
 	pub MaximumMultiplier: Multiplier = sp_runtime::traits::Bounded::max_value();
@@ -192,21 +198,36 @@ impl pallet_transaction_payment::Config for TestRuntime {
 }
 
 impl pallet_bridge_grandpa::Config for TestRuntime {
+	type RuntimeEvent = RuntimeEvent;
 	type BridgedChain = BridgedUnderlyingChain;
 	type MaxRequests = ConstU32<50>;
 	type HeadersToKeep = ConstU32<8>;
 	type MaxBridgedAuthorities = ConstU32<1024>;
+	type MaxFinalityProofSize = ConstU32<{ 1024 * 1024 }>;
+	type AuthoritySetsToKeep = ConstU32<4>;
+	type TrustedCheckpoint = frame_support::traits::GetDefault;
+	type FreeHeadersInterval = frame_support::traits::GetDefault;
+	type MaxFinalizedHeadersJump = frame_support::traits::GetDefault;
+	type OnEquivocation = ();
+	type MaxStallPeriod = frame_support::traits::GetDefault;
+	type OnBridgeStalled = ();
 	type WeightInfo = pallet_bridge_grandpa::weights::BridgeWeight<TestRuntime>;
 }
 
 impl pallet_bridge_parachains::Config for TestRuntime {
 	type RuntimeEvent = RuntimeEvent;
-	type BridgesGrandpaPalletInstance = ();
+	type BridgedRelayChain = BridgedUnderlyingChain;
+	type RelayChainHeaders = pallet_bridge_grandpa::GrandpaChainHeaders<TestRuntime, ()>;
 	type ParasPalletName = BridgedParasPalletName;
 	type ParaStoredHeaderDataBuilder =
 		SingleParaStoredHeaderDataBuilder<BridgedUnderlyingParachain>;
 	type HeadsToKeep = ConstU32<8>;
 	type MaxParaHeadDataSize = ConstU32<1024>;
+	type OnlyRegisteredParachains = frame_support::traits::ConstBool<false>;
+	type MaxHeadsReorgDepth = frame_support::traits::GetDefault;
+	type FreeHeadsInterval = frame_support::traits::GetDefault;
+	type Currency = Balances;
+	type ParachainRegistrationDeposit = frame_support::traits::GetDefault;
 	type WeightInfo = pallet_bridge_parachains::weights::BridgeWeight<TestRuntime>;
 }
 
@@ -214,10 +235,20 @@ impl pallet_bridge_messages::Config for TestRuntime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = pallet_bridge_messages::weights::BridgeWeight<TestRuntime>;
 	type ActiveOutboundLanes = ActiveOutboundLanes;
+	type MaxDynamicOutboundLanes = ConstU32<0>;
+	type LaneOwnerOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureRoot<ThisChainAccountId>>;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = ConstU64<16>;
 	type MaxUnconfirmedMessagesAtInboundLane = ConstU64<16>;
+	type UnorderedInboundLanes = frame_support::traits::GetDefault;
+	type MaxImmediateDispatchWeight = MaxImmediateDispatchWeight;
+	type MaxParkedMessages = ConstU32<0>;
+	type MaxParkedMessagesPerLane = ConstU32<0>;
+	type MaxInboundLanesToPrunePerBlock = ConstU32<0>;
 
 	type MaximalOutboundPayloadSize = FromThisChainMaximalOutboundPayloadSize<OnThisChainBridge>;
+	type MaxMessagesPerLanePerBlock = ConstU64<1_024>;
+	type MaxBytesPerLanePerBlock = ConstU32<16_777_216>;
 	type OutboundPayload = FromThisChainMessagePayload;
 
 	type InboundPayload = FromBridgedChainMessagePayload<ThisChainRuntimeCall>;
@@ -226,11 +257,16 @@ impl pallet_bridge_messages::Config for TestRuntime {
 
 	type TargetHeaderChain = TargetHeaderChainAdapter<OnThisChainBridge>;
 	type LaneMessageVerifier = FromThisChainMessageVerifier<OnThisChainBridge>;
+	type DeliveryFeeCalculator = ();
 	type DeliveryConfirmationPayments = pallet_bridge_relayers::DeliveryConfirmationPaymentsAdapter<
 		TestRuntime,
 		frame_support::traits::ConstU64<100_000>,
 		frame_support::traits::ConstU64<10_000>,
 	>;
+	type MessageTtl = frame_support::traits::GetDefault;
+	type OnMessageExpired = ();
+	type OnMessageCancelled = ();
+	type OnDeliveryConfirmed = ();
 
 	type SourceHeaderChain = SourceHeaderChainAdapter<OnThisChainBridge>;
 	type MessageDispatch =
@@ -241,7 +277,20 @@ impl pallet_bridge_messages::Config for TestRuntime {
 impl pallet_bridge_relayers::Config for TestRuntime {
 	type RuntimeEvent = RuntimeEvent;
 	type Reward = ThisChainBalance;
-	type PaymentProcedure = ();
+	type PaymentProcedure = bp_relayers::PayLaneRewardFromAccount<Balances, ThisChainAccountId>;
+	type MaxLanesPerClaim = ConstU32<4>;
+	type RewardDecayPerBlock = ();
+	type Currency = Balances;
+	type RequiredStake = ConstU64<100>;
+	type StakeSlashFraction = RelayerStakeSlashFraction;
+	type SlashBeneficiary = ();
+	type RewardEraLength = ();
+	type RewardPayoutDelay = ();
+	type LaneOwnerOrigin =
+		frame_support::traits::AsEnsureOriginWithArg<frame_system::EnsureRoot<ThisChainAccountId>>;
+	type MaxAllowlistedRelayers = ConstU32<16>;
+	type LowRewardsPotThreshold = ();
+	type OnLowRewardsPot = ();
 	type WeightInfo = ();
 }
 
@@ -282,6 +331,12 @@ impl HeaderChain<ThisUnderlyingChain> for ThisHeaderChain {
 	fn finalized_header_state_root(_hash: HashOf<ThisChain>) -> Option<HashOf<ThisChain>> {
 		unreachable!()
 	}
+
+	fn finalized_header_number(
+		_hash: HashOf<ThisChain>,
+	) -> Option<BlockNumberOf<ThisUnderlyingChain>> {
+		unreachable!()
+	}
 }
 
 /// Call origin at `BridgedChain`.
@@ -344,6 +399,14 @@ impl BridgedChainWithMessages for ThisChain {
 	fn verify_dispatch_weight(_message_payload: &[u8]) -> bool {
 		unreachable!()
 	}
+
+	fn maximal_unconfirmed_messages_at_inbound_lane() -> MessageNonce {
+		unreachable!()
+	}
+
+	fn maximal_unrewarded_relayer_entries_at_inbound_lane() -> MessageNonce {
+		unreachable!()
+	}
 }
 
 /// Underlying chain of `BridgedChain`.
@@ -419,4 +482,12 @@ impl BridgedChainWithMessages for BridgedChain {
 		message_payload.len() >= BRIDGED_CHAIN_MIN_EXTRINSIC_WEIGHT &&
 			message_payload.len() <= BRIDGED_CHAIN_MAX_EXTRINSIC_WEIGHT
 	}
+
+	fn maximal_unconfirmed_messages_at_inbound_lane() -> MessageNonce {
+		BRIDGED_CHAIN_MAX_UNCONFIRMED_MESSAGES_AT_INBOUND_LANE
+	}
+
+	fn maximal_unrewarded_relayer_entries_at_inbound_lane() -> MessageNonce {
+		BRIDGED_CHAIN_MAX_UNREWARDED_RELAYERS_AT_INBOUND_LANE
+	}
 }
@@ -35,16 +35,19 @@ use sp_trie::{trie_types::TrieDBMutBuilderV1, LayoutV1, MemoryDB, Recorder, Trie
 ///
 /// In addition to returning valid messages proof, environment is prepared to verify this message
 /// proof.
-pub fn prepare_parachain_heads_proof<R, PI>(
+pub fn prepare_parachain_heads_proof<R, GI, PI>(
 	parachains: &[ParaId],
 	parachain_head_size: u32,
 	size: StorageProofSize,
 ) -> (RelayBlockNumber, RelayBlockHash, ParaHeadsProof, Vec<(ParaId, ParaHash)>)
 where
-	R: pallet_bridge_parachains::Config<PI>
-		+ pallet_bridge_grandpa::Config<R::BridgesGrandpaPalletInstance>,
+	R: pallet_bridge_parachains::Config<
+			PI,
+			RelayChainHeaders = pallet_bridge_grandpa::GrandpaChainHeaders<R, GI>,
+		> + pallet_bridge_grandpa::Config<GI>,
+	GI: 'static,
 	PI: 'static,
-	<R as pallet_bridge_grandpa::Config<R::BridgesGrandpaPalletInstance>>::BridgedChain:
+	<R as pallet_bridge_grandpa::Config<GI>>::BridgedChain:
 		bp_runtime::Chain<BlockNumber = RelayBlockNumber, Hash = RelayBlockHash>,
 {
 	let parachain_head = ParaHead(vec![0u8; parachain_head_size as usize]);
@@ -79,7 +82,7 @@ where
 	let proof = proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect();
 
 	let (relay_block_number, relay_block_hash) =
-		insert_header_to_grandpa_pallet::<R, R::BridgesGrandpaPalletInstance>(state_root);
+		insert_header_to_grandpa_pallet::<R, GI>(state_root);
 
 	(relay_block_number, relay_block_hash, ParaHeadsProof(proof), parachain_heads)
 }
@@ -17,70 +17,648 @@
 //! Signed extension that refunds relayer if he has delivered some new messages.
 //! It also refunds transaction cost if the transaction is an `utility.batchAll()`
 //! with calls that are: delivering new messsage and all necessary underlying headers
-//! (parachain or relay chain).
+//! (parachain or relay chain). Relayers that are confirming messages delivery are
+//! refunded as well.
 
-use crate::messages::target::FromBridgedChainMessagesProof;
+use crate::{
+	messages::{
+		source::FromBridgedChainMessagesDeliveryProof, target::FromBridgedChainMessagesProof,
+	},
+	refund_relayer_extension_weights::WeightInfo as RefundExtensionWeightInfo,
+	BridgeRuntimeFilterCall,
+};
 
-use bp_messages::{target_chain::SourceHeaderChain, LaneId, MessageNonce};
+use bp_messages::{
+	calc_relayers_rewards,
+	source_chain::{RelayersRewards, TargetHeaderChain},
+	target_chain::SourceHeaderChain,
+	LaneId, MessageNonce,
+};
 use bp_polkadot_core::parachains::ParaId;
-use bp_runtime::{Chain, HashOf};
+use bp_relayers::{RewardKind, RewardsAccountParams};
+use bp_runtime::{Chain, HashOf, OperatingMode, OwnedBridgeModule, Size};
 use codec::{Decode, Encode};
 use frame_support::{
-	dispatch::{CallableCallFor, DispatchInfo, Dispatchable, PostDispatchInfo},
-	traits::IsSubType,
+	dispatch::{CallableCallFor, DispatchInfo, Dispatchable, GetDispatchInfo, PostDispatchInfo},
+	traits::{fungibles, IsSubType},
+	weights::Weight,
 	CloneNoBound, DefaultNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 };
 use pallet_bridge_grandpa::{
-	BridgedChain, Call as GrandpaCall, Config as GrandpaConfig, Pallet as GrandpaPallet,
+	BridgedChain, Call as GrandpaCall, Config as GrandpaConfig, GrandpaChainHeaders,
+	Pallet as GrandpaPallet,
 };
 use pallet_bridge_messages::{
-	Call as MessagesCall, Config as MessagesConfig, Pallet as MessagesPallet,
+	Call as MessagesCall, Config as MessagesConfig, OutboundLanes, Pallet as MessagesPallet,
+	WeightInfoExt as MessagesWeightInfoExt,
 };
 use pallet_bridge_parachains::{
 	Call as ParachainsCall, Config as ParachainsConfig, Pallet as ParachainsPallet, RelayBlockHash,
 	RelayBlockHasher, RelayBlockNumber,
 };
-use pallet_bridge_relayers::{Config as RelayersConfig, Pallet as RelayersPallet};
+use pallet_bridge_relayers::{
+	Config as RelayersConfig, Error as RelayersError, Pallet as RelayersPallet, RefundSkipReason,
+};
 use pallet_transaction_payment::{Config as TransactionPaymentConfig, OnChargeTransaction};
 use pallet_utility::{Call as UtilityCall, Config as UtilityConfig, Pallet as UtilityPallet};
 use scale_info::TypeInfo;
 use sp_runtime::{
-	traits::{DispatchInfoOf, Get, Header as HeaderT, PostDispatchInfoOf, SignedExtension, Zero},
-	transaction_validity::{TransactionValidity, TransactionValidityError, ValidTransaction},
-	DispatchResult, FixedPointOperand,
+	traits::{
+		AtLeast32BitUnsigned, DispatchInfoOf, Get, Header as HeaderT, PostDispatchInfoOf,
+		Saturating, SignedExtension, UniqueSaturatedFrom, UniqueSaturatedInto, Zero,
+	},
+	transaction_validity::{
+		InvalidTransaction, TransactionPriority, TransactionValidity, TransactionValidityError,
+		ValidTransaction,
+	},
+	DispatchResult, FixedPointNumber, FixedPointOperand, FixedU128, Perbill,
 };
-use sp_std::marker::PhantomData;
+use sp_std::{fmt::Debug, marker::PhantomData, vec::Vec};
+
+// Custom `InvalidTransaction` codes that are returned by the extension. Codes are never reused,
+// so relayers may rely on them to decide whether the transaction may be resubmitted in some
+// other form.
+
+/// Custom `InvalidTransaction` code that is returned by the extension when message delivery
+/// transaction is larger than allowed by the `MDL` parameter of the extension.
+pub const OVERSIZED_DELIVERY_TRANSACTION: u8 = 1;
+/// Custom `InvalidTransaction` code that is returned by the extension when message delivery
+/// batch has more calls than allowed by the `RefundConfig::MAX_DELIVERY_BATCH_CALLS`.
+///
+/// Unlike `InvalidTransaction::Stale`, it doesn't mean that the bundled calls are obsolete, so the
+/// relayer may split the batch and resubmit its calls.
+pub const OVERSIZED_DELIVERY_BATCH: u8 = 2;
+/// Custom `InvalidTransaction` code that is returned by the extension when message delivery
+/// transaction is submitted by the relayer that is not in the relayers allowlist of the lane.
+///
+/// Allowlists are managed by the relayers pallet (see
+/// `pallet_bridge_relayers::Pallet::set_lane_relayers_allowlist`).
+pub const NOT_ALLOWLISTED_RELAYER: u8 = 3;
+
+/// Optional settings of the refund extension.
+///
+/// Every setting has a default value that preserves the basic extension behavior, so `()` may
+/// be used by runtimes that don't need to tune anything. Settings that runtimes may want to
+/// change without runtime upgrade are `Get<>`-typed parameters of the extension instead.
+pub trait RefundConfig {
+	/// Reward that is registered for every relayer that has delivered messages, which delivery
+	/// is confirmed by the refunded confirmation transaction. The reward is registered for every
+	/// confirmed message.
+	///
+	/// The confirmation transaction cost is always refunded to the confirmation relayer. Relayers
+	/// that have originally delivered messages are only rewarded by the extension if this value
+	/// is non-zero. Such runtimes shall not reward relayers from the messages pallet (e.g. using
+	/// `DeliveryConfirmationPaymentsAdapter`), or they will be rewarded twice. Keep in mind that
+	/// non-zero value means that the messages delivery proof is verified twice - once by the
+	/// extension in `pre_dispatch` and once by the messages pallet. The weight of the extra
+	/// verification is registered as an extra weight of the block in `pre_dispatch`. Zero means
+	/// that the relayers are not rewarded by the extension.
+	const DELIVERY_REWARD_PER_CONFIRMED_MESSAGE: u32 = 0;
+
+	/// Maximal number of calls in the `utility.batchAll` that is delivering messages over the
+	/// configured lane.
+	///
+	/// Every call in the batch is checked by the `BE` filter, so large batches are wasting
+	/// resources of the transaction pool. Batches with more calls are rejected with the
+	/// `OVERSIZED_DELIVERY_BATCH` code.
+	const MAX_DELIVERY_BATCH_CALLS: u32 = u32::MAX;
+
+	/// Human-readable name of the bridge, used in log messages.
+	///
+	/// Runtimes that are using several refund extensions may use it to distinguish log messages
+	/// of different bridges. If it is `None`, parachains and lane identifiers are logged instead.
+	const BRIDGE_NAME: Option<&'static str> = None;
+
+	/// If true, standalone relay chain finality transactions are refunded.
+	///
+	/// Mandatory relay chain headers are free for relayers anyway (the GRANDPA pallet returns
+	/// `Pays::No`), so the refund only matters for non-mandatory headers. The extension can't
+	/// tell whether the non-mandatory header is actually needed by the bridge, so relayers may
+	/// submit every finalized relay chain header and get refunds for that. Only enable it if the
+	/// number of such refunds is limited otherwise (e.g. by the GRANDPA pallet `MaxRequests`) or
+	/// if relayers are trusted.
+	const REFUND_RELAY_FINALITY: bool = false;
+
+	/// If true, standalone parachain finality transactions are refunded.
+	///
+	/// Only transactions that are updating head of one of parachains, served by the extension, are
+	/// refunded and only if the head is actually updated. Relayers may submit every new parachain
+	/// head and get refunds for that, so only enable it if relayers are trusted or if the number
+	/// of such refunds is limited otherwise.
+	const REFUND_PARACHAIN_FINALITY: bool = false;
+
+	/// Priority boost of the message delivery transaction, for every message it delivers.
+	///
+	/// Message delivery transactions may be starved out of full blocks by regular transactions.
+	/// The boost is added to the priority of delivery transactions over the extension lane,
+	/// including batches. Zero means that the priority is not changed.
+	const PRIORITY_BOOST_PER_MESSAGE: TransactionPriority = 0;
+
+	/// Maximal number of relay chain finality calls in the `utility.batchAll` that is delivering
+	/// relay chain finality, parachain finality and messages.
+	///
+	/// Some bridges may need to import several relay chain headers (e.g. when relay chain
+	/// authorities set is changed several times) before they're able to prove parachain head.
+	/// Relay chain headers must precede the parachain head call. The last relay chain header is
+	/// expected to be the best relay chain header after the batch is dispatched. Zero means that
+	/// such batches are not refunded.
+	const MAX_RELAY_HEADERS_PER_BATCH: u32 = 1;
+
+	/// Number of blocks, during which the message delivery transaction, which is delivering the
+	/// same messages as the recently refunded delivery, is rejected.
+	///
+	/// Such transaction would only waste block space, because messages are delivered once. The
+	/// refunded deliveries are remembered in the relayers pallet storage, which has limited
+	/// capacity (see `pallet_bridge_relayers::MAX_RECENTLY_REFUNDED_DELIVERIES`). Zero means that
+	/// refunded deliveries are not remembered.
+	const REPLAY_GUARD_BLOCKS: u32 = 0;
+
+	/// If true, successful bridge transactions that have not advanced the bridge state (e.g.
+	/// delivery transactions that have not delivered any new messages) are flagged.
+	///
+	/// Such transactions are never refunded. If this flag is set, the misbehavior score and the
+	/// number of invalid submissions of the submitter are also incremented in the relayers pallet
+	/// and the `NoOpSubmission` event is deposited. It may be used to detect and deter registered
+	/// relayers that are spamming the chain with useless bridge transactions.
+	const TRACK_NO_OP_TRANSACTIONS: bool = false;
+
+	/// Length of the refund era in blocks.
+	///
+	/// If it is non-zero, the total amount of refunds that are paid for serving the lane during
+	/// every era is stored in the relayers pallet (see `pallet_bridge_relayers::TotalRefunded`).
+	/// Era `N` starts at block `N * REFUND_ERA_BLOCKS`. Zero means that refunds are not accounted
+	/// and the `REFUND_BUDGET_PER_ERA` is not enforced.
+	const REFUND_ERA_BLOCKS: u32 = 0;
+
+	/// Maximal total amount of refunds that are paid for serving the lane during single refund
+	/// era.
+	///
+	/// When the refund would exceed the budget of the current era, it is withheld and the
+	/// `RefundBudgetExhausted` event of the relayers pallet is deposited. Rewards of relayers that
+	/// have delivered confirmed messages are not limited by the budget. Only used when the
+	/// `REFUND_ERA_BLOCKS` is non-zero. Values that don't fit the reward type are saturated.
+	const REFUND_BUDGET_PER_ERA: u128 = u128::MAX;
+
+	/// Maximal number of other calls in the `utility.batchAll`, which wraps the nested bridge
+	/// `utility.batchAll`.
+	///
+	/// Relayers may wrap the bridge batch into another batch, e.g. together with the
+	/// `system.remark` call to tag their transactions. Only one level of nesting is supported.
+	/// The whole transaction fee is refunded, including the cost of other calls, so keep this
+	/// value small. Zero means that nested batches are not refunded.
+	const MAX_NESTED_BATCH_EXTRA_CALLS: u32 = 0;
+
+	/// Priority boost of bridge transactions that are submitted by registered relayers.
+	///
+	/// Relayers are registered in the relayers pallet by reserving the stake (see
+	/// `pallet_bridge_relayers::Pallet::is_registration_active`). The boost lets registered
+	/// relayers win races against relayers that are front-running their proofs. It is added to
+	/// the priority of every bridge transaction, recognized by the extension. Zero means that
+	/// the priority is not changed.
+	const REGISTERED_RELAYER_PRIORITY_BOOST: TransactionPriority = 0;
+
+	/// If true, only relayers that are registered in the relayers pallet are refunded.
+	///
+	/// Bridge transactions of unregistered relayers are still dispatched, but they are never
+	/// refunded and the `RefundSkipped` event of the relayers pallet is deposited instead.
+	/// Rewards of relayers that have delivered confirmed messages are not affected.
+	const REFUND_REGISTERED_RELAYERS_ONLY: bool = false;
+
+	/// If true, registered relayers are slashed and deregistered when their bridge transaction,
+	/// recognized by the extension, fails or doesn't advance the bridge state.
+	///
+	/// Relayers are slashed by the `pallet_bridge_relayers::Pallet::slash_and_deregister`, so
+	/// the slashed part of the stake and its beneficiary are configured in the relayers pallet.
+	/// Unregistered relayers are never slashed. Keep in mind that honest relayers may lose races
+	/// to other relayers, so it only makes sense when there's a single relayer per lane or when
+	/// relayers are coordinating their actions.
+	const SLASH_MISBEHAVING_RELAYERS: bool = false;
+
+	/// If true, statistics of relayers are collected in the relayers pallet.
+	///
+	/// Statistics include the number of new delivered messages, the number of new submitted
+	/// headers, the total amount of refunds and the number of bridge transactions that have not
+	/// been refunded. They may be queried using the `BridgeRelayersApi::relayer_statistics`.
+	const COLLECT_RELAYER_STATISTICS: bool = false;
+
+	/// Weight of parsing the batch transaction with given number of inspected calls.
+	///
+	/// The extension looks for the nested bridge batch (see `MAX_NESTED_BATCH_EXTRA_CALLS`) in
+	/// every batch transaction. This weight is registered as an extra weight of the block in
+	/// `pre_dispatch`. Runtimes must return weights of the `unpack_bridge_batch` benchmark here
+	/// (see the `refund_relayer_extension_benchmarking` module), executed on the reference
+	/// hardware.
+	fn bridge_batch_parse_weight(calls: u32) -> Weight;
+}
+
+impl RefundConfig for () {
+	// nested batches are not supported by this configuration, so the batch is never searched for
+	// the nested bridge batch
+	fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+		Weight::zero()
+	}
+}
+
+/// Extension and bridge identifier that is used in extension log messages.
+struct BridgeLogName<PID, LID, CFG>(&'static str, PhantomData<(PID, LID, CFG)>);
+
+impl<PID, LID, CFG> sp_std::fmt::Display for BridgeLogName<PID, LID, CFG>
+where
+	PID: Get<Vec<u32>>,
+	LID: Get<LaneId>,
+	CFG: RefundConfig,
+{
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		match CFG::BRIDGE_NAME {
+			Some(bridge_name) => write!(f, "{} of {} bridge", self.0, bridge_name),
+			None => write!(f, "{} from parachains {:?} via {:?}", self.0, PID::get(), LID::get()),
+		}
+	}
+}
+
+/// Something that is able to unpack calls, bundled into the `utility.batchAll`, `utility.batch`
+/// or `utility.forceBatch` call.
+///
+/// Use `()` if runtime has no `pallet_utility`. In this case only standalone message delivery
+/// transactions are refunded.
+pub trait BatchCallUnpacker<Call> {
+	/// Returns calls of the `utility.batchAll` call or `None` if the `call` is not a batch.
+	fn unpack_batch_all(call: &Call) -> Option<&Vec<Call>>;
+
+	/// Returns calls of the non-atomic batch (`utility.batch` or `utility.forceBatch`) or `None`
+	/// if the `call` is not a non-atomic batch.
+	///
+	/// Some calls of the non-atomic batch may fail while the batch itself succeeds. So the relayer
+	/// is only refunded for the successful message delivery portion of such batch.
+	fn unpack_non_atomic_batch(_call: &Call) -> Option<&Vec<Call>> {
+		None
+	}
+
+	/// Returns calls of any supported batch or `None` if the `call` is not a batch.
+	fn unpack_batch(call: &Call) -> Option<&Vec<Call>> {
+		Self::unpack_batch_all(call).or_else(|| Self::unpack_non_atomic_batch(call))
+	}
+}
+
+impl<Call> BatchCallUnpacker<Call> for () {
+	fn unpack_batch_all(_call: &Call) -> Option<&Vec<Call>> {
+		None
+	}
+}
+
+/// Batch call unpacker that is using `pallet_utility`.
+pub struct UtilityBatchCallUnpacker<R>(PhantomData<R>);
 
-// TODO (https://github.com/paritytech/parity-bridges-common/issues/1667):
-// support multiple bridges in this extension
+impl<R> BatchCallUnpacker<CallOf<R>> for UtilityBatchCallUnpacker<R>
+where
+	R: UtilityConfig<RuntimeCall = CallOf<R>>,
+	CallOf<R>: IsSubType<CallableCallFor<UtilityPallet<R>, R>>,
+{
+	fn unpack_batch_all(call: &CallOf<R>) -> Option<&Vec<CallOf<R>>> {
+		match call.is_sub_type() {
+			Some(UtilityCall::<R>::batch_all { ref calls }) => Some(calls),
+			_ => None,
+		}
+	}
+
+	fn unpack_non_atomic_batch(call: &CallOf<R>) -> Option<&Vec<CallOf<R>>> {
+		match call.is_sub_type() {
+			Some(UtilityCall::<R>::batch { ref calls }) |
+			Some(UtilityCall::<R>::force_batch { ref calls }) => Some(calls),
+			_ => None,
+		}
+	}
+}
 
 /// Transaction fee calculation.
-pub trait TransactionFeeCalculation<Balance> {
+pub trait TransactionFeeCalculation {
+	/// Type of the transaction fee.
+	///
+	/// It may be different from the relayer reward type. The fee is converted to the reward type
+	/// before it is registered. Fees that don't fit the reward type are saturated.
+	type Balance: Zero;
+
 	/// Compute fee that is paid for given transaction. The fee is later refunded to relayer.
+	///
+	/// Returns `None` if relayer shall not be refunded for the transaction. Note that `Some(0)`
+	/// means that the zero reward is registered.
 	fn compute_fee(
 		info: &DispatchInfo,
 		post_info: &PostDispatchInfo,
 		len: usize,
-		tip: Balance,
-	) -> Balance;
+		tip: Self::Balance,
+	) -> Option<Self::Balance>;
+
+	/// If true, the extension computes benchmarked weight of bridge calls of every refunded
+	/// transaction and uses `compute_bridge_calls_fee` to compute the refund.
+	const REFUND_BRIDGE_CALLS_ONLY: bool = false;
+
+	/// Compute fee that is paid for bridge calls of given transaction. The `bridge_calls_weight`
+	/// is the benchmarked weight of bridge calls (finality and message delivery calls) of the
+	/// transaction.
+	///
+	/// By default, the fee of the whole transaction is computed.
+	fn compute_bridge_calls_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		tip: Self::Balance,
+		_bridge_calls_weight: Weight,
+	) -> Option<Self::Balance> {
+		Self::compute_fee(info, post_info, len, tip)
+	}
 }
 
-impl<R> TransactionFeeCalculation<BalanceOf<R>> for R
+impl<R> TransactionFeeCalculation for R
 where
 	R: TransactionPaymentConfig,
 	<R as frame_system::Config>::RuntimeCall:
 		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
 	BalanceOf<R>: FixedPointOperand,
 {
+	type Balance = BalanceOf<R>;
+
 	fn compute_fee(
 		info: &DispatchInfo,
 		post_info: &PostDispatchInfo,
 		len: usize,
 		tip: BalanceOf<R>,
-	) -> BalanceOf<R> {
-		pallet_transaction_payment::Pallet::<R>::compute_actual_fee(len as _, info, post_info, tip)
+	) -> Option<BalanceOf<R>> {
+		Some(pallet_transaction_payment::Pallet::<R>::compute_actual_fee(
+			len as _, info, post_info, tip,
+		))
+	}
+}
+
+/// Transaction fee calculation that only refunds the `Part` of the fee, computed by the `FEE`.
+///
+/// It may be used to limit exposure of the account that is paying rewards to expensive bridge
+/// transactions.
+pub struct ProportionalRefund<FEE, Part>(PhantomData<(FEE, Part)>);
+
+impl<FEE, Part> TransactionFeeCalculation for ProportionalRefund<FEE, Part>
+where
+	FEE: TransactionFeeCalculation,
+	FEE::Balance: AtLeast32BitUnsigned,
+	Part: Get<Perbill>,
+{
+	type Balance = FEE::Balance;
+
+	fn compute_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		tip: Self::Balance,
+	) -> Option<Self::Balance> {
+		FEE::compute_fee(info, post_info, len, tip).map(|fee| Part::get().mul_floor(fee))
+	}
+
+	const REFUND_BRIDGE_CALLS_ONLY: bool = FEE::REFUND_BRIDGE_CALLS_ONLY;
+
+	fn compute_bridge_calls_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		tip: Self::Balance,
+		bridge_calls_weight: Weight,
+	) -> Option<Self::Balance> {
+		FEE::compute_bridge_calls_fee(info, post_info, len, tip, bridge_calls_weight)
+			.map(|fee| Part::get().mul_floor(fee))
+	}
+}
+
+/// Transaction fee calculation that never refunds more than `Max`. The fee is computed by the
+/// `FEE`.
+pub struct CappedRefund<FEE, Max>(PhantomData<(FEE, Max)>);
+
+impl<FEE, Max> TransactionFeeCalculation for CappedRefund<FEE, Max>
+where
+	FEE: TransactionFeeCalculation,
+	FEE::Balance: Ord,
+	Max: Get<FEE::Balance>,
+{
+	type Balance = FEE::Balance;
+
+	fn compute_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		tip: Self::Balance,
+	) -> Option<Self::Balance> {
+		FEE::compute_fee(info, post_info, len, tip).map(|fee| sp_std::cmp::min(fee, Max::get()))
+	}
+
+	const REFUND_BRIDGE_CALLS_ONLY: bool = FEE::REFUND_BRIDGE_CALLS_ONLY;
+
+	fn compute_bridge_calls_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		tip: Self::Balance,
+		bridge_calls_weight: Weight,
+	) -> Option<Self::Balance> {
+		FEE::compute_bridge_calls_fee(info, post_info, len, tip, bridge_calls_weight)
+			.map(|fee| sp_std::cmp::min(fee, Max::get()))
+	}
+}
+
+/// Transaction fee calculation that also refunds the transaction tip, but never more than
+/// `MaxRefundableTip`. The fee is computed by the `FEE`.
+///
+/// The extension has no access to the tip, so the `Tip` must return the tip of the transaction
+/// that is currently dispatched (e.g. recorded by the runtime extension that charges transaction
+/// fees). Small tips may be used to outbid spam transactions. Larger tips are clamped, so relayer
+/// can't deplete the account that is paying rewards by using huge tips.
+pub struct TipRefund<FEE, Tip, MaxRefundableTip>(PhantomData<(FEE, Tip, MaxRefundableTip)>);
+
+impl<FEE, Tip, MaxRefundableTip> TipRefund<FEE, Tip, MaxRefundableTip>
+where
+	FEE: TransactionFeeCalculation,
+	FEE::Balance: Ord,
+	Tip: Get<FEE::Balance>,
+	MaxRefundableTip: Get<FEE::Balance>,
+{
+	/// Returns part of the transaction tip that may be refunded.
+	fn refundable_tip() -> FEE::Balance {
+		sp_std::cmp::min(Tip::get(), MaxRefundableTip::get())
+	}
+}
+
+impl<FEE, Tip, MaxRefundableTip> TransactionFeeCalculation for TipRefund<FEE, Tip, MaxRefundableTip>
+where
+	FEE: TransactionFeeCalculation,
+	FEE::Balance: Ord,
+	Tip: Get<FEE::Balance>,
+	MaxRefundableTip: Get<FEE::Balance>,
+{
+	type Balance = FEE::Balance;
+
+	fn compute_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		_tip: Self::Balance,
+	) -> Option<Self::Balance> {
+		FEE::compute_fee(info, post_info, len, Self::refundable_tip())
+	}
+
+	const REFUND_BRIDGE_CALLS_ONLY: bool = FEE::REFUND_BRIDGE_CALLS_ONLY;
+
+	fn compute_bridge_calls_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		_tip: Self::Balance,
+		bridge_calls_weight: Weight,
+	) -> Option<Self::Balance> {
+		FEE::compute_bridge_calls_fee(
+			info,
+			post_info,
+			len,
+			Self::refundable_tip(),
+			bridge_calls_weight,
+		)
+	}
+}
+
+/// Transaction fee calculation that only refunds the benchmarked weight of bridge calls of the
+/// transaction. The fee is computed by the `FEE`.
+///
+/// Unlike other calculations, it doesn't use the weight of the whole transaction. So if relayer
+/// bundles unrelated calls into the bridge batch, their weight is never refunded. The actual
+/// weight of the transaction is only used if it is lower than the benchmarked weight of bridge
+/// calls. The length fee is still computed for the whole transaction, so the
+/// `MDL` parameter of the extension should be used to limit it.
+pub struct BridgeCallsWeightRefund<FEE>(PhantomData<FEE>);
+
+impl<FEE> TransactionFeeCalculation for BridgeCallsWeightRefund<FEE>
+where
+	FEE: TransactionFeeCalculation,
+{
+	type Balance = FEE::Balance;
+
+	fn compute_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		tip: Self::Balance,
+	) -> Option<Self::Balance> {
+		FEE::compute_fee(info, post_info, len, tip)
+	}
+
+	const REFUND_BRIDGE_CALLS_ONLY: bool = true;
+
+	fn compute_bridge_calls_fee(
+		info: &DispatchInfo,
+		post_info: &PostDispatchInfo,
+		len: usize,
+		tip: Self::Balance,
+		bridge_calls_weight: Weight,
+	) -> Option<Self::Balance> {
+		let bridge_calls_info = DispatchInfo { weight: bridge_calls_weight, ..*info };
+		let bridge_calls_post_info = PostDispatchInfo {
+			actual_weight: post_info
+				.actual_weight
+				.map(|actual_weight| actual_weight.min(bridge_calls_weight)),
+			pays_fee: post_info.pays_fee,
+		};
+		FEE::compute_fee(&bridge_calls_info, &bridge_calls_post_info, len, tip)
+	}
+}
+
+/// Custom refund eligibility check.
+///
+/// It is called right before the relayer reward is registered. Use `()` if all relayers that
+/// have submitted useful bridge transactions shall be refunded.
+pub trait RefundEligibility<AccountId, Reward> {
+	/// Returns true if the relayer shall be refunded for the transaction, described by the `data`.
+	fn is_eligible(data: &PreDispatchData<AccountId>, reward: &Reward) -> bool;
+}
+
+impl<AccountId, Reward> RefundEligibility<AccountId, Reward> for () {
+	fn is_eligible(_data: &PreDispatchData<AccountId>, _reward: &Reward) -> bool {
+		true
+	}
+}
+
+/// Converter of the relayer account at the bridged chain into the relayer account at this chain.
+///
+/// It is used to check that the message delivery transaction is submitted by the relayer, which
+/// account at the bridged chain (`relayer_id_at_bridged_chain`) is declared in the delivery call.
+/// If accounts don't match, the relayer is not refunded, but the delivery itself is not rejected.
+/// Use `()` if relayer accounts at both sides of the bridge are unrelated.
+pub trait RelayerAccountIdConverter<BridgedAccountId, AccountId> {
+	/// Returns account of the relayer at this chain, or `None` if accounts are not checked.
+	fn convert(relayer_id_at_bridged_chain: &BridgedAccountId) -> Option<AccountId>;
+}
+
+impl<BridgedAccountId, AccountId> RelayerAccountIdConverter<BridgedAccountId, AccountId> for () {
+	fn convert(_relayer_id_at_bridged_chain: &BridgedAccountId) -> Option<AccountId> {
+		None
+	}
+}
+
+/// Handler of relayer refunds.
+///
+/// By default, refunds are registered in the relayers pallet and the relayer may claim them
+/// later. The handler may be used to pay refunds differently (e.g. in some asset instead of the
+/// native token). If the handler returns `false`, the refund is registered in the relayers pallet
+/// as usual. Handler is not called in the shadow mode.
+pub trait OnRefund<AccountId, Reward> {
+	/// Handle the `reward` of the `relayer` for serving the `lane_id`. Returns `true` if the refund
+	/// has been handled.
+	fn on_refund(lane_id: LaneId, relayer: &AccountId, reward: &Reward) -> bool;
+}
+
+impl<AccountId, Reward> OnRefund<AccountId, Reward> for () {
+	fn on_refund(_lane_id: LaneId, _relayer: &AccountId, _reward: &Reward) -> bool {
+		false
 	}
 }
+
+/// Refund handler that pays refunds in the `Asset` of `Assets` (e.g. `pallet_assets`).
+///
+/// The refund is converted to the asset using the `Rate`. If the `Source` account is `None`,
+/// the asset is minted. Otherwise, it is transferred from the `Source` account. If the payment
+/// fails, the refund is registered in the relayers pallet.
+pub struct RefundInAsset<Assets, Asset, Source, Rate>(PhantomData<(Assets, Asset, Source, Rate)>);
+
+impl<AccountId, Reward, Assets, Asset, Source, Rate> OnRefund<AccountId, Reward>
+	for RefundInAsset<Assets, Asset, Source, Rate>
+where
+	AccountId: Debug,
+	Reward: Debug + FixedPointOperand,
+	Assets: fungibles::Mutate<AccountId> + fungibles::Transfer<AccountId>,
+	Assets::Balance: UniqueSaturatedFrom<Reward>,
+	Asset: Get<Assets::AssetId>,
+	Source: Get<Option<AccountId>>,
+	Rate: Get<FixedU128>,
+{
+	fn on_refund(lane_id: LaneId, relayer: &AccountId, reward: &Reward) -> bool {
+		let amount =
+			Assets::Balance::unique_saturated_from(Rate::get().saturating_mul_int(*reward));
+		let result = match Source::get() {
+			Some(source) =>
+				Assets::transfer(Asset::get(), &source, relayer, amount, false).map(drop),
+			None => Assets::mint_into(Asset::get(), relayer, amount),
+		};
+
+		if let Err(e) = result {
+			log::error!(
+				target: "runtime::bridge",
+				"Failed to pay {:?} refund to {:?} for serving lane {:?} in asset. Error: {:?}",
+				reward,
+				relayer,
+				lane_id,
+				e,
+			);
+			return false
+		}
+
+		true
+	}
+}
+
 /// Signed extension that refunds relayer for new messages coming from the parachain.
 ///
 /// Also refunds relayer for successful finality delivery if it comes in batch (`utility.batchAll`)
@@ -88,7 +666,45 @@ where
 /// parachain head, or just parachain head. Corresponding headers must be used in messages
 /// proof verification.
 ///
-/// Extension does not refund transaction tip due to security reasons.
+/// Standalone messages delivery confirmation transactions are also refunded if they have
+/// confirmed at least one message. Batches are unpacked using the `UB` parameter. Extension does
+/// not refund transaction tip due to security reasons, unless the `FEE` parameter is wrapped into
+/// `TipRefund`, which refunds tips up to the configured cap. Optional extension settings are
+/// provided by the `CFG` parameter. Runtimes may use the `ELG` parameter to implement custom rules
+/// that decide whether the relayer shall be refunded. The `AIC` parameter may be used to only
+/// refund message deliveries that are submitted by the relayer, declared in the delivery call.
+/// Refunds may be limited by wrapping the `FEE` parameter into `ProportionalRefund` or
+/// `CappedRefund`. Obsolete bridge calls of the batch are rejected by the `BE` filter (e.g. tuple
+/// of bridge pallets or `BridgeRejectObsoleteHeadersAndMessages`). Refunds are registered in the
+/// relayers pallet, unless they are handled by the optional `REF` handler (e.g. `RefundInAsset`).
+///
+/// Optional `Get<>`-typed parameters (`()` disables the corresponding feature):
+///
+/// - `MDL` (`Get<u32>`) is the maximal encoded length (in bytes) of the message delivery
+///   transaction per every message that it declares to deliver. The transaction length affects the
+///   refund, so relayer could pad delivery transaction with junk to inflate it. Delivery
+///   transactions with length that is larger than `messages_count * MDL` are rejected with the
+///   `OVERSIZED_DELIVERY_TRANSACTION` code. The value must also account for finality proofs that
+///   may be bundled with the delivery. Zero means that the length is not limited;
+/// - `MBR` (`Get<BlockNumber>`) is the minimal number of blocks between two refunds of the same
+///   relayer at the same lane. If relayer reward for serving the lane has been updated (by this
+///   extension or by any other code that is using the relayers pallet) less than `MBR` blocks ago,
+///   the refund is withheld. The bridge transaction itself is still dispatched. Zero means that
+///   there's no cooldown;
+/// - `SHD` (`Get<bool>`) enables the shadow (observe-only) mode. In the shadow mode, the extension
+///   does everything to compute relayer rewards, but instead of registering them in the relayers
+///   pallet, it deposits the `ShadowReward` event of the relayers pallet. It may be used to check
+///   the extension before enabling refunds at the production bridge;
+/// - `IMM` (`Get<bool>`) makes the extension pay rewards immediately, using the `PaymentProcedure`
+///   of the relayers pallet, instead of registering them. If the payment fails (e.g. if the account
+///   that is paying rewards has insufficient funds), the reward is registered as usual;
+/// - `FCR` (`Get<Option<FixedU128>>`) is the rate of conversion from this chain fee units to the
+///   bridged chain fee units. If it is `Some(_)`, the `RelayerRefunded` event of the relayers
+///   pallet contains the bridged chain equivalent of the registered reward. The registered reward
+///   itself is never changed.
+///
+/// All parameters may read runtime storage (e.g. if they're declared with `pub storage` in the
+/// `parameter_types!`), so the settings may be changed without runtime upgrade.
 #[derive(
 	CloneNoBound,
 	Decode,
@@ -99,10 +715,50 @@ where
 	RuntimeDebugNoBound,
 	TypeInfo,
 )]
-#[scale_info(skip_type_params(RT, GI, PI, MI, BE, PID, LID, FEE))]
+#[scale_info(skip_type_params(
+	RT, GI, PI, MI, UB, BE, PID, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR
+))]
 #[allow(clippy::type_complexity)] // TODO: get rid of that in https://github.com/paritytech/parity-bridges-common/issues/1666
-pub struct RefundRelayerForMessagesFromParachain<RT, GI, PI, MI, BE, PID, LID, FEE>(
-	PhantomData<(RT, GI, PI, MI, BE, PID, LID, FEE)>,
+pub struct RefundRelayerForMessagesFromParachain<
+	RT,
+	GI,
+	PI,
+	MI,
+	UB,
+	BE,
+	PID,
+	LID,
+	FEE,
+	ELG,
+	AIC,
+	CFG,
+	REF = (),
+	MDL = (),
+	MBR = (),
+	SHD = (),
+	IMM = (),
+	FCR = (),
+>(
+	PhantomData<(
+		RT,
+		GI,
+		PI,
+		MI,
+		UB,
+		BE,
+		PID,
+		LID,
+		FEE,
+		ELG,
+		AIC,
+		CFG,
+		REF,
+		MDL,
+		MBR,
+		SHD,
+		IMM,
+		FCR,
+	)>,
 );
 
 /// Data that is crafted in `pre_dispatch` method and used at `post_dispatch`.
@@ -113,28 +769,94 @@ pub struct PreDispatchData<AccountId> {
 	pub relayer: AccountId,
 	/// Type of the call.
 	pub call_type: CallType,
+	/// Relayers that have delivered messages, confirmed by the transaction, with the number of
+	/// confirmed messages. It is only filled for delivery confirmation transactions and only if
+	/// `RefundConfig::DELIVERY_REWARD_PER_CONFIRMED_MESSAGE` is non-zero.
+	pub confirmed_messages_relayers: RelayersRewards<AccountId>,
+	/// Weight of the message delivery call, bundled into the non-atomic batch. If finality calls
+	/// of such batch have failed, relayer is only refunded for the delivery call.
+	pub non_atomic_batch_delivery_weight: Option<Weight>,
+	/// Benchmarked weight of bridge calls of the transaction. It is only computed if the
+	/// `TransactionFeeCalculation::REFUND_BRIDGE_CALLS_ONLY` is true.
+	pub bridge_calls_weight: Option<Weight>,
 }
 
 /// Type of the call that the extension recognizes.
 #[derive(Clone, Copy, PartialEq, RuntimeDebugNoBound)]
 pub enum CallType {
-	/// Relay chain finality + parachain finality + message delivery calls.
+	/// Relay chain finality (one or several calls) + parachain finality + message delivery calls.
 	AllFinalityAndDelivery(ExpectedRelayChainState, ExpectedParachainState, MessagesState),
 	/// Parachain finality + message delivery calls.
 	ParachainFinalityAndDelivery(ExpectedParachainState, MessagesState),
 	/// Standalone message delivery call.
 	Delivery(MessagesState),
+	/// Standalone messages delivery confirmation call.
+	DeliveryConfirmation(ConfirmationState),
+	/// Standalone relay chain finality call. Only supported if the
+	/// `RefundConfig::REFUND_RELAY_FINALITY` is true.
+	RelayFinality(ExpectedRelayChainState),
+	/// Standalone parachain finality call. Only supported if the
+	/// `RefundConfig::REFUND_PARACHAIN_FINALITY` is true.
+	ParachainFinality(ExpectedParachainState),
+	/// Relay chain finality + message delivery calls. Only supported by the
+	/// `RefundRelayerForMessagesDeliveryFromRelayChain`.
+	RelayFinalityAndDelivery(ExpectedRelayChainState, MessagesState),
 }
 
 impl CallType {
-	/// Returns the pre-dispatch messages pallet state.
-	fn pre_dispatch_messages_state(&self) -> MessagesState {
+	/// Returns the pre-dispatch messages pallet state, if the call is delivering messages.
+	fn pre_dispatch_messages_state(&self) -> Option<MessagesState> {
 		match *self {
-			Self::AllFinalityAndDelivery(_, _, messages_state) => messages_state,
-			Self::ParachainFinalityAndDelivery(_, messages_state) => messages_state,
-			Self::Delivery(messages_state) => messages_state,
+			Self::AllFinalityAndDelivery(_, _, messages_state) => Some(messages_state),
+			Self::ParachainFinalityAndDelivery(_, messages_state) => Some(messages_state),
+			Self::Delivery(messages_state) => Some(messages_state),
+			Self::DeliveryConfirmation(_) => None,
+			Self::RelayFinality(_) => None,
+			Self::ParachainFinality(_) => None,
+			Self::RelayFinalityAndDelivery(_, messages_state) => Some(messages_state),
 		}
 	}
+
+	/// Returns kind of the reward that the relayer gets for submitting the call.
+	fn reward_kind(&self) -> RewardKind {
+		match *self {
+			Self::DeliveryConfirmation(_) => RewardKind::DeliveryConfirmation,
+			_ => RewardKind::MessageDelivery,
+		}
+	}
+}
+
+/// Type of the call that the extension recognizes, based only on the call structure.
+///
+/// Unlike `CallType`, it doesn't include any state that is read from the runtime storage.
+#[derive(Clone, Copy, PartialEq, RuntimeDebugNoBound)]
+pub enum StructuralCallType {
+	/// Relay chain finality (one or several calls) + parachain finality + message delivery calls.
+	AllFinalityAndDelivery(ExpectedRelayChainState, ExpectedParachainState),
+	/// Parachain finality + message delivery calls.
+	ParachainFinalityAndDelivery(ExpectedParachainState),
+	/// Standalone message delivery call.
+	Delivery,
+	/// Standalone messages delivery confirmation call.
+	DeliveryConfirmation,
+	/// Standalone relay chain finality call. Only supported if the
+	/// `RefundConfig::REFUND_RELAY_FINALITY` is true.
+	RelayFinality(ExpectedRelayChainState),
+	/// Standalone parachain finality call. Only supported if the
+	/// `RefundConfig::REFUND_PARACHAIN_FINALITY` is true.
+	ParachainFinality(ExpectedParachainState),
+}
+
+/// Reason why the extension doesn't support (and never refunds) the transaction.
+#[derive(Clone, Copy, PartialEq, RuntimeDebugNoBound)]
+pub enum UnsupportedReason {
+	/// The call is not a bridge transaction, or it has structure that we do not support.
+	UnknownCall,
+	/// The call is delivering messages over the lane that is not served by the extension.
+	WrongLane(LaneId),
+	/// The message delivery call is submitted by the relayer, which account doesn't match
+	/// relayer account at the bridged chain, declared in the call.
+	RelayerIdMismatch,
 }
 
 /// Expected post-dispatch state of the relay chain pallet.
@@ -147,6 +869,8 @@ pub struct ExpectedRelayChainState {
 /// Expected post-dispatch state of the parachain pallet.
 #[derive(Clone, Copy, PartialEq, RuntimeDebugNoBound)]
 pub struct ExpectedParachainState {
+	/// Identifier of the parachain, which head has been updated.
+	pub para_id: ParaId,
 	/// At which relay block the parachain head has been updated?
 	pub at_relay_block_number: RelayBlockNumber,
 }
@@ -163,37 +887,57 @@ pub struct MessagesState {
 	pub best_nonce: MessageNonce,
 }
 
+/// Pre-dispatch state of messages pallet at the source side of the lane.
+///
+/// Similar to `MessagesState`, the confirmation transaction is considered "helpful" if it confirms
+/// at least one message.
+#[derive(Clone, Copy, PartialEq, RuntimeDebugNoBound)]
+pub struct ConfirmationState {
+	/// Best confirmed message nonce.
+	pub best_confirmed_nonce: MessageNonce,
+}
+
 // without this typedef rustfmt fails with internal err
 type BalanceOf<R> =
 	<<R as TransactionPaymentConfig>::OnChargeTransaction as OnChargeTransaction<R>>::Balance;
 type CallOf<R> = <R as frame_system::Config>::RuntimeCall;
+type MessagesWeightInfoOf<R, MI> = <R as MessagesConfig<MI>>::WeightInfo;
 
-impl<R, GI, PI, MI, BE, PID, LID, FEE> SignedExtension
-	for RefundRelayerForMessagesFromParachain<R, GI, PI, MI, BE, PID, LID, FEE>
+impl<R, GI, PI, MI, UB, BE, PID, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR>
+	RefundRelayerForMessagesFromParachain<
+		R,
+		GI,
+		PI,
+		MI,
+		UB,
+		BE,
+		PID,
+		LID,
+		FEE,
+		ELG,
+		AIC,
+		CFG,
+		REF,
+		MDL,
+		MBR,
+		SHD,
+		IMM,
+		FCR,
+	>
 where
-	R: 'static
-		+ Send
-		+ Sync
-		+ frame_system::Config
-		+ UtilityConfig<RuntimeCall = CallOf<R>>
-		+ GrandpaConfig<GI>
-		+ ParachainsConfig<PI, BridgesGrandpaPalletInstance = GI>
-		+ MessagesConfig<MI>
-		+ RelayersConfig,
-	GI: 'static + Send + Sync,
-	PI: 'static + Send + Sync,
-	MI: 'static + Send + Sync,
-	BE: 'static
-		+ Send
-		+ Sync
-		+ Default
-		+ SignedExtension<AccountId = R::AccountId, Call = CallOf<R>>,
-	PID: 'static + Send + Sync + Get<u32>,
-	LID: 'static + Send + Sync + Get<LaneId>,
-	FEE: 'static + Send + Sync + TransactionFeeCalculation<<R as RelayersConfig>::Reward>,
-	<R as frame_system::Config>::RuntimeCall:
-		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
-	CallOf<R>: IsSubType<CallableCallFor<UtilityPallet<R>, R>>
+	R: GrandpaConfig<GI>
+		+ ParachainsConfig<PI, RelayChainHeaders = GrandpaChainHeaders<R, GI>>
+		+ MessagesConfig<MI>,
+	GI: 'static,
+	PI: 'static,
+	MI: 'static,
+	UB: BatchCallUnpacker<CallOf<R>>,
+	PID: Get<Vec<u32>>,
+	LID: Get<LaneId>,
+	FEE: TransactionFeeCalculation,
+	AIC: RelayerAccountIdConverter<<R as MessagesConfig<MI>>::InboundRelayer, R::AccountId>,
+	CFG: RefundConfig,
+	CallOf<R>: GetDispatchInfo
 		+ IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>
 		+ IsSubType<CallableCallFor<ParachainsPallet<R, PI>, R>>
 		+ IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
@@ -202,79 +946,417 @@ where
 	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
 		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
 	>,
+	<R as MessagesConfig<MI>>::TargetHeaderChain: TargetHeaderChain<
+		<R as MessagesConfig<MI>>::OutboundPayload,
+		<R as frame_system::Config>::AccountId,
+		MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<R, GI>>>,
+	>,
 {
-	const IDENTIFIER: &'static str = "RefundRelayerForMessagesFromParachain";
-	type AccountId = R::AccountId;
-	type Call = CallOf<R>;
-	type AdditionalSigned = ();
-	type Pre = Option<PreDispatchData<R::AccountId>>;
+	/// Checks if the call matches one of transaction types that we support.
+	///
+	/// Returns type of the call, or the reason why it isn't supported by the extension.
+	pub fn parse_call_type(call: &CallOf<R>) -> Result<CallType, UnsupportedReason> {
+		Ok(match Self::classify_call(call)? {
+			StructuralCallType::AllFinalityAndDelivery(
+				expected_relay_chain_state,
+				expected_parachain_state,
+			) => CallType::AllFinalityAndDelivery(
+				expected_relay_chain_state,
+				expected_parachain_state,
+				messages_state::<R, MI, LID>(),
+			),
+			StructuralCallType::ParachainFinalityAndDelivery(expected_parachain_state) =>
+				CallType::ParachainFinalityAndDelivery(
+					expected_parachain_state,
+					messages_state::<R, MI, LID>(),
+				),
+			StructuralCallType::Delivery => CallType::Delivery(messages_state::<R, MI, LID>()),
+			StructuralCallType::DeliveryConfirmation =>
+				CallType::DeliveryConfirmation(confirmation_state::<R, MI, LID>()),
+			StructuralCallType::RelayFinality(expected_relay_chain_state) =>
+				CallType::RelayFinality(expected_relay_chain_state),
+			StructuralCallType::ParachainFinality(expected_parachain_state) =>
+				CallType::ParachainFinality(expected_parachain_state),
+		})
+	}
 
-	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
-		Ok(())
+	/// Prepares data that is required to refund relayer for the bridge call, that is not yet
+	/// dispatched.
+	///
+	/// Only required when bridge calls are dispatched without running signed extensions (e.g. by
+	/// the `pallet_scheduler`). It must be called right before the call is dispatched. Returns
+	/// `None` if the call is not supported by the extension. See `refund_dispatched_call` for
+	/// details.
+	pub fn prepare_refund(
+		relayer: R::AccountId,
+		call: &CallOf<R>,
+	) -> Option<PreDispatchData<R::AccountId>> {
+		Self::prepare_pre_dispatch_data(relayer, call).ok()
 	}
 
-	fn validate(
-		&self,
-		who: &Self::AccountId,
-		call: &Self::Call,
-		info: &DispatchInfoOf<Self::Call>,
+	/// Estimates the refund that the relayer will get for the bridge call, assuming that the call
+	/// succeeds.
+	///
+	/// It may be used by the block building code to prioritize bridge transactions that will be
+	/// refunded. The call is not dispatched and the runtime storage is never changed. The `len`
+	/// is the encoded length of the whole transaction. Returns `None` if the relayer won't be
+	/// refunded for the call.
+	pub fn estimated_refund(
+		relayer: R::AccountId,
+		call: &CallOf<R>,
 		len: usize,
-	) -> TransactionValidity {
-		// reject batch transactions with obsolete headers
-		if let Some(UtilityCall::<R>::batch_all { ref calls }) = call.is_sub_type() {
-			for nested_call in calls {
-				let reject_obsolete_transactions = BE::default();
-				reject_obsolete_transactions.pre_dispatch(who, nested_call, info, len)?;
-			}
+	) -> Option<<R as RelayersConfig>::Reward>
+	where
+		R: RelayersConfig,
+		FEE::Balance: UniqueSaturatedInto<<R as RelayersConfig>::Reward>,
+		ELG: RefundEligibility<R::AccountId, <R as RelayersConfig>::Reward>,
+		MBR: Get<<R as frame_system::Config>::BlockNumber>,
+		SHD: Get<bool>,
+		<R as RelayersConfig>::Reward: FixedPointOperand,
+	{
+		// rewards are not registered in the shadow mode
+		if SHD::get() {
+			return None
+		}
+
+		let pre_dispatch_data = Self::prepare_pre_dispatch_data(relayer, call).ok()?;
+		let rewards_account_params =
+			RewardsAccountParams::new(LID::get(), pre_dispatch_data.call_type.reward_kind());
+		let info = call.get_dispatch_info();
+		let post_info = PostDispatchInfo { actual_weight: None, pays_fee: info.pays_fee };
+		let reward = compute_reward::<R, FEE>(
+			rewards_account_params,
+			&info,
+			&post_info,
+			len,
+			pre_dispatch_data.bridge_calls_weight,
+		)?;
+
+		if recent_reward_block_number::<R, MBR>(rewards_account_params, &pre_dispatch_data.relayer)
+			.is_some()
+		{
+			return None
+		}
+		if !ELG::is_eligible(&pre_dispatch_data, &reward) {
+			return None
+		}
+		if CFG::REFUND_REGISTERED_RELAYERS_ONLY &&
+			!RelayersPallet::<R>::is_registration_active(&pre_dispatch_data.relayer)
+		{
+			return None
 		}
 
-		Ok(ValidTransaction::default())
+		Some(reward)
 	}
 
-	fn pre_dispatch(
-		self,
-		who: &Self::AccountId,
-		call: &Self::Call,
-		info: &DispatchInfoOf<Self::Call>,
-		len: usize,
-	) -> Result<Self::Pre, TransactionValidityError> {
-		// reject batch transactions with obsolete headers
-		self.validate(who, call, info, len).map(drop)?;
+	/// Prepares data that is passed from `pre_dispatch` to `post_dispatch`.
+	fn prepare_pre_dispatch_data(
+		relayer: R::AccountId,
+		call: &CallOf<R>,
+	) -> Result<PreDispatchData<R::AccountId>, UnsupportedReason> {
+		let call_type = Self::parse_call_type(call)?;
 
-		// now try to check if tx matches one of types we support
-		let parse_call_type = || {
-			if let Some(UtilityCall::<R>::batch_all { ref calls }) = call.is_sub_type() {
-				if calls.len() == 3 {
-					return Some(CallType::AllFinalityAndDelivery(
-						extract_expected_relay_chain_state::<R, GI>(&calls[0])?,
-						extract_expected_parachain_state::<R, GI, PI, PID>(&calls[1])?,
-						extract_messages_state::<R, GI, MI, LID>(&calls[2])?,
-					))
-				}
-				if calls.len() == 2 {
-					return Some(CallType::ParachainFinalityAndDelivery(
-						extract_expected_parachain_state::<R, GI, PI, PID>(&calls[0])?,
-						extract_messages_state::<R, GI, MI, LID>(&calls[1])?,
-					))
-				}
-				return None
+		// check that the delivery transaction is submitted by the relayer, declared in the call
+		if let Some(relayer_id_at_bridged_chain) =
+			bundled_relayer_id_at_bridged_chain::<R, GI, MI, UB, LID, CFG>(call)
+		{
+			match AIC::convert(relayer_id_at_bridged_chain) {
+				Some(expected_relayer) if expected_relayer != relayer =>
+					return Err(UnsupportedReason::RelayerIdMismatch),
+				_ => (),
 			}
+		}
 
-			Some(CallType::Delivery(extract_messages_state::<R, GI, MI, LID>(call)?))
+		// remember relayers that have delivered confirmed messages, if we need to reward them
+		let confirmed_messages_relayers = match call_type {
+			CallType::DeliveryConfirmation(confirmation_state)
+				if CFG::DELIVERY_REWARD_PER_CONFIRMED_MESSAGE != 0 =>
+				extract_confirmed_messages_relayers::<R, GI, MI>(call, confirmation_state),
+			_ => RelayersRewards::new(),
 		};
 
-		Ok(parse_call_type()
-			.map(|call_type| {
+		// remember weight of the delivery call, bundled into the non-atomic batch
+		let non_atomic_batch_delivery_weight = match call_type.pre_dispatch_messages_state() {
+			Some(_) if UB::unpack_batch_all(call).is_none() => UB::unpack_non_atomic_batch(call)
+				.and_then(|calls| calls.last())
+				.map(|delivery_call| delivery_call.get_dispatch_info().weight),
+			_ => None,
+		};
+
+		// remember benchmarked weight of bridge calls, if we only refund bridge calls
+		let bridge_calls_weight = if FEE::REFUND_BRIDGE_CALLS_ONLY {
+			Some(match unpack_bridge_batch::<_, UB, CFG>(call) {
+				Some(calls) => calls
+					.iter()
+					.filter(|call| is_header_or_delivery_call::<R, GI, PI, MI>(call))
+					.fold(Weight::zero(), |weight, call| {
+						weight.saturating_add(call.get_dispatch_info().weight)
+					}),
+				None => call.get_dispatch_info().weight,
+			})
+		} else {
+			None
+		};
+
+		Ok(PreDispatchData {
+			relayer,
+			call_type,
+			confirmed_messages_relayers,
+			non_atomic_batch_delivery_weight,
+			bridge_calls_weight,
+		})
+	}
+
+	/// Checks if the call structure matches one of transaction types that we support.
+	///
+	/// Unlike `parse_call_type`, it never reads runtime storage, so it may be used by offline
+	/// tools to check how the extension would treat the (decoded) call.
+	pub fn classify_call_structure(call: &CallOf<R>) -> Option<StructuralCallType> {
+		Self::classify_call(call).ok()
+	}
+
+	/// Checks if the call structure matches one of transaction types that we support.
+	///
+	/// Returns structural type of the call, or the reason why it isn't supported by the extension.
+	fn classify_call(call: &CallOf<R>) -> Result<StructuralCallType, UnsupportedReason> {
+		if let Some(calls) = unpack_bridge_batch::<_, UB, CFG>(call) {
+			return match calls.as_slice() {
+				[parachain_head_call, delivery_call] => {
+					let expected_parachain_state =
+						extract_expected_parachain_state::<R, GI, PI, PID>(parachain_head_call)
+							.ok_or(UnsupportedReason::UnknownCall)?;
+					check_messages_delivery_call::<R, GI, MI, LID>(delivery_call)?;
+					Ok(StructuralCallType::ParachainFinalityAndDelivery(expected_parachain_state))
+				},
+				[relay_header_calls @ .., parachain_head_call, delivery_call]
+					if relay_header_calls.len() <= CFG::MAX_RELAY_HEADERS_PER_BATCH as usize =>
+				{
+					// the batch has at least three calls here, so there's at least one relay
+					// chain header call
+					let mut expected_relay_chain_state = None;
+					for relay_header_call in relay_header_calls {
+						expected_relay_chain_state = Some(
+							extract_expected_relay_chain_state::<R, GI>(relay_header_call)
+								.ok_or(UnsupportedReason::UnknownCall)?,
+						);
+					}
+					let expected_relay_chain_state =
+						expected_relay_chain_state.ok_or(UnsupportedReason::UnknownCall)?;
+					let expected_parachain_state =
+						extract_expected_parachain_state::<R, GI, PI, PID>(parachain_head_call)
+							.ok_or(UnsupportedReason::UnknownCall)?;
+					// parachain head must be proved using the relay chain header that is finalized
+					// by the same batch (or by some previous transaction)
+					if expected_parachain_state.at_relay_block_number >
+						expected_relay_chain_state.best_block_number
+					{
+						return Err(UnsupportedReason::UnknownCall)
+					}
+					check_messages_delivery_call::<R, GI, MI, LID>(delivery_call)?;
+					Ok(StructuralCallType::AllFinalityAndDelivery(
+						expected_relay_chain_state,
+						expected_parachain_state,
+					))
+				},
+				// empty and single-call batches are never refunded - there's no reason to wrap
+				// standalone bridge call into the batch. Batches with too many relay chain headers
+				// are not refunded either
+				_ => Err(UnsupportedReason::UnknownCall),
+			}
+		}
+
+		if CFG::REFUND_RELAY_FINALITY {
+			if let Some(expected_relay_chain_state) =
+				extract_expected_relay_chain_state::<R, GI>(call)
+			{
+				return Ok(StructuralCallType::RelayFinality(expected_relay_chain_state))
+			}
+		}
+
+		if CFG::REFUND_PARACHAIN_FINALITY {
+			if let Some(expected_parachain_state) =
+				extract_expected_parachain_state::<R, GI, PI, PID>(call)
+			{
+				return Ok(StructuralCallType::ParachainFinality(expected_parachain_state))
+			}
+		}
+
+		match check_messages_delivery_call::<R, GI, MI, LID>(call) {
+			Ok(()) => Ok(StructuralCallType::Delivery),
+			Err(UnsupportedReason::UnknownCall) => {
+				check_messages_confirmation_call::<R, GI, MI, LID>(call)?;
+				Ok(StructuralCallType::DeliveryConfirmation)
+			},
+			Err(reason) => Err(reason),
+		}
+	}
+}
+
+impl<R, GI, PI, MI, UB, BE, PID, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR>
+	SignedExtension
+	for RefundRelayerForMessagesFromParachain<
+		R,
+		GI,
+		PI,
+		MI,
+		UB,
+		BE,
+		PID,
+		LID,
+		FEE,
+		ELG,
+		AIC,
+		CFG,
+		REF,
+		MDL,
+		MBR,
+		SHD,
+		IMM,
+		FCR,
+	>
+where
+	R: 'static
+		+ Send
+		+ Sync
+		+ frame_system::Config
+		+ GrandpaConfig<GI>
+		+ ParachainsConfig<PI, RelayChainHeaders = GrandpaChainHeaders<R, GI>>
+		+ MessagesConfig<MI>
+		+ RelayersConfig,
+	GI: 'static + Send + Sync,
+	PI: 'static + Send + Sync,
+	MI: 'static + Send + Sync,
+	UB: 'static + Send + Sync + BatchCallUnpacker<CallOf<R>>,
+	BE: 'static + Send + Sync + BridgeRuntimeFilterCall<CallOf<R>>,
+	PID: 'static + Send + Sync + Get<Vec<u32>>,
+	LID: 'static + Send + Sync + Get<LaneId>,
+	FEE: 'static + Send + Sync + TransactionFeeCalculation,
+	FEE::Balance: UniqueSaturatedInto<<R as RelayersConfig>::Reward>,
+	ELG: 'static + Send + Sync + RefundEligibility<R::AccountId, <R as RelayersConfig>::Reward>,
+	AIC: 'static
+		+ Send
+		+ Sync
+		+ RelayerAccountIdConverter<<R as MessagesConfig<MI>>::InboundRelayer, R::AccountId>,
+	CFG: 'static + Send + Sync + RefundConfig,
+	REF: 'static + Send + Sync + OnRefund<R::AccountId, <R as RelayersConfig>::Reward>,
+	MDL: 'static + Send + Sync + Get<u32>,
+	MBR: 'static + Send + Sync + Get<<R as frame_system::Config>::BlockNumber>,
+	SHD: 'static + Send + Sync + Get<bool>,
+	IMM: 'static + Send + Sync + Get<bool>,
+	FCR: 'static + Send + Sync + Get<Option<FixedU128>>,
+	<R as RelayersConfig>::Reward: FixedPointOperand,
+	<R as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	CallOf<R>: GetDispatchInfo
+		+ IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>
+		+ IsSubType<CallableCallFor<ParachainsPallet<R, PI>, R>>
+		+ IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as GrandpaConfig<GI>>::BridgedChain:
+		Chain<BlockNumber = RelayBlockNumber, Hash = RelayBlockHash, Hasher = RelayBlockHasher>,
+	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
+		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+	<R as MessagesConfig<MI>>::TargetHeaderChain: TargetHeaderChain<
+		<R as MessagesConfig<MI>>::OutboundPayload,
+		<R as frame_system::Config>::AccountId,
+		MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	const IDENTIFIER: &'static str = "RefundRelayerForMessagesFromParachain";
+	type AccountId = R::AccountId;
+	type Call = CallOf<R>;
+	type AdditionalSigned = ();
+	type Pre = Option<PreDispatchData<R::AccountId>>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let valid = validate_delivery_transaction::<R, GI, MI, UB, BE, PID, LID, CFG, MDL>(
+			Self::IDENTIFIER,
+			who,
+			call,
+			len,
+			is_header_or_delivery_call::<R, GI, PI, MI>,
+		)?;
+
+		// boost priority of bridge transactions, submitted by registered relayers
+		let priority = if CFG::REGISTERED_RELAYER_PRIORITY_BOOST != 0 &&
+			Self::classify_call(call).is_ok() &&
+			RelayersPallet::<R>::is_registration_active(who)
+		{
+			valid.priority.saturating_add(CFG::REGISTERED_RELAYER_PRIORITY_BOOST)
+		} else {
+			valid.priority
+		};
+
+		Ok(ValidTransaction { priority, ..valid })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		// reject batch transactions with obsolete headers
+		self.validate(who, call, info, len)?;
+		register_bridge_batch_parse_weight::<R, UB, CFG>(call, info);
+
+		// now try to check if tx matches one of types we support
+		let pre_dispatch_data = match Self::prepare_pre_dispatch_data(who.clone(), call) {
+			Ok(pre_dispatch_data) => pre_dispatch_data,
+			Err(UnsupportedReason::WrongLane(lane)) => {
+				log::debug!(
+					target: "runtime::bridge",
+					"{} ignores bridge transaction \
+						that is delivering messages over lane {:?}",
+					BridgeLogName::<PID, LID, CFG>(Self::IDENTIFIER, PhantomData),
+					lane,
+				);
+				return Ok(None)
+			},
+			Err(UnsupportedReason::RelayerIdMismatch) => {
 				log::trace!(
 					target: "runtime::bridge",
-					"RefundRelayerForMessagesFromParachain from parachain {} via {:?} parsed bridge transaction in pre-dispatch: {:?}",
-					PID::get(),
-					LID::get(),
-					call_type,
+					"{} won't refund delivery transaction: \
+						relayer {:?} is not the relayer, declared in the call",
+					BridgeLogName::<PID, LID, CFG>(Self::IDENTIFIER, PhantomData),
+					who,
 				);
-				PreDispatchData { relayer: who.clone(), call_type }
-			})
-		)
+				note_skipped_refund::<R, LID, CFG>(who, RefundSkipReason::RelayerIdMismatch);
+				return Ok(None)
+			},
+			Err(UnsupportedReason::UnknownCall) => {
+				// we don't know if the call has been targeting our bridge, unless it is
+				// delivering messages over our lane
+				if bundled_messages_proof::<R, GI, MI, UB, LID, CFG>(call).is_some() {
+					note_skipped_refund::<R, LID, CFG>(who, RefundSkipReason::UnrecognizedCall);
+				}
+				return Ok(None)
+			},
+		};
+
+		log::trace!(
+			target: "runtime::bridge",
+			"{} parsed bridge transaction in pre-dispatch: {:?}",
+			BridgeLogName::<PID, LID, CFG>(Self::IDENTIFIER, PhantomData),
+			pre_dispatch_data.call_type,
+		);
+		if let CallType::DeliveryConfirmation(_) = pre_dispatch_data.call_type {
+			if CFG::DELIVERY_REWARD_PER_CONFIRMED_MESSAGE != 0 {
+				register_delivery_proof_verification_weight::<R, MI>(call, info);
+			}
+		}
+		Ok(Some(pre_dispatch_data))
 	}
 
 	fn post_dispatch(
@@ -286,22 +1368,39 @@ where
 	) -> Result<(), TransactionValidityError> {
 		// we never refund anything if it is not bridge transaction or if it is a bridge
 		// transaction that we do not support here
-		let (relayer, call_type) = match pre {
-			Some(Some(pre)) => (pre.relayer, pre.call_type),
+		let pre_dispatch_data = match pre {
+			Some(Some(pre_dispatch_data)) => pre_dispatch_data,
 			_ => return Ok(()),
 		};
+		let PreDispatchData { ref relayer, call_type, non_atomic_batch_delivery_weight, .. } =
+			pre_dispatch_data;
 
 		// we never refund anything if transaction has failed
 		if result.is_err() {
+			slash_misbehaving_relayer::<R, PID, LID, CFG>(Self::IDENTIFIER, relayer);
+			note_skipped_refund::<R, LID, CFG>(relayer, RefundSkipReason::DispatchFailed);
 			return Ok(())
 		}
 
+		// finality calls of the non-atomic batch may fail without failing the whole transaction.
+		// In this case we only refund relayer for the delivery call (if it has succeeded)
+		let mut refund_delivery_only = false;
+
 		// check if relay chain state has been updated
-		if let CallType::AllFinalityAndDelivery(expected_relay_chain_state, _, _) = call_type {
+		if let CallType::AllFinalityAndDelivery(expected_relay_chain_state, _, _) |
+		CallType::RelayFinalityAndDelivery(expected_relay_chain_state, _) |
+		CallType::RelayFinality(expected_relay_chain_state) = call_type
+		{
 			let actual_relay_chain_state = relay_chain_state::<R, GI>();
 			if actual_relay_chain_state != Some(expected_relay_chain_state) {
 				// we only refund relayer if all calls have updated chain state
-				return Ok(())
+				if non_atomic_batch_delivery_weight.is_none() {
+					note_no_op_transaction::<R, PID, LID, CFG>(Self::IDENTIFIER, relayer);
+					slash_misbehaving_relayer::<R, PID, LID, CFG>(Self::IDENTIFIER, relayer);
+					note_skipped_refund::<R, LID, CFG>(relayer, RefundSkipReason::StateNotAdvanced);
+					return Ok(())
+				}
+				refund_delivery_only = true;
 			}
 
 			// there's a conflict between how bridge GRANDPA pallet works and the
@@ -309,584 +1408,5856 @@ where
 			// pallet returns `Pays::No`, because such transaction is mandatory for operating the
 			// bridge. But `utility.batchAll` transaction always requires payment. But in both cases
 			// we'll refund relayer - either explicitly here, or using `Pays::No` if he's choosing
-			// to submit dedicated transaction.
+			// to submit dedicated transaction. If standalone mandatory header is refunded by the
+			// extension, the computed fee is zero, so the relayer isn't refunded twice.
 		}
 
 		// check if parachain state has been updated
 		match call_type {
 			CallType::AllFinalityAndDelivery(_, expected_parachain_state, _) |
-			CallType::ParachainFinalityAndDelivery(expected_parachain_state, _) => {
-				let actual_parachain_state = parachain_state::<R, PI, PID>();
+			CallType::ParachainFinalityAndDelivery(expected_parachain_state, _) |
+			CallType::ParachainFinality(expected_parachain_state) => {
+				let actual_parachain_state =
+					parachain_state::<R, PI>(expected_parachain_state.para_id);
 				if actual_parachain_state != Some(expected_parachain_state) {
 					// we only refund relayer if all calls have updated chain state
-					return Ok(())
+					if non_atomic_batch_delivery_weight.is_none() {
+						note_no_op_transaction::<R, PID, LID, CFG>(Self::IDENTIFIER, relayer);
+						slash_misbehaving_relayer::<R, PID, LID, CFG>(Self::IDENTIFIER, relayer);
+						note_skipped_refund::<R, LID, CFG>(
+							relayer,
+							RefundSkipReason::StateNotAdvanced,
+						);
+						return Ok(())
+					}
+					refund_delivery_only = true;
 				}
 			},
 			_ => (),
 		}
 
-		// check if messages have been delivered
-		let actual_messages_state = messages_state::<R, MI, LID>();
-		let pre_dispatch_messages_state = call_type.pre_dispatch_messages_state();
-		if actual_messages_state == Some(pre_dispatch_messages_state) {
-			// we only refund relayer if all calls have updated chain state
-			return Ok(())
-		}
-
-		// regarding the tip - refund that happens here (at this side of the bridge) isn't the whole
-		// relayer compensation. He'll receive some amount at the other side of the bridge. It shall
-		// (in theory) cover the tip here. Otherwise, if we'll be compensating tip here, some
-		// malicious relayer may use huge tips, effectively depleting account that pay rewards. The
-		// cost of this attack is nothing. Hence we use zero as tip here.
-		let tip = Zero::zero();
-
-		// compute the relayer reward
-		let reward = FEE::compute_fee(info, post_info, len, tip);
-
-		// finally - register reward in relayers pallet
-		RelayersPallet::<R>::register_relayer_reward(LID::get(), &relayer, reward);
-
-		log::trace!(
-			target: "runtime::bridge",
-			"RefundRelayerForMessagesFromParachain from parachain {} via {:?} has registered {:?} reward: {:?}",
-			PID::get(),
-			LID::get(),
-			relayer,
-			reward,
+		refund_relayer::<R, MI, PID, LID, FEE, ELG, CFG, REF, MBR, SHD, IMM, FCR>(
+			Self::IDENTIFIER,
+			&pre_dispatch_data,
+			refund_delivery_only,
+			info,
+			post_info,
+			len,
 		);
 
 		Ok(())
 	}
 }
 
-/// Extracts expected relay chain state from the call.
-fn extract_expected_relay_chain_state<R, GI>(call: &CallOf<R>) -> Option<ExpectedRelayChainState>
+impl<R, GI, PI, MI, UB, BE, PID, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR>
+	RefundRelayerForMessagesFromParachain<
+		R,
+		GI,
+		PI,
+		MI,
+		UB,
+		BE,
+		PID,
+		LID,
+		FEE,
+		ELG,
+		AIC,
+		CFG,
+		REF,
+		MDL,
+		MBR,
+		SHD,
+		IMM,
+		FCR,
+	>
 where
-	R: GrandpaConfig<GI>,
-	GI: 'static,
-	<R as GrandpaConfig<GI>>::BridgedChain: Chain<BlockNumber = RelayBlockNumber>,
-	CallOf<R>: IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>,
+	R: frame_system::Config,
+	Self: SignedExtension<
+		AccountId = R::AccountId,
+		Call = CallOf<R>,
+		Pre = Option<PreDispatchData<R::AccountId>>,
+	>,
 {
-	if let Some(GrandpaCall::<R, GI>::submit_finality_proof { ref finality_target, .. }) =
-		call.is_sub_type()
-	{
-		return Some(ExpectedRelayChainState { best_block_number: *finality_target.number() })
+	/// Refunds relayer for the bridge call that has been dispatched without running signed
+	/// extensions.
+	///
+	/// Calls that are dispatched by e.g. the `pallet_scheduler` are bypassing signed extensions,
+	/// so the relayer is never refunded for such calls. Runtimes that want to refund them, may
+	/// call `prepare_refund` with the relayer account right before the call is dispatched and
+	/// then pass its result to this function, along with dispatch results. The refund is computed
+	/// as if the call has been submitted in the transaction of `len` bytes.
+	pub fn refund_dispatched_call(
+		pre_dispatch_data: PreDispatchData<R::AccountId>,
+		info: &DispatchInfoOf<CallOf<R>>,
+		post_info: &PostDispatchInfoOf<CallOf<R>>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		Self::post_dispatch(Some(Some(pre_dispatch_data)), info, post_info, len, result)
 	}
-	None
 }
 
-/// Extracts expected parachain state from the call.
-fn extract_expected_parachain_state<R, GI, PI, PID>(
-	call: &CallOf<R>,
-) -> Option<ExpectedParachainState>
-where
-	R: GrandpaConfig<GI> + ParachainsConfig<PI, BridgesGrandpaPalletInstance = GI>,
-	GI: 'static,
-	PI: 'static,
-	PID: Get<u32>,
-	<R as GrandpaConfig<GI>>::BridgedChain:
-		Chain<BlockNumber = RelayBlockNumber, Hash = RelayBlockHash, Hasher = RelayBlockHasher>,
-	CallOf<R>: IsSubType<CallableCallFor<ParachainsPallet<R, PI>, R>>,
+/// Refund extension of the single bridge, which may be used as an element of the
+/// `RefundableBridges` tuple.
+pub trait RefundableBridge:
+	Default + SignedExtension<Pre = Option<PreDispatchData<<Self as SignedExtension>::AccountId>>>
 {
-	if let Some(ParachainsCall::<R, PI>::submit_parachain_heads {
-		ref at_relay_block,
-		ref parachains,
-		..
-	}) = call.is_sub_type()
-	{
-		if parachains.len() != 1 || parachains[0].0 != ParaId(PID::get()) {
-			return None
-		}
-
-		return Some(ExpectedParachainState { at_relay_block_number: at_relay_block.0 })
-	}
-	None
+	/// Returns true if the call is the transaction of this bridge. Only the extension of the
+	/// bridge that recognizes the transaction is used to `pre_dispatch` it.
+	///
+	/// Must not read runtime storage.
+	fn is_bridge_call(call: &Self::Call) -> bool;
 }
 
-/// Extracts messages state from the call.
-fn extract_messages_state<R, GI, MI, LID>(call: &CallOf<R>) -> Option<MessagesState>
+impl<R, GI, PI, MI, UB, BE, PID, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR>
+	RefundableBridge
+	for RefundRelayerForMessagesFromParachain<
+		R,
+		GI,
+		PI,
+		MI,
+		UB,
+		BE,
+		PID,
+		LID,
+		FEE,
+		ELG,
+		AIC,
+		CFG,
+		REF,
+		MDL,
+		MBR,
+		SHD,
+		IMM,
+		FCR,
+	>
 where
-	R: GrandpaConfig<GI> + MessagesConfig<MI>,
-	GI: 'static,
-	MI: 'static,
-	LID: Get<LaneId>,
-	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	R: 'static
+		+ Send
+		+ Sync
+		+ frame_system::Config
+		+ GrandpaConfig<GI>
+		+ ParachainsConfig<PI, RelayChainHeaders = GrandpaChainHeaders<R, GI>>
+		+ MessagesConfig<MI>
+		+ RelayersConfig,
+	GI: 'static + Send + Sync,
+	PI: 'static + Send + Sync,
+	MI: 'static + Send + Sync,
+	UB: 'static + Send + Sync + BatchCallUnpacker<CallOf<R>>,
+	BE: 'static + Send + Sync + BridgeRuntimeFilterCall<CallOf<R>>,
+	PID: 'static + Send + Sync + Get<Vec<u32>>,
+	LID: 'static + Send + Sync + Get<LaneId>,
+	FEE: 'static + Send + Sync + TransactionFeeCalculation,
+	FEE::Balance: UniqueSaturatedInto<<R as RelayersConfig>::Reward>,
+	ELG: 'static + Send + Sync + RefundEligibility<R::AccountId, <R as RelayersConfig>::Reward>,
+	AIC: 'static
+		+ Send
+		+ Sync
+		+ RelayerAccountIdConverter<<R as MessagesConfig<MI>>::InboundRelayer, R::AccountId>,
+	CFG: 'static + Send + Sync + RefundConfig,
+	REF: 'static + Send + Sync + OnRefund<R::AccountId, <R as RelayersConfig>::Reward>,
+	MDL: 'static + Send + Sync + Get<u32>,
+	MBR: 'static + Send + Sync + Get<<R as frame_system::Config>::BlockNumber>,
+	SHD: 'static + Send + Sync + Get<bool>,
+	IMM: 'static + Send + Sync + Get<bool>,
+	FCR: 'static + Send + Sync + Get<Option<FixedU128>>,
+	<R as RelayersConfig>::Reward: FixedPointOperand,
+	<R as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	CallOf<R>: GetDispatchInfo
+		+ IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>
+		+ IsSubType<CallableCallFor<ParachainsPallet<R, PI>, R>>
+		+ IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as GrandpaConfig<GI>>::BridgedChain:
+		Chain<BlockNumber = RelayBlockNumber, Hash = RelayBlockHash, Hasher = RelayBlockHasher>,
 	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
 		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
 	>,
+	<R as MessagesConfig<MI>>::TargetHeaderChain: TargetHeaderChain<
+		<R as MessagesConfig<MI>>::OutboundPayload,
+		<R as frame_system::Config>::AccountId,
+		MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<R, GI>>>,
+	>,
 {
-	if let Some(MessagesCall::<R, MI>::receive_messages_proof { ref proof, .. }) =
-		call.is_sub_type()
-	{
-		if LID::get() != proof.lane {
-			return None
-		}
-
-		return Some(MessagesState {
-			best_nonce: MessagesPallet::<R, MI>::inbound_lane_data(proof.lane)
-				.last_delivered_nonce(),
-		})
+	fn is_bridge_call(call: &CallOf<R>) -> bool {
+		Self::classify_call_structure(call).is_some() ||
+			bundled_messages_proof::<R, GI, MI, UB, LID, CFG>(call).is_some()
 	}
-	None
 }
 
-/// Returns relay chain state that we are interested in.
-fn relay_chain_state<R, GI>() -> Option<ExpectedRelayChainState>
-where
-	R: GrandpaConfig<GI>,
-	GI: 'static,
-	<R as GrandpaConfig<GI>>::BridgedChain: Chain<BlockNumber = RelayBlockNumber>,
-{
-	GrandpaPallet::<R, GI>::best_finalized_number()
-		.map(|best_block_number| ExpectedRelayChainState { best_block_number })
+/// Set of bridges, which transactions are refunded by the `RefundRelayerForMessagesFromParachains`
+/// extension.
+///
+/// It is implemented for tuples (up to 8 elements) of refund extensions of single bridges (e.g.
+/// `RefundRelayerForMessagesFromParachain`), implementing the `RefundableBridge` trait. Bridges
+/// are identified by their index in the tuple.
+pub trait RefundableBridges<AccountId, Call: Dispatchable> {
+	/// Validates transaction using extensions of all bridges.
+	fn validate(
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> TransactionValidity;
+
+	/// Runs `pre_dispatch` of extension of the first bridge that recognizes the transaction.
+	/// Returns index of this bridge, along with its pre-dispatch data. Transactions that are
+	/// not recognized by any bridge are ignored.
+	fn pre_dispatch(
+		who: &AccountId,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Option<(u32, PreDispatchData<AccountId>)>, TransactionValidityError>;
+
+	/// Runs `post_dispatch` of extension of the bridge with given index.
+	fn post_dispatch(
+		bridge_index: u32,
+		pre_dispatch_data: PreDispatchData<AccountId>,
+		info: &DispatchInfoOf<Call>,
+		post_info: &PostDispatchInfoOf<Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError>;
 }
 
-/// Returns parachain state that we are interested in.
-fn parachain_state<R, PI, PID>() -> Option<ExpectedParachainState>
-where
-	R: ParachainsConfig<PI>,
-	PI: 'static,
-	PID: Get<u32>,
-{
-	ParachainsPallet::<R, PI>::best_parachain_info(ParaId(PID::get())).map(|para_info| {
-		ExpectedParachainState {
-			at_relay_block_number: para_info.best_head_hash.at_relay_block_number,
+macro_rules! impl_refundable_bridges_for_tuple {
+	($($index:tt => $bridge:ident),+) => {
+		impl<AccountId, Call, $($bridge),+> RefundableBridges<AccountId, Call> for ($($bridge,)+)
+		where
+			Call: Dispatchable,
+			$(
+				$bridge: RefundableBridge<AccountId = AccountId, Call = Call>,
+			)+
+		{
+			fn validate(
+				who: &AccountId,
+				call: &Call,
+				info: &DispatchInfoOf<Call>,
+				len: usize,
+			) -> TransactionValidity {
+				let valid = ValidTransaction::default();
+				$(
+					let valid = valid.combine_with($bridge::default().validate(who, call, info, len)?);
+				)+
+				Ok(valid)
+			}
+
+			fn pre_dispatch(
+				who: &AccountId,
+				call: &Call,
+				info: &DispatchInfoOf<Call>,
+				len: usize,
+			) -> Result<Option<(u32, PreDispatchData<AccountId>)>, TransactionValidityError> {
+				$(
+					if $bridge::is_bridge_call(call) {
+						return Ok($bridge::default()
+							.pre_dispatch(who, call, info, len)?
+							.map(|data| ($index, data)))
+					}
+				)+
+				Ok(None)
+			}
+
+			fn post_dispatch(
+				bridge_index: u32,
+				pre_dispatch_data: PreDispatchData<AccountId>,
+				info: &DispatchInfoOf<Call>,
+				post_info: &PostDispatchInfoOf<Call>,
+				len: usize,
+				result: &DispatchResult,
+			) -> Result<(), TransactionValidityError> {
+				match bridge_index {
+					$(
+						$index => $bridge::post_dispatch(
+							Some(Some(pre_dispatch_data)),
+							info,
+							post_info,
+							len,
+							result,
+						),
+					)+
+					_ => Ok(()),
+				}
+			}
 		}
-	})
+	};
 }
 
-/// Returns messages state that we are interested in.
-fn messages_state<R, MI, LID>() -> Option<MessagesState>
+impl_refundable_bridges_for_tuple!(0 => B0);
+impl_refundable_bridges_for_tuple!(0 => B0, 1 => B1);
+impl_refundable_bridges_for_tuple!(0 => B0, 1 => B1, 2 => B2);
+impl_refundable_bridges_for_tuple!(0 => B0, 1 => B1, 2 => B2, 3 => B3);
+impl_refundable_bridges_for_tuple!(0 => B0, 1 => B1, 2 => B2, 3 => B3, 4 => B4);
+impl_refundable_bridges_for_tuple!(0 => B0, 1 => B1, 2 => B2, 3 => B3, 4 => B4, 5 => B5);
+impl_refundable_bridges_for_tuple!(0 => B0, 1 => B1, 2 => B2, 3 => B3, 4 => B4, 5 => B5, 6 => B6);
+impl_refundable_bridges_for_tuple!(
+	0 => B0, 1 => B1, 2 => B2, 3 => B3, 4 => B4, 5 => B5, 6 => B6, 7 => B7
+);
+
+/// Signed extension that refunds relayers for transactions of several bridges.
+///
+/// Runtimes that are bridging with several chains (or with several parachains) may use it instead
+/// of adding separate refund extension for every bridge. Every bridge is configured using its own
+/// refund extension (e.g. `RefundRelayerForMessagesFromParachain`), so it has its own lane,
+/// parachains, GRANDPA instance and refund settings. The `B` parameter is a tuple of such
+/// extensions. Transaction is refunded by the first bridge that recognizes it.
+#[derive(
+	CloneNoBound,
+	Decode,
+	DefaultNoBound,
+	Encode,
+	EqNoBound,
+	PartialEqNoBound,
+	RuntimeDebugNoBound,
+	TypeInfo,
+)]
+#[scale_info(skip_type_params(RT, B))]
+pub struct RefundRelayerForMessagesFromParachains<RT, B>(PhantomData<(RT, B)>);
+
+impl<R, B> SignedExtension for RefundRelayerForMessagesFromParachains<R, B>
 where
-	R: MessagesConfig<MI>,
-	MI: 'static,
-	LID: Get<LaneId>,
+	R: 'static + Send + Sync + frame_system::Config,
+	B: 'static + Send + Sync + RefundableBridges<R::AccountId, CallOf<R>>,
+	<R as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
 {
-	Some(MessagesState {
-		best_nonce: MessagesPallet::<R, MI>::inbound_lane_data(LID::get()).last_delivered_nonce(),
-	})
-}
+	const IDENTIFIER: &'static str = "RefundRelayerForMessagesFromParachains";
+	type AccountId = R::AccountId;
+	type Call = CallOf<R>;
+	type AdditionalSigned = ();
+	type Pre = Option<(u32, PreDispatchData<R::AccountId>)>;
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-	use crate::{messages::target::FromBridgedChainMessagesProof, mock::*};
-	use bp_messages::InboundLaneData;
-	use bp_parachains::{BestParaHeadHash, ParaInfo};
-	use bp_polkadot_core::parachains::ParaHeadsProof;
-	use bp_runtime::HeaderId;
-	use bp_test_utils::make_default_justification;
-	use frame_support::{assert_storage_noop, parameter_types, weights::Weight};
-	use sp_runtime::{transaction_validity::InvalidTransaction, DispatchError};
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
 
-	parameter_types! {
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		B::validate(who, call, info, len)
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		B::pre_dispatch(who, call, info, len)
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		match pre {
+			Some(Some((bridge_index, pre_dispatch_data))) =>
+				B::post_dispatch(bridge_index, pre_dispatch_data, info, post_info, len, result),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Signed extension that refunds relayer for new messages coming from the standalone (GRANDPA)
+/// chain.
+///
+/// It is a sibling of the `RefundRelayerForMessagesFromParachain`, which may be used by bridges
+/// where messages are coming directly from the chain, which finality is tracked by the GRANDPA
+/// pallet. Refunds relayer for successful relay chain finality delivery if it comes in batch
+/// with message delivery transaction. Standalone messages delivery and delivery confirmation
+/// transactions are refunded too.
+///
+/// Batches are unpacked the same way as in the `RefundRelayerForMessagesFromParachain` - using
+/// the `UB` parameter and supporting non-atomic and nested batches (if enabled in `CFG`). The
+/// `AIC`, `ELG`, `FEE`, `REF`, `MDL`, `MBR`, `SHD`, `IMM` and `FCR` parameters have the same
+/// meaning too. Differences are:
+///
+/// - only a single relay chain header may be bundled with the message delivery call, so the
+///   `RefundConfig::MAX_RELAY_HEADERS_PER_BATCH` is not used;
+/// - there are no parachains, so the `RefundConfig::REFUND_PARACHAIN_FINALITY` is not used;
+/// - relayers of confirmed messages are not rewarded, so the
+///   `RefundConfig::DELIVERY_REWARD_PER_CONFIRMED_MESSAGE` is not used.
+#[derive(
+	CloneNoBound,
+	Decode,
+	DefaultNoBound,
+	Encode,
+	EqNoBound,
+	PartialEqNoBound,
+	RuntimeDebugNoBound,
+	TypeInfo,
+)]
+#[scale_info(skip_type_params(
+	RT, GI, MI, UB, BE, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR
+))]
+#[allow(clippy::type_complexity)]
+pub struct RefundRelayerForMessagesDeliveryFromRelayChain<
+	RT,
+	GI,
+	MI,
+	UB,
+	BE,
+	LID,
+	FEE,
+	ELG,
+	AIC,
+	CFG,
+	REF = (),
+	MDL = (),
+	MBR = (),
+	SHD = (),
+	IMM = (),
+	FCR = (),
+>(PhantomData<(RT, GI, MI, UB, BE, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR)>);
+
+impl<R, GI, MI, UB, BE, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR>
+	RefundRelayerForMessagesDeliveryFromRelayChain<
+		R,
+		GI,
+		MI,
+		UB,
+		BE,
+		LID,
+		FEE,
+		ELG,
+		AIC,
+		CFG,
+		REF,
+		MDL,
+		MBR,
+		SHD,
+		IMM,
+		FCR,
+	>
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI>,
+	GI: 'static,
+	MI: 'static,
+	UB: BatchCallUnpacker<CallOf<R>>,
+	LID: Get<LaneId>,
+	FEE: TransactionFeeCalculation,
+	AIC: RelayerAccountIdConverter<<R as MessagesConfig<MI>>::InboundRelayer, R::AccountId>,
+	CFG: RefundConfig,
+	CallOf<R>: GetDispatchInfo
+		+ IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>
+		+ IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as GrandpaConfig<GI>>::BridgedChain: Chain<BlockNumber = RelayBlockNumber>,
+	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
+		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+	<R as MessagesConfig<MI>>::TargetHeaderChain: TargetHeaderChain<
+		<R as MessagesConfig<MI>>::OutboundPayload,
+		<R as frame_system::Config>::AccountId,
+		MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	/// Checks if the call matches one of transaction types that we support.
+	///
+	/// Returns type of the call, or the reason why it isn't supported by the extension.
+	pub fn parse_call_type(call: &CallOf<R>) -> Result<CallType, UnsupportedReason> {
+		if let Some(calls) = unpack_bridge_batch::<_, UB, CFG>(call) {
+			return match calls.as_slice() {
+				[relay_header_call, delivery_call] => {
+					let expected_relay_chain_state =
+						extract_expected_relay_chain_state::<R, GI>(relay_header_call)
+							.ok_or(UnsupportedReason::UnknownCall)?;
+					check_messages_delivery_call::<R, GI, MI, LID>(delivery_call)?;
+					Ok(CallType::RelayFinalityAndDelivery(
+						expected_relay_chain_state,
+						messages_state::<R, MI, LID>(),
+					))
+				},
+				_ => Err(UnsupportedReason::UnknownCall),
+			}
+		}
+
+		if CFG::REFUND_RELAY_FINALITY {
+			if let Some(expected_relay_chain_state) =
+				extract_expected_relay_chain_state::<R, GI>(call)
+			{
+				return Ok(CallType::RelayFinality(expected_relay_chain_state))
+			}
+		}
+
+		match check_messages_delivery_call::<R, GI, MI, LID>(call) {
+			Ok(()) => Ok(CallType::Delivery(messages_state::<R, MI, LID>())),
+			Err(UnsupportedReason::UnknownCall) => {
+				check_messages_confirmation_call::<R, GI, MI, LID>(call)?;
+				Ok(CallType::DeliveryConfirmation(confirmation_state::<R, MI, LID>()))
+			},
+			Err(reason) => Err(reason),
+		}
+	}
+
+	/// Prepares data that is passed from `pre_dispatch` to `post_dispatch`.
+	fn prepare_pre_dispatch_data(
+		relayer: R::AccountId,
+		call: &CallOf<R>,
+	) -> Result<PreDispatchData<R::AccountId>, UnsupportedReason> {
+		let call_type = Self::parse_call_type(call)?;
+
+		// check that the delivery transaction is submitted by the relayer, declared in the call
+		if let Some(relayer_id_at_bridged_chain) =
+			bundled_relayer_id_at_bridged_chain::<R, GI, MI, UB, LID, CFG>(call)
+		{
+			match AIC::convert(relayer_id_at_bridged_chain) {
+				Some(expected_relayer) if expected_relayer != relayer =>
+					return Err(UnsupportedReason::RelayerIdMismatch),
+				_ => (),
+			}
+		}
+
+		// remember weight of the delivery call, bundled into the non-atomic batch
+		let non_atomic_batch_delivery_weight = match call_type.pre_dispatch_messages_state() {
+			Some(_) if UB::unpack_batch_all(call).is_none() => UB::unpack_non_atomic_batch(call)
+				.and_then(|calls| calls.last())
+				.map(|delivery_call| delivery_call.get_dispatch_info().weight),
+			_ => None,
+		};
+
+		// remember benchmarked weight of bridge calls, if we only refund bridge calls
+		let bridge_calls_weight = if FEE::REFUND_BRIDGE_CALLS_ONLY {
+			Some(match unpack_bridge_batch::<_, UB, CFG>(call) {
+				Some(calls) => calls
+					.iter()
+					.filter(|call| is_relay_header_or_delivery_call::<R, GI, MI>(call))
+					.fold(Weight::zero(), |weight, call| {
+						weight.saturating_add(call.get_dispatch_info().weight)
+					}),
+				None => call.get_dispatch_info().weight,
+			})
+		} else {
+			None
+		};
+
+		Ok(PreDispatchData {
+			relayer,
+			call_type,
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight,
+			bridge_calls_weight,
+		})
+	}
+}
+
+impl<R, GI, MI, UB, BE, LID, FEE, ELG, AIC, CFG, REF, MDL, MBR, SHD, IMM, FCR> SignedExtension
+	for RefundRelayerForMessagesDeliveryFromRelayChain<
+		R,
+		GI,
+		MI,
+		UB,
+		BE,
+		LID,
+		FEE,
+		ELG,
+		AIC,
+		CFG,
+		REF,
+		MDL,
+		MBR,
+		SHD,
+		IMM,
+		FCR,
+	>
+where
+	R: 'static
+		+ Send
+		+ Sync
+		+ frame_system::Config
+		+ GrandpaConfig<GI>
+		+ MessagesConfig<MI>
+		+ RelayersConfig,
+	GI: 'static + Send + Sync,
+	MI: 'static + Send + Sync,
+	UB: 'static + Send + Sync + BatchCallUnpacker<CallOf<R>>,
+	BE: 'static + Send + Sync + BridgeRuntimeFilterCall<CallOf<R>>,
+	LID: 'static + Send + Sync + Get<LaneId>,
+	FEE: 'static + Send + Sync + TransactionFeeCalculation,
+	FEE::Balance: UniqueSaturatedInto<<R as RelayersConfig>::Reward>,
+	ELG: 'static + Send + Sync + RefundEligibility<R::AccountId, <R as RelayersConfig>::Reward>,
+	AIC: 'static
+		+ Send
+		+ Sync
+		+ RelayerAccountIdConverter<<R as MessagesConfig<MI>>::InboundRelayer, R::AccountId>,
+	CFG: 'static + Send + Sync + RefundConfig,
+	REF: 'static + Send + Sync + OnRefund<R::AccountId, <R as RelayersConfig>::Reward>,
+	MDL: 'static + Send + Sync + Get<u32>,
+	MBR: 'static + Send + Sync + Get<<R as frame_system::Config>::BlockNumber>,
+	SHD: 'static + Send + Sync + Get<bool>,
+	IMM: 'static + Send + Sync + Get<bool>,
+	FCR: 'static + Send + Sync + Get<Option<FixedU128>>,
+	<R as RelayersConfig>::Reward: FixedPointOperand,
+	<R as frame_system::Config>::RuntimeCall:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	CallOf<R>: GetDispatchInfo
+		+ IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>
+		+ IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as GrandpaConfig<GI>>::BridgedChain: Chain<BlockNumber = RelayBlockNumber>,
+	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
+		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+	<R as MessagesConfig<MI>>::TargetHeaderChain: TargetHeaderChain<
+		<R as MessagesConfig<MI>>::OutboundPayload,
+		<R as frame_system::Config>::AccountId,
+		MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	const IDENTIFIER: &'static str = "RefundRelayerForMessagesDeliveryFromRelayChain";
+	type AccountId = R::AccountId;
+	type Call = CallOf<R>;
+	type AdditionalSigned = ();
+	type Pre = Option<PreDispatchData<R::AccountId>>;
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let valid = validate_delivery_transaction::<R, GI, MI, UB, BE, (), LID, CFG, MDL>(
+			Self::IDENTIFIER,
+			who,
+			call,
+			len,
+			is_relay_header_or_delivery_call::<R, GI, MI>,
+		)?;
+
+		// boost priority of bridge transactions, submitted by registered relayers
+		let priority = if CFG::REGISTERED_RELAYER_PRIORITY_BOOST != 0 &&
+			Self::parse_call_type(call).is_ok() &&
+			RelayersPallet::<R>::is_registration_active(who)
+		{
+			valid.priority.saturating_add(CFG::REGISTERED_RELAYER_PRIORITY_BOOST)
+		} else {
+			valid.priority
+		};
+
+		Ok(ValidTransaction { priority, ..valid })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		// reject batch transactions with obsolete headers
+		self.validate(who, call, info, len)?;
+		register_bridge_batch_parse_weight::<R, UB, CFG>(call, info);
+
+		// now try to check if tx matches one of types we support
+		let pre_dispatch_data = match Self::prepare_pre_dispatch_data(who.clone(), call) {
+			Ok(pre_dispatch_data) => pre_dispatch_data,
+			Err(UnsupportedReason::WrongLane(lane)) => {
+				log::debug!(
+					target: "runtime::bridge",
+					"{} ignores bridge transaction \
+						that is delivering messages over lane {:?}",
+					BridgeLogName::<(), LID, CFG>(Self::IDENTIFIER, PhantomData),
+					lane,
+				);
+				return Ok(None)
+			},
+			Err(UnsupportedReason::RelayerIdMismatch) => {
+				log::trace!(
+					target: "runtime::bridge",
+					"{} won't refund delivery \
+						transaction: relayer {:?} is not the relayer, declared in the call",
+					BridgeLogName::<(), LID, CFG>(Self::IDENTIFIER, PhantomData),
+					who,
+				);
+				note_skipped_refund::<R, LID, CFG>(who, RefundSkipReason::RelayerIdMismatch);
+				return Ok(None)
+			},
+			Err(UnsupportedReason::UnknownCall) => {
+				// we don't know if the call has been targeting our bridge, unless it is
+				// delivering messages over our lane
+				if bundled_messages_proof::<R, GI, MI, UB, LID, CFG>(call).is_some() {
+					note_skipped_refund::<R, LID, CFG>(who, RefundSkipReason::UnrecognizedCall);
+				}
+				return Ok(None)
+			},
+		};
+
+		log::trace!(
+			target: "runtime::bridge",
+			"{} parsed bridge transaction in \
+				pre-dispatch: {:?}",
+			BridgeLogName::<(), LID, CFG>(Self::IDENTIFIER, PhantomData),
+			pre_dispatch_data.call_type,
+		);
+		Ok(Some(pre_dispatch_data))
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		// we never refund anything if it is not bridge transaction or if it is a bridge
+		// transaction that we do not support here
+		let pre_dispatch_data = match pre {
+			Some(Some(pre_dispatch_data)) => pre_dispatch_data,
+			_ => return Ok(()),
+		};
+		let PreDispatchData { ref relayer, call_type, non_atomic_batch_delivery_weight, .. } =
+			pre_dispatch_data;
+
+		// we never refund anything if transaction has failed
+		if result.is_err() {
+			slash_misbehaving_relayer::<R, (), LID, CFG>(Self::IDENTIFIER, relayer);
+			note_skipped_refund::<R, LID, CFG>(relayer, RefundSkipReason::DispatchFailed);
+			return Ok(())
+		}
+
+		// finality call of the non-atomic batch may fail without failing the whole transaction.
+		// In this case we only refund relayer for the delivery call (if it has succeeded)
+		let mut refund_delivery_only = false;
+
+		// check if relay chain state has been updated
+		if let CallType::RelayFinalityAndDelivery(expected_relay_chain_state, _) |
+		CallType::RelayFinality(expected_relay_chain_state) = call_type
+		{
+			if relay_chain_state::<R, GI>() != Some(expected_relay_chain_state) {
+				// we only refund relayer if all calls have updated chain state
+				if non_atomic_batch_delivery_weight.is_none() {
+					note_no_op_transaction::<R, (), LID, CFG>(Self::IDENTIFIER, relayer);
+					slash_misbehaving_relayer::<R, (), LID, CFG>(Self::IDENTIFIER, relayer);
+					note_skipped_refund::<R, LID, CFG>(relayer, RefundSkipReason::StateNotAdvanced);
+					return Ok(())
+				}
+				refund_delivery_only = true;
+			}
+		}
+
+		refund_relayer::<R, MI, (), LID, FEE, ELG, CFG, REF, MBR, SHD, IMM, FCR>(
+			Self::IDENTIFIER,
+			&pre_dispatch_data,
+			refund_delivery_only,
+			info,
+			post_info,
+			len,
+		);
+
+		Ok(())
+	}
+}
+
+/// Validates transaction that is delivering messages over the `LID` lane. Other transactions
+/// are always valid.
+///
+/// Calls of the delivery batch, recognized by the `is_bridge_call`, are checked using the `BE`
+/// filter.
+fn validate_delivery_transaction<R, GI, MI, UB, BE, PID, LID, CFG, MDL>(
+	extension: &'static str,
+	who: &R::AccountId,
+	call: &CallOf<R>,
+	len: usize,
+	is_bridge_call: fn(&CallOf<R>) -> bool,
+) -> TransactionValidity
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI> + RelayersConfig,
+	GI: 'static,
+	MI: 'static,
+	UB: BatchCallUnpacker<CallOf<R>>,
+	BE: BridgeRuntimeFilterCall<CallOf<R>>,
+	PID: Get<Vec<u32>>,
+	LID: Get<LaneId>,
+	CFG: RefundConfig,
+	MDL: Get<u32>,
+	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
+		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	// the extension only checks transactions that are delivering messages over its lane
+	let bundled_messages_proof = bundled_messages_proof::<R, GI, MI, UB, LID, CFG>(call);
+	let bundled_messages_count = bundled_messages_proof.map(|(_, messages_count)| messages_count);
+
+	// reject message delivery transactions if the messages pallet or our lane is halted -
+	// they'll fail anyway. Operating modes are checked after the call, so other transactions
+	// don't need to read the storage
+	if bundled_messages_count.is_some() &&
+		(MessagesPallet::<R, MI>::is_halted() ||
+			MessagesPallet::<R, MI>::lane_operating_mode(LID::get()).is_halted())
+	{
+		log::trace!(
+			target: "runtime::bridge",
+			"{} has rejected delivery transaction: \
+				messages pallet or lane is halted",
+			BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+		);
+
+		return InvalidTransaction::Call.into()
+	}
+
+	// reject message delivery transactions of relayers that are not allowed to serve the lane
+	if bundled_messages_count.is_some() &&
+		!RelayersPallet::<R>::is_relayer_allowlisted(LID::get(), who)
+	{
+		log::trace!(
+			target: "runtime::bridge",
+			"{} has rejected delivery transaction: \
+				relayer {:?} is not in the lane allowlist",
+			BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+			who,
+		);
+
+		return InvalidTransaction::Custom(NOT_ALLOWLISTED_RELAYER).into()
+	}
+
+	// reject message delivery transactions that are delivering recently refunded messages
+	if let Some((proof, _)) = bundled_messages_proof {
+		if CFG::REPLAY_GUARD_BLOCKS != 0 &&
+			RelayersPallet::<R>::is_delivery_recently_refunded(
+				LID::get(),
+				proof.nonces_end,
+				CFG::REPLAY_GUARD_BLOCKS.into(),
+			) {
+			log::trace!(
+				target: "runtime::bridge",
+				"{} has rejected delivery transaction: \
+					messages up to {} have been delivered recently",
+				BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+				proof.nonces_end,
+			);
+
+			return InvalidTransaction::Stale.into()
+		}
+	}
+
+	// reject oversized batch transactions that are delivering messages
+	if let (Some(calls), Some(_)) =
+		(unpack_bridge_batch::<_, UB, CFG>(call), bundled_messages_count)
+	{
+		if calls.len() > CFG::MAX_DELIVERY_BATCH_CALLS as usize {
+			log::trace!(
+				target: "runtime::bridge",
+				"{} has rejected \
+					delivery batch of {} calls: maximal number of calls is {}",
+				BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+				calls.len(),
+				CFG::MAX_DELIVERY_BATCH_CALLS,
+			);
+
+			return InvalidTransaction::Custom(OVERSIZED_DELIVERY_BATCH).into()
+		}
+
+		// reject batch transactions with obsolete headers. Batches that are targeting other
+		// bridges (or aren't bridge transactions at all) are not checked here, because
+		// obsolete checks of our bridge may be wrong for them. Runtimes that are bridging with
+		// several chains are using different extensions (each with its own `BE`) to check
+		// batches of different bridges. Calls of the batch, that are not recognized by the
+		// `is_bridge_call`, are never checked
+		for nested_call in calls {
+			if !is_bridge_call(nested_call) {
+				continue
+			}
+
+			BE::validate(nested_call)?;
+		}
+	}
+
+	// reject message delivery transactions that may have been padded to inflate the refund
+	let max_len_per_message = MDL::get();
+	if let Some(messages_count) = bundled_messages_count.filter(|_| max_len_per_message != 0) {
+		let max_len = max_len_per_message.saturating_mul(messages_count.max(1));
+		if len > max_len as usize {
+			log::trace!(
+				target: "runtime::bridge",
+				"{} has rejected \
+					delivery transaction of {} messages: its length {} is larger than {}",
+				BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+				messages_count,
+				len,
+				max_len,
+			);
+
+			return InvalidTransaction::Custom(OVERSIZED_DELIVERY_TRANSACTION).into()
+		}
+	}
+
+	// boost priority of message delivery transactions
+	let priority = bundled_messages_count
+		.map(|messages_count| {
+			CFG::PRIORITY_BOOST_PER_MESSAGE.saturating_mul(messages_count as TransactionPriority)
+		})
+		.unwrap_or(0);
+
+	Ok(ValidTransaction { priority, ..Default::default() })
+}
+
+/// Refunds relayer for the bridge transaction that has updated headers of bridged chains (if it
+/// was supposed to do that).
+///
+/// Checks that the transaction has also advanced the `LID` lane state, updates relayer
+/// statistics and registers relayer reward in the relayers pallet.
+fn refund_relayer<R, MI, PID, LID, FEE, ELG, CFG, REF, MBR, SHD, IMM, FCR>(
+	extension: &'static str,
+	pre_dispatch_data: &PreDispatchData<R::AccountId>,
+	refund_delivery_only: bool,
+	info: &DispatchInfo,
+	post_info: &PostDispatchInfo,
+	len: usize,
+) where
+	R: MessagesConfig<MI> + RelayersConfig,
+	MI: 'static,
+	PID: Get<Vec<u32>>,
+	LID: Get<LaneId>,
+	FEE: TransactionFeeCalculation,
+	FEE::Balance: UniqueSaturatedInto<<R as RelayersConfig>::Reward>,
+	ELG: RefundEligibility<R::AccountId, <R as RelayersConfig>::Reward>,
+	CFG: RefundConfig,
+	REF: OnRefund<R::AccountId, <R as RelayersConfig>::Reward>,
+	MBR: Get<<R as frame_system::Config>::BlockNumber>,
+	SHD: Get<bool>,
+	IMM: Get<bool>,
+	FCR: Get<Option<FixedU128>>,
+	<R as RelayersConfig>::Reward: FixedPointOperand,
+{
+	let PreDispatchData {
+		ref relayer,
+		call_type,
+		ref confirmed_messages_relayers,
+		non_atomic_batch_delivery_weight,
+		bridge_calls_weight,
+	} = *pre_dispatch_data;
+
+	// check if messages have been delivered
+	if let Some(pre_dispatch_messages_state) = call_type.pre_dispatch_messages_state() {
+		let actual_messages_state = messages_state::<R, MI, LID>();
+		if actual_messages_state == pre_dispatch_messages_state {
+			// we only refund relayer if all calls have updated chain state
+			note_no_op_transaction::<R, PID, LID, CFG>(extension, relayer);
+			slash_misbehaving_relayer::<R, PID, LID, CFG>(extension, relayer);
+			note_skipped_refund::<R, LID, CFG>(relayer, RefundSkipReason::NoNewMessages);
+			return
+		}
+	}
+
+	// check if messages delivery has been confirmed
+	if let CallType::DeliveryConfirmation(pre_dispatch_confirmation_state) = call_type {
+		let actual_confirmation_state = confirmation_state::<R, MI, LID>();
+		if actual_confirmation_state == pre_dispatch_confirmation_state {
+			// we only refund relayer if he has confirmed at least one message
+			note_no_op_transaction::<R, PID, LID, CFG>(extension, relayer);
+			slash_misbehaving_relayer::<R, PID, LID, CFG>(extension, relayer);
+			note_skipped_refund::<R, LID, CFG>(relayer, RefundSkipReason::NoNewMessages);
+			return
+		}
+	}
+
+	// update relayer statistics: count new delivered messages and new submitted headers
+	if CFG::COLLECT_RELAYER_STATISTICS {
+		let delivered_messages = call_type
+			.pre_dispatch_messages_state()
+			.map(|pre_dispatch_messages_state| {
+				messages_state::<R, MI, LID>()
+					.best_nonce
+					.saturating_sub(pre_dispatch_messages_state.best_nonce)
+			})
+			.unwrap_or(0);
+		let submitted_headers = if refund_delivery_only {
+			0
+		} else {
+			match call_type {
+				CallType::AllFinalityAndDelivery(..) => 2,
+				CallType::RelayFinalityAndDelivery(..) |
+				CallType::ParachainFinalityAndDelivery(..) |
+				CallType::RelayFinality(..) |
+				CallType::ParachainFinality(..) => 1,
+				_ => 0,
+			}
+		};
+		RelayersPallet::<R>::note_relayer_work(relayer, delivered_messages, submitted_headers);
+	}
+
+	// compute the relayer reward
+	let rewards_account_params = RewardsAccountParams::new(LID::get(), call_type.reward_kind());
+	let reward = match non_atomic_batch_delivery_weight {
+		Some(delivery_weight) if refund_delivery_only => {
+			let delivery_info = DispatchInfo { weight: delivery_weight, ..*info };
+			let delivery_post_info =
+				PostDispatchInfo { actual_weight: None, pays_fee: post_info.pays_fee };
+			compute_reward::<R, FEE>(
+				rewards_account_params,
+				&delivery_info,
+				&delivery_post_info,
+				len,
+				bridge_calls_weight.map(|_| delivery_weight),
+			)
+		},
+		_ => compute_reward::<R, FEE>(
+			rewards_account_params,
+			info,
+			post_info,
+			len,
+			bridge_calls_weight,
+		),
+	};
+	let reward = match reward {
+		Some(reward) => reward,
+		None => {
+			log::trace!(
+				target: "runtime::bridge",
+				"{} has declined to refund {:?}",
+				BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+				relayer,
+			);
+
+			return
+		},
+	};
+
+	// the cooldown is checked before registering any rewards, because the confirmation
+	// relayer may be one of relayers that have delivered confirmed messages
+	let recent_reward_block_number =
+		recent_reward_block_number::<R, MBR>(rewards_account_params, relayer);
+
+	// reward relayers that have delivered confirmed messages
+	if !confirmed_messages_relayers.is_empty() {
+		let delivery_rewards_account_params =
+			RewardsAccountParams::new(LID::get(), RewardKind::MessageDelivery);
+		let delivery_reward: <R as RelayersConfig>::Reward =
+			CFG::DELIVERY_REWARD_PER_CONFIRMED_MESSAGE.into();
+		for (messages_relayer, messages) in confirmed_messages_relayers {
+			let messages_relayer_reward =
+				<R as RelayersConfig>::Reward::unique_saturated_from(*messages)
+					.saturating_mul(delivery_reward);
+			if let Err(e) = register_reward::<R, REF, SHD, IMM>(
+				delivery_rewards_account_params,
+				messages_relayer,
+				messages_relayer_reward,
+			) {
+				log::error!(
+					target: "runtime::bridge",
+					"{} has failed to register {:?} \
+						reward for delivering confirmed messages: {:?}. Error: {:?}",
+					BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+					messages_relayer,
+					messages_relayer_reward,
+					e,
+				);
+			}
+		}
+	}
+
+	// we don't refund relayer if he has been rewarded recently
+	if let Some(last_reward_block_number) = recent_reward_block_number {
+		log::trace!(
+			target: "runtime::bridge",
+			"{} has withheld {:?} \
+				reward: {:?}. Last reward has been registered at block {:?}",
+			BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+			relayer,
+			reward,
+			last_reward_block_number,
+		);
+
+		return
+	}
+
+	// check custom eligibility rules
+	if !ELG::is_eligible(pre_dispatch_data, &reward) {
+		log::trace!(
+			target: "runtime::bridge",
+			"{} has withheld {:?} reward: {:?}. \
+				Relayer is not eligible for refund",
+			BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+			relayer,
+			reward,
+		);
+
+		return
+	}
+
+	// only refund registered relayers, if configured
+	if CFG::REFUND_REGISTERED_RELAYERS_ONLY && !RelayersPallet::<R>::is_registration_active(relayer)
+	{
+		log::trace!(
+			target: "runtime::bridge",
+			"{} has withheld {:?} reward: {:?}. \
+				Relayer is not registered",
+			BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+			relayer,
+			reward,
+		);
+
+		note_skipped_refund::<R, LID, CFG>(relayer, RefundSkipReason::RelayerNotRegistered);
+		return
+	}
+
+	// check the refund budget of the current era
+	let refund_era = refund_era::<R, CFG>();
+	if let Some(refund_era) = refund_era {
+		let total_refunded = RelayersPallet::<R>::total_refunded(LID::get(), refund_era);
+		let refund_budget =
+			<R as RelayersConfig>::Reward::unique_saturated_from(CFG::REFUND_BUDGET_PER_ERA);
+		if total_refunded.saturating_add(reward) > refund_budget {
+			log::trace!(
+				target: "runtime::bridge",
+				"{} has withheld {:?} reward: {:?}. \
+					Refund budget of era {} is exhausted",
+				BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+				relayer,
+				reward,
+				refund_era,
+			);
+
+			RelayersPallet::<R>::note_exhausted_refund_budget(
+				LID::get(),
+				refund_era,
+				relayer,
+				reward,
+			);
+			return
+		}
+	}
+
+	// finally - register reward in relayers pallet. The transaction is not invalidated if
+	// it fails, because it has already been dispatched
+	if let Err(e) = register_reward::<R, REF, SHD, IMM>(rewards_account_params, relayer, reward) {
+		log::error!(
+			target: "runtime::bridge",
+			"{} has failed to register {:?} reward: {:?}. \
+				Error: {:?}",
+			BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+			relayer,
+			reward,
+			e,
+		);
+		if CFG::COLLECT_RELAYER_STATISTICS {
+			RelayersPallet::<R>::note_failed_refund(relayer);
+		}
+
+		return
+	}
+	if !SHD::get() {
+		let bridged_equivalent_reward = FCR::get().map(|rate| rate.saturating_mul_int(reward));
+		RelayersPallet::<R>::note_relayer_refund(
+			LID::get(),
+			relayer,
+			reward,
+			bridged_equivalent_reward,
+		);
+		if CFG::COLLECT_RELAYER_STATISTICS {
+			RelayersPallet::<R>::note_refunded_reward(relayer, reward);
+		}
+		if let Some(refund_era) = refund_era {
+			RelayersPallet::<R>::note_lane_refund(LID::get(), refund_era, reward);
+		}
+	}
+
+	// remember refunded delivery to reject its duplicates
+	if CFG::REPLAY_GUARD_BLOCKS != 0 && call_type.pre_dispatch_messages_state().is_some() {
+		RelayersPallet::<R>::note_refunded_delivery(
+			LID::get(),
+			messages_state::<R, MI, LID>().best_nonce,
+			CFG::REPLAY_GUARD_BLOCKS.into(),
+		);
+	}
+
+	log::trace!(
+		target: "runtime::bridge",
+		"{} has registered {:?} reward: {:?}{}",
+		BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+		relayer,
+		reward,
+		if SHD::get() { " in shadow mode" } else { "" },
+	);
+}
+
+/// Extracts expected relay chain state from the call.
+fn extract_expected_relay_chain_state<R, GI>(call: &CallOf<R>) -> Option<ExpectedRelayChainState>
+where
+	R: GrandpaConfig<GI>,
+	GI: 'static,
+	<R as GrandpaConfig<GI>>::BridgedChain: Chain<BlockNumber = RelayBlockNumber>,
+	CallOf<R>: IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>,
+{
+	if let Some(GrandpaCall::<R, GI>::submit_finality_proof { ref finality_target, .. }) =
+		call.is_sub_type()
+	{
+		return Some(ExpectedRelayChainState { best_block_number: *finality_target.number() })
+	}
+	None
+}
+
+/// Extracts expected parachain state from the call.
+///
+/// The call must update head of the single parachain that is one of the `PID` parachains.
+fn extract_expected_parachain_state<R, GI, PI, PID>(
+	call: &CallOf<R>,
+) -> Option<ExpectedParachainState>
+where
+	R: GrandpaConfig<GI> + ParachainsConfig<PI, RelayChainHeaders = GrandpaChainHeaders<R, GI>>,
+	GI: 'static,
+	PI: 'static,
+	PID: Get<Vec<u32>>,
+	<R as GrandpaConfig<GI>>::BridgedChain:
+		Chain<BlockNumber = RelayBlockNumber, Hash = RelayBlockHash, Hasher = RelayBlockHasher>,
+	CallOf<R>: IsSubType<CallableCallFor<ParachainsPallet<R, PI>, R>>,
+{
+	if let Some(ParachainsCall::<R, PI>::submit_parachain_heads {
+		ref at_relay_block,
+		ref parachains,
+		..
+	}) = call.is_sub_type()
+	{
+		if parachains.len() != 1 {
+			return None
+		}
+
+		let para_id = parachains[0].0;
+		if !PID::get().contains(&para_id.0) {
+			return None
+		}
+
+		return Some(ExpectedParachainState { para_id, at_relay_block_number: at_relay_block.0 })
+	}
+	None
+}
+
+/// Checks that the call is delivering messages over the configured lane.
+///
+/// Calls with malformed messages proof, which is declaring inverted nonces range (i.e.
+/// `nonces_start > nonces_end`), are never supported. Such proofs can't deliver any messages.
+/// Proofs that are delivering messages of multiple lanes are not supported either - we only
+/// refund for deliveries over the single configured lane.
+fn check_messages_delivery_call<R, GI, MI, LID>(call: &CallOf<R>) -> Result<(), UnsupportedReason>
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI>,
+	GI: 'static,
+	MI: 'static,
+	LID: Get<LaneId>,
+	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
+		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	if let Some(MessagesCall::<R, MI>::receive_messages_proof { ref proof, .. }) =
+		call.is_sub_type()
+	{
+		if LID::get() != proof.lane {
+			return Err(UnsupportedReason::WrongLane(proof.lane))
+		}
+
+		if proof.nonces_start > proof.nonces_end || !proof.additional_lanes.is_empty() {
+			return Err(UnsupportedReason::UnknownCall)
+		}
+
+		return Ok(())
+	}
+	Err(UnsupportedReason::UnknownCall)
+}
+
+/// Checks that the call is confirming messages delivery over the configured lane.
+fn check_messages_confirmation_call<R, GI, MI, LID>(
+	call: &CallOf<R>,
+) -> Result<(), UnsupportedReason>
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI>,
+	GI: 'static,
+	MI: 'static,
+	LID: Get<LaneId>,
+	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as MessagesConfig<MI>>::TargetHeaderChain: TargetHeaderChain<
+		<R as MessagesConfig<MI>>::OutboundPayload,
+		<R as frame_system::Config>::AccountId,
+		MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	if let Some(MessagesCall::<R, MI>::receive_messages_delivery_proof { ref proof, .. }) =
+		call.is_sub_type()
+	{
+		if LID::get() != proof.lane {
+			return Err(UnsupportedReason::WrongLane(proof.lane))
+		}
+
+		return Ok(())
+	}
+	Err(UnsupportedReason::UnknownCall)
+}
+
+/// Extracts relayers that have delivered messages, which are confirmed by the call.
+///
+/// The messages delivery proof is verified here, so it must only be called for calls that
+/// have been already recognized as supported delivery confirmation calls.
+fn extract_confirmed_messages_relayers<R, GI, MI>(
+	call: &CallOf<R>,
+	confirmation_state: ConfirmationState,
+) -> RelayersRewards<R::AccountId>
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI>,
+	GI: 'static,
+	MI: 'static,
+	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as MessagesConfig<MI>>::TargetHeaderChain: TargetHeaderChain<
+		<R as MessagesConfig<MI>>::OutboundPayload,
+		<R as frame_system::Config>::AccountId,
+		MessagesDeliveryProof = FromBridgedChainMessagesDeliveryProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	if let Some(MessagesCall::<R, MI>::receive_messages_delivery_proof { ref proof, .. }) =
+		call.is_sub_type()
+	{
+		if let Ok((_, lane_data, _)) =
+			<R as MessagesConfig<MI>>::TargetHeaderChain::verify_messages_delivery_proof(
+				proof.clone(),
+			) {
+			let received_range = confirmation_state.best_confirmed_nonce.saturating_add(1)..=
+				lane_data.last_delivered_nonce();
+			return calc_relayers_rewards(lane_data.relayers, &received_range)
+		}
+	}
+	RelayersRewards::new()
+}
+
+/// Returns calls of the batch that may be refunded by the extension or `None` if the `call` is not
+/// a batch.
+///
+/// It is either the `call` itself or the single `utility.batchAll`, nested into the
+/// `utility.batchAll` `call`, if `RefundConfig::MAX_NESTED_BATCH_EXTRA_CALLS` is non-zero and
+/// the number of other calls of the outer batch doesn't exceed that limit.
+///
+/// At most `RefundConfig::MAX_NESTED_BATCH_EXTRA_CALLS + 1` calls of the batch are inspected, so
+/// the cost of parsing is bounded (see `bridge_batch_parse_weight`).
+pub(crate) fn unpack_bridge_batch<Call, UB, CFG>(call: &Call) -> Option<&Vec<Call>>
+where
+	UB: BatchCallUnpacker<Call>,
+	CFG: RefundConfig,
+{
+	let calls = UB::unpack_batch(call)?;
+	if CFG::MAX_NESTED_BATCH_EXTRA_CALLS == 0 || UB::unpack_batch_all(call).is_none() {
+		return Some(calls)
+	}
+
+	// batches with too many calls are never searched for nested batches
+	if calls.len() > max_inspected_batch_calls::<CFG>() {
+		return Some(calls)
+	}
+
+	// only one level of nesting is supported and the nested batch must be atomic
+	let mut nested_batches = calls.iter().filter(|call| UB::unpack_batch(call).is_some());
+	match (nested_batches.next(), nested_batches.next()) {
+		(Some(nested_batch), None) => UB::unpack_batch_all(nested_batch).or(Some(calls)),
+		_ => Some(calls),
+	}
+}
+
+/// Returns maximal number of batch calls, inspected by the `unpack_bridge_batch`.
+fn max_inspected_batch_calls<CFG: RefundConfig>() -> usize {
+	(CFG::MAX_NESTED_BATCH_EXTRA_CALLS as usize).saturating_add(1)
+}
+
+/// Weight of the `unpack_bridge_batch` call for given transaction.
+///
+/// Returns `None` if the call is not a batch.
+fn bridge_batch_parse_weight<Call, UB, CFG>(call: &Call) -> Option<Weight>
+where
+	UB: BatchCallUnpacker<Call>,
+	CFG: RefundConfig,
+{
+	let calls = UB::unpack_batch(call)?;
+	let inspected_calls = calls.len().min(max_inspected_batch_calls::<CFG>());
+	Some(CFG::bridge_batch_parse_weight(inspected_calls as u32))
+}
+
+/// Registers weight of the bridge batch parsing as an extra weight of the current block.
+///
+/// The extension is parsing every batch transaction and the cost of parsing is not a part of the
+/// call weight, so we need to account it separately.
+fn register_bridge_batch_parse_weight<R, UB, CFG>(call: &CallOf<R>, info: &DispatchInfo)
+where
+	R: frame_system::Config,
+	UB: BatchCallUnpacker<CallOf<R>>,
+	CFG: RefundConfig,
+{
+	if let Some(weight) = bridge_batch_parse_weight::<_, UB, CFG>(call) {
+		frame_system::Pallet::<R>::register_extra_weight_unchecked(weight, info.class);
+	}
+}
+
+/// Registers weight of the messages delivery proof verification as an extra weight of the current
+/// block.
+///
+/// The extension verifies the delivery proof of the confirmation call to reward relayers that have
+/// delivered confirmed messages (see `extract_confirmed_messages_relayers`). The proof is verified
+/// once again by the call itself, so the call weight doesn't include the cost of the first
+/// verification and we need to account it separately.
+fn register_delivery_proof_verification_weight<R, MI>(call: &CallOf<R>, info: &DispatchInfo)
+where
+	R: MessagesConfig<MI>,
+	MI: 'static,
+	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+{
+	if let Some(MessagesCall::<R, MI>::receive_messages_delivery_proof { ref proof, .. }) =
+		call.is_sub_type()
+	{
+		// we need to read the bridged header state root and to verify the storage proof
+		let proof_weight =
+			<MessagesWeightInfoOf<R, MI> as MessagesWeightInfoExt>::storage_proof_size_overhead(
+				proof.size(),
+			);
+		let weight = R::DbWeight::get().reads(1).saturating_add(proof_weight);
+		frame_system::Pallet::<R>::register_extra_weight_unchecked(weight, info.class);
+	}
+}
+
+/// Returns messages proof and number of messages that the message delivery call to the configured
+/// lane declares to deliver. The delivery call may either be a standalone call or the last call of
+/// the `utility.batchAll`.
+fn bundled_messages_proof<R, GI, MI, UB, LID, CFG>(
+	call: &CallOf<R>,
+) -> Option<(&FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>, u32)>
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI>,
+	GI: 'static,
+	MI: 'static,
+	UB: BatchCallUnpacker<CallOf<R>>,
+	LID: Get<LaneId>,
+	CFG: RefundConfig,
+	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
+		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	let delivery_call = match unpack_bridge_batch::<_, UB, CFG>(call) {
+		Some(calls) => calls.last()?,
+		None => call,
+	};
+
+	if let Some(MessagesCall::<R, MI>::receive_messages_proof {
+		ref proof,
+		ref messages_count,
+		..
+	}) = delivery_call.is_sub_type()
+	{
+		if LID::get() == proof.lane {
+			return Some((proof, *messages_count))
+		}
+	}
+	None
+}
+
+/// Returns relayer account at the bridged chain, declared in the message delivery call to the
+/// configured lane. The delivery call may either be a standalone call or the last call of the
+/// `utility.batchAll`.
+fn bundled_relayer_id_at_bridged_chain<R, GI, MI, UB, LID, CFG>(
+	call: &CallOf<R>,
+) -> Option<&<R as MessagesConfig<MI>>::InboundRelayer>
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI>,
+	GI: 'static,
+	MI: 'static,
+	UB: BatchCallUnpacker<CallOf<R>>,
+	LID: Get<LaneId>,
+	CFG: RefundConfig,
+	CallOf<R>: IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+	<R as MessagesConfig<MI>>::SourceHeaderChain: SourceHeaderChain<
+		MessagesProof = FromBridgedChainMessagesProof<HashOf<BridgedChain<R, GI>>>,
+	>,
+{
+	let delivery_call = match unpack_bridge_batch::<_, UB, CFG>(call) {
+		Some(calls) => calls.last()?,
+		None => call,
+	};
+
+	if let Some(MessagesCall::<R, MI>::receive_messages_proof {
+		ref relayer_id_at_bridged_chain,
+		ref proof,
+		..
+	}) = delivery_call.is_sub_type()
+	{
+		if LID::get() == proof.lane {
+			return Some(relayer_id_at_bridged_chain)
+		}
+	}
+	None
+}
+
+/// Returns true if the call is relay chain finality, parachain finality or message delivery call.
+fn is_header_or_delivery_call<R, GI, PI, MI>(call: &CallOf<R>) -> bool
+where
+	R: GrandpaConfig<GI> + ParachainsConfig<PI> + MessagesConfig<MI>,
+	GI: 'static,
+	PI: 'static,
+	MI: 'static,
+	CallOf<R>: IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>
+		+ IsSubType<CallableCallFor<ParachainsPallet<R, PI>, R>>
+		+ IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+{
+	let is_parachain_head_call =
+		matches!(call.is_sub_type(), Some(ParachainsCall::<R, PI>::submit_parachain_heads { .. }));
+	is_relay_header_or_delivery_call::<R, GI, MI>(call) || is_parachain_head_call
+}
+
+/// Returns true if call is the relay chain header submission or message delivery call.
+fn is_relay_header_or_delivery_call<R, GI, MI>(call: &CallOf<R>) -> bool
+where
+	R: GrandpaConfig<GI> + MessagesConfig<MI>,
+	GI: 'static,
+	MI: 'static,
+	CallOf<R>: IsSubType<CallableCallFor<GrandpaPallet<R, GI>, R>>
+		+ IsSubType<CallableCallFor<MessagesPallet<R, MI>, R>>,
+{
+	let is_relay_header_call =
+		matches!(call.is_sub_type(), Some(GrandpaCall::<R, GI>::submit_finality_proof { .. }));
+	let is_delivery_call =
+		matches!(call.is_sub_type(), Some(MessagesCall::<R, MI>::receive_messages_proof { .. }));
+	is_relay_header_call || is_delivery_call
+}
+
+/// Returns relay chain state that we are interested in.
+fn relay_chain_state<R, GI>() -> Option<ExpectedRelayChainState>
+where
+	R: GrandpaConfig<GI>,
+	GI: 'static,
+	<R as GrandpaConfig<GI>>::BridgedChain: Chain<BlockNumber = RelayBlockNumber>,
+{
+	GrandpaPallet::<R, GI>::best_finalized_number()
+		.map(|best_block_number| ExpectedRelayChainState { best_block_number })
+}
+
+/// Returns parachain state that we are interested in.
+fn parachain_state<R, PI>(para_id: ParaId) -> Option<ExpectedParachainState>
+where
+	R: ParachainsConfig<PI>,
+	PI: 'static,
+{
+	ParachainsPallet::<R, PI>::best_parachain_info(para_id).map(|para_info| {
+		ExpectedParachainState {
+			para_id,
+			at_relay_block_number: para_info.best_head_hash.at_relay_block_number,
+		}
+	})
+}
+
+/// Computes reward for the relayer that has submitted bridge transaction.
+///
+/// Returns `None` if the `FEE` has declined to refund the transaction.
+fn compute_reward<R, FEE>(
+	rewards_account_params: RewardsAccountParams,
+	info: &DispatchInfo,
+	post_info: &PostDispatchInfo,
+	len: usize,
+	bridge_calls_weight: Option<Weight>,
+) -> Option<<R as RelayersConfig>::Reward>
+where
+	R: RelayersConfig,
+	FEE: TransactionFeeCalculation,
+	FEE::Balance: UniqueSaturatedInto<<R as RelayersConfig>::Reward>,
+	<R as RelayersConfig>::Reward: FixedPointOperand,
+{
+	// regarding the tip - refund that happens here (at this side of the bridge) isn't the whole
+	// relayer compensation. He'll receive some amount at the other side of the bridge. It shall
+	// (in theory) cover the tip here. Otherwise, if we'll be compensating tip here, some
+	// malicious relayer may use huge tips, effectively depleting account that pay rewards. The
+	// cost of this attack is nothing. Hence we use zero as tip here. The `TipRefund` may be used
+	// to refund tips up to the configured cap.
+	let tip = Zero::zero();
+
+	// the fee may be larger than the maximal reward, so we saturate it here
+	let fee = match bridge_calls_weight {
+		Some(bridge_calls_weight) =>
+			FEE::compute_bridge_calls_fee(info, post_info, len, tip, bridge_calls_weight),
+		None => FEE::compute_fee(info, post_info, len, tip),
+	};
+	let reward: <R as RelayersConfig>::Reward = fee?.unique_saturated_into();
+
+	// apply the reward multiplier of the rewards account
+	Some(
+		RelayersPallet::<R>::lane_reward_multiplier(rewards_account_params)
+			.saturating_mul_int(reward),
+	)
+}
+
+/// Returns block number of the last relayer reward registration, if it has happened less than
+/// `MBR` blocks ago.
+fn recent_reward_block_number<R, MBR>(
+	rewards_account_params: RewardsAccountParams,
+	relayer: &R::AccountId,
+) -> Option<<R as frame_system::Config>::BlockNumber>
+where
+	R: RelayersConfig,
+	MBR: Get<<R as frame_system::Config>::BlockNumber>,
+{
+	let min_blocks_between_refunds = MBR::get();
+	if min_blocks_between_refunds.is_zero() {
+		return None
+	}
+
+	let current_block_number = frame_system::Pallet::<R>::block_number();
+	let last_reward_block_number =
+		RelayersPallet::<R>::relayer_reward_updated_at(relayer, rewards_account_params)?;
+	if current_block_number.saturating_sub(last_reward_block_number) < min_blocks_between_refunds {
+		return Some(last_reward_block_number)
+	}
+	None
+}
+
+/// Registers relayer reward in the relayers pallet, or only deposits the `ShadowReward` event if
+/// the extension works in the shadow mode. If the extension is configured to pay rewards
+/// immediately, the reward is paid instead of being registered. The `REF` handler is given a
+/// chance to handle the refund before the relayers pallet.
+///
+/// If the relayers pallet fails to register the reward, it deposits the `RewardRegistrationFailed`
+/// event and the error is returned.
+fn register_reward<R, REF, SHD, IMM>(
+	rewards_account_params: RewardsAccountParams,
+	relayer: &R::AccountId,
+	reward: <R as RelayersConfig>::Reward,
+) -> Result<(), RelayersError<R>>
+where
+	R: RelayersConfig,
+	REF: OnRefund<R::AccountId, <R as RelayersConfig>::Reward>,
+	SHD: Get<bool>,
+	IMM: Get<bool>,
+{
+	if SHD::get() {
+		RelayersPallet::<R>::note_shadow_relayer_reward(rewards_account_params, relayer, reward);
+		Ok(())
+	} else if REF::on_refund(rewards_account_params.lane_id, relayer, &reward) {
+		Ok(())
+	} else if IMM::get() {
+		RelayersPallet::<R>::pay_relayer_reward(rewards_account_params, relayer, reward)
+	} else {
+		RelayersPallet::<R>::register_relayer_reward(rewards_account_params, relayer, reward)
+	}
+}
+
+/// Returns index of the current refund era or `None` if refunds are not accounted.
+fn refund_era<R, CFG>() -> Option<u32>
+where
+	R: frame_system::Config,
+	CFG: RefundConfig,
+{
+	if CFG::REFUND_ERA_BLOCKS == 0 {
+		return None
+	}
+
+	let block_number: u32 = frame_system::Pallet::<R>::block_number().unique_saturated_into();
+	Some(block_number / CFG::REFUND_ERA_BLOCKS)
+}
+
+/// Notes that the relayer has not been refunded for submitting bridge transaction and updates
+/// relayer statistics, if the extension is configured to collect them.
+fn note_skipped_refund<R, LID, CFG>(relayer: &R::AccountId, reason: RefundSkipReason)
+where
+	R: RelayersConfig,
+	LID: Get<LaneId>,
+	CFG: RefundConfig,
+{
+	if CFG::COLLECT_RELAYER_STATISTICS {
+		RelayersPallet::<R>::note_failed_refund(relayer);
+	}
+	// transactions, rejected by `pre_dispatch`, are not included into the block, so obsolete
+	// transactions may only be counted here
+	if CFG::TRACK_NO_OP_TRANSACTIONS &&
+		matches!(reason, RefundSkipReason::StateNotAdvanced | RefundSkipReason::NoNewMessages)
+	{
+		RelayersPallet::<R>::note_invalid_submission(relayer);
+	}
+	RelayersPallet::<R>::note_skipped_refund(LID::get(), relayer, reason);
+}
+
+/// Flags the bridge transaction that has not advanced the bridge state, if the extension is
+/// configured to do so.
+fn note_no_op_transaction<R, PID, LID, CFG>(extension: &'static str, relayer: &R::AccountId)
+where
+	R: RelayersConfig,
+	PID: Get<Vec<u32>>,
+	LID: Get<LaneId>,
+	CFG: RefundConfig,
+{
+	if !CFG::TRACK_NO_OP_TRANSACTIONS {
+		return
+	}
+
+	log::trace!(
+		target: "runtime::bridge",
+		"{} has flagged transaction of {:?} that has not \
+			advanced the bridge state",
+		BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+		relayer,
+	);
+
+	RelayersPallet::<R>::note_no_op_submission(LID::get(), relayer);
+}
+
+/// Slashes and deregisters the registered relayer that has submitted bridge transaction, which
+/// has failed or has not advanced the bridge state, if the extension is configured to do so.
+fn slash_misbehaving_relayer<R, PID, LID, CFG>(extension: &'static str, relayer: &R::AccountId)
+where
+	R: RelayersConfig,
+	PID: Get<Vec<u32>>,
+	LID: Get<LaneId>,
+	CFG: RefundConfig,
+{
+	if !CFG::SLASH_MISBEHAVING_RELAYERS || RelayersPallet::<R>::relayer_stake(relayer).is_none() {
+		return
+	}
+
+	log::trace!(
+		target: "runtime::bridge",
+		"{} is slashing registered relayer {:?}",
+		BridgeLogName::<PID, LID, CFG>(extension, PhantomData),
+		relayer,
+	);
+
+	RelayersPallet::<R>::slash_and_deregister(relayer);
+}
+
+/// Returns messages state that we are interested in.
+fn messages_state<R, MI, LID>() -> MessagesState
+where
+	R: MessagesConfig<MI>,
+	MI: 'static,
+	LID: Get<LaneId>,
+{
+	MessagesState {
+		best_nonce: MessagesPallet::<R, MI>::inbound_lane_data(LID::get()).last_delivered_nonce(),
+	}
+}
+
+/// Returns confirmation state that we are interested in.
+fn confirmation_state<R, MI, LID>() -> ConfirmationState
+where
+	R: MessagesConfig<MI>,
+	MI: 'static,
+	LID: Get<LaneId>,
+{
+	ConfirmationState {
+		best_confirmed_nonce: OutboundLanes::<R, MI>::get(LID::get()).latest_received_nonce,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		messages::{
+			source::FromBridgedChainMessagesDeliveryProof,
+			target::{FromBridgedChainMessagesProof, MessagesStorageProof},
+		},
+		mock::*,
+	};
+	use bp_messages::{
+		InboundLaneData, MessagesOperatingMode, OutboundLaneData, UnrewardedRelayersState,
+	};
+	use bp_parachains::{BestParaHeadHash, ParaInfo};
+	use bp_polkadot_core::parachains::ParaHeadsProof;
+	use bp_runtime::{BasicOperatingMode, HeaderId};
+	use bp_test_utils::make_default_justification;
+	use frame_support::{
+		assert_storage_noop, parameter_types,
+		traits::{Currency, ReservableCurrency},
+		weights::Weight,
+	};
+	use sp_runtime::{DispatchError, FixedU128};
+	use sp_trie::CompactProof;
+
+	parameter_types! {
 		pub TestParachain: u32 = 1000;
+		pub TestParachains: Vec<u32> = vec![TestParachain::get()];
+		pub TwoTestParachains: Vec<u32> = vec![TestParachain::get(), TestParachain::get() + 1];
 		pub TestLaneId: LaneId = TEST_LANE_ID;
+		pub DeclinedFee: Option<ThisChainBalance> = None;
+		pub ZeroFee: Option<ThisChainBalance> = Some(0);
+		pub NonZeroFee: Option<ThisChainBalance> = Some(42);
+		pub NonZeroWideFee: Option<u128> = Some(42);
+		pub OverflowingWideFee: Option<u128> = Some(u128::MAX);
+	}
+
+	type TestExtension = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+	>;
+
+	parameter_types! {
+		pub const MaxDeliveryEncodedLen: u32 = 1024;
+		pub const MinBlocksBetweenRefunds: ThisChainBlockNumber = 5;
+		pub const ShadowMode: bool = true;
+		pub const ImmediatePayout: bool = true;
+		pub BridgedFeeConversion: Option<FixedU128> = Some(FixedU128::from_rational(5, 2));
+	}
+
+	/// Extension that limits the length of delivery transactions.
+	type TestExtensionWithLenLimit = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+		(),
+		MaxDeliveryEncodedLen,
+	>;
+
+	/// Refund configuration that limits the number of calls in delivery batches.
+	struct TestRefundConfigWithBatchLimit;
+
+	impl RefundConfig for TestRefundConfigWithBatchLimit {
+		const MAX_DELIVERY_BATCH_CALLS: u32 = 2;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithBatchLimit = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithBatchLimit,
+	>;
+
+	/// Extension for runtimes without `pallet_utility`.
+	type TestExtensionWithoutUtility = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		(),
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+	>;
+
+	/// Extension that has a cooldown between refunds.
+	type TestExtensionWithCooldown = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+		(),
+		(),
+		MinBlocksBetweenRefunds,
+	>;
+
+	/// Refund configuration that rewards relayers that have delivered confirmed messages.
+	struct TestRefundConfigWithConfirmedMessagesRewards;
+
+	impl RefundConfig for TestRefundConfigWithConfirmedMessagesRewards {
+		const DELIVERY_REWARD_PER_CONFIRMED_MESSAGE: u32 = 10;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithConfirmedMessagesRewards = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithConfirmedMessagesRewards,
+	>;
+
+	/// Fee calculation that returns the fee of type `B` from the `F`.
+	struct TestFeeCalculation<B, F>(PhantomData<(B, F)>);
+
+	impl<B: Zero, F: Get<Option<B>>> TransactionFeeCalculation for TestFeeCalculation<B, F> {
+		type Balance = B;
+
+		fn compute_fee(
+			_info: &DispatchInfo,
+			_post_info: &PostDispatchInfo,
+			_len: usize,
+			_tip: B,
+		) -> Option<B> {
+			F::get()
+		}
+	}
+
+	type TestExtensionWithFee<F, B = ThisChainBalance> = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestFeeCalculation<B, F>,
+		(),
+		(),
+		(),
+	>;
+
+	type TestExtensionWithFeeCalculation<FEE> = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		FEE,
+		(),
+		(),
+		(),
+	>;
+
+	parameter_types! {
+		pub HalfRefund: Perbill = Perbill::from_percent(50);
+		pub const MaxRefund: ThisChainBalance = 100;
+		pub static TestTip: ThisChainBalance = 0;
+		pub const MaxRefundableTip: ThisChainBalance = 10;
+	}
+
+	/// Extension that works in the shadow mode.
+	type TestExtensionInShadowMode = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+		(),
+		(),
+		(),
+		ShadowMode,
+	>;
+
+	/// Refund configuration with bridge name.
+	struct TestRefundConfigWithBridgeName;
+
+	impl RefundConfig for TestRefundConfigWithBridgeName {
+		const BRIDGE_NAME: Option<&'static str> = Some("TestBridge");
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithBridgeName = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithBridgeName,
+	>;
+
+	/// Refund eligibility check that only allows refunds at even blocks.
+	struct TestRefundEligibilityAtEvenBlocks;
+
+	impl RefundEligibility<ThisChainAccountId, ThisChainBalance> for TestRefundEligibilityAtEvenBlocks {
+		fn is_eligible(
+			_data: &PreDispatchData<ThisChainAccountId>,
+			_reward: &ThisChainBalance,
+		) -> bool {
+			frame_system::Pallet::<TestRuntime>::block_number() % 2 == 0
+		}
+	}
+
+	type TestExtensionWithEligibilityCheck = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		TestRefundEligibilityAtEvenBlocks,
+		(),
+		(),
+	>;
+
+	/// Refund configuration that refunds standalone relay chain finality transactions.
+	struct TestRefundConfigWithRelayFinality;
+
+	impl RefundConfig for TestRefundConfigWithRelayFinality {
+		const REFUND_RELAY_FINALITY: bool = true;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithRelayFinality = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithRelayFinality,
+	>;
+
+	/// Refund configuration that refunds standalone parachain finality transactions.
+	struct TestRefundConfigWithParachainFinality;
+
+	impl RefundConfig for TestRefundConfigWithParachainFinality {
+		const REFUND_PARACHAIN_FINALITY: bool = true;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithParachainFinality = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithParachainFinality,
+	>;
+
+	/// Refund configuration that allows two relay chain headers in the batch.
+	struct TestRefundConfigWithTwoRelayHeaders;
+
+	impl RefundConfig for TestRefundConfigWithTwoRelayHeaders {
+		const MAX_RELAY_HEADERS_PER_BATCH: u32 = 2;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithTwoRelayHeaders = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithTwoRelayHeaders,
+	>;
+
+	/// Relayer account converter that maps relayer account at the bridged chain to the account
+	/// with the same number at this chain.
+	struct TestRelayerAccountIdConverter;
+
+	impl RelayerAccountIdConverter<BridgedChainAccountId, ThisChainAccountId>
+		for TestRelayerAccountIdConverter
+	{
+		fn convert(
+			relayer_id_at_bridged_chain: &BridgedChainAccountId,
+		) -> Option<ThisChainAccountId> {
+			Some(*relayer_id_at_bridged_chain as ThisChainAccountId)
+		}
+	}
+
+	type TestExtensionWithRelayerIdCheck = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		TestRelayerAccountIdConverter,
+		(),
+	>;
+
+	/// Extension that pays rewards immediately.
+	type TestExtensionWithImmediatePayout = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+		(),
+		(),
+		(),
+		(),
+		ImmediatePayout,
+	>;
+
+	/// Refund handler that stores refunds in the runtime storage instead of paying them.
+	struct TestOnRefund;
+
+	impl OnRefund<ThisChainAccountId, ThisChainBalance> for TestOnRefund {
+		fn on_refund(
+			_lane_id: LaneId,
+			relayer: &ThisChainAccountId,
+			reward: &ThisChainBalance,
+		) -> bool {
+			frame_support::storage::unhashed::put(b"TestOnRefund", &(*relayer, *reward));
+			true
+		}
+	}
+
+	type TestExtensionWithRefundHandler = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+		TestOnRefund,
+	>;
+
+	/// Extension that converts rewards to the bridged chain fee units.
+	type TestExtensionWithBridgedFeeConversion = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+		(),
+		(),
+		(),
+		(),
+		(),
+		BridgedFeeConversion,
+	>;
+
+	/// Refund configuration that boosts priority of message delivery transactions.
+	struct TestRefundConfigWithPriorityBoost;
+
+	impl RefundConfig for TestRefundConfigWithPriorityBoost {
+		const PRIORITY_BOOST_PER_MESSAGE: TransactionPriority = 10;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithPriorityBoost = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithPriorityBoost,
+	>;
+
+	/// Refund configuration that flags transactions that have not advanced the bridge state.
+	struct TestRefundConfigWithNoOpTracking;
+
+	impl RefundConfig for TestRefundConfigWithNoOpTracking {
+		const TRACK_NO_OP_TRANSACTIONS: bool = true;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithNoOpTracking = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithNoOpTracking,
+	>;
+
+	/// Refund configuration that limits total amount of refunds per refund era.
+	struct TestRefundConfigWithRefundBudget;
+
+	impl RefundConfig for TestRefundConfigWithRefundBudget {
+		const REFUND_ERA_BLOCKS: u32 = 10;
+		const REFUND_BUDGET_PER_ERA: u128 = 1_000_000_000_000_000;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithRefundBudget = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithRefundBudget,
+	>;
+
+	/// Refund configuration that supports nested batches.
+	struct TestRefundConfigWithNestedBatches;
+
+	impl RefundConfig for TestRefundConfigWithNestedBatches {
+		const MAX_NESTED_BATCH_EXTRA_CALLS: u32 = 1;
+
+		fn bridge_batch_parse_weight(calls: u32) -> Weight {
+			<() as RefundExtensionWeightInfo>::unpack_bridge_batch(calls)
+		}
+	}
+
+	type TestExtensionWithNestedBatches = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithNestedBatches,
+	>;
+
+	/// Refund configuration that only refunds registered relayers and boosts priority of their
+	/// transactions.
+	struct TestRefundConfigWithRegisteredRelayers;
+
+	impl RefundConfig for TestRefundConfigWithRegisteredRelayers {
+		const REGISTERED_RELAYER_PRIORITY_BOOST: TransactionPriority = 1_000;
+		const REFUND_REGISTERED_RELAYERS_ONLY: bool = true;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithRegisteredRelayers = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithRegisteredRelayers,
+	>;
+
+	/// Refund configuration that slashes misbehaving registered relayers.
+	struct TestRefundConfigWithSlashing;
+
+	impl RefundConfig for TestRefundConfigWithSlashing {
+		const SLASH_MISBEHAVING_RELAYERS: bool = true;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithSlashing = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithSlashing,
+	>;
+
+	struct TestRefundConfigWithStatistics;
+
+	impl RefundConfig for TestRefundConfigWithStatistics {
+		const COLLECT_RELAYER_STATISTICS: bool = true;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithStatistics = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithStatistics,
+	>;
+
+	type TestRelayChainExtension = TestRelayChainExtensionWithFeeCalculation<TestRuntime>;
+
+	type TestRelayChainExtensionWithNestedBatches = RefundRelayerForMessagesDeliveryFromRelayChain<
+		TestRuntime,
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithNestedBatches,
+	>;
+
+	type TestRelayChainExtensionWithRelayerIdCheck = RefundRelayerForMessagesDeliveryFromRelayChain<
+		TestRuntime,
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestLaneId,
+		TestRuntime,
+		(),
+		TestRelayerAccountIdConverter,
+		(),
+	>;
+
+	type TestRelayChainExtensionWithFeeCalculation<FEE> =
+		RefundRelayerForMessagesDeliveryFromRelayChain<
+			TestRuntime,
+			(),
+			(),
+			UtilityBatchCallUnpacker<TestRuntime>,
+			BridgeRejectObsoleteHeadersAndMessages,
+			TestLaneId,
+			FEE,
+			(),
+			(),
+			(),
+		>;
+
+	/// Refund configuration that rejects deliveries of recently refunded messages.
+	struct TestRefundConfigWithReplayGuard;
+
+	impl RefundConfig for TestRefundConfigWithReplayGuard {
+		const REPLAY_GUARD_BLOCKS: u32 = 5;
+
+		fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+			Weight::zero()
+		}
+	}
+
+	type TestExtensionWithReplayGuard = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		TestRefundConfigWithReplayGuard,
+	>;
+
+	parameter_types! {
+		pub OtherTestLaneId: LaneId = LaneId([0, 0, 0, 1]);
+	}
+
+	/// Signed extension that rejects all calls.
+	#[derive(Clone, Debug, Decode, Default, Encode, Eq, PartialEq, TypeInfo)]
+	struct RejectAllCalls;
+
+	impl SignedExtension for RejectAllCalls {
+		const IDENTIFIER: &'static str = "RejectAllCalls";
+		type AccountId = ThisChainAccountId;
+		type Call = RuntimeCall;
+		type AdditionalSigned = ();
+		type Pre = ();
+
+		fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+			Ok(())
+		}
+
+		fn validate(
+			&self,
+			_who: &Self::AccountId,
+			_call: &Self::Call,
+			_info: &DispatchInfoOf<Self::Call>,
+			_len: usize,
+		) -> TransactionValidity {
+			InvalidTransaction::Call.into()
+		}
+
+		fn pre_dispatch(
+			self,
+			who: &Self::AccountId,
+			call: &Self::Call,
+			info: &DispatchInfoOf<Self::Call>,
+			len: usize,
+		) -> Result<Self::Pre, TransactionValidityError> {
+			self.validate(who, call, info, len).map(drop)
+		}
+	}
+
+	/// Signed extension that rejects all balances calls.
+	#[derive(Clone, Debug, Decode, Default, Encode, Eq, PartialEq, TypeInfo)]
+	struct RejectBalancesCalls;
+
+	impl SignedExtension for RejectBalancesCalls {
+		const IDENTIFIER: &'static str = "RejectBalancesCalls";
+		type AccountId = ThisChainAccountId;
+		type Call = RuntimeCall;
+		type AdditionalSigned = ();
+		type Pre = ();
+
+		fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+			Ok(())
+		}
+
+		fn validate(
+			&self,
+			_who: &Self::AccountId,
+			call: &Self::Call,
+			_info: &DispatchInfoOf<Self::Call>,
+			_len: usize,
+		) -> TransactionValidity {
+			match call {
+				RuntimeCall::Balances(_) => InvalidTransaction::Call.into(),
+				_ => Ok(ValidTransaction::default()),
+			}
+		}
+
+		fn pre_dispatch(
+			self,
+			who: &Self::AccountId,
+			call: &Self::Call,
+			info: &DispatchInfoOf<Self::Call>,
+			len: usize,
+		) -> Result<Self::Pre, TransactionValidityError> {
+			self.validate(who, call, info, len).map(drop)
+		}
+	}
+
+	type TestExtensionRejectingBalancesCalls = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		RejectBalancesCalls,
+		TestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+	>;
+
+	/// Extension of other bridge, which uses different obsolete transactions check.
+	type TestExtensionOfOtherBridge = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		RejectAllCalls,
+		TestParachains,
+		OtherTestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+	>;
+
+	/// Extension of the second bridge, which is serving other lane.
+	type TestExtensionOfSecondBridge = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TestParachains,
+		OtherTestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+	>;
+
+	type TestMultiBridgeExtension = RefundRelayerForMessagesFromParachains<
+		TestRuntime,
+		(TestExtension, TestExtensionOfSecondBridge),
+	>;
+
+	type TestExtensionWithTwoParachains = RefundRelayerForMessagesFromParachain<
+		TestRuntime,
+		(),
+		(),
+		(),
+		UtilityBatchCallUnpacker<TestRuntime>,
+		BridgeRejectObsoleteHeadersAndMessages,
+		TwoTestParachains,
+		TestLaneId,
+		TestRuntime,
+		(),
+		(),
+		(),
+	>;
+
+	thread_local! {
+		static CAPTURED_LOGS: sp_std::cell::RefCell<Vec<String>> = Default::default();
+	}
+
+	/// Logger that captures log messages, emitted by the current thread.
+	struct CapturingLogger;
+
+	impl log::Log for CapturingLogger {
+		fn enabled(&self, _metadata: &log::Metadata) -> bool {
+			true
+		}
+
+		fn log(&self, record: &log::Record) {
+			CAPTURED_LOGS.with(|logs| logs.borrow_mut().push(record.args().to_string()));
+		}
+
+		fn flush(&self) {}
+	}
+
+	/// Runs given closure and returns all log messages that it has emitted.
+	fn capture_logs(f: impl FnOnce()) -> Vec<String> {
+		static INIT_LOGGER: std::sync::Once = std::sync::Once::new();
+		INIT_LOGGER.call_once(|| {
+			log::set_logger(&CapturingLogger).expect("no other logger is set in tests; qed");
+			log::set_max_level(log::LevelFilter::Trace);
+		});
+
+		CAPTURED_LOGS.with(|logs| logs.borrow_mut().clear());
+		f();
+		CAPTURED_LOGS.with(|logs| logs.take())
+	}
+
+	fn relayer_account_at_this_chain() -> ThisChainAccountId {
+		0
+	}
+
+	fn relayer_account_at_bridged_chain() -> BridgedChainAccountId {
+		0
+	}
+
+	fn initialize_environment(
+		best_relay_header_number: RelayBlockNumber,
+		parachain_head_at_relay_header_number: RelayBlockNumber,
+		best_delivered_message: MessageNonce,
+	) {
+		let best_relay_header = HeaderId(best_relay_header_number, RelayBlockHash::default());
+		pallet_bridge_grandpa::BestFinalized::<TestRuntime>::put(best_relay_header);
+
+		let para_id = ParaId(TestParachain::get());
+		let para_info = ParaInfo {
+			best_head_hash: BestParaHeadHash {
+				at_relay_block_number: parachain_head_at_relay_header_number,
+				head_hash: Default::default(),
+			},
+			next_imported_hash_position: 0,
+		};
+		pallet_bridge_parachains::ParasInfo::<TestRuntime>::insert(para_id, para_info);
+
+		let lane_id = TestLaneId::get();
+		let lane_data =
+			InboundLaneData { last_confirmed_nonce: best_delivered_message, ..Default::default() };
+		pallet_bridge_messages::InboundLanes::<TestRuntime>::insert(lane_id, lane_data);
+	}
+
+	fn set_best_confirmed_message(best_confirmed_message: MessageNonce) {
+		pallet_bridge_messages::OutboundLanes::<TestRuntime>::insert(
+			TestLaneId::get(),
+			OutboundLaneData {
+				latest_received_nonce: best_confirmed_message,
+				latest_generated_nonce: best_confirmed_message + 100,
+				..Default::default()
+			},
+		);
+	}
+
+	fn submit_relay_header_call(relay_header_number: RelayBlockNumber) -> RuntimeCall {
+		let relay_header = BridgedChainHeader::new(
+			relay_header_number,
+			Default::default(),
+			Default::default(),
+			Default::default(),
+			Default::default(),
+		);
+		let relay_justification = make_default_justification(&relay_header);
+
+		RuntimeCall::BridgeGrandpa(GrandpaCall::submit_finality_proof {
+			finality_target: Box::new(relay_header),
+			justification: relay_justification,
+		})
+	}
+
+	fn submit_parachain_head_call(
+		parachain_head_at_relay_header_number: RelayBlockNumber,
+	) -> RuntimeCall {
+		RuntimeCall::BridgeParachains(ParachainsCall::submit_parachain_heads {
+			at_relay_block: (parachain_head_at_relay_header_number, RelayBlockHash::default()),
+			parachains: vec![(ParaId(TestParachain::get()), [1u8; 32].into())],
+			parachain_heads_proof: ParaHeadsProof(vec![]),
+		})
+	}
+
+	fn message_delivery_call(best_message: MessageNonce) -> RuntimeCall {
+		message_delivery_call_at_lane(TestLaneId::get(), best_message)
+	}
+
+	fn message_delivery_call_at_lane(lane: LaneId, best_message: MessageNonce) -> RuntimeCall {
+		RuntimeCall::BridgeMessages(MessagesCall::receive_messages_proof {
+			relayer_id_at_bridged_chain: relayer_account_at_bridged_chain(),
+			proof: FromBridgedChainMessagesProof {
+				bridged_header_hash: Default::default(),
+				storage_proof: MessagesStorageProof::Compact(CompactProof {
+					encoded_nodes: vec![],
+				}),
+				lane,
+				nonces_start: best_message,
+				nonces_end: best_message,
+				additional_lanes: Vec::new(),
+			},
+			messages_count: 1,
+			dispatch_weight: Weight::zero(),
+		})
+	}
+
+	fn message_confirmation_call(best_message: MessageNonce) -> RuntimeCall {
+		RuntimeCall::BridgeMessages(MessagesCall::receive_messages_delivery_proof {
+			proof: FromBridgedChainMessagesDeliveryProof {
+				bridged_header_hash: Default::default(),
+				storage_proof: vec![],
+				lane: TestLaneId::get(),
+				dispatch_result_nonces: vec![],
+			},
+			relayers_state: UnrewardedRelayersState {
+				last_delivered_nonce: best_message,
+				..Default::default()
+			},
+		})
+	}
+
+	fn remark_call() -> RuntimeCall {
+		RuntimeCall::System(frame_system::Call::remark { remark: vec![42] })
+	}
+
+	fn parachain_finality_and_delivery_batch_call(
+		parachain_head_at_relay_header_number: RelayBlockNumber,
+		best_message: MessageNonce,
+	) -> RuntimeCall {
+		RuntimeCall::Utility(UtilityCall::batch_all {
+			calls: vec![
+				submit_parachain_head_call(parachain_head_at_relay_header_number),
+				message_delivery_call(best_message),
+			],
+		})
+	}
+
+	fn all_finality_and_delivery_batch_call(
+		relay_header_number: RelayBlockNumber,
+		parachain_head_at_relay_header_number: RelayBlockNumber,
+		best_message: MessageNonce,
+	) -> RuntimeCall {
+		RuntimeCall::Utility(UtilityCall::batch_all {
+			calls: vec![
+				submit_relay_header_call(relay_header_number),
+				submit_parachain_head_call(parachain_head_at_relay_header_number),
+				message_delivery_call(best_message),
+			],
+		})
+	}
+
+	fn all_finality_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
+		PreDispatchData {
+			relayer: relayer_account_at_this_chain(),
+			call_type: CallType::AllFinalityAndDelivery(
+				ExpectedRelayChainState { best_block_number: 200 },
+				ExpectedParachainState {
+					para_id: ParaId(TestParachain::get()),
+					at_relay_block_number: 200,
+				},
+				MessagesState { best_nonce: 100 },
+			),
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight: None,
+			bridge_calls_weight: None,
+		}
+	}
+
+	fn relay_finality_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
+		PreDispatchData {
+			relayer: relayer_account_at_this_chain(),
+			call_type: CallType::RelayFinality(ExpectedRelayChainState { best_block_number: 200 }),
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight: None,
+			bridge_calls_weight: None,
+		}
+	}
+
+	fn standalone_parachain_finality_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
+		PreDispatchData {
+			relayer: relayer_account_at_this_chain(),
+			call_type: CallType::ParachainFinality(ExpectedParachainState {
+				para_id: ParaId(TestParachain::get()),
+				at_relay_block_number: 200,
+			}),
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight: None,
+			bridge_calls_weight: None,
+		}
+	}
+
+	fn parachain_finality_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
+		PreDispatchData {
+			relayer: relayer_account_at_this_chain(),
+			call_type: CallType::ParachainFinalityAndDelivery(
+				ExpectedParachainState {
+					para_id: ParaId(TestParachain::get()),
+					at_relay_block_number: 200,
+				},
+				MessagesState { best_nonce: 100 },
+			),
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight: None,
+			bridge_calls_weight: None,
+		}
+	}
+
+	fn delivery_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
+		PreDispatchData {
+			relayer: relayer_account_at_this_chain(),
+			call_type: CallType::Delivery(MessagesState { best_nonce: 100 }),
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight: None,
+			bridge_calls_weight: None,
+		}
+	}
+
+	fn confirmation_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
+		PreDispatchData {
+			relayer: relayer_account_at_this_chain(),
+			call_type: CallType::DeliveryConfirmation(ConfirmationState {
+				best_confirmed_nonce: 100,
+			}),
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight: None,
+			bridge_calls_weight: None,
+		}
+	}
+
+	/// Kind of the call that may be bundled into the batch by the batch shapes generator.
+	#[derive(Clone, Copy, Debug, PartialEq)]
+	enum BatchItem {
+		RelayHeader,
+		ParachainHead,
+		Delivery,
+		WrongLaneDelivery,
+		Confirmation,
+		Neutral,
+	}
+
+	impl BatchItem {
+		const ALL: [BatchItem; 6] = [
+			BatchItem::RelayHeader,
+			BatchItem::ParachainHead,
+			BatchItem::Delivery,
+			BatchItem::WrongLaneDelivery,
+			BatchItem::Confirmation,
+			BatchItem::Neutral,
+		];
+
+		fn call(&self) -> RuntimeCall {
+			match *self {
+				BatchItem::RelayHeader => submit_relay_header_call(200),
+				BatchItem::ParachainHead => submit_parachain_head_call(200),
+				BatchItem::Delivery => message_delivery_call(200),
+				BatchItem::WrongLaneDelivery =>
+					message_delivery_call_at_lane(LaneId([0, 0, 0, 1]), 200),
+				BatchItem::Confirmation => message_confirmation_call(200),
+				BatchItem::Neutral => remark_call(),
+			}
+		}
+	}
+
+	/// Generates all possible batch shapes (permutations and subsets of all `BatchItem`s, including
+	/// duplicates) with up to `max_len` calls.
+	fn all_batch_shapes(max_len: usize) -> Vec<Vec<BatchItem>> {
+		let mut shapes = vec![vec![]];
+		let mut longest_shapes = vec![vec![]];
+		for _ in 0..max_len {
+			longest_shapes = longest_shapes
+				.into_iter()
+				.flat_map(|shape: Vec<BatchItem>| {
+					BatchItem::ALL.into_iter().map(move |item| {
+						let mut shape = shape.clone();
+						shape.push(item);
+						shape
+					})
+				})
+				.collect();
+			shapes.extend(longest_shapes.iter().cloned());
+		}
+		shapes
+	}
+
+	fn run_test(test: impl FnOnce()) {
+		sp_io::TestExternalities::new(Default::default()).execute_with(test)
+	}
+
+	fn run_validate(call: RuntimeCall) -> TransactionValidity {
+		let extension: TestExtension = RefundRelayerForMessagesFromParachain(PhantomData);
+		extension.validate(&relayer_account_at_this_chain(), &call, &DispatchInfo::default(), 0)
+	}
+
+	fn run_pre_dispatch(
+		call: RuntimeCall,
+	) -> Result<Option<PreDispatchData<ThisChainAccountId>>, TransactionValidityError> {
+		let extension: TestExtension = RefundRelayerForMessagesFromParachain(PhantomData);
+		extension.pre_dispatch(&relayer_account_at_this_chain(), &call, &DispatchInfo::default(), 0)
+	}
+
+	fn dispatch_info() -> DispatchInfo {
+		DispatchInfo {
+			weight: Weight::from_ref_time(
+				frame_support::weights::constants::WEIGHT_REF_TIME_PER_SECOND,
+			),
+			class: frame_support::dispatch::DispatchClass::Normal,
+			pays_fee: frame_support::dispatch::Pays::Yes,
+		}
+	}
+
+	fn post_dispatch_info() -> PostDispatchInfo {
+		PostDispatchInfo { actual_weight: None, pays_fee: frame_support::dispatch::Pays::Yes }
+	}
+
+	fn run_post_dispatch(
+		pre_dispatch_data: Option<PreDispatchData<ThisChainAccountId>>,
+		dispatch_result: DispatchResult,
+	) {
+		run_post_dispatch_with::<TestExtension>(pre_dispatch_data, dispatch_result)
+	}
+
+	fn run_post_dispatch_with<E>(
+		pre_dispatch_data: Option<PreDispatchData<ThisChainAccountId>>,
+		dispatch_result: DispatchResult,
+	) where
+		E: SignedExtension<Call = RuntimeCall, Pre = Option<PreDispatchData<ThisChainAccountId>>>,
+	{
+		let post_dispatch_result = E::post_dispatch(
+			Some(pre_dispatch_data),
+			&dispatch_info(),
+			&post_dispatch_info(),
+			1024,
+			&dispatch_result,
+		);
+		assert_eq!(post_dispatch_result, Ok(()));
+	}
+
+	fn delivery_rewards_account_params() -> RewardsAccountParams {
+		RewardsAccountParams::new(TestLaneId::get(), RewardKind::MessageDelivery)
+	}
+
+	fn confirmation_rewards_account_params() -> RewardsAccountParams {
+		RewardsAccountParams::new(TestLaneId::get(), RewardKind::DeliveryConfirmation)
+	}
+
+	fn expected_reward() -> ThisChainBalance {
+		pallet_transaction_payment::Pallet::<TestRuntime>::compute_actual_fee(
+			1024,
+			&dispatch_info(),
+			&post_dispatch_info(),
+			Zero::zero(),
+		)
+	}
+
+	#[test]
+	fn validate_allows_non_obsolete_transactions() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()),);
+
+			assert_eq!(
+				run_validate(parachain_finality_and_delivery_batch_call(200, 200)),
+				Ok(ValidTransaction::default()),
+			);
+
+			assert_eq!(
+				run_validate(all_finality_and_delivery_batch_call(200, 200, 200)),
+				Ok(ValidTransaction::default()),
+			);
+		});
+	}
+
+	#[test]
+	fn validate_rejects_over_padded_delivery_transactions() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let run_validate_with_len = |call: RuntimeCall, len: usize| {
+				let extension: TestExtensionWithLenLimit =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&call,
+					&DispatchInfo::default(),
+					len,
+				)
+			};
+
+			assert_eq!(
+				run_validate_with_len(message_delivery_call(200), 1024),
+				Ok(ValidTransaction::default()),
+			);
+			assert_eq!(
+				run_validate_with_len(parachain_finality_and_delivery_batch_call(200, 200), 1024),
+				Ok(ValidTransaction::default()),
+			);
+
+			assert_eq!(
+				run_validate_with_len(message_delivery_call(200), 1025),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Custom(
+					OVERSIZED_DELIVERY_TRANSACTION
+				))),
+			);
+			assert_eq!(
+				run_validate_with_len(parachain_finality_and_delivery_batch_call(200, 200), 1025),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Custom(
+					OVERSIZED_DELIVERY_TRANSACTION
+				))),
+			);
+		});
+	}
+
+	#[test]
+	fn validate_rejects_oversized_delivery_batches() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let run_validate_with_batch_limit = |call: RuntimeCall| {
+				let extension: TestExtensionWithBatchLimit =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&call,
+					&DispatchInfo::default(),
+					0,
+				)
+			};
+
+			assert_eq!(
+				run_validate_with_batch_limit(parachain_finality_and_delivery_batch_call(200, 200)),
+				Ok(ValidTransaction::default()),
+			);
+			assert_eq!(
+				run_validate_with_batch_limit(all_finality_and_delivery_batch_call(200, 200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Custom(
+					OVERSIZED_DELIVERY_BATCH
+				))),
+			);
+
+			// batches that are not delivering messages are not limited
+			assert_eq!(
+				run_validate_with_batch_limit(RuntimeCall::Utility(UtilityCall::batch_all {
+					calls: vec![remark_call(), remark_call(), remark_call()],
+				})),
+				Ok(ValidTransaction::default()),
+			);
+		});
+	}
+
+	#[test]
+	fn validate_rejects_delivery_transactions_when_messages_pallet_is_halted() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let set_operating_mode = |operating_mode| {
+				pallet_bridge_messages::PalletOperatingMode::<TestRuntime>::put(operating_mode)
+			};
+			let set_lane_operating_mode = |lane, operating_mode| {
+				pallet_bridge_messages::LaneOperatingModes::<TestRuntime>::insert(
+					lane,
+					operating_mode,
+				)
+			};
+
+			// when pallet is halted, delivery transactions are rejected
+			set_operating_mode(MessagesOperatingMode::Basic(BasicOperatingMode::Halted));
+			assert_eq!(
+				run_validate(message_delivery_call(200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+			assert_eq!(
+				run_validate(parachain_finality_and_delivery_batch_call(200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+			assert_eq!(
+				run_validate(all_finality_and_delivery_batch_call(200, 200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+
+			// other transactions are unaffected
+			assert_eq!(
+				run_validate(message_confirmation_call(200)),
+				Ok(ValidTransaction::default())
+			);
+			assert_eq!(run_validate(remark_call()), Ok(ValidTransaction::default()));
+
+			// when pallet is not halted, delivery transactions are accepted
+			for operating_mode in [
+				MessagesOperatingMode::Basic(BasicOperatingMode::Normal),
+				MessagesOperatingMode::RejectingOutboundMessages,
+			] {
+				set_operating_mode(operating_mode);
+				assert_eq!(
+					run_validate(message_delivery_call(200)),
+					Ok(ValidTransaction::default()),
+				);
+				assert_eq!(
+					run_validate(all_finality_and_delivery_batch_call(200, 200, 200)),
+					Ok(ValidTransaction::default()),
+				);
+			}
+
+			// when our lane is halted, delivery transactions are rejected too
+			set_lane_operating_mode(
+				TestLaneId::get(),
+				MessagesOperatingMode::Basic(BasicOperatingMode::Halted),
+			);
+			assert_eq!(
+				run_validate(message_delivery_call(200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+			assert_eq!(
+				run_validate(parachain_finality_and_delivery_batch_call(200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+			assert_eq!(
+				run_validate(all_finality_and_delivery_batch_call(200, 200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+
+			// but halting other lane doesn't affect our transactions
+			set_lane_operating_mode(
+				TestLaneId::get(),
+				MessagesOperatingMode::Basic(BasicOperatingMode::Normal),
+			);
+			set_lane_operating_mode(
+				OtherTestLaneId::get(),
+				MessagesOperatingMode::Basic(BasicOperatingMode::Halted),
+			);
+			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()));
+		});
+	}
+
+	#[test]
+	fn validate_rejects_delivery_transactions_of_not_allowlisted_relayers() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+			let set_allowlist = |allowlist: Option<Vec<ThisChainAccountId>>| {
+				RelayersPallet::<TestRuntime>::set_lane_relayers_allowlist(
+					RuntimeOrigin::root(),
+					TestLaneId::get(),
+					allowlist.map(|allowlist| allowlist.try_into().unwrap()),
+				)
+				.unwrap();
+			};
+
+			// when relayer is not in the lane allowlist, delivery transactions are rejected
+			set_allowlist(Some(vec![relayer_account_at_this_chain() + 1]));
+			assert_eq!(
+				run_validate(message_delivery_call(200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Custom(
+					NOT_ALLOWLISTED_RELAYER
+				))),
+			);
+			assert_eq!(
+				run_validate(all_finality_and_delivery_batch_call(200, 200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Custom(
+					NOT_ALLOWLISTED_RELAYER
+				))),
+			);
+
+			// other transactions are unaffected
+			assert_eq!(
+				run_validate(message_confirmation_call(200)),
+				Ok(ValidTransaction::default())
+			);
+
+			// allowlisted relayer may deliver messages
+			set_allowlist(Some(vec![relayer_account_at_this_chain()]));
+			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()));
+
+			// any relayer may deliver messages when the allowlist is removed
+			set_allowlist(None);
+			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()));
+		});
+	}
+
+	#[test]
+	fn validate_only_runs_obsolete_checks_of_targeted_bridge() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let other_lane = LaneId([0, 0, 0, 1]);
+			let batch_call_at_lane = |lane| {
+				RuntimeCall::Utility(UtilityCall::batch_all {
+					calls: vec![
+						submit_relay_header_call(100),
+						submit_parachain_head_call(200),
+						message_delivery_call_at_lane(lane, 200),
+					],
+				})
+			};
+			let run_validate_with_other_bridge = |call: RuntimeCall| {
+				let extension: TestExtensionOfOtherBridge =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&call,
+					&DispatchInfo::default(),
+					0,
+				)
+			};
+
+			// batch of our bridge is only checked by our `BE`
+			assert_eq!(
+				run_validate(batch_call_at_lane(TestLaneId::get())),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+			assert_eq!(
+				run_validate_with_other_bridge(batch_call_at_lane(TestLaneId::get())),
+				Ok(ValidTransaction::default()),
+			);
+
+			// batch of other bridge is only checked by its `BE`
+			assert_eq!(
+				run_validate(batch_call_at_lane(other_lane)),
+				Ok(ValidTransaction::default()),
+			);
+			assert_eq!(
+				run_validate_with_other_bridge(batch_call_at_lane(other_lane)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Call)),
+			);
+		});
+	}
+
+	#[test]
+	fn validate_does_not_run_obsolete_checks_for_unrelated_calls_of_batch() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let extension: TestExtensionRejectingBalancesCalls =
+				RefundRelayerForMessagesFromParachain(PhantomData);
+			let call = RuntimeCall::Utility(UtilityCall::batch_all {
+				calls: vec![
+					RuntimeCall::Balances(pallet_balances::Call::transfer { dest: 1, value: 1 }),
+					message_delivery_call(200),
+				],
+			});
+			assert_eq!(
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&call,
+					&DispatchInfo::default(),
+					0
+				),
+				Ok(ValidTransaction::default()),
+			);
+		});
+	}
+
+	#[test]
+	fn validate_boosts_priority_of_delivery_transactions() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let validate_priority = |call: RuntimeCall| {
+				let extension: TestExtensionWithPriorityBoost =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension
+					.validate(&relayer_account_at_this_chain(), &call, &DispatchInfo::default(), 0)
+					.map(|valid| valid.priority)
+			};
+
+			let delivery_call_of_three_messages =
+				RuntimeCall::BridgeMessages(MessagesCall::receive_messages_proof {
+					relayer_id_at_bridged_chain: relayer_account_at_bridged_chain(),
+					proof: FromBridgedChainMessagesProof {
+						bridged_header_hash: Default::default(),
+						storage_proof: MessagesStorageProof::Compact(CompactProof {
+							encoded_nodes: vec![],
+						}),
+						lane: TestLaneId::get(),
+						nonces_start: 101,
+						nonces_end: 103,
+						additional_lanes: Vec::new(),
+					},
+					messages_count: 3,
+					dispatch_weight: Weight::zero(),
+				});
+
+			assert_eq!(validate_priority(message_delivery_call(200)), Ok(10));
+			assert_eq!(validate_priority(delivery_call_of_three_messages), Ok(30));
+			assert_eq!(
+				validate_priority(parachain_finality_and_delivery_batch_call(200, 200)),
+				Ok(10)
+			);
+			assert_eq!(validate_priority(message_confirmation_call(200)), Ok(0));
+			assert_eq!(
+				validate_priority(message_delivery_call_at_lane(LaneId([1, 2, 3, 4]), 200)),
+				Ok(0)
+			);
+			assert_eq!(validate_priority(remark_call()), Ok(0));
+
+			// priority is not changed by default
+			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()));
+		});
+	}
+
+	#[test]
+	fn validate_boosts_priority_of_registered_relayer_transactions() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let validate_priority = |call: RuntimeCall| {
+				let extension: TestExtensionWithRegisteredRelayers =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension
+					.validate(&relayer_account_at_this_chain(), &call, &DispatchInfo::default(), 0)
+					.map(|valid| valid.priority)
+			};
+
+			// transactions of unregistered relayer are not boosted
+			assert_eq!(validate_priority(message_delivery_call(200)), Ok(0));
+			assert_eq!(validate_priority(message_confirmation_call(200)), Ok(0));
+
+			// transactions of registered relayer are boosted
+			pallet_bridge_relayers::RegisteredRelayers::<TestRuntime>::insert(
+				relayer_account_at_this_chain(),
+				100,
+			);
+			assert_eq!(validate_priority(message_delivery_call(200)), Ok(1_000));
+			assert_eq!(validate_priority(message_confirmation_call(200)), Ok(1_000));
+			assert_eq!(
+				validate_priority(parachain_finality_and_delivery_batch_call(200, 200)),
+				Ok(1_000)
+			);
+
+			// but not if they're not bridge transactions
+			assert_eq!(
+				validate_priority(message_delivery_call_at_lane(LaneId([1, 2, 3, 4]), 200)),
+				Ok(0)
+			);
+			assert_eq!(validate_priority(remark_call()), Ok(0));
+
+			// priority is not changed by default
+			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()));
+		});
+	}
+
+	#[test]
+	fn validate_rejects_recently_refunded_deliveries() {
+		run_test(|| {
+			let run_validate_with_replay_guard = |call: RuntimeCall| {
+				let extension: TestExtensionWithReplayGuard =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&call,
+					&DispatchInfo::default(),
+					0,
+				)
+			};
+
+			// refund delivery of messages up to 200 at block 1
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			initialize_environment(200, 200, 200);
+			run_post_dispatch_with::<TestExtensionWithReplayGuard>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+
+			// duplicate delivery is rejected within the window
+			frame_system::Pallet::<TestRuntime>::set_block_number(5);
+			assert_eq!(
+				run_validate_with_replay_guard(message_delivery_call(200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+			assert_eq!(
+				run_validate_with_replay_guard(parachain_finality_and_delivery_batch_call(
+					200, 200
+				)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+			assert_eq!(
+				run_validate_with_replay_guard(message_delivery_call(201)),
+				Ok(ValidTransaction::default()),
+			);
+			// extension without guard accepts it
+			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()));
+
+			// and is accepted outside of the window
+			frame_system::Pallet::<TestRuntime>::set_block_number(6);
+			assert_eq!(
+				run_validate_with_replay_guard(message_delivery_call(200)),
+				Ok(ValidTransaction::default()),
+			);
+		});
+	}
+
+	#[test]
+	fn ext_rejects_batch_with_obsolete_relay_chain_header() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				run_pre_dispatch(all_finality_and_delivery_batch_call(100, 200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+
+			assert_eq!(
+				run_validate(all_finality_and_delivery_batch_call(100, 200, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+		});
+	}
+
+	#[test]
+	fn ext_rejects_batch_with_obsolete_parachain_head() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				run_pre_dispatch(all_finality_and_delivery_batch_call(101, 100, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+
+			assert_eq!(
+				run_pre_dispatch(parachain_finality_and_delivery_batch_call(100, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+
+			assert_eq!(
+				run_validate(all_finality_and_delivery_batch_call(101, 100, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+
+			assert_eq!(
+				run_validate(parachain_finality_and_delivery_batch_call(100, 200)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+		});
+	}
+
+	#[test]
+	fn ext_rejects_batch_with_obsolete_messages() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				run_pre_dispatch(all_finality_and_delivery_batch_call(200, 200, 100)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+
+			assert_eq!(
+				run_pre_dispatch(parachain_finality_and_delivery_batch_call(200, 100)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+
+			assert_eq!(
+				run_validate(all_finality_and_delivery_batch_call(200, 200, 100)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+
+			assert_eq!(
+				run_validate(parachain_finality_and_delivery_batch_call(200, 100)),
+				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_registers_batch_parse_weight() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let block_weight = || {
+				*frame_system::Pallet::<TestRuntime>::block_weight()
+					.get(frame_support::dispatch::DispatchClass::Normal)
+			};
+
+			let run_pre_dispatch_with_nested_batches = |call| {
+				let extension: TestExtensionWithNestedBatches =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&call,
+					&DispatchInfo::default(),
+					0,
+				)
+			};
+
+			// batches are not searched for nested batches if they're not supported
+			assert!(run_pre_dispatch(parachain_finality_and_delivery_batch_call(200, 200)).is_ok());
+			assert_eq!(block_weight(), Weight::zero());
+
+			// standalone calls are not parsed
+			assert!(run_pre_dispatch_with_nested_batches(message_delivery_call(200)).is_ok());
+			assert_eq!(block_weight(), Weight::zero());
+
+			// batches are
+			assert!(run_pre_dispatch_with_nested_batches(
+				parachain_finality_and_delivery_batch_call(200, 200)
+			)
+			.is_ok());
+			assert_eq!(block_weight(), <() as RefundExtensionWeightInfo>::unpack_bridge_batch(2));
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_registers_delivery_proof_verification_weight() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let block_weight = || {
+				*frame_system::Pallet::<TestRuntime>::block_weight()
+					.get(frame_support::dispatch::DispatchClass::Normal)
+			};
+
+			// proof is not verified by the extension if confirmed messages relayers are not
+			// rewarded
+			assert!(run_pre_dispatch(message_confirmation_call(200)).is_ok());
+			assert_eq!(block_weight(), Weight::zero());
+
+			// and it is verified otherwise
+			let extension: TestExtensionWithConfirmedMessagesRewards =
+				RefundRelayerForMessagesFromParachain(PhantomData);
+			assert!(extension
+				.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&message_confirmation_call(200),
+					&DispatchInfo::default(),
+					0,
+				)
+				.is_ok());
+			let proof_weight =
+				MessagesWeightInfoOf::<TestRuntime, ()>::storage_proof_size_overhead(0);
+			let db_weight = <TestRuntime as frame_system::Config>::DbWeight::get();
+			assert_eq!(block_weight(), db_weight.reads(1).saturating_add(proof_weight));
+		});
+	}
+
+	#[test]
+	fn bridge_batch_parse_weight_is_bounded() {
+		let batch_call = |calls| RuntimeCall::Utility(UtilityCall::batch_all { calls });
+		let parse_weight = |call| {
+			bridge_batch_parse_weight::<
+				_,
+				UtilityBatchCallUnpacker<TestRuntime>,
+				TestRefundConfigWithNestedBatches,
+			>(&call)
+		};
+
+		assert_eq!(parse_weight(remark_call()), None);
+		assert_eq!(
+			parse_weight(batch_call(vec![remark_call()])),
+			Some(<() as RefundExtensionWeightInfo>::unpack_bridge_batch(1)),
+		);
+		assert_eq!(
+			parse_weight(batch_call(vec![remark_call(); 2])),
+			Some(<() as RefundExtensionWeightInfo>::unpack_bridge_batch(2)),
+		);
+		// at most `MAX_NESTED_BATCH_EXTRA_CALLS + 1` calls are inspected
+		assert_eq!(
+			parse_weight(batch_call(vec![remark_call(); 100])),
+			Some(<() as RefundExtensionWeightInfo>::unpack_bridge_batch(2)),
+		);
+
+		// and the nested batch isn't searched for in large batches
+		let mut calls = vec![remark_call(); 99];
+		calls.push(batch_call(vec![message_delivery_call(200)]));
+		assert_eq!(
+			unpack_bridge_batch::<
+				_,
+				UtilityBatchCallUnpacker<TestRuntime>,
+				TestRefundConfigWithNestedBatches,
+			>(&batch_call(calls.clone())),
+			Some(&calls),
+		);
+	}
+
+	#[test]
+	fn pre_dispatch_parses_batch_with_relay_chain_and_parachain_headers() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				run_pre_dispatch(all_finality_and_delivery_batch_call(200, 200, 200)),
+				Ok(Some(all_finality_pre_dispatch_data())),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_fails_to_parse_batch_with_parachain_head_ahead_of_relay_header() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let call = all_finality_and_delivery_batch_call(200, 201, 200);
+			assert_eq!(TestExtension::parse_call_type(&call), Err(UnsupportedReason::UnknownCall));
+			assert_eq!(run_pre_dispatch(call), Ok(None));
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_parses_batch_with_parachain_header() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				run_pre_dispatch(parachain_finality_and_delivery_batch_call(200, 200)),
+				Ok(Some(parachain_finality_pre_dispatch_data())),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_parses_non_atomic_batches() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let delivery_weight = message_delivery_call(200).get_dispatch_info().weight;
+			let calls = vec![
+				submit_relay_header_call(200),
+				submit_parachain_head_call(200),
+				message_delivery_call(200),
+			];
+			let expected_pre_dispatch_data = || {
+				let mut pre_dispatch_data = all_finality_pre_dispatch_data();
+				pre_dispatch_data.non_atomic_batch_delivery_weight = Some(delivery_weight);
+				pre_dispatch_data
+			};
+
+			assert_eq!(
+				run_pre_dispatch(RuntimeCall::Utility(UtilityCall::batch { calls: calls.clone() })),
+				Ok(Some(expected_pre_dispatch_data())),
+			);
+			assert_eq!(
+				run_pre_dispatch(RuntimeCall::Utility(UtilityCall::force_batch { calls })),
+				Ok(Some(expected_pre_dispatch_data())),
+			);
+		});
+	}
+
+	#[test]
+	fn nested_batches_are_only_supported_if_enabled() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let nested_batch = |calls| RuntimeCall::Utility(UtilityCall::batch_all { calls });
+			let call = nested_batch(vec![
+				remark_call(),
+				all_finality_and_delivery_batch_call(200, 200, 200),
+			]);
+			assert_eq!(TestExtension::parse_call_type(&call), Err(UnsupportedReason::UnknownCall));
+			assert_eq!(
+				TestExtensionWithNestedBatches::prepare_refund(
+					relayer_account_at_this_chain(),
+					&call,
+				),
+				Some(all_finality_pre_dispatch_data()),
+			);
+
+			// too many other calls
+			let call = nested_batch(vec![
+				remark_call(),
+				parachain_finality_and_delivery_batch_call(200, 200),
+				remark_call(),
+			]);
+			assert_eq!(
+				TestExtensionWithNestedBatches::parse_call_type(&call),
+				Err(UnsupportedReason::UnknownCall),
+			);
+
+			// several nested batches
+			let call = nested_batch(vec![
+				parachain_finality_and_delivery_batch_call(200, 200),
+				parachain_finality_and_delivery_batch_call(200, 200),
+			]);
+			assert_eq!(
+				TestExtensionWithNestedBatches::parse_call_type(&call),
+				Err(UnsupportedReason::UnknownCall),
+			);
+
+			// nested batch must be atomic
+			let call = nested_batch(vec![
+				remark_call(),
+				RuntimeCall::Utility(UtilityCall::batch {
+					calls: vec![submit_parachain_head_call(200), message_delivery_call(200)],
+				}),
+			]);
+			assert_eq!(
+				TestExtensionWithNestedBatches::parse_call_type(&call),
+				Err(UnsupportedReason::UnknownCall),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_fails_to_parse_batch_with_multiple_parachain_headers() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let call = RuntimeCall::Utility(UtilityCall::batch_all {
+				calls: vec![
+					RuntimeCall::BridgeParachains(ParachainsCall::submit_parachain_heads {
+						at_relay_block: (100, RelayBlockHash::default()),
+						parachains: vec![
+							(ParaId(TestParachain::get()), [1u8; 32].into()),
+							(ParaId(TestParachain::get() + 1), [1u8; 32].into()),
+						],
+						parachain_heads_proof: ParaHeadsProof(vec![]),
+					}),
+					message_delivery_call(200),
+				],
+			});
+
+			assert_eq!(run_pre_dispatch(call), Ok(None),);
+		});
+	}
+
+	#[test]
+	fn ext_ignores_empty_and_single_call_batches() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			for calls in [vec![], vec![remark_call()], vec![message_delivery_call(200)]] {
+				let call = RuntimeCall::Utility(UtilityCall::batch_all { calls });
+				assert_eq!(
+					TestExtension::parse_call_type(&call),
+					Err(UnsupportedReason::UnknownCall)
+				);
+				assert_eq!(run_validate(call.clone()), Ok(ValidTransaction::default()));
+				assert_eq!(run_pre_dispatch(call), Ok(None));
+			}
+		});
+	}
+
+	#[test]
+	fn ext_refunds_relayer_in_batch_with_head_of_any_configured_parachain() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let second_para_id = ParaId(TestParachain::get() + 1);
+			let call = RuntimeCall::Utility(UtilityCall::batch_all {
+				calls: vec![
+					RuntimeCall::BridgeParachains(ParachainsCall::submit_parachain_heads {
+						at_relay_block: (200, RelayBlockHash::default()),
+						parachains: vec![(second_para_id, [1u8; 32].into())],
+						parachain_heads_proof: ParaHeadsProof(vec![]),
+					}),
+					message_delivery_call(200),
+				],
+			});
+
+			// single-parachain extension doesn't support the call
+			assert_eq!(run_pre_dispatch(call.clone()), Ok(None));
+
+			// but extension that serves both parachains does
+			let extension: TestExtensionWithTwoParachains =
+				RefundRelayerForMessagesFromParachain(PhantomData);
+			let pre_dispatch_data = extension
+				.pre_dispatch(&relayer_account_at_this_chain(), &call, &DispatchInfo::default(), 0)
+				.unwrap();
+			assert_eq!(
+				pre_dispatch_data,
+				Some(PreDispatchData {
+					relayer: relayer_account_at_this_chain(),
+					call_type: CallType::ParachainFinalityAndDelivery(
+						ExpectedParachainState {
+							para_id: second_para_id,
+							at_relay_block_number: 200
+						},
+						MessagesState { best_nonce: 100 },
+					),
+					confirmed_messages_relayers: Default::default(),
+					non_atomic_batch_delivery_weight: None,
+					bridge_calls_weight: None,
+				}),
+			);
+
+			// the head of the first parachain is not updated, but the refund must happen because
+			// the call has updated the second parachain head
+			initialize_environment(200, 100, 200);
+			pallet_bridge_parachains::ParasInfo::<TestRuntime>::insert(
+				second_para_id,
+				ParaInfo {
+					best_head_hash: BestParaHeadHash {
+						at_relay_block_number: 200,
+						head_hash: Default::default(),
+					},
+					next_imported_hash_position: 0,
+				},
+			);
+			run_post_dispatch_with::<TestExtensionWithTwoParachains>(pre_dispatch_data, Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_parses_message_delivery_transaction() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				run_pre_dispatch(message_delivery_call(200)),
+				Ok(Some(delivery_pre_dispatch_data())),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_checks_relayer_id_at_bridged_chain() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let run_pre_dispatch_with_relayer_id_check = |who, call| {
+				let extension: TestExtensionWithRelayerIdCheck =
+					RefundRelayerForMessagesFromParachain(PhantomData);
+				extension.pre_dispatch(&who, &call, &DispatchInfo::default(), 0)
+			};
+
+			// relayer accounts match => transaction is refunded
+			assert_eq!(
+				run_pre_dispatch_with_relayer_id_check(
+					relayer_account_at_this_chain(),
+					message_delivery_call(200),
+				),
+				Ok(Some(delivery_pre_dispatch_data())),
+			);
+			assert_eq!(
+				run_pre_dispatch_with_relayer_id_check(
+					relayer_account_at_this_chain(),
+					parachain_finality_and_delivery_batch_call(200, 200),
+				)
+				.map(|data| data.is_some()),
+				Ok(true),
+			);
+
+			// relayer accounts do not match => transaction is not refunded, but is not rejected
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			let other_relayer = relayer_account_at_this_chain() + 1;
+			assert_eq!(
+				run_pre_dispatch_with_relayer_id_check(other_relayer, message_delivery_call(200)),
+				Ok(None),
+			);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().map(|record| &record.event),
+				Some(&RuntimeEvent::BridgeRelayers(pallet_bridge_relayers::Event::RefundSkipped {
+					relayer: other_relayer,
+					lane_id: TestLaneId::get(),
+					reason: RefundSkipReason::RelayerIdMismatch,
+				})),
+			);
+			assert_eq!(
+				run_pre_dispatch_with_relayer_id_check(
+					other_relayer,
+					parachain_finality_and_delivery_batch_call(200, 200),
+				),
+				Ok(None),
+			);
+
+			// check is disabled by default
+			let extension: TestExtension = RefundRelayerForMessagesFromParachain(PhantomData);
+			assert!(matches!(
+				extension.pre_dispatch(
+					&other_relayer,
+					&message_delivery_call(200),
+					&DispatchInfo::default(),
+					0,
+				),
+				Ok(Some(_)),
+			));
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_checks_nonces_range_of_messages_proof() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let delivery_call_with_nonces = |nonces_start, nonces_end| {
+				RuntimeCall::BridgeMessages(MessagesCall::receive_messages_proof {
+					relayer_id_at_bridged_chain: relayer_account_at_bridged_chain(),
+					proof: FromBridgedChainMessagesProof {
+						bridged_header_hash: Default::default(),
+						storage_proof: MessagesStorageProof::Compact(CompactProof {
+							encoded_nodes: vec![],
+						}),
+						lane: TestLaneId::get(),
+						nonces_start,
+						nonces_end,
+						additional_lanes: Vec::new(),
+					},
+					messages_count: 1,
+					dispatch_weight: Weight::zero(),
+				})
+			};
+
+			// proof with inverted nonces range is never supported
+			let call = delivery_call_with_nonces(201, 200);
+			assert_eq!(TestExtension::parse_call_type(&call), Err(UnsupportedReason::UnknownCall));
+			assert_eq!(run_pre_dispatch(call), Ok(None));
+
+			// proof of single message is supported
+			assert_eq!(
+				run_pre_dispatch(delivery_call_with_nonces(200, 200)),
+				Ok(Some(delivery_pre_dispatch_data())),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_reports_delivery_over_wrong_lane() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let wrong_lane = LaneId([0, 0, 0, 1]);
+			let call = message_delivery_call_at_lane(wrong_lane, 200);
+			assert_eq!(
+				TestExtension::parse_call_type(&call),
+				Err(UnsupportedReason::WrongLane(wrong_lane)),
+			);
+			assert_eq!(run_pre_dispatch(call), Ok(None));
+
+			let call = RuntimeCall::Utility(UtilityCall::batch_all {
+				calls: vec![
+					submit_parachain_head_call(200),
+					message_delivery_call_at_lane(wrong_lane, 200),
+				],
+			});
+			assert_eq!(
+				TestExtension::parse_call_type(&call),
+				Err(UnsupportedReason::WrongLane(wrong_lane)),
+			);
+			assert_eq!(run_pre_dispatch(call), Ok(None));
+		});
+	}
+
+	#[test]
+	fn batch_parser_respects_invariants_for_all_batch_shapes() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let all_finality_and_delivery_shape =
+				vec![BatchItem::RelayHeader, BatchItem::ParachainHead, BatchItem::Delivery];
+			let parachain_finality_and_delivery_shape =
+				vec![BatchItem::ParachainHead, BatchItem::Delivery];
+
+			let shapes = all_batch_shapes(4);
+			assert_eq!(shapes.len(), 1 + 6 + 6 * 6 + 6 * 6 * 6 + 6 * 6 * 6 * 6);
+
+			for shape in shapes {
+				let call = RuntimeCall::Utility(UtilityCall::batch_all {
+					calls: shape.iter().map(BatchItem::call).collect(),
+				});
+				let parsed = TestExtension::parse_call_type(&call);
+				match parsed {
+					Ok(CallType::AllFinalityAndDelivery(..)) => assert_eq!(
+						shape, all_finality_and_delivery_shape,
+						"unexpected batch {:?} is parsed as all-finality batch",
+						shape,
+					),
+					Ok(CallType::ParachainFinalityAndDelivery(..)) => assert_eq!(
+						shape, parachain_finality_and_delivery_shape,
+						"unexpected batch {:?} is parsed as parachain-finality batch",
+						shape,
+					),
+					Ok(CallType::Delivery(..)) =>
+						panic!("batch {:?} is parsed as standalone delivery", shape),
+					Ok(CallType::DeliveryConfirmation(..)) =>
+						panic!("batch {:?} is parsed as standalone confirmation", shape),
+					Ok(CallType::RelayFinality(..)) =>
+						panic!("batch {:?} is parsed as standalone relay finality", shape),
+					Ok(CallType::ParachainFinality(..)) =>
+						panic!("batch {:?} is parsed as standalone parachain finality", shape),
+					Ok(CallType::RelayFinalityAndDelivery(..)) =>
+						panic!("batch {:?} is parsed as relay finality and delivery batch", shape),
+					Err(_) => assert!(
+						shape != all_finality_and_delivery_shape &&
+							shape != parachain_finality_and_delivery_shape,
+						"supported batch {:?} is not recognized",
+						shape,
+					),
+				}
+
+				if shape.contains(&BatchItem::WrongLaneDelivery) {
+					assert!(
+						parsed.is_err(),
+						"batch {:?} with wrong lane delivery is parsed as {:?}",
+						shape,
+						parsed,
+					);
+				}
+			}
+		});
+	}
+
+	#[test]
+	fn classify_call_structure_works_without_storage() {
+		// this test is not using `run_test`, so it panics if the storage is accessed
+		assert_eq!(
+			TestExtension::classify_call_structure(&all_finality_and_delivery_batch_call(
+				200, 200, 200
+			)),
+			Some(StructuralCallType::AllFinalityAndDelivery(
+				ExpectedRelayChainState { best_block_number: 200 },
+				ExpectedParachainState {
+					para_id: ParaId(TestParachain::get()),
+					at_relay_block_number: 200,
+				},
+			)),
+		);
+		assert_eq!(
+			TestExtension::classify_call_structure(&parachain_finality_and_delivery_batch_call(
+				200, 200
+			)),
+			Some(StructuralCallType::ParachainFinalityAndDelivery(ExpectedParachainState {
+				para_id: ParaId(TestParachain::get()),
+				at_relay_block_number: 200,
+			})),
+		);
+		assert_eq!(
+			TestExtension::classify_call_structure(&message_delivery_call(200)),
+			Some(StructuralCallType::Delivery),
+		);
+		assert_eq!(
+			TestExtension::classify_call_structure(&message_confirmation_call(200)),
+			Some(StructuralCallType::DeliveryConfirmation),
+		);
+		assert_eq!(
+			TestExtension::classify_call_structure(&message_delivery_call_at_lane(
+				LaneId([0, 0, 0, 1]),
+				200
+			)),
+			None,
+		);
+		assert_eq!(TestExtension::classify_call_structure(&remark_call()), None);
+	}
+
+	#[test]
+	fn pre_dispatch_without_utility_ignores_batches() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let extension: TestExtensionWithoutUtility =
+				RefundRelayerForMessagesFromParachain(PhantomData);
+			assert_eq!(
+				extension.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&parachain_finality_and_delivery_batch_call(200, 200),
+					&DispatchInfo::default(),
+					0,
+				),
+				Ok(None),
+			);
+		});
+	}
+
+	#[test]
+	fn ext_without_utility_refunds_relayer_in_message_delivery_transaction() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let extension: TestExtensionWithoutUtility =
+				RefundRelayerForMessagesFromParachain(PhantomData);
+			let pre_dispatch_data = extension
+				.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&message_delivery_call(200),
+					&DispatchInfo::default(),
+					0,
+				)
+				.unwrap();
+			assert_eq!(pre_dispatch_data, Some(delivery_pre_dispatch_data()));
+
+			initialize_environment(100, 100, 200);
+			assert_eq!(
+				TestExtensionWithoutUtility::post_dispatch(
+					Some(pre_dispatch_data),
+					&dispatch_info(),
+					&post_dispatch_info(),
+					1024,
+					&Ok(()),
+				),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_unknown_transaction() {
+		run_test(|| {
+			assert_storage_noop!(run_post_dispatch(None, Ok(())));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_failed_transaction() {
+		run_test(|| {
+			assert_storage_noop!(run_post_dispatch(
+				Some(all_finality_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin)
+			));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_transaction_that_has_not_updated_relay_chain_state() {
+		run_test(|| {
+			initialize_environment(100, 200, 200);
+
+			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_transaction_that_has_not_updated_parachain_state() {
+		run_test(|| {
+			initialize_environment(200, 100, 200);
+
+			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
+			assert_storage_noop!(run_post_dispatch(
+				Some(parachain_finality_pre_dispatch_data()),
+				Ok(())
+			));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_transaction_that_has_not_delivered_any_messages() {
+		run_test(|| {
+			initialize_environment(200, 200, 100);
+
+			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
+			assert_storage_noop!(run_post_dispatch(
+				Some(parachain_finality_pre_dispatch_data()),
+				Ok(())
+			));
+			assert_storage_noop!(run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(())));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_flags_transactions_that_have_not_advanced_bridge_state() {
+		run_test(|| {
+			initialize_environment(200, 200, 100);
+			set_best_confirmed_message(100);
+
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::misbehavior_score(relayer_account_at_this_chain()),
+				0,
+			);
+
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(confirmation_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::misbehavior_score(relayer_account_at_this_chain()),
+				2,
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
+			);
+
+			// failed transactions are not flagged
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(delivery_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::misbehavior_score(relayer_account_at_this_chain()),
+				2,
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_relayer_in_all_finality_batch() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_delivery_portion_of_non_atomic_batch() {
+		run_test(|| {
+			initialize_environment(100, 200, 200);
+
+			// finality calls of the batch have failed, but messages have been delivered
+			let delivery_weight = Weight::from_ref_time(dispatch_info().weight.ref_time() / 4);
+			let mut pre_dispatch_data = all_finality_pre_dispatch_data();
+			pre_dispatch_data.non_atomic_batch_delivery_weight = Some(delivery_weight);
+			run_post_dispatch(Some(pre_dispatch_data), Ok(()));
+
+			let expected_delivery_reward =
+				pallet_transaction_payment::Pallet::<TestRuntime>::compute_actual_fee(
+					1024,
+					&DispatchInfo { weight: delivery_weight, ..dispatch_info() },
+					&post_dispatch_info(),
+					Zero::zero(),
+				);
+			assert!(expected_delivery_reward < expected_reward());
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_delivery_reward),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_whole_non_atomic_batch_if_all_calls_have_succeeded() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			let mut pre_dispatch_data = all_finality_pre_dispatch_data();
+			pre_dispatch_data.non_atomic_batch_delivery_weight =
+				Some(Weight::from_ref_time(dispatch_info().weight.ref_time() / 4));
+			run_post_dispatch(Some(pre_dispatch_data), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_non_atomic_batch_that_has_not_delivered_any_messages() {
+		run_test(|| {
+			initialize_environment(100, 200, 100);
+
+			let mut pre_dispatch_data = all_finality_pre_dispatch_data();
+			pre_dispatch_data.non_atomic_batch_delivery_weight =
+				Some(Weight::from_ref_time(dispatch_info().weight.ref_time() / 4));
+			assert_storage_noop!(run_post_dispatch(Some(pre_dispatch_data), Ok(())));
+		});
+	}
+
+	#[test]
+	fn relay_finality_transactions_are_only_supported_if_enabled() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				TestExtension::parse_call_type(&submit_relay_header_call(200)),
+				Err(UnsupportedReason::UnknownCall),
+			);
+			assert_eq!(
+				TestExtensionWithRelayFinality::parse_call_type(&submit_relay_header_call(200)),
+				Ok(CallType::RelayFinality(ExpectedRelayChainState { best_block_number: 200 })),
+			);
+			assert_eq!(
+				TestExtensionWithRelayFinality::prepare_refund(
+					relayer_account_at_this_chain(),
+					&submit_relay_header_call(200),
+				),
+				Some(relay_finality_pre_dispatch_data()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_relayer_for_relay_finality_transaction() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch_with::<TestExtensionWithRelayFinality>(
+				Some(relay_finality_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_relay_finality_transaction_if_header_is_not_imported() {
+		run_test(|| {
+			initialize_environment(100, 200, 200);
+
+			assert_storage_noop!(run_post_dispatch_with::<TestExtensionWithRelayFinality>(
+				Some(relay_finality_pre_dispatch_data()),
+				Ok(()),
+			));
+		});
+	}
+
+	#[test]
+	fn parachain_finality_transactions_are_only_supported_if_enabled() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			assert_eq!(
+				TestExtension::parse_call_type(&submit_parachain_head_call(200)),
+				Err(UnsupportedReason::UnknownCall),
+			);
+			assert_eq!(
+				TestExtensionWithParachainFinality::prepare_refund(
+					relayer_account_at_this_chain(),
+					&submit_parachain_head_call(200),
+				),
+				Some(standalone_parachain_finality_pre_dispatch_data()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_relayer_for_parachain_finality_transaction() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch_with::<TestExtensionWithParachainFinality>(
+				Some(standalone_parachain_finality_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_parachain_finality_transaction_if_head_is_not_updated() {
+		run_test(|| {
+			initialize_environment(200, 100, 200);
+
+			assert_storage_noop!(run_post_dispatch_with::<TestExtensionWithParachainFinality>(
+				Some(standalone_parachain_finality_pre_dispatch_data()),
+				Ok(()),
+			));
+		});
+	}
+
+	#[test]
+	fn batch_with_several_relay_headers_is_refunded_if_allowed() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let call = RuntimeCall::Utility(UtilityCall::batch_all {
+				calls: vec![
+					submit_relay_header_call(150),
+					submit_relay_header_call(200),
+					submit_parachain_head_call(200),
+					message_delivery_call(200),
+				],
+			});
+			assert_eq!(TestExtension::parse_call_type(&call), Err(UnsupportedReason::UnknownCall));
+			let pre_dispatch_data = TestExtensionWithTwoRelayHeaders::prepare_refund(
+				relayer_account_at_this_chain(),
+				&call,
+			);
+			assert_eq!(pre_dispatch_data, Some(all_finality_pre_dispatch_data()));
+
+			// relay chain state is checked using the last header
+			initialize_environment(200, 200, 200);
+			run_post_dispatch_with::<TestExtensionWithTwoRelayHeaders>(pre_dispatch_data, Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_relayer_in_parachain_finality_batch() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch(Some(parachain_finality_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_relayer_in_message_delivery_transaction() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
+	}
+
+	#[test]
+	fn pre_dispatch_parses_message_confirmation_transaction() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+			set_best_confirmed_message(100);
+
+			assert_eq!(
+				run_pre_dispatch(message_confirmation_call(200)),
+				Ok(Some(confirmation_pre_dispatch_data())),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_ignores_transaction_that_has_not_confirmed_any_messages() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			set_best_confirmed_message(100);
+
+			assert_storage_noop!(run_post_dispatch(Some(confirmation_pre_dispatch_data()), Ok(())));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_confirmation_relayer() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			set_best_confirmed_message(200);
+
+			let mut pre_dispatch_data = confirmation_pre_dispatch_data();
+			pre_dispatch_data.confirmed_messages_relayers =
+				vec![(1, 60), (2, 40)].into_iter().collect();
+			run_post_dispatch(Some(pre_dispatch_data), Ok(()));
+
+			// only confirmation relayer is refunded by default
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					confirmation_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(1, delivery_rewards_account_params()),
+				None
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(2, delivery_rewards_account_params()),
+				None
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_rewards_both_confirmation_relayer_and_messages_relayers() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			set_best_confirmed_message(200);
+
+			let mut pre_dispatch_data = confirmation_pre_dispatch_data();
+			pre_dispatch_data.confirmed_messages_relayers =
+				vec![(relayer_account_at_this_chain(), 20), (1, 50), (2, 30)]
+					.into_iter()
+					.collect();
+			run_post_dispatch_with::<TestExtensionWithConfirmedMessagesRewards>(
+				Some(pre_dispatch_data),
+				Ok(()),
+			);
+
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					confirmation_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(20 * 10),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(1, delivery_rewards_account_params()),
+				Some(50 * 10),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(2, delivery_rewards_account_params()),
+				Some(30 * 10),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_does_not_refund_relayer_if_fee_calculation_declines_refund() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			assert_storage_noop!(run_post_dispatch_with::<TestExtensionWithFee<DeclinedFee>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_registers_zero_reward_if_fee_calculation_returns_zero() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			// zero rewards are ignored by the relayers pallet
+			run_post_dispatch_with::<TestExtensionWithFee<ZeroFee>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_registers_reward_computed_by_fee_calculation() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch_with::<TestExtensionWithFee<NonZeroFee>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(42),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_applies_lane_reward_multiplier() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			pallet_bridge_relayers::LaneRewardMultiplier::<TestRuntime>::insert(
+				delivery_rewards_account_params(),
+				FixedU128::from_rational(3, 2),
+			);
+			run_post_dispatch_with::<TestExtensionWithFee<NonZeroFee>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(63),
+			);
+
+			pallet_bridge_relayers::LaneRewardMultiplier::<TestRuntime>::insert(
+				delivery_rewards_account_params(),
+				FixedU128::from_rational(1, 2),
+			);
+			run_post_dispatch_with::<TestExtensionWithFee<NonZeroFee>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(63 + 21),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_applies_reward_multiplier_of_the_rewards_account() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			set_best_confirmed_message(200);
+
+			pallet_bridge_relayers::LaneRewardMultiplier::<TestRuntime>::insert(
+				confirmation_rewards_account_params(),
+				FixedU128::from_rational(3, 2),
+			);
+			run_post_dispatch_with::<TestExtensionWithFee<NonZeroFee>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			run_post_dispatch_with::<TestExtensionWithFee<NonZeroFee>>(
+				Some(confirmation_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(42),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					confirmation_rewards_account_params()
+				),
+				Some(63),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_does_not_register_reward_in_shadow_mode() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			run_post_dispatch_with::<TestExtensionInShadowMode>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
+			);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().map(|record| &record.event),
+				Some(&RuntimeEvent::BridgeRelayers(pallet_bridge_relayers::Event::ShadowReward {
+					relayer: relayer_account_at_this_chain(),
+					rewards_account_params: delivery_rewards_account_params(),
+					reward: expected_reward(),
+				})),
+			);
+		});
+	}
+
+	#[test]
+	fn refund_skipped_events_are_deposited() {
+		run_test(|| {
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			let last_skip_reason = || {
+				frame_system::Pallet::<TestRuntime>::events()
+					.last()
+					.and_then(|record| match record.event {
+						RuntimeEvent::BridgeRelayers(
+							pallet_bridge_relayers::Event::RefundSkipped {
+								ref relayer,
+								lane_id,
+								reason,
+							},
+						) if *relayer == relayer_account_at_this_chain() &&
+							lane_id == TestLaneId::get() =>
+							Some(reason),
+						_ => None,
+					})
+			};
+
+			initialize_environment(100, 200, 200);
+			run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(()));
+			assert_eq!(last_skip_reason(), Some(RefundSkipReason::StateNotAdvanced));
+
+			initialize_environment(200, 200, 100);
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(last_skip_reason(), Some(RefundSkipReason::NoNewMessages));
+
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Err(DispatchError::BadOrigin));
+			assert_eq!(last_skip_reason(), Some(RefundSkipReason::DispatchFailed));
+
+			let call = RuntimeCall::Utility(UtilityCall::batch_all {
+				calls: vec![remark_call(), message_delivery_call(200)],
+			});
+			assert_eq!(run_pre_dispatch(call), Ok(None));
+			assert_eq!(last_skip_reason(), Some(RefundSkipReason::UnrecognizedCall));
+		});
+	}
+
+	#[test]
+	fn post_dispatch_counts_obsolete_transactions_of_relayer() {
+		run_test(|| {
+			let invalid_submissions = || {
+				RelayersPallet::<TestRuntime>::invalid_submissions(relayer_account_at_this_chain())
+			};
+
+			// obsolete transactions are not counted if no-op transactions are not tracked
+			initialize_environment(200, 200, 100);
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(invalid_submissions(), 0);
+
+			// obsolete transactions are counted
+			initialize_environment(100, 200, 200);
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(all_finality_pre_dispatch_data()),
+				Ok(()),
+			);
+			initialize_environment(200, 100, 200);
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(parachain_finality_pre_dispatch_data()),
+				Ok(()),
+			);
+			initialize_environment(200, 200, 100);
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(invalid_submissions(), 3);
+
+			// failed and valid transactions are not counted
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(delivery_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin),
+			);
+			initialize_environment(200, 200, 200);
+			run_post_dispatch_with::<TestExtensionWithNoOpTracking>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(invalid_submissions(), 3);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_deposits_refund_event() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().map(|record| &record.event),
+				Some(&RuntimeEvent::BridgeRelayers(
+					pallet_bridge_relayers::Event::RelayerRefunded {
+						relayer: relayer_account_at_this_chain(),
+						lane_id: TestLaneId::get(),
+						reward: expected_reward(),
+						bridged_equivalent_reward: None,
+					}
+				)),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_deposits_bridged_equivalent_reward() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+
+			run_post_dispatch_with::<TestExtensionWithBridgedFeeConversion>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+
+			// the registered reward is not changed
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().map(|record| &record.event),
+				Some(&RuntimeEvent::BridgeRelayers(
+					pallet_bridge_relayers::Event::RelayerRefunded {
+						relayer: relayer_account_at_this_chain(),
+						lane_id: TestLaneId::get(),
+						reward: expected_reward(),
+						bridged_equivalent_reward: Some(expected_reward() * 5 / 2),
+					}
+				)),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_pays_reward_immediately() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			let rewards_account = bp_relayers::PayLaneRewardFromAccount::<
+				Balances,
+				ThisChainAccountId,
+			>::lane_rewards_account(TestLaneId::get());
+			Balances::make_free_balance_be(&rewards_account, 2 * expected_reward());
+
+			run_post_dispatch_with::<TestExtensionWithImmediatePayout>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
+			);
+			assert_eq!(Balances::free_balance(relayer_account_at_this_chain()), expected_reward());
+			assert_eq!(Balances::free_balance(rewards_account), expected_reward());
+		});
+	}
+
+	#[test]
+	fn post_dispatch_routes_refund_through_refund_handler() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch_with::<TestExtensionWithRefundHandler>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
+			);
+			assert_eq!(
+				frame_support::storage::unhashed::get(b"TestOnRefund"),
+				Some((relayer_account_at_this_chain(), expected_reward())),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_registers_reward_if_immediate_payout_fails() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			// rewards account has no funds
+			run_post_dispatch_with::<TestExtensionWithImmediatePayout>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+			assert_eq!(Balances::free_balance(relayer_account_at_this_chain()), 0);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_reports_failed_reward_registration() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			// the reward can't be registered, because it would overflow
+			pallet_bridge_relayers::RelayerRewards::<TestRuntime>::insert(
+				relayer_account_at_this_chain(),
+				delivery_rewards_account_params(),
+				ThisChainBalance::MAX,
+			);
+
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(ThisChainBalance::MAX),
+			);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().map(|record| &record.event),
+				Some(&RuntimeEvent::BridgeRelayers(
+					pallet_bridge_relayers::Event::RewardRegistrationFailed {
+						relayer: relayer_account_at_this_chain(),
+						rewards_account_params: delivery_rewards_account_params(),
+						reward: expected_reward(),
+					}
+				)),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_part_of_the_fee() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			run_post_dispatch_with::<
+				TestExtensionWithFeeCalculation<ProportionalRefund<TestRuntime, HalfRefund>>,
+			>(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward() / 2),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_caps_the_refund() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			assert!(expected_reward() > MaxRefund::get());
+
+			run_post_dispatch_with::<
+				TestExtensionWithFeeCalculation<CappedRefund<TestRuntime, MaxRefund>>,
+			>(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(MaxRefund::get()),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_refunds_tip_up_to_the_cap() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			// small tip is refunded in full
+			TestTip::set(5);
+			run_post_dispatch_with::<
+				TestExtensionWithFeeCalculation<TipRefund<TestRuntime, TestTip, MaxRefundableTip>>,
+			>(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward() + 5),
+			);
+
+			// larger tip is clamped
+			TestTip::set(1_000);
+			run_post_dispatch_with::<
+				TestExtensionWithFeeCalculation<TipRefund<TestRuntime, TestTip, MaxRefundableTip>>,
+			>(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(2 * expected_reward() + 5 + MaxRefundableTip::get()),
+			);
+		});
+	}
+
+	#[test]
+	fn bridge_calls_weight_is_only_computed_if_required() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let call = all_finality_and_delivery_batch_call(200, 200, 200);
+			assert_eq!(
+				TestExtension::prepare_refund(relayer_account_at_this_chain(), &call)
+					.map(|data| data.bridge_calls_weight),
+				Some(None),
+			);
+
+			let expected_bridge_calls_weight = submit_relay_header_call(200)
+				.get_dispatch_info()
+				.weight
+				.saturating_add(submit_parachain_head_call(200).get_dispatch_info().weight)
+				.saturating_add(message_delivery_call(200).get_dispatch_info().weight);
+			assert_eq!(
+				TestExtensionWithFeeCalculation::<BridgeCallsWeightRefund<TestRuntime>>::prepare_refund(
+					relayer_account_at_this_chain(),
+					&call,
+				)
+				.map(|data| data.bridge_calls_weight),
+				Some(Some(expected_bridge_calls_weight)),
+			);
+		});
+	}
+
+	#[test]
+	fn post_dispatch_only_refunds_bridge_calls_weight() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			let bridge_calls_weight = Weight::from_ref_time(dispatch_info().weight.ref_time() / 4);
+			let mut pre_dispatch_data = delivery_pre_dispatch_data();
+			pre_dispatch_data.bridge_calls_weight = Some(bridge_calls_weight);
+			run_post_dispatch_with::<
+				TestExtensionWithFeeCalculation<BridgeCallsWeightRefund<TestRuntime>>,
+			>(Some(pre_dispatch_data), Ok(()));
+
+			let expected_bridge_calls_reward =
+				pallet_transaction_payment::Pallet::<TestRuntime>::compute_actual_fee(
+					1024,
+					&DispatchInfo { weight: bridge_calls_weight, ..dispatch_info() },
+					&post_dispatch_info(),
+					Zero::zero(),
+				);
+			assert!(expected_bridge_calls_reward < expected_reward());
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_bridge_calls_reward),
+			);
+		});
 	}
 
-	type TestExtension = RefundRelayerForMessagesFromParachain<
-		TestRuntime,
-		(),
-		(),
-		(),
-		BridgeRejectObsoleteHeadersAndMessages,
-		TestParachain,
-		TestLaneId,
-		TestRuntime,
-	>;
+	#[test]
+	fn post_dispatch_converts_fee_to_reward_type() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			// fee that fits the reward type is registered as is
+			run_post_dispatch_with::<TestExtensionWithFee<NonZeroWideFee, u128>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(42),
+			);
+		});
+
+		run_test(|| {
+			initialize_environment(100, 100, 100);
 
-	fn relayer_account_at_this_chain() -> ThisChainAccountId {
-		0
+			// fee that doesn't fit the reward type is saturated
+			run_post_dispatch_with::<TestExtensionWithFee<OverflowingWideFee, u128>>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(ThisChainBalance::MAX),
+			);
+		});
 	}
 
-	fn relayer_account_at_bridged_chain() -> BridgedChainAccountId {
-		0
+	#[test]
+	fn post_dispatch_checks_refund_eligibility() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			assert_storage_noop!(run_post_dispatch_with::<TestExtensionWithEligibilityCheck>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			));
+
+			frame_system::Pallet::<TestRuntime>::set_block_number(2);
+			run_post_dispatch_with::<TestExtensionWithEligibilityCheck>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
 	}
 
-	fn initialize_environment(
-		best_relay_header_number: RelayBlockNumber,
-		parachain_head_at_relay_header_number: RelayBlockNumber,
-		best_delivered_message: MessageNonce,
-	) {
-		let best_relay_header = HeaderId(best_relay_header_number, RelayBlockHash::default());
-		pallet_bridge_grandpa::BestFinalized::<TestRuntime>::put(best_relay_header);
+	#[test]
+	fn post_dispatch_only_refunds_registered_relayers_if_configured() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
 
-		let para_id = ParaId(TestParachain::get());
-		let para_info = ParaInfo {
-			best_head_hash: BestParaHeadHash {
-				at_relay_block_number: parachain_head_at_relay_header_number,
-				head_hash: Default::default(),
-			},
-			next_imported_hash_position: 0,
-		};
-		pallet_bridge_parachains::ParasInfo::<TestRuntime>::insert(para_id, para_info);
+			// unregistered relayer is not refunded
+			run_post_dispatch_with::<TestExtensionWithRegisteredRelayers>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
+			);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().map(|r| r.event.clone()),
+				Some(RuntimeEvent::BridgeRelayers(pallet_bridge_relayers::Event::RefundSkipped {
+					relayer: relayer_account_at_this_chain(),
+					lane_id: TestLaneId::get(),
+					reason: RefundSkipReason::RelayerNotRegistered,
+				})),
+			);
 
-		let lane_id = TestLaneId::get();
-		let lane_data =
-			InboundLaneData { last_confirmed_nonce: best_delivered_message, ..Default::default() };
-		pallet_bridge_messages::InboundLanes::<TestRuntime>::insert(lane_id, lane_data);
+			// registered relayer is refunded
+			pallet_bridge_relayers::RegisteredRelayers::<TestRuntime>::insert(
+				relayer_account_at_this_chain(),
+				100,
+			);
+			run_post_dispatch_with::<TestExtensionWithRegisteredRelayers>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
 	}
 
-	fn submit_relay_header_call(relay_header_number: RelayBlockNumber) -> RuntimeCall {
-		let relay_header = BridgedChainHeader::new(
-			relay_header_number,
-			Default::default(),
-			Default::default(),
-			Default::default(),
-			Default::default(),
-		);
-		let relay_justification = make_default_justification(&relay_header);
+	#[test]
+	fn post_dispatch_slashes_misbehaving_registered_relayers_if_configured() {
+		run_test(|| {
+			let relayer = relayer_account_at_this_chain();
+			let register_relayer = || {
+				RelayersPallet::<TestRuntime>::register(RuntimeOrigin::signed(relayer)).unwrap();
+			};
+			Balances::make_free_balance_be(&relayer, 1_000);
 
-		RuntimeCall::BridgeGrandpa(GrandpaCall::submit_finality_proof {
-			finality_target: Box::new(relay_header),
-			justification: relay_justification,
-		})
-	}
+			// relayer is not slashed by default
+			register_relayer();
+			initialize_environment(200, 200, 100);
+			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			assert!(RelayersPallet::<TestRuntime>::is_registration_active(&relayer));
 
-	fn submit_parachain_head_call(
-		parachain_head_at_relay_header_number: RelayBlockNumber,
-	) -> RuntimeCall {
-		RuntimeCall::BridgeParachains(ParachainsCall::submit_parachain_heads {
-			at_relay_block: (parachain_head_at_relay_header_number, RelayBlockHash::default()),
-			parachains: vec![(ParaId(TestParachain::get()), [1u8; 32].into())],
-			parachain_heads_proof: ParaHeadsProof(vec![]),
-		})
-	}
+			// relayer is slashed if the transaction has not delivered any new messages
+			run_post_dispatch_with::<TestExtensionWithSlashing>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(RelayersPallet::<TestRuntime>::relayer_stake(relayer), None);
+			assert_eq!(Balances::reserved_balance(&relayer), 0);
+			assert_eq!(Balances::free_balance(&relayer), 950);
 
-	fn message_delivery_call(best_message: MessageNonce) -> RuntimeCall {
-		RuntimeCall::BridgeMessages(MessagesCall::receive_messages_proof {
-			relayer_id_at_bridged_chain: relayer_account_at_bridged_chain(),
-			proof: FromBridgedChainMessagesProof {
-				bridged_header_hash: Default::default(),
-				storage_proof: vec![],
-				lane: TestLaneId::get(),
-				nonces_start: best_message,
-				nonces_end: best_message,
-			},
-			messages_count: 1,
-			dispatch_weight: Weight::zero(),
-		})
-	}
+			// relayer is slashed if the transaction has failed
+			register_relayer();
+			run_post_dispatch_with::<TestExtensionWithSlashing>(
+				Some(delivery_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin),
+			);
+			assert_eq!(RelayersPallet::<TestRuntime>::relayer_stake(relayer), None);
+			assert_eq!(Balances::free_balance(&relayer), 900);
 
-	fn parachain_finality_and_delivery_batch_call(
-		parachain_head_at_relay_header_number: RelayBlockNumber,
-		best_message: MessageNonce,
-	) -> RuntimeCall {
-		RuntimeCall::Utility(UtilityCall::batch_all {
-			calls: vec![
-				submit_parachain_head_call(parachain_head_at_relay_header_number),
-				message_delivery_call(best_message),
-			],
-		})
-	}
+			// unregistered relayer is not slashed
+			run_post_dispatch_with::<TestExtensionWithSlashing>(
+				Some(delivery_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin),
+			);
+			assert_eq!(Balances::free_balance(&relayer), 900);
 
-	fn all_finality_and_delivery_batch_call(
-		relay_header_number: RelayBlockNumber,
-		parachain_head_at_relay_header_number: RelayBlockNumber,
-		best_message: MessageNonce,
-	) -> RuntimeCall {
-		RuntimeCall::Utility(UtilityCall::batch_all {
-			calls: vec![
-				submit_relay_header_call(relay_header_number),
-				submit_parachain_head_call(parachain_head_at_relay_header_number),
-				message_delivery_call(best_message),
-			],
-		})
+			// registered relayer is not slashed for useful transactions
+			register_relayer();
+			initialize_environment(200, 200, 200);
+			run_post_dispatch_with::<TestExtensionWithSlashing>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert!(RelayersPallet::<TestRuntime>::is_registration_active(&relayer));
+		});
 	}
 
-	fn all_finality_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
-		PreDispatchData {
-			relayer: relayer_account_at_this_chain(),
-			call_type: CallType::AllFinalityAndDelivery(
-				ExpectedRelayChainState { best_block_number: 200 },
-				ExpectedParachainState { at_relay_block_number: 200 },
-				MessagesState { best_nonce: 100 },
-			),
-		}
-	}
+	#[test]
+	fn logs_contain_bridge_name() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
 
-	fn parachain_finality_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
-		PreDispatchData {
-			relayer: relayer_account_at_this_chain(),
-			call_type: CallType::ParachainFinalityAndDelivery(
-				ExpectedParachainState { at_relay_block_number: 200 },
-				MessagesState { best_nonce: 100 },
-			),
-		}
-	}
+			let logs = capture_logs(|| {
+				run_post_dispatch_with::<TestExtension>(Some(delivery_pre_dispatch_data()), Ok(()))
+			});
+			assert!(logs.iter().any(|log| log.contains(&format!(
+				"from parachains {:?} via {:?}",
+				TestParachains::get(),
+				TestLaneId::get()
+			))));
+			assert!(!logs.iter().any(|log| log.contains("TestBridge")));
 
-	fn delivery_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
-		PreDispatchData {
-			relayer: relayer_account_at_this_chain(),
-			call_type: CallType::Delivery(MessagesState { best_nonce: 100 }),
-		}
+			let logs = capture_logs(|| {
+				run_post_dispatch_with::<TestExtensionWithBridgeName>(
+					Some(delivery_pre_dispatch_data()),
+					Ok(()),
+				)
+			});
+			assert!(logs.iter().any(|log| log.contains("of TestBridge bridge")));
+		});
 	}
 
-	fn run_test(test: impl FnOnce()) {
-		sp_io::TestExternalities::new(Default::default()).execute_with(test)
-	}
+	#[test]
+	fn estimated_refund_matches_actual_refund() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
 
-	fn run_validate(call: RuntimeCall) -> TransactionValidity {
-		let extension: TestExtension = RefundRelayerForMessagesFromParachain(PhantomData);
-		extension.validate(&relayer_account_at_this_chain(), &call, &DispatchInfo::default(), 0)
+			let call = message_delivery_call(200);
+			let mut estimated_refund = None;
+			assert_storage_noop!(
+				estimated_refund =
+					TestExtension::estimated_refund(relayer_account_at_this_chain(), &call, 1024)
+			);
+			assert!(estimated_refund.is_some());
+			assert_eq!(
+				TestExtension::estimated_refund(
+					relayer_account_at_this_chain(),
+					&remark_call(),
+					1024
+				),
+				None,
+			);
+
+			// dispatch the call and check that the actual refund matches the estimation
+			let pre_dispatch_data = run_pre_dispatch(call.clone()).unwrap();
+			initialize_environment(200, 200, 200);
+			let info = call.get_dispatch_info();
+			assert_eq!(
+				TestExtension::post_dispatch(
+					Some(pre_dispatch_data),
+					&info,
+					&PostDispatchInfo { actual_weight: None, pays_fee: info.pays_fee },
+					1024,
+					&Ok(()),
+				),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				estimated_refund,
+			);
+		});
 	}
 
-	fn run_pre_dispatch(
-		call: RuntimeCall,
-	) -> Result<Option<PreDispatchData<ThisChainAccountId>>, TransactionValidityError> {
-		let extension: TestExtension = RefundRelayerForMessagesFromParachain(PhantomData);
-		extension.pre_dispatch(&relayer_account_at_this_chain(), &call, &DispatchInfo::default(), 0)
+	#[test]
+	fn refund_dispatched_call_refunds_relayer() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+
+			let pre_dispatch_data = TestExtension::prepare_refund(
+				relayer_account_at_this_chain(),
+				&message_delivery_call(200),
+			)
+			.unwrap();
+			assert_eq!(pre_dispatch_data, delivery_pre_dispatch_data());
+			assert_eq!(
+				TestExtension::prepare_refund(relayer_account_at_this_chain(), &remark_call()),
+				None
+			);
+
+			// the call is dispatched (e.g. by the scheduler) and then the relayer is refunded
+			initialize_environment(100, 100, 200);
+			assert_eq!(
+				TestExtension::refund_dispatched_call(
+					pre_dispatch_data,
+					&dispatch_info(),
+					&post_dispatch_info(),
+					1024,
+					&Ok(()),
+				),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
 	}
 
-	fn dispatch_info() -> DispatchInfo {
-		DispatchInfo {
-			weight: Weight::from_ref_time(
-				frame_support::weights::constants::WEIGHT_REF_TIME_PER_SECOND,
-			),
-			class: frame_support::dispatch::DispatchClass::Normal,
-			pays_fee: frame_support::dispatch::Pays::Yes,
-		}
+	#[test]
+	fn post_dispatch_collects_relayer_statistics_if_configured() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			// statistics are not collected by default
+			run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(()));
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_statistics(relayer_account_at_this_chain()),
+				Default::default(),
+			);
+
+			// delivered messages, submitted headers and refunds are counted
+			run_post_dispatch_with::<TestExtensionWithStatistics>(
+				Some(all_finality_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_statistics(relayer_account_at_this_chain()),
+				bp_relayers::RelayerStatistics {
+					delivered_messages: 100,
+					submitted_headers: 2,
+					total_rewards: expected_reward(),
+					failed_refunds: 0,
+				},
+			);
+
+			// failed refunds are counted
+			run_post_dispatch_with::<TestExtensionWithStatistics>(
+				Some(all_finality_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_statistics(relayer_account_at_this_chain())
+					.failed_refunds,
+				1,
+			);
+		});
 	}
 
-	fn post_dispatch_info() -> PostDispatchInfo {
-		PostDispatchInfo { actual_weight: None, pays_fee: frame_support::dispatch::Pays::Yes }
+	#[test]
+	fn post_dispatch_withholds_refund_within_cooldown() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
+
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			run_post_dispatch_with::<TestExtensionWithCooldown>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+
+			frame_system::Pallet::<TestRuntime>::set_block_number(2);
+			run_post_dispatch_with::<TestExtensionWithCooldown>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
+			);
+		});
 	}
 
-	fn run_post_dispatch(
-		pre_dispatch_data: Option<PreDispatchData<ThisChainAccountId>>,
-		dispatch_result: DispatchResult,
-	) {
-		let post_dispatch_result = TestExtension::post_dispatch(
-			Some(pre_dispatch_data),
-			&dispatch_info(),
-			&post_dispatch_info(),
-			1024,
-			&dispatch_result,
-		);
-		assert_eq!(post_dispatch_result, Ok(()));
-	}
+	#[test]
+	fn post_dispatch_refunds_relayer_after_cooldown() {
+		run_test(|| {
+			initialize_environment(200, 200, 200);
 
-	fn expected_reward() -> ThisChainBalance {
-		pallet_transaction_payment::Pallet::<TestRuntime>::compute_actual_fee(
-			1024,
-			&dispatch_info(),
-			&post_dispatch_info(),
-			Zero::zero(),
-		)
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			run_post_dispatch_with::<TestExtensionWithCooldown>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+
+			frame_system::Pallet::<TestRuntime>::set_block_number(6);
+			run_post_dispatch_with::<TestExtensionWithCooldown>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(2 * expected_reward()),
+			);
+		});
 	}
 
 	#[test]
-	fn validate_allows_non_obsolete_transactions() {
+	fn multi_bridge_extension_recognizes_transactions_of_all_bridges() {
 		run_test(|| {
 			initialize_environment(100, 100, 100);
 
-			assert_eq!(run_validate(message_delivery_call(200)), Ok(ValidTransaction::default()),);
+			let run_multi_bridge_pre_dispatch = |call: RuntimeCall| {
+				let extension: TestMultiBridgeExtension =
+					RefundRelayerForMessagesFromParachains(PhantomData);
+				extension.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&call,
+					&DispatchInfo::default(),
+					0,
+				)
+			};
 
 			assert_eq!(
-				run_validate(parachain_finality_and_delivery_batch_call(200, 200)),
-				Ok(ValidTransaction::default()),
+				run_multi_bridge_pre_dispatch(message_delivery_call(200)),
+				Ok(Some((0, delivery_pre_dispatch_data()))),
 			);
-
 			assert_eq!(
-				run_validate(all_finality_and_delivery_batch_call(200, 200, 200)),
-				Ok(ValidTransaction::default()),
+				run_multi_bridge_pre_dispatch(message_delivery_call_at_lane(
+					OtherTestLaneId::get(),
+					200
+				))
+				.map(|pre| pre.map(|(bridge_index, data)| (bridge_index, data.call_type))),
+				Ok(Some((1, CallType::Delivery(MessagesState { best_nonce: 0 })))),
 			);
+			assert_eq!(run_multi_bridge_pre_dispatch(remark_call()), Ok(None));
 		});
 	}
 
 	#[test]
-	fn ext_rejects_batch_with_obsolete_relay_chain_header() {
+	fn multi_bridge_extension_runs_pre_dispatch_of_recognizing_bridge_only() {
 		run_test(|| {
 			initialize_environment(100, 100, 100);
 
-			assert_eq!(
-				run_pre_dispatch(all_finality_and_delivery_batch_call(100, 200, 200)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
-			);
+			let mut pre_dispatch_result = None;
+			let logs = capture_logs(|| {
+				let extension: TestMultiBridgeExtension =
+					RefundRelayerForMessagesFromParachains(PhantomData);
+				pre_dispatch_result = Some(extension.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&message_delivery_call_at_lane(OtherTestLaneId::get(), 200),
+					&DispatchInfo::default(),
+					0,
+				));
+			});
 
+			// the transaction is refunded by the second bridge
 			assert_eq!(
-				run_validate(all_finality_and_delivery_batch_call(100, 200, 200)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				pre_dispatch_result
+					.unwrap()
+					.map(|pre| pre.map(|(bridge_index, data)| (bridge_index, data.call_type))),
+				Ok(Some((1, CallType::Delivery(MessagesState { best_nonce: 0 })))),
 			);
+			// and the first bridge has never seen it in pre-dispatch
+			assert!(!logs.iter().any(|log| log.contains("delivering messages over lane")));
 		});
 	}
 
 	#[test]
-	fn ext_rejects_batch_with_obsolete_parachain_head() {
+	fn multi_bridge_extension_refunds_transactions_of_all_bridges() {
 		run_test(|| {
 			initialize_environment(100, 100, 100);
 
-			assert_eq!(
-				run_pre_dispatch(all_finality_and_delivery_batch_call(101, 100, 200)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+			let extension: TestMultiBridgeExtension =
+				RefundRelayerForMessagesFromParachains(PhantomData);
+			let pre_dispatch_data = extension
+				.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&message_delivery_call_at_lane(OtherTestLaneId::get(), 200),
+					&DispatchInfo::default(),
+					0,
+				)
+				.unwrap();
+
+			// messages are delivered over the lane of the second bridge
+			pallet_bridge_messages::InboundLanes::<TestRuntime>::insert(
+				OtherTestLaneId::get(),
+				InboundLaneData { last_confirmed_nonce: 200, ..Default::default() },
 			);
 
 			assert_eq!(
-				run_pre_dispatch(parachain_finality_and_delivery_batch_call(100, 200)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				TestMultiBridgeExtension::post_dispatch(
+					Some(pre_dispatch_data),
+					&dispatch_info(),
+					&post_dispatch_info(),
+					1024,
+					&Ok(()),
+				),
+				Ok(()),
 			);
-
 			assert_eq!(
-				run_validate(all_finality_and_delivery_batch_call(101, 100, 200)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					RewardsAccountParams::new(OtherTestLaneId::get(), RewardKind::MessageDelivery)
+				),
+				Some(expected_reward()),
 			);
-
 			assert_eq!(
-				run_validate(parachain_finality_and_delivery_batch_call(100, 200)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
 			);
 		});
 	}
 
 	#[test]
-	fn ext_rejects_batch_with_obsolete_messages() {
+	fn post_dispatch_accounts_refunds_and_respects_refund_budget() {
 		run_test(|| {
-			initialize_environment(100, 100, 100);
+			initialize_environment(200, 200, 200);
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			let budget =
+				TestRefundConfigWithRefundBudget::REFUND_BUDGET_PER_ERA as ThisChainBalance;
+			assert!(expected_reward() < budget);
 
+			// refunds are accounted
+			run_post_dispatch_with::<TestExtensionWithRefundBudget>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
 			assert_eq!(
-				run_pre_dispatch(all_finality_and_delivery_batch_call(200, 200, 100)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				RelayersPallet::<TestRuntime>::total_refunded(TestLaneId::get(), 0),
+				expected_reward(),
 			);
 
+			// refund that exceeds the budget is withheld
+			pallet_bridge_relayers::TotalRefunded::<TestRuntime>::insert(
+				TestLaneId::get(),
+				0,
+				budget - expected_reward() + 1,
+			);
+			run_post_dispatch_with::<TestExtensionWithRefundBudget>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
 			assert_eq!(
-				run_pre_dispatch(parachain_finality_and_delivery_batch_call(200, 100)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward()),
 			);
-
 			assert_eq!(
-				run_validate(all_finality_and_delivery_batch_call(200, 200, 100)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				frame_system::Pallet::<TestRuntime>::events().last().map(|record| &record.event),
+				Some(&RuntimeEvent::BridgeRelayers(
+					pallet_bridge_relayers::Event::RefundBudgetExhausted {
+						relayer: relayer_account_at_this_chain(),
+						lane_id: TestLaneId::get(),
+						era: 0,
+						reward: expected_reward(),
+					}
+				)),
 			);
 
+			// budget is restored in the next era
+			frame_system::Pallet::<TestRuntime>::set_block_number(10);
+			run_post_dispatch_with::<TestExtensionWithRefundBudget>(
+				Some(delivery_pre_dispatch_data()),
+				Ok(()),
+			);
 			assert_eq!(
-				run_validate(parachain_finality_and_delivery_batch_call(200, 100)),
-				Err(TransactionValidityError::Invalid(InvalidTransaction::Stale)),
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				Some(expected_reward() * 2),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::total_refunded(TestLaneId::get(), 1),
+				expected_reward(),
 			);
 		});
 	}
 
+	fn relay_finality_and_delivery_batch_call(
+		relay_header_number: RelayBlockNumber,
+		best_message: MessageNonce,
+	) -> RuntimeCall {
+		RuntimeCall::Utility(UtilityCall::batch_all {
+			calls: vec![
+				submit_relay_header_call(relay_header_number),
+				message_delivery_call(best_message),
+			],
+		})
+	}
+
+	fn relay_finality_and_delivery_pre_dispatch_data() -> PreDispatchData<ThisChainAccountId> {
+		PreDispatchData {
+			relayer: relayer_account_at_this_chain(),
+			call_type: CallType::RelayFinalityAndDelivery(
+				ExpectedRelayChainState { best_block_number: 200 },
+				MessagesState { best_nonce: 100 },
+			),
+			confirmed_messages_relayers: Default::default(),
+			non_atomic_batch_delivery_weight: None,
+			bridge_calls_weight: None,
+		}
+	}
+
 	#[test]
-	fn pre_dispatch_parses_batch_with_relay_chain_and_parachain_headers() {
+	fn relay_chain_extension_parses_supported_calls() {
 		run_test(|| {
 			initialize_environment(100, 100, 100);
 
 			assert_eq!(
-				run_pre_dispatch(all_finality_and_delivery_batch_call(200, 200, 200)),
-				Ok(Some(all_finality_pre_dispatch_data())),
+				TestRelayChainExtension::parse_call_type(&relay_finality_and_delivery_batch_call(
+					200, 200
+				)),
+				Ok(relay_finality_and_delivery_pre_dispatch_data().call_type),
+			);
+			assert_eq!(
+				TestRelayChainExtension::parse_call_type(&message_delivery_call(200)),
+				Ok(delivery_pre_dispatch_data().call_type),
+			);
+			assert_eq!(
+				TestRelayChainExtension::parse_call_type(&all_finality_and_delivery_batch_call(
+					200, 200, 200
+				)),
+				Err(UnsupportedReason::UnknownCall),
+			);
+			assert_eq!(
+				TestRelayChainExtension::parse_call_type(&submit_relay_header_call(200)),
+				Err(UnsupportedReason::UnknownCall),
+			);
+
+			let extension: TestRelayChainExtension =
+				RefundRelayerForMessagesDeliveryFromRelayChain(PhantomData);
+			assert_eq!(
+				extension.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&relay_finality_and_delivery_batch_call(200, 200),
+					&DispatchInfo::default(),
+					0,
+				),
+				Ok(Some(relay_finality_and_delivery_pre_dispatch_data())),
 			);
 		});
 	}
 
 	#[test]
-	fn pre_dispatch_parses_batch_with_parachain_header() {
+	fn relay_chain_extension_rejects_obsolete_batches() {
 		run_test(|| {
 			initialize_environment(100, 100, 100);
 
+			let extension: TestRelayChainExtension =
+				RefundRelayerForMessagesDeliveryFromRelayChain(PhantomData);
 			assert_eq!(
-				run_pre_dispatch(parachain_finality_and_delivery_batch_call(200, 200)),
-				Ok(Some(parachain_finality_pre_dispatch_data())),
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&relay_finality_and_delivery_batch_call(100, 200),
+					&DispatchInfo::default(),
+					0,
+				),
+				InvalidTransaction::Stale.into(),
 			);
 		});
 	}
 
 	#[test]
-	fn pre_dispatch_fails_to_parse_batch_with_multiple_parachain_headers() {
+	fn relay_chain_extension_only_checks_bridge_calls_of_batch() {
 		run_test(|| {
 			initialize_environment(100, 100, 100);
 
-			let call = RuntimeCall::Utility(UtilityCall::batch_all {
-				calls: vec![
-					RuntimeCall::BridgeParachains(ParachainsCall::submit_parachain_heads {
-						at_relay_block: (100, RelayBlockHash::default()),
-						parachains: vec![
-							(ParaId(TestParachain::get()), [1u8; 32].into()),
-							(ParaId(TestParachain::get() + 1), [1u8; 32].into()),
+			// the obsolete parachain head call is not a call of our bridge, so it is not checked
+			let extension: TestRelayChainExtension =
+				RefundRelayerForMessagesDeliveryFromRelayChain(PhantomData);
+			assert_eq!(
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&RuntimeCall::Utility(UtilityCall::batch_all {
+						calls: vec![
+							submit_parachain_head_call(100),
+							submit_relay_header_call(200),
+							message_delivery_call(200),
 						],
-						parachain_heads_proof: ParaHeadsProof(vec![]),
 					}),
-					message_delivery_call(200),
-				],
-			});
-
-			assert_eq!(run_pre_dispatch(call), Ok(None),);
+					&DispatchInfo::default(),
+					0,
+				),
+				Ok(ValidTransaction::default()),
+			);
 		});
 	}
 
 	#[test]
-	fn pre_dispatch_parses_message_delivery_transaction() {
+	fn relay_chain_extension_rejects_delivery_transactions_when_lane_is_halted() {
 		run_test(|| {
 			initialize_environment(100, 100, 100);
+			pallet_bridge_messages::LaneOperatingModes::<TestRuntime>::insert(
+				TestLaneId::get(),
+				MessagesOperatingMode::Basic(BasicOperatingMode::Halted),
+			);
 
+			let extension: TestRelayChainExtension =
+				RefundRelayerForMessagesDeliveryFromRelayChain(PhantomData);
 			assert_eq!(
-				run_pre_dispatch(message_delivery_call(200)),
-				Ok(Some(delivery_pre_dispatch_data())),
+				extension.validate(
+					&relayer_account_at_this_chain(),
+					&relay_finality_and_delivery_batch_call(200, 200),
+					&DispatchInfo::default(),
+					0,
+				),
+				InvalidTransaction::Call.into(),
 			);
 		});
 	}
 
 	#[test]
-	fn post_dispatch_ignores_unknown_transaction() {
+	fn relay_chain_extension_computes_weight_of_bridge_calls() {
 		run_test(|| {
-			assert_storage_noop!(run_post_dispatch(None, Ok(())));
-		});
-	}
+			initialize_environment(100, 100, 100);
 
-	#[test]
-	fn post_dispatch_ignores_failed_transaction() {
-		run_test(|| {
-			assert_storage_noop!(run_post_dispatch(
-				Some(all_finality_pre_dispatch_data()),
-				Err(DispatchError::BadOrigin)
-			));
+			let extension: TestRelayChainExtensionWithFeeCalculation<
+				BridgeCallsWeightRefund<TestRuntime>,
+			> = RefundRelayerForMessagesDeliveryFromRelayChain(PhantomData);
+			let expected_bridge_calls_weight = submit_relay_header_call(200)
+				.get_dispatch_info()
+				.weight
+				.saturating_add(message_delivery_call(200).get_dispatch_info().weight);
+			assert_eq!(
+				extension
+					.pre_dispatch(
+						&relayer_account_at_this_chain(),
+						&relay_finality_and_delivery_batch_call(200, 200),
+						&dispatch_info(),
+						0,
+					)
+					.map(|pre| pre.map(|data| data.bridge_calls_weight)),
+				Ok(Some(Some(expected_bridge_calls_weight))),
+			);
 		});
 	}
 
 	#[test]
-	fn post_dispatch_ignores_transaction_that_has_not_updated_relay_chain_state() {
+	fn relay_chain_extension_deposits_refund_skipped_events() {
 		run_test(|| {
-			initialize_environment(100, 200, 200);
+			initialize_environment(100, 100, 200);
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			let last_skip_reason = || {
+				frame_system::Pallet::<TestRuntime>::events()
+					.last()
+					.and_then(|record| match record.event {
+						RuntimeEvent::BridgeRelayers(
+							pallet_bridge_relayers::Event::RefundSkipped { reason, .. },
+						) => Some(reason),
+						_ => None,
+					})
+			};
 
-			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
-		});
-	}
+			run_post_dispatch_with::<TestRelayChainExtension>(
+				Some(relay_finality_and_delivery_pre_dispatch_data()),
+				Err(DispatchError::BadOrigin),
+			);
+			assert_eq!(last_skip_reason(), Some(RefundSkipReason::DispatchFailed));
 
-	#[test]
-	fn post_dispatch_ignores_transaction_that_has_not_updated_parachain_state() {
-		run_test(|| {
-			initialize_environment(200, 100, 200);
+			// relay chain header has not been imported
+			run_post_dispatch_with::<TestRelayChainExtension>(
+				Some(relay_finality_and_delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(last_skip_reason(), Some(RefundSkipReason::StateNotAdvanced));
 
-			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
-			assert_storage_noop!(run_post_dispatch(
-				Some(parachain_finality_pre_dispatch_data()),
-				Ok(())
-			));
+			// messages have not been delivered
+			initialize_environment(200, 100, 100);
+			run_post_dispatch_with::<TestRelayChainExtension>(
+				Some(relay_finality_and_delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(last_skip_reason(), Some(RefundSkipReason::NoNewMessages));
 		});
 	}
 
 	#[test]
-	fn post_dispatch_ignores_transaction_that_has_not_delivered_any_messages() {
+	fn relay_chain_extension_refunds_relayer_if_all_calls_have_succeeded() {
 		run_test(|| {
-			initialize_environment(200, 200, 100);
-
-			assert_storage_noop!(run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(())));
-			assert_storage_noop!(run_post_dispatch(
-				Some(parachain_finality_pre_dispatch_data()),
-				Ok(())
-			));
-			assert_storage_noop!(run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(())));
-		});
-	}
+			initialize_environment(100, 100, 200);
 
-	#[test]
-	fn post_dispatch_refunds_relayer_in_all_finality_batch() {
-		run_test(|| {
-			initialize_environment(200, 200, 200);
+			// relay chain header has not been imported
+			run_post_dispatch_with::<TestRelayChainExtension>(
+				Some(relay_finality_and_delivery_pre_dispatch_data()),
+				Ok(()),
+			);
+			assert_eq!(
+				RelayersPallet::<TestRuntime>::relayer_reward(
+					relayer_account_at_this_chain(),
+					delivery_rewards_account_params()
+				),
+				None,
+			);
 
-			run_post_dispatch(Some(all_finality_pre_dispatch_data()), Ok(()));
+			initialize_environment(200, 100, 200);
+			run_post_dispatch_with::<TestRelayChainExtension>(
+				Some(relay_finality_and_delivery_pre_dispatch_data()),
+				Ok(()),
+			);
 			assert_eq!(
 				RelayersPallet::<TestRuntime>::relayer_reward(
 					relayer_account_at_this_chain(),
-					TestLaneId::get()
+					delivery_rewards_account_params()
 				),
 				Some(expected_reward()),
 			);
@@ -894,33 +7265,103 @@ mod tests {
 	}
 
 	#[test]
-	fn post_dispatch_refunds_relayer_in_parachain_finality_batch() {
+	fn relay_chain_extension_unpacks_batches_the_same_way_as_parachain_extension() {
 		run_test(|| {
-			initialize_environment(200, 200, 200);
+			initialize_environment(100, 100, 100);
 
-			run_post_dispatch(Some(parachain_finality_pre_dispatch_data()), Ok(()));
+			// nested batches are supported if enabled
+			let call = RuntimeCall::Utility(UtilityCall::batch_all {
+				calls: vec![remark_call(), relay_finality_and_delivery_batch_call(200, 200)],
+			});
 			assert_eq!(
-				RelayersPallet::<TestRuntime>::relayer_reward(
-					relayer_account_at_this_chain(),
-					TestLaneId::get()
+				TestRelayChainExtension::parse_call_type(&call),
+				Err(UnsupportedReason::UnknownCall),
+			);
+			assert_eq!(
+				TestRelayChainExtensionWithNestedBatches::parse_call_type(&call),
+				Ok(relay_finality_and_delivery_pre_dispatch_data().call_type),
+			);
+
+			// non-atomic batches are supported
+			let extension: TestRelayChainExtension =
+				RefundRelayerForMessagesDeliveryFromRelayChain(PhantomData);
+			assert_eq!(
+				extension.pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&RuntimeCall::Utility(UtilityCall::batch {
+						calls: vec![submit_relay_header_call(200), message_delivery_call(200)],
+					}),
+					&DispatchInfo::default(),
+					0,
 				),
-				Some(expected_reward()),
+				Ok(Some(PreDispatchData {
+					non_atomic_batch_delivery_weight: Some(
+						message_delivery_call(200).get_dispatch_info().weight
+					),
+					..relay_finality_and_delivery_pre_dispatch_data()
+				})),
 			);
 		});
 	}
 
 	#[test]
-	fn post_dispatch_refunds_relayer_in_message_delivery_transaction() {
+	fn relay_chain_extension_refunds_delivery_call_if_relay_header_call_of_non_atomic_batch_fails()
+	{
 		run_test(|| {
-			initialize_environment(200, 200, 200);
+			initialize_environment(100, 100, 200);
 
-			run_post_dispatch(Some(delivery_pre_dispatch_data()), Ok(()));
+			run_post_dispatch_with::<TestRelayChainExtension>(
+				Some(PreDispatchData {
+					non_atomic_batch_delivery_weight: Some(
+						message_delivery_call(200).get_dispatch_info().weight,
+					),
+					..relay_finality_and_delivery_pre_dispatch_data()
+				}),
+				Ok(()),
+			);
+			assert!(RelayersPallet::<TestRuntime>::relayer_reward(
+				relayer_account_at_this_chain(),
+				delivery_rewards_account_params()
+			)
+			.is_some());
+		});
+	}
+
+	#[test]
+	fn relay_chain_extension_checks_relayer_id_at_bridged_chain() {
+		run_test(|| {
+			initialize_environment(100, 100, 100);
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+
+			let extension: TestRelayChainExtensionWithRelayerIdCheck =
+				RefundRelayerForMessagesDeliveryFromRelayChain(PhantomData);
 			assert_eq!(
-				RelayersPallet::<TestRuntime>::relayer_reward(
-					relayer_account_at_this_chain(),
-					TestLaneId::get()
+				extension.clone().pre_dispatch(
+					&relayer_account_at_this_chain(),
+					&relay_finality_and_delivery_batch_call(200, 200),
+					&DispatchInfo::default(),
+					0,
 				),
-				Some(expected_reward()),
+				Ok(Some(relay_finality_and_delivery_pre_dispatch_data())),
+			);
+
+			let other_relayer = relayer_account_at_this_chain() + 1;
+			assert_eq!(
+				extension.pre_dispatch(
+					&other_relayer,
+					&relay_finality_and_delivery_batch_call(200, 200),
+					&DispatchInfo::default(),
+					0,
+				),
+				Ok(None),
+			);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().map(|record| &record.event),
+				Some(&RuntimeEvent::BridgeRelayers(pallet_bridge_relayers::Event::RefundSkipped {
+					relayer: other_relayer,
+					lane_id: TestLaneId::get(),
+					reason: RefundSkipReason::RelayerIdMismatch,
+				})),
 			);
 		});
 	}
@@ -0,0 +1,83 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks of the batch parsing, performed by refund relayer extensions.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use crate::refund_relayer_extension::{
+	unpack_bridge_batch, RefundConfig, UtilityBatchCallUnpacker,
+};
+
+use frame_benchmarking::benchmarks;
+use frame_support::{dispatch::CallableCallFor, traits::IsSubType, weights::Weight};
+use pallet_utility::{Call as UtilityCall, Config as UtilityConfig, Pallet as UtilityPallet};
+use sp_std::prelude::*;
+
+/// Maximal number of calls in the benchmarked batch.
+const MAX_BATCH_CALLS: u32 = 128;
+
+/// Pallet we're benchmarking here.
+pub struct Pallet<T: Config>(frame_system::Pallet<T>);
+
+/// Trait that must be implemented by runtime.
+pub trait Config: UtilityConfig<RuntimeCall = <Self as frame_system::Config>::RuntimeCall> {}
+
+/// Refund configuration that makes extension scan all calls of the batch, looking for the nested
+/// bridge batch.
+struct WorstCaseRefundConfig;
+
+impl RefundConfig for WorstCaseRefundConfig {
+	const MAX_NESTED_BATCH_EXTRA_CALLS: u32 = u32::MAX;
+
+	// this is what we're benchmarking here
+	fn bridge_batch_parse_weight(_calls: u32) -> Weight {
+		Weight::zero()
+	}
+}
+
+benchmarks! {
+	where_clause {
+		where
+			<T as frame_system::Config>::RuntimeCall: From<UtilityCall<T>>
+				+ From<frame_system::Call<T>>
+				+ IsSubType<CallableCallFor<UtilityPallet<T>, T>>,
+	}
+
+	// Benchmark parsing of the worst-case `utility.batchAll` with `c` calls, where the nested
+	// bridge batch is the last call. The cost is expected to grow linearly with `c`.
+	unpack_bridge_batch {
+		let c in 1 .. MAX_BATCH_CALLS;
+
+		let neutral_call = || -> <T as frame_system::Config>::RuntimeCall {
+			frame_system::Call::<T>::remark { remark: vec![] }.into()
+		};
+		let mut calls = (1..c).map(|_| neutral_call()).collect::<Vec<_>>();
+		calls.push(UtilityCall::<T>::batch_all { calls: vec![neutral_call()] }.into());
+		let call: <T as frame_system::Config>::RuntimeCall =
+			UtilityCall::<T>::batch_all { calls }.into();
+		let mut unpacked_calls = 0;
+	}: {
+		unpacked_calls =
+			unpack_bridge_batch::<_, UtilityBatchCallUnpacker<T>, WorstCaseRefundConfig>(&call)
+				.map(|calls| calls.len())
+				.unwrap_or(0);
+	}
+	verify {
+		// the nested batch has been found
+		assert_eq!(unpacked_calls, 1);
+	}
+}
@@ -0,0 +1,78 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights of the refund relayer extensions.
+//!
+//! The weights below are provisional estimates and have NOT been produced by the benchmark CLI.
+//! They must be replaced with the output of the `refund_relayer_extension` benchmarks (see the
+//! `refund_relayer_extension_benchmarking` module), executed on the reference hardware. The file
+//! must also be regenerated when the batch parsing code is changed.
+
+// TODO: regenerate using the `refund_relayer_extension` benchmarks on the reference hardware.
+//
+// Command to regenerate (the benchmark is registered in the Millau runtime):
+// cargo build --release -p millau-bridge-node --features runtime-benchmarks
+// target/release/millau-bridge-node
+// benchmark
+// pallet
+// --chain=dev
+// --steps=50
+// --repeat=20
+// --pallet=refund_relayer_extension
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=Compiled
+// --heap-pages=4096
+// --output=./bin/runtime-common/src/refund_relayer_extension_weights.rs
+// --template=./.maintain/millau-weight-template.hbs
+
+#![allow(clippy::all)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for refund relayer extensions.
+pub trait WeightInfo {
+	fn unpack_bridge_batch(c: u32) -> Weight;
+}
+
+/// Provisional weights for refund relayer extensions.
+///
+/// Those weights are estimates that are not backed by benchmark results, so they must never be
+/// used in production.
+pub struct BridgeWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for BridgeWeight<T> {
+	/// The range of component `c` is `[1, 128]`.
+	fn unpack_bridge_batch(c: u32) -> Weight {
+		Weight::from_ref_time(2_114_000 as u64)
+			.saturating_add(Weight::from_ref_time(163_000 as u64).saturating_mul(c as u64))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	/// The range of component `c` is `[1, 128]`.
+	fn unpack_bridge_batch(c: u32) -> Weight {
+		Weight::from_ref_time(2_114_000 as u64)
+			.saturating_add(Weight::from_ref_time(163_000 as u64).saturating_mul(c as u64))
+	}
+}
@@ -0,0 +1,107 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks for the BEEFY Pallet.
+//!
+//! The cost of the `submit_header` call mostly depends on the size of the MMR proof, which
+//! is verified against the MMR root of the already imported commitment. The MMR proof of the
+//! single leaf contains (roughly) `log2(leaf_count)` items, so we're benchmarking proofs of the
+//! first leaf in the MMR with `2^p` leafs.
+
+use crate::*;
+
+use bp_beefy::MmrLeafVersion;
+use bp_runtime::BasicOperatingMode;
+use codec::Encode;
+use frame_benchmarking::{benchmarks_instance_pallet, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_runtime::traits::{Hash, Header as HeaderT, One};
+use sp_std::vec;
+
+// The maximal number of items in the MMR proof. It is enough to prove any leaf of the MMR
+// with up to `2^32` leafs.
+const MAX_MMR_PROOF_ITEMS: u32 = 32;
+
+/// Prepare header, its MMR leaf and MMR proof with `proof_items` items to submit using
+/// `submit_header`.
+///
+/// The commitment with the MMR root, computed from the proof, is inserted directly into the
+/// runtime storage.
+fn prepare_benchmark_data<T: Config<I>, I: 'static>(
+	proof_items: u32,
+) -> (BridgedHeader<T, I>, BridgedBlockNumber<T, I>, BridgedBeefyMmrLeaf<T, I>, BridgedMmrProof<T, I>)
+where
+	BridgedBeefyMmrLeafExtra<T, I>: Default,
+{
+	let header: BridgedHeader<T, I> = bp_test_utils::test_header(One::one());
+	let commitment_block_number = *header.number() + One::one();
+	let mmr_leaf = BridgedBeefyMmrLeaf::<T, I> {
+		version: MmrLeafVersion::new(0, 0),
+		parent_number_and_hash: (*header.number(), header.hash()),
+		beefy_next_authority_set: CurrentAuthoritySetInfo::<T, I>::get(),
+		leaf_extra: Default::default(),
+	};
+
+	// the leaf is the leftmost leaf of the single-peak MMR, so all its siblings are on the right
+	let mmr_proof_items = vec![BridgedMmrHash::<T, I>::default(); proof_items as usize];
+	let mmr_root = mmr_proof_items.iter().fold(
+		BridgedMmrHashing::<T, I>::hash(&mmr_leaf.encode()),
+		|node, sibling| {
+			BridgedMmrHashing::<T, I>::hash(&[node.as_ref(), sibling.as_ref()].concat())
+		},
+	);
+	let mmr_proof = BridgedMmrProof::<T, I> {
+		leaf_indices: vec![0],
+		leaf_count: 1u64 << proof_items,
+		items: mmr_proof_items,
+	};
+
+	PalletOperatingMode::<T, I>::put(BasicOperatingMode::Normal);
+	ImportedCommitments::<T, I>::insert(
+		commitment_block_number,
+		ImportedCommitment::<T, I> {
+			parent_number_and_hash: (*header.number(), header.hash()),
+			mmr_root,
+		},
+	);
+
+	(header, commitment_block_number, mmr_leaf, mmr_proof)
+}
+
+benchmarks_instance_pallet! {
+	where_clause {
+		where
+			BridgedBeefyMmrLeafExtra<T, I>: Default,
+			BridgedMmrHashing<T, I>: 'static + Send + Sync,
+	}
+
+	submit_header {
+		let p in 0..MAX_MMR_PROOF_ITEMS;
+		let caller: T::AccountId = whitelisted_caller();
+		let (header, commitment_block_number, mmr_leaf, mmr_proof) =
+			prepare_benchmark_data::<T, I>(p);
+	}: submit_header(
+		RawOrigin::Signed(caller),
+		Box::new(header),
+		commitment_block_number,
+		Box::new(mmr_leaf),
+		mmr_proof
+	)
+	verify {
+		let header: BridgedHeader<T, I> = bp_test_utils::test_header(One::one());
+		assert!(ImportedHeaders::<T, I>::contains_key(header.hash()));
+	}
+}
@@ -28,17 +28,31 @@
 //!
 //! Given the header hash, other pallets are able to verify header-based proofs
 //! (e.g. storage proofs, transaction inclusion proofs, etc.).
+//!
+//! Headers, which hashes are proved by the MMR leafs, may also be imported into the pallet.
+//! Imported headers are exposed using the `bp_header_chain::HeaderChain` trait (see
+//! `BeefyChainHeaders`), so the pallet may be used instead of the GRANDPA pallet as the source
+//! of finalized headers for other bridge pallets (e.g. `pallet-bridge-parachains` and
+//! `pallet-bridge-messages`).
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use bp_beefy::{ChainWithBeefy, InitializationData};
-use sp_std::{boxed::Box, prelude::*};
+use bp_header_chain::{HeaderChain, StoredHeaderData};
+use bp_runtime::{BlockNumberOf, HashOf};
+use sp_std::{boxed::Box, marker::PhantomData, prelude::*};
 
 // Re-export in crate namespace for `construct_runtime!`
 pub use pallet::*;
+pub use weights::WeightInfo;
 
 mod utils;
 
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+pub mod benchmarking;
+
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
@@ -53,6 +67,11 @@ pub type BridgedChain<T, I> = <T as Config<I>>::BridgedChain;
 pub type BridgedBlockNumber<T, I> = bp_runtime::BlockNumberOf<BridgedChain<T, I>>;
 /// Block hash, used by configured bridged chain.
 pub type BridgedBlockHash<T, I> = bp_runtime::HashOf<BridgedChain<T, I>>;
+/// Header of the configured bridged chain.
+pub type BridgedHeader<T, I> = bp_runtime::HeaderOf<BridgedChain<T, I>>;
+/// Header data of the configured bridged chain, that is stored by the pallet.
+pub type BridgedStoredHeaderData<T, I> =
+	StoredHeaderData<BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>>;
 
 /// Pallet initialization data.
 pub type InitializationDataOf<T, I> =
@@ -97,9 +116,11 @@ pub struct ImportedCommitmentsInfoData<BlockNumber> {
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
+	use bp_header_chain::StoredHeaderDataBuilder;
 	use bp_runtime::{BasicOperatingMode, OwnedBridgeModule};
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::Header as HeaderT;
 
 	#[pallet::config]
 	pub trait Config<I: 'static = ()>: frame_system::Config {
@@ -120,8 +141,19 @@ pub mod pallet {
 		#[pallet::constant]
 		type CommitmentsToKeep: Get<u32>;
 
+		/// Maximal number of imported headers to keep in the storage.
+		///
+		/// The setting is there to prevent growing the on-chain state indefinitely. Note
+		/// the setting does not relate to block numbers - we will simply keep as much items
+		/// in the storage, so it doesn't guarantee any fixed timeframe for imported headers.
+		#[pallet::constant]
+		type HeadersToKeep: Get<u32>;
+
 		/// The chain we are bridging to here.
 		type BridgedChain: ChainWithBeefy;
+
+		/// Weights gathered through benchmarking.
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
@@ -272,6 +304,70 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Submit a header of the bridged chain, which hash is proved by the MMR leaf.
+		///
+		/// The MMR leaf must be proved against MMR root of the already imported commitment,
+		/// referenced by the `commitment_block_number`. The leaf stores the number and hash of its
+		/// parent block, so the leaf of block `N` must be provided to import header `N - 1`.
+		///
+		/// If successful, the header state root becomes available to other pallets through the
+		/// `BeefyChainHeaders` adapter. Headers that are already imported are rejected.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::submit_header(
+			mmr_proof.items.len().try_into().unwrap_or(u32::MAX),
+		))]
+		pub fn submit_header(
+			origin: OriginFor<T>,
+			header: Box<BridgedHeader<T, I>>,
+			commitment_block_number: BridgedBlockNumber<T, I>,
+			mmr_leaf: Box<BridgedBeefyMmrLeaf<T, I>>,
+			mmr_proof: BridgedMmrProof<T, I>,
+		) -> DispatchResult {
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+			ensure_signed(origin)?;
+
+			ensure!(Self::request_count() < T::MaxRequests::get(), <Error<T, I>>::TooManyRequests);
+
+			// Reject headers that are already imported.
+			let header_hash = header.hash();
+			ensure!(
+				!ImportedHeaders::<T, I>::contains_key(header_hash),
+				Error::<T, I>::HeaderAlreadyImported,
+			);
+
+			// Verify that the header is referenced by the MMR leaf.
+			let commitment = ImportedCommitments::<T, I>::get(commitment_block_number)
+				.ok_or(Error::<T, I>::UnknownCommitment)?;
+			utils::verify_beefy_mmr_leaf::<T, I>(&mmr_leaf, mmr_proof, commitment.mmr_root)?;
+			ensure!(
+				mmr_leaf.parent_number_and_hash == (*header.number(), header_hash),
+				Error::<T, I>::HeaderMismatch,
+			);
+
+			// Update request count.
+			RequestCount::<T, I>::mutate(|count| *count += 1);
+
+			// Import header.
+			let index = ImportedHeaderHashesPointer::<T, I>::get();
+			let to_prune = ImportedHeaderHashes::<T, I>::try_get(index);
+			ImportedHeaders::<T, I>::insert(header_hash, header.build());
+			ImportedHeaderHashes::<T, I>::insert(index, header_hash);
+			ImportedHeaderHashesPointer::<T, I>::put((index + 1) % T::HeadersToKeep::get());
+			if let Ok(old_header_hash) = to_prune {
+				log::debug!(target: LOG_TARGET, "Pruning old header: {:?}.", old_header_hash);
+				ImportedHeaders::<T, I>::remove(old_header_hash);
+			}
+
+			log::info!(
+				target: LOG_TARGET,
+				"Successfully imported header {:?} ({:?})",
+				header_hash,
+				header.number(),
+			);
+
+			Ok(())
+		}
 	}
 
 	/// The current number of requests which have written to storage.
@@ -305,6 +401,22 @@ pub mod pallet {
 	pub type ImportedCommitments<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, BridgedBlockNumber<T, I>, ImportedCommitment<T, I>>;
 
+	/// Data of all the headers that we have imported and haven't been pruned yet.
+	#[pallet::storage]
+	pub type ImportedHeaders<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, BridgedBlockHash<T, I>, BridgedStoredHeaderData<T, I>>;
+
+	/// A ring buffer containing the hashes of the headers that we have imported, ordered by the
+	/// insertion time.
+	#[pallet::storage]
+	pub(super) type ImportedHeaderHashes<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, u32, BridgedBlockHash<T, I>>;
+
+	/// The head of the `ImportedHeaderHashes` ring buffer.
+	#[pallet::storage]
+	pub(super) type ImportedHeaderHashesPointer<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, u32, ValueQuery>;
+
 	/// The current BEEFY authority set at the bridged chain.
 	#[pallet::storage]
 	pub type CurrentAuthoritySetInfo<T: Config<I>, I: 'static = ()> =
@@ -388,6 +500,12 @@ pub mod pallet {
 		MmrProofVerificationFailed,
 		/// The validators are not matching the merkle tree root of the authority set.
 		InvalidValidatorSetRoot,
+		/// The referenced commitment is not imported or has already been pruned.
+		UnknownCommitment,
+		/// The header is not referenced by the provided MMR leaf.
+		HeaderMismatch,
+		/// The header has already been imported.
+		HeaderAlreadyImported,
 		/// Error generated by the `OwnedBridgeModule` trait.
 		BridgeModule(bp_runtime::OwnedBridgeModuleError),
 	}
@@ -411,6 +529,23 @@ pub mod pallet {
 	}
 }
 
+/// Bridge BEEFY pallet as header chain.
+pub struct BeefyChainHeaders<T, I>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> HeaderChain<BridgedChain<T, I>> for BeefyChainHeaders<T, I> {
+	fn finalized_header_state_root(
+		header_hash: HashOf<BridgedChain<T, I>>,
+	) -> Option<HashOf<BridgedChain<T, I>>> {
+		ImportedHeaders::<T, I>::get(header_hash).map(|h| h.state_root)
+	}
+
+	fn finalized_header_number(
+		header_hash: HashOf<BridgedChain<T, I>>,
+	) -> Option<BlockNumberOf<BridgedChain<T, I>>> {
+		ImportedHeaders::<T, I>::get(header_hash).map(|h| h.number)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -644,5 +779,102 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn fails_to_import_header_if_commitment_is_unknown() {
+		run_test_with_initialize(1, || {
+			let chain = ChainBuilder::new(1).append_finalized_headers(2);
+			assert_noop!(
+				import_parent_header(chain.header(1).header, chain.header(2), 2),
+				Error::<TestRuntime, ()>::UnknownCommitment,
+			);
+		})
+	}
+
+	#[test]
+	fn fails_to_import_header_if_it_is_not_referenced_by_mmr_leaf() {
+		run_test_with_initialize(1, || {
+			let chain = ChainBuilder::new(1).append_finalized_headers(2);
+			assert_ok!(import_commitment(chain.header(2)));
+			assert_noop!(
+				import_parent_header(chain.header(2).header, chain.header(2), 2),
+				Error::<TestRuntime, ()>::HeaderMismatch,
+			);
+		})
+	}
+
+	#[test]
+	fn fails_to_import_header_if_mmr_proof_is_invalid() {
+		run_test_with_initialize(1, || {
+			let chain = ChainBuilder::new(1).append_finalized_headers(2);
+			assert_ok!(import_commitment(chain.header(2)));
+			let mut header = chain.header(2);
+			header.leaf_proof.items.clear();
+			assert_noop!(
+				import_parent_header(chain.header(1).header, header, 2),
+				Error::<TestRuntime, ()>::MmrProofVerificationFailed,
+			);
+		})
+	}
+
+	#[test]
+	fn imported_header_is_available_through_header_chain() {
+		run_test_with_initialize(1, || {
+			let chain = ChainBuilder::new(1).append_finalized_headers(2);
+			let parent_header = chain.header(1).header;
+			let parent_hash = parent_header.hash();
+			assert_ok!(import_commitment(chain.header(2)));
+			assert_ok!(import_parent_header(parent_header.clone(), chain.header(2), 2));
+
+			assert_eq!(
+				BeefyChainHeaders::<TestRuntime, ()>::finalized_header_state_root(parent_hash),
+				Some(parent_header.state_root),
+			);
+			assert_eq!(
+				BeefyChainHeaders::<TestRuntime, ()>::finalized_header_number(parent_hash),
+				Some(1),
+			);
+		})
+	}
+
+	#[test]
+	fn fails_to_import_already_imported_header() {
+		run_test_with_initialize(1, || {
+			let chain = ChainBuilder::new(1).append_finalized_headers(2);
+			assert_ok!(import_commitment(chain.header(2)));
+			assert_ok!(import_parent_header(chain.header(1).header, chain.header(2), 2));
+			assert_noop!(
+				import_parent_header(chain.header(1).header, chain.header(2), 2),
+				Error::<TestRuntime, ()>::HeaderAlreadyImported,
+			);
+		})
+	}
+
+	#[test]
+	fn header_pruning_works() {
+		run_test_with_initialize(1, || {
+			let headers_to_keep = <TestRuntime as Config<()>>::HeadersToKeep::get();
+			let chain = ChainBuilder::new(1).append_finalized_headers(headers_to_keep as usize + 2);
+
+			// import `HeadersToKeep + 1` headers
+			for number in 2..headers_to_keep as TestBridgedBlockNumber + 3 {
+				next_block();
+				next_block();
+				assert_ok!(import_commitment(chain.header(number)));
+				assert_ok!(import_parent_header(
+					chain.header(number - 1).header,
+					chain.header(number),
+					number
+				));
+			}
+
+			// the first imported header is pruned
+			assert!(ImportedHeaders::<TestRuntime>::get(chain.header(1).header.hash()).is_none());
+			for number in 2..headers_to_keep as TestBridgedBlockNumber + 2 {
+				assert!(ImportedHeaders::<TestRuntime>::get(chain.header(number).header.hash())
+					.is_some());
+			}
+		})
+	}
+
 	generate_owned_bridge_module_tests!(BasicOperatingMode::Normal, BasicOperatingMode::Halted);
 }
@@ -108,6 +108,8 @@ impl beefy::Config for TestRuntime {
 	type MaxRequests = frame_support::traits::ConstU32<16>;
 	type BridgedChain = TestBridgedChain;
 	type CommitmentsToKeep = frame_support::traits::ConstU32<16>;
+	type HeadersToKeep = frame_support::traits::ConstU32<16>;
+	type WeightInfo = ();
 }
 
 #[derive(Debug)]
@@ -181,6 +183,21 @@ pub fn import_commitment(
 	)
 }
 
+/// Import parent of given header, using its MMR leaf and the commitment at given block.
+pub fn import_parent_header(
+	parent_header: TestBridgedHeader,
+	header: crate::mock_chain::HeaderAndCommitment,
+	commitment_block_number: TestBridgedBlockNumber,
+) -> sp_runtime::DispatchResult {
+	crate::Pallet::<TestRuntime>::submit_header(
+		RuntimeOrigin::signed(1),
+		Box::new(parent_header),
+		commitment_block_number,
+		Box::new(header.leaf),
+		header.leaf_proof,
+	)
+}
+
 pub fn validator_pairs(index: u32, count: u32) -> Vec<BeefyPair> {
 	(index..index + count)
 		.map(|index| {
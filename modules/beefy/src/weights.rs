@@ -0,0 +1,67 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights for `pallet_bridge_beefy`.
+//!
+//! The `pallet_bridge_beefy` benchmarks (see the `benchmarking` module) have not been executed on
+//! the reference hardware yet, so there's no `BridgeWeight` implementation here. The weights of the
+//! `()` implementation are provisional estimates, used in tests and test runtimes only. They must
+//! be replaced with the benchmark output once it is available.
+
+// TODO: regenerate using the `pallet_bridge_beefy` benchmarks on the reference hardware.
+//
+// Command to regenerate (the benchmark is registered in the Rialto runtime):
+// cargo build --release -p rialto-bridge-node --features runtime-benchmarks
+// target/release/rialto-bridge-node
+// benchmark
+// pallet
+// --chain=dev
+// --steps=50
+// --repeat=20
+// --pallet=pallet_bridge_beefy
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=Compiled
+// --heap-pages=4096
+// --output=./modules/beefy/src/weights.rs
+// --template=./.maintain/millau-weight-template.hbs
+
+#![allow(clippy::all)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for `pallet_bridge_beefy`.
+pub trait WeightInfo {
+	fn submit_header(p: u32) -> Weight;
+}
+
+// Provisional weights for tests and test runtimes
+impl WeightInfo for () {
+	/// The range of component `p` is `[0, 32]`.
+	fn submit_header(p: u32) -> Weight {
+		Weight::from_ref_time(48_212_000 as u64)
+			.saturating_add(Weight::from_ref_time(2_384_000 as u64).saturating_mul(p as u64))
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes(5 as u64))
+	}
+}
@@ -0,0 +1,107 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Benchmarks for the fee market pallet.
+//!
+//! All calls are benchmarked with the maximal number of enrolled relayers, because every call
+//! reads and rewrites the whole `Relayers` vector.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use crate::*;
+
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::Get;
+use frame_system::RawOrigin;
+use sp_runtime::traits::{Bounded, One};
+
+const SEED: u32 = 0;
+
+/// Lane of the benchmarked orders.
+const LANE: LaneId = LaneId([0, 0, 0, 0]);
+
+/// Returns account with enough funds to lock collateral or to pay delivery fee.
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let account = account(name, index, SEED);
+	T::Currency::make_free_balance_be(&account, BalanceOf::<T>::max_value() / 2u32.into());
+	account
+}
+
+/// Enroll given number of relayers with increasing quotes.
+fn enroll_relayers<T: Config>(count: u32) {
+	for index in 0..count {
+		let relayer = funded_account::<T>("relayer", index);
+		Pallet::<T>::enroll(
+			RawOrigin::Signed(relayer).into(),
+			T::MinCollateral::get(),
+			BalanceOf::<T>::from(index.saturating_add(1)),
+		)
+		.expect("relayers are not enrolled yet and there's a room for them; qed");
+	}
+}
+
+benchmarks! {
+	// Benchmark `enroll` call with the worst case: the relayer is inserted at the beginning of
+	// the vector, containing `MaxRelayers - 1` relayers.
+	enroll {
+		enroll_relayers::<T>(T::MaxRelayers::get().saturating_sub(1));
+		let relayer = funded_account::<T>("caller", 0);
+	}: _(RawOrigin::Signed(relayer.clone()), T::MinCollateral::get(), Zero::zero())
+	verify {
+		assert_eq!(Relayers::<T>::get().first().map(|r| r.id.clone()), Some(relayer));
+	}
+
+	// Benchmark `update_quote` call with the worst case: the last relayer of the full vector
+	// is moved to its beginning.
+	update_quote {
+		let max_relayers = T::MaxRelayers::get();
+		enroll_relayers::<T>(max_relayers);
+		let relayer: T::AccountId = account("relayer", max_relayers.saturating_sub(1), SEED);
+	}: _(RawOrigin::Signed(relayer.clone()), Zero::zero())
+	verify {
+		assert_eq!(Relayers::<T>::get().first().map(|r| r.id.clone()), Some(relayer));
+	}
+
+	// Benchmark `cancel_enrollment` call with the worst case: the first relayer is removed from
+	// the full vector.
+	cancel_enrollment {
+		enroll_relayers::<T>(T::MaxRelayers::get());
+		let relayer: T::AccountId = account("relayer", 0, SEED);
+	}: _(RawOrigin::Signed(relayer.clone()))
+	verify {
+		assert!(!Relayers::<T>::get().iter().any(|r| r.id == relayer));
+	}
+
+	// Benchmark `settle_order` with the worst case: the message is delivered after all delivery
+	// slots have ended, so every assigned relayer is slashed and removed from the full vector.
+	settle_order {
+		enroll_relayers::<T>(T::MaxRelayers::get());
+		let sender = funded_account::<T>("sender", 0);
+		Pallet::<T>::create_order(&sender, LANE, 1)
+			.expect("enough relayers are enrolled and sender has enough funds; qed");
+
+		let slots = T::BlockNumber::from(T::AssignedRelayersNumber::get());
+		let delivery_deadline = frame_system::Pallet::<T>::block_number()
+			.saturating_add(T::SlotLength::get().saturating_mul(slots));
+		frame_system::Pallet::<T>::set_block_number(delivery_deadline + One::one());
+		let delivery_relayer = funded_account::<T>("delivery_relayer", 0);
+	}: {
+		Pallet::<T>::settle_order(LANE, 1, &delivery_relayer);
+	}
+	verify {
+		assert!(Orders::<T>::get((LANE, 1)).is_none());
+	}
+}
@@ -0,0 +1,718 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime module that implements relayers fee market for message delivery.
+//!
+//! Relayers are enrolling to the market by locking collateral and quoting their price for
+//! delivering single message. Relayers with the cheapest quotes are assigned to every sent
+//! message and late assigned relayers are slashed.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+use bp_messages::{LaneId, MessageNonce};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	traits::{BalanceStatus, Currency, ReservableCurrency},
+	BoundedVec, RuntimeDebug,
+};
+use scale_info::TypeInfo;
+use sp_runtime::traits::{Saturating, UniqueSaturatedFrom, Zero};
+use sp_std::vec::Vec;
+
+pub use message_verifier::FeeMarketMessageVerifier;
+pub use pallet::*;
+pub use payment_adapter::{CancelledMessagesRefundAdapter, DeliveryConfirmationPaymentsAdapter};
+pub use weights::WeightInfo;
+
+pub mod benchmarking;
+pub mod weights;
+
+mod message_verifier;
+mod mock;
+mod payment_adapter;
+
+/// The target that will be used when publishing logs related to this pallet.
+pub const LOG_TARGET: &str = "runtime::bridge-fee-market";
+
+/// Balance type of the pallet currency.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Relayer that is enrolled to the fee market.
+#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Relayer<AccountId, Balance> {
+	/// Relayer account.
+	pub id: AccountId,
+	/// Collateral that is reserved at the relayer account.
+	pub collateral: Balance,
+	/// Price of delivering single message, quoted by the relayer.
+	pub quote: Balance,
+}
+
+/// Delivery order of the single outbound message.
+#[derive(Clone, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Order<AccountId, Balance, BlockNumber, AssignedRelayers> {
+	/// Account that has sent the message.
+	pub sender: AccountId,
+	/// Delivery fee that is reserved at the sender account.
+	pub fee: Balance,
+	/// Number of the block where the message has been sent.
+	pub sent_at: BlockNumber,
+	/// Relayers that are assigned to deliver the message, ordered by their delivery slots.
+	pub assigned_relayers: AssignedRelayers,
+}
+
+/// Relayer type used by the pallet.
+pub type RelayerOf<T> = Relayer<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+/// Order type used by the pallet.
+pub type OrderOf<T> = Order<
+	<T as frame_system::Config>::AccountId,
+	BalanceOf<T>,
+	<T as frame_system::Config>::BlockNumber,
+	BoundedVec<<T as frame_system::Config>::AccountId, <T as Config>::AssignedRelayersNumber>,
+>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+		/// Currency that is used to lock relayers collateral and to pay delivery fees.
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// Minimal collateral that relayer must lock to be enrolled to the market.
+		#[pallet::constant]
+		type MinCollateral: Get<BalanceOf<Self>>;
+		/// Maximal number of relayers that may be enrolled to the market.
+		#[pallet::constant]
+		type MaxRelayers: Get<u32>;
+		/// Number of relayers that are assigned to every outbound message.
+		#[pallet::constant]
+		type AssignedRelayersNumber: Get<u32>;
+		/// Number of blocks that every assigned relayer has to deliver the message.
+		#[pallet::constant]
+		type SlotLength: Get<Self::BlockNumber>;
+		/// Collateral that is slashed from every assigned relayer if message is delivered after
+		/// all delivery slots have ended.
+		#[pallet::constant]
+		type LateDeliverySlash: Get<BalanceOf<Self>>;
+		/// Benchmarks results from runtime we're plugged into.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(PhantomData<T>);
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Enroll to the market.
+		///
+		/// The `collateral` is reserved at the relayer account. The `quote` is the price that the
+		/// relayer wants to receive for delivering single message.
+		#[pallet::call_index(0)]
+		#[pallet::weight(T::WeightInfo::enroll())]
+		pub fn enroll(
+			origin: OriginFor<T>,
+			collateral: BalanceOf<T>,
+			quote: BalanceOf<T>,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			ensure!(collateral >= T::MinCollateral::get(), Error::<T>::InsufficientCollateral);
+
+			Relayers::<T>::try_mutate(|relayers| {
+				ensure!(!relayers.iter().any(|r| r.id == relayer), Error::<T>::AlreadyEnrolled);
+				T::Currency::reserve(&relayer, collateral)
+					.map_err(|_| Error::<T>::FailedToReserveCollateral)?;
+				insert_relayer::<T>(relayers, Relayer { id: relayer.clone(), collateral, quote })
+			})?;
+
+			Self::deposit_event(Event::<T>::RelayerEnrolled { relayer, collateral, quote });
+			Ok(())
+		}
+
+		/// Update the price, quoted by the enrolled relayer.
+		#[pallet::call_index(1)]
+		#[pallet::weight(T::WeightInfo::update_quote())]
+		pub fn update_quote(origin: OriginFor<T>, quote: BalanceOf<T>) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+
+			Relayers::<T>::try_mutate(|relayers| {
+				let mut entry = take_relayer::<T>(relayers, &relayer)?;
+				entry.quote = quote;
+				insert_relayer::<T>(relayers, entry)
+			})?;
+
+			Self::deposit_event(Event::<T>::QuoteUpdated { relayer, quote });
+			Ok(())
+		}
+
+		/// Leave the market and unreserve collateral.
+		///
+		/// The relayer may only leave the market when all messages that it has been assigned to
+		/// are delivered.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::cancel_enrollment())]
+		pub fn cancel_enrollment(origin: OriginFor<T>) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			ensure!(ActiveOrders::<T>::get(&relayer) == 0, Error::<T>::HasActiveOrders);
+
+			let entry =
+				Relayers::<T>::try_mutate(|relayers| take_relayer::<T>(relayers, &relayer))?;
+			T::Currency::unreserve(&relayer, entry.collateral);
+
+			Self::deposit_event(Event::<T>::RelayerLeft { relayer });
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Return current price of delivering single message.
+		///
+		/// Returns `None` if there are not enough relayers in the market.
+		pub fn market_fee() -> Option<BalanceOf<T>> {
+			let assigned_relayers = T::AssignedRelayersNumber::get() as usize;
+			if assigned_relayers == 0 {
+				return None
+			}
+
+			Relayers::<T>::get().get(assigned_relayers - 1).map(|relayer| relayer.quote)
+		}
+
+		/// Assign relayers to the outbound message and reserve delivery fee at the sender
+		/// account.
+		pub fn create_order(
+			sender: &T::AccountId,
+			lane_id: LaneId,
+			nonce: MessageNonce,
+		) -> Result<BalanceOf<T>, Error<T>> {
+			let fee = Self::market_fee().ok_or(Error::<T>::NotEnoughRelayers)?;
+			T::Currency::reserve(sender, fee).map_err(|_| Error::<T>::FailedToReserveFee)?;
+
+			let assigned_relayers: BoundedVec<T::AccountId, T::AssignedRelayersNumber> =
+				Relayers::<T>::get()
+					.into_iter()
+					.take(T::AssignedRelayersNumber::get() as usize)
+					.map(|relayer| relayer.id)
+					.collect::<Vec<_>>()
+					.try_into()
+					.expect("we're taking at most `AssignedRelayersNumber` relayers; qed");
+			for relayer in &assigned_relayers {
+				ActiveOrders::<T>::mutate(relayer, |orders| *orders = orders.saturating_add(1));
+			}
+
+			Orders::<T>::insert(
+				(lane_id, nonce),
+				Order {
+					sender: sender.clone(),
+					fee,
+					sent_at: frame_system::Pallet::<T>::block_number(),
+					assigned_relayers,
+				},
+			);
+
+			Self::deposit_event(Event::<T>::OrderCreated { lane_id, nonce, fee });
+			Ok(fee)
+		}
+
+		/// Pay delivery fee to the relayer that has delivered the message and slash assigned
+		/// relayers if the message has been delivered late.
+		pub fn settle_order(lane_id: LaneId, nonce: MessageNonce, delivery_relayer: &T::AccountId) {
+			let order = match Orders::<T>::take((lane_id, nonce)) {
+				Some(order) => order,
+				None => return,
+			};
+
+			// pay delivery fee
+			let unpaid_fee = T::Currency::repatriate_reserved(
+				&order.sender,
+				delivery_relayer,
+				order.fee,
+				BalanceStatus::Free,
+			)
+			.unwrap_or(order.fee);
+			if !unpaid_fee.is_zero() {
+				log::trace!(
+					target: LOG_TARGET,
+					"Failed to pay {:?} of delivery fee for message {:?}/{} to {:?}",
+					unpaid_fee,
+					lane_id,
+					nonce,
+					delivery_relayer,
+				);
+			}
+
+			// slash assigned relayers if all delivery slots have ended
+			let slots = T::BlockNumber::unique_saturated_from(order.assigned_relayers.len() as u64);
+			let delivery_deadline =
+				order.sent_at.saturating_add(T::SlotLength::get().saturating_mul(slots));
+			let is_late = frame_system::Pallet::<T>::block_number() > delivery_deadline;
+			for relayer in &order.assigned_relayers {
+				ActiveOrders::<T>::mutate_exists(relayer, |orders| {
+					*orders = orders.map(|orders| orders.saturating_sub(1)).filter(|o| *o != 0)
+				});
+				if is_late {
+					Self::slash_relayer(relayer, delivery_relayer);
+				}
+			}
+
+			Self::deposit_event(Event::<T>::OrderSettled {
+				lane_id,
+				nonce,
+				delivery_relayer: delivery_relayer.clone(),
+				fee: order.fee.saturating_sub(unpaid_fee),
+			});
+		}
+
+		/// Cancel order of the message that has been cancelled by its sender, returning reserved
+		/// delivery fee back to the sender.
+		pub fn cancel_order(lane_id: LaneId, nonce: MessageNonce) {
+			let order = match Orders::<T>::take((lane_id, nonce)) {
+				Some(order) => order,
+				None => return,
+			};
+
+			let not_unreserved = T::Currency::unreserve(&order.sender, order.fee);
+			for relayer in &order.assigned_relayers {
+				ActiveOrders::<T>::mutate_exists(relayer, |orders| {
+					*orders = orders.map(|orders| orders.saturating_sub(1)).filter(|o| *o != 0)
+				});
+			}
+
+			Self::deposit_event(Event::<T>::OrderCancelled {
+				lane_id,
+				nonce,
+				fee: order.fee.saturating_sub(not_unreserved),
+			});
+		}
+
+		/// Slash collateral of the late relayer, removing it from the market if its collateral
+		/// drops below `MinCollateral`.
+		fn slash_relayer(relayer: &T::AccountId, beneficiary: &T::AccountId) {
+			Relayers::<T>::mutate(|relayers| {
+				let entry = match relayers.iter_mut().find(|r| r.id == *relayer) {
+					Some(entry) => entry,
+					None => return,
+				};
+
+				let slash = T::LateDeliverySlash::get().min(entry.collateral);
+				let not_slashed = T::Currency::repatriate_reserved(
+					relayer,
+					beneficiary,
+					slash,
+					BalanceStatus::Free,
+				)
+				.unwrap_or(slash);
+				let slashed = slash.saturating_sub(not_slashed);
+				entry.collateral = entry.collateral.saturating_sub(slashed);
+				Self::deposit_event(Event::<T>::RelayerSlashed {
+					relayer: relayer.clone(),
+					amount: slashed,
+				});
+
+				if entry.collateral < T::MinCollateral::get() {
+					T::Currency::unreserve(relayer, entry.collateral);
+					relayers.retain(|r| r.id != *relayer);
+					Self::deposit_event(Event::<T>::RelayerLeft { relayer: relayer.clone() });
+				}
+			});
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Relayer has enrolled to the market.
+		RelayerEnrolled {
+			/// Relayer account.
+			relayer: T::AccountId,
+			/// Reserved collateral.
+			collateral: BalanceOf<T>,
+			/// Price of delivering single message.
+			quote: BalanceOf<T>,
+		},
+		/// Relayer has updated its quote.
+		QuoteUpdated {
+			/// Relayer account.
+			relayer: T::AccountId,
+			/// New price of delivering single message.
+			quote: BalanceOf<T>,
+		},
+		/// Relayer has left the market.
+		RelayerLeft {
+			/// Relayer account.
+			relayer: T::AccountId,
+		},
+		/// Relayers have been assigned to the outbound message.
+		OrderCreated {
+			/// Message lane.
+			lane_id: LaneId,
+			/// Message nonce.
+			nonce: MessageNonce,
+			/// Delivery fee, paid by the sender.
+			fee: BalanceOf<T>,
+		},
+		/// Message delivery has been confirmed and delivery fee has been paid.
+		OrderSettled {
+			/// Message lane.
+			lane_id: LaneId,
+			/// Message nonce.
+			nonce: MessageNonce,
+			/// Relayer that has delivered the message.
+			delivery_relayer: T::AccountId,
+			/// Delivery fee, paid to the relayer.
+			fee: BalanceOf<T>,
+		},
+		/// Message has been cancelled by its sender and delivery fee has been returned.
+		OrderCancelled {
+			/// Message lane.
+			lane_id: LaneId,
+			/// Message nonce.
+			nonce: MessageNonce,
+			/// Delivery fee, returned to the sender.
+			fee: BalanceOf<T>,
+		},
+		/// Assigned relayer has been slashed for late message delivery.
+		RelayerSlashed {
+			/// Relayer account.
+			relayer: T::AccountId,
+			/// Slashed amount.
+			amount: BalanceOf<T>,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Collateral is below `MinCollateral`.
+		InsufficientCollateral,
+		/// Failed to reserve collateral at the relayer account.
+		FailedToReserveCollateral,
+		/// Relayer is already enrolled to the market.
+		AlreadyEnrolled,
+		/// Relayer is not enrolled to the market.
+		NotEnrolled,
+		/// Too many relayers are enrolled to the market.
+		TooManyRelayers,
+		/// Relayer is assigned to undelivered messages.
+		HasActiveOrders,
+		/// There are not enough relayers in the market to assign to the message.
+		NotEnoughRelayers,
+		/// Failed to reserve delivery fee at the sender account.
+		FailedToReserveFee,
+	}
+
+	/// Relayers that are enrolled to the market, ordered by their quotes.
+	#[pallet::storage]
+	pub type Relayers<T: Config> =
+		StorageValue<_, BoundedVec<RelayerOf<T>, T::MaxRelayers>, ValueQuery>;
+
+	/// Delivery orders of undelivered outbound messages.
+	#[pallet::storage]
+	pub type Orders<T: Config> =
+		StorageMap<_, Blake2_128Concat, (LaneId, MessageNonce), OrderOf<T>, OptionQuery>;
+
+	/// Number of undelivered messages that the relayer is assigned to.
+	#[pallet::storage]
+	pub type ActiveOrders<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+}
+
+/// Insert relayer into the quote-ordered relayers list. Relayer is inserted after all relayers
+/// with the same quote.
+fn insert_relayer<T: Config>(
+	relayers: &mut BoundedVec<RelayerOf<T>, T::MaxRelayers>,
+	relayer: RelayerOf<T>,
+) -> Result<(), Error<T>> {
+	let index = relayers.iter().position(|r| r.quote > relayer.quote).unwrap_or(relayers.len());
+	relayers.try_insert(index, relayer).map_err(|_| Error::<T>::TooManyRelayers)
+}
+
+/// Remove relayer from the relayers list.
+fn take_relayer<T: Config>(
+	relayers: &mut BoundedVec<RelayerOf<T>, T::MaxRelayers>,
+	relayer: &T::AccountId,
+) -> Result<RelayerOf<T>, Error<T>> {
+	let index = relayers.iter().position(|r| r.id == *relayer).ok_or(Error::<T>::NotEnrolled)?;
+	Ok(relayers.remove(index))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+	use bp_messages::{
+		source_chain::{DeliveryConfirmationPayments, LaneMessageVerifier, OnMessageCancelled},
+		DeliveredMessageDispatchResult, DeliveredMessages, OutboundLaneData, UnrewardedRelayer,
+	};
+	use frame_support::{assert_noop, assert_ok, traits::Contains, weights::Weight};
+
+	/// Dispatch-level result of messages in tests is the flag whether the message has been
+	/// dispatched.
+	struct IsNotDispatched;
+
+	impl Contains<DeliveredMessageDispatchResult> for IsNotDispatched {
+		fn contains(dispatch_result: &DeliveredMessageDispatchResult) -> bool {
+			dispatch_result.dispatch_level_result::<bool>() == Some(false)
+		}
+	}
+
+	fn enroll_relayers() {
+		for (relayer, quote) in [(RELAYER_1, 30), (RELAYER_2, 10), (RELAYER_3, 20)] {
+			assert_ok!(Pallet::<TestRuntime>::enroll(
+				RuntimeOrigin::signed(relayer),
+				COLLATERAL,
+				quote,
+			));
+		}
+	}
+
+	fn send_message(nonce: MessageNonce) {
+		let outbound_data =
+			OutboundLaneData { latest_generated_nonce: nonce - 1, ..Default::default() };
+		assert_ok!(FeeMarketMessageVerifier::<TestRuntime>::verify_message(
+			&RuntimeOrigin::signed(SENDER),
+			&TEST_LANE_ID,
+			&outbound_data,
+			&(),
+		));
+	}
+
+	fn confirm_delivery(nonce: MessageNonce, delivery_relayer: AccountId) {
+		DeliveryConfirmationPaymentsAdapter::<TestRuntime>::pay_reward(
+			TEST_LANE_ID,
+			vec![UnrewardedRelayer {
+				relayer: delivery_relayer,
+				messages: DeliveredMessages { begin: nonce, end: nonce },
+			}]
+			.into(),
+			&delivery_relayer,
+			&(nonce..=nonce),
+		);
+	}
+
+	fn cancel_message(nonce: MessageNonce, is_dispatched: bool) {
+		CancelledMessagesRefundAdapter::<TestRuntime, IsNotDispatched>::on_message_cancelled(
+			TEST_LANE_ID,
+			&SENDER,
+			&DeliveredMessageDispatchResult {
+				nonce,
+				unspent_weight: Weight::zero(),
+				encoded_dispatch_level_result: is_dispatched.encode(),
+			},
+		);
+	}
+
+	#[test]
+	fn enroll_keeps_relayers_ordered_by_quotes() {
+		run_test(|| {
+			enroll_relayers();
+
+			assert_eq!(
+				Relayers::<TestRuntime>::get().into_iter().map(|r| r.id).collect::<Vec<_>>(),
+				vec![RELAYER_2, RELAYER_3, RELAYER_1],
+			);
+			assert_eq!(Balances::reserved_balance(RELAYER_1), COLLATERAL);
+
+			assert_ok!(Pallet::<TestRuntime>::update_quote(RuntimeOrigin::signed(RELAYER_1), 5));
+			assert_eq!(
+				Relayers::<TestRuntime>::get().into_iter().map(|r| r.id).collect::<Vec<_>>(),
+				vec![RELAYER_1, RELAYER_2, RELAYER_3],
+			);
+		});
+	}
+
+	#[test]
+	fn enroll_rejects_invalid_requests() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::enroll(
+					RuntimeOrigin::signed(RELAYER_1),
+					MIN_COLLATERAL - 1,
+					10,
+				),
+				Error::<TestRuntime>::InsufficientCollateral,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::enroll(
+					RuntimeOrigin::signed(POOR_ACCOUNT),
+					MIN_COLLATERAL,
+					10,
+				),
+				Error::<TestRuntime>::FailedToReserveCollateral,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::enroll(
+				RuntimeOrigin::signed(RELAYER_1),
+				COLLATERAL,
+				10,
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::enroll(RuntimeOrigin::signed(RELAYER_1), COLLATERAL, 10),
+				Error::<TestRuntime>::AlreadyEnrolled,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::update_quote(RuntimeOrigin::signed(RELAYER_2), 10),
+				Error::<TestRuntime>::NotEnrolled,
+			);
+		});
+	}
+
+	#[test]
+	fn cancel_enrollment_works_only_without_active_orders() {
+		run_test(|| {
+			enroll_relayers();
+			send_message(1);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_enrollment(RuntimeOrigin::signed(RELAYER_2)),
+				Error::<TestRuntime>::HasActiveOrders,
+			);
+
+			confirm_delivery(1, RELAYER_2);
+			assert_ok!(Pallet::<TestRuntime>::cancel_enrollment(RuntimeOrigin::signed(RELAYER_2)));
+			assert_eq!(Balances::reserved_balance(RELAYER_2), 0);
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_enrollment(RuntimeOrigin::signed(RELAYER_2)),
+				Error::<TestRuntime>::NotEnrolled,
+			);
+		});
+	}
+
+	#[test]
+	fn sending_message_assigns_cheapest_relayers() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::market_fee(), None);
+			enroll_relayers();
+			assert_eq!(Pallet::<TestRuntime>::market_fee(), Some(20));
+
+			send_message(1);
+
+			let order = Orders::<TestRuntime>::get((TEST_LANE_ID, 1)).unwrap();
+			assert_eq!(order.sender, SENDER);
+			assert_eq!(order.fee, 20);
+			assert_eq!(order.assigned_relayers.into_inner(), vec![RELAYER_2, RELAYER_3]);
+			assert_eq!(Balances::reserved_balance(SENDER), 20);
+			assert_eq!(ActiveOrders::<TestRuntime>::get(RELAYER_2), 1);
+			assert_eq!(ActiveOrders::<TestRuntime>::get(RELAYER_1), 0);
+		});
+	}
+
+	#[test]
+	fn message_is_rejected_when_there_are_not_enough_relayers() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::enroll(
+				RuntimeOrigin::signed(RELAYER_1),
+				COLLATERAL,
+				10,
+			));
+
+			assert_eq!(
+				FeeMarketMessageVerifier::<TestRuntime>::verify_message(
+					&RuntimeOrigin::signed(SENDER),
+					&TEST_LANE_ID,
+					&OutboundLaneData::default(),
+					&(),
+				),
+				Err("NotEnoughRelayers"),
+			);
+			assert_eq!(
+				FeeMarketMessageVerifier::<TestRuntime>::verify_message(
+					&RuntimeOrigin::root(),
+					&TEST_LANE_ID,
+					&OutboundLaneData::default(),
+					&(),
+				),
+				Err(message_verifier::NON_SIGNED_SENDER),
+			);
+		});
+	}
+
+	#[test]
+	fn delivery_fee_is_paid_to_delivery_relayer() {
+		run_test(|| {
+			enroll_relayers();
+			send_message(1);
+
+			// message is delivered within the last delivery slot
+			System::set_block_number(2 * SLOT_LENGTH);
+			let relayer_balance = Balances::free_balance(RELAYER_1);
+			confirm_delivery(1, RELAYER_1);
+
+			assert_eq!(Balances::free_balance(RELAYER_1), relayer_balance + 20);
+			assert_eq!(Balances::reserved_balance(SENDER), 0);
+			assert_eq!(Balances::reserved_balance(RELAYER_2), COLLATERAL);
+			assert_eq!(Balances::reserved_balance(RELAYER_3), COLLATERAL);
+			assert!(Orders::<TestRuntime>::get((TEST_LANE_ID, 1)).is_none());
+			assert_eq!(ActiveOrders::<TestRuntime>::get(RELAYER_2), 0);
+		});
+	}
+
+	#[test]
+	fn late_assigned_relayers_are_slashed() {
+		run_test(|| {
+			enroll_relayers();
+			send_message(1);
+			send_message(2);
+
+			System::set_block_number(2 + 2 * SLOT_LENGTH);
+			let relayer_balance = Balances::free_balance(RELAYER_1);
+			confirm_delivery(1, RELAYER_1);
+
+			assert_eq!(
+				Balances::free_balance(RELAYER_1),
+				relayer_balance + 20 + 2 * LATE_DELIVERY_SLASH,
+			);
+			assert_eq!(Balances::reserved_balance(RELAYER_2), COLLATERAL - LATE_DELIVERY_SLASH);
+			assert_eq!(Balances::reserved_balance(RELAYER_3), COLLATERAL - LATE_DELIVERY_SLASH);
+
+			// after second slash, collateral drops below minimal and relayers leave the market
+			confirm_delivery(2, RELAYER_1);
+			assert_eq!(Balances::reserved_balance(RELAYER_2), 0);
+			assert_eq!(Balances::reserved_balance(RELAYER_3), 0);
+			assert_eq!(
+				Relayers::<TestRuntime>::get().into_iter().map(|r| r.id).collect::<Vec<_>>(),
+				vec![RELAYER_1],
+			);
+		});
+	}
+
+	#[test]
+	fn delivery_fee_is_returned_when_cancelled_message_is_not_dispatched() {
+		run_test(|| {
+			enroll_relayers();
+			send_message(1);
+			assert_eq!(Balances::reserved_balance(SENDER), 20);
+			assert_eq!(ActiveOrders::<TestRuntime>::get(RELAYER_2), 1);
+
+			// nothing is returned if the message has been dispatched
+			cancel_message(1, true);
+			assert_eq!(Balances::reserved_balance(SENDER), 20);
+			assert!(Orders::<TestRuntime>::get((TEST_LANE_ID, 1)).is_some());
+
+			cancel_message(1, false);
+			assert_eq!(Balances::reserved_balance(SENDER), 0);
+			assert!(Orders::<TestRuntime>::get((TEST_LANE_ID, 1)).is_none());
+			assert_eq!(ActiveOrders::<TestRuntime>::get(RELAYER_2), 0);
+			assert_eq!(ActiveOrders::<TestRuntime>::get(RELAYER_3), 0);
+		});
+	}
+}
@@ -0,0 +1,58 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Code that allows fee market pallet to assign relayers to messages, sent using the messages
+//! pallet.
+
+use crate::{Config, Pallet};
+
+use bp_messages::{source_chain::LaneMessageVerifier, LaneId, OutboundLaneData};
+use frame_system::RawOrigin;
+use sp_std::marker::PhantomData;
+
+/// Error that is returned when message is sent by non-signed origin.
+pub const NON_SIGNED_SENDER: &str = "Only signed origins may send messages through fee market";
+
+/// Message verifier that assigns relayers to every outbound message and reserves delivery fee
+/// at the sender account.
+///
+/// Only messages from signed origins are accepted.
+pub struct FeeMarketMessageVerifier<T>(PhantomData<T>);
+
+impl<T, SenderOrigin, Payload> LaneMessageVerifier<SenderOrigin, Payload>
+	for FeeMarketMessageVerifier<T>
+where
+	T: Config,
+	SenderOrigin: Clone + Into<Result<RawOrigin<T::AccountId>, SenderOrigin>>,
+{
+	type Error = &'static str;
+
+	fn verify_message(
+		submitter: &SenderOrigin,
+		lane: &LaneId,
+		outbound_data: &OutboundLaneData,
+		_payload: &Payload,
+	) -> Result<(), Self::Error> {
+		let sender = match submitter.clone().into() {
+			Ok(RawOrigin::Signed(sender)) => sender,
+			_ => return Err(NON_SIGNED_SENDER),
+		};
+
+		Pallet::<T>::create_order(&sender, *lane, outbound_data.latest_generated_nonce + 1)
+			.map(drop)
+			.map_err(Into::into)
+	}
+}
@@ -0,0 +1,131 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(test)]
+
+use crate as pallet_bridge_fee_market;
+
+use bp_messages::LaneId;
+use frame_support::traits::{ConstU32, ConstU64};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header as SubstrateHeader,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+pub type AccountId = u64;
+pub type Balance = u64;
+pub type BlockNumber = u64;
+
+type Block = frame_system::mocking::MockBlock<TestRuntime>;
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
+
+frame_support::construct_runtime! {
+	pub enum TestRuntime where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Event<T>},
+		FeeMarket: pallet_bridge_fee_market::{Pallet, Call, Event<T>},
+	}
+}
+
+impl frame_system::Config for TestRuntime {
+	type RuntimeOrigin = RuntimeOrigin;
+	type Index = u64;
+	type RuntimeCall = RuntimeCall;
+	type BlockNumber = BlockNumber;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = SubstrateHeader;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type BaseCallFilter = frame_support::traits::Everything;
+	type SystemWeightInfo = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for TestRuntime {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type DustRemoval = ();
+	type RuntimeEvent = RuntimeEvent;
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = frame_system::Pallet<TestRuntime>;
+	type WeightInfo = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = ();
+}
+
+impl pallet_bridge_fee_market::Config for TestRuntime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type MinCollateral = ConstU64<MIN_COLLATERAL>;
+	type MaxRelayers = ConstU32<4>;
+	type AssignedRelayersNumber = ConstU32<2>;
+	type SlotLength = ConstU64<SLOT_LENGTH>;
+	type LateDeliverySlash = ConstU64<LATE_DELIVERY_SLASH>;
+	type WeightInfo = ();
+}
+
+/// Message lane that we're using in tests.
+pub const TEST_LANE_ID: LaneId = LaneId([0, 0, 0, 0]);
+
+/// Minimal relayer collateral.
+pub const MIN_COLLATERAL: Balance = 100;
+/// Collateral that relayers are locking in tests.
+pub const COLLATERAL: Balance = 150;
+/// Number of blocks that every assigned relayer has to deliver the message.
+pub const SLOT_LENGTH: BlockNumber = 10;
+/// Collateral that is slashed from late relayers.
+pub const LATE_DELIVERY_SLASH: Balance = 30;
+
+/// Account that is sending messages.
+pub const SENDER: AccountId = 1;
+/// Account that has no funds.
+pub const POOR_ACCOUNT: AccountId = 2;
+/// First relayer account.
+pub const RELAYER_1: AccountId = 10;
+/// Second relayer account.
+pub const RELAYER_2: AccountId = 20;
+/// Third relayer account.
+pub const RELAYER_3: AccountId = 30;
+
+/// Run pallet test.
+pub fn run_test<T>(test: impl FnOnce() -> T) -> T {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<TestRuntime>().unwrap();
+	pallet_balances::GenesisConfig::<TestRuntime> {
+		balances: vec![(SENDER, 1_000), (RELAYER_1, 1_000), (RELAYER_2, 1_000), (RELAYER_3, 1_000)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(test)
+}
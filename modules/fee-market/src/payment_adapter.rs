@@ -0,0 +1,91 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Code that allows fee market pallet to be used as a payment mechanism for the messages pallet.
+
+use crate::{weights::WeightInfo, Config, Pallet};
+
+use bp_messages::{
+	source_chain::{DeliveryConfirmationPayments, OnMessageCancelled},
+	DeliveredMessageDispatchResult, LaneId, MessageNonce, UnrewardedRelayer,
+};
+use frame_support::{
+	traits::{Contains, Get},
+	weights::Weight,
+};
+use sp_std::{collections::vec_deque::VecDeque, marker::PhantomData, ops::RangeInclusive};
+
+/// Adapter that pays delivery fees to relayers that have delivered messages and slashes late
+/// assigned relayers.
+pub struct DeliveryConfirmationPaymentsAdapter<T>(PhantomData<T>);
+
+impl<T: Config> DeliveryConfirmationPayments<T::AccountId>
+	for DeliveryConfirmationPaymentsAdapter<T>
+{
+	type Error = &'static str;
+
+	fn pay_reward_weight(messages: MessageNonce) -> Weight {
+		// order of every confirmed message is settled
+		T::WeightInfo::settle_order().saturating_mul(messages)
+	}
+
+	fn pay_reward(
+		lane_id: LaneId,
+		messages_relayers: VecDeque<UnrewardedRelayer<T::AccountId>>,
+		_confirmation_relayer: &T::AccountId,
+		received_range: &RangeInclusive<MessageNonce>,
+	) {
+		for entry in messages_relayers {
+			let begin = sp_std::cmp::max(entry.messages.begin, *received_range.start());
+			let end = sp_std::cmp::min(entry.messages.end, *received_range.end());
+			for nonce in begin..=end {
+				Pallet::<T>::settle_order(lane_id, nonce, &entry.relayer);
+			}
+		}
+	}
+}
+
+/// Adapter that returns delivery fee to the sender of cancelled message, if its dispatch result
+/// proves that the message has not been dispatched at the bridged chain.
+///
+/// The `IsNotDispatched` shall only accept dispatch results, that are proving that the message
+/// has not been dispatched. It depends on the dispatch-level result type, used by the message
+/// dispatcher at the bridged chain.
+pub struct CancelledMessagesRefundAdapter<T, IsNotDispatched>(PhantomData<(T, IsNotDispatched)>);
+
+impl<T, IsNotDispatched> OnMessageCancelled<T::AccountId>
+	for CancelledMessagesRefundAdapter<T, IsNotDispatched>
+where
+	T: Config,
+	IsNotDispatched: Contains<DeliveredMessageDispatchResult>,
+{
+	fn on_message_cancelled_weight() -> Weight {
+		// order is read and removed, sender account is updated and active orders counter of
+		// every assigned relayer is updated
+		let assigned_relayers = T::AssignedRelayersNumber::get() as u64;
+		T::DbWeight::get().reads_writes(2 + assigned_relayers, 2 + assigned_relayers)
+	}
+
+	fn on_message_cancelled(
+		lane: LaneId,
+		_sender: &T::AccountId,
+		dispatch_result: &DeliveredMessageDispatchResult,
+	) {
+		if IsNotDispatched::contains(dispatch_result) {
+			Pallet::<T>::cancel_order(lane, dispatch_result.nonce)
+		}
+	}
+}
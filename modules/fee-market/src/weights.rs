@@ -0,0 +1,82 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights for `pallet_bridge_fee_market`.
+//!
+//! The `pallet_bridge_fee_market` benchmarks (see the `benchmarking` module) have not been
+//! executed on the reference hardware yet, so there's no `BridgeWeight` implementation here. The
+//! weights of the `()` implementation are provisional estimates, used in tests only. They must be
+//! replaced with the benchmark output once the pallet is used by some runtime.
+
+// TODO: generate using the `pallet_bridge_fee_market` benchmarks on the reference hardware.
+//
+// Command to generate (the benchmark must be registered in the runtime first):
+// target/release/millau-bridge-node
+// benchmark
+// pallet
+// --chain=dev
+// --steps=50
+// --repeat=20
+// --pallet=pallet_bridge_fee_market
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=Compiled
+// --heap-pages=4096
+// --output=./modules/fee-market/src/weights.rs
+// --template=./.maintain/millau-weight-template.hbs
+
+#![allow(clippy::all)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{
+	traits::Get,
+	weights::{constants::RocksDbWeight, Weight},
+};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for `pallet_bridge_fee_market`.
+pub trait WeightInfo {
+	fn enroll() -> Weight;
+	fn update_quote() -> Weight;
+	fn cancel_enrollment() -> Weight;
+	fn settle_order() -> Weight;
+}
+
+// Provisional weights for tests
+impl WeightInfo for () {
+	fn enroll() -> Weight {
+		Weight::from_ref_time(62_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	fn update_quote() -> Weight {
+		Weight::from_ref_time(38_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	fn cancel_enrollment() -> Weight {
+		Weight::from_ref_time(58_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	fn settle_order() -> Weight {
+		Weight::from_ref_time(126_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().writes(9 as u64))
+	}
+}
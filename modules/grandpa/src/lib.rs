@@ -28,7 +28,8 @@
 //!
 //! Since this pallet only tracks finalized headers it does not deal with forks. Forks can only
 //! occur if the GRANDPA validator set on the bridged chain is either colluding or there is a severe
-//! bug causing resulting in an equivocation. Such events are outside the scope of this pallet.
+//! bug causing resulting in an equivocation. The pallet accepts equivocation reports and passes
+//! them to the `OnEquivocation` handler, so that the runtime may react (e.g. halt the bridge).
 //! Shall the fork occur on the bridged chain governance intervention will be required to
 //! re-initialize the bridge and track the right fork.
 
@@ -42,18 +43,25 @@ use bp_header_chain::{
 	justification::GrandpaJustification, HeaderChain, InitializationData, StoredHeaderData,
 	StoredHeaderDataBuilder,
 };
-use bp_runtime::{BlockNumberOf, Chain, HashOf, HasherOf, HeaderId, HeaderOf, OwnedBridgeModule};
+use bp_runtime::{
+	BlockNumberOf, Chain, HashOf, HasherOf, HeaderId, HeaderIdOf, HeaderOf, OwnedBridgeModule,
+};
+use codec::Encode;
 use finality_grandpa::voter_set::VoterSet;
-use frame_support::{ensure, fail};
-use sp_finality_grandpa::{ConsensusLog, GRANDPA_ENGINE_ID};
+use frame_support::{dispatch::PostDispatchInfo, ensure, fail, weights::Weight};
+use sp_finality_grandpa::{
+	ConsensusLog, SetId, VersionedAuthorityList, GRANDPA_AUTHORITIES_KEY, GRANDPA_ENGINE_ID,
+};
 use sp_runtime::traits::{Header as HeaderT, Zero};
-use sp_std::{boxed::Box, convert::TryInto};
+use sp_std::{boxed::Box, convert::TryInto, marker::PhantomData, vec::Vec};
 
 mod extension;
 #[cfg(test)]
 mod mock;
 mod storage_types;
 
+pub mod migration;
+
 /// Module, containing weights for this pallet.
 pub mod weights;
 
@@ -90,8 +98,15 @@ pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
 
+	/// The current storage version.
+	pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::config]
 	pub trait Config<I: 'static = ()>: frame_system::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self, I>>
+			+ IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
 		/// The chain we are bridging to here.
 		type BridgedChain: Chain;
 
@@ -118,11 +133,78 @@ pub mod pallet {
 		#[pallet::constant]
 		type MaxBridgedAuthorities: Get<u32>;
 
+		/// Maximal size (in bytes) of the SCALE-encoded finality proof (justification).
+		///
+		/// Larger finality proofs are rejected before verifying any signatures, so that
+		/// adversarially bloated proofs can't be used to waste block weight.
+		#[pallet::constant]
+		type MaxFinalityProofSize: Get<u32>;
+
+		/// Maximal number of previous authority sets to keep in the storage.
+		///
+		/// Previous authority sets are used to verify finality proofs of already finalized headers,
+		/// that they have finalized before the handoff to the next set (e.g. when reporting
+		/// equivocations or when conflicting finality proof is submitted). New headers are always
+		/// verified using the current set. Every verification that may use previous sets is
+		/// charged for reading at most `AuthoritySetsToKeep` sets.
+		#[pallet::constant]
+		type AuthoritySetsToKeep: Get<u32>;
+
+		/// Trusted checkpoint of the bridged chain, that may be used to initialize the pallet
+		/// without involving the pallet owner (or root).
+		///
+		/// It is the hash of the bridged chain header and the id of the GRANDPA authority set
+		/// that is active at this header. Permissionless initialization is disabled if it is
+		/// `None`.
+		type TrustedCheckpoint: Get<Option<(BridgedBlockHash<Self, I>, SetId)>>;
+
+		/// Minimal difference between numbers of the best finalized header and the imported
+		/// non-mandatory header, which makes the import transaction free.
+		///
+		/// Mandatory headers are always imported for free. If it is `None`, all other headers are
+		/// paid. Otherwise, relayer may import one free header every `FreeHeadersInterval`
+		/// bridged chain blocks. It may be used to bound relayer costs at low-traffic bridges.
+		#[pallet::constant]
+		type FreeHeadersInterval: Get<Option<u32>>;
+
+		/// Maximal difference between numbers of the best finalized header and the imported
+		/// header.
+		///
+		/// If valid finality proof for a header that is too far ahead of the best finalized
+		/// header is submitted, the header is not imported and the pallet is halted. The same
+		/// happens if valid finality proof for a header that conflicts with already imported
+		/// header is submitted. It is up to the pallet owner (or root) to resume pallet
+		/// operations. If it is `None`, the distance between finalized headers is not checked.
+		#[pallet::constant]
+		type MaxFinalizedHeadersJump: Get<Option<u32>>;
+
+		/// Handler of GRANDPA equivocations, reported using the `report_equivocation` call.
+		///
+		/// Runtimes may use it to slash bridged chain authorities or to halt the bridge (see
+		/// `HaltBridgeOnEquivocation`).
+		type OnEquivocation: OnEquivocation<Self::BridgedChain>;
+
+		/// Maximal number of this chain blocks without new bridged headers, imported by the
+		/// pallet, after which the bridge is considered stalled.
+		///
+		/// When the bridge is stalled, the `BridgeStalled` event is deposited and the
+		/// `OnBridgeStalled` handler is called. It happens once per stall - the next stall may
+		/// only be detected after the next header import. If it is `None`, stalls are not
+		/// detected.
+		#[pallet::constant]
+		type MaxStallPeriod: Get<Option<Self::BlockNumber>>;
+
+		/// Handler of bridge stalls.
+		///
+		/// Runtimes may use it to alert bridge operators or to boost relayer incentives.
+		type OnBridgeStalled: OnBridgeStalled<Self::BridgedChain>;
+
 		/// Weights gathered through benchmarking.
 		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
 
 	#[pallet::hooks]
@@ -132,6 +214,14 @@ pub mod pallet {
 
 			T::DbWeight::get().reads_writes(1, 1)
 		}
+
+		fn on_idle(block: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			// continue the multi-block storage migration (if it is in progress)
+			let migration_weight = migration::migrate_to_v1_step::<T, I>(remaining_weight);
+			let remaining_weight = remaining_weight.saturating_sub(migration_weight);
+
+			migration_weight.saturating_add(Self::detect_stall(block, remaining_weight))
+		}
 	}
 
 	impl<T: Config<I>, I: 'static> OwnedBridgeModule<T> for Pallet<T, I> {
@@ -154,7 +244,7 @@ pub mod pallet {
 		#[pallet::weight(T::WeightInfo::submit_finality_proof(
 			justification.commit.precommits.len().try_into().unwrap_or(u32::MAX),
 			justification.votes_ancestries.len().try_into().unwrap_or(u32::MAX),
-		))]
+		).saturating_add(authority_sets_history_weight::<T, I>()))]
 		pub fn submit_finality_proof(
 			_origin: OriginFor<T>,
 			finality_target: Box<BridgedHeader<T, I>>,
@@ -164,7 +254,7 @@ pub mod pallet {
 
 			ensure!(Self::request_count() < T::MaxRequests::get(), <Error<T, I>>::TooManyRequests);
 
-			let (hash, number) = (finality_target.hash(), finality_target.number());
+			let (hash, number) = (finality_target.hash(), *finality_target.number());
 			log::trace!(
 				target: LOG_TARGET,
 				"Going to try and finalize header {:?}",
@@ -183,19 +273,24 @@ pub mod pallet {
 				},
 			};
 
-			// We do a quick check here to ensure that our header chain is making progress and isn't
-			// "travelling back in time" (which could be indicative of something bad, e.g a
-			// hard-fork).
-			ensure!(best_finalized_number < *number, <Error<T, I>>::OldHeader);
-
-			let authority_set = <CurrentAuthoritySet<T, I>>::get();
-			let set_id = authority_set.set_id;
-			verify_justification::<T, I>(&justification, hash, *number, authority_set.into())?;
-
-			let is_authorities_change_enacted =
-				try_enact_authority_change::<T, I>(&finality_target, set_id)?;
+			let mut authority_set: bp_header_chain::AuthoritySet =
+				<CurrentAuthoritySet<T, I>>::get().into();
+			if halt_on_suspicious_header::<T, I>(
+				&justification,
+				best_finalized_number,
+				hash,
+				number,
+				&authority_set,
+			)? {
+				return Ok(Pays::Yes.into())
+			}
+			let (is_mandatory_header, verified_precommits) = verify_and_import_header::<T, I>(
+				*finality_target,
+				&justification,
+				best_finalized_number,
+				&mut authority_set,
+			)?;
 			<RequestCount<T, I>>::mutate(|count| *count += 1);
-			insert_header::<T, I>(*finality_target, hash);
 			log::info!(
 				target: LOG_TARGET,
 				"Successfully imported finalized header with hash {:?}!",
@@ -206,11 +301,20 @@ pub mod pallet {
 			// further without importing this header. So every bridge MUST import mandatory headers.
 			//
 			// We don't want to charge extra costs for mandatory operations. So relayer is not
-			// paying fee for mandatory headers import transactions.
-			let is_mandatory_header = is_authorities_change_enacted;
-			let pays_fee = if is_mandatory_header { Pays::No } else { Pays::Yes };
-
-			Ok(pays_fee.into())
+			// paying fee for mandatory headers import transactions. Additionally, the runtime may
+			// allow importing one free header every `FreeHeadersInterval` blocks.
+			let is_free_header = is_free_header::<T, I>(best_finalized_number, number);
+			let pays_fee = if is_mandatory_header || is_free_header { Pays::No } else { Pays::Yes };
+
+			// precommits of unknown authorities and duplicate precommits are not verified, so we
+			// may refund the weight of their verification
+			let actual_weight = T::WeightInfo::submit_finality_proof(
+				verified_precommits,
+				justification.votes_ancestries.len().try_into().unwrap_or(u32::MAX),
+			)
+			.saturating_add(authority_sets_history_weight::<T, I>());
+
+			Ok(PostDispatchInfo { actual_weight: Some(actual_weight), pays_fee })
 		}
 
 		/// Bootstrap the bridge pallet with an initial header and authority set from which to sync.
@@ -263,6 +367,249 @@ pub mod pallet {
 		) -> DispatchResult {
 			<Self as OwnedBridgeModule<_>>::set_operating_mode(origin, operating_mode)
 		}
+
+		/// Report equivocation of the bridged chain GRANDPA authorities.
+		///
+		/// Both justifications must be signed by the same authority set, known to the pallet
+		/// (either current, or one of `AuthoritySetsToKeep` previous sets).
+		/// They must belong to the same GRANDPA round and finalize different headers with the same
+		/// number. Every equivocation may only be reported once. The verified equivocation is
+		/// passed to the `OnEquivocation` handler.
+		///
+		/// The call must be signed. The transaction is free if the equivocation is verified.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::WeightInfo::submit_finality_proof(
+			first.commit.precommits.len().try_into().unwrap_or(u32::MAX),
+			first.votes_ancestries.len().try_into().unwrap_or(u32::MAX),
+		).saturating_add(T::WeightInfo::submit_finality_proof(
+			second.commit.precommits.len().try_into().unwrap_or(u32::MAX),
+			second.votes_ancestries.len().try_into().unwrap_or(u32::MAX),
+		)).saturating_add(authority_sets_history_weight::<T, I>().saturating_mul(2)))]
+		pub fn report_equivocation(
+			origin: OriginFor<T>,
+			first: GrandpaJustification<BridgedHeader<T, I>>,
+			second: GrandpaJustification<BridgedHeader<T, I>>,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+			ensure!(
+				first.round == second.round &&
+					first.commit.target_number == second.commit.target_number &&
+					first.commit.target_hash != second.commit.target_hash,
+				<Error<T, I>>::NotEquivocation
+			);
+
+			let first_id = HeaderId(first.commit.target_number, first.commit.target_hash);
+			let second_id = HeaderId(second.commit.target_number, second.commit.target_hash);
+			let set_id = verify_justification_with_history::<T, I>(&first, first_id.1, first_id.0)?;
+			let second_set_id =
+				verify_justification_with_history::<T, I>(&second, second_id.1, second_id.0)?;
+			ensure!(set_id == second_set_id, <Error<T, I>>::NotEquivocation);
+
+			let round = first.round;
+			ensure!(
+				!<ReportedEquivocations<T, I>>::contains_key((set_id, round)),
+				<Error<T, I>>::EquivocationAlreadyReported
+			);
+
+			<ReportedEquivocations<T, I>>::insert((set_id, round), first_id.0);
+			log::warn!(
+				target: LOG_TARGET,
+				"Authority set {} has finalized both {:?} and {:?} at round {}",
+				set_id,
+				first_id,
+				second_id,
+				round,
+			);
+
+			T::OnEquivocation::on_equivocation(set_id, round, first_id, second_id);
+
+			Ok(Pays::No.into())
+		}
+
+		/// Replace the tracked authority set and the best finalized header.
+		///
+		/// The pallet is unable to follow forced GRANDPA authority set changes of the bridged
+		/// chain, because they are not justified. This call may be used to recover the bridge
+		/// after such change. The `header` becomes the best finalized header and the new
+		/// authorities are expected to finalize its descendants. The replaced authority set is
+		/// moved to the `PreviousAuthoritySets`. The `header` must not be older than the current
+		/// best finalized header, so the best finalized header never goes backwards.
+		///
+		/// May only be called either by root, or by `PalletOwner`. Just like `initialize`, it
+		/// writes to storage with no other checks of the data validity.
+		#[pallet::call_index(5)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(3, 7), DispatchClass::Operational))]
+		pub fn force_set_authorities(
+			origin: OriginFor<T>,
+			header: Box<BridgedHeader<T, I>>,
+			authority_list: sp_finality_grandpa::AuthorityList,
+			set_id: SetId,
+		) -> DispatchResult {
+			Self::ensure_owner_or_root(origin)?;
+			let best_finalized_number =
+				Self::best_finalized_number().ok_or(<Error<T, I>>::NotInitialized)?;
+
+			let hash = header.hash();
+			let number = *header.number();
+			ensure!(number >= best_finalized_number, <Error<T, I>>::OldHeader);
+
+			let authority_set = StoredAuthoritySet::<T, I>::try_new(authority_list, set_id)
+				.map_err(|_| <Error<T, I>>::TooManyAuthoritiesInSet)?;
+			archive_current_authority_set::<T, I>(best_finalized_number);
+			insert_header::<T, I>(*header, hash);
+			<CurrentAuthoritySet<T, I>>::put(&authority_set);
+
+			log::info!(
+				target: LOG_TARGET,
+				"Forced authority set {} at header {:?}. New authorities are: {:?}",
+				set_id,
+				HeaderId(number, hash),
+				authority_set,
+			);
+
+			Ok(())
+		}
+
+		/// Pin imported header, so that it is not pruned until it is unpinned.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::call_index(6)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(3, 2), DispatchClass::Operational))]
+		pub fn pin_header(origin: OriginFor<T>, hash: BridgedBlockHash<T, I>) -> DispatchResult {
+			Self::ensure_owner_or_root(origin)?;
+			Self::pin_imported_header(hash).map_err(Into::into)
+		}
+
+		/// Unpin previously pinned header.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::call_index(7)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(3, 4), DispatchClass::Operational))]
+		pub fn unpin_header(origin: OriginFor<T>, hash: BridgedBlockHash<T, I>) -> DispatchResult {
+			Self::ensure_owner_or_root(origin)?;
+			Self::unpin_imported_header(hash).map_err(Into::into)
+		}
+
+		/// Verify and import several consecutive finalized headers.
+		///
+		/// Headers must be ordered by their numbers. Every header is verified using the authority
+		/// set, enacted by previous headers of the batch. Every imported header counts as a
+		/// separate request (see `MaxRequests`), so there may be at most `MaxRequests` headers in
+		/// the batch. The transaction is free if every header of the batch is either mandatory,
+		/// or free (see `FreeHeadersInterval`).
+		#[pallet::call_index(8)]
+		#[pallet::weight(finality_proofs.iter().fold(Weight::zero(), |weight, (_, justification)| {
+			weight.saturating_add(T::WeightInfo::submit_finality_proof(
+				justification.commit.precommits.len().try_into().unwrap_or(u32::MAX),
+				justification.votes_ancestries.len().try_into().unwrap_or(u32::MAX),
+			)).saturating_add(authority_sets_history_weight::<T, I>())
+		}))]
+		pub fn submit_finality_proof_batch(
+			_origin: OriginFor<T>,
+			finality_proofs: BoundedVec<
+				(BridgedHeader<T, I>, GrandpaJustification<BridgedHeader<T, I>>),
+				T::MaxRequests,
+			>,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+
+			ensure!(!finality_proofs.is_empty(), <Error<T, I>>::EmptyBatch);
+			// every header of the batch is a separate request
+			ensure!(
+				Self::request_count().saturating_add(finality_proofs.len() as u32) <=
+					T::MaxRequests::get(),
+				<Error<T, I>>::TooManyRequests
+			);
+
+			let mut best_finalized_number =
+				Self::best_finalized_number().ok_or(<Error<T, I>>::NotInitialized)?;
+			let mut authority_set: bp_header_chain::AuthoritySet =
+				<CurrentAuthoritySet<T, I>>::get().into();
+			let mut pays_fee = Pays::No;
+			for (finality_target, justification) in finality_proofs {
+				let (hash, number) = (finality_target.hash(), *finality_target.number());
+				if halt_on_suspicious_header::<T, I>(
+					&justification,
+					best_finalized_number,
+					hash,
+					number,
+					&authority_set,
+				)? {
+					pays_fee = Pays::Yes;
+					break
+				}
+				let (is_mandatory_header, _) = verify_and_import_header::<T, I>(
+					finality_target,
+					&justification,
+					best_finalized_number,
+					&mut authority_set,
+				)?;
+				if !is_mandatory_header && !is_free_header::<T, I>(best_finalized_number, number) {
+					pays_fee = Pays::Yes;
+				}
+				best_finalized_number = number;
+				<RequestCount<T, I>>::mutate(|count| *count += 1);
+			}
+			log::info!(
+				target: LOG_TARGET,
+				"Successfully imported batch of finalized headers. Best header: {:?}",
+				BestFinalized::<T, I>::get(),
+			);
+
+			Ok(pays_fee.into())
+		}
+
+		/// Bootstrap the bridge pallet using the `TrustedCheckpoint`.
+		///
+		/// Unlike `initialize`, this call may be dispatched by any signed origin. The `header`
+		/// must match the configured trusted checkpoint and the `authority_set_proof` must be the
+		/// storage proof of the GRANDPA authorities (`:grandpa_authorities` key), read at this
+		/// header. The pallet starts in the normal operating mode.
+		///
+		/// The call is weighed by the proof size and by the maximal number of authorities. The
+		/// weight of missing authorities is refunded.
+		#[pallet::call_index(9)]
+		#[pallet::weight(initialize_with_proof_weight::<T, I>(
+			authority_set_proof_size(authority_set_proof),
+			T::MaxBridgedAuthorities::get(),
+		))]
+		pub fn initialize_with_proof(
+			origin: OriginFor<T>,
+			header: Box<BridgedHeader<T, I>>,
+			authority_set_proof: Vec<Vec<u8>>,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+
+			let init_allowed = !<BestFinalized<T, I>>::exists();
+			ensure!(init_allowed, <Error<T, I>>::AlreadyInitialized);
+
+			let (trusted_hash, set_id) =
+				T::TrustedCheckpoint::get().ok_or(<Error<T, I>>::UntrustedCheckpoint)?;
+			ensure!(header.hash() == trusted_hash, <Error<T, I>>::UntrustedCheckpoint);
+
+			let proof_size = authority_set_proof_size(&authority_set_proof);
+			let authority_list =
+				read_authority_list::<T, I>(*header.state_root(), authority_set_proof)?;
+			let actual_weight = initialize_with_proof_weight::<T, I>(
+				proof_size,
+				authority_list.len().try_into().unwrap_or(u32::MAX),
+			);
+			let init_data = super::InitializationData {
+				header,
+				authority_list,
+				set_id,
+				operating_mode: BasicOperatingMode::Normal,
+			};
+			initialize_bridge::<T, I>(init_data.clone())?;
+
+			log::info!(
+				target: LOG_TARGET,
+				"Pallet has been initialized using trusted checkpoint: {:?}",
+				init_data
+			);
+
+			Ok(Some(actual_weight).into())
+		}
 	}
 
 	/// The current number of requests which have written to storage.
@@ -302,11 +649,54 @@ pub mod pallet {
 	pub type ImportedHeaders<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Identity, BridgedBlockHash<T, I>, BridgedStoredHeaderData<T, I>>;
 
+	/// Imported headers that are not pruned when they're leaving the `ImportedHashes` ring buffer.
+	///
+	/// The value is `true` if the header has already left the ring buffer. Such header is pruned
+	/// when it is unpinned. At most `HeadersToKeep` headers may be pinned at once.
+	#[pallet::storage]
+	pub type PinnedHeaders<T: Config<I>, I: 'static = ()> =
+		CountedStorageMap<_, Identity, BridgedBlockHash<T, I>, bool>;
+
+	/// Hashes of imported headers, indexed by their numbers.
+	///
+	/// Entries are pruned together with `ImportedHeaders`. It is used to check whether the
+	/// imported header is an ancestor of the best finalized header. Headers, imported before
+	/// the storage version 1, are indexed by the `migration::MigrateToV1`.
+	#[pallet::storage]
+	pub type ImportedHeaderHashes<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, BridgedBlockNumber<T, I>, BridgedBlockHash<T, I>>;
+
 	/// The current GRANDPA Authority set.
 	#[pallet::storage]
 	pub(super) type CurrentAuthoritySet<T: Config<I>, I: 'static = ()> =
 		StorageValue<_, StoredAuthoritySet<T, I>, ValueQuery>;
 
+	/// Previous GRANDPA authority sets, mapped to the number of the last header that the set
+	/// has been able to finalize.
+	///
+	/// At most `AuthoritySetsToKeep` sets are stored.
+	#[pallet::storage]
+	pub(super) type PreviousAuthoritySets<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Identity, SetId, (StoredAuthoritySet<T, I>, BridgedBlockNumber<T, I>)>;
+
+	/// Equivocations that have been reported using the `report_equivocation` call.
+	///
+	/// Maps the authority set id and GRANDPA round to the number of header that has been
+	/// finalized twice.
+	#[pallet::storage]
+	#[pallet::getter(fn reported_equivocation)]
+	pub type ReportedEquivocations<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, (SetId, u64), BridgedBlockNumber<T, I>>;
+
+	/// Number of this chain block, where the last bridged header has been imported.
+	#[pallet::storage]
+	pub type LastImportBlockNumber<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, T::BlockNumber, OptionQuery>;
+
+	/// Is `true` if the bridge has been stalled and no headers have been imported since then.
+	#[pallet::storage]
+	pub type IsStalled<T: Config<I>, I: 'static = ()> = StorageValue<_, bool, ValueQuery>;
+
 	/// Optional pallet owner.
 	///
 	/// Pallet owner has a right to halt all pallet operations and then resume it. If it is
@@ -356,6 +746,26 @@ pub mod pallet {
 		}
 	}
 
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config<I>, I: 'static = ()> {
+		/// The pallet has been halted because of suspicious finality proof. Pallet operations
+		/// must be resumed by the pallet owner (or root).
+		SuspiciousFinalityProof {
+			/// The best finalized header at the moment of submission.
+			best_finalized: BridgedBlockId<T, I>,
+			/// The header, finalized by the suspicious finality proof.
+			finalized: BridgedBlockId<T, I>,
+		},
+		/// No bridged headers have been imported for more than `MaxStallPeriod` blocks.
+		BridgeStalled {
+			/// The best finalized header, known to the pallet.
+			best_finalized: BridgedBlockId<T, I>,
+			/// Number of this chain block, where the best finalized header has been imported.
+			last_import_block_number: T::BlockNumber,
+		},
+	}
+
 	#[pallet::error]
 	pub enum Error<T, I = ()> {
 		/// The given justification is invalid for the given header.
@@ -376,6 +786,27 @@ pub mod pallet {
 		AlreadyInitialized,
 		/// Too many authorities in the set.
 		TooManyAuthoritiesInSet,
+		/// The reported justifications are not proving an equivocation.
+		NotEquivocation,
+		/// The equivocation has already been reported.
+		EquivocationAlreadyReported,
+		/// The header is unknown to the pallet.
+		UnknownHeader,
+		/// The header is already pinned.
+		AlreadyPinned,
+		/// The header is not pinned.
+		NotPinned,
+		/// Too many headers are pinned.
+		TooManyPinnedHeaders,
+		/// The batch of finality proofs is empty.
+		EmptyBatch,
+		/// The finality proof is larger than `MaxFinalityProofSize`.
+		TooLargeFinalityProof,
+		/// The header doesn't match the `TrustedCheckpoint` or permissionless initialization is
+		/// disabled.
+		UntrustedCheckpoint,
+		/// The storage proof of GRANDPA authorities is invalid.
+		InvalidAuthoritySetProof,
 		/// Error generated by the `OwnedBridgeModule` trait.
 		BridgeModule(bp_runtime::OwnedBridgeModuleError),
 	}
@@ -414,6 +845,7 @@ pub mod pallet {
 
 			// Since our header schedules a change and we know the delay is 0, it must also enact
 			// the change.
+			archive_current_authority_set::<T, I>(*header.number());
 			<CurrentAuthoritySet<T, I>>::put(&next_authorities);
 			change_enacted = true;
 
@@ -433,26 +865,32 @@ pub mod pallet {
 	///
 	/// Will use the GRANDPA current authorities known to the pallet.
 	///
-	/// If successful it returns the decoded GRANDPA justification so we can refund any weight which
-	/// was overcharged in the initial call.
+	/// If successful it returns the number of precommits with verified signatures, so we can
+	/// refund any weight which was overcharged in the initial call.
 	pub(crate) fn verify_justification<T: Config<I>, I: 'static>(
 		justification: &GrandpaJustification<BridgedHeader<T, I>>,
 		hash: BridgedBlockHash<T, I>,
 		number: BridgedBlockNumber<T, I>,
 		authority_set: bp_header_chain::AuthoritySet,
-	) -> Result<(), sp_runtime::DispatchError> {
-		use bp_header_chain::justification::verify_justification;
+	) -> Result<u32, sp_runtime::DispatchError> {
+		use bp_header_chain::justification::verify_justification_and_count_precommits;
+
+		ensure!(
+			justification.encoded_size() <= T::MaxFinalityProofSize::get() as usize,
+			<Error<T, I>>::TooLargeFinalityProof,
+		);
 
 		let voter_set =
 			VoterSet::new(authority_set.authorities).ok_or(<Error<T, I>>::InvalidAuthoritySet)?;
 		let set_id = authority_set.set_id;
 
-		Ok(verify_justification::<BridgedHeader<T, I>>(
+		Ok(verify_justification_and_count_precommits::<BridgedHeader<T, I>>(
 			(hash, number),
 			set_id,
 			&voter_set,
 			justification,
 		)
+		.map(|verified_precommits| verified_precommits.try_into().unwrap_or(u32::MAX))
 		.map_err(|e| {
 			log::error!(
 				target: LOG_TARGET,
@@ -464,6 +902,192 @@ pub mod pallet {
 		})?)
 	}
 
+	/// Verify a GRANDPA justification (finality proof) for a given header, using the authority
+	/// set that has been active at the header (see `authority_set_at`).
+	///
+	/// Previous authority sets are only used to verify justifications of headers that they have
+	/// been able to finalize. Returns identifier of the set that has generated the justification.
+	pub(crate) fn verify_justification_with_history<T: Config<I>, I: 'static>(
+		justification: &GrandpaJustification<BridgedHeader<T, I>>,
+		hash: BridgedBlockHash<T, I>,
+		number: BridgedBlockNumber<T, I>,
+	) -> Result<SetId, sp_runtime::DispatchError> {
+		let authority_set =
+			authority_set_at::<T, I>(number, <CurrentAuthoritySet<T, I>>::get().into());
+		let set_id = authority_set.set_id;
+		verify_justification::<T, I>(justification, hash, number, authority_set)?;
+		Ok(set_id)
+	}
+
+	/// Returns the authority set that has been active at the header with given number. It is
+	/// either the `current_authority_set`, or one of previous sets, known to the pallet.
+	///
+	/// Justifications don't include the id of the set that has generated them, so the set is
+	/// selected using numbers of the last headers, finalized by previous sets. So the caller may
+	/// verify the justification only once, no matter how many sets are kept. It takes at most
+	/// `AuthoritySetsToKeep` storage reads.
+	pub(crate) fn authority_set_at<T: Config<I>, I: 'static>(
+		number: BridgedBlockNumber<T, I>,
+		current_authority_set: bp_header_chain::AuthoritySet,
+	) -> bp_header_chain::AuthoritySet {
+		let current_set_id = current_authority_set.set_id;
+		let oldest_set_id = current_set_id.saturating_sub(T::AuthoritySetsToKeep::get().into());
+		let mut authority_set = current_authority_set;
+		for set_id in (oldest_set_id..current_set_id).rev() {
+			match <PreviousAuthoritySets<T, I>>::get(set_id) {
+				Some((previous_authority_set, last_number)) if number <= last_number =>
+					authority_set = previous_authority_set.into(),
+				_ => break,
+			}
+		}
+		authority_set
+	}
+
+	/// Weight of reading previous authority sets to select the set that has been active at some
+	/// header (see `authority_set_at`).
+	pub(crate) fn authority_sets_history_weight<T: Config<I>, I: 'static>() -> Weight {
+		T::DbWeight::get().reads(T::AuthoritySetsToKeep::get().into())
+	}
+
+	/// Weight of the `initialize_with_proof` call with given proof size and number of
+	/// authorities.
+	///
+	/// The call has not been benchmarked yet, so provisional estimates are used: the cost of
+	/// hashing and decoding every byte of the authority set proof and the cost of decoding and
+	/// storing every authority.
+	pub(crate) fn initialize_with_proof_weight<T: Config<I>, I: 'static>(
+		proof_size: u32,
+		authorities: u32,
+	) -> Weight {
+		const BASE_REF_TIME: u64 = 50_000_000;
+		const PROOF_BYTE_REF_TIME: u64 = 5_000;
+		const AUTHORITY_REF_TIME: u64 = 1_000_000;
+
+		Weight::from_ref_time(BASE_REF_TIME)
+			.saturating_add(
+				Weight::from_ref_time(PROOF_BYTE_REF_TIME).saturating_mul(proof_size.into()),
+			)
+			.saturating_add(
+				Weight::from_ref_time(AUTHORITY_REF_TIME).saturating_mul(authorities.into()),
+			)
+			.saturating_add(T::DbWeight::get().reads_writes(2, 5))
+	}
+
+	/// Returns total size of all trie nodes of the authority set proof.
+	pub(crate) fn authority_set_proof_size(authority_set_proof: &[Vec<u8>]) -> u32 {
+		authority_set_proof
+			.iter()
+			.fold(0usize, |size, node| size.saturating_add(node.len()))
+			.try_into()
+			.unwrap_or(u32::MAX)
+	}
+
+	/// Move the current authority set to the `PreviousAuthoritySets`, pruning the oldest set
+	/// if required.
+	///
+	/// The `last_number` is the number of the last header that the set is able to finalize.
+	pub(crate) fn archive_current_authority_set<T: Config<I>, I: 'static>(
+		last_number: BridgedBlockNumber<T, I>,
+	) {
+		let current_authority_set = <CurrentAuthoritySet<T, I>>::get();
+		let set_id = current_authority_set.set_id;
+		<PreviousAuthoritySets<T, I>>::insert(set_id, (current_authority_set, last_number));
+		if let Some(pruned_set_id) = set_id.checked_sub(T::AuthoritySetsToKeep::get().into()) {
+			<PreviousAuthoritySets<T, I>>::remove(pruned_set_id);
+		}
+	}
+
+	/// Verify finality proof of the header and import it to the storage.
+	///
+	/// The `authority_set` must be the current authority set, known to the pallet. It is updated
+	/// if the header enacts authority set change. Returns true if the header is mandatory (i.e.
+	/// it has enacted authority set change) and the number of precommits with verified signatures.
+	///
+	/// Previous authority sets are never used here. Every previous set has finalized headers up
+	/// to the header that has enacted the next set, and only descendants of the best finalized
+	/// header may be imported.
+	pub(crate) fn verify_and_import_header<T: Config<I>, I: 'static>(
+		finality_target: BridgedHeader<T, I>,
+		justification: &GrandpaJustification<BridgedHeader<T, I>>,
+		best_finalized_number: BridgedBlockNumber<T, I>,
+		authority_set: &mut bp_header_chain::AuthoritySet,
+	) -> Result<(bool, u32), sp_runtime::DispatchError> {
+		let (hash, number) = (finality_target.hash(), *finality_target.number());
+
+		// We do a quick check here to ensure that our header chain is making progress and isn't
+		// "travelling back in time" (which could be indicative of something bad, e.g a
+		// hard-fork).
+		ensure!(best_finalized_number < number, <Error<T, I>>::OldHeader);
+
+		let verified_precommits =
+			verify_justification::<T, I>(justification, hash, number, authority_set.clone())?;
+
+		let is_authorities_change_enacted =
+			try_enact_authority_change::<T, I>(&finality_target, authority_set.set_id)?;
+		if is_authorities_change_enacted {
+			*authority_set = <CurrentAuthoritySet<T, I>>::get().into();
+		}
+		insert_header::<T, I>(finality_target, hash);
+
+		Ok((is_authorities_change_enacted, verified_precommits))
+	}
+
+	/// Returns true if the non-mandatory header import is free, according to the
+	/// `FreeHeadersInterval`.
+	pub(crate) fn is_free_header<T: Config<I>, I: 'static>(
+		best_finalized_number: BridgedBlockNumber<T, I>,
+		number: BridgedBlockNumber<T, I>,
+	) -> bool {
+		T::FreeHeadersInterval::get().map_or(false, |interval| {
+			number >= best_finalized_number.saturating_add(interval.into())
+		})
+	}
+
+	/// Halts the pallet if valid finality proof of given header implies that the bridged chain
+	/// finality has gone backwards or has jumped too far ahead.
+	///
+	/// Returns `Ok(true)` if the pallet has been halted. The header must not be imported then.
+	pub(crate) fn halt_on_suspicious_header<T: Config<I>, I: 'static>(
+		justification: &GrandpaJustification<BridgedHeader<T, I>>,
+		best_finalized_number: BridgedBlockNumber<T, I>,
+		hash: BridgedBlockHash<T, I>,
+		number: BridgedBlockNumber<T, I>,
+		authority_set: &bp_header_chain::AuthoritySet,
+	) -> Result<bool, sp_runtime::DispatchError> {
+		let is_finality_jump = T::MaxFinalizedHeadersJump::get().map_or(false, |max_jump| {
+			number.saturating_sub(best_finalized_number) > max_jump.into()
+		});
+		let is_conflicting_header = number <= best_finalized_number &&
+			<ImportedHeaderHashes<T, I>>::get(number)
+				.map_or(false, |imported_hash| imported_hash != hash);
+		if !is_finality_jump && !is_conflicting_header {
+			return Ok(false)
+		}
+
+		// the proof may be just a garbage and we don't want anyone to be able to halt the pallet
+		if is_conflicting_header {
+			verify_justification_with_history::<T, I>(justification, hash, number)?;
+		} else {
+			verify_justification::<T, I>(justification, hash, number, authority_set.clone())?;
+		}
+
+		let best_finalized = <BestFinalized<T, I>>::get().ok_or(<Error<T, I>>::NotInitialized)?;
+		log::warn!(
+			target: LOG_TARGET,
+			"Halting the pallet because of suspicious finality proof of header {:?}/{:?}. Best finalized: {:?}",
+			number,
+			hash,
+			best_finalized,
+		);
+		PalletOperatingMode::<T, I>::put(bp_runtime::BasicOperatingMode::Halted);
+		Pallet::<T, I>::deposit_event(Event::SuspiciousFinalityProof {
+			best_finalized,
+			finalized: HeaderId(number, hash),
+		});
+
+		Ok(true)
+	}
+
 	/// Import a previously verified header to the storage.
 	///
 	/// Note this function solely takes care of updating the storage and pruning old entries,
@@ -475,14 +1099,33 @@ pub mod pallet {
 		let index = <ImportedHashesPointer<T, I>>::get();
 		let pruning = <ImportedHashes<T, I>>::try_get(index);
 		<BestFinalized<T, I>>::put(HeaderId(*header.number(), hash));
+		<ImportedHeaderHashes<T, I>>::insert(*header.number(), hash);
 		<ImportedHeaders<T, I>>::insert(hash, header.build());
 		<ImportedHashes<T, I>>::insert(index, hash);
+		<LastImportBlockNumber<T, I>>::put(frame_system::Pallet::<T>::block_number());
+		<IsStalled<T, I>>::kill();
 
 		// Update ring buffer pointer and remove old header.
 		<ImportedHashesPointer<T, I>>::put((index + 1) % T::HeadersToKeep::get());
 		if let Ok(hash) = pruning {
-			log::debug!(target: LOG_TARGET, "Pruning old header: {:?}.", hash);
-			<ImportedHeaders<T, I>>::remove(hash);
+			if <PinnedHeaders<T, I>>::contains_key(hash) {
+				log::debug!(target: LOG_TARGET, "Not pruning pinned header: {:?}.", hash);
+				<PinnedHeaders<T, I>>::insert(hash, true);
+			} else {
+				log::debug!(target: LOG_TARGET, "Pruning old header: {:?}.", hash);
+				prune_header::<T, I>(hash);
+			}
+		}
+	}
+
+	/// Remove imported header from the storage.
+	pub(crate) fn prune_header<T: Config<I>, I: 'static>(hash: BridgedBlockHash<T, I>) {
+		if let Some(pruned_header) = <ImportedHeaders<T, I>>::take(hash) {
+			<ImportedHeaderHashes<T, I>>::mutate_exists(pruned_header.number, |indexed_hash| {
+				if *indexed_hash == Some(hash) {
+					*indexed_hash = None;
+				}
+			});
 		}
 	}
 
@@ -518,6 +1161,24 @@ pub mod pallet {
 		Ok(())
 	}
 
+	/// Read GRANDPA authorities of the bridged chain from the storage proof.
+	pub(crate) fn read_authority_list<T: Config<I>, I: 'static>(
+		state_root: BridgedBlockHash<T, I>,
+		authority_set_proof: Vec<Vec<u8>>,
+	) -> Result<sp_finality_grandpa::AuthorityList, Error<T, I>> {
+		let storage = bp_runtime::StorageProofChecker::<BridgedBlockHasher<T, I>>::new(
+			state_root,
+			sp_trie::StorageProof::new(authority_set_proof),
+		)
+		.map_err(|_| Error::<T, I>::InvalidAuthoritySetProof)?;
+		storage
+			.read_and_decode_value::<VersionedAuthorityList>(GRANDPA_AUTHORITIES_KEY)
+			.ok()
+			.flatten()
+			.map(Into::into)
+			.ok_or(Error::<T, I>::InvalidAuthoritySetProof)
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	pub(crate) fn bootstrap_bridge<T: Config<I>, I: 'static>(
 		init_params: super::InitializationData<BridgedHeader<T, I>>,
@@ -543,10 +1204,149 @@ pub mod pallet {
 }
 
 impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// Marks the bridge as stalled if no headers have been imported for `MaxStallPeriod` blocks.
+	///
+	/// Returns the used weight.
+	fn detect_stall(block: T::BlockNumber, remaining_weight: Weight) -> Weight {
+		let max_stall_period = match T::MaxStallPeriod::get() {
+			Some(max_stall_period) => max_stall_period,
+			None => return Weight::zero(),
+		};
+
+		// we'll need at most to read stall flag, last import block number and best finalized
+		// header, and to update stall flag
+		let db_weight = T::DbWeight::get();
+		if !remaining_weight.all_gte(db_weight.reads_writes(3, 1)) {
+			return Weight::zero()
+		}
+
+		if IsStalled::<T, I>::get() {
+			return db_weight.reads(1)
+		}
+		let last_import_block_number = match LastImportBlockNumber::<T, I>::get() {
+			Some(last_import_block_number) => last_import_block_number,
+			None => return db_weight.reads(2),
+		};
+		if block.saturating_sub(last_import_block_number) <= max_stall_period {
+			return db_weight.reads(2)
+		}
+		let best_finalized = match BestFinalized::<T, I>::get() {
+			Some(best_finalized) => best_finalized,
+			None => return db_weight.reads(3),
+		};
+
+		log::warn!(
+			target: LOG_TARGET,
+			"Bridge is stalled. No headers have been imported since block {:?}. Best finalized: {:?}",
+			last_import_block_number,
+			best_finalized,
+		);
+		IsStalled::<T, I>::put(true);
+		Self::deposit_event(Event::BridgeStalled { best_finalized, last_import_block_number });
+		let handler_weight = T::OnBridgeStalled::on_bridge_stalled(best_finalized);
+
+		db_weight.reads_writes(3, 1).saturating_add(handler_weight)
+	}
+
 	/// Get the best finalized block number.
 	pub fn best_finalized_number() -> Option<BridgedBlockNumber<T, I>> {
 		BestFinalized::<T, I>::get().map(|id| id.number())
 	}
+
+	/// Get the best finalized block id and its state root.
+	pub fn best_finalized_with_state_root() -> Option<(BridgedBlockId<T, I>, BridgedBlockHash<T, I>)>
+	{
+		let best_finalized = BestFinalized::<T, I>::get()?;
+		let state_root = ImportedHeaders::<T, I>::get(best_finalized.hash())?.state_root;
+		Some((best_finalized, state_root))
+	}
+
+	/// Pin imported header, so that it is not pruned until it is unpinned.
+	///
+	/// It may be used by other pallets to keep headers that are required to verify pending
+	/// proofs (e.g. message proofs).
+	pub fn pin_imported_header(hash: BridgedBlockHash<T, I>) -> Result<(), Error<T, I>> {
+		ensure!(ImportedHeaders::<T, I>::contains_key(hash), Error::<T, I>::UnknownHeader);
+		ensure!(!PinnedHeaders::<T, I>::contains_key(hash), Error::<T, I>::AlreadyPinned);
+		ensure!(
+			PinnedHeaders::<T, I>::count() < T::HeadersToKeep::get(),
+			Error::<T, I>::TooManyPinnedHeaders
+		);
+
+		PinnedHeaders::<T, I>::insert(hash, false);
+		Ok(())
+	}
+
+	/// Unpin previously pinned header. If the header has already left the ring buffer of imported
+	/// headers, it is pruned immediately.
+	pub fn unpin_imported_header(hash: BridgedBlockHash<T, I>) -> Result<(), Error<T, I>> {
+		let is_pruning_delayed =
+			PinnedHeaders::<T, I>::take(hash).ok_or(Error::<T, I>::NotPinned)?;
+		if is_pruning_delayed {
+			log::debug!(target: LOG_TARGET, "Pruning unpinned header: {:?}.", hash);
+			prune_header::<T, I>(hash);
+		}
+
+		Ok(())
+	}
+
+	/// Returns true if the header with given hash is the best finalized header or its ancestor.
+	///
+	/// Only headers that have been imported by the pallet and are not yet pruned may be checked.
+	/// Headers that have been imported before the `force_set_authorities` call are considered
+	/// ancestors until they're pruned or replaced by headers with the same number.
+	pub fn is_finalized_ancestor(hash: BridgedBlockHash<T, I>) -> bool {
+		let best_finalized_number = match Self::best_finalized_number() {
+			Some(best_finalized_number) => best_finalized_number,
+			None => return false,
+		};
+
+		ImportedHeaders::<T, I>::get(hash).map_or(false, |header| {
+			header.number <= best_finalized_number &&
+				ImportedHeaderHashes::<T, I>::get(header.number) == Some(hash)
+		})
+	}
+}
+
+/// Handler of GRANDPA equivocations of the bridged chain authorities.
+pub trait OnEquivocation<C: Chain> {
+	/// Called when authorities of the `set_id` have finalized both `first` and `second` headers
+	/// at the same GRANDPA `round`.
+	fn on_equivocation(set_id: SetId, round: u64, first: HeaderIdOf<C>, second: HeaderIdOf<C>);
+}
+
+impl<C: Chain> OnEquivocation<C> for () {
+	fn on_equivocation(_: SetId, _: u64, _: HeaderIdOf<C>, _: HeaderIdOf<C>) {}
+}
+
+/// Equivocation handler that halts all operations of the pallet.
+pub struct HaltBridgeOnEquivocation<T, I>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> OnEquivocation<BridgedChain<T, I>>
+	for HaltBridgeOnEquivocation<T, I>
+{
+	fn on_equivocation(
+		_: SetId,
+		_: u64,
+		_: HeaderIdOf<BridgedChain<T, I>>,
+		_: HeaderIdOf<BridgedChain<T, I>>,
+	) {
+		log::warn!(target: LOG_TARGET, "Halting the pallet because of reported equivocation");
+		PalletOperatingMode::<T, I>::put(bp_runtime::BasicOperatingMode::Halted);
+	}
+}
+
+/// Handler of bridge stalls.
+pub trait OnBridgeStalled<C: Chain> {
+	/// Called when no headers have been imported for more than `MaxStallPeriod` blocks.
+	/// Returns the weight, consumed by the handler.
+	fn on_bridge_stalled(best_finalized: HeaderIdOf<C>) -> Weight;
+}
+
+impl<C: Chain> OnBridgeStalled<C> for () {
+	fn on_bridge_stalled(_: HeaderIdOf<C>) -> Weight {
+		Weight::zero()
+	}
 }
 
 /// Bridge GRANDPA pallet as header chain.
@@ -558,6 +1358,16 @@ impl<T: Config<I>, I: 'static> HeaderChain<BridgedChain<T, I>> for GrandpaChainH
 	) -> Option<HashOf<BridgedChain<T, I>>> {
 		ImportedHeaders::<T, I>::get(header_hash).map(|h| h.state_root)
 	}
+
+	fn finalized_header_number(
+		header_hash: HashOf<BridgedChain<T, I>>,
+	) -> Option<BlockNumberOf<BridgedChain<T, I>>> {
+		ImportedHeaders::<T, I>::get(header_hash).map(|h| h.number)
+	}
+
+	fn is_finalized_ancestor(header_hash: HashOf<BridgedChain<T, I>>) -> bool {
+		Pallet::<T, I>::is_finalized_ancestor(header_hash)
+	}
 }
 
 pub(crate) fn find_scheduled_change<H: HeaderT>(
@@ -613,19 +1423,23 @@ pub fn initialize_for_benchmarks<T: Config<I>, I: 'static>(header: BridgedHeader
 mod tests {
 	use super::*;
 	use crate::mock::{
-		run_test, test_header, RuntimeOrigin, TestHeader, TestNumber, TestRuntime,
-		MAX_BRIDGED_AUTHORITIES,
+		run_test, test_header, FreeHeadersInterval, MaxFinalizedHeadersJump, MaxStallPeriod,
+		RuntimeEvent, RuntimeOrigin, TestHeader, TestNumber, TestRuntime, TrustedCheckpoint,
+		MAX_BRIDGED_AUTHORITIES, MAX_FINALITY_PROOF_SIZE,
 	};
 	use bp_header_chain::BridgeGrandpaCall;
 	use bp_runtime::BasicOperatingMode;
 	use bp_test_utils::{
 		authority_list, generate_owned_bridge_module_tests, make_default_justification,
 		make_justification_for_header, JustificationGeneratorParams, ALICE, BOB,
+		TEST_GRANDPA_ROUND,
 	};
 	use codec::Encode;
 	use frame_support::{
-		assert_err, assert_noop, assert_ok, dispatch::PostDispatchInfo,
+		assert_err, assert_noop, assert_ok,
+		dispatch::{GetDispatchInfo, PostDispatchInfo},
 		storage::generator::StorageValue,
+		weights::Weight,
 	};
 	use sp_runtime::{Digest, DigestItem, DispatchError};
 
@@ -662,6 +1476,14 @@ mod tests {
 		)
 	}
 
+	fn finality_proof_weight(justification: &GrandpaJustification<TestHeader>) -> Weight {
+		<TestRuntime as Config>::WeightInfo::submit_finality_proof(
+			justification.commit.precommits.len() as u32,
+			justification.votes_ancestries.len() as u32,
+		)
+		.saturating_add(authority_sets_history_weight::<TestRuntime, ()>())
+	}
+
 	fn next_block() {
 		use frame_support::traits::OnInitialize;
 
@@ -802,7 +1624,9 @@ mod tests {
 			assert_ok!(
 				submit_finality_proof(1),
 				PostDispatchInfo {
-					actual_weight: None,
+					actual_weight: Some(finality_proof_weight(&make_default_justification(
+						&test_header(1)
+					))),
 					pays_fee: frame_support::dispatch::Pays::Yes,
 				},
 			);
@@ -814,15 +1638,183 @@ mod tests {
 	}
 
 	#[test]
-	fn rejects_justification_that_skips_authority_set_transition() {
+	fn refunds_weight_of_unverified_precommits() {
 		run_test(|| {
 			initialize_substrate_bridge();
 
+			// duplicate precommits are not verified
 			let header = test_header(1);
+			let justification = make_default_justification(&header);
+			let expected_weight = finality_proof_weight(&justification);
+			let mut justification_with_duplicates = justification.clone();
+			justification_with_duplicates
+				.commit
+				.precommits
+				.extend(justification.commit.precommits.iter().cloned());
+			assert!(finality_proof_weight(&justification_with_duplicates) != expected_weight);
 
-			let params =
-				JustificationGeneratorParams::<TestHeader> { set_id: 2, ..Default::default() };
-			let justification = make_justification_for_header(params);
+			assert_ok!(
+				Pallet::<TestRuntime>::submit_finality_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header),
+					justification_with_duplicates,
+				),
+				PostDispatchInfo {
+					actual_weight: Some(expected_weight),
+					pays_fee: frame_support::dispatch::Pays::Yes,
+				},
+			);
+		})
+	}
+
+	#[test]
+	fn imports_header_for_free_once_per_free_headers_interval() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let free_headers_interval = FreeHeadersInterval::get().unwrap() as u8;
+			let expected_weight =
+				finality_proof_weight(&make_default_justification(&test_header(1)));
+			assert_ok!(
+				submit_finality_proof(free_headers_interval - 1),
+				PostDispatchInfo {
+					actual_weight: Some(expected_weight),
+					pays_fee: frame_support::dispatch::Pays::Yes,
+				},
+			);
+			assert_ok!(
+				submit_finality_proof(2 * free_headers_interval - 1),
+				PostDispatchInfo {
+					actual_weight: Some(expected_weight),
+					pays_fee: frame_support::dispatch::Pays::No,
+				},
+			);
+		})
+	}
+
+	#[test]
+	fn imports_batch_of_headers() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let finality_proofs = (1..=2)
+				.map(|number| {
+					let header = test_header(number);
+					let justification = make_default_justification(&header);
+					(header, justification)
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.unwrap();
+			assert_ok!(
+				Pallet::<TestRuntime>::submit_finality_proof_batch(
+					RuntimeOrigin::signed(1),
+					finality_proofs,
+				),
+				PostDispatchInfo {
+					actual_weight: None,
+					pays_fee: frame_support::dispatch::Pays::Yes,
+				},
+			);
+
+			assert_eq!(<BestFinalized<TestRuntime>>::get().unwrap().1, test_header(2).hash());
+			for number in 1..=2 {
+				assert!(<ImportedHeaders<TestRuntime>>::contains_key(test_header(number).hash()));
+			}
+			assert_eq!(Pallet::<TestRuntime>::request_count(), 2);
+		})
+	}
+
+	#[test]
+	fn every_header_of_batch_counts_as_separate_request() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+
+			let finality_proofs = (2..=3)
+				.map(|number| {
+					let header = test_header(number);
+					let justification = make_default_justification(&header);
+					(header, justification)
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.unwrap();
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_finality_proof_batch(
+					RuntimeOrigin::signed(1),
+					finality_proofs,
+				),
+				<Error<TestRuntime>>::TooManyRequests,
+			);
+		})
+	}
+
+	#[test]
+	fn batch_of_mandatory_headers_is_free() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let mut header = test_header(2);
+			header.digest = change_log(0);
+			let justification = make_default_justification(&header);
+			assert_ok!(
+				Pallet::<TestRuntime>::submit_finality_proof_batch(
+					RuntimeOrigin::signed(1),
+					vec![(header, justification)].try_into().unwrap(),
+				),
+				PostDispatchInfo {
+					actual_weight: None,
+					pays_fee: frame_support::dispatch::Pays::No,
+				},
+			);
+			assert_eq!(CurrentAuthoritySet::<TestRuntime>::get().set_id, 2);
+		})
+	}
+
+	#[test]
+	fn rejects_invalid_batch_of_headers() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_finality_proof_batch(
+					RuntimeOrigin::signed(1),
+					Default::default(),
+				),
+				<Error<TestRuntime>>::EmptyBatch,
+			);
+
+			let finality_proofs = [2, 1]
+				.into_iter()
+				.map(|number| {
+					let header = test_header(number);
+					let justification = make_default_justification(&header);
+					(header, justification)
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.unwrap();
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_finality_proof_batch(
+					RuntimeOrigin::signed(1),
+					finality_proofs,
+				),
+				<Error<TestRuntime>>::OldHeader,
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_justification_that_skips_authority_set_transition() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let header = test_header(1);
+
+			let params =
+				JustificationGeneratorParams::<TestHeader> { set_id: 2, ..Default::default() };
+			let justification = make_justification_for_header(params);
 
 			assert_err!(
 				Pallet::<TestRuntime>::submit_finality_proof(
@@ -912,6 +1904,7 @@ mod tests {
 			let justification = make_default_justification(&header);
 
 			// Let's import our test header
+			let expected_weight = finality_proof_weight(&justification);
 			assert_ok!(
 				Pallet::<TestRuntime>::submit_finality_proof(
 					RuntimeOrigin::signed(1),
@@ -919,7 +1912,7 @@ mod tests {
 					justification
 				),
 				PostDispatchInfo {
-					actual_weight: None,
+					actual_weight: Some(expected_weight),
 					pays_fee: frame_support::dispatch::Pays::No,
 				},
 			);
@@ -934,6 +1927,19 @@ mod tests {
 				StoredAuthoritySet::<TestRuntime, ()>::try_new(next_authorities, next_set_id)
 					.unwrap(),
 			);
+
+			// Make sure that the previous authority set is kept in the storage
+			assert_eq!(
+				<PreviousAuthoritySets<TestRuntime>>::get(TEST_GRANDPA_SET_ID),
+				Some((
+					StoredAuthoritySet::<TestRuntime, ()>::try_new(
+						authority_list(),
+						TEST_GRANDPA_SET_ID
+					)
+					.unwrap(),
+					2,
+				)),
+			);
 		})
 	}
 
@@ -1012,6 +2018,447 @@ mod tests {
 		});
 	}
 
+	fn equivocating_justifications(
+	) -> (GrandpaJustification<TestHeader>, GrandpaJustification<TestHeader>) {
+		let first_header = test_header(1);
+		let mut second_header = test_header(1);
+		second_header.set_state_root(sp_core::H256::repeat_byte(1));
+		(make_default_justification(&first_header), make_default_justification(&second_header))
+	}
+
+	#[test]
+	fn reports_equivocation() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let (first, second) = equivocating_justifications();
+			let number = first.commit.target_number;
+			assert_noop!(
+				Pallet::<TestRuntime>::report_equivocation(
+					RuntimeOrigin::root(),
+					first.clone(),
+					second.clone(),
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::report_equivocation(
+					RuntimeOrigin::signed(1),
+					first,
+					second,
+				)
+				.map(|post_info| post_info.pays_fee),
+				Ok(frame_support::dispatch::Pays::No),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::reported_equivocation((
+					TEST_GRANDPA_SET_ID,
+					TEST_GRANDPA_ROUND
+				)),
+				Some(number),
+			);
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), BasicOperatingMode::Halted);
+		})
+	}
+
+	#[test]
+	fn reports_equivocation_of_previous_authority_set() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			// enact new authority set at header#2
+			let mut header = test_header(2);
+			header.digest = change_log(0);
+			let justification = make_default_justification(&header);
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof(
+				RuntimeOrigin::signed(1),
+				Box::new(header),
+				justification,
+			));
+			assert_eq!(CurrentAuthoritySet::<TestRuntime>::get().set_id, TEST_GRANDPA_SET_ID + 1);
+
+			// equivocation of the previous set at header#1 is still reported
+			let (first, second) = equivocating_justifications();
+			assert_ok!(Pallet::<TestRuntime>::report_equivocation(
+				RuntimeOrigin::signed(1),
+				first,
+				second,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::reported_equivocation((
+					TEST_GRANDPA_SET_ID,
+					TEST_GRANDPA_ROUND
+				)),
+				Some(1),
+			);
+		})
+	}
+
+	#[test]
+	fn previous_authority_set_is_not_used_to_verify_descendants_of_handoff_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			// enact new authority set at header#1
+			let mut header = test_header(1);
+			header.digest = change_log(0);
+			let justification = make_default_justification(&header);
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof(
+				RuntimeOrigin::signed(1),
+				Box::new(header),
+				justification,
+			));
+
+			// previous set is unable to finalize header#2
+			let first_header = test_header(2);
+			let mut second_header = test_header(2);
+			second_header.set_state_root(sp_core::H256::repeat_byte(1));
+			assert_noop!(
+				Pallet::<TestRuntime>::report_equivocation(
+					RuntimeOrigin::signed(1),
+					make_default_justification(&first_header),
+					make_default_justification(&second_header),
+				),
+				<Error<TestRuntime>>::InvalidJustification
+			);
+		})
+	}
+
+	#[test]
+	fn previous_authority_set_is_not_used_to_import_headers() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			// enact new authority set at header#1
+			let mut header = test_header(1);
+			header.digest = change_log(0);
+			let justification = make_default_justification(&header);
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof(
+				RuntimeOrigin::signed(1),
+				Box::new(header),
+				justification,
+			));
+
+			// proof of header#2, generated by the previous set, is rejected
+			next_block();
+			assert_noop!(submit_finality_proof(2), <Error<TestRuntime>>::InvalidJustification);
+		})
+	}
+
+	#[test]
+	fn rejects_equivocation_that_has_already_been_reported() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let (first, second) = equivocating_justifications();
+			assert_ok!(Pallet::<TestRuntime>::report_equivocation(
+				RuntimeOrigin::signed(1),
+				first.clone(),
+				second.clone(),
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::report_equivocation(RuntimeOrigin::signed(1), second, first),
+				<Error<TestRuntime>>::EquivocationAlreadyReported
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_justifications_that_are_not_equivocating() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let (first, mut second) = equivocating_justifications();
+			assert_noop!(
+				Pallet::<TestRuntime>::report_equivocation(
+					RuntimeOrigin::signed(1),
+					first.clone(),
+					first.clone(),
+				),
+				<Error<TestRuntime>>::NotEquivocation
+			);
+
+			second.round += 1;
+			assert_noop!(
+				Pallet::<TestRuntime>::report_equivocation(RuntimeOrigin::signed(1), first, second),
+				<Error<TestRuntime>>::NotEquivocation
+			);
+		})
+	}
+
+	#[test]
+	fn rejects_equivocation_with_invalid_justification() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let (first, _) = equivocating_justifications();
+			let mut second_header = test_header(1);
+			second_header.set_state_root(sp_core::H256::repeat_byte(1));
+			let second =
+				make_justification_for_header(JustificationGeneratorParams::<TestHeader> {
+					header: second_header,
+					set_id: 2,
+					..Default::default()
+				});
+			assert_noop!(
+				Pallet::<TestRuntime>::report_equivocation(RuntimeOrigin::signed(1), first, second),
+				<Error<TestRuntime>>::InvalidJustification
+			);
+		})
+	}
+
+	#[test]
+	fn force_set_authorities_replaces_authority_set_and_best_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let header = test_header(5);
+			let next_authorities = vec![(ALICE.into(), 1), (BOB.into(), 1)];
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(
+					RuntimeOrigin::signed(1),
+					Box::new(header.clone()),
+					next_authorities.clone(),
+					42,
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::force_set_authorities(
+				RuntimeOrigin::root(),
+				Box::new(header.clone()),
+				next_authorities.clone(),
+				42,
+			));
+
+			assert_eq!(BestFinalized::<TestRuntime>::get(), Some(HeaderId(5, header.hash())));
+			assert!(<ImportedHeaders<TestRuntime>>::contains_key(header.hash()));
+			assert_eq!(
+				CurrentAuthoritySet::<TestRuntime>::get(),
+				StoredAuthoritySet::<TestRuntime, ()>::try_new(next_authorities, 42).unwrap(),
+			);
+		})
+	}
+
+	#[test]
+	fn force_set_authorities_prunes_old_authority_sets() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			for set_id in 2..=4 {
+				assert_ok!(Pallet::<TestRuntime>::force_set_authorities(
+					RuntimeOrigin::root(),
+					Box::new(test_header(set_id)),
+					authority_list(),
+					set_id,
+				));
+			}
+
+			// AuthoritySetsToKeep is 2, so only sets 2 and 3 are kept
+			assert!(!PreviousAuthoritySets::<TestRuntime>::contains_key(1));
+			assert_eq!(
+				PreviousAuthoritySets::<TestRuntime>::get(2).map(|(_, number)| number),
+				Some(2)
+			);
+			assert_eq!(
+				PreviousAuthoritySets::<TestRuntime>::get(3).map(|(_, number)| number),
+				Some(3)
+			);
+			assert_eq!(CurrentAuthoritySet::<TestRuntime>::get().set_id, 4);
+		})
+	}
+
+	#[test]
+	fn force_set_authorities_rejects_header_older_than_best_finalized_header() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(3));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(
+					RuntimeOrigin::root(),
+					Box::new(test_header(2)),
+					authority_list(),
+					TEST_GRANDPA_SET_ID + 1,
+				),
+				<Error<TestRuntime>>::OldHeader,
+			);
+		})
+	}
+
+	#[test]
+	fn force_set_authorities_fails_if_pallet_is_not_initialized() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(
+					RuntimeOrigin::root(),
+					Box::new(test_header(5)),
+					authority_list(),
+					42,
+				),
+				<Error<TestRuntime>>::NotInitialized,
+			);
+		})
+	}
+
+	#[test]
+	fn force_set_authorities_fails_if_there_are_too_many_authorities_in_the_set() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			assert_noop!(
+				Pallet::<TestRuntime>::force_set_authorities(
+					RuntimeOrigin::root(),
+					Box::new(test_header(5)),
+					std::iter::repeat(authority_list().remove(0))
+						.take(MAX_BRIDGED_AUTHORITIES as usize + 1)
+						.collect(),
+					42,
+				),
+				<Error<TestRuntime>>::TooManyAuthoritiesInSet,
+			);
+		})
+	}
+
+	fn checkpoint_with_authority_set_proof() -> (TestHeader, Vec<Vec<u8>>) {
+		use sp_trie::{trie_types::TrieDBMutBuilderV1, LayoutV1, MemoryDB, Recorder, TrieMut};
+
+		let mut root = Default::default();
+		let mut mdb = MemoryDB::default();
+		{
+			let mut trie =
+				TrieDBMutBuilderV1::<sp_runtime::traits::BlakeTwo256>::new(&mut mdb, &mut root)
+					.build();
+			trie.insert(
+				GRANDPA_AUTHORITIES_KEY,
+				&VersionedAuthorityList::from(&authority_list()).encode(),
+			)
+			.expect("TrieMut::insert should not fail in tests");
+		}
+
+		let mut proof_recorder = Recorder::<LayoutV1<sp_runtime::traits::BlakeTwo256>>::new();
+		bp_runtime::record_all_trie_keys::<LayoutV1<sp_runtime::traits::BlakeTwo256>, _>(
+			&mdb,
+			&root,
+			&mut proof_recorder,
+		)
+		.expect("record_all_trie_keys should not fail in tests");
+		let proof = proof_recorder.drain().into_iter().map(|n| n.data.to_vec()).collect();
+
+		let mut header = test_header(10);
+		header.set_state_root(root);
+		(header, proof)
+	}
+
+	#[test]
+	fn initialize_with_proof_works() {
+		run_test(|| {
+			let (header, proof) = checkpoint_with_authority_set_proof();
+			TrustedCheckpoint::set(&Some((header.hash(), TEST_GRANDPA_SET_ID)));
+
+			let result = Pallet::<TestRuntime>::initialize_with_proof(
+				RuntimeOrigin::signed(1),
+				Box::new(header.clone()),
+				proof.clone(),
+			);
+			assert_eq!(
+				result.expect("call succeeds").actual_weight,
+				Some(initialize_with_proof_weight::<TestRuntime, ()>(
+					authority_set_proof_size(&proof),
+					authority_list().len() as u32,
+				)),
+			);
+
+			assert_eq!(BestFinalized::<TestRuntime>::get(), Some(HeaderId(10, header.hash())));
+			assert_eq!(
+				CurrentAuthoritySet::<TestRuntime>::get(),
+				StoredAuthoritySet::<TestRuntime, ()>::try_new(
+					authority_list(),
+					TEST_GRANDPA_SET_ID
+				)
+				.unwrap(),
+			);
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), BasicOperatingMode::Normal);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::initialize_with_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header),
+					proof,
+				),
+				<Error<TestRuntime>>::AlreadyInitialized,
+			);
+		})
+	}
+
+	#[test]
+	fn initialize_with_proof_weight_depends_on_proof_size() {
+		let (_, proof) = checkpoint_with_authority_set_proof();
+		let mut large_proof = proof.clone();
+		large_proof.push(vec![42; 1024]);
+
+		let call_weight = |proof| {
+			Call::<TestRuntime>::initialize_with_proof {
+				header: Box::new(test_header(10)),
+				authority_set_proof: proof,
+			}
+			.get_dispatch_info()
+			.weight
+		};
+		assert!(call_weight(large_proof).all_gt(call_weight(proof)));
+	}
+
+	#[test]
+	fn initialize_with_proof_rejects_untrusted_checkpoint() {
+		run_test(|| {
+			let (header, proof) = checkpoint_with_authority_set_proof();
+			assert_noop!(
+				Pallet::<TestRuntime>::initialize_with_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header.clone()),
+					proof.clone(),
+				),
+				<Error<TestRuntime>>::UntrustedCheckpoint,
+			);
+
+			TrustedCheckpoint::set(&Some((test_header(10).hash(), TEST_GRANDPA_SET_ID)));
+			assert_noop!(
+				Pallet::<TestRuntime>::initialize_with_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header),
+					proof,
+				),
+				<Error<TestRuntime>>::UntrustedCheckpoint,
+			);
+		})
+	}
+
+	#[test]
+	fn initialize_with_proof_rejects_invalid_authority_set_proof() {
+		run_test(|| {
+			let (header, _) = checkpoint_with_authority_set_proof();
+			TrustedCheckpoint::set(&Some((header.hash(), TEST_GRANDPA_SET_ID)));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::initialize_with_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header.clone()),
+					vec![],
+				),
+				<Error<TestRuntime>>::InvalidAuthoritySetProof,
+			);
+
+			let (_, proof_for_other_root) = bp_runtime::craft_valid_storage_proof();
+			assert_noop!(
+				Pallet::<TestRuntime>::initialize_with_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header),
+					proof_for_other_root.into_iter_nodes().collect(),
+				),
+				<Error<TestRuntime>>::InvalidAuthoritySetProof,
+			);
+		})
+	}
+
 	#[test]
 	fn parse_finalized_storage_proof_rejects_proof_on_unknown_header() {
 		run_test(|| {
@@ -1149,6 +2596,121 @@ mod tests {
 				!ImportedHeaders::<TestRuntime, ()>::contains_key(first_header_hash),
 				"First header should be pruned.",
 			);
+			assert!(
+				!ImportedHeaderHashes::<TestRuntime, ()>::contains_key(1),
+				"First header should be pruned from the index.",
+			);
+		})
+	}
+
+	#[test]
+	fn pinned_header_is_not_pruned_until_it_is_unpinned() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+			let pinned_header_hash = test_header(1).hash();
+			assert_noop!(
+				Pallet::<TestRuntime>::pin_header(RuntimeOrigin::signed(1), pinned_header_hash),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::pin_header(
+				RuntimeOrigin::root(),
+				pinned_header_hash
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::pin_header(RuntimeOrigin::root(), pinned_header_hash),
+				<Error<TestRuntime>>::AlreadyPinned,
+			);
+
+			for header in 2..=6 {
+				next_block();
+				assert_ok!(submit_finality_proof(header));
+			}
+			assert!(ImportedHeaders::<TestRuntime>::contains_key(pinned_header_hash));
+			assert_eq!(PinnedHeaders::<TestRuntime>::get(pinned_header_hash), Some(true));
+
+			assert_ok!(Pallet::<TestRuntime>::unpin_header(
+				RuntimeOrigin::root(),
+				pinned_header_hash
+			));
+			assert!(!ImportedHeaders::<TestRuntime>::contains_key(pinned_header_hash));
+			assert!(!PinnedHeaders::<TestRuntime>::contains_key(pinned_header_hash));
+		})
+	}
+
+	#[test]
+	fn unpinned_header_is_pruned_as_usual() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+			let pinned_header_hash = test_header(1).hash();
+			assert_ok!(Pallet::<TestRuntime>::pin_header(
+				RuntimeOrigin::root(),
+				pinned_header_hash
+			));
+			assert_ok!(Pallet::<TestRuntime>::unpin_header(
+				RuntimeOrigin::root(),
+				pinned_header_hash
+			));
+			assert!(ImportedHeaders::<TestRuntime>::contains_key(pinned_header_hash));
+			assert_noop!(
+				Pallet::<TestRuntime>::unpin_header(RuntimeOrigin::root(), pinned_header_hash),
+				<Error<TestRuntime>>::NotPinned,
+			);
+
+			for header in 2..=6 {
+				next_block();
+				assert_ok!(submit_finality_proof(header));
+			}
+			assert!(!ImportedHeaders::<TestRuntime>::contains_key(pinned_header_hash));
+		})
+	}
+
+	#[test]
+	fn only_imported_headers_may_be_pinned() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			assert_noop!(
+				Pallet::<TestRuntime>::pin_header(RuntimeOrigin::root(), test_header(1).hash()),
+				<Error<TestRuntime>>::UnknownHeader,
+			);
+		})
+	}
+
+	#[test]
+	fn checks_whether_header_is_finalized_ancestor() {
+		run_test(|| {
+			assert!(!Pallet::<TestRuntime>::is_finalized_ancestor(test_header(0).hash()));
+
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+			assert_ok!(submit_finality_proof(2));
+
+			assert!(Pallet::<TestRuntime>::is_finalized_ancestor(test_header(0).hash()));
+			assert!(Pallet::<TestRuntime>::is_finalized_ancestor(test_header(1).hash()));
+			assert!(Pallet::<TestRuntime>::is_finalized_ancestor(test_header(2).hash()));
+			assert!(!Pallet::<TestRuntime>::is_finalized_ancestor(test_header(3).hash()));
+
+			// header from the other fork is not an ancestor
+			let mut fork_header = test_header(2);
+			fork_header.set_state_root(sp_core::H256::repeat_byte(1));
+			assert!(!Pallet::<TestRuntime>::is_finalized_ancestor(fork_header.hash()));
+		})
+	}
+
+	#[test]
+	fn returns_best_finalized_header_with_state_root() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::best_finalized_with_state_root(), None);
+
+			initialize_substrate_bridge();
+			assert_ok!(submit_finality_proof(1));
+
+			let header = test_header(1);
+			assert_eq!(
+				Pallet::<TestRuntime>::best_finalized_with_state_root(),
+				Some((HeaderId(1, header.hash()), *header.state_root())),
+			);
 		})
 	}
 
@@ -1193,5 +2755,167 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn rejects_too_large_finality_proof() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let header = test_header(1);
+			let mut justification = make_default_justification(&header);
+			let mut large_ancestor = test_header(2);
+			large_ancestor.digest = Digest {
+				logs: vec![DigestItem::Other(vec![42; MAX_FINALITY_PROOF_SIZE as usize])],
+			};
+			justification.votes_ancestries.push(large_ancestor);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_finality_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header),
+					justification,
+				),
+				<Error<TestRuntime>>::TooLargeFinalityProof,
+			);
+		})
+	}
+
+	#[test]
+	fn halts_pallet_if_finalized_headers_jump_is_too_large() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+
+			let max_jump = MaxFinalizedHeadersJump::get().unwrap() as u8;
+			let header = test_header((max_jump + 1).into());
+			let hash = header.hash();
+			assert_ok!(submit_finality_proof(max_jump + 1));
+
+			// header is not imported and the pallet is halted
+			assert_eq!(<BestFinalized<TestRuntime>>::get().unwrap().number(), 0);
+			assert!(<ImportedHeaders<TestRuntime>>::get(hash).is_none());
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), BasicOperatingMode::Halted);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().unwrap().event,
+				RuntimeEvent::Grandpa(Event::SuspiciousFinalityProof {
+					best_finalized: HeaderId(0, test_header(0).hash()),
+					finalized: HeaderId((max_jump + 1).into(), hash),
+				}),
+			);
+		})
+	}
+
+	#[test]
+	fn detects_bridge_stall() {
+		use frame_support::traits::OnIdle;
+
+		run_test(|| {
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			initialize_substrate_bridge();
+
+			// bridge is not stalled until `MaxStallPeriod` blocks are passed
+			let max_stall_period = MaxStallPeriod::get().unwrap();
+			Pallet::<TestRuntime>::on_idle(1 + max_stall_period, Weight::MAX);
+			assert!(!IsStalled::<TestRuntime>::get());
+			assert!(frame_system::Pallet::<TestRuntime>::events().is_empty());
+
+			// bridge is stalled and the event is deposited
+			Pallet::<TestRuntime>::on_idle(2 + max_stall_period, Weight::MAX);
+			assert!(IsStalled::<TestRuntime>::get());
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().unwrap().event,
+				RuntimeEvent::Grandpa(Event::BridgeStalled {
+					best_finalized: HeaderId(0, test_header(0).hash()),
+					last_import_block_number: 1,
+				}),
+			);
+
+			// the event is only deposited once per stall
+			Pallet::<TestRuntime>::on_idle(3 + max_stall_period, Weight::MAX);
+			assert_eq!(frame_system::Pallet::<TestRuntime>::events().len(), 1);
+
+			// stall flag is reset after next header import
+			assert_ok!(submit_finality_proof(1));
+			assert!(!IsStalled::<TestRuntime>::get());
+		})
+	}
+
+	#[test]
+	fn halts_pallet_if_conflicting_header_is_finalized() {
+		run_test(|| {
+			initialize_substrate_bridge();
+			frame_system::Pallet::<TestRuntime>::set_block_number(1);
+			assert_ok!(submit_finality_proof(1));
+			assert_ok!(submit_finality_proof(2));
+
+			let mut header = test_header(1);
+			header.digest = Digest { logs: vec![DigestItem::Other(vec![42])] };
+			let hash = header.hash();
+			let justification = make_default_justification(&header);
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof(
+				RuntimeOrigin::signed(1),
+				Box::new(header),
+				justification,
+			));
+
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), BasicOperatingMode::Halted);
+			assert_eq!(
+				frame_system::Pallet::<TestRuntime>::events().last().unwrap().event,
+				RuntimeEvent::Grandpa(Event::SuspiciousFinalityProof {
+					best_finalized: HeaderId(2, test_header(2).hash()),
+					finalized: HeaderId(1, hash),
+				}),
+			);
+		})
+	}
+
+	#[test]
+	fn does_not_halt_pallet_if_suspicious_finality_proof_is_invalid() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let max_jump = MaxFinalizedHeadersJump::get().unwrap() as TestNumber;
+			let header = test_header(max_jump + 1);
+			let params =
+				JustificationGeneratorParams::<TestHeader> { set_id: 2, ..Default::default() };
+			let justification = make_justification_for_header(params);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::submit_finality_proof(
+					RuntimeOrigin::signed(1),
+					Box::new(header),
+					justification,
+				),
+				<Error<TestRuntime>>::InvalidJustification
+			);
+		})
+	}
+
+	#[test]
+	fn halts_pallet_in_the_middle_of_batch_with_suspicious_finality_proof() {
+		run_test(|| {
+			initialize_substrate_bridge();
+
+			let max_jump = MaxFinalizedHeadersJump::get().unwrap() as TestNumber;
+			let finality_proofs = [1, max_jump + 2]
+				.into_iter()
+				.map(|number| {
+					let header = test_header(number);
+					let justification = make_default_justification(&header);
+					(header, justification)
+				})
+				.collect::<Vec<_>>()
+				.try_into()
+				.unwrap();
+			assert_ok!(Pallet::<TestRuntime>::submit_finality_proof_batch(
+				RuntimeOrigin::signed(1),
+				finality_proofs,
+			));
+
+			// only the first header is imported
+			assert_eq!(<BestFinalized<TestRuntime>>::get().unwrap().number(), 1);
+			assert_eq!(PalletOperatingMode::<TestRuntime>::get(), BasicOperatingMode::Halted);
+		})
+	}
+
 	generate_owned_bridge_module_tests!(BasicOperatingMode::Normal, BasicOperatingMode::Halted);
 }
@@ -0,0 +1,225 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migrations of the GRANDPA pallet.
+//!
+//! At storage version 0, there's no `ImportedHeaderHashes` map. At version 1, it maps numbers of
+//! all imported (and not yet pruned) headers to their hashes. Without the migration, headers,
+//! imported before the upgrade, are not considered finalized ancestors and the pallet is not
+//! halted when someone provides a valid finality proof of the conflicting header with the same
+//! number. The migration backfills the map from the `ImportedHeaders` map, which contains both
+//! headers of the `ImportedHashes` ring buffer and pinned headers.
+//!
+//! The number of imported headers is only bounded by the `HeadersToKeep` (which is usually large),
+//! so they are migrated in several blocks. The `MigrateToV1` runtime upgrade migrates as many
+//! headers as fits into the quarter of the block and the rest is migrated by the `on_idle` hook of
+//! the pallet. Entries that are already in the `ImportedHeaderHashes` map (e.g. of headers that
+//! are imported after the upgrade) are never overwritten.
+
+use crate::{
+	BridgedBlockHash, Config, ImportedHeaderHashes, ImportedHeaders, Pallet, LOG_TARGET,
+	STORAGE_VERSION,
+};
+
+use codec::Decode;
+use frame_support::{
+	pallet_prelude::*, storage::StoragePrefixedMap, traits::OnRuntimeUpgrade, weights::Weight,
+};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Raw key of the last inspected `ImportedHeaders` entry. Migration continues from the next key.
+#[frame_support::storage_alias]
+type MigrationCursor<T: Config<I>, I: 'static> = StorageValue<Pallet<T, I>, Vec<u8>, OptionQuery>;
+
+/// Migrates the pallet storage from version 0 to version 1.
+///
+/// Migrates as many headers as fits into the quarter of the maximal block weight. The rest is
+/// migrated by the `on_idle` hook of the pallet (see `migrate_to_v1_step`).
+pub struct MigrateToV1<T, I = ()>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for MigrateToV1<T, I> {
+	fn on_runtime_upgrade() -> Weight {
+		migrate_to_v1_step::<T, I>(T::BlockWeights::get().max_block / 4)
+	}
+}
+
+/// Backfills `ImportedHeaderHashes` entries of imported headers while there's enough
+/// `remaining_weight`.
+///
+/// Does nothing if the on-chain storage version is not 0. Sets the storage version to 1 when all
+/// headers are migrated. Returns the used weight.
+pub fn migrate_to_v1_step<T: Config<I>, I: 'static>(remaining_weight: Weight) -> Weight {
+	// we'll need at least to read the storage version
+	let db_weight = T::DbWeight::get();
+	let mut used_weight = db_weight.reads(1);
+	if !remaining_weight.all_gte(used_weight) {
+		return Weight::zero()
+	}
+	if Pallet::<T, I>::on_chain_storage_version() != 0 {
+		return used_weight
+	}
+
+	// we'll need to read and update the cursor and to update the storage version
+	let step_weight = db_weight.reads_writes(1, 2);
+	// and for every header: to read its key, the header itself, and to read and update the index
+	let entry_weight = db_weight.reads_writes(3, 1);
+	if !remaining_weight.all_gte(used_weight + step_weight + entry_weight) {
+		return used_weight
+	}
+	used_weight += step_weight;
+
+	let prefix = ImportedHeaders::<T, I>::final_prefix();
+	let mut key = MigrationCursor::<T, I>::get().unwrap_or_else(|| prefix.to_vec());
+	let mut migrated_headers = 0u32;
+	loop {
+		if !remaining_weight.all_gte(used_weight + entry_weight) {
+			MigrationCursor::<T, I>::put(key);
+			log::info!(
+				target: LOG_TARGET,
+				"Indexed {} imported headers at storage version {:?}. Migration continues",
+				migrated_headers,
+				STORAGE_VERSION,
+			);
+			return used_weight
+		}
+
+		used_weight += db_weight.reads(1);
+		key = match frame_support::sp_io::storage::next_key(&key)
+			.filter(|next_key| next_key.starts_with(&prefix))
+		{
+			Some(next_key) => next_key,
+			None => break,
+		};
+
+		// the `ImportedHeaders` map is using the `Identity` hasher, so the key is the header hash
+		let hash = match BridgedBlockHash::<T, I>::decode(&mut &key[prefix.len()..]) {
+			Ok(hash) => hash,
+			Err(_) => continue,
+		};
+		used_weight += db_weight.reads(1);
+		let header = match ImportedHeaders::<T, I>::get(hash) {
+			Some(header) => header,
+			None => continue,
+		};
+		used_weight += db_weight.reads_writes(1, 1);
+		ImportedHeaderHashes::<T, I>::mutate(header.number, |indexed_hash| {
+			if indexed_hash.is_none() {
+				*indexed_hash = Some(hash);
+			}
+		});
+		migrated_headers += 1;
+	}
+
+	MigrationCursor::<T, I>::kill();
+	STORAGE_VERSION.put::<Pallet<T, I>>();
+	log::info!(
+		target: LOG_TARGET,
+		"Indexed {} imported headers at storage version {:?}. Migration is completed",
+		migrated_headers,
+		STORAGE_VERSION,
+	);
+
+	used_weight
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{mock::*, BestFinalized};
+	use bp_header_chain::StoredHeaderDataBuilder;
+	use bp_runtime::HeaderId;
+	use sp_runtime::traits::Header as _;
+
+	fn insert_old_imported_headers(count: TestNumber) {
+		StorageVersion::new(0).put::<Pallet<TestRuntime>>();
+		for number in 1..=count {
+			let header = test_header(number);
+			ImportedHeaders::<TestRuntime>::insert(header.hash(), header.build());
+		}
+		BestFinalized::<TestRuntime>::put(HeaderId(count, test_header(count).hash()));
+	}
+
+	#[test]
+	fn migration_to_v1_indexes_imported_headers() {
+		run_test(|| {
+			insert_old_imported_headers(4);
+			// header that has been imported after the upgrade, but before migration
+			ImportedHeaderHashes::<TestRuntime>::insert(
+				2,
+				BridgedBlockHash::<TestRuntime, ()>::default(),
+			);
+
+			MigrateToV1::<TestRuntime>::on_runtime_upgrade();
+
+			assert_eq!(Pallet::<TestRuntime>::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(MigrationCursor::<TestRuntime, ()>::get(), None);
+			assert_eq!(
+				ImportedHeaderHashes::<TestRuntime>::get(2),
+				Some(BridgedBlockHash::<TestRuntime, ()>::default()),
+			);
+			for number in [1, 3, 4] {
+				assert_eq!(
+					ImportedHeaderHashes::<TestRuntime>::get(number),
+					Some(test_header(number).hash()),
+				);
+				assert!(Pallet::<TestRuntime>::is_finalized_ancestor(test_header(number).hash()));
+			}
+
+			// second run is a no-op
+			assert_eq!(
+				MigrateToV1::<TestRuntime>::on_runtime_upgrade(),
+				<TestRuntime as frame_system::Config>::DbWeight::get().reads(1),
+			);
+		});
+	}
+
+	#[test]
+	fn migration_to_v1_continues_in_next_blocks() {
+		run_test(|| {
+			insert_old_imported_headers(4);
+
+			// the first step migrates single header
+			let db_weight = <TestRuntime as frame_system::Config>::DbWeight::get();
+			let step_weight = db_weight.reads_writes(2, 2) + db_weight.reads_writes(3, 1);
+			assert_eq!(migrate_to_v1_step::<TestRuntime, ()>(step_weight), step_weight);
+			assert_eq!(Pallet::<TestRuntime>::on_chain_storage_version(), 0);
+			assert!(MigrationCursor::<TestRuntime, ()>::get().is_some());
+			assert_eq!(ImportedHeaderHashes::<TestRuntime>::iter().count(), 1);
+
+			// not enough weight to migrate a single header
+			assert_eq!(
+				migrate_to_v1_step::<TestRuntime, ()>(db_weight.reads(1)),
+				db_weight.reads(1)
+			);
+
+			// the rest is migrated in next blocks
+			let mut steps = 1;
+			while Pallet::<TestRuntime>::on_chain_storage_version() == 0 {
+				assert!(steps < 16);
+				migrate_to_v1_step::<TestRuntime, ()>(step_weight);
+				steps += 1;
+			}
+			assert_eq!(MigrationCursor::<TestRuntime, ()>::get(), None);
+			assert_eq!(Pallet::<TestRuntime>::on_chain_storage_version(), STORAGE_VERSION);
+			for number in 1..=4 {
+				assert_eq!(
+					ImportedHeaderHashes::<TestRuntime>::get(number),
+					Some(test_header(number).hash()),
+				);
+			}
+		});
+	}
+}
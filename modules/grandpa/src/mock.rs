@@ -18,7 +18,11 @@
 #![allow(clippy::from_over_into)]
 
 use bp_runtime::Chain;
-use frame_support::{construct_runtime, parameter_types, traits::ConstU64, weights::Weight};
+use frame_support::{
+	construct_runtime, parameter_types,
+	traits::ConstU64,
+	weights::{RuntimeDbWeight, Weight},
+};
 use sp_core::sr25519::Signature;
 use sp_runtime::{
 	testing::{Header, H256},
@@ -34,6 +38,7 @@ type Block = frame_system::mocking::MockBlock<TestRuntime>;
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<TestRuntime>;
 
 pub const MAX_BRIDGED_AUTHORITIES: u32 = 2048;
+pub const MAX_FINALITY_PROOF_SIZE: u32 = 16 * 1024;
 
 use crate as grandpa;
 
@@ -44,7 +49,7 @@ construct_runtime! {
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Grandpa: grandpa::{Pallet, Call},
+		Grandpa: grandpa::{Pallet, Call, Event<T>},
 	}
 }
 
@@ -52,6 +57,7 @@ parameter_types! {
 	pub const MaximumBlockWeight: Weight = Weight::from_ref_time(1024);
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
 }
 
 impl frame_system::Config for TestRuntime {
@@ -64,7 +70,7 @@ impl frame_system::Config for TestRuntime {
 	type AccountId = AccountId;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type RuntimeEvent = ();
+	type RuntimeEvent = RuntimeEvent;
 	type BlockHashCount = ConstU64<250>;
 	type Version = ();
 	type PalletInfo = PalletInfo;
@@ -73,7 +79,7 @@ impl frame_system::Config for TestRuntime {
 	type OnKilledAccount = ();
 	type BaseCallFilter = frame_support::traits::Everything;
 	type SystemWeightInfo = ();
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type BlockWeights = ();
 	type BlockLength = ();
 	type SS58Prefix = ();
@@ -86,13 +92,27 @@ parameter_types! {
 	pub const HeadersToKeep: u32 = 5;
 	pub const SessionLength: u64 = 5;
 	pub const NumValidators: u32 = 5;
+	pub const FreeHeadersInterval: Option<u32> = Some(32);
+	pub const MaxFinalizedHeadersJump: Option<u32> = Some(64);
+	pub const AuthoritySetsToKeep: u32 = 2;
+	pub const MaxStallPeriod: Option<u64> = Some(10);
+	pub storage TrustedCheckpoint: Option<(H256, u64)> = None;
 }
 
 impl grandpa::Config for TestRuntime {
+	type RuntimeEvent = RuntimeEvent;
 	type BridgedChain = TestBridgedChain;
 	type MaxRequests = MaxRequests;
 	type HeadersToKeep = HeadersToKeep;
 	type MaxBridgedAuthorities = frame_support::traits::ConstU32<MAX_BRIDGED_AUTHORITIES>;
+	type MaxFinalityProofSize = frame_support::traits::ConstU32<MAX_FINALITY_PROOF_SIZE>;
+	type AuthoritySetsToKeep = AuthoritySetsToKeep;
+	type TrustedCheckpoint = TrustedCheckpoint;
+	type FreeHeadersInterval = FreeHeadersInterval;
+	type MaxFinalizedHeadersJump = MaxFinalizedHeadersJump;
+	type OnEquivocation = grandpa::HaltBridgeOnEquivocation<TestRuntime, ()>;
+	type MaxStallPeriod = MaxStallPeriod;
+	type OnBridgeStalled = ();
 	type WeightInfo = ();
 }
 
@@ -26,7 +26,11 @@ use bp_messages::{
 use codec::{Decode, Encode, EncodeLike, MaxEncodedLen};
 use frame_support::{traits::Get, RuntimeDebug};
 use scale_info::{Type, TypeInfo};
-use sp_std::prelude::PartialEq;
+use sp_std::{collections::vec_deque::VecDeque, prelude::PartialEq};
+
+/// Maximal distance between the nonce of the message, that is delivered out of order, and the
+/// nonce of the next expected message at the unordered inbound lane.
+pub const MAX_OUT_OF_ORDER_MESSAGES: MessageNonce = 127;
 
 /// Inbound lane storage.
 pub trait InboundLaneStorage {
@@ -43,6 +47,17 @@ pub trait InboundLaneStorage {
 	fn data(&self) -> InboundLaneData<Self::Relayer>;
 	/// Update lane data in the storage.
 	fn set_data(&mut self, data: InboundLaneData<Self::Relayer>);
+	/// Returns true if messages may be delivered to the lane out of order.
+	fn is_unordered(&self) -> bool;
+	/// Get bitmap of messages that have been delivered out of order. The bit `i` is set if the
+	/// message with nonce `last_delivered_nonce + 1 + i` has been delivered.
+	fn delivered_ahead(&self) -> u128;
+	/// Update bitmap of messages that have been delivered out of order.
+	fn set_delivered_ahead(&mut self, delivered_ahead: u128);
+	/// Get relayer that has delivered message with given nonce out of order.
+	fn delivered_ahead_relayer(&self, nonce: MessageNonce) -> Option<Self::Relayer>;
+	/// Update relayer that has delivered message with given nonce out of order.
+	fn set_delivered_ahead_relayer(&mut self, nonce: MessageNonce, relayer: Option<Self::Relayer>);
 }
 
 /// Inbound lane data wrapper that implements `MaxEncodedLen`.
@@ -133,31 +148,33 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 			return None
 		}
 
-		let new_confirmed_nonce = outbound_lane_data.latest_received_nonce;
-		data.last_confirmed_nonce = new_confirmed_nonce;
-		// Firstly, remove all of the records where higher nonce <= new confirmed nonce
-		while data
-			.relayers
-			.front()
-			.map(|entry| entry.messages.end <= new_confirmed_nonce)
-			.unwrap_or(false)
-		{
-			data.relayers.pop_front();
-		}
-		// Secondly, update the next record with lower nonce equal to new confirmed nonce if needed.
-		// Note: There will be max. 1 record to update as we don't allow messages from relayers to
-		// overlap.
-		match data.relayers.front_mut() {
-			Some(entry) if entry.messages.begin < new_confirmed_nonce => {
-				entry.messages.begin = new_confirmed_nonce + 1;
-			},
-			_ => {},
+		data.last_confirmed_nonce = outbound_lane_data.latest_received_nonce;
+		prune_relayer_entries(&mut data);
+		// confirmation may free some space for messages that have been delivered out of order
+		if self.storage.is_unordered() {
+			let delivered_ahead = self.storage.delivered_ahead();
+			self.drain_delivered_ahead(&mut data, delivered_ahead);
 		}
 
 		self.storage.set_data(data);
 		Some(outbound_lane_data.latest_received_nonce)
 	}
 
+	/// Prune and consolidate unrewarded relayer entries of the lane.
+	///
+	/// Entries that are beyond the confirmation horizon (i.e. only cover messages with nonces
+	/// that are less than or equal to the `last_confirmed_nonce`) are removed and adjacent
+	/// entries of the same relayer are merged. Returns true if lane data has been updated.
+	pub fn prune_relayers(&mut self) -> bool {
+		let mut data = self.storage.data();
+		if !prune_relayer_entries(&mut data) {
+			return false
+		}
+
+		self.storage.set_data(data);
+		true
+	}
+
 	/// Receive new message.
 	pub fn receive_message<Dispatch: MessageDispatch<AccountId>, AccountId>(
 		&mut self,
@@ -167,8 +184,24 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 		message_data: DispatchMessageData<Dispatch::DispatchPayload>,
 	) -> ReceivalResult<Dispatch::DispatchLevelResult> {
 		let mut data = self.storage.data();
-		let is_correct_message = nonce == data.last_delivered_nonce() + 1;
-		if !is_correct_message {
+		let next_nonce = data.last_delivered_nonce() + 1;
+		let is_next_message = nonce == next_nonce;
+		let mut delivered_ahead = 0;
+		let mut ahead_offset = 0;
+		if self.storage.is_unordered() {
+			// messages may be delivered out of order to unordered lanes, within the window
+			// of `MAX_OUT_OF_ORDER_MESSAGES` messages
+			ahead_offset = match nonce.checked_sub(next_nonce) {
+				Some(offset) if offset <= MAX_OUT_OF_ORDER_MESSAGES => offset,
+				_ => return ReceivalResult::InvalidNonce,
+			};
+			// the next message may have been delivered ahead, if its relayer entry has not been
+			// added because of lane limits
+			delivered_ahead = self.storage.delivered_ahead();
+			if delivered_ahead & (1u128 << ahead_offset) != 0 {
+				return ReceivalResult::InvalidNonce
+			}
+		} else if !is_next_message {
 			return ReceivalResult::InvalidNonce
 		}
 
@@ -192,24 +225,127 @@ impl<S: InboundLaneStorage> InboundLane<S> {
 			},
 		);
 
+		// messages that are delivered out of order are recorded in the bitmap, along with their
+		// relayers. They're added to unrewarded relayer entries when the gap is closed
+		if !is_next_message {
+			self.storage
+				.set_delivered_ahead_relayer(nonce, Some(relayer_at_bridged_chain.clone()));
+			self.storage.set_delivered_ahead(delivered_ahead | (1u128 << ahead_offset));
+			return ReceivalResult::Dispatched(dispatch_result)
+		}
+
 		// now let's update inbound lane storage
-		let push_new = match data.relayers.back_mut() {
-			Some(entry) if entry.relayer == *relayer_at_bridged_chain => {
-				entry.messages.note_dispatched_message();
-				false
-			},
-			_ => true,
-		};
-		if push_new {
-			data.relayers.push_back(UnrewardedRelayer {
-				relayer: (*relayer_at_bridged_chain).clone(),
-				messages: DeliveredMessages::new(nonce),
-			});
+		note_delivered_message(&mut data, relayer_at_bridged_chain, nonce);
+		if delivered_ahead != 0 {
+			self.drain_delivered_ahead(&mut data, delivered_ahead >> 1);
 		}
 		self.storage.set_data(data);
 
 		ReceivalResult::Dispatched(dispatch_result)
 	}
+
+	/// Add messages, that have been delivered out of order and have no gap before them, to the
+	/// unrewarded relayer entries of their relayers.
+	///
+	/// The bit `i` of `delivered_ahead` must be set if the message with nonce
+	/// `data.last_delivered_nonce() + 1 + i` has been delivered. Messages are added while lane
+	/// limits allow that. Remaining messages are added after the next delivery confirmation.
+	fn drain_delivered_ahead(
+		&mut self,
+		data: &mut InboundLaneData<S::Relayer>,
+		mut delivered_ahead: u128,
+	) {
+		while delivered_ahead & 1 != 0 {
+			let nonce = data.last_delivered_nonce() + 1;
+			if nonce.saturating_sub(data.last_confirmed_nonce) >
+				self.storage.max_unconfirmed_messages()
+			{
+				break
+			}
+
+			// the relayer is always recorded along with the bit, but let's be safe here and
+			// credit the message to the relayer of the previous message
+			let relayer = match self
+				.storage
+				.delivered_ahead_relayer(nonce)
+				.or_else(|| data.relayers.back().map(|entry| entry.relayer.clone()))
+			{
+				Some(relayer) => relayer,
+				None => break,
+			};
+			let is_new_entry =
+				data.relayers.back().map(|entry| entry.relayer != relayer).unwrap_or(true);
+			if is_new_entry &&
+				data.relayers.len() as MessageNonce >=
+					self.storage.max_unrewarded_relayer_entries()
+			{
+				break
+			}
+
+			note_delivered_message(data, &relayer, nonce);
+			self.storage.set_delivered_ahead_relayer(nonce, None);
+			delivered_ahead >>= 1;
+		}
+		self.storage.set_delivered_ahead(delivered_ahead);
+	}
+}
+
+/// Add delivered message to the last unrewarded relayer entry, or push new entry if the message
+/// has been delivered by other relayer.
+fn note_delivered_message<RelayerId: Clone + PartialEq>(
+	data: &mut InboundLaneData<RelayerId>,
+	relayer: &RelayerId,
+	nonce: MessageNonce,
+) {
+	let push_new = match data.relayers.back_mut() {
+		Some(entry) if entry.relayer == *relayer => {
+			entry.messages.note_dispatched_message();
+			false
+		},
+		_ => true,
+	};
+	if push_new {
+		data.relayers.push_back(UnrewardedRelayer {
+			relayer: relayer.clone(),
+			messages: DeliveredMessages::new(nonce),
+		});
+	}
+}
+
+/// Remove unrewarded relayer entries, covering already confirmed messages, and merge adjacent
+/// entries of the same relayer.
+///
+/// Returns true if `data` has been updated.
+fn prune_relayer_entries<RelayerId: PartialEq>(data: &mut InboundLaneData<RelayerId>) -> bool {
+	let last_confirmed_nonce = data.last_confirmed_nonce;
+	let mut relayers = VecDeque::with_capacity(data.relayers.len());
+	let mut is_updated = false;
+	for mut entry in data.relayers.drain(..) {
+		// remove the entry if all its messages are confirmed
+		if entry.messages.end <= last_confirmed_nonce {
+			is_updated = true;
+			continue
+		}
+		// drop confirmed messages from the partially confirmed entry. There will be max. 1 such
+		// entry, because we don't allow messages from relayers to overlap
+		if entry.messages.begin <= last_confirmed_nonce {
+			entry.messages.begin = last_confirmed_nonce + 1;
+			is_updated = true;
+		}
+
+		match relayers.back_mut() {
+			Some(UnrewardedRelayer { relayer, messages })
+				if *relayer == entry.relayer && messages.end + 1 == entry.messages.begin =>
+			{
+				messages.end = entry.messages.end;
+				is_updated = true;
+			},
+			_ => relayers.push_back(entry),
+		}
+	}
+	data.relayers = relayers;
+
+	is_updated
 }
 
 #[cfg(test)]
@@ -219,8 +355,8 @@ mod tests {
 		inbound_lane,
 		mock::{
 			dispatch_result, inbound_message_data, run_test, unrewarded_relayer,
-			TestMessageDispatch, TestRuntime, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_RELAYER_A,
-			TEST_RELAYER_B, TEST_RELAYER_C,
+			TestMessageDispatch, TestRuntime, REGULAR_PAYLOAD, TEST_LANE_ID, TEST_LANE_ID_3,
+			TEST_RELAYER_A, TEST_RELAYER_B, TEST_RELAYER_C,
 		},
 		RuntimeInboundLaneStorage,
 	};
@@ -353,6 +489,36 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn prune_relayers_removes_confirmed_entries_and_merges_adjacent_entries() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID);
+			let mut seed_storage_data = lane.storage.data();
+			seed_storage_data.last_confirmed_nonce = 3;
+			seed_storage_data.relayers.push_back(unrewarded_relayer(1, 2, TEST_RELAYER_A));
+			seed_storage_data.relayers.push_back(unrewarded_relayer(3, 4, TEST_RELAYER_B));
+			seed_storage_data.relayers.push_back(unrewarded_relayer(5, 6, TEST_RELAYER_B));
+			seed_storage_data.relayers.push_back(unrewarded_relayer(7, 7, TEST_RELAYER_C));
+			seed_storage_data.relayers.push_back(unrewarded_relayer(8, 8, TEST_RELAYER_B));
+			lane.storage.set_data(seed_storage_data);
+
+			assert!(lane.prune_relayers());
+			assert_eq!(lane.storage.data().last_confirmed_nonce, 3);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 8);
+			assert_eq!(
+				lane.storage.data().relayers,
+				vec![
+					unrewarded_relayer(4, 6, TEST_RELAYER_B),
+					unrewarded_relayer(7, 7, TEST_RELAYER_C),
+					unrewarded_relayer(8, 8, TEST_RELAYER_B),
+				]
+			);
+
+			// nothing to prune now
+			assert!(!lane.prune_relayers());
+		});
+	}
+
 	#[test]
 	fn fails_to_receive_message_with_incorrect_nonce() {
 		run_test(|| {
@@ -542,4 +708,176 @@ mod tests {
 			);
 		});
 	}
+
+	#[test]
+	fn unordered_lane_accepts_messages_out_of_order() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID_3);
+			receive_regular_message(&mut lane, 3);
+			receive_regular_message(&mut lane, 2);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 0);
+			assert_eq!(lane.storage.delivered_ahead(), 0b110);
+
+			// already delivered message is rejected
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					&TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					3,
+					inbound_message_data(REGULAR_PAYLOAD)
+				),
+				ReceivalResult::InvalidNonce,
+			);
+
+			// when missing message is delivered, all messages are added to entries of their
+			// relayers
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					&TEST_RELAYER_B,
+					&TEST_RELAYER_B,
+					1,
+					inbound_message_data(REGULAR_PAYLOAD)
+				),
+				ReceivalResult::Dispatched(dispatch_result(0))
+			);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 3);
+			assert_eq!(
+				lane.storage.data().relayers,
+				vec![
+					unrewarded_relayer(1, 1, TEST_RELAYER_B),
+					unrewarded_relayer(2, 3, TEST_RELAYER_A)
+				]
+			);
+			assert_eq!(lane.storage.delivered_ahead(), 0);
+			assert_eq!(lane.storage.delivered_ahead_relayer(2), None);
+			assert_eq!(lane.storage.delivered_ahead_relayer(3), None);
+
+			receive_regular_message(&mut lane, 4);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 4);
+		});
+	}
+
+	#[test]
+	fn unordered_lane_rejects_messages_outside_of_window() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID_3);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					&TEST_RELAYER_A,
+					&TEST_RELAYER_A,
+					MAX_OUT_OF_ORDER_MESSAGES + 2,
+					inbound_message_data(REGULAR_PAYLOAD)
+				),
+				ReceivalResult::InvalidNonce,
+			);
+		});
+	}
+
+	#[test]
+	fn unordered_lane_credits_messages_delivered_ahead_to_their_relayers() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID_3);
+			for (relayer, nonce) in [(TEST_RELAYER_A, 2), (TEST_RELAYER_B, 3), (TEST_RELAYER_A, 4)]
+			{
+				assert_eq!(
+					lane.receive_message::<TestMessageDispatch, _>(
+						&relayer,
+						&relayer,
+						nonce,
+						inbound_message_data(REGULAR_PAYLOAD)
+					),
+					ReceivalResult::Dispatched(dispatch_result(0))
+				);
+			}
+			assert_eq!(lane.storage.data().relayers, vec![]);
+			assert_eq!(lane.storage.delivered_ahead_relayer(3), Some(TEST_RELAYER_B));
+
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					&TEST_RELAYER_B,
+					&TEST_RELAYER_B,
+					1,
+					inbound_message_data(REGULAR_PAYLOAD)
+				),
+				ReceivalResult::Dispatched(dispatch_result(0))
+			);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), 4);
+			assert_eq!(
+				lane.storage.data().relayers,
+				vec![
+					unrewarded_relayer(1, 1, TEST_RELAYER_B),
+					unrewarded_relayer(2, 2, TEST_RELAYER_A),
+					unrewarded_relayer(3, 3, TEST_RELAYER_B),
+					unrewarded_relayer(4, 4, TEST_RELAYER_A),
+				]
+			);
+			assert_eq!(lane.storage.delivered_ahead(), 0);
+		});
+	}
+
+	#[test]
+	fn unordered_lane_respects_unrewarded_relayer_entries_limit_when_gap_is_closed() {
+		run_test(|| {
+			let mut lane = inbound_lane::<TestRuntime, _>(TEST_LANE_ID_3);
+			let max_entries =
+				<TestRuntime as Config>::MaxUnrewardedRelayerEntriesAtInboundLane::get();
+			let mut seed_storage_data = lane.storage.data();
+			for nonce in 1..max_entries - 1 {
+				seed_storage_data.relayers.push_back(unrewarded_relayer(
+					nonce,
+					nonce,
+					TEST_RELAYER_A + nonce,
+				));
+			}
+			lane.storage.set_data(seed_storage_data);
+
+			// deliver two messages ahead of the gap, by different relayers
+			let next_nonce = max_entries - 1;
+			for (relayer, nonce) in
+				[(TEST_RELAYER_B, next_nonce + 1), (TEST_RELAYER_C, next_nonce + 2)]
+			{
+				assert_eq!(
+					lane.receive_message::<TestMessageDispatch, _>(
+						&relayer,
+						&relayer,
+						nonce,
+						inbound_message_data(REGULAR_PAYLOAD)
+					),
+					ReceivalResult::Dispatched(dispatch_result(0))
+				);
+			}
+
+			// when the gap is closed, there's only room for the message of the first relayer
+			receive_regular_message(&mut lane, next_nonce);
+			assert_eq!(lane.storage.data().relayers.len() as MessageNonce, max_entries);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), next_nonce + 1);
+			assert_eq!(lane.storage.delivered_ahead(), 0b1);
+			assert_eq!(
+				lane.receive_message::<TestMessageDispatch, _>(
+					&TEST_RELAYER_C,
+					&TEST_RELAYER_C,
+					next_nonce + 2,
+					inbound_message_data(REGULAR_PAYLOAD)
+				),
+				ReceivalResult::InvalidNonce,
+			);
+
+			// when delivery is confirmed, the remaining message is added to the entry of its
+			// relayer
+			assert_eq!(
+				lane.receive_state_update(OutboundLaneData {
+					latest_received_nonce: 1,
+					..Default::default()
+				}),
+				Some(1),
+			);
+			assert_eq!(lane.storage.data().last_delivered_nonce(), next_nonce + 2);
+			assert_eq!(
+				lane.storage.data().relayers.back(),
+				Some(&unrewarded_relayer(next_nonce + 2, next_nonce + 2, TEST_RELAYER_C)),
+			);
+			assert_eq!(lane.storage.delivered_ahead(), 0);
+			assert_eq!(lane.storage.delivered_ahead_relayer(next_nonce + 2), None);
+		});
+	}
 }
@@ -37,7 +37,7 @@
 // Generated by `decl_event!`
 #![allow(clippy::unused_unit)]
 
-pub use inbound_lane::StoredInboundLaneData;
+pub use inbound_lane::{StoredInboundLaneData, MAX_OUT_OF_ORDER_MESSAGES};
 pub use outbound_lane::StoredMessagePayload;
 pub use weights::WeightInfo;
 pub use weights_ext::{
@@ -52,20 +52,37 @@ use crate::{
 
 use bp_messages::{
 	source_chain::{
-		DeliveryConfirmationPayments, LaneMessageVerifier, SendMessageArtifacts, TargetHeaderChain,
+		DeliveryConfirmationPayments, LaneMessageVerifier, MessageDeliveryFeeCalculator,
+		OnDeliveryConfirmed, OnMessageCancelled, OnMessageExpired, SendMessageArtifacts,
+		TargetHeaderChain,
 	},
 	target_chain::{
-		DeliveryPayments, DispatchMessage, MessageDispatch, ProvedLaneMessages, ProvedMessages,
+		DeliveryPayments, DispatchMessage, DispatchMessageData, MessageDispatch, ProvedMessages,
 		SourceHeaderChain,
 	},
-	total_unrewarded_messages, DeliveredMessages, InboundLaneData, InboundMessageDetails, LaneId,
-	MessageKey, MessageNonce, MessagePayload, MessagesOperatingMode, OutboundLaneData,
-	OutboundMessageDetails, UnrewardedRelayersState,
+	total_unrewarded_messages, DeliveredMessageDispatchResult, DeliveredMessages, InboundLaneData,
+	InboundLaneDiagnostics, InboundMessageDeliveryProofKeys, InboundMessageDetails, LaneId,
+	LaneState, Message, MessageKey, MessageNonce, MessagePayload, MessagesOperatingMode,
+	OutboundLaneData, OutboundLaneDiagnostics, OutboundMessageDetails, ReceivalResult,
+	UnrewardedRelayersState,
+};
+use bp_runtime::{
+	messages::MessageDispatchResult, BasicOperatingMode, ChainId, OperatingMode, OwnedBridgeModule,
+	Size,
 };
-use bp_runtime::{BasicOperatingMode, ChainId, OwnedBridgeModule, Size};
 use codec::{Decode, Encode, MaxEncodedLen};
-use frame_support::{dispatch::PostDispatchInfo, ensure, fail, traits::Get};
-use sp_runtime::traits::UniqueSaturatedFrom;
+use frame_support::{
+	dispatch::{DispatchClass, PostDispatchInfo},
+	ensure, fail,
+	traits::Get,
+	weights::Weight,
+	RuntimeDebug,
+};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{One, Saturating, UniqueSaturatedFrom, UniqueSaturatedInto},
+	FixedPointNumber, FixedU128,
+};
 use sp_std::{cell::RefCell, marker::PhantomData, prelude::*};
 
 mod inbound_lane;
@@ -85,11 +102,19 @@ pub use pallet::*;
 /// The target that will be used when publishing logs related to this pallet.
 pub const LOG_TARGET: &str = "runtime::bridge-messages";
 
+/// Maximal number of latest inbound messages of every lane, whose dispatch results are kept in
+/// the runtime storage.
+pub const MAX_STORED_DISPATCH_RESULTS: MessageNonce = 1024;
+
+/// Maximal number of outbound messages of every lane, that have been cancelled by the same sender
+/// and which delivery is not yet confirmed.
+pub const MAX_CANCELLED_MESSAGES_PER_SENDER: u32 = 16;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 	use bp_messages::{ReceivalResult, ReceivedMessages};
-	use frame_support::pallet_prelude::*;
+	use frame_support::{pallet_prelude::*, traits::EnsureOriginWithArg};
 	use frame_system::pallet_prelude::*;
 
 	#[pallet::config]
@@ -108,6 +133,13 @@ pub mod pallet {
 
 		/// Get all active outbound lanes that the message pallet is serving.
 		type ActiveOutboundLanes: Get<&'static [LaneId]>;
+		/// Maximal number of outbound lanes that may be opened using the `open_lane` call, in
+		/// addition to the `ActiveOutboundLanes`.
+		#[pallet::constant]
+		type MaxDynamicOutboundLanes: Get<u32>;
+		/// Origin that may change operating mode of the lane. Root and the `PalletOwner` may
+		/// change operating mode of any lane.
+		type LaneOwnerOrigin: EnsureOriginWithArg<Self::RuntimeOrigin, LaneId>;
 		/// Maximal number of unrewarded relayer entries at inbound lane. Unrewarded means that the
 		/// relayer has delivered messages, but either confirmations haven't been delivered back to
 		/// the source chain, or we haven't received reward confirmations yet.
@@ -131,10 +163,44 @@ pub mod pallet {
 		/// Transaction that is declaring more messages than this value, will be rejected. Even if
 		/// these messages are from different lanes.
 		type MaxUnconfirmedMessagesAtInboundLane: Get<MessageNonce>;
+		/// Inbound lanes that accept messages out of order.
+		///
+		/// Message with nonce up to `last_delivered_nonce + 1 + MAX_OUT_OF_ORDER_MESSAGES` may
+		/// be delivered to such lane before messages with lower nonces. Delivery of such messages
+		/// is confirmed to the source chain (and rewarded) once all previous messages are
+		/// delivered.
+		type UnorderedInboundLanes: Get<&'static [LaneId]>;
+		/// Maximal dispatch weight of the inbound message that is dispatched immediately when it
+		/// is received. Dispatch of heavier messages is deferred until it may be performed by the
+		/// `on_idle` hook or by the `execute_parked_message` call.
+		#[pallet::constant]
+		type MaxImmediateDispatchWeight: Get<Weight>;
+		/// Maximal number of inbound messages, which dispatch has been deferred.
+		#[pallet::constant]
+		type MaxParkedMessages: Get<u32>;
+		/// Maximal number of inbound messages of every lane, which dispatch has been deferred.
+		///
+		/// Relayers are not paying for dispatch of parked messages, so this limits the weight
+		/// that relayers of the single lane may have dispatched from the block idle time.
+		#[pallet::constant]
+		type MaxParkedMessagesPerLane: Get<u32>;
+		/// Maximal number of inbound lanes, which unrewarded relayer entries are pruned by the
+		/// `on_idle` hook in a single block. If zero, entries are only pruned when the lane state
+		/// update is received or by the `prune_lane` call.
+		#[pallet::constant]
+		type MaxInboundLanesToPrunePerBlock: Get<u32>;
 
 		/// Maximal encoded size of the outbound payload.
 		#[pallet::constant]
 		type MaximalOutboundPayloadSize: Get<u32>;
+		/// Maximal number of messages that may be accepted by a single outbound lane within
+		/// a single block.
+		#[pallet::constant]
+		type MaxMessagesPerLanePerBlock: Get<MessageNonce>;
+		/// Maximal total encoded size of payloads that may be accepted by a single outbound lane
+		/// within a single block.
+		#[pallet::constant]
+		type MaxBytesPerLanePerBlock: Get<u32>;
 		/// Payload type of outbound messages. This payload is dispatched on the bridged chain.
 		type OutboundPayload: Parameter + Size;
 
@@ -152,8 +218,22 @@ pub mod pallet {
 		type TargetHeaderChain: TargetHeaderChain<Self::OutboundPayload, Self::AccountId>;
 		/// Message payload verifier.
 		type LaneMessageVerifier: LaneMessageVerifier<Self::RuntimeOrigin, Self::OutboundPayload>;
+		/// Message delivery fee calculator. The fee is charged from the sender of every message,
+		/// accepted by the outbound lane.
+		type DeliveryFeeCalculator: MessageDeliveryFeeCalculator<Self::RuntimeOrigin>;
 		/// Delivery confirmation payments.
 		type DeliveryConfirmationPayments: DeliveryConfirmationPayments<Self::AccountId>;
+		/// Number of blocks after which the undelivered outbound message expires. If `None`,
+		/// outbound messages never expire.
+		#[pallet::constant]
+		type MessageTtl: Get<Option<Self::BlockNumber>>;
+		/// Handler for expired outbound messages.
+		type OnMessageExpired: OnMessageExpired;
+		/// Handler for outbound messages, cancelled by their senders.
+		type OnMessageCancelled: OnMessageCancelled<Self::AccountId>;
+		/// Handler for confirmed deliveries of outbound messages. It also receives dispatch
+		/// results of confirmed messages, included in the messages delivery proof.
+		type OnDeliveryConfirmed: OnDeliveryConfirmed;
 
 		// Types that are used by inbound_lane (on target chain).
 
@@ -193,31 +273,13 @@ pub mod pallet {
 		u32: TryFrom<<T as frame_system::Config>::BlockNumber>,
 	{
 		fn on_idle(_block: T::BlockNumber, remaining_weight: Weight) -> Weight {
-			// we'll need at least to read outbound lane state, kill a message and update lane state
-			let db_weight = T::DbWeight::get();
-			if !remaining_weight.all_gte(db_weight.reads_writes(1, 2)) {
-				return Weight::zero()
-			}
-
-			// messages from lane with index `i` in `ActiveOutboundLanes` are pruned when
-			// `System::block_number() % lanes.len() == i`. Otherwise we need to read lane states on
-			// every block, wasting the whole `remaining_weight` for nothing and causing starvation
-			// of the last lane pruning
-			let active_lanes = T::ActiveOutboundLanes::get();
-			let active_lanes_len = (active_lanes.len() as u32).into();
-			let active_lane_index = u32::unique_saturated_from(
-				frame_system::Pallet::<T>::block_number() % active_lanes_len,
-			);
-			let active_lane_id = active_lanes[active_lane_index as usize];
-
-			// first db read - outbound lane state
-			let mut active_lane = outbound_lane::<T, I>(active_lane_id);
-			let mut used_weight = db_weight.reads(1);
-			// and here we'll have writes
-			used_weight += active_lane.prune_messages(db_weight, remaining_weight - used_weight);
-
-			// we already checked we have enough `remaining_weight` to cover this `used_weight`
-			used_weight
+			let mut used_weight = Self::prune_outbound_lanes(remaining_weight);
+			used_weight = used_weight.saturating_add(Self::dispatch_parked_messages(
+				remaining_weight.saturating_sub(used_weight),
+			));
+			used_weight.saturating_add(Self::prune_inbound_lanes(
+				remaining_weight.saturating_sub(used_weight),
+			))
 		}
 	}
 
@@ -250,7 +312,7 @@ pub mod pallet {
 		/// state update. Because of that, the submitter (relayer) has no benefit of not including
 		/// this data in the transaction, so reward confirmations lags should be minimal.
 		#[pallet::call_index(2)]
-		#[pallet::weight(T::WeightInfo::receive_messages_proof_weight(proof, *messages_count, *dispatch_weight))]
+		#[pallet::weight(receive_messages_proof_weight::<T, I>(proof, *messages_count, *dispatch_weight))]
 		pub fn receive_messages_proof(
 			origin: OriginFor<T>,
 			relayer_id_at_bridged_chain: T::InboundRelayer,
@@ -270,38 +332,47 @@ pub mod pallet {
 			// why do we need to know the weight of this (`receive_messages_proof`) call? Because
 			// we may want to return some funds for not-dispatching (or partially dispatching) some
 			// messages to the call origin (relayer). And this is done by returning actual weight
-			// from the call. But we only know dispatch weight of every messages. So to refund
-			// relayer because we have not dispatched Message, we need to:
+			// from the call. We are tracking actual dispatch weight of every message, so to refund
+			// relayer for not-dispatched messages and for declared, but unused dispatch weight,
+			// we need to:
 			//
-			// ActualWeight = DeclaredWeight - Message.DispatchWeight
+			// ActualWeight = DeclaredWeight - DeclaredDispatchWeight + ActualDispatchWeight
 			//
 			// The DeclaredWeight is exactly what's computed here. Unfortunately it is impossible
 			// to get pre-computed value (and it has been already computed by the executive).
-			let declared_weight = T::WeightInfo::receive_messages_proof_weight(
-				&proof,
-				messages_count,
-				dispatch_weight,
-			);
-			let mut actual_weight = declared_weight;
+			let declared_weight =
+				receive_messages_proof_weight::<T, I>(&proof, messages_count, dispatch_weight);
 
-			// verify messages proof && convert proof into messages
-			let messages = verify_and_decode_messages_proof::<
-				T::SourceHeaderChain,
-				T::InboundPayload,
-			>(proof, messages_count)
-			.map_err(|err| {
-				log::trace!(target: LOG_TARGET, "Rejecting invalid messages proof: {:?}", err,);
+			// verify messages proof
+			let messages = verify_messages_proof::<T::SourceHeaderChain>(proof, messages_count)
+				.map_err(|err| {
+					log::trace!(target: LOG_TARGET, "Rejecting invalid messages proof: {:?}", err,);
 
-				Error::<T, I>::InvalidMessagesProof
-			})?;
+					Error::<T, I>::InvalidMessagesProof
+				})?;
+
+			// the proof may carry messages of multiple lanes. The declared weight only accounts
+			// for a single lane state update, so every additional lane is paid from the declared
+			// dispatch weight
+			let additional_lanes_weight =
+				T::WeightInfo::receive_messages_proof_outbound_lane_state_overhead()
+					.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+					.saturating_mul(messages.len().saturating_sub(1) as u64);
+			ensure!(
+				additional_lanes_weight.all_lte(dispatch_weight),
+				Error::<T, I>::InsufficientDispatchWeight
+			);
 
 			// dispatch messages and (optionally) update lane(s) state(s)
 			let mut total_messages = 0;
 			let mut valid_messages = 0;
 			let mut messages_received_status = Vec::with_capacity(messages.len());
-			let mut dispatch_weight_left = dispatch_weight;
+			let mut dispatch_weight_left = dispatch_weight.saturating_sub(additional_lanes_weight);
 			for (lane_id, lane_data) in messages {
+				ensure_lane_not_halted::<T, I>(lane_id)?;
+
 				let mut lane = inbound_lane::<T, I>(lane_id);
+				let is_unordered_lane = T::UnorderedInboundLanes::get().contains(&lane_id);
 
 				if let Some(lane_state) = lane_data.lane_state {
 					let updated_latest_confirmed_nonce = lane.receive_state_update(lane_state);
@@ -319,7 +390,7 @@ pub mod pallet {
 					ReceivedMessages::new(lane_id, Vec::with_capacity(lane_data.messages.len()));
 				let mut is_lane_processing_stopped_no_weight_left = false;
 
-				for mut message in lane_data.messages {
+				for message in lane_data.messages {
 					debug_assert_eq!(message.key.lane_id, lane_id);
 					total_messages += 1;
 
@@ -329,11 +400,29 @@ pub mod pallet {
 						continue
 					}
 
+					let mut dispatch_message = DispatchMessage {
+						key: message.key.clone(),
+						data: DispatchMessageData {
+							payload: T::InboundPayload::decode(&mut &message.payload[..]),
+						},
+					};
+					let message_dispatch_weight =
+						T::MessageDispatch::dispatch_weight(&mut dispatch_message);
+
+					// messages that are too heavy to be dispatched immediately are parked if
+					// there's a room in the parked messages queue, the lane has not reached
+					// its parked messages limit and the message may be dispatched by a single
+					// transaction. Relayer doesn't need to declare dispatch weight of such
+					// messages
+					let park_message = message_dispatch_weight
+						.any_gt(T::MaxImmediateDispatchWeight::get()) &&
+						may_park_message::<T, I>(lane_id, message_dispatch_weight);
+
 					// ensure that relayer has declared enough weight for dispatching next message
-					// on this lane. We can't dispatch lane messages out-of-order, so if declared
-					// weight is not enough, let's move to next lane
-					let message_dispatch_weight = T::MessageDispatch::dispatch_weight(&mut message);
-					if message_dispatch_weight.any_gt(dispatch_weight_left) {
+					// on this lane. We can't dispatch messages of ordered lanes out-of-order, so if
+					// declared weight is not enough, let's move to next lane. Messages of unordered
+					// lanes are simply skipped
+					if !park_message && message_dispatch_weight.any_gt(dispatch_weight_left) {
 						log::trace!(
 							target: LOG_TARGET,
 							"Cannot dispatch any more messages on lane {:?}. Weight: declared={}, left={}",
@@ -343,16 +432,27 @@ pub mod pallet {
 						);
 						lane_messages_received_status
 							.push_skipped_for_not_enough_weight(message.key.nonce);
-						is_lane_processing_stopped_no_weight_left = true;
+						is_lane_processing_stopped_no_weight_left = !is_unordered_lane;
 						continue
 					}
 
-					let receival_result = lane.receive_message::<T::MessageDispatch, T::AccountId>(
-						&relayer_id_at_bridged_chain,
-						&relayer_id_at_this_chain,
-						message.key.nonce,
-						message.data,
-					);
+					let receival_result = if park_message {
+						parked_receival_result(
+							lane.receive_message::<ParkMessage<T, I>, T::AccountId>(
+								&relayer_id_at_bridged_chain,
+								&relayer_id_at_this_chain,
+								message.key.nonce,
+								DispatchMessageData { payload: Ok(message.payload) },
+							),
+						)
+					} else {
+						lane.receive_message::<T::MessageDispatch, T::AccountId>(
+							&relayer_id_at_bridged_chain,
+							&relayer_id_at_this_chain,
+							message.key.nonce,
+							dispatch_message.data,
+						)
+					};
 
 					// note that we're returning unspent weight to relayer even if message has been
 					// rejected by the lane. This allows relayers to submit spam transactions with
@@ -363,8 +463,24 @@ pub mod pallet {
 					let unspent_weight = match &receival_result {
 						ReceivalResult::Dispatched(dispatch_result) => {
 							valid_messages += 1;
+							store_dispatch_result::<T, I>(
+								lane_id,
+								message.key.nonce,
+								dispatch_result.clone(),
+							);
 							dispatch_result.unspent_weight
 						},
+						ReceivalResult::Parked => {
+							valid_messages += 1;
+							log::trace!(
+								target: LOG_TARGET,
+								"Parked inbound message {:?}/{}. Dispatch weight: {}",
+								lane_id,
+								message.key.nonce,
+								message_dispatch_weight,
+							);
+							message_dispatch_weight
+						},
 						ReceivalResult::InvalidNonce |
 						ReceivalResult::TooManyUnrewardedRelayers |
 						ReceivalResult::TooManyUnconfirmedMessages => message_dispatch_weight,
@@ -373,12 +489,15 @@ pub mod pallet {
 
 					let unspent_weight = unspent_weight.min(message_dispatch_weight);
 					dispatch_weight_left -= message_dispatch_weight - unspent_weight;
-					actual_weight = actual_weight.saturating_sub(unspent_weight);
 				}
 
 				messages_received_status.push(lane_messages_received_status);
 			}
 
+			// dispatch weight, that has been declared by the relayer, but not spent on messages
+			// dispatch, is refunded
+			let actual_weight = declared_weight.saturating_sub(dispatch_weight_left);
+
 			// let's now deal with relayer payments
 			T::DeliveryPayments::pay_reward(
 				relayer_id_at_this_chain,
@@ -403,10 +522,22 @@ pub mod pallet {
 
 		/// Receive messages delivery proof from bridged chain.
 		#[pallet::call_index(3)]
-		#[pallet::weight(T::WeightInfo::receive_messages_delivery_proof_weight(
-			proof,
-			relayers_state,
-		))]
+		#[pallet::weight(
+			T::WeightInfo::receive_messages_delivery_proof_weight(proof, relayers_state)
+				.saturating_add(T::OnDeliveryConfirmed::on_delivery_confirmed_weight(
+					relayers_state.total_messages,
+				))
+				.saturating_add(T::DeliveryConfirmationPayments::pay_reward_weight(
+					relayers_state.total_messages,
+				))
+				// every confirmed message may be a cancelled message with proved dispatch result
+				.saturating_add(
+					T::DbWeight::get()
+						.reads_writes(2, 1)
+						.saturating_add(T::OnMessageCancelled::on_message_cancelled_weight())
+						.saturating_mul(relayers_state.total_messages),
+				)
+		)]
 		pub fn receive_messages_delivery_proof(
 			origin: OriginFor<T>,
 			proof: MessagesDeliveryProofOf<T, I>,
@@ -415,8 +546,8 @@ pub mod pallet {
 			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
 
 			let confirmation_relayer = ensure_signed(origin)?;
-			let (lane_id, lane_data) = T::TargetHeaderChain::verify_messages_delivery_proof(proof)
-				.map_err(|err| {
+			let (lane_id, lane_data, mut dispatch_results) =
+				T::TargetHeaderChain::verify_messages_delivery_proof(proof).map_err(|err| {
 					log::trace!(
 						target: LOG_TARGET,
 						"Rejecting invalid messages delivery proof: {:?}",
@@ -425,6 +556,7 @@ pub mod pallet {
 
 					Error::<T, I>::InvalidMessagesDeliveryProof
 				})?;
+			ensure_lane_not_halted::<T, I>(lane_id)?;
 
 			// verify that the relayer has declared correct `lane_data::relayers` state
 			// (we only care about total number of entries and messages, because this affects call
@@ -482,9 +614,18 @@ pub mod pallet {
 				let received_range = confirmed_messages.begin..=confirmed_messages.end;
 				Self::deposit_event(Event::MessagesDelivered {
 					lane_id,
-					messages: confirmed_messages,
+					messages: confirmed_messages.clone(),
 				});
 
+				// results of messages, which delivery has been confirmed before, are ignored
+				dispatch_results.retain(|result| received_range.contains(&result.nonce));
+
+				// senders of cancelled messages may only be refunded once the dispatch result
+				// of their message is proved
+				for dispatch_result in &dispatch_results {
+					on_cancelled_message_delivered::<T, I>(lane_id, dispatch_result);
+				}
+
 				// if some new messages have been confirmed, reward relayers
 				T::DeliveryConfirmationPayments::pay_reward(
 					lane_id,
@@ -492,6 +633,13 @@ pub mod pallet {
 					&confirmation_relayer,
 					&received_range,
 				);
+
+				// and let applications know about delivery and dispatch results of their messages
+				T::OnDeliveryConfirmed::on_delivery_confirmed(
+					lane_id,
+					&confirmed_messages,
+					&dispatch_results,
+				);
 			}
 
 			log::trace!(
@@ -503,6 +651,307 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		/// Open new outbound lane.
+		///
+		/// The lane must not be one of `ActiveOutboundLanes` and must not be already opened. If
+		/// the lane has been used before, its message nonces are continued.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::call_index(4)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(2, 1), DispatchClass::Operational))]
+		pub fn open_lane(origin: OriginFor<T>, lane_id: LaneId) -> DispatchResult {
+			Self::ensure_owner_or_root(origin)?;
+
+			ensure!(
+				!T::ActiveOutboundLanes::get().contains(&lane_id),
+				Error::<T, I>::LaneAlreadyOpened
+			);
+			DynamicOutboundLanes::<T, I>::try_mutate(|lanes| {
+				ensure!(
+					!lanes.iter().any(|(id, _)| *id == lane_id),
+					Error::<T, I>::LaneAlreadyOpened
+				);
+				lanes
+					.try_push((lane_id, LaneState::Opened))
+					.map_err(|_| Error::<T, I>::TooManyDynamicOutboundLanes)
+			})?;
+
+			log::info!(target: LOG_TARGET, "Opened outbound lane {:?}", lane_id);
+			Self::deposit_event(Event::LaneOpened { lane_id });
+
+			Ok(())
+		}
+
+		/// Start closing the outbound lane, that has been opened using the `open_lane` call.
+		///
+		/// The lane stops accepting new messages immediately. Already queued messages are still
+		/// delivered to the bridged chain. The lane is closed once all of them are delivered and
+		/// pruned.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		#[pallet::call_index(5)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(2, 1), DispatchClass::Operational))]
+		pub fn close_lane(origin: OriginFor<T>, lane_id: LaneId) -> DispatchResult {
+			Self::ensure_owner_or_root(origin)?;
+
+			DynamicOutboundLanes::<T, I>::try_mutate(|lanes| {
+				match lanes
+					.iter_mut()
+					.find(|(id, state)| *id == lane_id && *state == LaneState::Opened)
+				{
+					Some((_, state)) => {
+						*state = LaneState::Closing;
+						Ok(())
+					},
+					None => Err(Error::<T, I>::LaneIsNotOpened),
+				}
+			})?;
+
+			log::info!(target: LOG_TARGET, "Closing outbound lane {:?}", lane_id);
+			Self::deposit_event(Event::LaneClosing { lane_id });
+
+			Ok(())
+		}
+
+		/// Prune expired outbound messages.
+		///
+		/// Payloads of undelivered messages, that have not been delivered within `MessageTtl`
+		/// blocks, are removed from the storage and the `OnMessageExpired` callback is called
+		/// for every such message. Expired messages are still delivered to the bridged chain,
+		/// but with empty payload, which can't be dispatched there. This call also removes
+		/// expiration records of already delivered messages.
+		///
+		/// At most `max_messages` messages are visited by the call. May be called by anyone.
+		#[pallet::call_index(6)]
+		#[pallet::weight(prune_expired_messages_weight::<T, I>(*max_messages, *max_messages))]
+		pub fn prune_expired_messages(
+			origin: OriginFor<T>,
+			lane_id: LaneId,
+			max_messages: MessageNonce,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+			ensure_signed(origin)?;
+			ensure_lane_not_halted::<T, I>(lane_id)?;
+
+			let block_number = frame_system::Pallet::<T>::block_number();
+			let lane_data = outbound_lane::<T, I>(lane_id).data();
+			let mut nonce = OutboundMessageDeadlinesCursor::<T, I>::get(lane_id).max(1);
+			let mut visited_messages = 0;
+			let mut expired_messages = 0;
+			while visited_messages < max_messages && nonce <= lane_data.latest_generated_nonce {
+				let key = MessageKey { lane_id, nonce };
+				if nonce > lane_data.latest_received_nonce {
+					match OutboundMessageDeadlines::<T, I>::get(&key) {
+						Some(deadline) if deadline < block_number => {
+							if let Some(payload) = OutboundMessages::<T, I>::get(&key) {
+								T::OnMessageExpired::on_message_expired(
+									lane_id,
+									nonce,
+									&payload.into(),
+								);
+								OutboundMessages::<T, I>::insert(
+									&key,
+									StoredMessagePayload::<T, I>::default(),
+								);
+							}
+							OutboundMessageSenders::<T, I>::remove(&key);
+
+							log::trace!(
+								target: LOG_TARGET,
+								"Outbound message {:?}/{} has expired",
+								lane_id,
+								nonce,
+							);
+							Self::deposit_event(Event::MessageExpired { lane_id, nonce });
+							expired_messages += 1;
+						},
+						Some(_) => break,
+						None => (),
+					}
+				}
+
+				OutboundMessageDeadlines::<T, I>::remove(&key);
+				visited_messages += 1;
+				nonce += 1;
+			}
+			OutboundMessageDeadlinesCursor::<T, I>::insert(lane_id, nonce);
+
+			Ok(PostDispatchInfo {
+				actual_weight: Some(prune_expired_messages_weight::<T, I>(
+					visited_messages,
+					expired_messages,
+				)),
+				pays_fee: Pays::Yes,
+			})
+		}
+
+		/// Cancel undelivered outbound message.
+		///
+		/// May only be called by the account that has sent the message. The message payload is
+		/// removed from the storage, so proofs generated after this call deliver the message
+		/// with empty payload. Proofs that have been generated before still carry the original
+		/// payload, so the message may still be dispatched at the bridged chain. That's why the
+		/// sender is not refunded here. Once the delivery is confirmed by the proof that
+		/// includes the message dispatch result, the `OnMessageCancelled` handler decides
+		/// whether the sender may be refunded. Relayers are rewarded for delivering cancelled
+		/// messages as for any other message.
+		///
+		/// Every sender may have at most `MAX_CANCELLED_MESSAGES_PER_SENDER` cancelled messages
+		/// at the lane, which delivery is not yet confirmed.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(5, 3))]
+		pub fn cancel_message(
+			origin: OriginFor<T>,
+			lane_id: LaneId,
+			nonce: MessageNonce,
+		) -> DispatchResult {
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+			let sender = ensure_signed(origin)?;
+			ensure_lane_not_halted::<T, I>(lane_id)?;
+
+			let lane_data = outbound_lane::<T, I>(lane_id).data();
+			ensure!(nonce <= lane_data.latest_generated_nonce, Error::<T, I>::MessageIsNotYetSent);
+			ensure!(
+				nonce > lane_data.latest_received_nonce,
+				Error::<T, I>::MessageIsAlreadyDelivered
+			);
+
+			let key = MessageKey { lane_id, nonce };
+			ensure!(
+				OutboundMessageSenders::<T, I>::get(&key).as_ref() == Some(&sender),
+				Error::<T, I>::NotMessageSender
+			);
+			OutboundCancelledMessages::<T, I>::try_mutate_exists(
+				lane_id,
+				&sender,
+				|cancelled_nonces| -> Result<(), Error<T, I>> {
+					let cancelled_nonces = cancelled_nonces.get_or_insert_with(Default::default);
+					// forget messages, which delivery has been confirmed without dispatch result
+					cancelled_nonces.retain(|nonce| *nonce > lane_data.latest_received_nonce);
+					ensure!(
+						!cancelled_nonces.contains(&nonce),
+						Error::<T, I>::MessageIsAlreadyCancelled
+					);
+					cancelled_nonces
+						.try_push(nonce)
+						.map_err(|_| Error::<T, I>::TooManyCancelledMessages)
+				},
+			)?;
+
+			OutboundMessages::<T, I>::insert(&key, StoredMessagePayload::<T, I>::default());
+			OutboundMessageDeadlines::<T, I>::remove(&key);
+
+			log::trace!(
+				target: LOG_TARGET,
+				"Outbound message {:?}/{} has been cancelled by its sender",
+				lane_id,
+				nonce,
+			);
+			Self::deposit_event(Event::MessageCancelled { lane_id, nonce });
+
+			Ok(())
+		}
+
+		/// Change operating mode of the lane.
+		///
+		/// Halted lane doesn't accept outbound messages and rejects all proofs and calls that
+		/// are touching the lane. Lane that is rejecting outbound messages is still accepting
+		/// inbound messages and proofs.
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the `LaneOwnerOrigin`.
+		#[pallet::call_index(9)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
+		pub fn set_lane_operating_mode(
+			origin: OriginFor<T>,
+			lane_id: LaneId,
+			operating_mode: MessagesOperatingMode,
+		) -> DispatchResult {
+			if Self::ensure_owner_or_root(origin.clone()).is_err() {
+				T::LaneOwnerOrigin::ensure_origin(origin, &lane_id)?;
+			}
+
+			LaneOperatingModes::<T, I>::insert(lane_id, operating_mode);
+
+			log::info!(
+				target: LOG_TARGET,
+				"Setting lane {:?} operating mode to {:?}",
+				lane_id,
+				operating_mode,
+			);
+			Self::deposit_event(Event::LaneOperatingModeChanged { lane_id, operating_mode });
+
+			Ok(())
+		}
+
+		/// Dispatch parked inbound message.
+		///
+		/// Inbound messages with dispatch weight above `MaxImmediateDispatchWeight` are not
+		/// dispatched when they're received. Instead, they're parked until there's enough weight
+		/// to dispatch them in the `on_idle` hook or until this call is submitted. The
+		/// `weight_limit` must cover dispatch weight of the message. May be called by anyone.
+		#[pallet::call_index(8)]
+		#[pallet::weight(
+			T::DbWeight::get()
+				.reads(1)
+				.saturating_add(parked_message_overhead::<T, I>())
+				.saturating_add(*weight_limit)
+		)]
+		pub fn execute_parked_message(
+			origin: OriginFor<T>,
+			lane_id: LaneId,
+			nonce: MessageNonce,
+			weight_limit: Weight,
+		) -> DispatchResultWithPostInfo {
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+			ensure_signed(origin)?;
+			ensure_lane_not_halted::<T, I>(lane_id)?;
+
+			let key = MessageKey { lane_id, nonce };
+			let (relayer, message, dispatch_weight) =
+				parked_message::<T, I>(&key).ok_or(Error::<T, I>::ParkedMessageNotFound)?;
+			ensure!(
+				dispatch_weight.all_lte(weight_limit),
+				Error::<T, I>::InsufficientDispatchWeight
+			);
+
+			let spent_weight =
+				dispatch_parked_message::<T, I>(key, &relayer, message, dispatch_weight);
+
+			Ok(PostDispatchInfo {
+				actual_weight: Some(
+					T::DbWeight::get()
+						.reads(1)
+						.saturating_add(parked_message_overhead::<T, I>())
+						.saturating_add(spent_weight),
+				),
+				pays_fee: Pays::Yes,
+			})
+		}
+
+		/// Prune unrewarded relayer entries of the inbound lane.
+		///
+		/// Entries, covering messages that are already confirmed by the bridged chain, are
+		/// removed and adjacent entries of the same relayer are merged. This keeps the size of
+		/// the lane state (and hence of messages delivery proofs) small. May be called by anyone.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 1))]
+		pub fn prune_lane(origin: OriginFor<T>, lane_id: LaneId) -> DispatchResultWithPostInfo {
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+			ensure_signed(origin)?;
+			ensure_lane_not_halted::<T, I>(lane_id)?;
+
+			// we only write lane data if it has been updated
+			let is_pruned = inbound_lane::<T, I>(lane_id).prune_relayers();
+			let actual_weight = if is_pruned {
+				log::trace!(target: LOG_TARGET, "Pruned inbound lane {:?}", lane_id);
+				T::DbWeight::get().reads_writes(3, 1)
+			} else {
+				T::DbWeight::get().reads(3)
+			};
+
+			Ok(PostDispatchInfo { actual_weight: Some(actual_weight), pays_fee: Pays::Yes })
+		}
 	}
 
 	#[pallet::event]
@@ -520,6 +969,28 @@ pub mod pallet {
 		),
 		/// Messages in the inclusive range have been delivered to the bridged chain.
 		MessagesDelivered { lane_id: LaneId, messages: DeliveredMessages },
+		/// Outbound message has expired before it has been delivered to the bridged chain.
+		MessageExpired { lane_id: LaneId, nonce: MessageNonce },
+		/// Outbound message has been cancelled by its sender before it has been delivered to
+		/// the bridged chain.
+		MessageCancelled { lane_id: LaneId, nonce: MessageNonce },
+		/// Parked inbound message has been dispatched.
+		ParkedMessageDispatched {
+			lane_id: LaneId,
+			nonce: MessageNonce,
+			dispatch_result: MessageDispatchResult<
+				<T::MessageDispatch as MessageDispatch<T::AccountId>>::DispatchLevelResult,
+			>,
+		},
+		/// Operating mode of the lane has been changed.
+		LaneOperatingModeChanged { lane_id: LaneId, operating_mode: MessagesOperatingMode },
+		/// Outbound lane has been opened.
+		LaneOpened { lane_id: LaneId },
+		/// Outbound lane has stopped accepting new messages and will be closed once all queued
+		/// messages are delivered.
+		LaneClosing { lane_id: LaneId },
+		/// Outbound lane has been closed.
+		LaneClosed { lane_id: LaneId },
 	}
 
 	#[pallet::error]
@@ -554,6 +1025,31 @@ pub mod pallet {
 		/// The number of actually confirmed messages is going to be larger than the number of
 		/// messages in the proof. This may mean that this or bridged chain storage is corrupted.
 		TryingToConfirmMoreMessagesThanExpected,
+		/// The outbound lane is already opened.
+		LaneAlreadyOpened,
+		/// The outbound lane is not opened using the `open_lane` call.
+		LaneIsNotOpened,
+		/// Too many outbound lanes are opened using the `open_lane` call.
+		TooManyDynamicOutboundLanes,
+		/// The outbound lane has already accepted maximal number of messages or bytes at this
+		/// block.
+		LaneQuotaExceeded,
+		/// The message may only be cancelled by the account that has sent it.
+		NotMessageSender,
+		/// The sender has too many cancelled messages at the lane, which delivery is not yet
+		/// confirmed.
+		TooManyCancelledMessages,
+		/// The message has already been cancelled.
+		MessageIsAlreadyCancelled,
+		/// There's no parked inbound message with given key.
+		ParkedMessageNotFound,
+		/// The declared dispatch weight doesn't cover dispatch weight of the parked message or
+		/// the cost of processing additional lanes of the messages proof.
+		InsufficientDispatchWeight,
+		/// The lane is halted.
+		LaneIsHalted,
+		/// The lane is not accepting outbound messages.
+		LaneNotOperatingNormally,
 		/// Error generated by the `OwnedBridgeModule` trait.
 		BridgeModule(bp_runtime::OwnedBridgeModuleError),
 	}
@@ -576,11 +1072,70 @@ pub mod pallet {
 	pub type PalletOperatingMode<T: Config<I>, I: 'static = ()> =
 		StorageValue<_, MessagesOperatingMode, ValueQuery>;
 
+	/// Operating modes of lanes.
+	///
+	/// Lane operating mode is checked in addition to the pallet operating mode. It is used to
+	/// halt/resume operations of the single lane, without touching other lanes.
+	#[pallet::storage]
+	#[pallet::getter(fn lane_operating_mode)]
+	pub type LaneOperatingModes<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, LaneId, MessagesOperatingMode, ValueQuery>;
+
 	/// Map of lane id => inbound lane data.
 	#[pallet::storage]
 	pub type InboundLanes<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, LaneId, StoredInboundLaneData<T, I>, ValueQuery>;
 
+	/// Inbound lane, which has been visited last by the `on_idle` relayer entries pruning.
+	///
+	/// If it is `None`, the next pruning starts from the first inbound lane.
+	#[pallet::storage]
+	pub type InboundLanesPruningCursor<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, LaneId, OptionQuery>;
+
+	/// Map of lane id => bitmap of messages that have been delivered out of order to the
+	/// unordered inbound lane.
+	#[pallet::storage]
+	pub type InboundLanesDeliveredAhead<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, LaneId, u128, ValueQuery>;
+
+	/// Map of message key => relayer that has delivered the message out of order to the
+	/// unordered inbound lane.
+	///
+	/// The entry is removed when the message is added to the unrewarded relayer entries of the
+	/// lane.
+	#[pallet::storage]
+	pub type InboundLanesDeliveredAheadRelayers<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, MessageKey, T::InboundRelayer>;
+
+	/// Dispatch results of latest `MAX_STORED_DISPATCH_RESULTS` messages, received by every
+	/// inbound lane.
+	#[pallet::storage]
+	pub type InboundMessageDispatchResults<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		Blake2_128Concat,
+		MessageKey,
+		MessageDispatchResult<
+			<T::MessageDispatch as MessageDispatch<T::AccountId>>::DispatchLevelResult,
+		>,
+	>;
+
+	/// Inbound messages, which dispatch has been deferred because of their large dispatch weight.
+	#[pallet::storage]
+	#[pallet::unbounded]
+	pub type ParkedMessages<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, MessageKey, ParkedMessage<T::AccountId>>;
+
+	/// Keys of parked inbound messages in order they have been received.
+	#[pallet::storage]
+	pub type ParkedMessagesQueue<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BoundedVec<MessageKey, T::MaxParkedMessages>, ValueQuery>;
+
+	/// Number of parked inbound messages of every lane.
+	#[pallet::storage]
+	pub type LaneParkedMessages<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, LaneId, u32, ValueQuery>;
+
 	/// Map of lane id => outbound lane data.
 	#[pallet::storage]
 	pub type OutboundLanes<T: Config<I>, I: 'static = ()> =
@@ -591,6 +1146,55 @@ pub mod pallet {
 	pub type OutboundMessages<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Blake2_128Concat, MessageKey, StoredMessagePayload<T, I>>;
 
+	/// Block after which the queued outbound message expires. Only set for messages that have been
+	/// sent when `MessageTtl` has been set to `Some(_)`.
+	#[pallet::storage]
+	pub type OutboundMessageDeadlines<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, MessageKey, T::BlockNumber>;
+
+	/// Account that has sent the queued outbound message. Only set for messages that have been
+	/// sent by signed origins and have not expired.
+	#[pallet::storage]
+	pub type OutboundMessageSenders<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, MessageKey, T::AccountId>;
+
+	/// Map of lane id and sender => nonces of outbound messages, cancelled by the sender, which
+	/// delivery is not yet confirmed. Nonces of messages, which delivery has been confirmed
+	/// without dispatch result, are removed when the sender cancels another message.
+	#[pallet::storage]
+	pub type OutboundCancelledMessages<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		LaneId,
+		Blake2_128Concat,
+		T::AccountId,
+		BoundedVec<MessageNonce, ConstU32<MAX_CANCELLED_MESSAGES_PER_SENDER>>,
+	>;
+
+	/// Nonce of the oldest outbound message that may have an entry in the
+	/// `OutboundMessageDeadlines` map.
+	#[pallet::storage]
+	pub type OutboundMessageDeadlinesCursor<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, LaneId, MessageNonce, ValueQuery>;
+
+	/// Outbound lane quota usage: block number, number of messages and total size of their
+	/// payloads, accepted by the lane at this block.
+	#[pallet::storage]
+	pub type OutboundLaneQuotas<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, LaneId, (T::BlockNumber, MessageNonce, u32)>;
+
+	/// Outbound lane fee factors. The delivery fee of every message, sent over the lane, is
+	/// multiplied by this factor. Missing entry means that the factor is equal to one.
+	#[pallet::storage]
+	#[pallet::getter(fn outbound_lane_fee_factor)]
+	pub type OutboundLaneFeeFactors<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, LaneId, FixedU128>;
+
+	/// Outbound lanes that have been opened using the `open_lane` call and are not yet closed.
+	#[pallet::storage]
+	pub type DynamicOutboundLanes<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BoundedVec<(LaneId, LaneState), T::MaxDynamicOutboundLanes>, ValueQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
 		/// Initial pallet operating mode.
@@ -647,74 +1251,323 @@ pub mod pallet {
 		pub fn inbound_lane_data(lane: LaneId) -> InboundLaneData<T::InboundRelayer> {
 			InboundLanes::<T, I>::get(lane).0
 		}
-	}
-}
 
-impl<T, I> bp_messages::source_chain::MessagesBridge<T::RuntimeOrigin, T::OutboundPayload>
-	for Pallet<T, I>
-where
-	T: Config<I>,
-	I: 'static,
-{
-	type Error = sp_runtime::DispatchErrorWithPostInfo<PostDispatchInfo>;
+		/// Return dispatch result of given inbound message.
+		///
+		/// Returns `None` if the message has not been delivered yet or if its dispatch result is
+		/// no longer stored in the runtime storage.
+		pub fn inbound_message_dispatch_result(
+			lane: LaneId,
+			nonce: MessageNonce,
+		) -> Option<
+			MessageDispatchResult<
+				<T::MessageDispatch as MessageDispatch<T::AccountId>>::DispatchLevelResult,
+			>,
+		> {
+			InboundMessageDispatchResults::<T, I>::get(MessageKey { lane_id: lane, nonce })
+		}
 
-	fn send_message(
-		sender: T::RuntimeOrigin,
-		lane: LaneId,
-		message: T::OutboundPayload,
-	) -> Result<SendMessageArtifacts, Self::Error> {
-		crate::send_message::<T, I>(sender, lane, message)
-	}
-}
+		/// Return storage keys that need to be proved to prove delivery and dispatch result of
+		/// given inbound message.
+		///
+		/// Returns `None` if the message has not been delivered yet or if its dispatch result is
+		/// no longer stored in the runtime storage.
+		pub fn inbound_message_delivery_proof_keys(
+			lane: LaneId,
+			nonce: MessageNonce,
+		) -> Option<InboundMessageDeliveryProofKeys> {
+			let key = MessageKey { lane_id: lane, nonce };
+			if !InboundMessageDispatchResults::<T, I>::contains_key(&key) {
+				return None
+			}
 
-/// Function that actually sends message.
-fn send_message<T: Config<I>, I: 'static>(
-	submitter: T::RuntimeOrigin,
-	lane_id: LaneId,
-	payload: T::OutboundPayload,
-) -> sp_std::result::Result<
-	SendMessageArtifacts,
-	sp_runtime::DispatchErrorWithPostInfo<PostDispatchInfo>,
-> {
-	ensure_normal_operating_mode::<T, I>()?;
+			Some(InboundMessageDeliveryProofKeys {
+				dispatch_result_key: sp_core::storage::StorageKey(InboundMessageDispatchResults::<
+					T,
+					I,
+				>::hashed_key_for(&key)),
+			})
+		}
 
-	// let's check if outbound lane is active
-	ensure!(T::ActiveOutboundLanes::get().contains(&lane_id), Error::<T, I>::InactiveOutboundLane,);
+		/// Return details of the outbound lane, that may be used to find out why the lane is stuck.
+		pub fn outbound_lane_diagnostics(lane: LaneId) -> OutboundLaneDiagnostics {
+			OutboundLaneDiagnostics {
+				lane_data: outbound_lane::<T, I>(lane).data(),
+				lane_state: Self::outbound_lane_state(lane),
+				operating_mode: LaneOperatingModes::<T, I>::get(lane),
+			}
+		}
 
-	// let's first check if message can be delivered to target chain
-	T::TargetHeaderChain::verify_message(&payload).map_err(|err| {
-		log::trace!(
-			target: LOG_TARGET,
-			"Message to lane {:?} is rejected by target chain: {:?}",
-			lane_id,
-			err,
-		);
+		/// Return details of the inbound lane, that may be used to find out why the lane is stuck.
+		pub fn inbound_lane_diagnostics(lane: LaneId) -> InboundLaneDiagnostics<T::InboundRelayer> {
+			InboundLaneDiagnostics {
+				lane_data: Self::inbound_lane_data(lane),
+				delivered_ahead: InboundLanesDeliveredAhead::<T, I>::get(lane),
+				operating_mode: LaneOperatingModes::<T, I>::get(lane),
+			}
+		}
 
-		Error::<T, I>::MessageRejectedByChainVerifier
-	})?;
+		/// Return outbound lane state.
+		pub fn outbound_lane_state(lane: LaneId) -> LaneState {
+			if T::ActiveOutboundLanes::get().contains(&lane) {
+				return LaneState::Opened
+			}
 
-	// now let's enforce any additional lane rules
-	let mut lane = outbound_lane::<T, I>(lane_id);
-	T::LaneMessageVerifier::verify_message(&submitter, &lane_id, &lane.data(), &payload).map_err(
-		|err| {
-			log::trace!(
-				target: LOG_TARGET,
-				"Message to lane {:?} is rejected by lane verifier: {:?}",
-				lane_id,
-				err,
-			);
+			DynamicOutboundLanes::<T, I>::get()
+				.into_iter()
+				.find(|(id, _)| *id == lane)
+				.map(|(_, state)| state)
+				.unwrap_or(LaneState::Closed)
+		}
 
-			Error::<T, I>::MessageRejectedByLaneVerifier
-		},
-	)?;
+		/// Prune delivered messages of one of outbound lanes.
+		fn prune_outbound_lanes(remaining_weight: Weight) -> Weight {
+			// we'll need at least to read dynamic lanes, outbound lane state, kill a message with
+			// its sender and update lane state
+			let db_weight = T::DbWeight::get();
+			if !remaining_weight.all_gte(db_weight.reads_writes(2, 3)) {
+				return Weight::zero()
+			}
 
-	// finally, save message in outbound storage and emit event
-	let encoded_payload = payload.encode();
+			// messages from lane with index `i` in `ActiveOutboundLanes` + `DynamicOutboundLanes`
+			// are pruned when `System::block_number() % lanes.len() == i`. Otherwise we need to
+			// read lane states on every block, wasting the whole `remaining_weight` for nothing and
+			// causing starvation of the last lane pruning
+			let static_lanes = T::ActiveOutboundLanes::get();
+			let mut dynamic_lanes = DynamicOutboundLanes::<T, I>::get();
+			let mut used_weight = db_weight.reads(1);
+			let active_lanes_len = static_lanes.len() + dynamic_lanes.len();
+			if active_lanes_len == 0 {
+				return used_weight
+			}
+			let active_lane_index = u32::unique_saturated_from(
+				frame_system::Pallet::<T>::block_number() % (active_lanes_len as u32).into(),
+			) as usize;
+			let (active_lane_id, active_lane_state) = match static_lanes.get(active_lane_index) {
+				Some(lane_id) => (*lane_id, LaneState::Opened),
+				None => dynamic_lanes[active_lane_index - static_lanes.len()],
+			};
+
+			// next db read - outbound lane state
+			let mut active_lane = outbound_lane::<T, I>(active_lane_id);
+			used_weight += db_weight.reads(1);
+			// and here we'll have writes
+			used_weight += active_lane.prune_messages(db_weight, remaining_weight - used_weight);
+
+			// closing lane is closed when all its messages are delivered and pruned
+			let close_lane_weight = db_weight.reads_writes(1, 1);
+			if active_lane_state == LaneState::Closing &&
+				remaining_weight.all_gte(used_weight + close_lane_weight)
+			{
+				used_weight += close_lane_weight;
+				let lane_data = active_lane.data();
+				if lane_data.oldest_unpruned_nonce > lane_data.latest_generated_nonce {
+					dynamic_lanes.retain(|(lane_id, _)| *lane_id != active_lane_id);
+					DynamicOutboundLanes::<T, I>::put(dynamic_lanes);
+					log::info!(target: LOG_TARGET, "Closed outbound lane {:?}", active_lane_id);
+					Self::deposit_event(Event::LaneClosed { lane_id: active_lane_id });
+				}
+			}
+
+			// we already checked we have enough `remaining_weight` to cover this `used_weight`
+			used_weight
+		}
+
+		/// Dispatch parked inbound messages while there's enough remaining weight.
+		fn dispatch_parked_messages(remaining_weight: Weight) -> Weight {
+			// we'll need at least to read the pallet operating mode and the parked messages queue
+			let db_weight = T::DbWeight::get();
+			let mut used_weight = db_weight.reads(2);
+			if !remaining_weight.all_gte(used_weight) {
+				return Weight::zero()
+			}
+
+			// messages are dispatched once the pallet is resumed
+			if Self::is_halted() {
+				return db_weight.reads(1)
+			}
+
+			for key in ParkedMessagesQueue::<T, I>::get() {
+				// we need to read the lane operating mode and the parked message to compute its
+				// dispatch weight
+				if !remaining_weight.all_gte(used_weight + db_weight.reads(2)) {
+					break
+				}
+				used_weight += db_weight.reads(2);
+
+				// messages of halted lanes are dispatched once the lane is resumed
+				if LaneOperatingModes::<T, I>::get(key.lane_id).is_halted() {
+					continue
+				}
+
+				let (relayer, message, dispatch_weight) = match parked_message::<T, I>(&key) {
+					Some(parked_message) => parked_message,
+					None => continue,
+				};
+				// lighter messages, following the heavy one, may still fit into the remaining
+				// weight
+				if !remaining_weight
+					.all_gte(used_weight + parked_message_overhead::<T, I>() + dispatch_weight)
+				{
+					continue
+				}
+
+				used_weight += parked_message_overhead::<T, I>();
+				used_weight +=
+					dispatch_parked_message::<T, I>(key, &relayer, message, dispatch_weight);
+			}
+
+			used_weight
+		}
+
+		/// Prune unrewarded relayer entries of at most `MaxInboundLanesToPrunePerBlock` inbound
+		/// lanes.
+		fn prune_inbound_lanes(remaining_weight: Weight) -> Weight {
+			let max_lanes_to_prune = T::MaxInboundLanesToPrunePerBlock::get();
+			if max_lanes_to_prune == 0 {
+				return Weight::zero()
+			}
+
+			// we'll need to read and write the cursor and (at most once) to reach the end of the
+			// inbound lanes map. Then, for every lane, we read its key and data and maybe update
+			// the data
+			let db_weight = T::DbWeight::get();
+			let prune_lane_weight = db_weight.reads_writes(2, 1);
+			let mut used_weight = db_weight.reads_writes(2, 1);
+			if !remaining_weight.all_gte(used_weight + prune_lane_weight) {
+				return Weight::zero()
+			}
+
+			// lanes are visited in the order of their keys in the storage, starting from the lane
+			// that follows the lane, visited last. When we reach the end of the map, next pruning
+			// starts from the first lane
+			let mut cursor = InboundLanesPruningCursor::<T, I>::get();
+			let mut lanes = match cursor {
+				Some(lane_id) => InboundLanes::<T, I>::iter_keys_from(
+					InboundLanes::<T, I>::hashed_key_for(lane_id),
+				),
+				None => InboundLanes::<T, I>::iter_keys(),
+			};
+			let mut visited_lanes = 0;
+			while visited_lanes < max_lanes_to_prune &&
+				remaining_weight.all_gte(used_weight + prune_lane_weight)
+			{
+				let lane_id = match lanes.next() {
+					Some(lane_id) => lane_id,
+					None => {
+						cursor = None;
+						break
+					},
+				};
+
+				used_weight += db_weight.reads(2);
+				if inbound_lane::<T, I>(lane_id).prune_relayers() {
+					used_weight += db_weight.writes(1);
+					log::trace!(target: LOG_TARGET, "Pruned inbound lane {:?}", lane_id);
+				}
+
+				cursor = Some(lane_id);
+				visited_lanes += 1;
+			}
+			InboundLanesPruningCursor::<T, I>::set(cursor);
+
+			// we already checked we have enough `remaining_weight` to cover this `used_weight`
+			used_weight
+		}
+	}
+}
+
+impl<T, I> bp_messages::source_chain::MessagesBridge<T::RuntimeOrigin, T::OutboundPayload>
+	for Pallet<T, I>
+where
+	T: Config<I>,
+	I: 'static,
+{
+	type Error = sp_runtime::DispatchErrorWithPostInfo<PostDispatchInfo>;
+
+	fn send_message(
+		sender: T::RuntimeOrigin,
+		lane: LaneId,
+		message: T::OutboundPayload,
+	) -> Result<SendMessageArtifacts, Self::Error> {
+		crate::send_message::<T, I>(sender, lane, message)
+	}
+}
+
+/// Function that actually sends message.
+fn send_message<T: Config<I>, I: 'static>(
+	submitter: T::RuntimeOrigin,
+	lane_id: LaneId,
+	payload: T::OutboundPayload,
+) -> sp_std::result::Result<
+	SendMessageArtifacts,
+	sp_runtime::DispatchErrorWithPostInfo<PostDispatchInfo>,
+> {
+	ensure_normal_operating_mode::<T, I>()?;
+	ensure_normal_lane_operating_mode::<T, I>(lane_id)?;
+
+	// let's check if outbound lane is active
+	ensure!(
+		Pallet::<T, I>::outbound_lane_state(lane_id) == LaneState::Opened,
+		Error::<T, I>::InactiveOutboundLane,
+	);
+
+	// let's first check if message can be delivered to target chain
+	T::TargetHeaderChain::verify_message(&payload).map_err(|err| {
+		log::trace!(
+			target: LOG_TARGET,
+			"Message to lane {:?} is rejected by target chain: {:?}",
+			lane_id,
+			err,
+		);
+
+		Error::<T, I>::MessageRejectedByChainVerifier
+	})?;
+
+	// now let's enforce any additional lane rules
+	let mut lane = outbound_lane::<T, I>(lane_id);
+	T::LaneMessageVerifier::verify_message(&submitter, &lane_id, &lane.data(), &payload).map_err(
+		|err| {
+			log::trace!(
+				target: LOG_TARGET,
+				"Message to lane {:?} is rejected by lane verifier: {:?}",
+				lane_id,
+				err,
+			);
+
+			Error::<T, I>::MessageRejectedByLaneVerifier
+		},
+	)?;
+
+	// finally, save message in outbound storage and emit event
+	let encoded_payload = payload.encode();
 	let encoded_payload_len = encoded_payload.len();
 	ensure!(
 		encoded_payload_len <= T::MaximalOutboundPayloadSize::get() as usize,
 		Error::<T, I>::MessageIsTooLarge
 	);
+	// the quota is checked before the fee factor is updated and the fee is paid, so nothing is
+	// charged if the lane is not accepting more messages at this block
+	let lane_quota = check_outbound_lane_quota::<T, I>(lane_id, encoded_payload_len as u32)?;
+	let fee_factor = update_outbound_lane_fee_factor::<T, I>(lane_id);
+	T::DeliveryFeeCalculator::pay_delivery_fee(
+		&submitter,
+		&lane_id,
+		&lane.data(),
+		encoded_payload_len as u32,
+		fee_factor,
+	)
+	.map_err(|err| {
+		log::trace!(
+			target: LOG_TARGET,
+			"Message to lane {:?} is rejected: failed to pay delivery fee: {:?}",
+			lane_id,
+			err,
+		);
+
+		Error::<T, I>::FailedToWithdrawMessageFee
+	})?;
+	OutboundLaneQuotas::<T, I>::insert(lane_id, lane_quota);
 	let nonce = lane.send_message(encoded_payload);
 
 	log::trace!(
@@ -728,16 +1581,323 @@ fn send_message<T: Config<I>, I: 'static>(
 	Pallet::<T, I>::deposit_event(Event::MessageAccepted { lane_id, nonce });
 
 	// we may introduce benchmarks for that, but no heavy ops planned here apart from
-	// db reads and writes. There are currently 2 db reads and 2 db writes:
+	// db reads and writes. There are currently 6 db reads and 4 db writes:
 	// - one db read for operation mode check (`ensure_normal_operating_mode`);
+	// - one db read for lane operation mode check (`ensure_normal_lane_operating_mode`);
+	// - one db read for dynamic outbound lanes (`outbound_lane_state`);
 	// - one db read for outbound lane state (`outbound_lane`);
+	// - one db read and one db write for lane fee factor (`update_outbound_lane_fee_factor`);
+	// - one db read and one db write for lane quota (`check_outbound_lane_quota`);
 	// - one db write for outbound lane state (`send_message`);
 	// - one db write for the message (`send_message`);
-	let actual_weight = T::DbWeight::get().reads_writes(2, 2);
+	// If message is sent by signed origin, there's also one db write for the message sender.
+	// If messages are expiring, there's also one db write for the message deadline.
+	// The fee calculator may perform additional db operations - they're not accounted here.
+	let mut actual_weight = T::DbWeight::get().reads_writes(6, 4);
+	if let Ok(sender) = frame_system::ensure_signed(submitter) {
+		OutboundMessageSenders::<T, I>::insert(MessageKey { lane_id, nonce }, sender);
+		actual_weight += T::DbWeight::get().writes(1);
+	}
+	if let Some(message_ttl) = T::MessageTtl::get() {
+		OutboundMessageDeadlines::<T, I>::insert(
+			MessageKey { lane_id, nonce },
+			frame_system::Pallet::<T>::block_number().saturating_add(message_ttl),
+		);
+		actual_weight += T::DbWeight::get().writes(1);
+	}
 
 	Ok(SendMessageArtifacts { nonce, weight: actual_weight })
 }
 
+/// Let the `OnMessageCancelled` handler decide whether the sender of the delivered message may
+/// be refunded, if the message has been cancelled.
+fn on_cancelled_message_delivered<T: Config<I>, I: 'static>(
+	lane_id: LaneId,
+	dispatch_result: &DeliveredMessageDispatchResult,
+) {
+	let nonce = dispatch_result.nonce;
+	let sender = match OutboundMessageSenders::<T, I>::get(MessageKey { lane_id, nonce }) {
+		Some(sender) => sender,
+		None => return,
+	};
+
+	let is_cancelled =
+		OutboundCancelledMessages::<T, I>::mutate_exists(lane_id, &sender, |cancelled_nonces| {
+			let is_cancelled = match cancelled_nonces.as_mut() {
+				Some(cancelled_nonces) => {
+					let cancelled_messages = cancelled_nonces.len();
+					cancelled_nonces.retain(|cancelled_nonce| *cancelled_nonce != nonce);
+					cancelled_nonces.len() != cancelled_messages
+				},
+				None => false,
+			};
+			if cancelled_nonces.as_ref().map(|n| n.is_empty()).unwrap_or(false) {
+				*cancelled_nonces = None;
+			}
+			is_cancelled
+		});
+	if is_cancelled {
+		T::OnMessageCancelled::on_message_cancelled(lane_id, &sender, dispatch_result);
+	}
+}
+
+/// Weight of the `prune_expired_messages` call that has visited `visited_messages` messages and
+/// has found `expired_messages` expired messages among them.
+fn prune_expired_messages_weight<T: Config<I>, I: 'static>(
+	visited_messages: MessageNonce,
+	expired_messages: MessageNonce,
+) -> Weight {
+	let db_weight = T::DbWeight::get();
+	// there's one db read for the lane operating mode, one db read for the lane state, one db read
+	// and write for the cursor. Then we read and remove deadline of every visited message. Every
+	// expired message is also read and overwritten and its sender is removed.
+	db_weight
+		.reads_writes(3, 1)
+		.saturating_add(db_weight.reads_writes(1, 1).saturating_mul(visited_messages))
+		.saturating_add(db_weight.reads_writes(1, 2).saturating_mul(expired_messages))
+		.saturating_add(
+			T::OnMessageExpired::on_message_expired_weight().saturating_mul(expired_messages),
+		)
+}
+
+/// Store dispatch result of the inbound message, removing the dispatch result of the message that
+/// has been received `MAX_STORED_DISPATCH_RESULTS` messages ago.
+fn store_dispatch_result<T: Config<I>, I: 'static>(
+	lane_id: LaneId,
+	nonce: MessageNonce,
+	dispatch_result: MessageDispatchResult<
+		<T::MessageDispatch as MessageDispatch<T::AccountId>>::DispatchLevelResult,
+	>,
+) {
+	InboundMessageDispatchResults::<T, I>::insert(MessageKey { lane_id, nonce }, dispatch_result);
+	if let Some(obsolete_nonce) = nonce.checked_sub(MAX_STORED_DISPATCH_RESULTS) {
+		InboundMessageDispatchResults::<T, I>::remove(MessageKey {
+			lane_id,
+			nonce: obsolete_nonce,
+		});
+	}
+}
+
+/// Inbound message, which dispatch has been deferred.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+pub struct ParkedMessage<AccountId> {
+	/// Account of the relayer that has delivered the message.
+	pub relayer: AccountId,
+	/// Message payload.
+	pub payload: MessagePayload,
+}
+
+/// Message "dispatcher" that parks messages instead of dispatching them.
+struct ParkMessage<T, I>(PhantomData<(T, I)>);
+
+impl<T: Config<I>, I: 'static> MessageDispatch<T::AccountId> for ParkMessage<T, I> {
+	type DispatchPayload = MessagePayload;
+	type DispatchLevelResult = ();
+
+	fn dispatch_weight(_message: &mut DispatchMessage<MessagePayload>) -> Weight {
+		Weight::zero()
+	}
+
+	fn dispatch(
+		relayer_account: &T::AccountId,
+		message: DispatchMessage<MessagePayload>,
+	) -> MessageDispatchResult<()> {
+		if let Ok(payload) = message.data.payload {
+			// we have checked that there's a room in the queue before receiving the message
+			let _ = ParkedMessagesQueue::<T, I>::try_append(message.key.clone());
+			LaneParkedMessages::<T, I>::mutate(message.key.lane_id, |count| {
+				*count = count.saturating_add(1)
+			});
+			ParkedMessages::<T, I>::insert(
+				message.key,
+				ParkedMessage { relayer: relayer_account.clone(), payload },
+			);
+		}
+
+		MessageDispatchResult { unspent_weight: Weight::zero(), dispatch_level_result: () }
+	}
+}
+
+/// Weight of the `receive_messages_proof` call.
+///
+/// In addition to the `WeightInfoExt::receive_messages_proof_weight`, it includes the weight of
+/// checking whether every message may be parked (see `may_park_message`).
+fn receive_messages_proof_weight<T: Config<I>, I: 'static>(
+	proof: &impl Size,
+	messages_count: u32,
+	dispatch_weight: Weight,
+) -> Weight {
+	T::WeightInfo::receive_messages_proof_weight(proof, messages_count, dispatch_weight)
+		.saturating_add(T::DbWeight::get().reads(2).saturating_mul(messages_count as u64))
+}
+
+/// Returns true if inbound message of given lane with given dispatch weight may be parked.
+///
+/// Message is never parked if it may not be dispatched by the `execute_parked_message` call,
+/// because it'd be stuck in the queue forever.
+fn may_park_message<T: Config<I>, I: 'static>(lane_id: LaneId, dispatch_weight: Weight) -> bool {
+	let max_extrinsic_weight = T::BlockWeights::get()
+		.get(DispatchClass::Normal)
+		.max_extrinsic
+		.unwrap_or(Weight::MAX);
+	let execute_weight = T::DbWeight::get()
+		.reads(1)
+		.saturating_add(parked_message_overhead::<T, I>())
+		.saturating_add(dispatch_weight);
+	if execute_weight.any_gt(max_extrinsic_weight) {
+		return false
+	}
+
+	let parked_messages = ParkedMessagesQueue::<T, I>::decode_len().unwrap_or(0);
+	parked_messages < T::MaxParkedMessages::get() as usize &&
+		LaneParkedMessages::<T, I>::get(lane_id) < T::MaxParkedMessagesPerLane::get()
+}
+
+/// Convert result of message parking into regular receival result.
+fn parked_receival_result<DispatchLevelResult>(
+	result: ReceivalResult<()>,
+) -> ReceivalResult<DispatchLevelResult> {
+	match result {
+		ReceivalResult::Dispatched(_) | ReceivalResult::Parked => ReceivalResult::Parked,
+		ReceivalResult::InvalidNonce => ReceivalResult::InvalidNonce,
+		ReceivalResult::TooManyUnrewardedRelayers => ReceivalResult::TooManyUnrewardedRelayers,
+		ReceivalResult::TooManyUnconfirmedMessages => ReceivalResult::TooManyUnconfirmedMessages,
+	}
+}
+
+/// Weight of reading, removing and storing dispatch result of the parked message, excluding its
+/// dispatch weight.
+fn parked_message_overhead<T: Config<I>, I: 'static>() -> Weight {
+	// we read the parked message, the queue and the lane parked messages counter, remove the
+	// message from both, update the counter and store the dispatch result, removing the obsolete
+	// one
+	T::DbWeight::get().reads_writes(3, 5)
+}
+
+/// Read parked message, returning relayer that has delivered it, the message itself and its
+/// dispatch weight.
+fn parked_message<T: Config<I>, I: 'static>(
+	key: &MessageKey,
+) -> Option<(T::AccountId, DispatchMessage<T::InboundPayload>, Weight)> {
+	let parked_message = ParkedMessages::<T, I>::get(key)?;
+	let mut message = DispatchMessage { key: key.clone(), data: parked_message.payload.into() };
+	let dispatch_weight = T::MessageDispatch::dispatch_weight(&mut message);
+	Some((parked_message.relayer, message, dispatch_weight))
+}
+
+/// Dispatch parked message, returning weight that has been spent on its dispatch.
+fn dispatch_parked_message<T: Config<I>, I: 'static>(
+	key: MessageKey,
+	relayer: &T::AccountId,
+	message: DispatchMessage<T::InboundPayload>,
+	dispatch_weight: Weight,
+) -> Weight {
+	ParkedMessages::<T, I>::remove(&key);
+	ParkedMessagesQueue::<T, I>::mutate(|queue| queue.retain(|k| *k != key));
+	LaneParkedMessages::<T, I>::mutate_exists(key.lane_id, |count| {
+		*count = count.and_then(|count| count.checked_sub(1)).filter(|count| *count != 0)
+	});
+
+	let dispatch_result = T::MessageDispatch::dispatch(relayer, message);
+	let unspent_weight = dispatch_result.unspent_weight.min(dispatch_weight);
+	store_dispatch_result::<T, I>(key.lane_id, key.nonce, dispatch_result.clone());
+
+	log::trace!(
+		target: LOG_TARGET,
+		"Dispatched parked inbound message {:?}/{}: {:?}",
+		key.lane_id,
+		key.nonce,
+		dispatch_result,
+	);
+	Pallet::<T, I>::deposit_event(Event::ParkedMessageDispatched {
+		lane_id: key.lane_id,
+		nonce: key.nonce,
+		dispatch_result,
+	});
+
+	dispatch_weight.saturating_sub(unspent_weight)
+}
+
+/// Return outbound lane quota usage, including the new message, failing if the quota is exceeded.
+/// The returned usage must be stored once the message is accepted.
+fn check_outbound_lane_quota<T: Config<I>, I: 'static>(
+	lane_id: LaneId,
+	encoded_payload_len: u32,
+) -> Result<(T::BlockNumber, MessageNonce, u32), Error<T, I>> {
+	let block_number = frame_system::Pallet::<T>::block_number();
+	let (messages, bytes) = match OutboundLaneQuotas::<T, I>::get(lane_id) {
+		Some((quota_block_number, messages, bytes)) if quota_block_number == block_number =>
+			(messages, bytes),
+		_ => (0, 0),
+	};
+
+	let messages = messages.saturating_add(1);
+	let bytes = bytes.saturating_add(encoded_payload_len);
+	if messages > T::MaxMessagesPerLanePerBlock::get() || bytes > T::MaxBytesPerLanePerBlock::get()
+	{
+		log::trace!(
+			target: LOG_TARGET,
+			"Message to lane {:?} is rejected: lane quota is exceeded at block {:?}",
+			lane_id,
+			block_number,
+		);
+
+		return Err(Error::<T, I>::LaneQuotaExceeded)
+	}
+
+	Ok((block_number, messages, bytes))
+}
+
+/// Update (if required) and return the fee factor of the outbound lane.
+///
+/// The factor is updated when the first message is sent over the lane at the block. The update
+/// is similar to EIP-1559 base fee update: the target lane usage is the half of
+/// `MaxBytesPerLanePerBlock`. The factor grows by up to 1/8 if the lane has been used above target
+/// at the last block when it has accepted any messages. Otherwise, it decreases by up to 1/8.
+/// Every block when the lane has not accepted any messages also decreases the factor by 1/8. The
+/// factor is never less than one.
+fn update_outbound_lane_fee_factor<T: Config<I>, I: 'static>(lane_id: LaneId) -> FixedU128 {
+	let fee_factor = OutboundLaneFeeFactors::<T, I>::get(lane_id).unwrap_or_else(FixedU128::one);
+	let block_number = frame_system::Pallet::<T>::block_number();
+	let (quota_block_number, bytes) = match OutboundLaneQuotas::<T, I>::get(lane_id) {
+		Some((quota_block_number, _, bytes)) if quota_block_number != block_number =>
+			(quota_block_number, bytes),
+		_ => return fee_factor,
+	};
+
+	// adjust factor according to lane usage at the last block when it has been used
+	let target_bytes = u128::from(T::MaxBytesPerLanePerBlock::get() / 2).max(1);
+	let bytes = u128::from(bytes);
+	let fee_factor = if bytes > target_bytes {
+		fee_factor.saturating_add(fee_factor.saturating_mul(FixedU128::saturating_from_rational(
+			bytes - target_bytes,
+			target_bytes.saturating_mul(8),
+		)))
+	} else {
+		fee_factor.saturating_sub(fee_factor.saturating_mul(FixedU128::saturating_from_rational(
+			target_bytes - bytes,
+			target_bytes.saturating_mul(8),
+		)))
+	};
+
+	// the lane has not been used at all during idle blocks
+	let idle_blocks: u32 = block_number
+		.saturating_sub(quota_block_number)
+		.saturating_sub(One::one())
+		.unique_saturated_into();
+	let fee_factor = fee_factor
+		.saturating_mul(FixedU128::saturating_from_rational(7, 8).saturating_pow(idle_blocks as _))
+		.max(FixedU128::one());
+
+	if fee_factor == FixedU128::one() {
+		OutboundLaneFeeFactors::<T, I>::remove(lane_id);
+	} else {
+		OutboundLaneFeeFactors::<T, I>::insert(lane_id, fee_factor);
+	}
+
+	fee_factor
+}
+
 /// Ensure that the pallet is in normal operational mode.
 fn ensure_normal_operating_mode<T: Config<I>, I: 'static>() -> Result<(), Error<T, I>> {
 	if PalletOperatingMode::<T, I>::get() ==
@@ -749,6 +1909,28 @@ fn ensure_normal_operating_mode<T: Config<I>, I: 'static>() -> Result<(), Error<
 	Err(Error::<T, I>::NotOperatingNormally)
 }
 
+/// Ensure that the lane is in normal operational mode.
+fn ensure_normal_lane_operating_mode<T: Config<I>, I: 'static>(
+	lane_id: LaneId,
+) -> Result<(), Error<T, I>> {
+	if LaneOperatingModes::<T, I>::get(lane_id) ==
+		MessagesOperatingMode::Basic(BasicOperatingMode::Normal)
+	{
+		return Ok(())
+	}
+
+	Err(Error::<T, I>::LaneNotOperatingNormally)
+}
+
+/// Ensure that the lane is not halted.
+fn ensure_lane_not_halted<T: Config<I>, I: 'static>(lane_id: LaneId) -> Result<(), Error<T, I>> {
+	if LaneOperatingModes::<T, I>::get(lane_id).is_halted() {
+		return Err(Error::<T, I>::LaneIsHalted)
+	}
+
+	Ok(())
+}
+
 /// Creates new inbound lane object, backed by runtime storage.
 fn inbound_lane<T: Config<I>, I: 'static>(
 	lane_id: LaneId,
@@ -818,6 +2000,33 @@ impl<T: Config<I>, I: 'static> InboundLaneStorage for RuntimeInboundLaneStorage<
 		) = Some(data.clone());
 		InboundLanes::<T, I>::insert(self.lane_id, StoredInboundLaneData::<T, I>(data))
 	}
+
+	fn is_unordered(&self) -> bool {
+		T::UnorderedInboundLanes::get().contains(&self.lane_id)
+	}
+
+	fn delivered_ahead(&self) -> u128 {
+		InboundLanesDeliveredAhead::<T, I>::get(self.lane_id)
+	}
+
+	fn set_delivered_ahead(&mut self, delivered_ahead: u128) {
+		InboundLanesDeliveredAhead::<T, I>::insert(self.lane_id, delivered_ahead)
+	}
+
+	fn delivered_ahead_relayer(&self, nonce: MessageNonce) -> Option<T::InboundRelayer> {
+		InboundLanesDeliveredAheadRelayers::<T, I>::get(MessageKey { lane_id: self.lane_id, nonce })
+	}
+
+	fn set_delivered_ahead_relayer(
+		&mut self,
+		nonce: MessageNonce,
+		relayer: Option<T::InboundRelayer>,
+	) {
+		InboundLanesDeliveredAheadRelayers::<T, I>::set(
+			MessageKey { lane_id: self.lane_id, nonce },
+			relayer,
+		)
+	}
 }
 
 /// Runtime outbound lane storage.
@@ -857,45 +2066,41 @@ impl<T: Config<I>, I: 'static> OutboundLaneStorage for RuntimeOutboundLaneStorag
 	}
 
 	fn remove_message(&mut self, nonce: &MessageNonce) {
-		OutboundMessages::<T, I>::remove(MessageKey { lane_id: self.lane_id, nonce: *nonce });
+		let key = MessageKey { lane_id: self.lane_id, nonce: *nonce };
+		OutboundMessages::<T, I>::remove(&key);
+		OutboundMessageSenders::<T, I>::remove(&key);
 	}
 }
 
-/// Verify messages proof and return proved messages with decoded payload.
-fn verify_and_decode_messages_proof<Chain: SourceHeaderChain, DispatchPayload: Decode>(
+/// Verify messages proof and return proved messages.
+fn verify_messages_proof<Chain: SourceHeaderChain>(
 	proof: Chain::MessagesProof,
 	messages_count: u32,
-) -> Result<ProvedMessages<DispatchMessage<DispatchPayload>>, Chain::Error> {
+) -> Result<ProvedMessages<Message>, Chain::Error> {
 	// `receive_messages_proof` weight formula and `MaxUnconfirmedMessagesAtInboundLane` check
 	// guarantees that the `message_count` is sane and Vec<Message> may be allocated.
 	// (tx with too many messages will either be rejected from the pool, or will fail earlier)
-	Chain::verify_messages_proof(proof, messages_count).map(|messages_by_lane| {
-		messages_by_lane
-			.into_iter()
-			.map(|(lane, lane_data)| {
-				(
-					lane,
-					ProvedLaneMessages {
-						lane_state: lane_data.lane_state,
-						messages: lane_data.messages.into_iter().map(Into::into).collect(),
-					},
-				)
-			})
-			.collect()
-	})
+	Chain::verify_messages_proof(proof, messages_count)
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate::mock::{
-		message, message_payload, run_test, unrewarded_relayer, AccountId, DbWeight,
-		RuntimeEvent as TestEvent, RuntimeOrigin, TestDeliveryConfirmationPayments,
-		TestDeliveryPayments, TestMessagesDeliveryProof, TestMessagesProof, TestRuntime,
-		MAX_OUTBOUND_PAYLOAD_SIZE, PAYLOAD_REJECTED_BY_TARGET_CHAIN, REGULAR_PAYLOAD, TEST_LANE_ID,
-		TEST_LANE_ID_2, TEST_LANE_ID_3, TEST_RELAYER_A, TEST_RELAYER_B,
+		dispatch_result, message, message_payload, run_test, unrewarded_relayer, AccountId,
+		DbWeight, MaxImmediateDispatchWeight, MaxInboundLanesToPrunePerBlock,
+		MaxParkedMessagesPerLane, MessageTtl, ProvedDispatchResults, RuntimeEvent as TestEvent,
+		RuntimeOrigin, TestDeliveryConfirmationPayments, TestDeliveryFeeCalculator,
+		TestDeliveryPayments, TestLaneOwner, TestMessagesDeliveryProof, TestMessagesProof,
+		TestOnDeliveryConfirmed, TestOnMessageCancelled, TestOnMessageExpired, TestRuntime,
+		ACCOUNT_WITHOUT_FUNDS, MAX_OUTBOUND_PAYLOAD_SIZE, PAYLOAD_REJECTED_BY_TARGET_CHAIN,
+		REGULAR_PAYLOAD, TEST_LANE_ID, TEST_LANE_ID_2, TEST_LANE_ID_3, TEST_RELAYER_A,
+		TEST_RELAYER_B,
+	};
+	use bp_messages::{
+		BridgeMessagesCall, DeliveredMessageDispatchResult, Message, ReceivedMessages,
+		UnrewardedRelayer, UnrewardedRelayersState,
 	};
-	use bp_messages::{BridgeMessagesCall, UnrewardedRelayer, UnrewardedRelayersState};
 	use bp_test_utils::generate_owned_bridge_module_tests;
 	use frame_support::{
 		assert_noop, assert_ok,
@@ -1099,721 +2304,2207 @@ mod tests {
 	}
 
 	#[test]
-	fn send_message_works() {
+	fn lane_operating_mode_may_be_changed_by_root_owner_or_lane_owner() {
 		run_test(|| {
-			send_regular_message();
-		});
-	}
+			get_ready_for_events();
+			let halted = MessagesOperatingMode::Basic(BasicOperatingMode::Halted);
 
-	#[test]
-	fn send_message_rejects_too_large_message() {
-		run_test(|| {
-			let mut message_payload = message_payload(1, 0);
-			// the payload isn't simply extra, so it'll definitely overflow
-			// `MAX_OUTBOUND_PAYLOAD_SIZE` if we add `MAX_OUTBOUND_PAYLOAD_SIZE` bytes to extra
-			message_payload
-				.extra
-				.extend_from_slice(&[0u8; MAX_OUTBOUND_PAYLOAD_SIZE as usize]);
 			assert_noop!(
-				send_message::<TestRuntime, ()>(
+				Pallet::<TestRuntime>::set_lane_operating_mode(
 					RuntimeOrigin::signed(1),
 					TEST_LANE_ID,
-					message_payload.clone(),
+					halted,
 				),
-				Error::<TestRuntime, ()>::MessageIsTooLarge,
+				DispatchError::BadOrigin,
 			);
 
-			// let's check that we're able to send `MAX_OUTBOUND_PAYLOAD_SIZE` messages
-			while message_payload.encoded_size() as u32 > MAX_OUTBOUND_PAYLOAD_SIZE {
-				message_payload.extra.pop();
-			}
-			assert_eq!(message_payload.encoded_size() as u32, MAX_OUTBOUND_PAYLOAD_SIZE);
-			assert_ok!(send_message::<TestRuntime, ()>(
-				RuntimeOrigin::signed(1),
+			assert_ok!(Pallet::<TestRuntime>::set_lane_operating_mode(
+				RuntimeOrigin::signed(TestLaneOwner::get()),
 				TEST_LANE_ID,
-				message_payload,
-			),);
-		})
-	}
-
-	#[test]
-	fn chain_verifier_rejects_invalid_message_in_send_message() {
+				halted,
+			));
+			assert_eq!(Pallet::<TestRuntime>::lane_operating_mode(TEST_LANE_ID), halted);
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_operating_mode(TEST_LANE_ID_2),
+				MessagesOperatingMode::default(),
+			);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Messages(Event::LaneOperatingModeChanged {
+						lane_id: TEST_LANE_ID,
+						operating_mode: halted,
+					}),
+					topics: vec![],
+				}],
+			);
+
+			PalletOwner::<TestRuntime>::put(2);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_operating_mode(
+				RuntimeOrigin::signed(2),
+				TEST_LANE_ID_2,
+				halted,
+			));
+			assert_ok!(Pallet::<TestRuntime>::set_lane_operating_mode(
+				RuntimeOrigin::root(),
+				TEST_LANE_ID,
+				MessagesOperatingMode::default(),
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_operating_mode(TEST_LANE_ID),
+				MessagesOperatingMode::default(),
+			);
+			assert_eq!(Pallet::<TestRuntime>::lane_operating_mode(TEST_LANE_ID_2), halted);
+		});
+	}
+
+	#[test]
+	fn halted_lane_rejects_transactions() {
 		run_test(|| {
-			// messages with this payload are rejected by target chain verifier
+			// send message first to be able to check that delivery_proof fails later
+			send_regular_message();
+
+			LaneOperatingModes::<TestRuntime, ()>::insert(
+				TEST_LANE_ID,
+				MessagesOperatingMode::Basic(BasicOperatingMode::Halted),
+			);
+
 			assert_noop!(
 				send_message::<TestRuntime, ()>(
 					RuntimeOrigin::signed(1),
 					TEST_LANE_ID,
-					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
+					REGULAR_PAYLOAD,
 				),
-				Error::<TestRuntime, ()>::MessageRejectedByChainVerifier,
+				Error::<TestRuntime, ()>::LaneNotOperatingNormally,
 			);
-		});
-	}
 
-	#[test]
-	fn lane_verifier_rejects_invalid_message_in_send_message() {
-		run_test(|| {
-			// messages with zero fee are rejected by lane verifier
-			let mut message = REGULAR_PAYLOAD;
-			message.reject_by_lane_verifier = true;
 			assert_noop!(
-				send_message::<TestRuntime, ()>(RuntimeOrigin::signed(1), TEST_LANE_ID, message,),
-				Error::<TestRuntime, ()>::MessageRejectedByLaneVerifier,
+				Pallet::<TestRuntime>::receive_messages_proof(
+					RuntimeOrigin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					1,
+					REGULAR_PAYLOAD.declared_weight,
+				),
+				Error::<TestRuntime, ()>::LaneIsHalted,
 			);
-		});
-	}
 
-	#[test]
-	fn receive_messages_proof_works() {
-		run_test(|| {
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
-				RuntimeOrigin::signed(1),
-				TEST_RELAYER_A,
-				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
-				1,
-				REGULAR_PAYLOAD.declared_weight,
-			));
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					RuntimeOrigin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							last_confirmed_nonce: 1,
+							relayers: vec![unrewarded_relayer(1, 1, TEST_RELAYER_A)]
+								.into_iter()
+								.collect(),
+						},
+					))),
+					UnrewardedRelayersState {
+						unrewarded_relayer_entries: 1,
+						messages_in_oldest_entry: 1,
+						total_messages: 1,
+						last_delivered_nonce: 1,
+					},
+				),
+				Error::<TestRuntime, ()>::LaneIsHalted,
+			);
 
-			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).0.last_delivered_nonce(), 1);
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_message(RuntimeOrigin::signed(1), TEST_LANE_ID, 1),
+				Error::<TestRuntime, ()>::LaneIsHalted,
+			);
 
-			assert!(TestDeliveryPayments::is_reward_paid(1));
+			// other lanes are still operating normally
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID_2,
+				REGULAR_PAYLOAD,
+			));
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_updates_confirmed_message_nonce() {
+	fn lane_rejects_new_messages_in_rejecting_outbound_messages_operating_mode() {
 		run_test(|| {
-			// say we have received 10 messages && last confirmed message is 8
-			InboundLanes::<TestRuntime, ()>::insert(
+			LaneOperatingModes::<TestRuntime, ()>::insert(
 				TEST_LANE_ID,
-				InboundLaneData {
-					last_confirmed_nonce: 8,
-					relayers: vec![
-						unrewarded_relayer(9, 9, TEST_RELAYER_A),
-						unrewarded_relayer(10, 10, TEST_RELAYER_B),
-					]
-					.into_iter()
-					.collect(),
-				},
-			);
-			assert_eq!(
-				inbound_unrewarded_relayers_state(TEST_LANE_ID),
-				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 2,
-					messages_in_oldest_entry: 1,
-					total_messages: 2,
-					last_delivered_nonce: 10,
-				},
+				MessagesOperatingMode::RejectingOutboundMessages,
 			);
 
-			// message proof includes outbound lane state with latest confirmed message updated to 9
-			let mut message_proof: TestMessagesProof =
-				Ok(vec![message(11, REGULAR_PAYLOAD)]).into();
-			message_proof.result.as_mut().unwrap()[0].1.lane_state =
-				Some(OutboundLaneData { latest_received_nonce: 9, ..Default::default() });
+			assert_noop!(
+				send_message::<TestRuntime, ()>(
+					RuntimeOrigin::signed(1),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+				),
+				Error::<TestRuntime, ()>::LaneNotOperatingNormally,
+			);
 
 			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
 				RuntimeOrigin::signed(1),
 				TEST_RELAYER_A,
-				message_proof,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
 				1,
 				REGULAR_PAYLOAD.declared_weight,
 			));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1);
+		});
+	}
 
-			assert_eq!(
-				InboundLanes::<TestRuntime>::get(TEST_LANE_ID).0,
-				InboundLaneData {
-					last_confirmed_nonce: 9,
-					relayers: vec![
-						unrewarded_relayer(10, 10, TEST_RELAYER_B),
-						unrewarded_relayer(11, 11, TEST_RELAYER_A)
-					]
-					.into_iter()
-					.collect(),
-				},
-			);
-			assert_eq!(
-				inbound_unrewarded_relayers_state(TEST_LANE_ID),
-				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 2,
-					messages_in_oldest_entry: 1,
-					total_messages: 2,
-					last_delivered_nonce: 11,
-				},
-			);
+	#[test]
+	fn send_message_works() {
+		run_test(|| {
+			send_regular_message();
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_does_not_accept_message_if_dispatch_weight_is_not_enough() {
+	fn send_message_rejects_too_large_message() {
 		run_test(|| {
-			let mut declared_weight = REGULAR_PAYLOAD.declared_weight;
-			*declared_weight.ref_time_mut() -= 1;
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+			let mut message_payload = message_payload(1, 0);
+			// the payload isn't simply extra, so it'll definitely overflow
+			// `MAX_OUTBOUND_PAYLOAD_SIZE` if we add `MAX_OUTBOUND_PAYLOAD_SIZE` bytes to extra
+			message_payload
+				.extra
+				.extend_from_slice(&[0u8; MAX_OUTBOUND_PAYLOAD_SIZE as usize]);
+			assert_noop!(
+				send_message::<TestRuntime, ()>(
+					RuntimeOrigin::signed(1),
+					TEST_LANE_ID,
+					message_payload.clone(),
+				),
+				Error::<TestRuntime, ()>::MessageIsTooLarge,
+			);
+
+			// let's check that we're able to send `MAX_OUTBOUND_PAYLOAD_SIZE` messages
+			while message_payload.encoded_size() as u32 > MAX_OUTBOUND_PAYLOAD_SIZE {
+				message_payload.extra.pop();
+			}
+			assert_eq!(message_payload.encoded_size() as u32, MAX_OUTBOUND_PAYLOAD_SIZE);
+			assert_ok!(send_message::<TestRuntime, ()>(
 				RuntimeOrigin::signed(1),
-				TEST_RELAYER_A,
-				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
-				1,
-				declared_weight,
-			));
-			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 0);
-		});
+				TEST_LANE_ID,
+				message_payload,
+			),);
+		})
 	}
 
 	#[test]
-	fn receive_messages_proof_rejects_invalid_proof() {
+	fn send_message_respects_lane_messages_quota() {
 		run_test(|| {
+			for _ in 0..4 {
+				send_regular_message();
+			}
 			assert_noop!(
-				Pallet::<TestRuntime, ()>::receive_messages_proof(
+				send_message::<TestRuntime, ()>(
 					RuntimeOrigin::signed(1),
-					TEST_RELAYER_A,
-					Err(()).into(),
-					1,
-					Weight::from_ref_time(0),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
 				),
-				Error::<TestRuntime, ()>::InvalidMessagesProof,
+				Error::<TestRuntime, ()>::LaneQuotaExceeded,
 			);
+
+			// other lanes are not affected
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID_2,
+				REGULAR_PAYLOAD,
+			));
+
+			// and the quota is restored at next block
+			System::<TestRuntime>::set_block_number(2);
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
 		});
 	}
 
 	#[test]
-	fn receive_messages_proof_rejects_proof_with_too_many_messages() {
+	fn send_message_respects_lane_bytes_quota() {
 		run_test(|| {
+			let mut message_payload = message_payload(1, 0);
+			message_payload
+				.extra
+				.extend_from_slice(&[0u8; MAX_OUTBOUND_PAYLOAD_SIZE as usize]);
+			while message_payload.encoded_size() as u32 > MAX_OUTBOUND_PAYLOAD_SIZE {
+				message_payload.extra.pop();
+			}
+
+			// two largest messages fill the quota
+			for _ in 0..2 {
+				assert_ok!(send_message::<TestRuntime, ()>(
+					RuntimeOrigin::signed(1),
+					TEST_LANE_ID,
+					message_payload.clone(),
+				));
+			}
 			assert_noop!(
-				Pallet::<TestRuntime, ()>::receive_messages_proof(
+				send_message::<TestRuntime, ()>(
 					RuntimeOrigin::signed(1),
-					TEST_RELAYER_A,
-					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
-					u32::MAX,
-					Weight::from_ref_time(0),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
 				),
-				Error::<TestRuntime, ()>::TooManyMessagesInTheProof,
+				Error::<TestRuntime, ()>::LaneQuotaExceeded,
 			);
+
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
 		});
 	}
 
 	#[test]
-	fn receive_messages_delivery_proof_works() {
+	fn send_message_pays_delivery_fee() {
 		run_test(|| {
 			send_regular_message();
-			receive_messages_delivery_proof();
-
 			assert_eq!(
-				OutboundLanes::<TestRuntime, ()>::get(TEST_LANE_ID).latest_received_nonce,
-				1,
+				TestDeliveryFeeCalculator::last_delivery_fee(TEST_LANE_ID),
+				Some((REGULAR_PAYLOAD.encoded_size() as u32, FixedU128::one())),
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_delivery_proof_rewards_relayers() {
+	fn send_message_rejects_message_if_sender_fails_to_pay_delivery_fee() {
 		run_test(|| {
-			assert_ok!(send_message::<TestRuntime, ()>(
-				RuntimeOrigin::signed(1),
-				TEST_LANE_ID,
-				REGULAR_PAYLOAD,
-			));
-			assert_ok!(send_message::<TestRuntime, ()>(
-				RuntimeOrigin::signed(1),
-				TEST_LANE_ID,
-				REGULAR_PAYLOAD,
-			));
-
-			// this reports delivery of message 1 => reward is paid to TEST_RELAYER_A
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+			assert_noop!(
+				send_message::<TestRuntime, ()>(
+					RuntimeOrigin::signed(ACCOUNT_WITHOUT_FUNDS),
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD,
+				),
+				Error::<TestRuntime, ()>::FailedToWithdrawMessageFee,
+			);
+		});
+	}
+
+	#[test]
+	fn outbound_lane_fee_factor_grows_when_lane_is_congested() {
+		run_test(|| {
+			let mut message_payload = message_payload(1, 0);
+			message_payload
+				.extra
+				.extend_from_slice(&[0u8; MAX_OUTBOUND_PAYLOAD_SIZE as usize]);
+			while message_payload.encoded_size() as u32 > MAX_OUTBOUND_PAYLOAD_SIZE {
+				message_payload.extra.pop();
+			}
+
+			// two largest messages are twice above the target lane usage => fee factor grows by
+			// 1/8 at every next block
+			let mut expected_fee_factor = FixedU128::one();
+			for block_number in 0..2 {
+				System::<TestRuntime>::set_block_number(block_number);
+				for _ in 0..2 {
+					assert_ok!(send_message::<TestRuntime, ()>(
+						RuntimeOrigin::signed(1),
+						TEST_LANE_ID,
+						message_payload.clone(),
+					));
+					assert_eq!(
+						TestDeliveryFeeCalculator::last_delivery_fee(TEST_LANE_ID),
+						Some((MAX_OUTBOUND_PAYLOAD_SIZE, expected_fee_factor)),
+					);
+				}
+				expected_fee_factor =
+					expected_fee_factor.saturating_mul(FixedU128::saturating_from_rational(9, 8));
+			}
+			System::<TestRuntime>::set_block_number(2);
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::outbound_lane_fee_factor(TEST_LANE_ID),
+				Some(expected_fee_factor),
+			);
+
+			// the lane has been used below target => fee factor decreases
+			System::<TestRuntime>::set_block_number(3);
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
+			let fee_factor = Pallet::<TestRuntime>::outbound_lane_fee_factor(TEST_LANE_ID).unwrap();
+			assert!(fee_factor > FixedU128::one());
+			assert!(fee_factor < expected_fee_factor);
+
+			// the lane has not been used for several blocks => fee factor drops to one
+			System::<TestRuntime>::set_block_number(10);
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
+			assert_eq!(
+				TestDeliveryFeeCalculator::last_delivery_fee(TEST_LANE_ID),
+				Some((REGULAR_PAYLOAD.encoded_size() as u32, FixedU128::one())),
+			);
+			assert_eq!(Pallet::<TestRuntime>::outbound_lane_fee_factor(TEST_LANE_ID), None);
+		});
+	}
+
+	#[test]
+	fn chain_verifier_rejects_invalid_message_in_send_message() {
+		run_test(|| {
+			// messages with this payload are rejected by target chain verifier
+			assert_noop!(
+				send_message::<TestRuntime, ()>(
+					RuntimeOrigin::signed(1),
+					TEST_LANE_ID,
+					PAYLOAD_REJECTED_BY_TARGET_CHAIN,
+				),
+				Error::<TestRuntime, ()>::MessageRejectedByChainVerifier,
+			);
+		});
+	}
+
+	#[test]
+	fn lane_verifier_rejects_invalid_message_in_send_message() {
+		run_test(|| {
+			// messages with zero fee are rejected by lane verifier
+			let mut message = REGULAR_PAYLOAD;
+			message.reject_by_lane_verifier = true;
+			assert_noop!(
+				send_message::<TestRuntime, ()>(RuntimeOrigin::signed(1), TEST_LANE_ID, message,),
+				Error::<TestRuntime, ()>::MessageRejectedByLaneVerifier,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_works() {
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.declared_weight,
+			));
+
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).0.last_delivered_nonce(), 1);
+
+			assert!(TestDeliveryPayments::is_reward_paid(1));
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_stores_dispatch_results() {
+		run_test(|| {
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_message_delivery_proof_keys(TEST_LANE_ID, 1),
+				None,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				REGULAR_PAYLOAD.declared_weight,
+			));
+
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 1),
+				Some(REGULAR_PAYLOAD.dispatch_result),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_message_delivery_proof_keys(TEST_LANE_ID, 1),
+				Some(InboundMessageDeliveryProofKeys {
+					dispatch_result_key:
+						bp_messages::storage_keys::inbound_message_dispatch_result_key(
+							"Messages",
+							&TEST_LANE_ID,
+							1
+						),
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn only_latest_dispatch_results_are_stored() {
+		run_test(|| {
+			store_dispatch_result::<TestRuntime, ()>(TEST_LANE_ID, 1, dispatch_result(0));
+			store_dispatch_result::<TestRuntime, ()>(
+				TEST_LANE_ID,
+				MAX_STORED_DISPATCH_RESULTS,
+				dispatch_result(0),
+			);
+			assert!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 1).is_some()
+			);
+
+			store_dispatch_result::<TestRuntime, ()>(
+				TEST_LANE_ID,
+				MAX_STORED_DISPATCH_RESULTS + 1,
+				dispatch_result(0),
+			);
+			assert!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 1).is_none()
+			);
+			assert!(Pallet::<TestRuntime>::inbound_message_dispatch_result(
+				TEST_LANE_ID,
+				MAX_STORED_DISPATCH_RESULTS + 1
+			)
+			.is_some());
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_updates_confirmed_message_nonce() {
+		run_test(|| {
+			// say we have received 10 messages && last confirmed message is 8
+			InboundLanes::<TestRuntime, ()>::insert(
+				TEST_LANE_ID,
+				InboundLaneData {
+					last_confirmed_nonce: 8,
+					relayers: vec![
+						unrewarded_relayer(9, 9, TEST_RELAYER_A),
+						unrewarded_relayer(10, 10, TEST_RELAYER_B),
+					]
+					.into_iter()
+					.collect(),
+				},
+			);
+			assert_eq!(
+				inbound_unrewarded_relayers_state(TEST_LANE_ID),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					messages_in_oldest_entry: 1,
+					total_messages: 2,
+					last_delivered_nonce: 10,
+				},
+			);
+
+			// message proof includes outbound lane state with latest confirmed message updated to 9
+			let mut message_proof: TestMessagesProof =
+				Ok(vec![message(11, REGULAR_PAYLOAD)]).into();
+			message_proof.result.as_mut().unwrap()[0].1.lane_state =
+				Some(OutboundLaneData { latest_received_nonce: 9, ..Default::default() });
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				message_proof,
+				1,
+				REGULAR_PAYLOAD.declared_weight,
+			));
+
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(TEST_LANE_ID).0,
+				InboundLaneData {
+					last_confirmed_nonce: 9,
+					relayers: vec![
+						unrewarded_relayer(10, 10, TEST_RELAYER_B),
+						unrewarded_relayer(11, 11, TEST_RELAYER_A)
+					]
+					.into_iter()
+					.collect(),
+				},
+			);
+			assert_eq!(
+				inbound_unrewarded_relayers_state(TEST_LANE_ID),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					messages_in_oldest_entry: 1,
+					total_messages: 2,
+					last_delivered_nonce: 11,
+				},
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_does_not_accept_message_if_dispatch_weight_is_not_enough() {
+		run_test(|| {
+			let mut declared_weight = REGULAR_PAYLOAD.declared_weight;
+			*declared_weight.ref_time_mut() -= 1;
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				declared_weight,
+			));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 0);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_skips_heavy_message_at_unordered_lane() {
+		run_test(|| {
+			let mut heavy_payload = REGULAR_PAYLOAD;
+			*heavy_payload.declared_weight.ref_time_mut() += 1;
+			let messages = vec![
+				Message {
+					key: MessageKey { lane_id: TEST_LANE_ID_3, nonce: 1 },
+					payload: heavy_payload.encode(),
+				},
+				Message {
+					key: MessageKey { lane_id: TEST_LANE_ID_3, nonce: 2 },
+					payload: REGULAR_PAYLOAD.encode(),
+				},
+			];
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(messages).into(),
+				2,
+				REGULAR_PAYLOAD.declared_weight,
+			));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID_3).last_delivered_nonce(), 0);
+			assert_eq!(InboundLanesDeliveredAhead::<TestRuntime>::get(TEST_LANE_ID_3), 0b10);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_parks_heavy_messages() {
+		run_test(|| {
+			get_ready_for_events();
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+
+			// relayer doesn't need to declare dispatch weight of parked messages
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				Weight::zero(),
+			));
+
+			let key = MessageKey { lane_id: TEST_LANE_ID, nonce: 1 };
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1);
+			assert_eq!(ParkedMessagesQueue::<TestRuntime>::get().into_inner(), vec![key.clone()]);
+			assert_eq!(LaneParkedMessages::<TestRuntime>::get(TEST_LANE_ID), 1);
+			assert_eq!(
+				ParkedMessages::<TestRuntime>::get(&key),
+				Some(ParkedMessage { relayer: 1, payload: REGULAR_PAYLOAD.encode() }),
+			);
+			assert!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 1).is_none()
+			);
+			assert_eq!(
+				System::<TestRuntime>::events().last().map(|r| r.event.clone()),
+				Some(TestEvent::Messages(Event::MessagesReceived(vec![ReceivedMessages {
+					lane: TEST_LANE_ID,
+					receive_results: vec![(1, ReceivalResult::Parked)],
+					skipped_for_not_enough_weight: vec![],
+				}]))),
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_dispatches_heavy_messages_if_parked_messages_queue_is_full() {
+		run_test(|| {
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![
+					message(1, REGULAR_PAYLOAD),
+					message(2, REGULAR_PAYLOAD),
+					message(3, REGULAR_PAYLOAD)
+				])
+				.into(),
+				3,
+				REGULAR_PAYLOAD.declared_weight,
+			));
+
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 3);
+			assert_eq!(
+				ParkedMessagesQueue::<TestRuntime>::get().into_inner(),
+				vec![
+					MessageKey { lane_id: TEST_LANE_ID, nonce: 1 },
+					MessageKey { lane_id: TEST_LANE_ID, nonce: 2 },
+				],
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 3),
+				Some(REGULAR_PAYLOAD.dispatch_result),
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_dispatches_heavy_messages_if_lane_has_too_many_parked_messages() {
+		run_test(|| {
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+			MaxParkedMessagesPerLane::set(&1);
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.declared_weight,
+			));
+			assert_eq!(
+				ParkedMessagesQueue::<TestRuntime>::get().into_inner(),
+				vec![MessageKey { lane_id: TEST_LANE_ID, nonce: 1 }],
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 2),
+				Some(REGULAR_PAYLOAD.dispatch_result),
+			);
+
+			// messages of other lanes may still be parked
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![Message {
+					key: MessageKey { lane_id: TEST_LANE_ID_2, nonce: 1 },
+					payload: REGULAR_PAYLOAD.encode(),
+				}])
+				.into(),
+				1,
+				Weight::zero(),
+			));
+			assert_eq!(ParkedMessagesQueue::<TestRuntime>::decode_len(), Some(2));
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_does_not_park_messages_that_can_not_be_executed() {
+		run_test(|| {
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+
+			let max_extrinsic_weight = <TestRuntime as frame_system::Config>::BlockWeights::get()
+				.get(DispatchClass::Normal)
+				.max_extrinsic
+				.unwrap();
+			let payload = message_payload(0, max_extrinsic_weight.ref_time());
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, payload)]).into(),
+				1,
+				Weight::zero(),
+			));
+
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 0);
+			assert!(ParkedMessagesQueue::<TestRuntime>::get().is_empty());
+			assert!(!LaneParkedMessages::<TestRuntime>::contains_key(TEST_LANE_ID));
+		});
+	}
+
+	#[test]
+	fn execute_parked_message_works() {
+		run_test(|| {
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+				1,
+				Weight::zero(),
+			));
+			get_ready_for_events();
+
+			// weight limit must cover message dispatch weight
+			let mut weight_limit = REGULAR_PAYLOAD.declared_weight;
+			*weight_limit.ref_time_mut() -= 1;
+			assert_noop!(
+				Pallet::<TestRuntime>::execute_parked_message(
+					RuntimeOrigin::signed(2),
+					TEST_LANE_ID,
+					1,
+					weight_limit,
+				),
+				Error::<TestRuntime, ()>::InsufficientDispatchWeight,
+			);
+
+			let post_info = Pallet::<TestRuntime>::execute_parked_message(
+				RuntimeOrigin::signed(2),
+				TEST_LANE_ID,
+				1,
+				Weight::from_ref_time(1_000),
+			)
+			.unwrap();
+			assert_eq!(
+				post_info.actual_weight,
+				Some(
+					DbWeight::get().reads(1) +
+						parked_message_overhead::<TestRuntime, ()>() +
+						REGULAR_PAYLOAD.declared_weight
+				),
+			);
+
+			let key = MessageKey { lane_id: TEST_LANE_ID, nonce: 1 };
+			assert!(ParkedMessagesQueue::<TestRuntime>::get().is_empty());
+			assert!(ParkedMessages::<TestRuntime>::get(&key).is_none());
+			assert!(!LaneParkedMessages::<TestRuntime>::contains_key(TEST_LANE_ID));
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 1),
+				Some(REGULAR_PAYLOAD.dispatch_result),
+			);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Messages(Event::ParkedMessageDispatched {
+						lane_id: TEST_LANE_ID,
+						nonce: 1,
+						dispatch_result: REGULAR_PAYLOAD.dispatch_result,
+					}),
+					topics: vec![],
+				}],
+			);
+
+			// message can't be dispatched twice
+			assert_noop!(
+				Pallet::<TestRuntime>::execute_parked_message(
+					RuntimeOrigin::signed(2),
+					TEST_LANE_ID,
+					1,
+					Weight::from_ref_time(1_000),
+				),
+				Error::<TestRuntime, ()>::ParkedMessageNotFound,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_invalid_proof() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime, ()>::receive_messages_proof(
+					RuntimeOrigin::signed(1),
+					TEST_RELAYER_A,
+					Err(()).into(),
+					1,
+					Weight::from_ref_time(0),
+				),
+				Error::<TestRuntime, ()>::InvalidMessagesProof,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_rejects_proof_with_too_many_messages() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime, ()>::receive_messages_proof(
+					RuntimeOrigin::signed(1),
+					TEST_RELAYER_A,
+					Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
+					u32::MAX,
+					Weight::from_ref_time(0),
+				),
+				Error::<TestRuntime, ()>::TooManyMessagesInTheProof,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_proof_charges_additional_lanes_from_declared_dispatch_weight() {
+		run_test(|| {
+			let messages = || -> TestMessagesProof {
+				let mut lane2_message = message(1, REGULAR_PAYLOAD);
+				lane2_message.key.lane_id = TEST_LANE_ID_2;
+				Ok(vec![message(1, REGULAR_PAYLOAD), lane2_message]).into()
+			};
+			let additional_lane_weight =
+				<() as WeightInfoExt>::receive_messages_proof_outbound_lane_state_overhead()
+					.saturating_add(DbWeight::get().reads_writes(1, 1));
+
+			// declared dispatch weight doesn't cover the additional lane
+			assert_noop!(
+				Pallet::<TestRuntime, ()>::receive_messages_proof(
+					RuntimeOrigin::signed(1),
+					TEST_RELAYER_A,
+					messages(),
+					2,
+					additional_lane_weight.saturating_sub(Weight::from_ref_time(1)),
+				),
+				Error::<TestRuntime, ()>::InsufficientDispatchWeight,
+			);
+
+			// declared dispatch weight covers the additional lane and dispatch of both messages
+			assert_ok!(Pallet::<TestRuntime, ()>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				messages(),
+				2,
+				REGULAR_PAYLOAD
+					.declared_weight
+					.saturating_mul(2)
+					.saturating_add(additional_lane_weight),
+			));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).0.last_delivered_nonce(), 1);
+			assert_eq!(
+				InboundLanes::<TestRuntime>::get(TEST_LANE_ID_2).0.last_delivered_nonce(),
+				1
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_works() {
+		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+
+			assert_eq!(
+				OutboundLanes::<TestRuntime, ()>::get(TEST_LANE_ID).latest_received_nonce,
+				1,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_reports_dispatch_results() {
+		run_test(|| {
+			send_regular_message();
+			send_regular_message();
+			receive_messages_delivery_proof();
+			assert_eq!(
+				TestOnDeliveryConfirmed::delivery_confirmed(TEST_LANE_ID),
+				Some((DeliveredMessages::new(1), vec![])),
+			);
+
+			// dispatch result of already confirmed message 1 is not reported again
+			let dispatch_result = |nonce, unspent_weight| DeliveredMessageDispatchResult {
+				nonce,
+				unspent_weight: Weight::from_ref_time(unspent_weight),
+				encoded_dispatch_level_result: Vec::new(),
+			};
+			ProvedDispatchResults::set(&vec![dispatch_result(1, 10), dispatch_result(2, 20)]);
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				RuntimeOrigin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						last_confirmed_nonce: 1,
+						relayers: vec![unrewarded_relayer(2, 2, TEST_RELAYER_A)]
+							.into_iter()
+							.collect(),
+					},
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 1,
+					last_delivered_nonce: 2,
+					..Default::default()
+				},
+			));
+			assert_eq!(
+				TestOnDeliveryConfirmed::delivery_confirmed(TEST_LANE_ID),
+				Some((DeliveredMessages::new(2), vec![dispatch_result(2, 20)])),
+			);
+
+			// nothing is reported if there are no new confirmations
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				RuntimeOrigin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						last_confirmed_nonce: 1,
+						relayers: vec![unrewarded_relayer(2, 2, TEST_RELAYER_A)]
+							.into_iter()
+							.collect(),
+					},
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 1,
+					last_delivered_nonce: 2,
+					..Default::default()
+				},
+			));
+			assert_eq!(TestOnDeliveryConfirmed::delivery_confirmed(TEST_LANE_ID), None);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_rewards_relayers() {
+		run_test(|| {
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
+
+			// this reports delivery of message 1 => reward is paid to TEST_RELAYER_A
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				RuntimeOrigin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![unrewarded_relayer(1, 1, TEST_RELAYER_A)]
+							.into_iter()
+							.collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 1,
+					last_delivered_nonce: 1,
+					..Default::default()
+				},
+			));
+			assert!(TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_A, 1));
+			assert!(!TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_B, 1));
+
+			// this reports delivery of both message 1 and message 2 => reward is paid only to
+			// TEST_RELAYER_B
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				RuntimeOrigin::signed(1),
+				TestMessagesDeliveryProof(Ok((
+					TEST_LANE_ID,
+					InboundLaneData {
+						relayers: vec![
+							unrewarded_relayer(1, 1, TEST_RELAYER_A),
+							unrewarded_relayer(2, 2, TEST_RELAYER_B)
+						]
+						.into_iter()
+						.collect(),
+						..Default::default()
+					}
+				))),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 2,
+					total_messages: 2,
+					last_delivered_nonce: 2,
+					..Default::default()
+				},
+			));
+			assert!(!TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_A, 1));
+			assert!(TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_B, 1));
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_rejects_invalid_proof() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					RuntimeOrigin::signed(1),
+					TestMessagesDeliveryProof(Err(())),
+					Default::default(),
+				),
+				Error::<TestRuntime, ()>::InvalidMessagesDeliveryProof,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_rejects_proof_if_declared_relayers_state_is_invalid() {
+		run_test(|| {
+			// when number of relayers entries is invalid
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					RuntimeOrigin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							relayers: vec![
+								unrewarded_relayer(1, 1, TEST_RELAYER_A),
+								unrewarded_relayer(2, 2, TEST_RELAYER_B)
+							]
+							.into_iter()
+							.collect(),
+							..Default::default()
+						}
+					))),
+					UnrewardedRelayersState {
+						unrewarded_relayer_entries: 1,
+						total_messages: 2,
+						last_delivered_nonce: 2,
+						..Default::default()
+					},
+				),
+				Error::<TestRuntime, ()>::InvalidUnrewardedRelayersState,
+			);
+
+			// when number of messages is invalid
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					RuntimeOrigin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							relayers: vec![
+								unrewarded_relayer(1, 1, TEST_RELAYER_A),
+								unrewarded_relayer(2, 2, TEST_RELAYER_B)
+							]
+							.into_iter()
+							.collect(),
+							..Default::default()
+						}
+					))),
+					UnrewardedRelayersState {
+						unrewarded_relayer_entries: 2,
+						total_messages: 1,
+						last_delivered_nonce: 2,
+						..Default::default()
+					},
+				),
+				Error::<TestRuntime, ()>::InvalidUnrewardedRelayersState,
+			);
+
+			// when last delivered nonce is invalid
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					RuntimeOrigin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData {
+							relayers: vec![
+								unrewarded_relayer(1, 1, TEST_RELAYER_A),
+								unrewarded_relayer(2, 2, TEST_RELAYER_B)
+							]
+							.into_iter()
+							.collect(),
+							..Default::default()
+						}
+					))),
+					UnrewardedRelayersState {
+						unrewarded_relayer_entries: 2,
+						total_messages: 2,
+						last_delivered_nonce: 8,
+						..Default::default()
+					},
+				),
+				Error::<TestRuntime, ()>::InvalidUnrewardedRelayersState,
+			);
+		});
+	}
+
+	#[test]
+	fn receive_messages_accepts_single_message_with_invalid_payload() {
+		run_test(|| {
+			let mut invalid_message = message(1, REGULAR_PAYLOAD);
+			invalid_message.payload = Vec::new();
+
+			assert_ok!(Pallet::<TestRuntime, ()>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![invalid_message]).into(),
+				1,
+				Weight::from_ref_time(0), /* weight may be zero in this case (all messages are
+				                           * improperly encoded) */
+			),);
+
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1,);
+		});
+	}
+
+	#[test]
+	fn receive_messages_accepts_batch_with_message_with_invalid_payload() {
+		run_test(|| {
+			let mut invalid_message = message(2, REGULAR_PAYLOAD);
+			invalid_message.payload = Vec::new();
+
+			assert_ok!(Pallet::<TestRuntime, ()>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(
+					vec![message(1, REGULAR_PAYLOAD), invalid_message, message(3, REGULAR_PAYLOAD),]
+				)
+				.into(),
+				3,
+				REGULAR_PAYLOAD.declared_weight + REGULAR_PAYLOAD.declared_weight,
+			),);
+
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 3,);
+		});
+	}
+
+	#[test]
+	fn actual_dispatch_weight_does_not_overlow() {
+		run_test(|| {
+			let message1 = message(1, message_payload(0, u64::MAX / 2));
+			let message2 = message(2, message_payload(0, u64::MAX / 2));
+			let message3 = message(3, message_payload(0, u64::MAX / 2));
+
+			assert_ok!(Pallet::<TestRuntime, ()>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				// this may cause overflow if source chain storage is invalid
+				Ok(vec![message1, message2, message3]).into(),
+				3,
+				Weight::MAX,
+			));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 2);
+		});
+	}
+
+	#[test]
+	fn weight_refund_from_receive_messages_proof_works() {
+		run_test(|| {
+			fn submit_with_unspent_weight(
+				nonce: MessageNonce,
+				unspent_weight: u64,
+			) -> (Weight, Weight) {
+				let mut payload = REGULAR_PAYLOAD;
+				*payload.dispatch_result.unspent_weight.ref_time_mut() = unspent_weight;
+				let proof = Ok(vec![message(nonce, payload)]).into();
+				let messages_count = 1;
+				let pre_dispatch_weight = receive_messages_proof_weight::<TestRuntime, ()>(
+					&proof,
+					messages_count,
+					REGULAR_PAYLOAD.declared_weight,
+				);
+				let post_dispatch_weight = Pallet::<TestRuntime>::receive_messages_proof(
+					RuntimeOrigin::signed(1),
+					TEST_RELAYER_A,
+					proof,
+					messages_count,
+					REGULAR_PAYLOAD.declared_weight,
+				)
+				.expect("delivery has failed")
+				.actual_weight
+				.expect("receive_messages_proof always returns Some");
+
+				(pre_dispatch_weight, post_dispatch_weight)
+			}
+
+			// when dispatch is returning `unspent_weight < declared_weight`
+			let (pre, post) = submit_with_unspent_weight(1, 1);
+			assert_eq!(post.ref_time(), pre.ref_time() - 1);
+
+			// when dispatch is returning `unspent_weight = declared_weight`
+			let (pre, post) =
+				submit_with_unspent_weight(2, REGULAR_PAYLOAD.declared_weight.ref_time());
+			assert_eq!(
+				post.ref_time(),
+				pre.ref_time() - REGULAR_PAYLOAD.declared_weight.ref_time()
+			);
+
+			// when dispatch is returning `unspent_weight > declared_weight`
+			let (pre, post) =
+				submit_with_unspent_weight(3, REGULAR_PAYLOAD.declared_weight.ref_time() + 1);
+			assert_eq!(
+				post.ref_time(),
+				pre.ref_time() - REGULAR_PAYLOAD.declared_weight.ref_time()
+			);
+
+			// when there's no unspent weight
+			let (pre, post) = submit_with_unspent_weight(4, 0);
+			assert_eq!(post, pre);
+
+			// when dispatch is returning `unspent_weight < declared_weight`
+			let (pre, post) = submit_with_unspent_weight(5, 1);
+			assert_eq!(post.ref_time(), pre.ref_time() - 1);
+		});
+	}
+
+	#[test]
+	fn unused_declared_dispatch_weight_is_refunded() {
+		run_test(|| {
+			// relayer declares more dispatch weight than messages need
+			let declared_dispatch_weight =
+				REGULAR_PAYLOAD.declared_weight + REGULAR_PAYLOAD.declared_weight;
+			let proof = Ok(vec![message(1, REGULAR_PAYLOAD)]).into();
+			let pre_dispatch_weight = receive_messages_proof_weight::<TestRuntime, ()>(
+				&proof,
+				1,
+				declared_dispatch_weight,
+			);
+			let post_dispatch_weight = Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				proof,
+				1,
+				declared_dispatch_weight,
+			)
+			.expect("delivery has failed")
+			.actual_weight
+			.expect("receive_messages_proof always returns Some");
+			assert_eq!(post_dispatch_weight, pre_dispatch_weight - REGULAR_PAYLOAD.declared_weight);
+		});
+	}
+
+	#[test]
+	fn messages_delivered_callbacks_are_called() {
+		run_test(|| {
+			send_regular_message();
+			send_regular_message();
+			send_regular_message();
+
+			// messages 1+2 are confirmed in 1 tx, message 3 in a separate tx
+			// dispatch of message 2 has failed
+			let mut delivered_messages_1_and_2 = DeliveredMessages::new(1);
+			delivered_messages_1_and_2.note_dispatched_message();
+			let messages_1_and_2_proof = Ok((
+				TEST_LANE_ID,
+				InboundLaneData {
+					last_confirmed_nonce: 0,
+					relayers: vec![UnrewardedRelayer {
+						relayer: 0,
+						messages: delivered_messages_1_and_2.clone(),
+					}]
+					.into_iter()
+					.collect(),
+				},
+			));
+			let delivered_message_3 = DeliveredMessages::new(3);
+			let messages_3_proof = Ok((
+				TEST_LANE_ID,
+				InboundLaneData {
+					last_confirmed_nonce: 0,
+					relayers: vec![UnrewardedRelayer { relayer: 0, messages: delivered_message_3 }]
+						.into_iter()
+						.collect(),
+				},
+			));
+
+			// first tx with messages 1+2
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				RuntimeOrigin::signed(1),
+				TestMessagesDeliveryProof(messages_1_and_2_proof),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 2,
+					last_delivered_nonce: 2,
+					..Default::default()
+				},
+			));
+			// second tx with message 3
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+				RuntimeOrigin::signed(1),
+				TestMessagesDeliveryProof(messages_3_proof),
+				UnrewardedRelayersState {
+					unrewarded_relayer_entries: 1,
+					total_messages: 1,
+					last_delivered_nonce: 3,
+					..Default::default()
+				},
+			));
+		});
+	}
+
+	#[test]
+	fn receive_messages_delivery_proof_rejects_proof_if_trying_to_confirm_more_messages_than_expected(
+	) {
+		run_test(|| {
+			// send message first to be able to check that delivery_proof fails later
+			send_regular_message();
+
+			// 1) InboundLaneData declares that the `last_confirmed_nonce` is 1;
+			// 2) InboundLaneData has no entries => `InboundLaneData::last_delivered_nonce()`
+			//    returns `last_confirmed_nonce`;
+			// 3) it means that we're going to confirm delivery of messages 1..=1;
+			// 4) so the number of declared messages (see `UnrewardedRelayersState`) is `0` and
+			//    numer of actually confirmed messages is `1`.
+			assert_noop!(
+				Pallet::<TestRuntime>::receive_messages_delivery_proof(
+					RuntimeOrigin::signed(1),
+					TestMessagesDeliveryProof(Ok((
+						TEST_LANE_ID,
+						InboundLaneData { last_confirmed_nonce: 1, relayers: Default::default() },
+					))),
+					UnrewardedRelayersState { last_delivered_nonce: 1, ..Default::default() },
+				),
+				Error::<TestRuntime, ()>::TryingToConfirmMoreMessagesThanExpected,
+			);
+		});
+	}
+
+	#[test]
+	fn storage_keys_computed_properly() {
+		assert_eq!(
+			PalletOperatingMode::<TestRuntime>::storage_value_final_key().to_vec(),
+			bp_messages::storage_keys::operating_mode_key("Messages").0,
+		);
+
+		assert_eq!(
+			OutboundMessages::<TestRuntime>::storage_map_final_key(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 42
+			}),
+			bp_messages::storage_keys::message_key("Messages", &TEST_LANE_ID, 42).0,
+		);
+
+		assert_eq!(
+			OutboundLanes::<TestRuntime>::storage_map_final_key(TEST_LANE_ID),
+			bp_messages::storage_keys::outbound_lane_data_key("Messages", &TEST_LANE_ID).0,
+		);
+
+		assert_eq!(
+			InboundLanes::<TestRuntime>::storage_map_final_key(TEST_LANE_ID),
+			bp_messages::storage_keys::inbound_lane_data_key("Messages", &TEST_LANE_ID).0,
+		);
+
+		assert_eq!(
+			InboundMessageDispatchResults::<TestRuntime>::storage_map_final_key(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 42
+			}),
+			bp_messages::storage_keys::inbound_message_dispatch_result_key(
+				"Messages",
+				&TEST_LANE_ID,
+				42
+			)
+			.0,
+		);
+	}
+
+	#[test]
+	fn inbound_message_details_works() {
+		run_test(|| {
+			assert_eq!(
+				Pallet::<TestRuntime>::inbound_message_data(
+					TEST_LANE_ID,
+					REGULAR_PAYLOAD.encode(),
+					OutboundMessageDetails {
+						nonce: 0,
+						dispatch_weight: Weight::from_ref_time(0),
+						size: 0,
+					},
+				),
+				InboundMessageDetails { dispatch_weight: REGULAR_PAYLOAD.declared_weight },
+			);
+		});
+	}
+
+	#[test]
+	fn lane_diagnostics_works() {
+		run_test(|| {
+			send_regular_message();
+			send_regular_message();
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				REGULAR_PAYLOAD.declared_weight.saturating_mul(2),
+			));
+			LaneOperatingModes::<TestRuntime>::insert(
+				TEST_LANE_ID,
+				MessagesOperatingMode::RejectingOutboundMessages,
+			);
+
+			let outbound_diagnostics =
+				Pallet::<TestRuntime>::outbound_lane_diagnostics(TEST_LANE_ID);
+			assert_eq!(
+				outbound_diagnostics,
+				OutboundLaneDiagnostics {
+					lane_data: OutboundLaneData {
+						oldest_unpruned_nonce: 1,
+						latest_received_nonce: 0,
+						latest_generated_nonce: 2,
+					},
+					lane_state: LaneState::Opened,
+					operating_mode: MessagesOperatingMode::RejectingOutboundMessages,
+				},
+			);
+			assert_eq!(outbound_diagnostics.unconfirmed_nonces(), 1..=2);
+
+			let inbound_diagnostics = Pallet::<TestRuntime>::inbound_lane_diagnostics(TEST_LANE_ID);
+			assert_eq!(
+				inbound_diagnostics,
+				InboundLaneDiagnostics {
+					lane_data: InboundLaneData {
+						last_confirmed_nonce: 0,
+						relayers: vec![unrewarded_relayer(1, 2, TEST_RELAYER_A)]
+							.into_iter()
+							.collect(),
+					},
+					delivered_ahead: 0,
+					operating_mode: MessagesOperatingMode::RejectingOutboundMessages,
+				},
+			);
+			assert_eq!(inbound_diagnostics.unconfirmed_nonces(), 1..=2);
+			assert!(Pallet::<TestRuntime>::inbound_lane_diagnostics(TEST_LANE_ID_2)
+				.unconfirmed_nonces()
+				.is_empty());
+		});
+	}
+
+	#[test]
+	fn on_idle_callback_respects_remaining_weight() {
+		run_test(|| {
+			send_regular_message();
+			send_regular_message();
+			send_regular_message();
+			send_regular_message();
+
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
 				RuntimeOrigin::signed(1),
 				TestMessagesDeliveryProof(Ok((
 					TEST_LANE_ID,
 					InboundLaneData {
-						relayers: vec![unrewarded_relayer(1, 1, TEST_RELAYER_A)]
+						last_confirmed_nonce: 4,
+						relayers: vec![unrewarded_relayer(1, 4, TEST_RELAYER_A)]
 							.into_iter()
 							.collect(),
-						..Default::default()
-					}
+					},
 				))),
 				UnrewardedRelayersState {
 					unrewarded_relayer_entries: 1,
-					total_messages: 1,
-					last_delivered_nonce: 1,
-					..Default::default()
+					messages_in_oldest_entry: 4,
+					total_messages: 4,
+					last_delivered_nonce: 4,
 				},
 			));
-			assert!(TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_A, 1));
-			assert!(!TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_B, 1));
 
-			// this reports delivery of both message 1 and message 2 => reward is paid only to
-			// TEST_RELAYER_B
+			// all 4 messages may be pruned now
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().latest_received_nonce,
+				4
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				1
+			);
+			System::<TestRuntime>::set_block_number(2);
+
+			// if passed wight is too low to prune anything (we only read pallet operating mode and
+			// parked messages queue)
+			let dbw = DbWeight::get();
+			assert_eq!(Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(1, 1)), dbw.reads(2));
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				1
+			);
+
+			// if passed wight is enough to prune single message
+			assert_eq!(
+				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(2, 3)),
+				dbw.reads_writes(2, 3),
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				2
+			);
+
+			// if passed wight is enough to prune two more messages
+			assert_eq!(
+				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(2, 5)),
+				dbw.reads_writes(2, 5),
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				4
+			);
+
+			// if passed wight is enough to prune many messages
+			assert_eq!(
+				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100)),
+				dbw.reads_writes(4, 3),
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				5
+			);
+		});
+	}
+
+	#[test]
+	fn on_idle_callback_is_rotating_lanes_to_prune() {
+		run_test(|| {
+			// send + receive confirmation for lane 1
+			send_regular_message();
+			receive_messages_delivery_proof();
+			// send + receive confirmation for lane 2
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID_2,
+				REGULAR_PAYLOAD,
+			));
 			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
 				RuntimeOrigin::signed(1),
 				TestMessagesDeliveryProof(Ok((
-					TEST_LANE_ID,
+					TEST_LANE_ID_2,
 					InboundLaneData {
-						relayers: vec![
-							unrewarded_relayer(1, 1, TEST_RELAYER_A),
-							unrewarded_relayer(2, 2, TEST_RELAYER_B)
-						]
-						.into_iter()
-						.collect(),
-						..Default::default()
-					}
+						last_confirmed_nonce: 1,
+						relayers: vec![unrewarded_relayer(1, 1, TEST_RELAYER_A)]
+							.into_iter()
+							.collect(),
+					},
 				))),
 				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 2,
-					total_messages: 2,
-					last_delivered_nonce: 2,
-					..Default::default()
+					unrewarded_relayer_entries: 1,
+					messages_in_oldest_entry: 1,
+					total_messages: 1,
+					last_delivered_nonce: 1,
 				},
 			));
-			assert!(!TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_A, 1));
-			assert!(TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_B, 1));
-		});
-	}
 
-	#[test]
-	fn receive_messages_delivery_proof_rejects_invalid_proof() {
-		run_test(|| {
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					RuntimeOrigin::signed(1),
-					TestMessagesDeliveryProof(Err(())),
-					Default::default(),
-				),
-				Error::<TestRuntime, ()>::InvalidMessagesDeliveryProof,
+			// nothing is pruned yet
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().latest_received_nonce,
+				1
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				1
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().latest_received_nonce,
+				1
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().oldest_unpruned_nonce,
+				1
+			);
+
+			// in block#2.on_idle lane messages of lane 1 are pruned
+			let dbw = DbWeight::get();
+			System::<TestRuntime>::set_block_number(2);
+			assert_eq!(
+				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100)),
+				dbw.reads_writes(4, 3),
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				2
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().oldest_unpruned_nonce,
+				1
+			);
+
+			// in block#3.on_idle lane messages of lane 2 are pruned
+			System::<TestRuntime>::set_block_number(3);
+
+			assert_eq!(
+				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100)),
+				dbw.reads_writes(4, 3),
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
+				2
+			);
+			assert_eq!(
+				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().oldest_unpruned_nonce,
+				2
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_delivery_proof_rejects_proof_if_declared_relayers_state_is_invalid() {
+	fn on_idle_callback_dispatches_parked_messages() {
 		run_test(|| {
-			// when number of relayers entries is invalid
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					RuntimeOrigin::signed(1),
-					TestMessagesDeliveryProof(Ok((
-						TEST_LANE_ID,
-						InboundLaneData {
-							relayers: vec![
-								unrewarded_relayer(1, 1, TEST_RELAYER_A),
-								unrewarded_relayer(2, 2, TEST_RELAYER_B)
-							]
-							.into_iter()
-							.collect(),
-							..Default::default()
-						}
-					))),
-					UnrewardedRelayersState {
-						unrewarded_relayer_entries: 1,
-						total_messages: 2,
-						last_delivered_nonce: 2,
-						..Default::default()
-					},
-				),
-				Error::<TestRuntime, ()>::InvalidUnrewardedRelayersState,
-			);
-
-			// when number of messages is invalid
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					RuntimeOrigin::signed(1),
-					TestMessagesDeliveryProof(Ok((
-						TEST_LANE_ID,
-						InboundLaneData {
-							relayers: vec![
-								unrewarded_relayer(1, 1, TEST_RELAYER_A),
-								unrewarded_relayer(2, 2, TEST_RELAYER_B)
-							]
-							.into_iter()
-							.collect(),
-							..Default::default()
-						}
-					))),
-					UnrewardedRelayersState {
-						unrewarded_relayer_entries: 2,
-						total_messages: 1,
-						last_delivered_nonce: 2,
-						..Default::default()
-					},
-				),
-				Error::<TestRuntime, ()>::InvalidUnrewardedRelayersState,
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, REGULAR_PAYLOAD), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				Weight::zero(),
+			));
+			assert_eq!(ParkedMessagesQueue::<TestRuntime>::decode_len(), Some(2));
+
+			// if passed weight is enough to dispatch single message
+			let dbw = DbWeight::get();
+			let single_message_weight = dbw.reads(4) +
+				parked_message_overhead::<TestRuntime, ()>() +
+				REGULAR_PAYLOAD.declared_weight;
+			assert_eq!(
+				Pallet::<TestRuntime>::dispatch_parked_messages(single_message_weight),
+				single_message_weight,
+			);
+			assert_eq!(
+				ParkedMessagesQueue::<TestRuntime>::get().into_inner(),
+				vec![MessageKey { lane_id: TEST_LANE_ID, nonce: 2 }],
+			);
+			assert!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 1).is_some()
 			);
 
-			// when last delivered nonce is invalid
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					RuntimeOrigin::signed(1),
-					TestMessagesDeliveryProof(Ok((
-						TEST_LANE_ID,
-						InboundLaneData {
-							relayers: vec![
-								unrewarded_relayer(1, 1, TEST_RELAYER_A),
-								unrewarded_relayer(2, 2, TEST_RELAYER_B)
-							]
-							.into_iter()
-							.collect(),
-							..Default::default()
-						}
-					))),
-					UnrewardedRelayersState {
-						unrewarded_relayer_entries: 2,
-						total_messages: 2,
-						last_delivered_nonce: 8,
-						..Default::default()
-					},
-				),
-				Error::<TestRuntime, ()>::InvalidUnrewardedRelayersState,
+			// if passed weight is enough to dispatch all messages
+			Pallet::<TestRuntime, ()>::on_idle(0, Weight::MAX);
+			assert!(ParkedMessagesQueue::<TestRuntime>::get().is_empty());
+			assert!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 2).is_some()
 			);
 		});
 	}
 
 	#[test]
-	fn receive_messages_accepts_single_message_with_invalid_payload() {
+	fn on_idle_callback_dispatches_parked_messages_following_heavier_message() {
 		run_test(|| {
-			let mut invalid_message = message(1, REGULAR_PAYLOAD);
-			invalid_message.payload = Vec::new();
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+			let heavy_payload = message_payload(0, 100);
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
+				RuntimeOrigin::signed(1),
+				TEST_RELAYER_A,
+				Ok(vec![message(1, heavy_payload), message(2, REGULAR_PAYLOAD)]).into(),
+				2,
+				Weight::zero(),
+			));
+			assert_eq!(LaneParkedMessages::<TestRuntime>::get(TEST_LANE_ID), 2);
 
-			assert_ok!(Pallet::<TestRuntime, ()>::receive_messages_proof(
+			// remaining weight is not enough to dispatch the first message, but it is enough to
+			// dispatch the second one
+			let dbw = DbWeight::get();
+			let remaining_weight = dbw.reads(6) +
+				parked_message_overhead::<TestRuntime, ()>() +
+				REGULAR_PAYLOAD.declared_weight;
+			assert_eq!(
+				Pallet::<TestRuntime>::dispatch_parked_messages(remaining_weight),
+				remaining_weight,
+			);
+			assert_eq!(
+				ParkedMessagesQueue::<TestRuntime>::get().into_inner(),
+				vec![MessageKey { lane_id: TEST_LANE_ID, nonce: 1 }],
+			);
+			assert_eq!(LaneParkedMessages::<TestRuntime>::get(TEST_LANE_ID), 1);
+			assert!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 2).is_some()
+			);
+		});
+	}
+
+	#[test]
+	fn on_idle_callback_does_not_dispatch_parked_messages_when_pallet_is_halted() {
+		run_test(|| {
+			MaxImmediateDispatchWeight::set(&Weight::from_ref_time(10));
+			assert_ok!(Pallet::<TestRuntime>::receive_messages_proof(
 				RuntimeOrigin::signed(1),
 				TEST_RELAYER_A,
-				Ok(vec![invalid_message]).into(),
+				Ok(vec![message(1, REGULAR_PAYLOAD)]).into(),
 				1,
-				Weight::from_ref_time(0), /* weight may be zero in this case (all messages are
-				                           * improperly encoded) */
-			),);
+				Weight::zero(),
+			));
 
-			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 1,);
+			PalletOperatingMode::<TestRuntime, ()>::put(MessagesOperatingMode::Basic(
+				BasicOperatingMode::Halted,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::dispatch_parked_messages(Weight::MAX),
+				DbWeight::get().reads(1),
+			);
+			assert_eq!(ParkedMessagesQueue::<TestRuntime>::decode_len(), Some(1));
+
+			// parked messages are dispatched once the pallet is resumed
+			PalletOperatingMode::<TestRuntime, ()>::put(MessagesOperatingMode::Basic(
+				BasicOperatingMode::Normal,
+			));
+			Pallet::<TestRuntime, ()>::on_idle(0, Weight::MAX);
+			assert!(ParkedMessagesQueue::<TestRuntime>::get().is_empty());
+			assert!(
+				Pallet::<TestRuntime>::inbound_message_dispatch_result(TEST_LANE_ID, 1).is_some()
+			);
 		});
 	}
 
+	fn insert_inbound_lane_with_confirmed_relayer_entries(lane_id: LaneId) {
+		InboundLanes::<TestRuntime, ()>::insert(
+			lane_id,
+			InboundLaneData {
+				last_confirmed_nonce: 2,
+				relayers: vec![
+					unrewarded_relayer(1, 2, TEST_RELAYER_A),
+					unrewarded_relayer(3, 3, TEST_RELAYER_B),
+					unrewarded_relayer(4, 4, TEST_RELAYER_B),
+				]
+				.into_iter()
+				.collect(),
+			},
+		);
+	}
+
+	fn is_inbound_lane_pruned(lane_id: LaneId) -> bool {
+		InboundLanes::<TestRuntime>::get(lane_id).0.relayers ==
+			vec![unrewarded_relayer(3, 4, TEST_RELAYER_B)]
+	}
+
 	#[test]
-	fn receive_messages_accepts_batch_with_message_with_invalid_payload() {
+	fn prune_lane_works() {
 		run_test(|| {
-			let mut invalid_message = message(2, REGULAR_PAYLOAD);
-			invalid_message.payload = Vec::new();
+			insert_inbound_lane_with_confirmed_relayer_entries(TEST_LANE_ID);
 
-			assert_ok!(Pallet::<TestRuntime, ()>::receive_messages_proof(
-				RuntimeOrigin::signed(1),
-				TEST_RELAYER_A,
-				Ok(
-					vec![message(1, REGULAR_PAYLOAD), invalid_message, message(3, REGULAR_PAYLOAD),]
-				)
-				.into(),
-				3,
-				REGULAR_PAYLOAD.declared_weight + REGULAR_PAYLOAD.declared_weight,
-			),);
+			let dbw = DbWeight::get();
+			assert_eq!(
+				Pallet::<TestRuntime>::prune_lane(RuntimeOrigin::signed(1), TEST_LANE_ID)
+					.unwrap()
+					.actual_weight,
+				Some(dbw.reads_writes(3, 1)),
+			);
+			assert!(is_inbound_lane_pruned(TEST_LANE_ID));
+			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 4);
 
-			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 3,);
+			// lane data is not updated if there's nothing to prune
+			assert_eq!(
+				Pallet::<TestRuntime>::prune_lane(RuntimeOrigin::signed(1), TEST_LANE_ID)
+					.unwrap()
+					.actual_weight,
+				Some(dbw.reads(3)),
+			);
 		});
 	}
 
 	#[test]
-	fn actual_dispatch_weight_does_not_overlow() {
+	fn prune_lane_rejects_halted_lane() {
 		run_test(|| {
-			let message1 = message(1, message_payload(0, u64::MAX / 2));
-			let message2 = message(2, message_payload(0, u64::MAX / 2));
-			let message3 = message(3, message_payload(0, u64::MAX / 2));
+			insert_inbound_lane_with_confirmed_relayer_entries(TEST_LANE_ID);
+			LaneOperatingModes::<TestRuntime>::insert(
+				TEST_LANE_ID,
+				MessagesOperatingMode::Basic(BasicOperatingMode::Halted),
+			);
 
-			assert_ok!(Pallet::<TestRuntime, ()>::receive_messages_proof(
-				RuntimeOrigin::signed(1),
-				TEST_RELAYER_A,
-				// this may cause overflow if source chain storage is invalid
-				Ok(vec![message1, message2, message3]).into(),
-				3,
-				Weight::MAX,
-			));
-			assert_eq!(InboundLanes::<TestRuntime>::get(TEST_LANE_ID).last_delivered_nonce(), 2);
+			assert_noop!(
+				Pallet::<TestRuntime>::prune_lane(RuntimeOrigin::signed(1), TEST_LANE_ID),
+				Error::<TestRuntime, ()>::LaneIsHalted,
+			);
 		});
 	}
 
 	#[test]
-	fn weight_refund_from_receive_messages_proof_works() {
+	fn on_idle_callback_prunes_inbound_lanes() {
 		run_test(|| {
-			fn submit_with_unspent_weight(
-				nonce: MessageNonce,
-				unspent_weight: u64,
-			) -> (Weight, Weight) {
-				let mut payload = REGULAR_PAYLOAD;
-				*payload.dispatch_result.unspent_weight.ref_time_mut() = unspent_weight;
-				let proof = Ok(vec![message(nonce, payload)]).into();
-				let messages_count = 1;
-				let pre_dispatch_weight =
-					<TestRuntime as Config>::WeightInfo::receive_messages_proof_weight(
-						&proof,
-						messages_count,
-						REGULAR_PAYLOAD.declared_weight,
-					);
-				let post_dispatch_weight = Pallet::<TestRuntime>::receive_messages_proof(
-					RuntimeOrigin::signed(1),
-					TEST_RELAYER_A,
-					proof,
-					messages_count,
-					REGULAR_PAYLOAD.declared_weight,
-				)
-				.expect("delivery has failed")
-				.actual_weight
-				.expect("receive_messages_proof always returns Some");
+			insert_inbound_lane_with_confirmed_relayer_entries(TEST_LANE_ID);
+			insert_inbound_lane_with_confirmed_relayer_entries(TEST_LANE_ID_2);
 
-				(pre_dispatch_weight, post_dispatch_weight)
-			}
+			// nothing is pruned if pruning is disabled
+			let dbw = DbWeight::get();
+			assert_eq!(Pallet::<TestRuntime>::prune_inbound_lanes(Weight::MAX), Weight::zero());
 
-			// when dispatch is returning `unspent_weight < declared_weight`
-			let (pre, post) = submit_with_unspent_weight(1, 1);
-			assert_eq!(post.ref_time(), pre.ref_time() - 1);
+			// nothing is pruned if passed weight is too low
+			MaxInboundLanesToPrunePerBlock::set(&1);
+			assert_eq!(
+				Pallet::<TestRuntime>::prune_inbound_lanes(dbw.reads_writes(3, 1)),
+				Weight::zero()
+			);
+			assert!(!is_inbound_lane_pruned(TEST_LANE_ID));
+			assert!(!is_inbound_lane_pruned(TEST_LANE_ID_2));
 
-			// when dispatch is returning `unspent_weight = declared_weight`
-			let (pre, post) =
-				submit_with_unspent_weight(2, REGULAR_PAYLOAD.declared_weight.ref_time());
+			// single lane is pruned in every block
 			assert_eq!(
-				post.ref_time(),
-				pre.ref_time() - REGULAR_PAYLOAD.declared_weight.ref_time()
+				Pallet::<TestRuntime>::prune_inbound_lanes(Weight::MAX),
+				dbw.reads_writes(4, 2),
 			);
+			let first_lane = InboundLanesPruningCursor::<TestRuntime>::get().unwrap();
+			let second_lane =
+				if first_lane == TEST_LANE_ID { TEST_LANE_ID_2 } else { TEST_LANE_ID };
+			assert!(is_inbound_lane_pruned(first_lane));
+			assert!(!is_inbound_lane_pruned(second_lane));
 
-			// when dispatch is returning `unspent_weight > declared_weight`
-			let (pre, post) =
-				submit_with_unspent_weight(3, REGULAR_PAYLOAD.declared_weight.ref_time() + 1);
 			assert_eq!(
-				post.ref_time(),
-				pre.ref_time() - REGULAR_PAYLOAD.declared_weight.ref_time()
+				Pallet::<TestRuntime>::prune_inbound_lanes(Weight::MAX),
+				dbw.reads_writes(4, 2),
 			);
+			assert_eq!(InboundLanesPruningCursor::<TestRuntime>::get(), Some(second_lane));
+			assert!(is_inbound_lane_pruned(second_lane));
 
-			// when there's no unspent weight
-			let (pre, post) = submit_with_unspent_weight(4, 0);
-			assert_eq!(post, pre);
+			// when all lanes are visited, pruning starts from the first lane
+			assert_eq!(
+				Pallet::<TestRuntime>::prune_inbound_lanes(Weight::MAX),
+				dbw.reads_writes(2, 1),
+			);
+			assert_eq!(InboundLanesPruningCursor::<TestRuntime>::get(), None);
 
-			// when dispatch is returning `unspent_weight < declared_weight`
-			let (pre, post) = submit_with_unspent_weight(5, 1);
-			assert_eq!(post.ref_time(), pre.ref_time() - 1);
+			// already pruned lanes are visited, but not updated
+			MaxInboundLanesToPrunePerBlock::set(&2);
+			assert_eq!(
+				Pallet::<TestRuntime>::prune_inbound_lanes(Weight::MAX),
+				dbw.reads_writes(6, 1),
+			);
+			assert_eq!(InboundLanesPruningCursor::<TestRuntime>::get(), Some(second_lane));
 		});
 	}
 
 	#[test]
-	fn messages_delivered_callbacks_are_called() {
+	fn outbound_message_from_unconfigured_lane_is_rejected() {
+		run_test(|| {
+			assert_noop!(
+				send_message::<TestRuntime, ()>(
+					RuntimeOrigin::signed(1),
+					TEST_LANE_ID_3,
+					REGULAR_PAYLOAD,
+				),
+				Error::<TestRuntime, ()>::InactiveOutboundLane,
+			);
+		});
+	}
+
+	#[test]
+	fn undelivered_messages_expire() {
 		run_test(|| {
+			MessageTtl::set(&Some(10));
 			send_regular_message();
 			send_regular_message();
 			send_regular_message();
+			receive_messages_delivery_proof();
 
-			// messages 1+2 are confirmed in 1 tx, message 3 in a separate tx
-			// dispatch of message 2 has failed
-			let mut delivered_messages_1_and_2 = DeliveredMessages::new(1);
-			delivered_messages_1_and_2.note_dispatched_message();
-			let messages_1_and_2_proof = Ok((
-				TEST_LANE_ID,
-				InboundLaneData {
-					last_confirmed_nonce: 0,
-					relayers: vec![UnrewardedRelayer {
-						relayer: 0,
-						messages: delivered_messages_1_and_2.clone(),
-					}]
-					.into_iter()
-					.collect(),
-				},
-			));
-			let delivered_message_3 = DeliveredMessages::new(3);
-			let messages_3_proof = Ok((
+			// messages have been sent at block 1 => they expire after block 11
+			System::<TestRuntime>::set_block_number(11);
+			assert_ok!(Pallet::<TestRuntime>::prune_expired_messages(
+				RuntimeOrigin::signed(1),
 				TEST_LANE_ID,
-				InboundLaneData {
-					last_confirmed_nonce: 0,
-					relayers: vec![UnrewardedRelayer { relayer: 0, messages: delivered_message_3 }]
-						.into_iter()
-						.collect(),
-				},
+				10,
 			));
+			assert!(!TestOnMessageExpired::is_message_expired(TEST_LANE_ID, 2));
+			assert!(OutboundMessageDeadlines::<TestRuntime>::get(MessageKey {
+				lane_id: TEST_LANE_ID,
+				nonce: 1
+			})
+			.is_none());
 
-			// first tx with messages 1+2
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
-				RuntimeOrigin::signed(1),
-				TestMessagesDeliveryProof(messages_1_and_2_proof),
-				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 1,
-					total_messages: 2,
-					last_delivered_nonce: 2,
-					..Default::default()
-				},
-			));
-			// second tx with message 3
-			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
+			get_ready_for_events();
+			System::<TestRuntime>::set_block_number(12);
+			assert_ok!(Pallet::<TestRuntime>::prune_expired_messages(
 				RuntimeOrigin::signed(1),
-				TestMessagesDeliveryProof(messages_3_proof),
-				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 1,
-					total_messages: 1,
-					last_delivered_nonce: 3,
-					..Default::default()
-				},
+				TEST_LANE_ID,
+				10,
 			));
-		});
-	}
-
-	#[test]
-	fn receive_messages_delivery_proof_rejects_proof_if_trying_to_confirm_more_messages_than_expected(
-	) {
-		run_test(|| {
-			// send message first to be able to check that delivery_proof fails later
-			send_regular_message();
-
-			// 1) InboundLaneData declares that the `last_confirmed_nonce` is 1;
-			// 2) InboundLaneData has no entries => `InboundLaneData::last_delivered_nonce()`
-			//    returns `last_confirmed_nonce`;
-			// 3) it means that we're going to confirm delivery of messages 1..=1;
-			// 4) so the number of declared messages (see `UnrewardedRelayersState`) is `0` and
-			//    numer of actually confirmed messages is `1`.
-			assert_noop!(
-				Pallet::<TestRuntime>::receive_messages_delivery_proof(
-					RuntimeOrigin::signed(1),
-					TestMessagesDeliveryProof(Ok((
-						TEST_LANE_ID,
-						InboundLaneData { last_confirmed_nonce: 1, relayers: Default::default() },
-					))),
-					UnrewardedRelayersState { last_delivered_nonce: 1, ..Default::default() },
-				),
-				Error::<TestRuntime, ()>::TryingToConfirmMoreMessagesThanExpected,
+			for nonce in 2..=3 {
+				let key = MessageKey { lane_id: TEST_LANE_ID, nonce };
+				assert!(TestOnMessageExpired::is_message_expired(TEST_LANE_ID, nonce));
+				assert_eq!(OutboundMessages::<TestRuntime>::get(&key), Some(Default::default()));
+				assert!(OutboundMessageDeadlines::<TestRuntime>::get(&key).is_none());
+			}
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Messages(Event::MessageExpired {
+							lane_id: TEST_LANE_ID,
+							nonce: 2
+						}),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Messages(Event::MessageExpired {
+							lane_id: TEST_LANE_ID,
+							nonce: 3
+						}),
+						topics: vec![],
+					},
+				],
 			);
 		});
 	}
 
 	#[test]
-	fn storage_keys_computed_properly() {
-		assert_eq!(
-			PalletOperatingMode::<TestRuntime>::storage_value_final_key().to_vec(),
-			bp_messages::storage_keys::operating_mode_key("Messages").0,
-		);
-
-		assert_eq!(
-			OutboundMessages::<TestRuntime>::storage_map_final_key(MessageKey {
-				lane_id: TEST_LANE_ID,
-				nonce: 42
-			}),
-			bp_messages::storage_keys::message_key("Messages", &TEST_LANE_ID, 42).0,
-		);
+	fn prune_expired_messages_respects_max_messages() {
+		run_test(|| {
+			MessageTtl::set(&Some(10));
+			send_regular_message();
+			send_regular_message();
 
-		assert_eq!(
-			OutboundLanes::<TestRuntime>::storage_map_final_key(TEST_LANE_ID),
-			bp_messages::storage_keys::outbound_lane_data_key("Messages", &TEST_LANE_ID).0,
-		);
+			System::<TestRuntime>::set_block_number(12);
+			assert_ok!(Pallet::<TestRuntime>::prune_expired_messages(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				1,
+			));
+			assert!(TestOnMessageExpired::is_message_expired(TEST_LANE_ID, 1));
+			assert!(!TestOnMessageExpired::is_message_expired(TEST_LANE_ID, 2));
 
-		assert_eq!(
-			InboundLanes::<TestRuntime>::storage_map_final_key(TEST_LANE_ID),
-			bp_messages::storage_keys::inbound_lane_data_key("Messages", &TEST_LANE_ID).0,
-		);
+			assert_ok!(Pallet::<TestRuntime>::prune_expired_messages(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				1,
+			));
+			assert!(TestOnMessageExpired::is_message_expired(TEST_LANE_ID, 2));
+		});
 	}
 
 	#[test]
-	fn inbound_message_details_works() {
+	fn messages_without_ttl_never_expire() {
 		run_test(|| {
-			assert_eq!(
-				Pallet::<TestRuntime>::inbound_message_data(
-					TEST_LANE_ID,
-					REGULAR_PAYLOAD.encode(),
-					OutboundMessageDetails {
-						nonce: 0,
-						dispatch_weight: Weight::from_ref_time(0),
-						size: 0,
-					},
-				),
-				InboundMessageDetails { dispatch_weight: REGULAR_PAYLOAD.declared_weight },
+			send_regular_message();
+
+			System::<TestRuntime>::set_block_number(1_000);
+			assert_ok!(Pallet::<TestRuntime>::prune_expired_messages(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				10,
+			));
+			assert!(!TestOnMessageExpired::is_message_expired(TEST_LANE_ID, 1));
+			assert_ne!(
+				OutboundMessages::<TestRuntime>::get(MessageKey {
+					lane_id: TEST_LANE_ID,
+					nonce: 1
+				}),
+				Some(Default::default()),
 			);
 		});
 	}
 
 	#[test]
-	fn on_idle_callback_respects_remaining_weight() {
+	fn sender_may_cancel_undelivered_message() {
 		run_test(|| {
+			MessageTtl::set(&Some(10));
 			send_regular_message();
 			send_regular_message();
-			send_regular_message();
-			send_regular_message();
 
+			get_ready_for_events();
+			assert_ok!(Pallet::<TestRuntime>::cancel_message(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				2,
+			));
+
+			// the sender is not refunded until the message dispatch result is proved
+			let key = MessageKey { lane_id: TEST_LANE_ID, nonce: 2 };
+			assert_eq!(TestOnMessageCancelled::message_cancelled_by(TEST_LANE_ID, 2), None);
+			assert_eq!(OutboundMessages::<TestRuntime>::get(&key), Some(Default::default()));
+			assert_eq!(OutboundMessageSenders::<TestRuntime>::get(&key), Some(1));
+			assert!(OutboundMessageDeadlines::<TestRuntime>::get(&key).is_none());
+			assert_eq!(
+				OutboundCancelledMessages::<TestRuntime>::get(TEST_LANE_ID, 1)
+					.map(|n| n.into_inner()),
+				Some(vec![2]),
+			);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Messages(Event::MessageCancelled {
+						lane_id: TEST_LANE_ID,
+						nonce: 2,
+					}),
+					topics: vec![],
+				}],
+			);
+
+			// message can't be cancelled twice
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_message(RuntimeOrigin::signed(1), TEST_LANE_ID, 2),
+				Error::<TestRuntime, ()>::MessageIsAlreadyCancelled,
+			);
+		});
+	}
+
+	#[test]
+	fn sender_of_cancelled_message_is_refunded_only_when_dispatch_result_is_proved() {
+		run_test(|| {
+			for _ in 0..4 {
+				send_regular_message();
+			}
+			for nonce in [4, 2] {
+				assert_ok!(Pallet::<TestRuntime>::cancel_message(
+					RuntimeOrigin::signed(1),
+					TEST_LANE_ID,
+					nonce,
+				));
+			}
+
+			ProvedDispatchResults::set(&vec![DeliveredMessageDispatchResult {
+				nonce: 2,
+				unspent_weight: Weight::zero(),
+				encoded_dispatch_level_result: Vec::new(),
+			}]);
 			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
 				RuntimeOrigin::signed(1),
 				TestMessagesDeliveryProof(Ok((
 					TEST_LANE_ID,
 					InboundLaneData {
-						last_confirmed_nonce: 4,
-						relayers: vec![unrewarded_relayer(1, 4, TEST_RELAYER_A)]
-							.into_iter()
-							.collect(),
+						relayers: vec![
+							unrewarded_relayer(1, 3, TEST_RELAYER_A),
+							unrewarded_relayer(4, 4, TEST_RELAYER_B),
+						]
+						.into_iter()
+						.collect(),
+						..Default::default()
 					},
 				))),
 				UnrewardedRelayersState {
-					unrewarded_relayer_entries: 1,
-					messages_in_oldest_entry: 4,
+					unrewarded_relayer_entries: 2,
 					total_messages: 4,
 					last_delivered_nonce: 4,
+					..Default::default()
 				},
 			));
 
-			// all 4 messages may be pruned now
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().latest_received_nonce,
-				4
-			);
+			// relayers are rewarded for all delivered messages
+			assert!(TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_A, 3));
+			assert!(TestDeliveryConfirmationPayments::is_reward_paid(TEST_RELAYER_B, 1));
+			// the handler is only called for the message with proved dispatch result
+			assert_eq!(TestOnMessageCancelled::message_cancelled_by(TEST_LANE_ID, 2), Some(1));
+			assert_eq!(TestOnMessageCancelled::message_cancelled_by(TEST_LANE_ID, 4), None);
 			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				1
+				OutboundCancelledMessages::<TestRuntime>::get(TEST_LANE_ID, 1)
+					.map(|n| n.into_inner()),
+				Some(vec![4]),
 			);
-			System::<TestRuntime>::set_block_number(2);
+		});
+	}
 
-			// if passed wight is too low to do anything
-			let dbw = DbWeight::get();
-			assert_eq!(
-				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(1, 1)),
-				Weight::zero(),
+	#[test]
+	fn cancelled_messages_are_limited_per_sender() {
+		run_test(|| {
+			send_regular_message();
+			send_regular_message();
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(2),
+				TEST_LANE_ID,
+				REGULAR_PAYLOAD,
+			));
+			receive_messages_delivery_proof();
+
+			// the sender can't cancel more messages
+			let max_cancelled_messages = MAX_CANCELLED_MESSAGES_PER_SENDER as MessageNonce;
+			OutboundCancelledMessages::<TestRuntime>::insert(
+				TEST_LANE_ID,
+				1,
+				(100..100 + max_cancelled_messages).collect::<Vec<_>>().try_into().unwrap(),
 			);
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				1
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_message(RuntimeOrigin::signed(1), TEST_LANE_ID, 2),
+				Error::<TestRuntime, ()>::TooManyCancelledMessages,
 			);
 
-			// if passed wight is enough to prune single message
-			assert_eq!(
-				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(1, 2)),
-				dbw.reads_writes(1, 2),
+			// but other senders may still cancel their messages
+			assert_ok!(Pallet::<TestRuntime>::cancel_message(
+				RuntimeOrigin::signed(2),
+				TEST_LANE_ID,
+				3,
+			));
+
+			// messages, which delivery has been confirmed without dispatch result, are forgotten
+			OutboundCancelledMessages::<TestRuntime>::insert(
+				TEST_LANE_ID,
+				1,
+				sp_std::iter::once(1)
+					.chain(100..99 + max_cancelled_messages)
+					.collect::<Vec<_>>()
+					.try_into()
+					.unwrap(),
 			);
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				2
+			assert_ok!(Pallet::<TestRuntime>::cancel_message(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID,
+				2,
+			));
+			assert!(!OutboundCancelledMessages::<TestRuntime>::get(TEST_LANE_ID, 1)
+				.unwrap()
+				.contains(&1));
+		});
+	}
+
+	#[test]
+	fn message_may_only_be_cancelled_by_its_sender() {
+		run_test(|| {
+			send_regular_message();
+
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_message(RuntimeOrigin::signed(2), TEST_LANE_ID, 1),
+				Error::<TestRuntime, ()>::NotMessageSender,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_message(RuntimeOrigin::signed(1), TEST_LANE_ID, 2),
+				Error::<TestRuntime, ()>::MessageIsNotYetSent,
 			);
+		});
+	}
+
+	#[test]
+	fn delivered_message_can_not_be_cancelled() {
+		run_test(|| {
+			send_regular_message();
+			receive_messages_delivery_proof();
+
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_message(RuntimeOrigin::signed(1), TEST_LANE_ID, 1),
+				Error::<TestRuntime, ()>::MessageIsAlreadyDelivered,
+			);
+			assert_eq!(TestOnMessageCancelled::message_cancelled_by(TEST_LANE_ID, 1), None);
+		});
+	}
+
+	#[test]
+	fn dynamic_outbound_lane_may_be_opened_and_closed() {
+		run_test(|| {
+			get_ready_for_events();
 
-			// if passed wight is enough to prune two more messages
 			assert_eq!(
-				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(1, 3)),
-				dbw.reads_writes(1, 3),
+				Pallet::<TestRuntime>::outbound_lane_state(TEST_LANE_ID_3),
+				LaneState::Closed
 			);
+			assert_ok!(Pallet::<TestRuntime>::open_lane(RuntimeOrigin::root(), TEST_LANE_ID_3));
 			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				4
+				Pallet::<TestRuntime>::outbound_lane_state(TEST_LANE_ID_3),
+				LaneState::Opened
 			);
+			assert_ok!(send_message::<TestRuntime, ()>(
+				RuntimeOrigin::signed(1),
+				TEST_LANE_ID_3,
+				REGULAR_PAYLOAD,
+			));
 
-			// if passed wight is enough to prune many messages
+			assert_ok!(Pallet::<TestRuntime>::close_lane(RuntimeOrigin::root(), TEST_LANE_ID_3));
 			assert_eq!(
-				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100)),
-				dbw.reads_writes(1, 2),
+				Pallet::<TestRuntime>::outbound_lane_state(TEST_LANE_ID_3),
+				LaneState::Closing
+			);
+			assert_noop!(
+				send_message::<TestRuntime, ()>(
+					RuntimeOrigin::signed(1),
+					TEST_LANE_ID_3,
+					REGULAR_PAYLOAD,
+				),
+				Error::<TestRuntime, ()>::InactiveOutboundLane,
 			);
+
 			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				5
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Messages(Event::LaneOpened { lane_id: TEST_LANE_ID_3 }),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Messages(Event::MessageAccepted {
+							lane_id: TEST_LANE_ID_3,
+							nonce: 1
+						}),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Messages(Event::LaneClosing { lane_id: TEST_LANE_ID_3 }),
+						topics: vec![],
+					},
+				],
 			);
 		});
 	}
 
 	#[test]
-	fn on_idle_callback_is_rotating_lanes_to_prune() {
+	fn closing_outbound_lane_is_closed_when_all_messages_are_pruned() {
 		run_test(|| {
-			// send + receive confirmation for lane 1
-			send_regular_message();
-			receive_messages_delivery_proof();
-			// send + receive confirmation for lane 2
+			assert_ok!(Pallet::<TestRuntime>::open_lane(RuntimeOrigin::root(), TEST_LANE_ID_3));
 			assert_ok!(send_message::<TestRuntime, ()>(
 				RuntimeOrigin::signed(1),
-				TEST_LANE_ID_2,
+				TEST_LANE_ID_3,
 				REGULAR_PAYLOAD,
 			));
+			assert_ok!(Pallet::<TestRuntime>::close_lane(RuntimeOrigin::root(), TEST_LANE_ID_3));
+
+			// message is not yet delivered => lane is not closed. Lanes are rotated, so dynamic
+			// lane is visited when `block_number % 3 == 2`
+			let dbw = DbWeight::get();
+			System::<TestRuntime>::set_block_number(2);
+			Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100));
+			assert_eq!(
+				Pallet::<TestRuntime>::outbound_lane_state(TEST_LANE_ID_3),
+				LaneState::Closing
+			);
+
+			// message is delivered => lane is closed when it is pruned
 			assert_ok!(Pallet::<TestRuntime>::receive_messages_delivery_proof(
 				RuntimeOrigin::signed(1),
 				TestMessagesDeliveryProof(Ok((
-					TEST_LANE_ID_2,
+					TEST_LANE_ID_3,
 					InboundLaneData {
 						last_confirmed_nonce: 1,
 						relayers: vec![unrewarded_relayer(1, 1, TEST_RELAYER_A)]
@@ -1828,69 +4519,64 @@ mod tests {
 					last_delivered_nonce: 1,
 				},
 			));
-
-			// nothing is pruned yet
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().latest_received_nonce,
-				1
-			);
+			get_ready_for_events();
+			System::<TestRuntime>::set_block_number(5);
 			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				1
+				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100)),
+				dbw.reads_writes(5, 4),
 			);
 			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().latest_received_nonce,
-				1
+				Pallet::<TestRuntime>::outbound_lane_state(TEST_LANE_ID_3),
+				LaneState::Closed
 			);
 			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().oldest_unpruned_nonce,
-				1
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Messages(Event::LaneClosed { lane_id: TEST_LANE_ID_3 }),
+					topics: vec![],
+				}],
 			);
 
-			// in block#2.on_idle lane messages of lane 1 are pruned
-			let dbw = DbWeight::get();
-			System::<TestRuntime>::set_block_number(2);
-			assert_eq!(
-				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100)),
-				dbw.reads_writes(1, 2),
+			// and the lane may be opened again
+			assert_ok!(Pallet::<TestRuntime>::open_lane(RuntimeOrigin::root(), TEST_LANE_ID_3));
+		});
+	}
+
+	#[test]
+	fn open_lane_and_close_lane_reject_invalid_lanes() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::open_lane(RuntimeOrigin::signed(1), TEST_LANE_ID_3),
+				DispatchError::BadOrigin,
 			);
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				2
+			assert_noop!(
+				Pallet::<TestRuntime>::open_lane(RuntimeOrigin::root(), TEST_LANE_ID),
+				Error::<TestRuntime, ()>::LaneAlreadyOpened,
 			);
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().oldest_unpruned_nonce,
-				1
+			assert_noop!(
+				Pallet::<TestRuntime>::close_lane(RuntimeOrigin::root(), TEST_LANE_ID),
+				Error::<TestRuntime, ()>::LaneIsNotOpened,
 			);
-
-			// in block#3.on_idle lane messages of lane 2 are pruned
-			System::<TestRuntime>::set_block_number(3);
-
-			assert_eq!(
-				Pallet::<TestRuntime, ()>::on_idle(0, dbw.reads_writes(100, 100)),
-				dbw.reads_writes(1, 2),
+			assert_noop!(
+				Pallet::<TestRuntime>::close_lane(RuntimeOrigin::root(), TEST_LANE_ID_3),
+				Error::<TestRuntime, ()>::LaneIsNotOpened,
 			);
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID).data().oldest_unpruned_nonce,
-				2
+
+			assert_ok!(Pallet::<TestRuntime>::open_lane(RuntimeOrigin::root(), TEST_LANE_ID_3));
+			assert_noop!(
+				Pallet::<TestRuntime>::open_lane(RuntimeOrigin::root(), TEST_LANE_ID_3),
+				Error::<TestRuntime, ()>::LaneAlreadyOpened,
 			);
-			assert_eq!(
-				outbound_lane::<TestRuntime, ()>(TEST_LANE_ID_2).data().oldest_unpruned_nonce,
-				2
+			assert_noop!(
+				Pallet::<TestRuntime>::open_lane(RuntimeOrigin::root(), LaneId([0, 0, 0, 4])),
+				Error::<TestRuntime, ()>::TooManyDynamicOutboundLanes,
 			);
-		});
-	}
 
-	#[test]
-	fn outbound_message_from_unconfigured_lane_is_rejected() {
-		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::close_lane(RuntimeOrigin::root(), TEST_LANE_ID_3));
 			assert_noop!(
-				send_message::<TestRuntime, ()>(
-					RuntimeOrigin::signed(1),
-					TEST_LANE_ID_3,
-					REGULAR_PAYLOAD,
-				),
-				Error::<TestRuntime, ()>::InactiveOutboundLane,
+				Pallet::<TestRuntime>::close_lane(RuntimeOrigin::root(), TEST_LANE_ID_3),
+				Error::<TestRuntime, ()>::LaneIsNotOpened,
 			);
 		});
 	}
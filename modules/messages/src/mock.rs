@@ -21,19 +21,22 @@ use crate::Config;
 
 use bp_messages::{
 	calc_relayers_rewards,
-	source_chain::{DeliveryConfirmationPayments, LaneMessageVerifier, TargetHeaderChain},
+	source_chain::{
+		DeliveryConfirmationPayments, LaneMessageVerifier, MessageDeliveryFeeCalculator,
+		OnDeliveryConfirmed, OnMessageCancelled, OnMessageExpired, TargetHeaderChain,
+	},
 	target_chain::{
 		DeliveryPayments, DispatchMessage, DispatchMessageData, MessageDispatch,
 		ProvedLaneMessages, ProvedMessages, SourceHeaderChain,
 	},
-	DeliveredMessages, InboundLaneData, LaneId, Message, MessageKey, MessageNonce, MessagePayload,
-	OutboundLaneData, UnrewardedRelayer,
+	DeliveredMessageDispatchResult, DeliveredMessages, InboundLaneData, LaneId, Message,
+	MessageKey, MessageNonce, MessagePayload, OutboundLaneData, UnrewardedRelayer,
 };
 use bp_runtime::{messages::MessageDispatchResult, Size};
 use codec::{Decode, Encode};
 use frame_support::{
-	parameter_types,
-	traits::ConstU64,
+	ord_parameter_types, parameter_types,
+	traits::{AsEnsureOriginWithArg, ConstU64},
 	weights::{RuntimeDbWeight, Weight},
 };
 use scale_info::TypeInfo;
@@ -41,7 +44,7 @@ use sp_core::H256;
 use sp_runtime::{
 	testing::Header as SubstrateHeader,
 	traits::{BlakeTwo256, IdentityLookup},
-	Perbill,
+	FixedU128, Perbill,
 };
 use std::{
 	collections::{BTreeMap, VecDeque},
@@ -140,16 +143,37 @@ parameter_types! {
 	pub const MaxUnconfirmedMessagesAtInboundLane: u64 = 32;
 	pub const TestBridgedChainId: bp_runtime::ChainId = *b"test";
 	pub const ActiveOutboundLanes: &'static [LaneId] = &[TEST_LANE_ID, TEST_LANE_ID_2];
+	pub const UnorderedInboundLanes: &'static [LaneId] = &[TEST_LANE_ID_3];
+	pub storage MessageTtl: Option<u64> = None;
+	pub storage MaxImmediateDispatchWeight: Weight = Weight::MAX;
+	pub storage MaxParkedMessagesPerLane: u32 = 2;
+	pub storage MaxInboundLanesToPrunePerBlock: u32 = 0;
+	pub storage ProvedDispatchResults: Vec<DeliveredMessageDispatchResult> = Vec::new();
+}
+
+ord_parameter_types! {
+	pub const TestLaneOwner: AccountId = 200;
 }
 
 impl Config for TestRuntime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = ();
 	type ActiveOutboundLanes = ActiveOutboundLanes;
+	type MaxDynamicOutboundLanes = frame_support::traits::ConstU32<1>;
+	type LaneOwnerOrigin =
+		AsEnsureOriginWithArg<frame_system::EnsureSignedBy<TestLaneOwner, AccountId>>;
 	type MaxUnrewardedRelayerEntriesAtInboundLane = MaxUnrewardedRelayerEntriesAtInboundLane;
 	type MaxUnconfirmedMessagesAtInboundLane = MaxUnconfirmedMessagesAtInboundLane;
+	type UnorderedInboundLanes = UnorderedInboundLanes;
+	type MaxImmediateDispatchWeight = MaxImmediateDispatchWeight;
+	type MaxParkedMessages = frame_support::traits::ConstU32<2>;
+	type MaxParkedMessagesPerLane = MaxParkedMessagesPerLane;
+	type MaxInboundLanesToPrunePerBlock = MaxInboundLanesToPrunePerBlock;
 
 	type MaximalOutboundPayloadSize = frame_support::traits::ConstU32<MAX_OUTBOUND_PAYLOAD_SIZE>;
+	type MaxMessagesPerLanePerBlock = frame_support::traits::ConstU64<4>;
+	type MaxBytesPerLanePerBlock =
+		frame_support::traits::ConstU32<{ 2 * MAX_OUTBOUND_PAYLOAD_SIZE }>;
 	type OutboundPayload = TestPayload;
 
 	type InboundPayload = TestPayload;
@@ -158,7 +182,12 @@ impl Config for TestRuntime {
 
 	type TargetHeaderChain = TestTargetHeaderChain;
 	type LaneMessageVerifier = TestLaneMessageVerifier;
+	type DeliveryFeeCalculator = TestDeliveryFeeCalculator;
 	type DeliveryConfirmationPayments = TestDeliveryConfirmationPayments;
+	type MessageTtl = MessageTtl;
+	type OnMessageExpired = TestOnMessageExpired;
+	type OnMessageCancelled = TestOnMessageCancelled;
+	type OnDeliveryConfirmed = TestOnDeliveryConfirmed;
 
 	type SourceHeaderChain = TestSourceHeaderChain;
 	type MessageDispatch = TestMessageDispatch;
@@ -177,6 +206,9 @@ pub const MAX_OUTBOUND_PAYLOAD_SIZE: u32 = 4096;
 /// Account that has balance to use in tests.
 pub const ENDOWED_ACCOUNT: AccountId = 0xDEAD;
 
+/// Account that is unable to pay message delivery fee.
+pub const ACCOUNT_WITHOUT_FUNDS: AccountId = 0xBAD;
+
 /// Account id of test relayer.
 pub const TEST_RELAYER_A: AccountId = 100;
 
@@ -263,8 +295,15 @@ impl TargetHeaderChain<TestPayload, TestRelayer> for TestTargetHeaderChain {
 
 	fn verify_messages_delivery_proof(
 		proof: Self::MessagesDeliveryProof,
-	) -> Result<(LaneId, InboundLaneData<TestRelayer>), Self::Error> {
-		proof.0.map_err(|_| TEST_ERROR)
+	) -> Result<
+		(LaneId, InboundLaneData<TestRelayer>, Vec<DeliveredMessageDispatchResult>),
+		Self::Error,
+	> {
+		// every valid proof also proves dispatch results, configured by the test
+		proof
+			.0
+			.map(|(lane, lane_data)| (lane, lane_data, ProvedDispatchResults::get()))
+			.map_err(|_| TEST_ERROR)
 	}
 }
 
@@ -289,6 +328,39 @@ impl LaneMessageVerifier<RuntimeOrigin, TestPayload> for TestLaneMessageVerifier
 	}
 }
 
+/// Message delivery fee calculator that is used in tests.
+#[derive(Debug, Default)]
+pub struct TestDeliveryFeeCalculator;
+
+impl TestDeliveryFeeCalculator {
+	/// Returns encoded payload size and fee factor of the last message, sent over given lane.
+	/// The record is cleared after the call.
+	pub fn last_delivery_fee(lane: LaneId) -> Option<(u32, FixedU128)> {
+		let key = (b":delivery-fee:", lane).encode();
+		frame_support::storage::unhashed::take::<(u32, FixedU128)>(&key)
+	}
+}
+
+impl MessageDeliveryFeeCalculator<RuntimeOrigin> for TestDeliveryFeeCalculator {
+	type Error = &'static str;
+
+	fn pay_delivery_fee(
+		submitter: &RuntimeOrigin,
+		lane: &LaneId,
+		_outbound_data: &OutboundLaneData,
+		encoded_payload_size: u32,
+		fee_factor: FixedU128,
+	) -> Result<(), Self::Error> {
+		if frame_system::ensure_signed(submitter.clone()) == Ok(ACCOUNT_WITHOUT_FUNDS) {
+			return Err(TEST_ERROR)
+		}
+
+		let key = (b":delivery-fee:", lane).encode();
+		frame_support::storage::unhashed::put(&key, &(encoded_payload_size, fee_factor));
+		Ok(())
+	}
+}
+
 /// Reward payments at the target chain during delivery transaction.
 #[derive(Debug, Default)]
 pub struct TestDeliveryPayments;
@@ -332,6 +404,10 @@ impl TestDeliveryConfirmationPayments {
 impl DeliveryConfirmationPayments<AccountId> for TestDeliveryConfirmationPayments {
 	type Error = &'static str;
 
+	fn pay_reward_weight(_messages: MessageNonce) -> Weight {
+		Weight::zero()
+	}
+
 	fn pay_reward(
 		_lane_id: LaneId,
 		messages_relayers: VecDeque<UnrewardedRelayer<AccountId>>,
@@ -346,6 +422,87 @@ impl DeliveryConfirmationPayments<AccountId> for TestDeliveryConfirmationPayment
 	}
 }
 
+/// Expired messages handler that is used in tests.
+#[derive(Debug, Default)]
+pub struct TestOnMessageExpired;
+
+impl TestOnMessageExpired {
+	/// Returns true if given message has expired. The expired flag is cleared after the call.
+	pub fn is_message_expired(lane: LaneId, nonce: MessageNonce) -> bool {
+		let key = (b":message-expired:", lane, nonce).encode();
+		frame_support::storage::unhashed::take::<bool>(&key).is_some()
+	}
+}
+
+impl OnMessageExpired for TestOnMessageExpired {
+	fn on_message_expired_weight() -> Weight {
+		Weight::zero()
+	}
+
+	fn on_message_expired(lane: LaneId, nonce: MessageNonce, _payload: &MessagePayload) {
+		let key = (b":message-expired:", lane, nonce).encode();
+		frame_support::storage::unhashed::put(&key, &true);
+	}
+}
+
+/// Message cancellation handler that is used in tests.
+#[derive(Debug, Default)]
+pub struct TestOnMessageCancelled;
+
+impl TestOnMessageCancelled {
+	/// Returns account that has cancelled given message, if the handler has been called for the
+	/// message. The record is cleared after the call.
+	pub fn message_cancelled_by(lane: LaneId, nonce: MessageNonce) -> Option<AccountId> {
+		let key = (b":message-cancelled:", lane, nonce).encode();
+		frame_support::storage::unhashed::take::<AccountId>(&key)
+	}
+}
+
+impl OnMessageCancelled<AccountId> for TestOnMessageCancelled {
+	fn on_message_cancelled_weight() -> Weight {
+		Weight::zero()
+	}
+
+	fn on_message_cancelled(
+		lane: LaneId,
+		sender: &AccountId,
+		dispatch_result: &DeliveredMessageDispatchResult,
+	) {
+		let key = (b":message-cancelled:", lane, dispatch_result.nonce).encode();
+		frame_support::storage::unhashed::put(&key, sender);
+	}
+}
+
+/// Delivery confirmation handler that is used in tests.
+#[derive(Debug, Default)]
+pub struct TestOnDeliveryConfirmed;
+
+impl TestOnDeliveryConfirmed {
+	/// Returns confirmed messages and their dispatch results, reported for given lane. The
+	/// record is cleared after the call.
+	pub fn delivery_confirmed(
+		lane: LaneId,
+	) -> Option<(DeliveredMessages, Vec<DeliveredMessageDispatchResult>)> {
+		let key = (b":delivery-confirmed:", lane).encode();
+		frame_support::storage::unhashed::take(&key)
+	}
+}
+
+impl OnDeliveryConfirmed for TestOnDeliveryConfirmed {
+	fn on_delivery_confirmed_weight(_messages: MessageNonce) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_delivery_confirmed(
+		lane: LaneId,
+		messages: &DeliveredMessages,
+		dispatch_results: &[DeliveredMessageDispatchResult],
+	) {
+		let key = (b":delivery-confirmed:", lane).encode();
+		frame_support::storage::unhashed::put(&key, &(messages, dispatch_results));
+	}
+}
+
 /// Source header chain that is used in tests.
 #[derive(Debug)]
 pub struct TestSourceHeaderChain;
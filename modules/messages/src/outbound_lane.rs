@@ -147,16 +147,17 @@ impl<S: OutboundLaneStorage> OutboundLane<S> {
 		mut remaining_weight: Weight,
 	) -> Weight {
 		let write_weight = db_weight.writes(1);
-		let two_writes_weight = write_weight + write_weight;
+		// both message and its sender are removed from the storage
+		let remove_message_weight = db_weight.writes(2);
 		let mut spent_weight = Weight::zero();
 		let mut data = self.storage.data();
-		while remaining_weight.all_gte(two_writes_weight) &&
+		while remaining_weight.all_gte(remove_message_weight + write_weight) &&
 			data.oldest_unpruned_nonce <= data.latest_received_nonce
 		{
 			self.storage.remove_message(&data.oldest_unpruned_nonce);
 
-			spent_weight += write_weight;
-			remaining_weight -= write_weight;
+			spent_weight += remove_message_weight;
+			remaining_weight -= remove_message_weight;
 			data.oldest_unpruned_nonce += 1;
 		}
 
@@ -390,7 +391,7 @@ mod tests {
 			);
 			assert_eq!(
 				lane.prune_messages(RocksDbWeight::get(), RocksDbWeight::get().writes(101)),
-				RocksDbWeight::get().writes(3),
+				RocksDbWeight::get().writes(5),
 			);
 			assert!(lane.storage.message(&1).is_none());
 			assert!(lane.storage.message(&2).is_none());
@@ -403,7 +404,7 @@ mod tests {
 			);
 			assert_eq!(
 				lane.prune_messages(RocksDbWeight::get(), RocksDbWeight::get().writes(101)),
-				RocksDbWeight::get().writes(2),
+				RocksDbWeight::get().writes(3),
 			);
 			assert!(lane.storage.message(&1).is_none());
 			assert!(lane.storage.message(&2).is_none());
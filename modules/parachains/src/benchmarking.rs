@@ -16,10 +16,7 @@
 
 //! Parachains finality pallet benchmarking.
 
-use crate::{
-	weights_ext::DEFAULT_PARACHAIN_HEAD_SIZE, Call, RelayBlockHash, RelayBlockHasher,
-	RelayBlockNumber,
-};
+use crate::{weights_ext::DEFAULT_PARACHAIN_HEAD_SIZE, Call, RelayBlockHash, RelayBlockNumber};
 
 use bp_polkadot_core::parachains::{ParaHash, ParaHeadsProof, ParaId};
 use bp_runtime::StorageProofSize;
@@ -43,16 +40,6 @@ pub trait Config<I: 'static>: crate::Config<I> {
 }
 
 benchmarks_instance_pallet! {
-	where_clause {
-		where
-			<T as pallet_bridge_grandpa::Config<T::BridgesGrandpaPalletInstance>>::BridgedChain:
-				bp_runtime::Chain<
-					BlockNumber = RelayBlockNumber,
-					Hash = RelayBlockHash,
-					Hasher = RelayBlockHasher,
-				>,
-	}
-
 	// Benchmark `submit_parachain_heads` extrinsic with different number of parachains.
 	submit_parachain_heads_with_n_parachains {
 		let p in 1..(T::parachains().len() + 1) as u32;
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{Config, Pallet, RelayBlockHash, RelayBlockHasher, RelayBlockNumber};
+use crate::{Config, Pallet, RelayBlockNumber};
 use bp_runtime::FilterCall;
 use frame_support::{dispatch::CallableCallFor, traits::IsSubType};
 use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidity, ValidTransaction};
@@ -34,13 +34,6 @@ impl<
 		T: frame_system::Config<RuntimeCall = Call> + Config<I>,
 		I: 'static,
 	> FilterCall<Call> for Pallet<T, I>
-where
-	<T as pallet_bridge_grandpa::Config<T::BridgesGrandpaPalletInstance>>::BridgedChain:
-		bp_runtime::Chain<
-			BlockNumber = RelayBlockNumber,
-			Hash = RelayBlockHash,
-			Hasher = RelayBlockHasher,
-		>,
 {
 	fn validate(call: &Call) -> TransactionValidity {
 		let (updated_at_relay_block_number, parachains) = match call.is_sub_type() {
@@ -16,20 +16,24 @@
 
 //! Parachains finality module.
 //!
-//! This module needs to be deployed with GRANDPA module, which is syncing relay
-//! chain blocks. The main entry point of this module is `submit_parachain_heads`, which
-//! accepts storage proof of some parachain `Heads` entries from bridged relay chain.
-//! It requires corresponding relay headers to be already synced.
+//! This module needs to be deployed with some module, which is syncing relay
+//! chain blocks (GRANDPA or BEEFY light client). The main entry point of this module is
+//! `submit_parachain_heads`, which accepts storage proof of some parachain `Heads` entries from
+//! bridged relay chain. It requires corresponding relay headers to be already synced.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use weights::WeightInfo;
 pub use weights_ext::WeightInfoExt;
 
-use bp_header_chain::HeaderChain;
-use bp_parachains::{parachain_head_storage_key_at_source, ParaInfo, ParaStoredHeaderData};
+use bp_header_chain::{HeaderChain, HeaderChainPinning};
+use bp_parachains::{
+	parachain_head_storage_key_at_source, ParaInfo, ParaStoredHeaderData, ParachainConfig,
+};
 use bp_polkadot_core::parachains::{ParaHash, ParaHead, ParaHeadsProof, ParaId};
-use bp_runtime::{Chain, HashOf, HeaderId, HeaderIdOf, Parachain, StorageProofError};
+use bp_runtime::{
+	BlockNumberOf, Chain, HashOf, HeaderId, HeaderIdOf, Parachain, StorageProofError,
+};
 use frame_support::dispatch::PostDispatchInfo;
 use sp_std::{marker::PhantomData, vec::Vec};
 
@@ -64,18 +68,32 @@ struct UpdateParachainHeadArtifacts {
 	pub prune_happened: bool,
 }
 
+/// Artifacts of the parachain heads submission.
+#[derive(Default)]
+struct SubmitParachainHeadsArtifacts {
+	/// Number of parachain heads that have been updated.
+	pub updated_heads: u32,
+	/// Number of old parachain heads that have been pruned.
+	pub pruned_heads: u32,
+	/// Number of parachain heads that have been imported for free.
+	pub free_heads: u32,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 	use bp_parachains::{
 		BestParaHeadHash, ImportedParaHeadsKeyProvider, ParaStoredHeaderDataBuilder,
-		ParasInfoKeyProvider,
+		ParasInfoKeyProvider, RequestedParachainHeadsKeyProvider,
 	};
 	use bp_runtime::{
 		BasicOperatingMode, BoundedStorageValue, OwnedBridgeModule, StorageDoubleMapKeyProvider,
 		StorageMapKeyProvider,
 	};
-	use frame_support::pallet_prelude::*;
+	use frame_support::{
+		pallet_prelude::*,
+		traits::{Currency, ReservableCurrency},
+	};
 	use frame_system::pallet_prelude::*;
 
 	/// Stored parachain head data of given parachains pallet.
@@ -83,6 +101,9 @@ pub mod pallet {
 		BoundedStorageValue<<T as Config<I>>::MaxParaHeadDataSize, ParaStoredHeaderData>;
 	/// Weight info of the given parachains pallet.
 	pub type WeightInfoOf<T, I> = <T as Config<I>>::WeightInfo;
+	/// Balance type of the given parachains pallet.
+	pub type BalanceOf<T, I> =
+		<<T as Config<I>>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -107,8 +128,25 @@ pub mod pallet {
 			parachain_head_hash: ParaHash,
 			parachain_head_size: u32,
 		},
+		/// The caller has provided head of parachain that is registered as inactive.
+		RejectedInactiveParachainHead { parachain: ParaId, parachain_head_hash: ParaHash },
+		/// The caller has provided parachain head that is not a descendant of any recently
+		/// imported head of the same parachain.
+		RejectedNonDescendantParachainHead { parachain: ParaId, parachain_head_hash: ParaHash },
 		/// Parachain head has been updated.
 		UpdatedParachainHead { parachain: ParaId, parachain_head_hash: ParaHash },
+		/// Parachain configuration has been registered or updated.
+		ParachainRegistered { parachain: ParaId, config: ParachainConfig },
+		/// Parachain has been registered by the given account, which has reserved the deposit.
+		ParachainRegisteredWithDeposit {
+			parachain: ParaId,
+			registrant: T::AccountId,
+			deposit: BalanceOf<T, I>,
+		},
+		/// Parachain has been deregistered.
+		ParachainDeregistered { parachain: ParaId },
+		/// Head of the parachain, read at (or after) given relay chain block has been requested.
+		ParachainHeadRequested { parachain: ParaId, at_relay_block_number: RelayBlockNumber },
 	}
 
 	#[pallet::error]
@@ -125,26 +163,49 @@ pub mod pallet {
 		StorageRootMismatch,
 		/// Failed to extract state root from given parachain head.
 		FailedToExtractStateRoot,
+		/// The parachain configuration is invalid.
+		InvalidParachainConfig,
+		/// Parachains registration with deposit is disabled.
+		RegistrationWithDepositDisabled,
+		/// The parachain is already registered.
+		ParachainAlreadyRegistered,
+		/// The parachain is not registered.
+		ParachainNotRegistered,
+		/// The registrant has failed to reserve the registration deposit.
+		FailedToReserveDeposit,
+		/// The parachain is not tracked by the pallet.
+		UntrackedParachain,
+		/// The pallet already knows parachain head that has been read at (or after) requested
+		/// relay chain block.
+		ParachainHeadAlreadyImported,
 		/// Error generated by the `OwnedBridgeModule` trait.
 		BridgeModule(bp_runtime::OwnedBridgeModuleError),
+		/// The parachain is already tracked by the pallet, so it can't be registered with deposit.
+		ParachainAlreadyTracked,
 	}
 
 	#[pallet::config]
-	#[pallet::disable_frame_system_supertrait_check]
-	pub trait Config<I: 'static = ()>:
-		pallet_bridge_grandpa::Config<Self::BridgesGrandpaPalletInstance>
-	{
+	pub trait Config<I: 'static = ()>: frame_system::Config {
 		/// The overarching event type.
 		type RuntimeEvent: From<Event<Self, I>>
 			+ IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		/// Benchmarks results from runtime we're plugged into.
 		type WeightInfo: WeightInfoExt;
 
-		/// Instance of bridges GRANDPA pallet (within this runtime) that this pallet is linked to.
+		/// Bridged relay chain, which parachains are tracked by this pallet.
+		type BridgedRelayChain: Chain<
+			BlockNumber = RelayBlockNumber,
+			Hash = RelayBlockHash,
+			Hasher = RelayBlockHasher,
+		>;
+
+		/// Finalized headers of the bridged relay chain.
 		///
-		/// The GRANDPA pallet instance must be configured to import headers of relay chain that
-		/// we're interested in.
-		type BridgesGrandpaPalletInstance: 'static;
+		/// Normally it is the `pallet_bridge_grandpa::GrandpaChainHeaders`, configured to import
+		/// headers of the `BridgedRelayChain`. But relay chain finality may also be tracked by
+		/// some other pallet (e.g. `pallet_bridge_beefy::BeefyChainHeaders`), so the runtime
+		/// doesn't need to have any GRANDPA pallet instance to use this pallet.
+		type RelayChainHeaders: HeaderChain<Self::BridgedRelayChain>;
 
 		/// Name of the original `paras` pallet in the `construct_runtime!()` call at the bridged
 		/// chain.
@@ -173,6 +234,9 @@ pub mod pallet {
 		/// the setting does not relate to parachain block numbers - we will simply keep as much
 		/// items in the storage, so it doesn't guarantee any fixed timeframe for heads.
 		///
+		/// This value is used for parachains that have no registered configuration
+		/// (see `ParachainConfigs`).
+		///
 		/// Incautious change of this constant may lead to orphan entries in the runtime storage.
 		#[pallet::constant]
 		type HeadsToKeep: Get<u32>;
@@ -186,8 +250,48 @@ pub mod pallet {
 		///
 		/// There's no mandatory headers in this pallet, so it can't stall if there's some header
 		/// that exceeds this bound.
+		///
+		/// This value is used for parachains that have no registered configuration
+		/// (see `ParachainConfigs`). Registered configurations may only lower it.
 		#[pallet::constant]
 		type MaxParaHeadDataSize: Get<u32>;
+
+		/// If `true`, the pallet only accepts heads of registered parachains (see
+		/// `ParachainConfigs`). Heads of all other parachains are rejected.
+		///
+		/// Enabling this prevents storage growth caused by relayers, submitting heads of
+		/// arbitrary parachains.
+		#[pallet::constant]
+		type OnlyRegisteredParachains: Get<bool>;
+
+		/// If `Some(depth)`, the pallet decodes every submitted parachain head as a header and
+		/// verifies that its parent is either the best known head of the parachain, or one of
+		/// `depth` heads imported before the best head (i.e. we allow reorgs of up to `depth`
+		/// heads). Heads that fail this check are rejected.
+		///
+		/// If it is `None`, ancestry of submitted heads is not verified. The check is also
+		/// skipped when the first head of the parachain is imported.
+		#[pallet::constant]
+		type MaxHeadsReorgDepth: Get<Option<u32>>;
+
+		/// The distance between bridged relay chain blocks at which parachain heads may be
+		/// imported for free.
+		///
+		/// If it is `None`, all parachain head updates are paid. Otherwise, relayer may import
+		/// one free head of every parachain every `FreeHeadsInterval` relay chain blocks. It may
+		/// be used to bound relayer costs at low-traffic bridges.
+		#[pallet::constant]
+		type FreeHeadsInterval: Get<Option<u32>>;
+
+		/// Currency used to reserve parachain registration deposits.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// Deposit that is reserved when parachain is registered with the
+		/// `register_parachain_with_deposit` call.
+		///
+		/// If it is `None`, parachains may only be registered by the pallet owner or root.
+		#[pallet::constant]
+		type ParachainRegistrationDeposit: Get<Option<BalanceOf<Self, I>>>;
 	}
 
 	/// Optional pallet owner.
@@ -236,6 +340,46 @@ pub mod pallet {
 	pub(super) type ImportedParaHashes<T: Config<I>, I: 'static = ()> =
 		StorageDoubleMap<_, Blake2_128Concat, ParaId, Twox64Concat, u32, ParaHash>;
 
+	/// Configurations of parachains, registered by the `register_parachain` call.
+	///
+	/// Parachains without registered configuration are tracked using the pallet-level
+	/// `HeadsToKeep` and `MaxParaHeadDataSize` values.
+	#[pallet::storage]
+	pub type ParachainConfigs<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, ParaId, ParachainConfig, OptionQuery>;
+
+	/// Requests of parachain heads.
+	///
+	/// The value is the number of relay chain block, at (or after) which the head of the parachain
+	/// is wanted. Relayers may use it to deliver heads on demand. The request is removed when
+	/// the head is imported.
+	#[pallet::storage]
+	pub type RequestedParachainHeads<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		<RequestedParachainHeadsKeyProvider as StorageMapKeyProvider>::Hasher,
+		<RequestedParachainHeadsKeyProvider as StorageMapKeyProvider>::Key,
+		<RequestedParachainHeadsKeyProvider as StorageMapKeyProvider>::Value,
+	>;
+
+	/// Number of pins of imported parachain heads.
+	///
+	/// Pinned heads are not pruned until they're unpinned (see `HeaderChainPinning`).
+	#[pallet::storage]
+	pub type PinnedParaHeads<T: Config<I>, I: 'static = ()> =
+		StorageDoubleMap<_, Blake2_128Concat, ParaId, Blake2_128Concat, ParaHash, u32, ValueQuery>;
+
+	/// Pinned parachain heads that should have been pruned. They're pruned when the last pin
+	/// is removed.
+	#[pallet::storage]
+	pub type PrunedPinnedParaHeads<T: Config<I>, I: 'static = ()> =
+		StorageDoubleMap<_, Blake2_128Concat, ParaId, Blake2_128Concat, ParaHash, (), OptionQuery>;
+
+	/// Accounts that have registered parachains with the `register_parachain_with_deposit`
+	/// call and their reserved deposits.
+	#[pallet::storage]
+	pub type ParachainRegistrants<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, ParaId, (T::AccountId, BalanceOf<T, I>), OptionQuery>;
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
@@ -248,24 +392,18 @@ pub mod pallet {
 	}
 
 	#[pallet::call]
-	impl<T: Config<I>, I: 'static> Pallet<T, I>
-	where
-		<T as pallet_bridge_grandpa::Config<T::BridgesGrandpaPalletInstance>>::BridgedChain:
-			bp_runtime::Chain<
-				BlockNumber = RelayBlockNumber,
-				Hash = RelayBlockHash,
-				Hasher = RelayBlockHasher,
-			>,
-	{
+	impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		/// Submit proof of one or several parachain heads.
 		///
 		/// The proof is supposed to be proof of some `Heads` entries from the
 		/// `polkadot-runtime-parachains::paras` pallet instance, deployed at the bridged chain.
 		/// The proof is supposed to be crafted at the `relay_header_hash` that must already be
-		/// imported by corresponding GRANDPA pallet at this chain.
+		/// imported by the `RelayChainHeaders` pallet at this chain.
+		///
+		/// The transaction is free if every submitted head is imported for free (see
+		/// `FreeHeadsInterval`).
 		#[pallet::call_index(0)]
-		#[pallet::weight(WeightInfoOf::<T, I>::submit_parachain_heads_weight(
-			T::DbWeight::get(),
+		#[pallet::weight(Pallet::<T, I>::submit_parachain_heads_weight(
 			parachain_heads_proof,
 			parachains.len() as _,
 		))]
@@ -279,55 +417,53 @@ pub mod pallet {
 
 			// we'll need relay chain header to verify that parachains heads are always increasing.
 			let (relay_block_number, relay_block_hash) = at_relay_block;
-			let relay_block = pallet_bridge_grandpa::ImportedHeaders::<
-				T,
-				T::BridgesGrandpaPalletInstance,
-			>::get(relay_block_hash)
-			.ok_or(Error::<T, I>::UnknownRelayChainBlock)?;
+			let relay_block_number_at_chain =
+				T::RelayChainHeaders::finalized_header_number(relay_block_hash)
+					.ok_or(Error::<T, I>::UnknownRelayChainBlock)?;
 			ensure!(
-				relay_block.number == relay_block_number,
+				relay_block_number_at_chain == relay_block_number,
 				Error::<T, I>::InvalidRelayChainBlockNumber,
 			);
 
 			// now parse storage proof and read parachain heads
-			let mut actual_weight = WeightInfoOf::<T, I>::submit_parachain_heads_weight(
-				T::DbWeight::get(),
-				&parachain_heads_proof,
-				parachains.len() as _,
-			);
+			let parachains_count = parachains.len() as u32;
+			let mut actual_weight =
+				Self::submit_parachain_heads_weight(&parachain_heads_proof, parachains_count);
 
-			pallet_bridge_grandpa::Pallet::<T, T::BridgesGrandpaPalletInstance>::parse_finalized_storage_proof(
+			let artifacts = T::RelayChainHeaders::parse_finalized_storage_proof(
 				relay_block_hash,
 				sp_trie::StorageProof::new(parachain_heads_proof.0),
 				move |storage| {
+					let mut artifacts = SubmitParachainHeadsArtifacts::default();
 					for (parachain, parachain_head_hash) in parachains {
-						let parachain_head = match Pallet::<T, I>::read_parachain_head(&storage, parachain) {
-							Ok(Some(parachain_head)) => parachain_head,
-							Ok(None) => {
-								log::trace!(
-									target: LOG_TARGET,
-									"The head of parachain {:?} is None. {}",
-									parachain,
-									if ParasInfo::<T, I>::contains_key(parachain) {
-										"Looks like it is not yet registered at the source relay chain"
-									} else {
-										"Looks like it has been deregistered from the source relay chain"
-									},
-								);
-								Self::deposit_event(Event::MissingParachainHead { parachain });
-								continue;
-							},
-							Err(e) => {
-								log::trace!(
-									target: LOG_TARGET,
-									"The read of head of parachain {:?} has failed: {:?}",
-									parachain,
-									e,
-								);
-								Self::deposit_event(Event::MissingParachainHead { parachain });
-								continue;
-							},
-						};
+						let parachain_head =
+							match Pallet::<T, I>::read_parachain_head(&storage, parachain) {
+								Ok(Some(parachain_head)) => parachain_head,
+								Ok(None) => {
+									log::trace!(
+										target: LOG_TARGET,
+										"The head of parachain {:?} is None. {}",
+										parachain,
+										if ParasInfo::<T, I>::contains_key(parachain) {
+											"Looks like it is not yet registered at the source relay chain"
+										} else {
+											"Looks like it has been deregistered from the source relay chain"
+										},
+									);
+									Self::deposit_event(Event::MissingParachainHead { parachain });
+									continue;
+								},
+								Err(e) => {
+									log::trace!(
+										target: LOG_TARGET,
+										"The read of head of parachain {:?} has failed: {:?}",
+										parachain,
+										e,
+									);
+									Self::deposit_event(Event::MissingParachainHead { parachain });
+									continue;
+								},
+							};
 
 						// if relayer has specified invalid parachain head hash, ignore the head
 						// (this isn't strictly necessary, but better safe than sorry)
@@ -349,7 +485,10 @@ pub mod pallet {
 						}
 
 						// convert from parachain head into stored parachain head data
-						let parachain_head_data = match T::ParaStoredHeaderDataBuilder::try_build(parachain, &parachain_head) {
+						let parachain_head_data = match T::ParaStoredHeaderDataBuilder::try_build(
+							parachain,
+							&parachain_head,
+						) {
 							Some(parachain_head_data) => parachain_head_data,
 							None => {
 								log::trace!(
@@ -357,40 +496,113 @@ pub mod pallet {
 									"The head of parachain {:?} has been provided, but it is not tracked by the pallet",
 									parachain,
 								);
-								Self::deposit_event(Event::UntrackedParachainRejected { parachain });
+								Self::deposit_event(Event::UntrackedParachainRejected {
+									parachain,
+								});
 								continue;
 							},
 						};
 
-						let update_result: Result<_, ()> = ParasInfo::<T, I>::try_mutate(parachain, |stored_best_head| {
-							let artifacts = Pallet::<T, I>::update_parachain_head(
+						// reject heads of parachains that are not registered if we only accept
+						// registered parachains
+						let maybe_parachain_config = ParachainConfigs::<T, I>::get(parachain);
+						if maybe_parachain_config.is_none() && T::OnlyRegisteredParachains::get() {
+							log::trace!(
+								target: LOG_TARGET,
+								"The head of parachain {:?} has been provided, but the parachain is not registered",
+								parachain,
+							);
+							Self::deposit_event(Event::UntrackedParachainRejected { parachain });
+							continue;
+						}
+
+						// reject heads of parachains that are deactivated by the governance
+						let parachain_config = maybe_parachain_config
+							.unwrap_or_else(Pallet::<T, I>::default_parachain_config);
+						if !parachain_config.is_active {
+							log::trace!(
+								target: LOG_TARGET,
+								"The head of parachain {:?} has been provided, but the parachain is inactive",
+								parachain,
+							);
+							Self::deposit_event(Event::RejectedInactiveParachainHead {
 								parachain,
-								stored_best_head.take(),
-								relay_block_number,
-								parachain_head_data,
 								parachain_head_hash,
-							)?;
-							*stored_best_head = Some(artifacts.best_head);
-							Ok(artifacts.prune_happened)
-						});
+							});
+							continue;
+						}
 
-						// we're refunding weight if update has not happened and if pruning has not happened
-						let is_update_happened = matches!(update_result, Ok(_));
-						if !is_update_happened {
-							actual_weight = actual_weight
-								.saturating_sub(WeightInfoOf::<T, I>::parachain_head_storage_write_weight(T::DbWeight::get()));
+						// reject heads that are not descendants of recently imported heads
+						if let Some(max_reorg_depth) = T::MaxHeadsReorgDepth::get() {
+							let is_descendant = Pallet::<T, I>::is_descendant_of_imported_head(
+								parachain,
+								&parachain_config,
+								&parachain_head,
+								max_reorg_depth,
+							);
+							if !is_descendant {
+								log::trace!(
+									target: LOG_TARGET,
+									"The head {:?} of parachain {:?} is not a descendant of recently imported heads",
+									parachain_head_hash,
+									parachain,
+								);
+								Self::deposit_event(Event::RejectedNonDescendantParachainHead {
+									parachain,
+									parachain_head_hash,
+								});
+								continue;
+							}
 						}
-						let is_prune_happened = matches!(update_result, Ok(true));
-						if !is_prune_happened {
-							actual_weight = actual_weight
-								.saturating_sub(WeightInfoOf::<T, I>::parachain_head_pruning_weight(T::DbWeight::get()));
+
+						let update_result: Result<_, ()> =
+							ParasInfo::<T, I>::try_mutate(parachain, |stored_best_head| {
+								let is_free_head = Pallet::<T, I>::is_free_parachain_head(
+									stored_best_head,
+									relay_block_number,
+								);
+								let artifacts = Pallet::<T, I>::update_parachain_head(
+									parachain,
+									&parachain_config,
+									stored_best_head.take(),
+									relay_block_number,
+									parachain_head_data,
+									parachain_head_hash,
+								)?;
+								*stored_best_head = Some(artifacts.best_head);
+								Ok((artifacts.prune_happened, is_free_head))
+							});
+
+						if let Ok((is_prune_happened, is_free_head)) = update_result {
+							artifacts.updated_heads += 1;
+							artifacts.pruned_heads += is_prune_happened as u32;
+							artifacts.free_heads += is_free_head as u32;
 						}
 					}
+
+					artifacts
 				},
 			)
 			.map_err(|_| Error::<T, I>::InvalidStorageProof)?;
 
-			Ok(PostDispatchInfo { actual_weight: Some(actual_weight), pays_fee: Pays::Yes })
+			// we're refunding weight of heads that have not been updated (they're either missing
+			// from the proof, or rejected for some reason) and of prunings that have not happened
+			let db_weight = T::DbWeight::get();
+			actual_weight = actual_weight
+				.saturating_sub(
+					WeightInfoOf::<T, I>::parachain_head_storage_write_weight(db_weight)
+						.saturating_mul((parachains_count - artifacts.updated_heads) as u64),
+				)
+				.saturating_sub(
+					WeightInfoOf::<T, I>::parachain_head_pruning_weight(db_weight)
+						.saturating_mul((parachains_count - artifacts.pruned_heads) as u64),
+				);
+
+			// the transaction is only free if every submitted head is imported for free
+			let is_free = artifacts.free_heads != 0 && artifacts.free_heads == parachains_count;
+			let pays_fee = if is_free { Pays::No } else { Pays::Yes };
+
+			Ok(PostDispatchInfo { actual_weight: Some(actual_weight), pays_fee })
 		}
 
 		/// Change `PalletOwner`.
@@ -413,6 +625,154 @@ pub mod pallet {
 		) -> DispatchResult {
 			<Self as OwnedBridgeModule<_>>::set_operating_mode(origin, operating_mode)
 		}
+
+		/// Register or update configuration of the given parachain.
+		///
+		/// May only be called either by root, or by `PalletOwner`.
+		///
+		/// Keep in mind that lowering `heads_to_keep` of already tracked parachain may lead to
+		/// orphan entries in the runtime storage.
+		#[pallet::call_index(3)]
+		#[pallet::weight((T::DbWeight::get().reads_writes(1, 1), DispatchClass::Operational))]
+		pub fn register_parachain(
+			origin: OriginFor<T>,
+			parachain: ParaId,
+			config: ParachainConfig,
+		) -> DispatchResult {
+			Self::ensure_owner_or_root(origin)?;
+			ensure!(
+				config.heads_to_keep != 0 && config.max_head_size <= T::MaxParaHeadDataSize::get(),
+				Error::<T, I>::InvalidParachainConfig,
+			);
+
+			ParachainConfigs::<T, I>::insert(parachain, config);
+			log::info!(
+				target: LOG_TARGET,
+				"Registered configuration of parachain {:?}: {:?}",
+				parachain,
+				config,
+			);
+			Self::deposit_event(Event::ParachainRegistered { parachain, config });
+
+			Ok(())
+		}
+
+		/// Register parachain with the default (pallet-level) configuration, reserving the
+		/// `ParachainRegistrationDeposit` from the caller account.
+		///
+		/// The deposit is returned when parachain is deregistered. Parachains that are already
+		/// tracked by the pallet (i.e. have imported heads) can't be registered with deposit,
+		/// because otherwise the registrant would be able to prune their heads by deregistering
+		/// them.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(4, 3))]
+		pub fn register_parachain_with_deposit(
+			origin: OriginFor<T>,
+			parachain: ParaId,
+		) -> DispatchResult {
+			let registrant = ensure_signed(origin)?;
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+			let deposit = T::ParachainRegistrationDeposit::get()
+				.ok_or(Error::<T, I>::RegistrationWithDepositDisabled)?;
+			ensure!(
+				!ParachainConfigs::<T, I>::contains_key(parachain),
+				Error::<T, I>::ParachainAlreadyRegistered,
+			);
+			ensure!(
+				!ParasInfo::<T, I>::contains_key(parachain),
+				Error::<T, I>::ParachainAlreadyTracked,
+			);
+
+			T::Currency::reserve(&registrant, deposit)
+				.map_err(|_| Error::<T, I>::FailedToReserveDeposit)?;
+			ParachainConfigs::<T, I>::insert(parachain, Self::default_parachain_config());
+			ParachainRegistrants::<T, I>::insert(parachain, (registrant.clone(), deposit));
+			log::info!(
+				target: LOG_TARGET,
+				"Parachain {:?} has been registered by {:?} with deposit {:?}",
+				parachain,
+				registrant,
+				deposit,
+			);
+			Self::deposit_event(Event::ParachainRegisteredWithDeposit {
+				parachain,
+				registrant,
+				deposit,
+			});
+
+			Ok(())
+		}
+
+		/// Deregister parachain, returning the registration deposit (if any) to the registrant.
+		///
+		/// May only be called either by root, or by `PalletOwner`, or by the account that has
+		/// registered parachain with the `register_parachain_with_deposit` call.
+		///
+		/// If the pallet only accepts heads of registered parachains (see
+		/// `OnlyRegisteredParachains`), imported heads of the parachain are pruned. Otherwise heads
+		/// have not been imported because of the registration, so they're kept. At most
+		/// `HeadsToKeep` heads are pruned, so if parachain has been registered with larger
+		/// `heads_to_keep`, remaining heads become orphan entries in the runtime storage. Pinned
+		/// heads are pruned when they're unpinned.
+		#[pallet::call_index(5)]
+		#[pallet::weight(
+			T::DbWeight::get()
+				.reads_writes(3, 5)
+				.saturating_add(
+					T::DbWeight::get().reads_writes(2, 2).saturating_mul(T::HeadsToKeep::get() as u64)
+				)
+		)]
+		pub fn deregister_parachain(origin: OriginFor<T>, parachain: ParaId) -> DispatchResult {
+			let registrant = ParachainRegistrants::<T, I>::get(parachain);
+			if Self::ensure_owner_or_root(origin.clone()).is_err() {
+				let caller = ensure_signed(origin)?;
+				ensure!(
+					registrant.as_ref().map(|(registrant, _)| registrant) == Some(&caller),
+					DispatchError::BadOrigin,
+				);
+			}
+			ensure!(
+				ParachainConfigs::<T, I>::contains_key(parachain),
+				Error::<T, I>::ParachainNotRegistered,
+			);
+
+			ParachainConfigs::<T, I>::remove(parachain);
+			if let Some((registrant, deposit)) = registrant {
+				ParachainRegistrants::<T, I>::remove(parachain);
+				T::Currency::unreserve(&registrant, deposit);
+			}
+			// heads of unregistered parachains are imported anyway, so we only prune heads if they
+			// have been imported because of the registration
+			if T::OnlyRegisteredParachains::get() {
+				Self::prune_parachain_heads(parachain);
+			}
+			log::info!(target: LOG_TARGET, "Parachain {:?} has been deregistered", parachain);
+			Self::deposit_event(Event::ParachainDeregistered { parachain });
+
+			Ok(())
+		}
+
+		/// Request head of the parachain, read at (or after) given relay chain block.
+		///
+		/// It may be called e.g. when outbound lane at the parachain has undelivered messages,
+		/// to let relayers know which parachain head needs to be delivered to this chain.
+		#[pallet::call_index(6)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(3, 1))]
+		pub fn request_parachain_head(
+			origin: OriginFor<T>,
+			parachain: ParaId,
+			at_relay_block_number: RelayBlockNumber,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::ensure_not_halted().map_err(Error::<T, I>::BridgeModule)?;
+			ensure!(
+				ParasInfo::<T, I>::contains_key(parachain) ||
+					ParachainConfigs::<T, I>::contains_key(parachain),
+				Error::<T, I>::UntrackedParachain,
+			);
+
+			Self::request_parachain_head_at(parachain, at_relay_block_number)
+		}
 	}
 
 	impl<T: Config<I>, I: 'static> Pallet<T, I> {
@@ -421,6 +781,20 @@ pub mod pallet {
 			ParasInfo::<T, I>::get(parachain)
 		}
 
+		/// Get best finalized head hashes of all tracked parachains, along with numbers of relay
+		/// chain blocks where these heads have been read.
+		pub fn best_parachain_heads() -> Vec<(ParaId, ParaHash, RelayBlockNumber)> {
+			ParasInfo::<T, I>::iter()
+				.map(|(parachain, info)| {
+					(
+						parachain,
+						info.best_head_hash.head_hash,
+						info.best_head_hash.at_relay_block_number,
+					)
+				})
+				.collect()
+		}
+
 		/// Get best finalized head data of the given parachain.
 		pub fn best_parachain_head(parachain: ParaId) -> Option<ParaStoredHeaderData> {
 			let best_para_head_hash = ParasInfo::<T, I>::get(parachain)?.best_head_hash.head_hash;
@@ -435,6 +809,13 @@ pub mod pallet {
 		/// Get best finalized head id of the given parachain.
 		pub fn best_parachain_head_id<C: Chain<Hash = ParaHash> + Parachain>(
 		) -> Result<Option<HeaderIdOf<C>>, codec::Error> {
+			Self::best_parachain_head_id_with_state_root::<C>()
+				.map(|head| head.map(|(head_id, _)| head_id))
+		}
+
+		/// Get best finalized head id of the given parachain and its state root.
+		pub fn best_parachain_head_id_with_state_root<C: Chain<Hash = ParaHash> + Parachain>(
+		) -> Result<Option<(HeaderIdOf<C>, ParaHash)>, codec::Error> {
 			let parachain = ParaId(C::PARACHAIN_ID);
 			let best_head_hash = match Self::best_parachain_head_hash(parachain) {
 				Some(best_head_hash) => best_head_hash,
@@ -446,7 +827,102 @@ pub mod pallet {
 			};
 			encoded_head
 				.decode_parachain_head_data::<C>()
-				.map(|data| Some(HeaderId(data.number, best_head_hash)))
+				.map(|data| Some((HeaderId(data.number, best_head_hash), data.state_root)))
+		}
+
+		/// Returns weight of the `submit_parachain_heads` call.
+		fn submit_parachain_heads_weight(
+			parachain_heads_proof: &ParaHeadsProof,
+			parachains_count: u32,
+		) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let ancestry_verification_weight = T::MaxHeadsReorgDepth::get()
+				.map(|max_reorg_depth| {
+					WeightInfoOf::<T, I>::parachain_head_ancestry_verification_weight(
+						db_weight,
+						max_reorg_depth,
+					)
+					.saturating_mul(parachains_count as u64)
+				})
+				.unwrap_or_else(Weight::zero);
+			WeightInfoOf::<T, I>::submit_parachain_heads_weight(
+				db_weight,
+				parachain_heads_proof,
+				parachains_count,
+			)
+			.saturating_add(ancestry_verification_weight)
+		}
+
+		/// Returns `true` if parent of given parachain head is either the best known head of the
+		/// parachain, or one of `max_reorg_depth` heads that have been imported before the best
+		/// head. Also returns `true` if there are no known heads of the parachain.
+		fn is_descendant_of_imported_head(
+			parachain: ParaId,
+			parachain_config: &ParachainConfig,
+			parachain_head: &ParaHead,
+			max_reorg_depth: u32,
+		) -> bool {
+			let stored_best_head = match ParasInfo::<T, I>::get(parachain) {
+				Some(stored_best_head) => stored_best_head,
+				None => return true,
+			};
+			let parent_hash = match T::ParaStoredHeaderDataBuilder::try_extract_parent_hash(
+				parachain,
+				parachain_head,
+			) {
+				Some(parent_hash) => parent_hash,
+				None => return false,
+			};
+			if parent_hash == stored_best_head.best_head_hash.head_hash {
+				return true
+			}
+
+			// the best head is stored right before the `next_imported_hash_position` and its
+			// ancestors are stored at preceding positions of the ring buffer
+			let heads_to_keep = parachain_config.heads_to_keep;
+			let max_reorg_depth =
+				sp_std::cmp::min(max_reorg_depth, heads_to_keep.saturating_sub(1));
+			let next_position =
+				stored_best_head.next_imported_hash_position % heads_to_keep.max(1) + heads_to_keep;
+			(1..=max_reorg_depth).any(|depth| {
+				let position = (next_position - 1 - depth) % heads_to_keep;
+				ImportedParaHashes::<T, I>::get(parachain, position) == Some(parent_hash)
+			})
+		}
+
+		/// Returns true if the parachain head update is free, according to the
+		/// `FreeHeadsInterval`.
+		fn is_free_parachain_head(
+			stored_best_head: &Option<ParaInfo>,
+			updated_at_relay_block_number: RelayBlockNumber,
+		) -> bool {
+			let stored_best_head = match stored_best_head {
+				Some(stored_best_head) => stored_best_head,
+				None => return false,
+			};
+			T::FreeHeadsInterval::get().map_or(false, |interval| {
+				updated_at_relay_block_number >=
+					stored_best_head
+						.best_head_hash
+						.at_relay_block_number
+						.saturating_add(interval)
+			})
+		}
+
+		/// Get configuration of the given parachain.
+		///
+		/// If configuration has not been registered, the pallet-level configuration is returned.
+		pub fn parachain_config(parachain: ParaId) -> ParachainConfig {
+			ParachainConfigs::<T, I>::get(parachain).unwrap_or_else(Self::default_parachain_config)
+		}
+
+		/// Get pallet-level parachain configuration.
+		pub fn default_parachain_config() -> ParachainConfig {
+			ParachainConfig {
+				max_head_size: T::MaxParaHeadDataSize::get(),
+				heads_to_keep: T::HeadsToKeep::get(),
+				is_active: true,
+			}
 		}
 
 		/// Get parachain head data with given hash.
@@ -454,6 +930,91 @@ pub mod pallet {
 			ImportedParaHeads::<T, I>::get(parachain, hash).map(|h| h.into_inner())
 		}
 
+		/// Request head of the parachain, read at (or after) given relay chain block.
+		///
+		/// If the head has been already requested at the later relay chain block, the request
+		/// is left unchanged.
+		pub fn request_parachain_head_at(
+			parachain: ParaId,
+			at_relay_block_number: RelayBlockNumber,
+		) -> DispatchResult {
+			let best_head_relay_block_number = ParasInfo::<T, I>::get(parachain)
+				.map(|info| info.best_head_hash.at_relay_block_number);
+			ensure!(
+				best_head_relay_block_number
+					.map_or(true, |best_number| best_number < at_relay_block_number),
+				Error::<T, I>::ParachainHeadAlreadyImported,
+			);
+
+			RequestedParachainHeads::<T, I>::mutate(parachain, |requested| {
+				*requested =
+					Some(sp_std::cmp::max(requested.unwrap_or_default(), at_relay_block_number));
+			});
+			log::trace!(
+				target: LOG_TARGET,
+				"Head of parachain {:?} has been requested at relay block {}",
+				parachain,
+				at_relay_block_number,
+			);
+			Self::deposit_event(Event::ParachainHeadRequested { parachain, at_relay_block_number });
+
+			Ok(())
+		}
+
+		/// Remove all imported heads of the deregistered parachain from the storage.
+		///
+		/// At most `HeadsToKeep` heads are pruned. Pinned heads are pruned when the last pin is
+		/// removed.
+		fn prune_parachain_heads(parachain: ParaId) {
+			ParasInfo::<T, I>::remove(parachain);
+			RequestedParachainHeads::<T, I>::remove(parachain);
+			for head_hash in ImportedParaHashes::<T, I>::drain_prefix(parachain)
+				.map(|(_, head_hash)| head_hash)
+				.take(T::HeadsToKeep::get() as usize)
+			{
+				if PinnedParaHeads::<T, I>::contains_key(parachain, head_hash) {
+					PrunedPinnedParaHeads::<T, I>::insert(parachain, head_hash, ());
+				} else {
+					ImportedParaHeads::<T, I>::remove(parachain, head_hash);
+				}
+			}
+		}
+
+		/// Pin imported parachain head, so that it isn't pruned until it is unpinned.
+		///
+		/// Returns `false` if the head is unknown.
+		pub fn pin_parachain_head(parachain: ParaId, hash: ParaHash) -> bool {
+			if !ImportedParaHeads::<T, I>::contains_key(parachain, hash) {
+				return false
+			}
+
+			PinnedParaHeads::<T, I>::mutate(parachain, hash, |pins| *pins = pins.saturating_add(1));
+			true
+		}
+
+		/// Unpin parachain head that has been pinned with `pin_parachain_head`.
+		///
+		/// If the head should have been pruned while it has been pinned, it is pruned when
+		/// the last pin is removed.
+		pub fn unpin_parachain_head(parachain: ParaId, hash: ParaHash) {
+			let pins = PinnedParaHeads::<T, I>::get(parachain, hash).saturating_sub(1);
+			if pins != 0 {
+				PinnedParaHeads::<T, I>::insert(parachain, hash, pins);
+				return
+			}
+
+			PinnedParaHeads::<T, I>::remove(parachain, hash);
+			if PrunedPinnedParaHeads::<T, I>::take(parachain, hash).is_some() {
+				log::trace!(
+					target: LOG_TARGET,
+					"Pruning unpinned old head of parachain {:?}: {}",
+					parachain,
+					hash,
+				);
+				ImportedParaHeads::<T, I>::remove(parachain, hash);
+			}
+		}
+
 		/// Read parachain head from storage proof.
 		fn read_parachain_head(
 			storage: &bp_runtime::StorageProofChecker<RelayBlockHasher>,
@@ -515,6 +1076,7 @@ pub mod pallet {
 		/// Try to update parachain head.
 		pub(super) fn update_parachain_head(
 			parachain: ParaId,
+			parachain_config: &ParachainConfig,
 			stored_best_head: Option<ParaInfo>,
 			updated_at_relay_block_number: RelayBlockNumber,
 			updated_head_data: ParaStoredHeaderData,
@@ -538,6 +1100,27 @@ pub mod pallet {
 				return Err(())
 			}
 
+			// verify that the parachain head data size is <= configured maximal size
+			let updated_head_data_size = updated_head_data.encoded_size();
+			if updated_head_data_size > parachain_config.max_head_size as usize {
+				log::trace!(
+					target: LOG_TARGET,
+					"{}. The parachain head data size for {:?} is {}. It exceeds maximal configured size {}.",
+					err_log_prefix,
+					parachain,
+					updated_head_data_size,
+					parachain_config.max_head_size,
+				);
+
+				Self::deposit_event(Event::RejectedLargeParachainHead {
+					parachain,
+					parachain_head_hash: updated_head_hash,
+					parachain_head_size: updated_head_data_size as _,
+				});
+
+				return Err(())
+			}
+
 			// verify that the parachain head data size is <= `MaxParaHeadDataSize`
 			let updated_head_data =
 				match StoredParaHeadDataOf::<T, I>::try_from_inner(updated_head_data) {
@@ -574,7 +1157,7 @@ pub mod pallet {
 					head_hash: updated_head_hash,
 				},
 				next_imported_hash_position: (next_imported_hash_position + 1) %
-					T::HeadsToKeep::get(),
+					parachain_config.heads_to_keep,
 			};
 			ImportedParaHashes::<T, I>::insert(
 				parachain,
@@ -589,16 +1172,33 @@ pub mod pallet {
 				updated_head_hash,
 			);
 
+			// remove head request if it is satisfied
+			RequestedParachainHeads::<T, I>::mutate_exists(parachain, |requested| {
+				if requested.map_or(false, |requested| requested <= updated_at_relay_block_number) {
+					*requested = None;
+				}
+			});
+
 			// remove old head
 			let prune_happened = head_hash_to_prune.is_ok();
 			if let Ok(head_hash_to_prune) = head_hash_to_prune {
-				log::trace!(
-					target: LOG_TARGET,
-					"Pruning old head of parachain {:?}: {}",
-					parachain,
-					head_hash_to_prune,
-				);
-				ImportedParaHeads::<T, I>::remove(parachain, head_hash_to_prune);
+				if PinnedParaHeads::<T, I>::contains_key(parachain, head_hash_to_prune) {
+					log::trace!(
+						target: LOG_TARGET,
+						"Postponing pruning of pinned old head of parachain {:?}: {}",
+						parachain,
+						head_hash_to_prune,
+					);
+					PrunedPinnedParaHeads::<T, I>::insert(parachain, head_hash_to_prune, ());
+				} else {
+					log::trace!(
+						target: LOG_TARGET,
+						"Pruning old head of parachain {:?}: {}",
+						parachain,
+						head_hash_to_prune,
+					);
+					ImportedParaHeads::<T, I>::remove(parachain, head_hash_to_prune);
+				}
 			}
 			Self::deposit_event(Event::UpdatedParachainHead {
 				parachain,
@@ -652,20 +1252,39 @@ impl<T: Config<I>, I: 'static, C: Parachain<Hash = ParaHash>> HeaderChain<C>
 			.and_then(|head| head.decode_parachain_head_data::<C>().ok())
 			.map(|h| h.state_root)
 	}
+
+	fn finalized_header_number(hash: HashOf<C>) -> Option<BlockNumberOf<C>> {
+		Pallet::<T, I>::parachain_head(ParaId(C::PARACHAIN_ID), hash)
+			.and_then(|head| head.decode_parachain_head_data::<C>().ok())
+			.map(|h| h.number)
+	}
+}
+
+impl<T: Config<I>, I: 'static, C: Parachain<Hash = ParaHash>> HeaderChainPinning<C>
+	for ParachainHeaders<T, I, C>
+{
+	fn pin_finalized_header(hash: HashOf<C>) -> bool {
+		Pallet::<T, I>::pin_parachain_head(ParaId(C::PARACHAIN_ID), hash)
+	}
+
+	fn unpin_finalized_header(hash: HashOf<C>) {
+		Pallet::<T, I>::unpin_parachain_head(ParaId(C::PARACHAIN_ID), hash)
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use crate::mock::{
-		run_test, test_relay_header, BigParachainHeader, RegularParachainHasher,
-		RegularParachainHeader, RuntimeEvent as TestEvent, RuntimeOrigin, TestRuntime,
-		PARAS_PALLET_NAME, UNTRACKED_PARACHAIN_ID,
+		run_test, test_relay_header, Balances, BigParachainHeader, Parachain1,
+		RegularParachainHasher, RegularParachainHeader, RuntimeEvent as TestEvent, RuntimeOrigin,
+		TestRuntime, PARAS_PALLET_NAME, UNTRACKED_PARACHAIN_ID,
 	};
 	use codec::Encode;
 
 	use bp_parachains::{
 		BestParaHeadHash, BridgeParachainCall, ImportedParaHeadsKeyProvider, ParasInfoKeyProvider,
+		RequestedParachainHeadsKeyProvider,
 	};
 	use bp_runtime::{
 		record_all_trie_keys, BasicOperatingMode, OwnedBridgeModuleError,
@@ -676,9 +1295,9 @@ mod tests {
 	};
 	use frame_support::{
 		assert_noop, assert_ok,
-		dispatch::DispatchResultWithPostInfo,
+		dispatch::{DispatchResultWithPostInfo, Pays},
 		storage::generator::{StorageDoubleMap, StorageMap},
-		traits::{Get, OnInitialize},
+		traits::{Currency, Get, OnInitialize, ReservableCurrency},
 		weights::Weight,
 	};
 	use frame_system::{EventRecord, Pallet as System, Phase};
@@ -770,12 +1389,25 @@ mod tests {
 		)
 	}
 
-	fn stored_head_data(parachain: u32, head_number: u32) -> ParaStoredHeaderData {
-		ParaStoredHeaderData(
-			(head_number as u64, RegularParachainHasher::hash(&(parachain, head_number).encode()))
-				.encode(),
-		)
-	}
+	fn head_data_with_parent(parachain: u32, head_number: u32, parent_hash: ParaHash) -> ParaHead {
+		ParaHead(
+			RegularParachainHeader::new(
+				head_number as _,
+				Default::default(),
+				RegularParachainHasher::hash(&(parachain, head_number).encode()),
+				parent_hash,
+				Default::default(),
+			)
+			.encode(),
+		)
+	}
+
+	fn stored_head_data(parachain: u32, head_number: u32) -> ParaStoredHeaderData {
+		ParaStoredHeaderData(
+			(head_number as u64, RegularParachainHasher::hash(&(parachain, head_number).encode()))
+				.encode(),
+		)
+	}
 
 	fn big_head_data(parachain: u32, head_number: u32) -> ParaHead {
 		ParaHead(
@@ -865,7 +1497,11 @@ mod tests {
 
 			// we're trying to update heads of parachains 1, 2 and 3
 			let expected_weight =
-				WeightInfo::submit_parachain_heads_weight(DbWeight::get(), &proof, 2);
+				WeightInfo::submit_parachain_heads_weight(DbWeight::get(), &proof, 2)
+					.saturating_sub(
+						WeightInfo::parachain_head_pruning_weight(DbWeight::get())
+							.saturating_mul(2),
+					);
 			let result = Pallet::<TestRuntime>::submit_parachain_heads(
 				RuntimeOrigin::signed(1),
 				(0, test_relay_header(0, state_root).hash()),
@@ -1024,6 +1660,34 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn refunds_weight_of_skipped_parachain_heads() {
+		let (state_root_0, proof_0, parachains_0) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5)), (3, head_data(3, 5))]);
+		let (state_root_1, proof_1, parachains_1) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5)), (3, head_data(3, 6))]);
+		run_test(|| {
+			initialize(state_root_0);
+			assert_ok!(import_parachain_1_head(0, state_root_0, parachains_0, proof_0));
+
+			// head of parachain#1 is obsolete, so we refund its storage write weight and
+			// pruning weight of both heads (since nothing has been pruned)
+			proceed(1, state_root_1);
+			let expected_weight =
+				WeightInfo::submit_parachain_heads_weight(DbWeight::get(), &proof_1, 2)
+					.saturating_sub(
+						WeightInfo::parachain_head_storage_write_weight(DbWeight::get()),
+					)
+					.saturating_sub(
+						WeightInfo::parachain_head_pruning_weight(DbWeight::get())
+							.saturating_mul(2),
+					);
+			let result = import_parachain_1_head(1, state_root_1, parachains_1, proof_1);
+			assert_ok!(result);
+			assert_eq!(result.expect("checked above").actual_weight, Some(expected_weight));
+		});
+	}
+
 	#[test]
 	fn ignores_untracked_parachain() {
 		let (state_root, proof, parachains) = prepare_parachain_heads_proof(vec![
@@ -1036,9 +1700,13 @@ mod tests {
 			// parachain
 			let expected_weight =
 				WeightInfo::submit_parachain_heads_weight(DbWeight::get(), &proof, 3)
-					.saturating_sub(WeightInfo::parachain_head_storage_write_weight(
-						DbWeight::get(),
-					));
+					.saturating_sub(
+						WeightInfo::parachain_head_storage_write_weight(DbWeight::get()),
+					)
+					.saturating_sub(
+						WeightInfo::parachain_head_pruning_weight(DbWeight::get())
+							.saturating_mul(3),
+					);
 			initialize(state_root);
 			let result = Pallet::<TestRuntime>::submit_parachain_heads(
 				RuntimeOrigin::signed(1),
@@ -1319,6 +1987,659 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn register_parachain_works() {
+		run_test(|| {
+			let config = ParachainConfig { max_head_size: 32, heads_to_keep: 2, is_active: true };
+			assert_eq!(
+				Pallet::<TestRuntime>::parachain_config(ParaId(1)),
+				ParachainConfig {
+					max_head_size: crate::mock::MAXIMAL_PARACHAIN_HEAD_DATA_SIZE,
+					heads_to_keep: crate::mock::HeadsToKeep::get(),
+					is_active: true,
+				},
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::register_parachain(
+					RuntimeOrigin::signed(1),
+					ParaId(1),
+					config
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::register_parachain(
+					RuntimeOrigin::root(),
+					ParaId(1),
+					ParachainConfig { heads_to_keep: 0, ..config },
+				),
+				Error::<TestRuntime>::InvalidParachainConfig,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::register_parachain(
+					RuntimeOrigin::root(),
+					ParaId(1),
+					ParachainConfig {
+						max_head_size: crate::mock::MAXIMAL_PARACHAIN_HEAD_DATA_SIZE + 1,
+						..config
+					},
+				),
+				Error::<TestRuntime>::InvalidParachainConfig,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::register_parachain(
+				RuntimeOrigin::root(),
+				ParaId(1),
+				config
+			));
+			assert_eq!(Pallet::<TestRuntime>::parachain_config(ParaId(1)), config);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Parachains(Event::ParachainRegistered {
+						parachain: ParaId(1),
+						config,
+					}),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn rejects_heads_of_inactive_parachain() {
+		let (state_root, proof, parachains) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5)), (3, head_data(3, 10))]);
+		run_test(|| {
+			assert_ok!(Pallet::<TestRuntime>::register_parachain(
+				RuntimeOrigin::root(),
+				ParaId(1),
+				ParachainConfig { max_head_size: 32, heads_to_keep: 2, is_active: false },
+			));
+			System::<TestRuntime>::reset_events();
+
+			initialize(state_root);
+			assert_ok!(Pallet::<TestRuntime>::submit_parachain_heads(
+				RuntimeOrigin::signed(1),
+				(0, test_relay_header(0, state_root).hash()),
+				parachains,
+				proof,
+			));
+			assert_eq!(ParasInfo::<TestRuntime>::get(ParaId(1)), None);
+			assert!(ParasInfo::<TestRuntime>::get(ParaId(3)).is_some());
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Parachains(Event::RejectedInactiveParachainHead {
+							parachain: ParaId(1),
+							parachain_head_hash: head_data(1, 5).hash(),
+						}),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Parachains(Event::UpdatedParachainHead {
+							parachain: ParaId(3),
+							parachain_head_hash: head_data(3, 10).hash(),
+						}),
+						topics: vec![],
+					},
+				],
+			);
+		});
+	}
+
+	#[test]
+	fn rejects_parachain_head_exceeding_registered_max_head_size() {
+		let (state_root, proof, parachains) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+		run_test(|| {
+			let max_head_size = stored_head_data(1, 5).encoded_size() as u32 - 1;
+			assert_ok!(Pallet::<TestRuntime>::register_parachain(
+				RuntimeOrigin::root(),
+				ParaId(1),
+				ParachainConfig { max_head_size, heads_to_keep: 2, is_active: true },
+			));
+			System::<TestRuntime>::reset_events();
+
+			initialize(state_root);
+			assert_ok!(import_parachain_1_head(0, state_root, parachains, proof));
+			assert_eq!(ParasInfo::<TestRuntime>::get(ParaId(1)), None);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Parachains(Event::RejectedLargeParachainHead {
+						parachain: ParaId(1),
+						parachain_head_hash: head_data(1, 5).hash(),
+						parachain_head_size: max_head_size + 1,
+					}),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn does_not_prune_pinned_heads() {
+		run_test(|| {
+			let heads_to_keep = crate::mock::HeadsToKeep::get();
+			let head_0_hash = head_hash(1, 0);
+
+			// unknown head can't be pinned
+			assert!(!Pallet::<TestRuntime>::pin_parachain_head(ParaId(1), head_0_hash));
+
+			// import head#0 and pin it twice
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+			initialize(state_root);
+			assert_ok!(import_parachain_1_head(0, state_root, parachains, proof));
+			assert!(Pallet::<TestRuntime>::pin_parachain_head(ParaId(1), head_0_hash));
+			assert!(ParachainHeaders::<TestRuntime, (), Parachain1>::pin_finalized_header(
+				head_0_hash
+			));
+
+			// import `HeadsToKeep` more heads, so that head#0 should be pruned
+			for i in 1..=heads_to_keep {
+				let (state_root, proof, parachains) =
+					prepare_parachain_heads_proof(vec![(1, head_data(1, i))]);
+				proceed(i, state_root);
+				assert_ok!(import_parachain_1_head(i, state_root, parachains, proof));
+			}
+
+			// but it is pinned, so it is still there
+			assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_0_hash).is_some());
+
+			// after first unpin, it is still pinned
+			Pallet::<TestRuntime>::unpin_parachain_head(ParaId(1), head_0_hash);
+			assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_0_hash).is_some());
+
+			// and after second unpin it is finally pruned
+			ParachainHeaders::<TestRuntime, (), Parachain1>::unpin_finalized_header(head_0_hash);
+			assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_0_hash).is_none());
+			assert!(!PinnedParaHeads::<TestRuntime>::contains_key(ParaId(1), head_0_hash));
+			assert!(!PrunedPinnedParaHeads::<TestRuntime>::contains_key(ParaId(1), head_0_hash));
+
+			// unpinning head that has not been pruned keeps it in the storage
+			assert!(Pallet::<TestRuntime>::pin_parachain_head(ParaId(1), head_hash(1, 1)));
+			Pallet::<TestRuntime>::unpin_parachain_head(ParaId(1), head_hash(1, 1));
+			assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_hash(1, 1)).is_some());
+		});
+	}
+
+	#[test]
+	fn prunes_old_heads_using_registered_heads_to_keep() {
+		run_test(|| {
+			let heads_to_keep = 2;
+			assert_ok!(Pallet::<TestRuntime>::register_parachain(
+				RuntimeOrigin::root(),
+				ParaId(1),
+				ParachainConfig {
+					max_head_size: crate::mock::MAXIMAL_PARACHAIN_HEAD_DATA_SIZE,
+					heads_to_keep,
+					is_active: true,
+				},
+			));
+
+			// import `heads_to_keep + 1` headers
+			for i in 0..=heads_to_keep {
+				let (state_root, proof, parachains) =
+					prepare_parachain_heads_proof(vec![(1, head_data(1, i))]);
+				if i == 0 {
+					initialize(state_root);
+				} else {
+					proceed(i, state_root);
+				}
+
+				assert_ok!(import_parachain_1_head(i, state_root, parachains, proof));
+			}
+
+			// and the head#0 is pruned
+			assert!(
+				ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_data(1, 0).hash()).is_none()
+			);
+			for i in 1..=heads_to_keep {
+				assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_data(1, i).hash())
+					.is_some());
+			}
+		});
+	}
+
+	#[test]
+	fn register_parachain_with_deposit_works() {
+		run_test(|| {
+			Balances::make_free_balance_be(&1, 150);
+
+			crate::mock::ParachainRegistrationDeposit::set(&None);
+			assert_noop!(
+				Pallet::<TestRuntime>::register_parachain_with_deposit(
+					RuntimeOrigin::signed(1),
+					ParaId(1)
+				),
+				Error::<TestRuntime>::RegistrationWithDepositDisabled,
+			);
+
+			crate::mock::ParachainRegistrationDeposit::set(&Some(100));
+			assert_ok!(Pallet::<TestRuntime>::register_parachain_with_deposit(
+				RuntimeOrigin::signed(1),
+				ParaId(1)
+			));
+			assert_eq!(Balances::reserved_balance(1), 100);
+			assert_eq!(ParachainRegistrants::<TestRuntime>::get(ParaId(1)), Some((1, 100)));
+			assert_eq!(
+				ParachainConfigs::<TestRuntime>::get(ParaId(1)),
+				Some(Pallet::<TestRuntime>::default_parachain_config()),
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::register_parachain_with_deposit(
+					RuntimeOrigin::signed(1),
+					ParaId(1)
+				),
+				Error::<TestRuntime>::ParachainAlreadyRegistered,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::register_parachain_with_deposit(
+					RuntimeOrigin::signed(1),
+					ParaId(2)
+				),
+				Error::<TestRuntime>::FailedToReserveDeposit,
+			);
+		});
+	}
+
+	#[test]
+	fn deregister_parachain_works() {
+		run_test(|| {
+			Balances::make_free_balance_be(&1, 150);
+			assert_ok!(Pallet::<TestRuntime>::register_parachain_with_deposit(
+				RuntimeOrigin::signed(1),
+				ParaId(1)
+			));
+
+			assert_noop!(
+				Pallet::<TestRuntime>::deregister_parachain(RuntimeOrigin::signed(2), ParaId(1)),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::deregister_parachain(RuntimeOrigin::root(), ParaId(2)),
+				Error::<TestRuntime>::ParachainNotRegistered,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::deregister_parachain(
+				RuntimeOrigin::signed(1),
+				ParaId(1)
+			));
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(ParachainRegistrants::<TestRuntime>::get(ParaId(1)), None);
+			assert_eq!(ParachainConfigs::<TestRuntime>::get(ParaId(1)), None);
+		});
+	}
+
+	#[test]
+	fn deregister_parachain_prunes_imported_heads() {
+		run_test(|| {
+			crate::mock::OnlyRegisteredParachains::set(&true);
+			Balances::make_free_balance_be(&1, 150);
+			assert_ok!(Pallet::<TestRuntime>::register_parachain_with_deposit(
+				RuntimeOrigin::signed(1),
+				ParaId(1)
+			));
+
+			// import two heads and pin the first one
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+			initialize(state_root);
+			assert_ok!(import_parachain_1_head(0, state_root, parachains, proof));
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, head_data(1, 1))]);
+			proceed(1, state_root);
+			assert_ok!(import_parachain_1_head(1, state_root, parachains, proof));
+			assert!(Pallet::<TestRuntime>::pin_parachain_head(ParaId(1), head_hash(1, 0)));
+
+			assert_ok!(Pallet::<TestRuntime>::deregister_parachain(
+				RuntimeOrigin::signed(1),
+				ParaId(1)
+			));
+			assert_eq!(Balances::reserved_balance(1), 0);
+			assert_eq!(ParasInfo::<TestRuntime>::get(ParaId(1)), None);
+			assert_eq!(ImportedParaHashes::<TestRuntime>::iter_prefix(ParaId(1)).count(), 0);
+			assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_hash(1, 1)).is_none());
+
+			// pinned head is pruned when it is unpinned
+			assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(1), head_hash(1, 0)).is_some());
+			Pallet::<TestRuntime>::unpin_parachain_head(ParaId(1), head_hash(1, 0));
+			assert_eq!(ImportedParaHeads::<TestRuntime>::iter_prefix(ParaId(1)).count(), 0);
+			assert!(!PrunedPinnedParaHeads::<TestRuntime>::contains_key(
+				ParaId(1),
+				head_hash(1, 0)
+			));
+		});
+	}
+
+	#[test]
+	fn tracked_parachain_can_not_be_registered_with_deposit_and_pruned() {
+		run_test(|| {
+			Balances::make_free_balance_be(&1, 150);
+
+			// parachain is tracked under the default configuration
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+			initialize(state_root);
+			assert_ok!(import_parachain_1_head(0, state_root, parachains, proof));
+
+			// someone tries to register it with deposit to be able to deregister it later
+			assert_noop!(
+				Pallet::<TestRuntime>::register_parachain_with_deposit(
+					RuntimeOrigin::signed(1),
+					ParaId(1)
+				),
+				Error::<TestRuntime>::ParachainAlreadyTracked,
+			);
+
+			// even if the parachain has been registered before its heads have been imported,
+			// registrant can't prune heads that would have been imported anyway
+			assert_ok!(Pallet::<TestRuntime>::register_parachain_with_deposit(
+				RuntimeOrigin::signed(1),
+				ParaId(2)
+			));
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(2, head_data(2, 0))]);
+			proceed(1, state_root);
+			assert_ok!(Pallet::<TestRuntime>::submit_parachain_heads(
+				RuntimeOrigin::signed(1),
+				(1, test_relay_header(1, state_root).hash()),
+				parachains,
+				proof,
+			));
+			assert_ok!(Pallet::<TestRuntime>::deregister_parachain(
+				RuntimeOrigin::signed(1),
+				ParaId(2)
+			));
+			assert!(ParasInfo::<TestRuntime>::get(ParaId(1)).is_some());
+			assert!(ParasInfo::<TestRuntime>::get(ParaId(2)).is_some());
+			assert!(ImportedParaHeads::<TestRuntime>::get(ParaId(2), head_hash(2, 0)).is_some());
+		});
+	}
+
+	#[test]
+	fn rejects_heads_of_unregistered_parachains_if_configured() {
+		let (state_root, proof, parachains) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5)), (3, head_data(3, 10))]);
+		run_test(|| {
+			crate::mock::OnlyRegisteredParachains::set(&true);
+			assert_ok!(Pallet::<TestRuntime>::register_parachain(
+				RuntimeOrigin::root(),
+				ParaId(3),
+				Pallet::<TestRuntime>::default_parachain_config(),
+			));
+			System::<TestRuntime>::reset_events();
+
+			initialize(state_root);
+			assert_ok!(Pallet::<TestRuntime>::submit_parachain_heads(
+				RuntimeOrigin::signed(1),
+				(0, test_relay_header(0, state_root).hash()),
+				parachains,
+				proof,
+			));
+			assert_eq!(ParasInfo::<TestRuntime>::get(ParaId(1)), None);
+			assert!(ParasInfo::<TestRuntime>::get(ParaId(3)).is_some());
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Parachains(Event::UntrackedParachainRejected {
+							parachain: ParaId(1),
+						}),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Parachains(Event::UpdatedParachainHead {
+							parachain: ParaId(3),
+							parachain_head_hash: head_data(3, 10).hash(),
+						}),
+						topics: vec![],
+					},
+				],
+			);
+		});
+	}
+
+	#[test]
+	fn verifies_parachain_head_ancestry() {
+		run_test(|| {
+			crate::mock::MaxHeadsReorgDepth::set(&Some(1));
+
+			// import heads #0, #1 and #2, where every head is a child of previous head
+			let mut head_hashes = Vec::new();
+			let mut parent_hash = Default::default();
+			for i in 0..3 {
+				let head = head_data_with_parent(1, i, parent_hash);
+				parent_hash = head.hash();
+				head_hashes.push(parent_hash);
+
+				let (state_root, proof, parachains) =
+					prepare_parachain_heads_proof(vec![(1, head)]);
+				if i == 0 {
+					initialize(state_root);
+				} else {
+					proceed(i, state_root);
+				}
+				assert_ok!(import_parachain_1_head(i, state_root, parachains, proof));
+			}
+
+			// head that is a child of head #0 is rejected, because it'd revert two heads
+			let too_deep_reorg_head = head_data_with_parent(1, 20, head_hashes[0]);
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, too_deep_reorg_head.clone())]);
+			proceed(3, state_root);
+			System::<TestRuntime>::reset_events();
+			assert_ok!(import_parachain_1_head(3, state_root, parachains, proof));
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head_hash(ParaId(1)),
+				Some(head_hashes[2]),
+			);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Parachains(Event::RejectedNonDescendantParachainHead {
+						parachain: ParaId(1),
+						parachain_head_hash: too_deep_reorg_head.hash(),
+					}),
+					topics: vec![],
+				}],
+			);
+
+			// but head that is a child of head #1 is accepted
+			let reorg_head = head_data_with_parent(1, 10, head_hashes[1]);
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, reorg_head.clone())]);
+			proceed(4, state_root);
+			assert_ok!(import_parachain_1_head(4, state_root, parachains, proof));
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head_hash(ParaId(1)),
+				Some(reorg_head.hash()),
+			);
+
+			// and so is the child of the best head
+			let child_head = head_data_with_parent(1, 11, reorg_head.hash());
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, child_head.clone())]);
+			proceed(5, state_root);
+			assert_ok!(import_parachain_1_head(5, state_root, parachains, proof));
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head_hash(ParaId(1)),
+				Some(child_head.hash()),
+			);
+		});
+	}
+
+	#[test]
+	fn imports_parachain_head_for_free_once_per_free_heads_interval() {
+		run_test(|| {
+			crate::mock::FreeHeadsInterval::set(&Some(5));
+
+			// the first head is paid
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, head_data(1, 0))]);
+			initialize(state_root);
+			let result = import_parachain_1_head(0, state_root, parachains, proof);
+			assert_eq!(result.expect("head is imported").pays_fee, Pays::Yes);
+
+			// the head that is imported before `FreeHeadsInterval` relay blocks is paid
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, head_data(1, 1))]);
+			proceed(4, state_root);
+			let result = import_parachain_1_head(4, state_root, parachains, proof);
+			assert_eq!(result.expect("head is imported").pays_fee, Pays::Yes);
+
+			// the head that is imported after `FreeHeadsInterval` relay blocks is free
+			let (state_root, proof, parachains) =
+				prepare_parachain_heads_proof(vec![(1, head_data(1, 2))]);
+			proceed(9, state_root);
+			let result = import_parachain_1_head(9, state_root, parachains, proof);
+			assert_eq!(result.expect("head is imported").pays_fee, Pays::No);
+
+			// but the transaction is paid if it has failed to import some head
+			let (state_root, proof, parachains) = prepare_parachain_heads_proof(vec![
+				(1, head_data(1, 3)),
+				(UNTRACKED_PARACHAIN_ID, head_data(1, 3)),
+			]);
+			proceed(14, state_root);
+			let result = import_parachain_1_head(14, state_root, parachains, proof);
+			assert_eq!(result.expect("head is imported").pays_fee, Pays::Yes);
+		});
+	}
+
+	#[test]
+	fn returns_best_parachain_heads() {
+		let (state_root, proof, parachains) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5)), (3, head_data(3, 10))]);
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::best_parachain_heads(), vec![]);
+
+			initialize(state_root);
+			assert_ok!(import_parachain_1_head(0, state_root, parachains, proof));
+
+			let mut best_parachain_heads = Pallet::<TestRuntime>::best_parachain_heads();
+			best_parachain_heads.sort_by_key(|(parachain, _, _)| *parachain);
+			assert_eq!(
+				best_parachain_heads,
+				vec![(ParaId(1), head_hash(1, 5), 0), (ParaId(3), head_hash(3, 10), 0)],
+			);
+		});
+	}
+
+	#[test]
+	fn request_parachain_head_works() {
+		let (state_root, proof, parachains) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+		run_test(|| {
+			// heads of untracked parachains can't be requested
+			assert_noop!(
+				Pallet::<TestRuntime>::request_parachain_head(
+					RuntimeOrigin::signed(1),
+					ParaId(1),
+					5
+				),
+				Error::<TestRuntime>::UntrackedParachain,
+			);
+
+			// heads of known parachains may be requested
+			initialize(state_root);
+			assert_ok!(import_parachain_1_head(0, state_root, parachains, proof));
+			assert_noop!(
+				Pallet::<TestRuntime>::request_parachain_head(
+					RuntimeOrigin::signed(1),
+					ParaId(1),
+					0
+				),
+				Error::<TestRuntime>::ParachainHeadAlreadyImported,
+			);
+			assert_ok!(Pallet::<TestRuntime>::request_parachain_head(
+				RuntimeOrigin::signed(1),
+				ParaId(1),
+				5
+			));
+			assert_eq!(RequestedParachainHeads::<TestRuntime>::get(ParaId(1)), Some(5));
+			assert_eq!(
+				System::<TestRuntime>::events().last().map(|record| record.event.clone()),
+				Some(TestEvent::Parachains(Event::ParachainHeadRequested {
+					parachain: ParaId(1),
+					at_relay_block_number: 5,
+				})),
+			);
+
+			// earlier request doesn't override the later one
+			assert_ok!(Pallet::<TestRuntime>::request_parachain_head(
+				RuntimeOrigin::signed(1),
+				ParaId(1),
+				3
+			));
+			assert_eq!(RequestedParachainHeads::<TestRuntime>::get(ParaId(1)), Some(5));
+		});
+	}
+
+	#[test]
+	fn parachain_head_request_is_removed_when_head_is_imported() {
+		run_test(|| {
+			for (i, relay_block_number) in [0, 3, 5].into_iter().enumerate() {
+				let (state_root, proof, parachains) =
+					prepare_parachain_heads_proof(vec![(1, head_data(1, i as u32))]);
+				if relay_block_number == 0 {
+					initialize(state_root);
+				} else {
+					proceed(relay_block_number, state_root);
+				}
+				assert_ok!(import_parachain_1_head(
+					relay_block_number,
+					state_root,
+					parachains,
+					proof
+				));
+
+				if relay_block_number == 0 {
+					assert_ok!(Pallet::<TestRuntime>::request_parachain_head_at(ParaId(1), 5));
+				}
+				// the request is only removed when head at relay block #5 is imported
+				assert_eq!(
+					RequestedParachainHeads::<TestRuntime>::get(ParaId(1)),
+					if relay_block_number < 5 { Some(5) } else { None },
+				);
+			}
+		});
+	}
+
+	#[test]
+	fn returns_best_parachain_head_id_with_state_root() {
+		let (state_root, proof, parachains) =
+			prepare_parachain_heads_proof(vec![(1, head_data(1, 5))]);
+		run_test(|| {
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head_id_with_state_root::<Parachain1>(),
+				Ok(None),
+			);
+
+			initialize(state_root);
+			assert_ok!(import_parachain_1_head(0, state_root, parachains, proof));
+
+			assert_eq!(
+				Pallet::<TestRuntime>::best_parachain_head_id_with_state_root::<Parachain1>(),
+				Ok(Some((
+					HeaderId(5, head_hash(1, 5)),
+					RegularParachainHasher::hash(&(1u32, 5u32).encode()),
+				))),
+			);
+		});
+	}
+
 	#[test]
 	fn fails_on_unknown_relay_chain_block() {
 		let (state_root, proof, parachains) =
@@ -1422,6 +2743,11 @@ mod tests {
 			)
 			.0,
 		);
+
+		assert_eq!(
+			RequestedParachainHeads::<TestRuntime>::storage_map_final_key(ParaId(42)).to_vec(),
+			RequestedParachainHeadsKeyProvider::final_key("Parachains", &ParaId(42)).0
+		);
 	}
 
 	#[test]
@@ -16,7 +16,11 @@
 
 use bp_polkadot_core::parachains::ParaId;
 use bp_runtime::{Chain, Parachain};
-use frame_support::{construct_runtime, parameter_types, traits::ConstU32, weights::Weight};
+use frame_support::{
+	construct_runtime, parameter_types,
+	traits::ConstU32,
+	weights::{RuntimeDbWeight, Weight},
+};
 use sp_runtime::{
 	testing::{Header, H256},
 	traits::{BlakeTwo256, Header as HeaderT, IdentityLookup},
@@ -26,6 +30,7 @@ use sp_runtime::{
 use crate as pallet_bridge_parachains;
 
 pub type AccountId = u64;
+pub type Balance = u64;
 pub type TestNumber = u64;
 
 pub type RelayBlockHeader =
@@ -147,8 +152,9 @@ construct_runtime! {
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Grandpa1: pallet_bridge_grandpa::<Instance1>::{Pallet},
-		Grandpa2: pallet_bridge_grandpa::<Instance2>::{Pallet},
+		Balances: pallet_balances::{Pallet, Event<T>},
+		Grandpa1: pallet_bridge_grandpa::<Instance1>::{Pallet, Event<T>},
+		Grandpa2: pallet_bridge_grandpa::<Instance2>::{Pallet, Event<T>},
 		Parachains: pallet_bridge_parachains::{Call, Pallet, Event<T>},
 	}
 }
@@ -158,6 +164,7 @@ parameter_types! {
 	pub const MaximumBlockWeight: Weight = Weight::from_ref_time(1024);
 	pub const MaximumBlockLength: u32 = 2 * 1024;
 	pub const AvailableBlockRatio: Perbill = Perbill::one();
+	pub const DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
 }
 
 impl frame_system::Config for TestRuntime {
@@ -174,12 +181,12 @@ impl frame_system::Config for TestRuntime {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<Balance>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type BaseCallFilter = frame_support::traits::Everything;
 	type SystemWeightInfo = ();
-	type DbWeight = ();
+	type DbWeight = DbWeight;
 	type BlockWeights = ();
 	type BlockLength = ();
 	type SS58Prefix = ();
@@ -187,6 +194,18 @@ impl frame_system::Config for TestRuntime {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+impl pallet_balances::Config for TestRuntime {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type DustRemoval = ();
+	type RuntimeEvent = RuntimeEvent;
+	type ExistentialDeposit = frame_support::traits::ConstU64<1>;
+	type AccountStore = frame_system::Pallet<TestRuntime>;
+	type WeightInfo = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = ();
+}
+
 parameter_types! {
 	pub const SessionLength: u64 = 5;
 	pub const NumValidators: u32 = 5;
@@ -194,18 +213,36 @@ parameter_types! {
 }
 
 impl pallet_bridge_grandpa::Config<pallet_bridge_grandpa::Instance1> for TestRuntime {
+	type RuntimeEvent = RuntimeEvent;
 	type BridgedChain = TestBridgedChain;
 	type MaxRequests = ConstU32<2>;
 	type HeadersToKeep = HeadersToKeep;
 	type MaxBridgedAuthorities = frame_support::traits::ConstU32<5>;
+	type MaxFinalityProofSize = frame_support::traits::ConstU32<{ 1024 * 1024 }>;
+	type AuthoritySetsToKeep = frame_support::traits::ConstU32<4>;
+	type TrustedCheckpoint = frame_support::traits::GetDefault;
+	type FreeHeadersInterval = frame_support::traits::GetDefault;
+	type MaxFinalizedHeadersJump = frame_support::traits::GetDefault;
+	type OnEquivocation = ();
+	type MaxStallPeriod = frame_support::traits::GetDefault;
+	type OnBridgeStalled = ();
 	type WeightInfo = ();
 }
 
 impl pallet_bridge_grandpa::Config<pallet_bridge_grandpa::Instance2> for TestRuntime {
+	type RuntimeEvent = RuntimeEvent;
 	type BridgedChain = TestBridgedChain;
 	type MaxRequests = ConstU32<2>;
 	type HeadersToKeep = HeadersToKeep;
 	type MaxBridgedAuthorities = frame_support::traits::ConstU32<5>;
+	type MaxFinalityProofSize = frame_support::traits::ConstU32<{ 1024 * 1024 }>;
+	type AuthoritySetsToKeep = frame_support::traits::ConstU32<4>;
+	type TrustedCheckpoint = frame_support::traits::GetDefault;
+	type FreeHeadersInterval = frame_support::traits::GetDefault;
+	type MaxFinalizedHeadersJump = frame_support::traits::GetDefault;
+	type OnEquivocation = ();
+	type MaxStallPeriod = frame_support::traits::GetDefault;
+	type OnBridgeStalled = ();
 	type WeightInfo = ();
 }
 
@@ -213,16 +250,27 @@ parameter_types! {
 	pub const HeadsToKeep: u32 = 4;
 	pub const ParasPalletName: &'static str = PARAS_PALLET_NAME;
 	pub GetTenFirstParachains: Vec<ParaId> = (0..10).map(ParaId).collect();
+	pub storage OnlyRegisteredParachains: bool = false;
+	pub storage ParachainRegistrationDeposit: Option<Balance> = Some(100);
+	pub storage MaxHeadsReorgDepth: Option<u32> = None;
+	pub storage FreeHeadsInterval: Option<u32> = None;
 }
 
 impl pallet_bridge_parachains::Config for TestRuntime {
 	type RuntimeEvent = RuntimeEvent;
 	type WeightInfo = ();
-	type BridgesGrandpaPalletInstance = pallet_bridge_grandpa::Instance1;
+	type BridgedRelayChain = TestBridgedChain;
+	type RelayChainHeaders =
+		pallet_bridge_grandpa::GrandpaChainHeaders<TestRuntime, pallet_bridge_grandpa::Instance1>;
 	type ParasPalletName = ParasPalletName;
 	type ParaStoredHeaderDataBuilder = (Parachain1, Parachain2, Parachain3, BigParachain);
 	type HeadsToKeep = HeadsToKeep;
 	type MaxParaHeadDataSize = frame_support::traits::ConstU32<MAXIMAL_PARACHAIN_HEAD_DATA_SIZE>;
+	type OnlyRegisteredParachains = OnlyRegisteredParachains;
+	type MaxHeadsReorgDepth = MaxHeadsReorgDepth;
+	type FreeHeadsInterval = FreeHeadsInterval;
+	type Currency = Balances;
+	type ParachainRegistrationDeposit = ParachainRegistrationDeposit;
 }
 
 #[derive(Debug)]
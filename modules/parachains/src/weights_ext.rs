@@ -75,14 +75,25 @@ pub trait WeightInfoExt: WeightInfo {
 	/// not included.
 	fn parachain_head_storage_write_weight(db_weight: RuntimeDbWeight) -> Weight {
 		// it's just a couple of operations - we need to write the hash (`ImportedParaHashes`) and
-		// the head itself (`ImportedParaHeads`. Pruning is not included here
-		db_weight.writes(2)
+		// the head itself (`ImportedParaHeads`. We may also need to remove the head request
+		// (`RequestedParachainHeads`). Pruning is not included here
+		db_weight.reads_writes(1, 3)
 	}
 
 	/// Returns weight of single parachain head pruning.
 	fn parachain_head_pruning_weight(db_weight: RuntimeDbWeight) -> Weight {
-		// it's just one write operation, we don't want any benchmarks for that
-		db_weight.writes(1)
+		// it's just one read (to check if the head is pinned) and one write operation, we don't
+		// want any benchmarks for that
+		db_weight.reads_writes(1, 1)
+	}
+
+	/// Returns weight of single parachain head ancestry verification.
+	fn parachain_head_ancestry_verification_weight(
+		db_weight: RuntimeDbWeight,
+		max_reorg_depth: u32,
+	) -> Weight {
+		// we may need to read up to `max_reorg_depth` hashes from the `ImportedParaHashes`
+		db_weight.reads(max_reorg_depth as u64)
 	}
 
 	/// Returns weight that needs to be accounted when storage proof of given size is received.
@@ -20,18 +20,32 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 
-use bp_messages::LaneId;
-use bp_relayers::{PaymentProcedure, RelayerRewardsKeyProvider};
+use bp_messages::{LaneId, MessageNonce};
+use bp_relayers::{
+	OnLowRewardsPot, PaymentProcedure, RelayerRewardsKeyProvider, RelayerStatistics, RewardKind,
+	RewardsAccountParams,
+};
 use bp_runtime::StorageDoubleMapKeyProvider;
-use frame_support::sp_runtime::Saturating;
-use sp_arithmetic::traits::{AtLeast32BitUnsigned, Zero};
-use sp_std::marker::PhantomData;
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	sp_runtime::Saturating,
+	traits::{BalanceStatus, Currency, EnsureOriginWithArg, Get, ReservableCurrency},
+	weights::Weight,
+	RuntimeDebug,
+};
+use scale_info::TypeInfo;
+use sp_arithmetic::{
+	traits::{AtLeast32BitUnsigned, CheckedAdd, UniqueSaturatedInto, Zero},
+	FixedPointNumber, FixedU128, Perbill,
+};
+use sp_std::{marker::PhantomData, vec::Vec};
 use weights::WeightInfo;
 
 pub use pallet::*;
 pub use payment_adapter::DeliveryConfirmationPaymentsAdapter;
 
 pub mod benchmarking;
+pub mod migration;
 
 mod mock;
 mod payment_adapter;
@@ -41,6 +55,31 @@ pub mod weights;
 /// The target that will be used when publishing logs related to this pallet.
 pub const LOG_TARGET: &str = "runtime::bridge-relayers";
 
+/// Maximal number of entries in the `RecentlyRefundedDeliveries` storage value.
+pub const MAX_RECENTLY_REFUNDED_DELIVERIES: u32 = 64;
+
+/// Balance type of the currency that is used to stake relayer registrations.
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Reason why the relayer has not been refunded for submitting bridge transaction.
+#[derive(Clone, Copy, Decode, Encode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum RefundSkipReason {
+	/// The transaction has not updated the bridged relay chain or parachain state.
+	StateNotAdvanced,
+	/// The transaction has not delivered (or confirmed delivery of) any new messages.
+	NoNewMessages,
+	/// The transaction dispatch has failed.
+	DispatchFailed,
+	/// The transaction is delivering messages, but its structure is not supported.
+	UnrecognizedCall,
+	/// The transaction has been submitted by the relayer without active registration.
+	RelayerNotRegistered,
+	/// The message delivery transaction has been submitted by the account, that doesn't match
+	/// the relayer account at the bridged chain, declared in the call.
+	RelayerIdMismatch,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -51,6 +90,9 @@ pub mod pallet {
 	type RelayerRewardsKeyProviderOf<T> =
 		RelayerRewardsKeyProvider<<T as frame_system::Config>::AccountId, <T as Config>::Reward>;
 
+	/// The current storage version.
+	pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
 		/// The overarching event type.
@@ -59,187 +101,2795 @@ pub mod pallet {
 		type Reward: AtLeast32BitUnsigned + Copy + Parameter + MaxEncodedLen;
 		/// Pay rewards adapter.
 		type PaymentProcedure: PaymentProcedure<Self::AccountId, Self::Reward>;
+		/// Maximal number of rewards accounts that may be passed to the `claim_rewards_for_lanes`
+		/// call.
+		#[pallet::constant]
+		type MaxLanesPerClaim: Get<u32>;
+		/// Fraction of the unclaimed relayer reward that is lost every block.
+		///
+		/// The decay is compounding and is applied lazily, when the reward is updated or claimed.
+		/// It starts at the block where the reward has been updated last time. Use `()` to disable
+		/// the decay.
+		#[pallet::constant]
+		type RewardDecayPerBlock: Get<Perbill>;
+		/// Currency that is used to stake relayer registrations.
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// Stake that is reserved from the relayer account when it registers.
+		///
+		/// Registered relayers may get privileges (e.g. increased priority of their bridge
+		/// transactions) from the code that refunds relayers. If the required stake is increased,
+		/// relayers that have registered with smaller stake lose their privileges until they top
+		/// up the stake.
+		#[pallet::constant]
+		type RequiredStake: Get<BalanceOf<Self>>;
+		/// Fraction of the relayer stake that is slashed when the registered relayer misbehaves.
+		#[pallet::constant]
+		type StakeSlashFraction: Get<Perbill>;
+		/// Account that receives slashed stakes of misbehaving relayers. If it is `None`, slashed
+		/// stakes are burned.
+		type SlashBeneficiary: Get<Option<Self::AccountId>>;
+		/// Length of the reward era in blocks.
+		///
+		/// Rewards that are registered during the same era are accumulated together and become
+		/// claimable at the same time. Only used if the `RewardPayoutDelay` is non-zero.
+		#[pallet::constant]
+		type RewardEraLength: Get<Self::BlockNumber>;
+		/// Number of blocks after the end of the reward era, after which rewards that have been
+		/// registered during the era become claimable.
+		///
+		/// Until then, rewards are pending and may be cancelled by governance (e.g. if relayer
+		/// misbehavior is discovered later). Zero means that rewards are claimable right after
+		/// they are registered.
+		#[pallet::constant]
+		type RewardPayoutDelay: Get<Self::BlockNumber>;
+		/// Origin that may change the relayers allowlist of the lane. Root may always change
+		/// allowlists.
+		type LaneOwnerOrigin: EnsureOriginWithArg<Self::RuntimeOrigin, LaneId>;
+		/// Maximal number of relayers in the allowlist of a single lane.
+		#[pallet::constant]
+		type MaxAllowlistedRelayers: Get<u32>;
+		/// If balance of the account that pays rewards from the rewards account falls below this
+		/// threshold after paying the reward, the `LowRewardsPot` event is deposited and the
+		/// `OnLowRewardsPot` handler is called.
+		///
+		/// The balance is provided by the `PaymentProcedure`. Use `()` to disable alerting.
+		#[pallet::constant]
+		type LowRewardsPotThreshold: Get<Self::Reward>;
+		/// Handler that is called when balance of the rewards pot falls below the
+		/// `LowRewardsPotThreshold`.
+		type OnLowRewardsPot: OnLowRewardsPot<Self::Reward>;
 		/// Pallet call weights.
 		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(PhantomData<T>);
 
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(_block: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			// continue the multi-block storage migration (if it is in progress)
+			migration::migrate_to_v1_step::<T>(remaining_weight)
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Claim accumulated rewards.
+		/// Claim accumulated rewards for serving given lane.
+		///
+		/// Both message delivery and confirmation delivery rewards are claimed. The call fails if
+		/// there are no rewards in both rewards accounts of the lane. Use the
+		/// `claim_rewards_from_account` call to claim rewards from the single rewards account.
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::claim_rewards())]
+		#[pallet::weight(claim_rewards_weight::<T>().saturating_mul(2))]
 		pub fn claim_rewards(origin: OriginFor<T>, lane_id: LaneId) -> DispatchResult {
 			let relayer = ensure_signed(origin)?;
-
-			RelayerRewards::<T>::try_mutate_exists(
+			Self::claim_rewards_from_accounts(
 				&relayer,
+				[
+					RewardsAccountParams::new(lane_id, RewardKind::MessageDelivery),
+					RewardsAccountParams::new(lane_id, RewardKind::DeliveryConfirmation),
+				],
+				&relayer,
+			)
+		}
+
+		/// Claim accumulated rewards from given rewards accounts (e.g. for serving several lanes
+		/// or for both message delivery and confirmation delivery at the same lane).
+		///
+		/// Rewards accounts without rewards are skipped. The call fails if there are no rewards at
+		/// all given rewards accounts. Rewards from every account are paid using the
+		/// `PaymentProcedure`, which may be using lane-specific accounts, so there's a separate
+		/// payment for every rewards account. Either all payments succeed, or the call fails
+		/// without any changes.
+		///
+		/// Rewards are paid to the `beneficiary` account, if it is specified. It allows relayer
+		/// to keep its rewards at the cold account, while using hot account to sign bridge
+		/// transactions. Otherwise, rewards are paid to the relayer account.
+		#[pallet::call_index(2)]
+		#[pallet::weight(
+			claim_rewards_weight::<T>().saturating_mul(rewards_accounts.len() as u64)
+		)]
+		pub fn claim_rewards_for_lanes(
+			origin: OriginFor<T>,
+			rewards_accounts: BoundedVec<RewardsAccountParams, T::MaxLanesPerClaim>,
+			beneficiary: Option<T::AccountId>,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			let beneficiary = beneficiary.unwrap_or_else(|| relayer.clone());
+			Self::claim_rewards_from_accounts(&relayer, rewards_accounts, &beneficiary)
+		}
+
+		/// Set multiplier that is applied to relayer refunds, registered in given rewards account.
+		///
+		/// Multipliers of message delivery and confirmation delivery rewards are set separately,
+		/// so the economics of the two relay legs may be tuned independently. May only be called
+		/// by root.
+		#[pallet::call_index(1)]
+		#[pallet::weight((T::DbWeight::get().writes(1), DispatchClass::Operational))]
+		pub fn set_lane_reward_multiplier(
+			origin: OriginFor<T>,
+			rewards_account_params: RewardsAccountParams,
+			multiplier: FixedU128,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			LaneRewardMultiplier::<T>::insert(rewards_account_params, multiplier);
+			Self::deposit_event(Event::<T>::LaneRewardMultiplierUpdated {
+				rewards_account_params,
+				multiplier,
+			});
+			Ok(())
+		}
+
+		/// Transfer accumulated rewards in given rewards account to other relayer account.
+		///
+		/// It may be used when relayer rotates its keys. The reward is added to the reward of the
+		/// `new_account`, if it already has reward in the same rewards account. Pending rewards,
+		/// which are not yet claimable, are not transferred.
+		#[pallet::call_index(3)]
+		#[pallet::weight(
+			T::DbWeight::get()
+				.reads_writes(4, 4)
+				.saturating_add(unlock_pending_rewards_weight::<T>())
+		)]
+		pub fn transfer_accrued_rewards(
+			origin: OriginFor<T>,
+			rewards_account_params: RewardsAccountParams,
+			new_account: T::AccountId,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			ensure!(relayer != new_account, Error::<T>::RewardsTransferToSelf);
+
+			Self::unlock_pending_rewards(&relayer, rewards_account_params)?;
+			let reward = Self::claimable_reward(&relayer, rewards_account_params)
+				.ok_or(Error::<T>::NoRewardForRelayer)?;
+			RelayerRewards::<T>::remove(&relayer, rewards_account_params);
+			RelayerRewardsUpdatedAt::<T>::remove(&relayer, rewards_account_params);
+			// if the reward has decayed to zero, it is simply dropped
+			Self::register_claimable_reward(rewards_account_params, &new_account, reward)?;
+
+			Self::deposit_event(Event::<T>::RewardsTransferred {
+				relayer,
+				new_account,
+				rewards_account_params,
+				reward,
+			});
+			Ok(())
+		}
+
+		/// Register relayer by reserving the `RequiredStake` from its account.
+		///
+		/// If the relayer is already registered with smaller stake (e.g. because the
+		/// `RequiredStake` has been increased), only the missing part of the stake is reserved.
+		#[pallet::call_index(4)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 2))]
+		pub fn register(origin: OriginFor<T>) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+
+			RegisteredRelayers::<T>::try_mutate(&relayer, |maybe_stake| -> DispatchResult {
+				let stake = maybe_stake.unwrap_or_else(Zero::zero);
+				let required_stake = T::RequiredStake::get();
+				ensure!(
+					maybe_stake.is_none() || stake < required_stake,
+					Error::<T>::AlreadyRegistered
+				);
+
+				let missing_stake = required_stake.saturating_sub(stake);
+				T::Currency::reserve(&relayer, missing_stake).map_err(|e| {
+					log::trace!(
+						target: LOG_TARGET,
+						"Failed to reserve {:?} stake of relayer {:?}: {:?}",
+						missing_stake,
+						relayer,
+						e,
+					);
+					Error::<T>::FailedToReserveStake
+				})?;
+
+				let stake = stake.saturating_add(missing_stake);
+				*maybe_stake = Some(stake);
+				Self::deposit_event(Event::<T>::RelayerRegistered {
+					relayer: relayer.clone(),
+					stake,
+				});
+				Ok(())
+			})
+		}
+
+		/// Deregister relayer and unreserve its stake.
+		#[pallet::call_index(5)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(2, 2))]
+		pub fn deregister(origin: OriginFor<T>) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+
+			let stake = RegisteredRelayers::<T>::take(&relayer).ok_or(Error::<T>::NotRegistered)?;
+			let not_unreserved = T::Currency::unreserve(&relayer, stake);
+			if !not_unreserved.is_zero() {
+				log::trace!(
+					target: LOG_TARGET,
+					"Failed to unreserve {:?}/{:?} stake of relayer {:?}",
+					not_unreserved,
+					stake,
+					relayer,
+				);
+			}
+
+			Self::deposit_event(Event::<T>::RelayerDeregistered { relayer, stake });
+			Ok(())
+		}
+
+		/// Cancel pending rewards of the relayer in given rewards account, registered during given
+		/// reward era.
+		///
+		/// May only be called by root. Rewards may be cancelled until they are claimed.
+		#[pallet::call_index(6)]
+		#[pallet::weight((T::DbWeight::get().writes(1), DispatchClass::Operational))]
+		pub fn cancel_pending_rewards(
+			origin: OriginFor<T>,
+			relayer: T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+			era: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let reward = PendingRewards::<T>::take((&relayer, rewards_account_params, era))
+				.ok_or(Error::<T>::NoPendingRewards)?;
+			Self::deposit_event(Event::<T>::PendingRewardsCancelled {
+				relayer,
+				rewards_account_params,
+				era,
+				reward,
+			});
+			Ok(())
+		}
+
+		/// Set or remove the relayers allowlist of given lane.
+		///
+		/// If the lane has an allowlist, only relayers from this list may deliver messages over
+		/// the lane. It is enforced by the code that refunds relayers. Lanes without allowlists
+		/// may be served by any relayer.
+		///
+		/// May only be called either by root, or by the `LaneOwnerOrigin`.
+		#[pallet::call_index(7)]
+		#[pallet::weight((T::DbWeight::get().writes(1), DispatchClass::Operational))]
+		pub fn set_lane_relayers_allowlist(
+			origin: OriginFor<T>,
+			lane_id: LaneId,
+			allowlist: Option<BoundedVec<T::AccountId, T::MaxAllowlistedRelayers>>,
+		) -> DispatchResult {
+			if ensure_root(origin.clone()).is_err() {
+				T::LaneOwnerOrigin::ensure_origin(origin, &lane_id)?;
+			}
+
+			let allowlisted_relayers = allowlist.as_ref().map(|allowlist| allowlist.len() as u32);
+			LaneRelayersAllowlist::<T>::set(lane_id, allowlist);
+			Self::deposit_event(Event::<T>::LaneRelayersAllowlistUpdated {
 				lane_id,
+				allowlisted_relayers,
+			});
+			Ok(())
+		}
+
+		/// Claim accumulated rewards from given rewards account.
+		#[pallet::call_index(8)]
+		#[pallet::weight(claim_rewards_weight::<T>())]
+		pub fn claim_rewards_from_account(
+			origin: OriginFor<T>,
+			rewards_account_params: RewardsAccountParams,
+		) -> DispatchResult {
+			let relayer = ensure_signed(origin)?;
+			Self::unlock_pending_rewards(&relayer, rewards_account_params)?;
+			Self::claim_lane_rewards(&relayer, rewards_account_params, &relayer)
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Pay accumulated rewards of the relayer in given rewards accounts to the beneficiary.
+		///
+		/// Rewards accounts without rewards are skipped. Fails if there are no rewards at all
+		/// given rewards accounts.
+		fn claim_rewards_from_accounts(
+			relayer: &T::AccountId,
+			rewards_accounts: impl IntoIterator<Item = RewardsAccountParams>,
+			beneficiary: &T::AccountId,
+		) -> DispatchResult {
+			let mut has_claimed_rewards = false;
+			for rewards_account_params in rewards_accounts {
+				Self::unlock_pending_rewards(relayer, rewards_account_params)?;
+				if !RelayerRewards::<T>::contains_key(relayer, rewards_account_params) {
+					continue
+				}
+
+				Self::claim_lane_rewards(relayer, rewards_account_params, beneficiary)?;
+				has_claimed_rewards = true;
+			}
+
+			ensure!(has_claimed_rewards, Error::<T>::NoRewardForRelayer);
+			Ok(())
+		}
+
+		/// Pay accumulated rewards of the relayer in given rewards account to the beneficiary.
+		fn claim_lane_rewards(
+			relayer: &T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+			beneficiary: &T::AccountId,
+		) -> DispatchResult {
+			RelayerRewards::<T>::try_mutate_exists(
+				relayer,
+				rewards_account_params,
 				|maybe_reward| -> DispatchResult {
 					let reward = maybe_reward.take().ok_or(Error::<T>::NoRewardForRelayer)?;
-					T::PaymentProcedure::pay_reward(&relayer, lane_id, reward).map_err(|e| {
-						log::trace!(
-							target: LOG_TARGET,
-							"Failed to pay {:?} rewards to {:?}: {:?}",
-							lane_id,
-							relayer,
-							e,
-						);
-						Error::<T>::FailedToPayReward
-					})?;
+					let reward = Self::decayed_reward(relayer, rewards_account_params, reward);
+					RelayerRewardsUpdatedAt::<T>::remove(relayer, rewards_account_params);
+					T::PaymentProcedure::pay_reward(beneficiary, rewards_account_params, reward)
+						.map_err(|e| {
+							log::trace!(
+								target: LOG_TARGET,
+								"Failed to pay {:?} rewards of {:?} to {:?}: {:?}",
+								rewards_account_params,
+								relayer,
+								beneficiary,
+								e,
+							);
+							Error::<T>::FailedToPayReward
+						})?;
 
-					Self::deposit_event(Event::<T>::RewardPaid {
-						relayer: relayer.clone(),
-						lane_id,
-						reward,
-					});
+					if beneficiary == relayer {
+						Self::deposit_event(Event::<T>::RewardPaid {
+							relayer: relayer.clone(),
+							rewards_account_params,
+							reward,
+						});
+					} else {
+						Self::deposit_event(Event::<T>::RewardPaidToBeneficiary {
+							relayer: relayer.clone(),
+							beneficiary: beneficiary.clone(),
+							rewards_account_params,
+							reward,
+						});
+					}
+
+					Self::check_rewards_pot(rewards_account_params);
 					Ok(())
 				},
 			)
 		}
-	}
 
-	impl<T: Config> Pallet<T> {
+		/// Deposit the `LowRewardsPot` event and call the `OnLowRewardsPot` handler if balance of
+		/// the account that pays rewards from given rewards account is below the
+		/// `LowRewardsPotThreshold`.
+		fn check_rewards_pot(rewards_account_params: RewardsAccountParams) {
+			let balance = match T::PaymentProcedure::rewards_pot_balance(rewards_account_params) {
+				Some(balance) => balance,
+				None => return,
+			};
+			let threshold = T::LowRewardsPotThreshold::get();
+			if balance >= threshold {
+				return
+			}
+
+			log::warn!(
+				target: LOG_TARGET,
+				"Balance {:?} of the account that pays rewards from {:?} is below the threshold {:?}",
+				balance,
+				rewards_account_params,
+				threshold,
+			);
+
+			T::OnLowRewardsPot::on_low_rewards_pot(rewards_account_params, balance);
+			Self::deposit_event(Event::<T>::LowRewardsPot {
+				rewards_account_params,
+				balance,
+				threshold,
+			});
+		}
+
 		/// Register reward for given relayer.
-		pub fn register_relayer_reward(lane_id: LaneId, relayer: &T::AccountId, reward: T::Reward) {
+		///
+		/// If the `RewardPayoutDelay` is non-zero, the reward is added to pending rewards of the
+		/// current reward era. Otherwise it may be claimed immediately. If the reward can't be
+		/// registered, relayer rewards are not changed and the `RewardRegistrationFailed` event
+		/// is deposited, so the failure may be detected and remediated by off-chain tools.
+		pub fn register_relayer_reward(
+			rewards_account_params: RewardsAccountParams,
+			relayer: &T::AccountId,
+			reward: T::Reward,
+		) -> Result<(), Error<T>> {
 			if reward.is_zero() {
-				return
+				return Ok(())
 			}
 
-			RelayerRewards::<T>::mutate(relayer, lane_id, |old_reward: &mut Option<T::Reward>| {
-				let new_reward = old_reward.unwrap_or_else(Zero::zero).saturating_add(reward);
-				*old_reward = Some(new_reward);
+			match Self::current_reward_era() {
+				Some(era) =>
+					Self::register_pending_reward(rewards_account_params, relayer, reward, era),
+				None => Self::register_claimable_reward(rewards_account_params, relayer, reward),
+			}
+		}
 
-				log::trace!(
+		/// Register reward that may be claimed by the relayer immediately.
+		fn register_claimable_reward(
+			rewards_account_params: RewardsAccountParams,
+			relayer: &T::AccountId,
+			reward: T::Reward,
+		) -> Result<(), Error<T>> {
+			if reward.is_zero() {
+				return Ok(())
+			}
+
+			let result = RelayerRewards::<T>::try_mutate(
+				relayer,
+				rewards_account_params,
+				|old_reward: &mut Option<T::Reward>| {
+					let new_reward = old_reward
+						.map(|old_reward| {
+							Self::decayed_reward(relayer, rewards_account_params, old_reward)
+						})
+						.unwrap_or_else(Zero::zero)
+						.checked_add(&reward)
+						.ok_or(Error::<T>::RewardOverflow)?;
+					*old_reward = Some(new_reward);
+
+					RelayerRewardsUpdatedAt::<T>::insert(
+						relayer,
+						rewards_account_params,
+						frame_system::Pallet::<T>::block_number(),
+					);
+
+					log::trace!(
+						target: crate::LOG_TARGET,
+						"Relayer {:?} can now claim reward from {:?}: {:?}",
+						relayer,
+						rewards_account_params,
+						new_reward,
+					);
+
+					Ok(())
+				},
+			);
+
+			if let Err(ref e) = result {
+				log::error!(
 					target: crate::LOG_TARGET,
-					"Relayer {:?} can now claim reward for serving lane {:?}: {:?}",
+					"Failed to register reward {:?} of relayer {:?} in {:?}: {:?}",
+					reward,
 					relayer,
-					lane_id,
-					new_reward,
+					rewards_account_params,
+					e,
 				);
-			});
+
+				Self::deposit_event(Event::<T>::RewardRegistrationFailed {
+					relayer: relayer.clone(),
+					rewards_account_params,
+					reward,
+				});
+			}
+
+			result
 		}
-	}
 
-	#[pallet::event]
-	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	pub enum Event<T: Config> {
-		/// Reward has been paid to the relayer.
-		RewardPaid {
-			/// Relayer account that has been rewarded.
-			relayer: T::AccountId,
-			/// Relayer has received reward for serving this lane.
-			lane_id: LaneId,
-			/// Reward amount.
+		/// Register reward that may only be claimed after the end of given reward era and the
+		/// `RewardPayoutDelay`.
+		///
+		/// Pending rewards of previous eras that have become claimable are unlocked first, so
+		/// there's a limited number of pending rewards for every relayer and rewards account.
+		fn register_pending_reward(
+			rewards_account_params: RewardsAccountParams,
+			relayer: &T::AccountId,
 			reward: T::Reward,
-		},
-	}
+			era: u32,
+		) -> Result<(), Error<T>> {
+			let result =
+				Self::unlock_pending_rewards(relayer, rewards_account_params).and_then(|_| {
+					ensure!(
+						PendingRewards::<T>::contains_key((relayer, rewards_account_params, era)) ||
+							Self::pending_reward_eras(relayer, rewards_account_params) <
+								max_pending_reward_eras::<T>(),
+						Error::<T>::TooManyPendingRewards,
+					);
+					PendingRewards::<T>::try_mutate(
+						(relayer, rewards_account_params, era),
+						|pending_reward: &mut Option<T::Reward>| {
+							let new_pending_reward = pending_reward
+								.unwrap_or_else(Zero::zero)
+								.checked_add(&reward)
+								.ok_or(Error::<T>::RewardOverflow)?;
+							*pending_reward = Some(new_pending_reward);
+							Ok(())
+						},
+					)
+				});
 
-	#[pallet::error]
-	pub enum Error<T> {
-		/// No reward can be claimed by given relayer.
-		NoRewardForRelayer,
-		/// Reward payment procedure has failed.
-		FailedToPayReward,
-	}
+			match result {
+				Ok(()) => {
+					Self::update_reward_block(relayer, rewards_account_params);
 
-	/// Map of the relayer => accumulated reward.
-	#[pallet::storage]
-	#[pallet::getter(fn relayer_reward)]
-	pub type RelayerRewards<T: Config> = StorageDoubleMap<
-		_,
-		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Hasher1,
-		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Key1,
-		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Hasher2,
-		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Key2,
-		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Value,
-		OptionQuery,
-	>;
-}
+					log::trace!(
+						target: crate::LOG_TARGET,
+						"Relayer {:?} will be able to claim reward from {:?} during era {}: {:?}",
+						relayer,
+						rewards_account_params,
+						era,
+						reward,
+					);
+				},
+				Err(ref e) => {
+					log::error!(
+						target: crate::LOG_TARGET,
+						"Failed to register pending reward {:?} of relayer {:?} in {:?}: {:?}",
+						reward,
+						relayer,
+						rewards_account_params,
+						e,
+					);
 
-#[cfg(test)]
-mod tests {
-	use super::*;
-	use mock::{RuntimeEvent as TestEvent, *};
+					Self::deposit_event(Event::<T>::RewardRegistrationFailed {
+						relayer: relayer.clone(),
+						rewards_account_params,
+						reward,
+					});
+				},
+			}
 
-	use crate::Event::RewardPaid;
-	use frame_support::{
-		assert_noop, assert_ok,
-		traits::fungible::{Inspect, Mutate},
-	};
-	use frame_system::{EventRecord, Pallet as System, Phase};
-	use sp_runtime::DispatchError;
+			result
+		}
 
-	fn get_ready_for_events() {
-		System::<TestRuntime>::set_block_number(1);
-		System::<TestRuntime>::reset_events();
-	}
+		/// Move pending rewards of the relayer in given rewards account, which have become
+		/// claimable, to the claimable rewards.
+		///
+		/// At most `max_pending_reward_eras` pending rewards are inspected.
+		fn unlock_pending_rewards(
+			relayer: &T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+		) -> Result<(), Error<T>> {
+			let unlocked_rewards =
+				PendingRewards::<T>::iter_prefix((relayer.clone(), rewards_account_params))
+					.take(max_pending_reward_eras::<T>() as usize)
+					.filter(|(era, _)| Self::is_reward_era_unlocked(*era))
+					.collect::<Vec<_>>();
+			for (era, reward) in unlocked_rewards {
+				PendingRewards::<T>::remove((relayer, rewards_account_params, era));
+				Self::register_claimable_reward(rewards_account_params, relayer, reward)?;
+			}
 
-	#[test]
-	fn root_cant_claim_anything() {
-		run_test(|| {
-			assert_noop!(
-				Pallet::<TestRuntime>::claim_rewards(RuntimeOrigin::root(), TEST_LANE_ID),
-				DispatchError::BadOrigin,
-			);
-		});
-	}
+			Ok(())
+		}
 
-	#[test]
-	fn relayer_cant_claim_if_no_reward_exists() {
-		run_test(|| {
-			assert_noop!(
-				Pallet::<TestRuntime>::claim_rewards(
-					RuntimeOrigin::signed(REGULAR_RELAYER),
-					TEST_LANE_ID
-				),
-				Error::<TestRuntime>::NoRewardForRelayer,
-			);
-		});
-	}
+		/// Returns sum of pending rewards of the relayer in given rewards account, which have
+		/// become claimable.
+		fn unlocked_pending_reward(
+			relayer: &T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+		) -> T::Reward {
+			PendingRewards::<T>::iter_prefix((relayer.clone(), rewards_account_params))
+				.take(max_pending_reward_eras::<T>() as usize)
+				.filter(|(era, _)| Self::is_reward_era_unlocked(*era))
+				.fold(Zero::zero(), |total, (_, reward)| total.saturating_add(reward))
+		}
 
-	#[test]
-	fn relayer_cant_claim_if_payment_procedure_fails() {
-		run_test(|| {
-			RelayerRewards::<TestRuntime>::insert(FAILING_RELAYER, TEST_LANE_ID, 100);
-			assert_noop!(
-				Pallet::<TestRuntime>::claim_rewards(
-					RuntimeOrigin::signed(FAILING_RELAYER),
-					TEST_LANE_ID
-				),
-				Error::<TestRuntime>::FailedToPayReward,
-			);
-		});
-	}
+		/// Returns number of reward eras with pending rewards of the relayer in given rewards
+		/// account.
+		///
+		/// At most `max_pending_reward_eras` eras are counted.
+		fn pending_reward_eras(
+			relayer: &T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+		) -> u32 {
+			PendingRewards::<T>::iter_prefix((relayer.clone(), rewards_account_params))
+				.take(max_pending_reward_eras::<T>() as usize)
+				.count() as u32
+		}
 
-	#[test]
-	fn relayer_can_claim_reward() {
-		run_test(|| {
-			get_ready_for_events();
+		/// Returns current reward era, or `None` if rewards are claimable immediately.
+		fn current_reward_era() -> Option<u32> {
+			if T::RewardPayoutDelay::get().is_zero() {
+				return None
+			}
 
-			RelayerRewards::<TestRuntime>::insert(REGULAR_RELAYER, TEST_LANE_ID, 100);
-			assert_ok!(Pallet::<TestRuntime>::claim_rewards(
-				RuntimeOrigin::signed(REGULAR_RELAYER),
-				TEST_LANE_ID
-			));
-			assert_eq!(RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_LANE_ID), None);
+			let era_length = T::RewardEraLength::get().max(1u32.into());
+			Some((frame_system::Pallet::<T>::block_number() / era_length).unique_saturated_into())
+		}
 
-			//Check if the `RewardPaid` event was emitted.
-			assert_eq!(
-				System::<TestRuntime>::events(),
-				vec![EventRecord {
-					phase: Phase::Initialization,
-					event: TestEvent::Relayers(RewardPaid {
-						relayer: REGULAR_RELAYER,
-						lane_id: TEST_LANE_ID,
-						reward: 100
-					}),
-					topics: vec![],
-				}],
+		/// Returns true if rewards of given reward era may be claimed.
+		fn is_reward_era_unlocked(era: u32) -> bool {
+			let era_length = T::RewardEraLength::get().max(1u32.into());
+			let unlocked_at = T::BlockNumber::from(era)
+				.saturating_add(1u32.into())
+				.saturating_mul(era_length)
+				.saturating_add(T::RewardPayoutDelay::get());
+			frame_system::Pallet::<T>::block_number() >= unlocked_at
+		}
+
+		/// Pay reward to the relayer immediately, instead of registering it.
+		///
+		/// If the payment has failed (e.g. because the account that is paying rewards has
+		/// insufficient funds), the reward is registered using `register_relayer_reward`. The
+		/// block of the last reward update is changed in both cases. If the `RewardPayoutDelay`
+		/// is non-zero, the reward is never paid immediately and is registered instead.
+		pub fn pay_relayer_reward(
+			rewards_account_params: RewardsAccountParams,
+			relayer: &T::AccountId,
+			reward: T::Reward,
+		) -> Result<(), Error<T>> {
+			if reward.is_zero() {
+				return Ok(())
+			}
+
+			if Self::current_reward_era().is_some() {
+				return Self::register_relayer_reward(rewards_account_params, relayer, reward)
+			}
+
+			let payment_result =
+				T::PaymentProcedure::pay_reward(relayer, rewards_account_params, reward);
+			Self::check_rewards_pot(rewards_account_params);
+			if let Err(e) = payment_result {
+				log::warn!(
+					target: LOG_TARGET,
+					"Failed to pay {:?} reward to {:?} from {:?}: {:?}. Registering it",
+					reward,
+					relayer,
+					rewards_account_params,
+					e,
+				);
+
+				return Self::register_relayer_reward(rewards_account_params, relayer, reward)
+			}
+
+			Self::update_reward_block(relayer, rewards_account_params);
+			Self::deposit_event(Event::<T>::RewardPaid {
+				relayer: relayer.clone(),
+				rewards_account_params,
+				reward,
+			});
+			Ok(())
+		}
+
+		/// Change the block of the last reward update without changing the reward amount.
+		///
+		/// The block of the last reward update is used to throttle relayer rewards, but it is also
+		/// used to compute the reward decay, so we need to apply the decay to the claimable reward
+		/// first.
+		fn update_reward_block(
+			relayer: &T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+		) {
+			RelayerRewards::<T>::mutate_exists(
+				relayer,
+				rewards_account_params,
+				|claimable_reward| {
+					if let Some(claimable_reward) = claimable_reward {
+						*claimable_reward = Self::decayed_reward(
+							relayer,
+							rewards_account_params,
+							*claimable_reward,
+						);
+					}
+				},
 			);
+			RelayerRewardsUpdatedAt::<T>::insert(
+				relayer,
+				rewards_account_params,
+				frame_system::Pallet::<T>::block_number(),
+			);
+		}
+
+		/// Returns reward that may be claimed by the relayer from given rewards account.
+		///
+		/// Unlike the `relayer_reward` getter, it accounts the reward decay and pending rewards
+		/// that have become claimable.
+		pub fn claimable_reward(
+			relayer: &T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+		) -> Option<T::Reward> {
+			let reward = RelayerRewards::<T>::get(relayer, rewards_account_params)
+				.map(|reward| Self::decayed_reward(relayer, rewards_account_params, reward));
+			let unlocked_pending_reward =
+				Self::unlocked_pending_reward(relayer, rewards_account_params);
+			if unlocked_pending_reward.is_zero() {
+				return reward
+			}
+
+			Some(reward.unwrap_or_else(Zero::zero).saturating_add(unlocked_pending_reward))
+		}
+
+		/// Returns non-zero rewards that may be claimed by the relayer from rewards accounts.
+		///
+		/// At most `bp_relayers::MAX_RELAYER_REWARDS_PER_QUERY` rewards accounts are returned. Like
+		/// the `claimable_reward`, it accounts the reward decay.
+		pub fn relayer_rewards(relayer: &T::AccountId) -> Vec<(RewardsAccountParams, T::Reward)> {
+			RelayerRewards::<T>::iter_prefix(relayer)
+				.map(|(rewards_account_params, reward)| {
+					(
+						rewards_account_params,
+						Self::decayed_reward(relayer, rewards_account_params, reward),
+					)
+				})
+				.filter(|(_, reward)| !reward.is_zero())
+				.take(bp_relayers::MAX_RELAYER_REWARDS_PER_QUERY as usize)
+				.collect()
+		}
+
+		/// Apply decay to the reward that has been stored for given relayer and rewards account.
+		fn decayed_reward(
+			relayer: &T::AccountId,
+			rewards_account_params: RewardsAccountParams,
+			reward: T::Reward,
+		) -> T::Reward {
+			let decay_per_block = T::RewardDecayPerBlock::get();
+			if decay_per_block.is_zero() {
+				return reward
+			}
+
+			// rewards that have been stored without block stamp are not decaying
+			let updated_at =
+				match RelayerRewardsUpdatedAt::<T>::get(relayer, rewards_account_params) {
+					Some(updated_at) => updated_at,
+					None => return reward,
+				};
+			let elapsed_blocks: u32 = frame_system::Pallet::<T>::block_number()
+				.saturating_sub(updated_at)
+				.unique_saturated_into();
+
+			let remaining_part = Perbill::one()
+				.saturating_sub(decay_per_block)
+				.saturating_pow(elapsed_blocks as _);
+			remaining_part * reward
+		}
+
+		/// Note that the reward would have been registered for given relayer, if the code that is
+		/// registering rewards was not running in the shadow (observe-only) mode.
+		///
+		/// It only deposits the `ShadowReward` event and never changes relayer rewards.
+		pub fn note_shadow_relayer_reward(
+			rewards_account_params: RewardsAccountParams,
+			relayer: &T::AccountId,
+			reward: T::Reward,
+		) {
+			Self::deposit_event(Event::<T>::ShadowReward {
+				relayer: relayer.clone(),
+				rewards_account_params,
+				reward,
+			});
+		}
+
+		/// Note that the relayer has been refunded for submitting bridge transaction.
+		///
+		/// It only deposits the `RelayerRefunded` event. The reward itself must be registered
+		/// separately, using `register_relayer_reward`.
+		pub fn note_relayer_refund(
+			lane_id: LaneId,
+			relayer: &T::AccountId,
+			reward: T::Reward,
+			bridged_equivalent_reward: Option<T::Reward>,
+		) {
+			Self::deposit_event(Event::<T>::RelayerRefunded {
+				relayer: relayer.clone(),
+				lane_id,
+				reward,
+				bridged_equivalent_reward,
+			});
+		}
+
+		/// Remember that the relayer has been refunded for delivering messages up to `nonces_end`.
+		///
+		/// Entries that have been remembered `max_age` or more blocks ago are removed. If there
+		/// are too many entries, the oldest entry is removed.
+		pub fn note_refunded_delivery(
+			lane_id: LaneId,
+			nonces_end: MessageNonce,
+			max_age: T::BlockNumber,
+		) {
+			let now = frame_system::Pallet::<T>::block_number();
+			RecentlyRefundedDeliveries::<T>::mutate(|deliveries| {
+				deliveries.retain(|(_, _, refunded_at)| now.saturating_sub(*refunded_at) < max_age);
+				if deliveries.len() >= MAX_RECENTLY_REFUNDED_DELIVERIES as usize {
+					deliveries.remove(0);
+				}
+				// we have just removed an entry if the vector was full
+				let _ = deliveries.try_push((lane_id, nonces_end, now));
+			});
+		}
+
+		/// Returns true if the relayer has been refunded for delivering messages up to
+		/// `nonces_end` less than `max_age` blocks ago.
+		pub fn is_delivery_recently_refunded(
+			lane_id: LaneId,
+			nonces_end: MessageNonce,
+			max_age: T::BlockNumber,
+		) -> bool {
+			let now = frame_system::Pallet::<T>::block_number();
+			RecentlyRefundedDeliveries::<T>::get().iter().any(
+				|(refunded_lane_id, refunded_nonces_end, refunded_at)| {
+					*refunded_lane_id == lane_id &&
+						*refunded_nonces_end == nonces_end &&
+						now.saturating_sub(*refunded_at) < max_age
+				},
+			)
+		}
+
+		/// Returns true if the relayer may deliver messages over given lane.
+		///
+		/// Lanes without allowlists may be served by any relayer.
+		pub fn is_relayer_allowlisted(lane_id: LaneId, relayer: &T::AccountId) -> bool {
+			LaneRelayersAllowlist::<T>::get(lane_id)
+				.map(|allowlist| allowlist.contains(relayer))
+				.unwrap_or(true)
+		}
+
+		/// Returns true if the relayer is registered with at least `RequiredStake` stake.
+		pub fn is_registration_active(relayer: &T::AccountId) -> bool {
+			RegisteredRelayers::<T>::get(relayer)
+				.map(|stake| stake >= T::RequiredStake::get())
+				.unwrap_or(false)
+		}
+
+		/// Slash the `StakeSlashFraction` of the registered relayer stake and deregister the
+		/// relayer.
+		///
+		/// It may be called by any runtime component that detects misbehavior of registered
+		/// relayer (e.g. by the code that refunds relayers, when relayer submits bridge
+		/// transaction that fails or doesn't advance the bridge state). Slashed stake is moved to
+		/// the `SlashBeneficiary` account. It is burned if there's no beneficiary or if the
+		/// beneficiary account doesn't exist. The rest of the stake is unreserved.
+		/// Does nothing if the relayer is not registered.
+		pub fn slash_and_deregister(relayer: &T::AccountId) {
+			let stake = match RegisteredRelayers::<T>::take(relayer) {
+				Some(stake) => stake,
+				None => return,
+			};
+
+			let to_slash = T::StakeSlashFraction::get().mul_floor(stake);
+			let not_slashed = match T::SlashBeneficiary::get() {
+				Some(beneficiary) => T::Currency::repatriate_reserved(
+					relayer,
+					&beneficiary,
+					to_slash,
+					BalanceStatus::Free,
+				)
+				.unwrap_or_else(|e| {
+					log::trace!(
+						target: LOG_TARGET,
+						"Failed to move {:?} slashed stake of relayer {:?} to {:?}: {:?}. \
+							Burning it",
+						to_slash,
+						relayer,
+						beneficiary,
+						e,
+					);
+					T::Currency::slash_reserved(relayer, to_slash).1
+				}),
+				None => T::Currency::slash_reserved(relayer, to_slash).1,
+			};
+			let slashed = to_slash.saturating_sub(not_slashed);
+			T::Currency::unreserve(relayer, stake.saturating_sub(slashed));
+
+			Self::deposit_event(Event::<T>::RelayerSlashed { relayer: relayer.clone(), slashed });
+		}
+
+		/// Note that the relayer has submitted bridge transaction that has delivered given number
+		/// of new messages and has submitted given number of new headers.
+		pub fn note_relayer_work(
+			relayer: &T::AccountId,
+			delivered_messages: MessageNonce,
+			submitted_headers: u32,
+		) {
+			RelayerStats::<T>::mutate(relayer, |stats| {
+				stats.delivered_messages =
+					stats.delivered_messages.saturating_add(delivered_messages);
+				stats.submitted_headers = stats.submitted_headers.saturating_add(submitted_headers);
+			});
+		}
+
+		/// Note that the relayer has been refunded for submitting bridge transaction.
+		///
+		/// Increases the total amount of rewards in the relayer statistics.
+		pub fn note_refunded_reward(relayer: &T::AccountId, reward: T::Reward) {
+			RelayerStats::<T>::mutate(relayer, |stats| {
+				stats.total_rewards = stats.total_rewards.saturating_add(reward);
+			});
+		}
+
+		/// Note that the relayer has not been refunded for submitting bridge transaction.
+		///
+		/// Increases the number of failed refunds in the relayer statistics.
+		pub fn note_failed_refund(relayer: &T::AccountId) {
+			RelayerStats::<T>::mutate(relayer, |stats| {
+				stats.failed_refunds = stats.failed_refunds.saturating_add(1);
+			});
+		}
+
+		/// Note that the relayer has submitted invalid (e.g. obsolete) bridge transaction.
+		pub fn note_invalid_submission(relayer: &T::AccountId) {
+			InvalidSubmissions::<T>::mutate(relayer, |invalid_submissions| {
+				*invalid_submissions = invalid_submissions.saturating_add(1);
+			});
+		}
+
+		/// Note that the relayer has been refunded for serving the lane during given refund era.
+		///
+		/// Increases the total amount of refunds, paid for serving the lane during the era, and
+		/// returns the updated total.
+		pub fn note_lane_refund(lane_id: LaneId, era: u32, reward: T::Reward) -> T::Reward {
+			TotalRefunded::<T>::mutate(lane_id, era, |total_refunded| {
+				*total_refunded = total_refunded.saturating_add(reward);
+				*total_refunded
+			})
+		}
+
+		/// Note that the relayer has not been refunded, because the refund budget of the lane for
+		/// given refund era is exhausted.
+		///
+		/// It only deposits the `RefundBudgetExhausted` event.
+		pub fn note_exhausted_refund_budget(
+			lane_id: LaneId,
+			era: u32,
+			relayer: &T::AccountId,
+			reward: T::Reward,
+		) {
+			Self::deposit_event(Event::<T>::RefundBudgetExhausted {
+				relayer: relayer.clone(),
+				lane_id,
+				era,
+				reward,
+			});
+		}
+
+		/// Note that the relayer has not been refunded for submitting bridge transaction.
+		///
+		/// It only deposits the `RefundSkipped` event.
+		pub fn note_skipped_refund(
+			lane_id: LaneId,
+			relayer: &T::AccountId,
+			reason: RefundSkipReason,
+		) {
+			Self::deposit_event(Event::<T>::RefundSkipped {
+				relayer: relayer.clone(),
+				lane_id,
+				reason,
+			});
+		}
+
+		/// Note that the relayer has submitted bridge transaction that has not advanced the bridge
+		/// state (e.g. message delivery transaction that has not delivered any new messages).
+		///
+		/// Increments the misbehavior score of the relayer and deposits the `NoOpSubmission`
+		/// event.
+		pub fn note_no_op_submission(lane_id: LaneId, relayer: &T::AccountId) {
+			let misbehavior_score = MisbehaviorScore::<T>::mutate(relayer, |misbehavior_score| {
+				*misbehavior_score = misbehavior_score.saturating_add(1);
+				*misbehavior_score
+			});
+
+			Self::deposit_event(Event::<T>::NoOpSubmission {
+				relayer: relayer.clone(),
+				lane_id,
+				misbehavior_score,
+			});
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Reward has been paid to the relayer.
+		RewardPaid {
+			/// Relayer account that has been rewarded.
+			relayer: T::AccountId,
+			/// Relayer has received reward from this rewards account.
+			rewards_account_params: RewardsAccountParams,
+			/// Reward amount.
+			reward: T::Reward,
+		},
+		/// Reward of the relayer has been paid to other account.
+		RewardPaidToBeneficiary {
+			/// Relayer account that has claimed the reward.
+			relayer: T::AccountId,
+			/// Account that has received the reward.
+			beneficiary: T::AccountId,
+			/// Relayer has received reward from this rewards account.
+			rewards_account_params: RewardsAccountParams,
+			/// Reward amount.
+			reward: T::Reward,
+		},
+		/// Reward would have been registered for the relayer, if the shadow mode was disabled.
+		ShadowReward {
+			/// Relayer account that would have been rewarded.
+			relayer: T::AccountId,
+			/// Relayer would have been rewarded in this rewards account.
+			rewards_account_params: RewardsAccountParams,
+			/// Reward amount.
+			reward: T::Reward,
+		},
+		/// Reward has not been registered for the relayer.
+		RewardRegistrationFailed {
+			/// Relayer account that should have been rewarded.
+			relayer: T::AccountId,
+			/// Relayer should have been rewarded in this rewards account.
+			rewards_account_params: RewardsAccountParams,
+			/// Reward amount.
+			reward: T::Reward,
+		},
+		/// Relayer has been refunded for submitting bridge transaction.
+		RelayerRefunded {
+			/// Relayer account that has been refunded.
+			relayer: T::AccountId,
+			/// Relayer has been refunded for serving this lane.
+			lane_id: LaneId,
+			/// Registered reward amount.
+			reward: T::Reward,
+			/// Estimated equivalent of the reward in the bridged chain fee units, if the
+			/// conversion rate is configured.
+			bridged_equivalent_reward: Option<T::Reward>,
+		},
+		/// Relayer has not been refunded for submitting bridge transaction.
+		RefundSkipped {
+			/// Relayer account that has submitted the transaction.
+			relayer: T::AccountId,
+			/// The transaction has been targeting this lane.
+			lane_id: LaneId,
+			/// Reason why the relayer has not been refunded.
+			reason: RefundSkipReason,
+		},
+		/// Relayer has not been refunded, because the refund budget of the lane is exhausted.
+		RefundBudgetExhausted {
+			/// Relayer account that should have been refunded.
+			relayer: T::AccountId,
+			/// Relayer should have been refunded for serving this lane.
+			lane_id: LaneId,
+			/// Refund era, which budget is exhausted.
+			era: u32,
+			/// Withheld reward amount.
+			reward: T::Reward,
+		},
+		/// Relayer rewards have been transferred to other account.
+		RewardsTransferred {
+			/// Relayer account that has transferred its rewards.
+			relayer: T::AccountId,
+			/// Account that has received the rewards.
+			new_account: T::AccountId,
+			/// Rewards in this rewards account have been transferred.
+			rewards_account_params: RewardsAccountParams,
+			/// Transferred reward amount.
+			reward: T::Reward,
+		},
+		/// Relayer has submitted bridge transaction that has not advanced the bridge state.
+		NoOpSubmission {
+			/// Relayer account that has submitted the transaction.
+			relayer: T::AccountId,
+			/// The transaction has been targeting this lane.
+			lane_id: LaneId,
+			/// Updated misbehavior score of the relayer.
+			misbehavior_score: u32,
+		},
+		/// Multiplier of relayer refunds, registered in the rewards account, has been updated.
+		LaneRewardMultiplierUpdated {
+			/// Rewards account which multiplier has been updated.
+			rewards_account_params: RewardsAccountParams,
+			/// New multiplier.
+			multiplier: FixedU128,
+		},
+		/// Relayer has been registered.
+		RelayerRegistered {
+			/// Registered relayer account.
+			relayer: T::AccountId,
+			/// Total stake that is reserved from the relayer account.
+			stake: BalanceOf<T>,
+		},
+		/// Relayer has been deregistered.
+		RelayerDeregistered {
+			/// Deregistered relayer account.
+			relayer: T::AccountId,
+			/// Stake that has been unreserved.
+			stake: BalanceOf<T>,
+		},
+		/// Pending rewards of the relayer have been cancelled.
+		PendingRewardsCancelled {
+			/// Relayer account, which rewards have been cancelled.
+			relayer: T::AccountId,
+			/// Relayer has been rewarded in this rewards account.
+			rewards_account_params: RewardsAccountParams,
+			/// Reward era, during which the rewards have been registered.
+			era: u32,
+			/// Cancelled reward amount.
+			reward: T::Reward,
+		},
+		/// Registered relayer has been slashed and deregistered.
+		RelayerSlashed {
+			/// Slashed relayer account.
+			relayer: T::AccountId,
+			/// Slashed part of the relayer stake.
+			slashed: BalanceOf<T>,
+		},
+		/// Relayers allowlist of the lane has been updated.
+		LaneRelayersAllowlistUpdated {
+			/// Lane, which allowlist has been updated.
+			lane_id: LaneId,
+			/// Number of relayers in the new allowlist. `None` if the allowlist has been removed.
+			allowlisted_relayers: Option<u32>,
+		},
+		/// Balance of the account that pays rewards from the rewards account is below the
+		/// threshold.
+		LowRewardsPot {
+			/// Rewards account, which rewards are paid from the account.
+			rewards_account_params: RewardsAccountParams,
+			/// Current balance of the account.
+			balance: T::Reward,
+			/// The `LowRewardsPotThreshold`.
+			threshold: T::Reward,
+		},
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// No reward can be claimed by given relayer.
+		NoRewardForRelayer,
+		/// Reward payment procedure has failed.
+		FailedToPayReward,
+		/// Relayer has tried to transfer rewards to itself.
+		RewardsTransferToSelf,
+		/// Relayer reward would overflow after registering new reward.
+		RewardOverflow,
+		/// Relayer is already registered with the required stake.
+		AlreadyRegistered,
+		/// Relayer is not registered.
+		NotRegistered,
+		/// Failed to reserve the relayer stake.
+		FailedToReserveStake,
+		/// There are no pending rewards to cancel.
+		NoPendingRewards,
+		/// Relayer already has pending rewards of the maximal number of reward eras.
+		TooManyPendingRewards,
+	}
+
+	/// Map of the relayer and rewards account => accumulated reward.
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_reward)]
+	pub type RelayerRewards<T: Config> = StorageDoubleMap<
+		_,
+		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Hasher1,
+		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Key1,
+		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Hasher2,
+		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Key2,
+		<RelayerRewardsKeyProviderOf<T> as StorageDoubleMapKeyProvider>::Value,
+		OptionQuery,
+	>;
+
+	/// Map of the relayer and rewards account => number of the block where its reward has been
+	/// updated last time.
+	///
+	/// The entry is removed together with the `RelayerRewards` entry, when the reward is claimed or
+	/// transferred to other account. So claiming rewards also resets the throttling of relayer
+	/// rewards.
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_reward_updated_at)]
+	pub type RelayerRewardsUpdatedAt<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Identity,
+		RewardsAccountParams,
+		T::BlockNumber,
+		OptionQuery,
+	>;
+
+	/// Map of the relayer => number of invalid (e.g. obsolete) bridge transactions it has
+	/// submitted.
+	///
+	/// Changes of transactions that are rejected by `pre_dispatch` are discarded by the block
+	/// builder, so the counter is only increased by `post_dispatch` of transactions, that have
+	/// been included into the block but have turned out to be obsolete. It only happens if the
+	/// refund extension is configured to track such transactions. The counter is only used for
+	/// monitoring relayers and is never decreased.
+	#[pallet::storage]
+	#[pallet::getter(fn invalid_submissions)]
+	pub type InvalidSubmissions<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Map of the relayer => its statistics.
+	///
+	/// It is only filled by the code that refunds relayers, if it is configured to collect
+	/// statistics. Statistics are only used for monitoring relayers and are never decreased.
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_statistics)]
+	pub type RelayerStats<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, RelayerStatistics<T::Reward>, ValueQuery>;
+
+	/// Map of the lane => refund era => total amount of refunds, paid for serving the lane during
+	/// the era.
+	///
+	/// It is only filled by the code that refunds relayers, if it is configured to account
+	/// refunds. Entries of previous eras are kept, so that they could be inspected later.
+	#[pallet::storage]
+	#[pallet::getter(fn total_refunded)]
+	pub type TotalRefunded<T: Config> =
+		StorageDoubleMap<_, Identity, LaneId, Twox64Concat, u32, T::Reward, ValueQuery>;
+
+	/// Map of the relayer => number of bridge transactions that it has submitted and that have not
+	/// advanced the bridge state.
+	///
+	/// The score is only increased by the code that refunds relayers (if it is configured to do
+	/// so) and is never decreased. It may be used to detect spamming relayers.
+	#[pallet::storage]
+	#[pallet::getter(fn misbehavior_score)]
+	pub type MisbehaviorScore<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Recently refunded message deliveries: lane, nonce of the last delivered message and number
+	/// of the block where the delivery has been refunded.
+	///
+	/// It is only filled by the code that refunds relayers, if it needs to reject duplicate
+	/// deliveries. Entries are removed when they're too old or when there are too many entries.
+	#[pallet::storage]
+	pub type RecentlyRefundedDeliveries<T: Config> = StorageValue<
+		_,
+		BoundedVec<
+			(LaneId, MessageNonce, T::BlockNumber),
+			ConstU32<MAX_RECENTLY_REFUNDED_DELIVERIES>,
+		>,
+		ValueQuery,
+	>;
+
+	/// Map of the relayer => stake that is reserved from its account while it is registered.
+	#[pallet::storage]
+	#[pallet::getter(fn relayer_stake)]
+	pub type RegisteredRelayers<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BalanceOf<T>, OptionQuery>;
+
+	/// Map of the relayer, rewards account and reward era => reward that is not yet claimable.
+	///
+	/// It is only filled if the `RewardPayoutDelay` is non-zero. Pending rewards are moved to the
+	/// `RelayerRewards` when they are claimed after the delay, or when new reward is registered.
+	/// There are at most `max_pending_reward_eras` entries for every relayer and rewards account.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_reward)]
+	pub type PendingRewards<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Identity, RewardsAccountParams>,
+			NMapKey<Twox64Concat, u32>,
+		),
+		T::Reward,
+		OptionQuery,
+	>;
+
+	/// Map of the lane => relayers that may deliver messages over the lane.
+	///
+	/// Lanes without allowlists may be served by any relayer.
+	#[pallet::storage]
+	#[pallet::getter(fn lane_relayers_allowlist)]
+	pub type LaneRelayersAllowlist<T: Config> = StorageMap<
+		_,
+		Identity,
+		LaneId,
+		BoundedVec<T::AccountId, T::MaxAllowlistedRelayers>,
+		OptionQuery,
+	>;
+
+	/// Default multiplier of relayer refunds.
+	#[pallet::type_value]
+	pub fn DefaultLaneRewardMultiplier() -> FixedU128 {
+		FixedU128::one()
+	}
+
+	/// Map of the rewards account => multiplier that is applied to relayer refunds, registered
+	/// in this rewards account.
+	///
+	/// Lanes with low traffic may use larger multipliers to attract relayers. Multipliers of
+	/// message delivery and confirmation delivery rewards at the same lane may be different.
+	#[pallet::storage]
+	#[pallet::getter(fn lane_reward_multiplier)]
+	pub type LaneRewardMultiplier<T: Config> = StorageMap<
+		_,
+		Identity,
+		RewardsAccountParams,
+		FixedU128,
+		ValueQuery,
+		DefaultLaneRewardMultiplier,
+	>;
+}
+
+/// Returns maximal number of reward eras with pending rewards of the same relayer in the same
+/// rewards account.
+///
+/// Pending rewards that have become claimable are unlocked before new pending reward is
+/// registered. So there are at most `RewardPayoutDelay / RewardEraLength` eras that have ended,
+/// but are not yet unlocked, the current era and the era that is partially covered by the
+/// delay. It is never zero, so pending rewards may be claimed even if the `RewardPayoutDelay`
+/// has been changed to zero.
+pub fn max_pending_reward_eras<T: Config>() -> u32 {
+	let era_length = T::RewardEraLength::get().max(1u32.into());
+	let delay_eras: u32 = (T::RewardPayoutDelay::get() / era_length).unique_saturated_into();
+	delay_eras.saturating_add(2)
+}
+
+/// Weight of unlocking pending rewards of the relayer in given rewards account.
+///
+/// For every pending reward we need to read and remove it, and to read and update the claimable
+/// reward and the block of its last update.
+fn unlock_pending_rewards_weight<T: Config>() -> Weight {
+	T::DbWeight::get()
+		.reads_writes(3, 3)
+		.saturating_mul(max_pending_reward_eras::<T>() as u64)
+}
+
+/// Weight of claiming rewards from the single rewards account.
+///
+/// In addition to the `WeightInfo::claim_rewards`, it includes the weight of unlocking pending
+/// rewards and of removing the block of the last reward update.
+fn claim_rewards_weight<T: Config>() -> Weight {
+	T::WeightInfo::claim_rewards()
+		.saturating_add(unlock_pending_rewards_weight::<T>())
+		.saturating_add(T::DbWeight::get().writes(1))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use mock::{RuntimeEvent as TestEvent, *};
+
+	use crate::Event::RewardPaid;
+	use frame_support::{
+		assert_noop, assert_ok,
+		traits::fungible::{Inspect, Mutate},
+	};
+	use frame_system::{EventRecord, Pallet as System, Phase};
+	use sp_runtime::DispatchError;
+
+	fn get_ready_for_events() {
+		System::<TestRuntime>::set_block_number(1);
+		System::<TestRuntime>::reset_events();
+	}
+
+	#[test]
+	fn root_cant_claim_anything() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards_from_account(
+					RuntimeOrigin::root(),
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				DispatchError::BadOrigin,
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_cant_claim_if_no_reward_exists() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards_from_account(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Error::<TestRuntime>::NoRewardForRelayer,
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_cant_claim_if_payment_procedure_fails() {
+		run_test(|| {
+			RelayerRewards::<TestRuntime>::insert(
+				FAILING_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				100,
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards_from_account(
+					RuntimeOrigin::signed(FAILING_RELAYER),
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Error::<TestRuntime>::FailedToPayReward,
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_can_claim_all_rewards_of_the_lane() {
+		run_test(|| {
+			let confirmation_rewards_account_params =
+				RewardsAccountParams::new(TEST_LANE_ID, RewardKind::DeliveryConfirmation);
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				100,
+			);
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				confirmation_rewards_account_params,
+				50,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_LANE_ID,
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(
+					REGULAR_RELAYER,
+					confirmation_rewards_account_params
+				),
+				None
+			);
+
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					TEST_LANE_ID,
+				),
+				Error::<TestRuntime>::NoRewardForRelayer,
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_can_claim_rewards_for_multiple_lanes() {
+		run_test(|| {
+			get_ready_for_events();
+
+			let lane1 =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				100,
+			);
+			RelayerRewards::<TestRuntime>::insert(REGULAR_RELAYER, lane1, 200);
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_for_lanes(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				vec![TEST_REWARDS_ACCOUNT_PARAMS, lane1].try_into().unwrap(),
+				None,
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+			assert_eq!(RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, lane1), None);
+
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Relayers(RewardPaid {
+							relayer: REGULAR_RELAYER,
+							rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+							reward: 100
+						}),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Relayers(RewardPaid {
+							relayer: REGULAR_RELAYER,
+							rewards_account_params: lane1,
+							reward: 200
+						}),
+						topics: vec![],
+					},
+				],
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_can_claim_rewards_for_lanes_with_and_without_rewards() {
+		run_test(|| {
+			let lane1 =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
+			RelayerRewards::<TestRuntime>::insert(REGULAR_RELAYER, lane1, 200);
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_for_lanes(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				vec![TEST_REWARDS_ACCOUNT_PARAMS, lane1].try_into().unwrap(),
+				None,
+			));
+			assert_eq!(RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, lane1), None);
+		});
+	}
+
+	#[test]
+	fn relayer_cant_claim_rewards_for_lanes_without_rewards() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards_for_lanes(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					vec![
+						TEST_REWARDS_ACCOUNT_PARAMS,
+						RewardsAccountParams::new(
+							LaneId([0, 0, 0, 1]),
+							RewardKind::MessageDelivery
+						),
+					]
+					.try_into()
+					.unwrap(),
+					None,
+				),
+				Error::<TestRuntime>::NoRewardForRelayer,
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_can_claim_rewards_to_beneficiary() {
+		run_test(|| {
+			get_ready_for_events();
+
+			let lane1 =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
+			RelayerRewards::<TestRuntime>::insert(
+				FAILING_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				100,
+			);
+			RelayerRewards::<TestRuntime>::insert(FAILING_RELAYER, lane1, 200);
+
+			// payments to the relayer account are failing
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards_for_lanes(
+					RuntimeOrigin::signed(FAILING_RELAYER),
+					vec![TEST_REWARDS_ACCOUNT_PARAMS, lane1].try_into().unwrap(),
+					None,
+				),
+				Error::<TestRuntime>::FailedToPayReward,
+			);
+
+			// but the relayer may claim rewards to other account
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_for_lanes(
+				RuntimeOrigin::signed(FAILING_RELAYER),
+				vec![TEST_REWARDS_ACCOUNT_PARAMS, lane1].try_into().unwrap(),
+				Some(NEW_RELAYER),
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(FAILING_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+			assert_eq!(RelayerRewards::<TestRuntime>::get(FAILING_RELAYER, lane1), None);
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Relayers(Event::RewardPaidToBeneficiary {
+							relayer: FAILING_RELAYER,
+							beneficiary: NEW_RELAYER,
+							rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+							reward: 100
+						}),
+						topics: vec![],
+					},
+					EventRecord {
+						phase: Phase::Initialization,
+						event: TestEvent::Relayers(Event::RewardPaidToBeneficiary {
+							relayer: FAILING_RELAYER,
+							beneficiary: NEW_RELAYER,
+							rewards_account_params: lane1,
+							reward: 200
+						}),
+						topics: vec![],
+					},
+				],
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_can_claim_reward() {
+		run_test(|| {
+			get_ready_for_events();
+
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				100,
+			);
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_from_account(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_REWARDS_ACCOUNT_PARAMS
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+
+			//Check if the `RewardPaid` event was emitted.
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(RewardPaid {
+						relayer: REGULAR_RELAYER,
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						reward: 100
+					}),
+					topics: vec![],
+				}],
+			);
+		});
+	}
+
+	#[test]
+	fn register_relayer_reward_updates_block_stamp() {
+		run_test(|| {
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward_updated_at(
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(1),
+			);
+
+			System::<TestRuntime>::set_block_number(5);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward_updated_at(
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(5),
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(200)
+			);
+		});
+	}
+
+	#[test]
+	fn register_relayer_reward_applies_decay_to_old_reward() {
+		run_test(|| {
+			RewardDecayPerBlock::set(Perbill::from_percent(10));
+
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				1000
+			));
+
+			// 1000 * 0.9 * 0.9 = 810
+			System::<TestRuntime>::set_block_number(3);
+			assert_eq!(
+				Pallet::<TestRuntime>::claimable_reward(
+					&REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(810),
+			);
+
+			// 810 + 100 = 910
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(910),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::claimable_reward(
+					&REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(910),
+			);
+		});
+	}
+
+	#[test]
+	fn claim_rewards_pays_decayed_reward() {
+		run_test(|| {
+			RewardDecayPerBlock::set(Perbill::from_percent(10));
+
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				1000
+			));
+
+			// 1000 * 0.9 * 0.9 * 0.9 = 729
+			System::<TestRuntime>::set_block_number(4);
+			System::<TestRuntime>::reset_events();
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_from_account(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_REWARDS_ACCOUNT_PARAMS
+			));
+			assert_eq!(
+				System::<TestRuntime>::events(),
+				vec![EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(RewardPaid {
+						relayer: REGULAR_RELAYER,
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						reward: 729
+					}),
+					topics: vec![],
+				}],
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward_updated_at(
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				None,
+			);
+		});
+	}
+
+	#[test]
+	fn reward_does_not_decay_without_block_stamp() {
+		run_test(|| {
+			RewardDecayPerBlock::set(Perbill::from_percent(10));
+
+			System::<TestRuntime>::set_block_number(10);
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				100,
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::claimable_reward(
+					&REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(100),
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_may_transfer_rewards_to_new_account() {
+		run_test(|| {
+			get_ready_for_events();
+
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_ok!(Pallet::<TestRuntime>::transfer_accrued_rewards(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				NEW_RELAYER,
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward_updated_at(
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				None,
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(NEW_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(100)
+			);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::RewardsTransferred {
+						relayer: REGULAR_RELAYER,
+						new_account: NEW_RELAYER,
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						reward: 100,
+					}),
+					topics: vec![],
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn transferred_rewards_are_merged_with_existing_rewards() {
+		run_test(|| {
+			let lane1 =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&NEW_RELAYER,
+				200
+			));
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				lane1,
+				&REGULAR_RELAYER,
+				300
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::transfer_accrued_rewards(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				NEW_RELAYER,
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(NEW_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(300)
+			);
+			// rewards for serving other lanes are not transferred
+			assert_eq!(RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, lane1), Some(300));
+			assert_eq!(RelayerRewards::<TestRuntime>::get(NEW_RELAYER, lane1), None);
+		});
+	}
+
+	#[test]
+	fn relayer_cant_transfer_missing_rewards_or_transfer_rewards_to_self() {
+		run_test(|| {
+			assert_noop!(
+				Pallet::<TestRuntime>::transfer_accrued_rewards(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					NEW_RELAYER,
+				),
+				Error::<TestRuntime>::NoRewardForRelayer,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::transfer_accrued_rewards(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					REGULAR_RELAYER,
+				),
+				Error::<TestRuntime>::RewardsTransferToSelf,
+			);
+		});
+	}
+
+	#[test]
+	fn pay_relayer_reward_pays_reward_immediately() {
+		run_test(|| {
+			get_ready_for_events();
+
+			assert_ok!(Pallet::<TestRuntime>::pay_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward_updated_at(
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(1),
+			);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(RewardPaid {
+						relayer: REGULAR_RELAYER,
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						reward: 100,
+					}),
+					topics: vec![],
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn pay_relayer_reward_applies_decay_to_old_reward() {
+		run_test(|| {
+			RewardDecayPerBlock::set(Perbill::from_percent(10));
+
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				1000
+			));
+
+			// 1000 * 0.9 * 0.9 = 810
+			System::<TestRuntime>::set_block_number(3);
+			assert_ok!(Pallet::<TestRuntime>::pay_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(810),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward_updated_at(
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(3),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::claimable_reward(
+					&REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(810),
+			);
+		});
+	}
+
+	#[test]
+	fn pay_relayer_reward_registers_reward_if_payment_fails() {
+		run_test(|| {
+			get_ready_for_events();
+
+			assert_ok!(Pallet::<TestRuntime>::pay_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&FAILING_RELAYER,
+				100
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(FAILING_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(100)
+			);
+			assert!(System::<TestRuntime>::events().is_empty());
+		});
+	}
+
+	#[test]
+	fn register_relayer_reward_fails_on_overflow() {
+		run_test(|| {
+			get_ready_for_events();
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				Balance::MAX,
+			);
+
+			assert_eq!(
+				Pallet::<TestRuntime>::register_relayer_reward(
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					&REGULAR_RELAYER,
+					1
+				)
+				.map_err(DispatchError::from),
+				Err(Error::<TestRuntime>::RewardOverflow.into()),
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(Balance::MAX),
+			);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::RewardRegistrationFailed {
+						relayer: REGULAR_RELAYER,
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						reward: 1,
+					}),
+					topics: vec![],
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_rewards_returns_rewards_for_all_lanes() {
+		run_test(|| {
+			let lane1 =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
+			let lane2 =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 2]), RewardKind::MessageDelivery);
+
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				lane1,
+				&REGULAR_RELAYER,
+				200
+			));
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				lane2,
+				&REGULAR_RELAYER,
+				300
+			));
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(lane1, &NEW_RELAYER, 400));
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				RewardsAccountParams::new(LaneId([0, 0, 0, 3]), RewardKind::MessageDelivery),
+				0,
+			);
+
+			let mut rewards = Pallet::<TestRuntime>::relayer_rewards(&REGULAR_RELAYER);
+			rewards.sort();
+			assert_eq!(
+				rewards,
+				vec![(TEST_REWARDS_ACCOUNT_PARAMS, 100), (lane1, 200), (lane2, 300)]
+			);
+			assert!(Pallet::<TestRuntime>::relayer_rewards(&FAILING_RELAYER).is_empty());
+		});
+	}
+
+	#[test]
+	fn recently_refunded_deliveries_are_pruned() {
+		run_test(|| {
+			let lane1 = LaneId([0, 0, 0, 1]);
+
+			System::<TestRuntime>::set_block_number(1);
+			Pallet::<TestRuntime>::note_refunded_delivery(TEST_LANE_ID, 100, 5);
+			assert!(Pallet::<TestRuntime>::is_delivery_recently_refunded(TEST_LANE_ID, 100, 5));
+			assert!(!Pallet::<TestRuntime>::is_delivery_recently_refunded(TEST_LANE_ID, 101, 5));
+			assert!(!Pallet::<TestRuntime>::is_delivery_recently_refunded(lane1, 100, 5));
+
+			// old entries are ignored and then pruned
+			System::<TestRuntime>::set_block_number(6);
+			assert!(!Pallet::<TestRuntime>::is_delivery_recently_refunded(TEST_LANE_ID, 100, 5));
+			Pallet::<TestRuntime>::note_refunded_delivery(lane1, 100, 5);
+			assert_eq!(
+				RecentlyRefundedDeliveries::<TestRuntime>::get().into_inner(),
+				vec![(lane1, 100, 6)],
+			);
+
+			// when there are too many entries, the oldest is removed
+			for nonce in 0..MAX_RECENTLY_REFUNDED_DELIVERIES as MessageNonce {
+				Pallet::<TestRuntime>::note_refunded_delivery(TEST_LANE_ID, nonce, 5);
+			}
+			let deliveries = RecentlyRefundedDeliveries::<TestRuntime>::get();
+			assert_eq!(deliveries.len(), MAX_RECENTLY_REFUNDED_DELIVERIES as usize);
+			assert_eq!(deliveries.first(), Some(&(TEST_LANE_ID, 0, 6)));
+			assert!(!Pallet::<TestRuntime>::is_delivery_recently_refunded(lane1, 100, 5));
+		});
+	}
+
+	#[test]
+	fn root_may_set_lane_reward_multiplier() {
+		run_test(|| {
+			get_ready_for_events();
+
+			let multiplier = FixedU128::from_rational(3, 2);
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_reward_multiplier(TEST_REWARDS_ACCOUNT_PARAMS),
+				FixedU128::one()
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_reward_multiplier(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					multiplier,
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::set_lane_reward_multiplier(
+				RuntimeOrigin::root(),
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				multiplier,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_reward_multiplier(TEST_REWARDS_ACCOUNT_PARAMS),
+				multiplier
+			);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::LaneRewardMultiplierUpdated {
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						multiplier,
+					}),
+					topics: vec![],
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn delivery_and_confirmation_rewards_are_tracked_separately() {
+		run_test(|| {
+			let confirmation_rewards_account_params =
+				RewardsAccountParams::new(TEST_LANE_ID, RewardKind::DeliveryConfirmation);
+
+			assert_ok!(Pallet::<TestRuntime>::set_lane_reward_multiplier(
+				RuntimeOrigin::root(),
+				confirmation_rewards_account_params,
+				FixedU128::from_rational(1, 2),
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::lane_reward_multiplier(TEST_REWARDS_ACCOUNT_PARAMS),
+				FixedU128::one()
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				confirmation_rewards_account_params,
+				&REGULAR_RELAYER,
+				20
+			));
+
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_from_account(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				confirmation_rewards_account_params
+			));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(
+					REGULAR_RELAYER,
+					confirmation_rewards_account_params
+				),
+				None
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(100)
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_may_register_and_deregister() {
+		run_test(|| {
+			get_ready_for_events();
+
+			Balances::mint_into(&REGULAR_RELAYER, 150).unwrap();
+			assert!(!Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+			assert_noop!(
+				Pallet::<TestRuntime>::deregister(RuntimeOrigin::signed(REGULAR_RELAYER)),
+				Error::<TestRuntime>::NotRegistered,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)));
+			assert!(Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+			assert_eq!(Pallet::<TestRuntime>::relayer_stake(REGULAR_RELAYER), Some(100));
+			assert_eq!(Balances::reserved_balance(&REGULAR_RELAYER), 100);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::RelayerRegistered {
+						relayer: REGULAR_RELAYER,
+						stake: 100,
+					}),
+					topics: vec![],
+				}),
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)),
+				Error::<TestRuntime>::AlreadyRegistered,
+			);
+
+			assert_ok!(Pallet::<TestRuntime>::deregister(RuntimeOrigin::signed(REGULAR_RELAYER)));
+			assert!(!Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+			assert_eq!(Pallet::<TestRuntime>::relayer_stake(REGULAR_RELAYER), None);
+			assert_eq!(Balances::reserved_balance(&REGULAR_RELAYER), 0);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::RelayerDeregistered {
+						relayer: REGULAR_RELAYER,
+						stake: 100,
+					}),
+					topics: vec![],
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_cant_register_without_required_stake() {
+		run_test(|| {
+			Balances::mint_into(&REGULAR_RELAYER, 50).unwrap();
+			assert_noop!(
+				Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)),
+				Error::<TestRuntime>::FailedToReserveStake,
+			);
+			assert!(!Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+		});
+	}
+
+	#[test]
+	fn relayer_may_top_up_stake_when_required_stake_is_increased() {
+		run_test(|| {
+			Balances::mint_into(&REGULAR_RELAYER, 500).unwrap();
+			assert_ok!(Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)));
+
+			RequiredStake::set(300);
+			assert!(!Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+
+			assert_ok!(Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)));
+			assert!(Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+			assert_eq!(Pallet::<TestRuntime>::relayer_stake(REGULAR_RELAYER), Some(300));
+			assert_eq!(Balances::reserved_balance(&REGULAR_RELAYER), 300);
+
+			// decreased required stake doesn't affect active registrations
+			RequiredStake::set(100);
+			assert!(Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+			assert_noop!(
+				Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)),
+				Error::<TestRuntime>::AlreadyRegistered,
+			);
+		});
+	}
+
+	#[test]
+	fn slash_and_deregister_moves_slashed_stake_to_beneficiary() {
+		run_test(|| {
+			get_ready_for_events();
+
+			Balances::mint_into(&REGULAR_RELAYER, 150).unwrap();
+			Balances::mint_into(&SLASH_BENEFICIARY, 10).unwrap();
+			assert_ok!(Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)));
+
+			Pallet::<TestRuntime>::slash_and_deregister(&REGULAR_RELAYER);
+			assert!(!Pallet::<TestRuntime>::is_registration_active(&REGULAR_RELAYER));
+			assert_eq!(Pallet::<TestRuntime>::relayer_stake(REGULAR_RELAYER), None);
+			assert_eq!(Balances::reserved_balance(&REGULAR_RELAYER), 0);
+			assert_eq!(Balances::free_balance(&REGULAR_RELAYER), 110);
+			assert_eq!(Balances::free_balance(&SLASH_BENEFICIARY), 50);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::RelayerSlashed {
+						relayer: REGULAR_RELAYER,
+						slashed: 40,
+					}),
+					topics: vec![],
+				}),
+			);
+
+			// unregistered relayer is not slashed
+			Pallet::<TestRuntime>::slash_and_deregister(&REGULAR_RELAYER);
+			assert_eq!(Balances::free_balance(&REGULAR_RELAYER), 110);
+		});
+	}
+
+	#[test]
+	fn slash_and_deregister_burns_slashed_stake_without_beneficiary() {
+		run_test(|| {
+			SlashBeneficiary::set(None);
+			Balances::mint_into(&REGULAR_RELAYER, 150).unwrap();
+			assert_ok!(Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)));
+
+			Pallet::<TestRuntime>::slash_and_deregister(&REGULAR_RELAYER);
+			assert_eq!(Balances::reserved_balance(&REGULAR_RELAYER), 0);
+			assert_eq!(Balances::free_balance(&REGULAR_RELAYER), 110);
+			assert_eq!(<Balances as Currency<AccountId>>::total_issuance(), 110);
+
+			// slashed stake is also burned if beneficiary account doesn't exist
+			SlashBeneficiary::set(Some(SLASH_BENEFICIARY));
+			assert_ok!(Pallet::<TestRuntime>::register(RuntimeOrigin::signed(REGULAR_RELAYER)));
+			Pallet::<TestRuntime>::slash_and_deregister(&REGULAR_RELAYER);
+			assert_eq!(Balances::free_balance(&REGULAR_RELAYER), 70);
+			assert_eq!(Balances::free_balance(&SLASH_BENEFICIARY), 0);
+			assert_eq!(<Balances as Currency<AccountId>>::total_issuance(), 70);
+		});
+	}
+
+	#[test]
+	fn pending_rewards_become_claimable_after_payout_delay() {
+		run_test(|| {
+			RewardPayoutDelay::set(5);
+
+			// rewards of era 0 are pending until block 15
+			System::<TestRuntime>::set_block_number(1);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			System::<TestRuntime>::set_block_number(9);
+			assert_ok!(Pallet::<TestRuntime>::pay_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				50
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					0
+				)),
+				Some(150)
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::claimable_reward(
+					&REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				None
+			);
+
+			System::<TestRuntime>::set_block_number(14);
+			assert_eq!(
+				Pallet::<TestRuntime>::claimable_reward(
+					&REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				None
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::claim_rewards_from_account(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Error::<TestRuntime>::NoRewardForRelayer,
+			);
+
+			// when new reward is registered, pending rewards of previous eras are unlocked
+			System::<TestRuntime>::set_block_number(15);
+			assert_eq!(
+				Pallet::<TestRuntime>::claimable_reward(
+					&REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS
+				),
+				Some(150)
+			);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				10
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					0
+				)),
+				None
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					1
+				)),
+				Some(10)
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				Some(150)
+			);
+
+			// only claimable rewards are claimed
+			get_ready_for_events();
+			System::<TestRuntime>::set_block_number(15);
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_from_account(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_REWARDS_ACCOUNT_PARAMS
+			));
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(RewardPaid {
+						relayer: REGULAR_RELAYER,
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						reward: 150,
+					}),
+					topics: vec![],
+				}),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_reward(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS),
+				None
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					1
+				)),
+				Some(10)
+			);
+
+			// rewards of era 1 are claimable at block 25
+			System::<TestRuntime>::set_block_number(25);
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_from_account(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_REWARDS_ACCOUNT_PARAMS
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					1
+				)),
+				None
+			);
+		});
+	}
+
+	#[test]
+	fn number_of_pending_reward_eras_is_bounded() {
+		run_test(|| {
+			RewardPayoutDelay::set(5);
+			assert_eq!(max_pending_reward_eras::<TestRuntime>(), 2);
+
+			// e.g. pending rewards, registered before the `RewardEraLength` has been decreased
+			for era in [10, 11] {
+				PendingRewards::<TestRuntime>::insert(
+					(REGULAR_RELAYER, TEST_REWARDS_ACCOUNT_PARAMS, era),
+					100,
+				);
+			}
+
+			// reward of the new era is not registered
+			System::<TestRuntime>::set_block_number(1);
+			assert_eq!(
+				Pallet::<TestRuntime>::register_relayer_reward(
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					&REGULAR_RELAYER,
+					100
+				),
+				Err(Error::<TestRuntime>::TooManyPendingRewards),
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					0
+				)),
+				None
+			);
+
+			// but reward of the era that already has pending rewards is registered
+			System::<TestRuntime>::set_block_number(105);
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					10
+				)),
+				Some(200)
+			);
+		});
+	}
+
+	#[test]
+	fn root_may_cancel_pending_rewards() {
+		run_test(|| {
+			get_ready_for_events();
+			RewardPayoutDelay::set(5);
+
+			assert_ok!(Pallet::<TestRuntime>::register_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				100
+			));
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_pending_rewards(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					0,
+				),
+				DispatchError::BadOrigin,
+			);
+			assert_ok!(Pallet::<TestRuntime>::cancel_pending_rewards(
+				RuntimeOrigin::root(),
+				REGULAR_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				0,
+			));
+			assert_eq!(
+				Pallet::<TestRuntime>::pending_reward((
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					0
+				)),
+				None
+			);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::PendingRewardsCancelled {
+						relayer: REGULAR_RELAYER,
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						era: 0,
+						reward: 100,
+					}),
+					topics: vec![],
+				}),
+			);
+			assert_noop!(
+				Pallet::<TestRuntime>::cancel_pending_rewards(
+					RuntimeOrigin::root(),
+					REGULAR_RELAYER,
+					TEST_REWARDS_ACCOUNT_PARAMS,
+					0,
+				),
+				Error::<TestRuntime>::NoPendingRewards,
+			);
+		});
+	}
+
+	#[test]
+	fn note_invalid_submission_increments_counter() {
+		run_test(|| {
+			assert_eq!(Pallet::<TestRuntime>::invalid_submissions(REGULAR_RELAYER), 0);
+
+			Pallet::<TestRuntime>::note_invalid_submission(&REGULAR_RELAYER);
+			Pallet::<TestRuntime>::note_invalid_submission(&REGULAR_RELAYER);
+			assert_eq!(Pallet::<TestRuntime>::invalid_submissions(REGULAR_RELAYER), 2);
+			assert_eq!(Pallet::<TestRuntime>::invalid_submissions(FAILING_RELAYER), 0);
+		});
+	}
+
+	#[test]
+	fn note_lane_refund_accumulates_refunds_per_era() {
+		run_test(|| {
+			let lane1 = LaneId([0, 0, 0, 1]);
+
+			assert_eq!(Pallet::<TestRuntime>::note_lane_refund(TEST_LANE_ID, 1, 100), 100);
+			assert_eq!(Pallet::<TestRuntime>::note_lane_refund(TEST_LANE_ID, 1, 200), 300);
+			assert_eq!(Pallet::<TestRuntime>::note_lane_refund(TEST_LANE_ID, 2, 50), 50);
+			assert_eq!(Pallet::<TestRuntime>::note_lane_refund(lane1, 1, 10), 10);
+
+			assert_eq!(Pallet::<TestRuntime>::total_refunded(TEST_LANE_ID, 0), 0);
+			assert_eq!(Pallet::<TestRuntime>::total_refunded(TEST_LANE_ID, 1), 300);
+			assert_eq!(Pallet::<TestRuntime>::total_refunded(TEST_LANE_ID, 2), 50);
+			assert_eq!(Pallet::<TestRuntime>::total_refunded(lane1, 1), 10);
+		});
+	}
+
+	#[test]
+	fn note_no_op_submission_increments_misbehavior_score() {
+		run_test(|| {
+			get_ready_for_events();
+
+			assert_eq!(Pallet::<TestRuntime>::misbehavior_score(REGULAR_RELAYER), 0);
+
+			Pallet::<TestRuntime>::note_no_op_submission(TEST_LANE_ID, &REGULAR_RELAYER);
+			Pallet::<TestRuntime>::note_no_op_submission(TEST_LANE_ID, &REGULAR_RELAYER);
+			assert_eq!(Pallet::<TestRuntime>::misbehavior_score(REGULAR_RELAYER), 2);
+			assert_eq!(Pallet::<TestRuntime>::misbehavior_score(FAILING_RELAYER), 0);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::NoOpSubmission {
+						relayer: REGULAR_RELAYER,
+						lane_id: TEST_LANE_ID,
+						misbehavior_score: 2,
+					}),
+					topics: vec![],
+				}),
+			);
+		});
+	}
+
+	#[test]
+	fn lane_relayers_allowlist_may_be_changed_by_root_or_lane_owner() {
+		run_test(|| {
+			get_ready_for_events();
+
+			// any relayer may serve the lane without allowlist
+			assert!(Pallet::<TestRuntime>::is_relayer_allowlisted(TEST_LANE_ID, &REGULAR_RELAYER));
+			assert!(Pallet::<TestRuntime>::is_relayer_allowlisted(TEST_LANE_ID, &NEW_RELAYER));
+
+			// regular account can't change the allowlist
+			assert_noop!(
+				Pallet::<TestRuntime>::set_lane_relayers_allowlist(
+					RuntimeOrigin::signed(REGULAR_RELAYER),
+					TEST_LANE_ID,
+					Some(vec![REGULAR_RELAYER].try_into().unwrap()),
+				),
+				DispatchError::BadOrigin,
+			);
+
+			// lane owner may set the allowlist
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers_allowlist(
+				RuntimeOrigin::signed(LANE_OWNER),
+				TEST_LANE_ID,
+				Some(vec![REGULAR_RELAYER].try_into().unwrap()),
+			));
+			assert!(Pallet::<TestRuntime>::is_relayer_allowlisted(TEST_LANE_ID, &REGULAR_RELAYER));
+			assert!(!Pallet::<TestRuntime>::is_relayer_allowlisted(TEST_LANE_ID, &NEW_RELAYER));
+			assert!(Pallet::<TestRuntime>::is_relayer_allowlisted(
+				LaneId([0, 0, 0, 1]),
+				&NEW_RELAYER
+			));
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::LaneRelayersAllowlistUpdated {
+						lane_id: TEST_LANE_ID,
+						allowlisted_relayers: Some(1),
+					}),
+					topics: vec![],
+				}),
+			);
+
+			// root may remove the allowlist
+			assert_ok!(Pallet::<TestRuntime>::set_lane_relayers_allowlist(
+				RuntimeOrigin::root(),
+				TEST_LANE_ID,
+				None,
+			));
+			assert_eq!(Pallet::<TestRuntime>::lane_relayers_allowlist(TEST_LANE_ID), None);
+			assert!(Pallet::<TestRuntime>::is_relayer_allowlisted(TEST_LANE_ID, &NEW_RELAYER));
+		});
+	}
+
+	#[test]
+	fn low_rewards_pot_is_reported_after_payment() {
+		run_test(|| {
+			get_ready_for_events();
+			LowRewardsPotThreshold::set(100);
+
+			// nothing is reported if the payment procedure doesn't know the pot balance
+			assert_ok!(Pallet::<TestRuntime>::pay_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				10
+			));
+			assert_eq!(LowRewardsPots::get(), vec![]);
+
+			// nothing is reported if the pot balance is above the threshold
+			RewardsPotBalance::set(Some(100));
+			assert_ok!(Pallet::<TestRuntime>::pay_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				10
+			));
+			assert_eq!(LowRewardsPots::get(), vec![]);
+
+			// low pot balance is reported after immediate payment
+			RewardsPotBalance::set(Some(99));
+			assert_ok!(Pallet::<TestRuntime>::pay_relayer_reward(
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				&REGULAR_RELAYER,
+				10
+			));
+			assert_eq!(LowRewardsPots::get(), vec![(TEST_REWARDS_ACCOUNT_PARAMS, 99)]);
+			assert_eq!(
+				System::<TestRuntime>::events().last(),
+				Some(&EventRecord {
+					phase: Phase::Initialization,
+					event: TestEvent::Relayers(Event::LowRewardsPot {
+						rewards_account_params: TEST_REWARDS_ACCOUNT_PARAMS,
+						balance: 99,
+						threshold: 100,
+					}),
+					topics: vec![],
+				}),
+			);
+
+			// low pot balance is reported after claim
+			RewardsPotBalance::set(Some(50));
+			RelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				TEST_REWARDS_ACCOUNT_PARAMS,
+				100,
+			);
+			assert_ok!(Pallet::<TestRuntime>::claim_rewards_from_account(
+				RuntimeOrigin::signed(REGULAR_RELAYER),
+				TEST_REWARDS_ACCOUNT_PARAMS
+			));
+			assert_eq!(
+				LowRewardsPots::get(),
+				vec![(TEST_REWARDS_ACCOUNT_PARAMS, 99), (TEST_REWARDS_ACCOUNT_PARAMS, 50)]
+			);
+		});
+	}
+
+	#[test]
+	fn relayer_statistics_are_updated() {
+		run_test(|| {
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_statistics(REGULAR_RELAYER),
+				RelayerStatistics::default(),
+			);
+
+			Pallet::<TestRuntime>::note_relayer_work(&REGULAR_RELAYER, 10, 2);
+			Pallet::<TestRuntime>::note_relayer_work(&REGULAR_RELAYER, 5, 1);
+			Pallet::<TestRuntime>::note_refunded_reward(&REGULAR_RELAYER, 100);
+			Pallet::<TestRuntime>::note_refunded_reward(&REGULAR_RELAYER, 50);
+			Pallet::<TestRuntime>::note_failed_refund(&REGULAR_RELAYER);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_statistics(REGULAR_RELAYER),
+				RelayerStatistics {
+					delivered_messages: 15,
+					submitted_headers: 3,
+					total_rewards: 150,
+					failed_refunds: 1,
+				},
+			);
+			assert_eq!(
+				Pallet::<TestRuntime>::relayer_statistics(FAILING_RELAYER),
+				RelayerStatistics::default(),
+			);
+		});
+	}
+
+	#[test]
+	fn pay_reward_from_account_and_by_minting_actually_pay_reward() {
+		type Balances = pallet_balances::Pallet<TestRuntime>;
+		type PayRewardFromAccount =
+			bp_relayers::PayRewardFromAccount<Balances, AccountId, RewardsPotAccount>;
+		type PayRewardByMinting = bp_relayers::PayRewardByMinting<Balances, AccountId>;
+
+		frame_support::parameter_types! {
+			pub const RewardsPotAccount: AccountId = 100;
+		}
+
+		run_test(|| {
+			let lane0_delivery =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 0]), RewardKind::MessageDelivery);
+			let lane1_delivery =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
+
+			Balances::mint_into(&RewardsPotAccount::get(), 150).unwrap();
+
+			PayRewardFromAccount::pay_reward(&1, lane0_delivery, 100).unwrap();
+			assert_eq!(Balances::balance(&RewardsPotAccount::get()), 50);
+			assert_eq!(Balances::balance(&1), 100);
+			assert!(PayRewardFromAccount::pay_reward(&1, lane1_delivery, 100).is_err());
+
+			PayRewardByMinting::pay_reward(&1, lane1_delivery, 100).unwrap();
+			assert_eq!(Balances::balance(&RewardsPotAccount::get()), 50);
+			assert_eq!(Balances::balance(&1), 200);
 		});
 	}
 
@@ -249,6 +2899,10 @@ mod tests {
 		type PayLaneRewardFromAccount = bp_relayers::PayLaneRewardFromAccount<Balances, AccountId>;
 
 		run_test(|| {
+			let lane0_delivery =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 0]), RewardKind::MessageDelivery);
+			let lane1_delivery =
+				RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
 			let lane0_rewards_account =
 				PayLaneRewardFromAccount::lane_rewards_account(LaneId([0, 0, 0, 0]));
 			let lane1_rewards_account =
@@ -260,12 +2914,12 @@ mod tests {
 			assert_eq!(Balances::balance(&lane1_rewards_account), 100);
 			assert_eq!(Balances::balance(&1), 0);
 
-			PayLaneRewardFromAccount::pay_reward(&1, LaneId([0, 0, 0, 0]), 100).unwrap();
+			PayLaneRewardFromAccount::pay_reward(&1, lane0_delivery, 100).unwrap();
 			assert_eq!(Balances::balance(&lane0_rewards_account), 0);
 			assert_eq!(Balances::balance(&lane1_rewards_account), 100);
 			assert_eq!(Balances::balance(&1), 100);
 
-			PayLaneRewardFromAccount::pay_reward(&1, LaneId([0, 0, 0, 1]), 100).unwrap();
+			PayLaneRewardFromAccount::pay_reward(&1, lane1_delivery, 100).unwrap();
 			assert_eq!(Balances::balance(&lane0_rewards_account), 0);
 			assert_eq!(Balances::balance(&lane1_rewards_account), 0);
 			assert_eq!(Balances::balance(&1), 200);
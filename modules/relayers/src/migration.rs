@@ -0,0 +1,265 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of Parity Bridges Common.
+
+// Parity Bridges Common is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Bridges Common is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Bridges Common.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migrations of the relayers pallet.
+//!
+//! At storage version 0, the `RelayerRewards` map has been keyed by the relayer account and the
+//! lane id. At version 1, its second key is the `RewardsAccountParams`. Old entries are moved to
+//! the `RewardKind::MessageDelivery` rewards account of the lane.
+//!
+//! The number of old entries is not bounded, so they are migrated in several blocks. The
+//! `MigrateToV1` runtime upgrade migrates as many entries as fits into the quarter of the block
+//! and the rest is migrated by the `on_idle` hook of the pallet. Rewards, registered after the
+//! upgrade, are added to migrated rewards. Old rewards can't be claimed until they're migrated.
+
+use crate::{Config, Pallet, RelayerRewards, LOG_TARGET, STORAGE_VERSION};
+
+use bp_messages::LaneId;
+use bp_relayers::{RewardKind, RewardsAccountParams};
+use codec::Decode;
+use frame_support::{
+	pallet_prelude::*,
+	storage::{unhashed, StoragePrefixedMap},
+	traits::OnRuntimeUpgrade,
+	weights::Weight,
+};
+use sp_arithmetic::traits::{Saturating, Zero};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+/// Raw key of the last inspected `RelayerRewards` entry. Migration continues from the next key.
+#[frame_support::storage_alias]
+type MigrationCursor<T: Config> = StorageValue<Pallet<T>, Vec<u8>, OptionQuery>;
+
+/// Migrates the pallet storage from version 0 to version 1.
+///
+/// Migrates as many entries as fits into the quarter of the maximal block weight. The rest is
+/// migrated by the `on_idle` hook of the pallet (see `migrate_to_v1_step`).
+pub struct MigrateToV1<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+	fn on_runtime_upgrade() -> Weight {
+		migrate_to_v1_step::<T>(T::BlockWeights::get().max_block / 4)
+	}
+}
+
+/// Migrates lane-keyed `RelayerRewards` entries while there's enough `remaining_weight`.
+///
+/// Does nothing if the on-chain storage version is not 0. Sets the storage version to 1 when all
+/// entries are migrated. Returns the used weight.
+pub fn migrate_to_v1_step<T: Config>(remaining_weight: Weight) -> Weight {
+	// we'll need at least to read the storage version
+	let db_weight = T::DbWeight::get();
+	let mut used_weight = db_weight.reads(1);
+	if !remaining_weight.all_gte(used_weight) {
+		return Weight::zero()
+	}
+	if Pallet::<T>::on_chain_storage_version() != 0 {
+		return used_weight
+	}
+
+	// we'll need to read and update the cursor and to update the storage version
+	let step_weight = db_weight.reads_writes(1, 2);
+	// and for every entry: to read its key, to take its value and to update the new entry
+	let entry_weight = db_weight.reads_writes(3, 2);
+	if !remaining_weight.all_gte(used_weight + step_weight + entry_weight) {
+		return used_weight
+	}
+	used_weight += step_weight;
+
+	let prefix = RelayerRewards::<T>::final_prefix();
+	let mut key = MigrationCursor::<T>::get().unwrap_or_else(|| prefix.to_vec());
+	let mut migrated_entries = 0u32;
+	loop {
+		if !remaining_weight.all_gte(used_weight + entry_weight) {
+			MigrationCursor::<T>::put(key);
+			log::info!(
+				target: LOG_TARGET,
+				"Migrated {} relayer rewards entries to storage version {:?}. Migration continues",
+				migrated_entries,
+				STORAGE_VERSION,
+			);
+			return used_weight
+		}
+
+		used_weight += db_weight.reads(1);
+		key = match frame_support::sp_io::storage::next_key(&key)
+			.filter(|next_key| next_key.starts_with(&prefix))
+		{
+			Some(next_key) => next_key,
+			None => break,
+		};
+
+		// entries with new keys (registered after the upgrade or already migrated) are skipped
+		let (relayer, lane_id) = match decode_v0_key::<T>(&key[prefix.len()..]) {
+			Some(old_key) => old_key,
+			None => continue,
+		};
+		used_weight += db_weight.reads_writes(1, 1);
+		let reward: T::Reward = match unhashed::take(&key) {
+			Some(reward) => reward,
+			None => continue,
+		};
+		used_weight += db_weight.reads_writes(1, 1);
+		RelayerRewards::<T>::mutate(
+			relayer,
+			RewardsAccountParams::new(lane_id, RewardKind::MessageDelivery),
+			|new_reward| {
+				*new_reward = Some(new_reward.unwrap_or_else(Zero::zero).saturating_add(reward))
+			},
+		);
+		migrated_entries += 1;
+	}
+
+	MigrationCursor::<T>::kill();
+	STORAGE_VERSION.put::<Pallet<T>>();
+	log::info!(
+		target: LOG_TARGET,
+		"Migrated {} relayer rewards entries to storage version {:?}. Migration is completed",
+		migrated_entries,
+		STORAGE_VERSION,
+	);
+
+	used_weight
+}
+
+/// Decodes key of the `RelayerRewards` entry at storage version 0.
+///
+/// The key (without storage prefix) is the `Blake2_128Concat` hash of the relayer account,
+/// followed by the `Identity` hash of the lane id. Returns `None` if the key has other format.
+fn decode_v0_key<T: Config>(raw_key: &[u8]) -> Option<(T::AccountId, LaneId)> {
+	let mut input = raw_key.get(16..)?;
+	let relayer = T::AccountId::decode(&mut input).ok()?;
+	match *input {
+		[b0, b1, b2, b3] => Some((relayer, LaneId([b0, b1, b2, b3]))),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::mock::*;
+
+	/// `RelayerRewards` map at storage version 0.
+	#[frame_support::storage_alias]
+	type OldRelayerRewards<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		Identity,
+		LaneId,
+		<T as Config>::Reward,
+		OptionQuery,
+	>;
+
+	fn insert_old_relayer_rewards() {
+		// both maps have the same storage prefix
+		assert_eq!(
+			OldRelayerRewards::<TestRuntime>::final_prefix(),
+			RelayerRewards::<TestRuntime>::final_prefix(),
+		);
+
+		StorageVersion::new(0).put::<Pallet<TestRuntime>>();
+		for lane_index in 0..4u8 {
+			OldRelayerRewards::<TestRuntime>::insert(
+				REGULAR_RELAYER,
+				LaneId([0, 0, 0, lane_index]),
+				100 + lane_index as u64,
+			);
+		}
+	}
+
+	fn message_delivery_account(lane_index: u8) -> RewardsAccountParams {
+		RewardsAccountParams::new(LaneId([0, 0, 0, lane_index]), RewardKind::MessageDelivery)
+	}
+
+	#[test]
+	fn migration_to_v1_moves_lane_rewards_to_message_delivery_account() {
+		run_test(|| {
+			insert_old_relayer_rewards();
+			// reward that has been registered after the upgrade, but before migration
+			RelayerRewards::<TestRuntime>::insert(REGULAR_RELAYER, message_delivery_account(1), 10);
+
+			MigrateToV1::<TestRuntime>::on_runtime_upgrade();
+
+			assert_eq!(Pallet::<TestRuntime>::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(MigrationCursor::<TestRuntime>::get(), None);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::iter_prefix(REGULAR_RELAYER).collect::<Vec<_>>(),
+				vec![
+					(message_delivery_account(0), 100),
+					(message_delivery_account(1), 111),
+					(message_delivery_account(2), 102),
+					(message_delivery_account(3), 103),
+				],
+			);
+
+			// second run is a no-op
+			assert_eq!(
+				MigrateToV1::<TestRuntime>::on_runtime_upgrade(),
+				<TestRuntime as frame_system::Config>::DbWeight::get().reads(1),
+			);
+		});
+	}
+
+	#[test]
+	fn migration_to_v1_continues_in_next_blocks() {
+		run_test(|| {
+			insert_old_relayer_rewards();
+
+			// the first step migrates single entry
+			let db_weight = <TestRuntime as frame_system::Config>::DbWeight::get();
+			let step_weight = db_weight.reads_writes(2, 2) + db_weight.reads_writes(3, 2);
+			assert_eq!(migrate_to_v1_step::<TestRuntime>(step_weight), step_weight);
+			assert_eq!(Pallet::<TestRuntime>::on_chain_storage_version(), 0);
+			assert!(MigrationCursor::<TestRuntime>::get().is_some());
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, message_delivery_account(0)),
+				Some(100),
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, message_delivery_account(1)),
+				None,
+			);
+			assert_eq!(
+				OldRelayerRewards::<TestRuntime>::get(REGULAR_RELAYER, LaneId([0, 0, 0, 1])),
+				Some(101),
+			);
+
+			// not enough weight to migrate a single entry
+			assert_eq!(migrate_to_v1_step::<TestRuntime>(db_weight.reads(1)), db_weight.reads(1));
+
+			// the rest is migrated in next blocks
+			let mut steps = 1;
+			while Pallet::<TestRuntime>::on_chain_storage_version() == 0 {
+				assert!(steps < 16);
+				migrate_to_v1_step::<TestRuntime>(step_weight);
+				steps += 1;
+			}
+			assert_eq!(MigrationCursor::<TestRuntime>::get(), None);
+			assert_eq!(Pallet::<TestRuntime>::on_chain_storage_version(), STORAGE_VERSION);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::iter_prefix(REGULAR_RELAYER).collect::<Vec<_>>(),
+				(0..4u8)
+					.map(|lane_index| (
+						message_delivery_account(lane_index),
+						100 + lane_index as u64
+					))
+					.collect::<Vec<_>>(),
+			);
+		});
+	}
+}
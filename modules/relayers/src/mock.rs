@@ -19,12 +19,13 @@
 use crate as pallet_bridge_relayers;
 
 use bp_messages::LaneId;
-use bp_relayers::PaymentProcedure;
-use frame_support::{parameter_types, weights::RuntimeDbWeight};
+use bp_relayers::{OnLowRewardsPot, PaymentProcedure, RewardKind, RewardsAccountParams};
+use frame_support::{parameter_types, traits::AsEnsureOriginWithArg, weights::RuntimeDbWeight};
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header as SubstrateHeader,
 	traits::{BlakeTwo256, IdentityLookup},
+	Perbill,
 };
 
 pub type AccountId = u64;
@@ -47,6 +48,16 @@ frame_support::construct_runtime! {
 
 parameter_types! {
 	pub const DbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 1, write: 2 };
+	pub static RewardDecayPerBlock: Perbill = Perbill::zero();
+	pub static RequiredStake: Balance = 100;
+	pub static StakeSlashFraction: Perbill = Perbill::from_percent(40);
+	pub static SlashBeneficiary: Option<AccountId> = Some(SLASH_BENEFICIARY);
+	pub static RewardEraLength: u64 = 10;
+	pub static RewardPayoutDelay: u64 = 0;
+	pub const TestLaneOwner: AccountId = LANE_OWNER;
+	pub static LowRewardsPotThreshold: Balance = 0;
+	pub static RewardsPotBalance: Option<Balance> = None;
+	pub static LowRewardsPots: Vec<(RewardsAccountParams, Balance)> = Vec::new();
 }
 
 impl frame_system::Config for TestRuntime {
@@ -92,19 +103,46 @@ impl pallet_bridge_relayers::Config for TestRuntime {
 	type RuntimeEvent = RuntimeEvent;
 	type Reward = Balance;
 	type PaymentProcedure = TestPaymentProcedure;
+	type MaxLanesPerClaim = frame_support::traits::ConstU32<4>;
+	type RewardDecayPerBlock = RewardDecayPerBlock;
+	type Currency = Balances;
+	type RequiredStake = RequiredStake;
+	type StakeSlashFraction = StakeSlashFraction;
+	type SlashBeneficiary = SlashBeneficiary;
+	type RewardEraLength = RewardEraLength;
+	type RewardPayoutDelay = RewardPayoutDelay;
+	type LaneOwnerOrigin =
+		AsEnsureOriginWithArg<frame_system::EnsureSignedBy<TestLaneOwner, AccountId>>;
+	type MaxAllowlistedRelayers = frame_support::traits::ConstU32<2>;
+	type LowRewardsPotThreshold = LowRewardsPotThreshold;
+	type OnLowRewardsPot = TestOnLowRewardsPot;
 	type WeightInfo = ();
 }
 
 /// Message lane that we're using in tests.
 pub const TEST_LANE_ID: LaneId = LaneId([0, 0, 0, 0]);
 
+/// Rewards account that we're using in tests.
+pub const TEST_REWARDS_ACCOUNT_PARAMS: RewardsAccountParams =
+	RewardsAccountParams::new(TEST_LANE_ID, RewardKind::MessageDelivery);
+
 /// Regular relayer that may receive rewards.
 pub const REGULAR_RELAYER: AccountId = 1;
 
 /// Relayer that can't receive rewards.
 pub const FAILING_RELAYER: AccountId = 2;
 
-/// Payment procedure that rejects payments to the `FAILING_RELAYER`.
+/// Account that may be used to transfer relayer rewards to.
+pub const NEW_RELAYER: AccountId = 3;
+
+/// Account that receives slashed relayer stakes.
+pub const SLASH_BENEFICIARY: AccountId = 4;
+
+/// Account that may change relayers allowlist of any lane.
+pub const LANE_OWNER: AccountId = 5;
+
+/// Payment procedure that rejects payments to the `FAILING_RELAYER`. Balance of its rewards pot
+/// is the `RewardsPotBalance`.
 pub struct TestPaymentProcedure;
 
 impl PaymentProcedure<AccountId, Balance> for TestPaymentProcedure {
@@ -112,7 +150,7 @@ impl PaymentProcedure<AccountId, Balance> for TestPaymentProcedure {
 
 	fn pay_reward(
 		relayer: &AccountId,
-		_lane_id: LaneId,
+		_rewards_account_params: RewardsAccountParams,
 		_reward: Balance,
 	) -> Result<(), Self::Error> {
 		match *relayer {
@@ -120,6 +158,21 @@ impl PaymentProcedure<AccountId, Balance> for TestPaymentProcedure {
 			_ => Ok(()),
 		}
 	}
+
+	fn rewards_pot_balance(_rewards_account_params: RewardsAccountParams) -> Option<Balance> {
+		RewardsPotBalance::get()
+	}
+}
+
+/// Low rewards pot handler that remembers all calls in the `LowRewardsPots`.
+pub struct TestOnLowRewardsPot;
+
+impl OnLowRewardsPot<Balance> for TestOnLowRewardsPot {
+	fn on_low_rewards_pot(rewards_account_params: RewardsAccountParams, balance: Balance) {
+		let mut low_rewards_pots = LowRewardsPots::get();
+		low_rewards_pots.push((rewards_account_params, balance));
+		LowRewardsPots::set(low_rewards_pots);
+	}
 }
 
 /// Run pallet test.
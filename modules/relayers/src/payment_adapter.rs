@@ -19,7 +19,8 @@
 use crate::{Config, Pallet};
 
 use bp_messages::source_chain::{DeliveryConfirmationPayments, RelayersRewards};
-use frame_support::{sp_runtime::SaturatedConversion, traits::Get};
+use bp_relayers::{RewardKind, RewardsAccountParams};
+use frame_support::{sp_runtime::SaturatedConversion, traits::Get, weights::Weight};
 use sp_arithmetic::traits::{Saturating, UniqueSaturatedFrom, Zero};
 use sp_std::{collections::vec_deque::VecDeque, marker::PhantomData, ops::RangeInclusive};
 
@@ -38,6 +39,11 @@ where
 {
 	type Error = &'static str;
 
+	fn pay_reward_weight(_messages: bp_messages::MessageNonce) -> Weight {
+		// registering rewards is covered by the messages pallet benchmarks
+		Weight::zero()
+	}
+
 	fn pay_reward(
 		lane_id: bp_messages::LaneId,
 		messages_relayers: VecDeque<bp_messages::UnrewardedRelayer<T::AccountId>>,
@@ -58,6 +64,9 @@ where
 }
 
 // Update rewards to given relayers, optionally rewarding confirmation relayer.
+//
+// Delivery rewards are registered in the message delivery rewards account of the lane and the
+// confirmation reward is registered in the confirmation delivery rewards account of the lane.
 fn register_relayers_rewards<T: Config>(
 	confirmation_relayer: &T::AccountId,
 	relayers_rewards: RelayersRewards<T::AccountId>,
@@ -65,7 +74,11 @@ fn register_relayers_rewards<T: Config>(
 	delivery_fee: T::Reward,
 	confirmation_fee: T::Reward,
 ) {
-	// reward every relayer except `confirmation_relayer`
+	let delivery_rewards_account = RewardsAccountParams::new(lane_id, RewardKind::MessageDelivery);
+	let confirmation_rewards_account =
+		RewardsAccountParams::new(lane_id, RewardKind::DeliveryConfirmation);
+
+	// reward every relayer for delivering messages
 	let mut confirmation_relayer_reward = T::Reward::zero();
 	for (relayer, messages) in relayers_rewards {
 		// sane runtime configurations guarantee that the number of messages will be below
@@ -85,18 +98,19 @@ fn register_relayers_rewards<T: Config>(
 			relayer_reward = relayer_reward.saturating_sub(confirmation_reward);
 			confirmation_relayer_reward =
 				confirmation_relayer_reward.saturating_add(confirmation_reward);
-			Pallet::<T>::register_relayer_reward(lane_id, &relayer, relayer_reward);
-		} else {
-			// If delivery confirmation is submitted by this relayer, let's add confirmation fee
-			// from other relayers to this relayer reward.
-			confirmation_relayer_reward =
-				confirmation_relayer_reward.saturating_add(relayer_reward);
 		}
+
+		// failures are reported by the pallet, there's nothing we can do here
+		let _ = Pallet::<T>::register_relayer_reward(
+			delivery_rewards_account,
+			&relayer,
+			relayer_reward,
+		);
 	}
 
 	// finally - pay reward to confirmation relayer
-	Pallet::<T>::register_relayer_reward(
-		lane_id,
+	let _ = Pallet::<T>::register_relayer_reward(
+		confirmation_rewards_account,
 		confirmation_relayer,
 		confirmation_relayer_reward,
 	);
@@ -111,6 +125,11 @@ mod tests {
 	const RELAYER_2: AccountId = 2;
 	const RELAYER_3: AccountId = 3;
 
+	const DELIVERY_REWARDS_ACCOUNT: RewardsAccountParams =
+		RewardsAccountParams::new(TEST_LANE_ID, RewardKind::MessageDelivery);
+	const CONFIRMATION_REWARDS_ACCOUNT: RewardsAccountParams =
+		RewardsAccountParams::new(TEST_LANE_ID, RewardKind::DeliveryConfirmation);
+
 	fn relayers_rewards() -> RelayersRewards<AccountId> {
 		vec![(RELAYER_1, 2), (RELAYER_2, 3)].into_iter().collect()
 	}
@@ -126,8 +145,18 @@ mod tests {
 				10,
 			);
 
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_1, TEST_LANE_ID), Some(80));
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_2, TEST_LANE_ID), Some(170));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_1, DELIVERY_REWARDS_ACCOUNT),
+				Some(80)
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_2, DELIVERY_REWARDS_ACCOUNT),
+				Some(150)
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_2, CONFIRMATION_REWARDS_ACCOUNT),
+				Some(20)
+			);
 		});
 	}
 
@@ -142,9 +171,22 @@ mod tests {
 				10,
 			);
 
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_1, TEST_LANE_ID), Some(80));
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_2, TEST_LANE_ID), Some(120));
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_3, TEST_LANE_ID), Some(50));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_1, DELIVERY_REWARDS_ACCOUNT),
+				Some(80)
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_2, DELIVERY_REWARDS_ACCOUNT),
+				Some(120)
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_3, DELIVERY_REWARDS_ACCOUNT),
+				None
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_3, CONFIRMATION_REWARDS_ACCOUNT),
+				Some(50)
+			);
 		});
 	}
 
@@ -159,9 +201,18 @@ mod tests {
 				1000,
 			);
 
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_1, TEST_LANE_ID), None);
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_2, TEST_LANE_ID), None);
-			assert_eq!(RelayerRewards::<TestRuntime>::get(RELAYER_3, TEST_LANE_ID), Some(250));
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_1, DELIVERY_REWARDS_ACCOUNT),
+				None
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_2, DELIVERY_REWARDS_ACCOUNT),
+				None
+			);
+			assert_eq!(
+				RelayerRewards::<TestRuntime>::get(RELAYER_3, CONFIRMATION_REWARDS_ACCOUNT),
+				Some(250)
+			);
 		});
 	}
 }
@@ -22,7 +22,8 @@ mod millau_hash;
 
 use bp_beefy::ChainWithBeefy;
 use bp_messages::{
-	InboundMessageDetails, LaneId, MessageNonce, MessagePayload, OutboundMessageDetails,
+	InboundLaneDiagnostics, InboundMessageDeliveryProofKeys, InboundMessageDetails, LaneId,
+	MessageNonce, MessagePayload, OutboundLaneDiagnostics, OutboundMessageDetails,
 };
 use bp_runtime::{decl_bridge_runtime_apis, Chain};
 use frame_support::{
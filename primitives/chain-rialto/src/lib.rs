@@ -19,7 +19,8 @@
 #![allow(clippy::too_many_arguments)]
 
 use bp_messages::{
-	InboundMessageDetails, LaneId, MessageNonce, MessagePayload, OutboundMessageDetails,
+	InboundLaneDiagnostics, InboundMessageDeliveryProofKeys, InboundMessageDetails, LaneId,
+	MessageNonce, MessagePayload, OutboundLaneDiagnostics, OutboundMessageDetails,
 };
 use bp_runtime::{decl_bridge_runtime_apis, Chain};
 use frame_support::{
@@ -87,6 +87,96 @@ pub fn verify_justification<Header: HeaderT>(
 	authorities_set: &VoterSet<AuthorityId>,
 	justification: &GrandpaJustification<Header>,
 ) -> Result<(), Error>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	verify_justification_with_mode(
+		finalized_target,
+		authorities_set_id,
+		authorities_set,
+		justification,
+		false,
+	)
+	.map(drop)
+}
+
+/// Verify that justification, that is generated by given authority set, finalizes given header
+/// and return the number of precommits with verified signatures.
+///
+/// Precommits from unknown authorities and duplicate precommits are ignored without signature
+/// verification, so they are not counted.
+pub fn verify_justification_and_count_precommits<Header: HeaderT>(
+	finalized_target: (Header::Hash, Header::Number),
+	authorities_set_id: SetId,
+	authorities_set: &VoterSet<AuthorityId>,
+	justification: &GrandpaJustification<Header>,
+) -> Result<usize, Error>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	verify_justification_with_mode(
+		finalized_target,
+		authorities_set_id,
+		authorities_set,
+		justification,
+		false,
+	)
+	.map(|redundant| {
+		justification.commit.precommits.len().saturating_sub(redundant.precommits.len())
+	})
+}
+
+/// Verify that justification, that is generated by given authority set, finalizes given header
+/// and return its optimized ("slim") version.
+///
+/// The optimized justification has no votes from unknown authorities, no duplicate votes and no
+/// votes that are not required to reach the threshold. It also has no ancestry headers that are
+/// not required to verify the remaining votes. Relayers may use it to reduce size and weight of
+/// the header import transaction.
+pub fn verify_and_optimize_justification<Header: HeaderT>(
+	finalized_target: (Header::Hash, Header::Number),
+	authorities_set_id: SetId,
+	authorities_set: &VoterSet<AuthorityId>,
+	mut justification: GrandpaJustification<Header>,
+) -> Result<GrandpaJustification<Header>, Error>
+where
+	Header::Number: finality_grandpa::BlockNumberOps,
+{
+	let redundant = verify_justification_with_mode(
+		finalized_target,
+		authorities_set_id,
+		authorities_set,
+		&justification,
+		true,
+	)?;
+
+	for precommit_index in redundant.precommits.into_iter().rev() {
+		justification.commit.precommits.remove(precommit_index);
+	}
+	justification
+		.votes_ancestries
+		.retain(|header| !redundant.votes_ancestries.contains(&header.hash()));
+
+	Ok(justification)
+}
+
+/// Justification items that are not required to verify the justification.
+struct RedundantItems<Hash> {
+	/// Indices of redundant precommits, in ascending order.
+	precommits: Vec<usize>,
+	/// Hashes of redundant ancestry headers.
+	votes_ancestries: BTreeSet<Hash>,
+}
+
+/// Verify justification. If `is_optimizing` is true, redundant justification items are collected
+/// instead of being ignored (or rejected, in case of extra ancestry headers).
+fn verify_justification_with_mode<Header: HeaderT>(
+	finalized_target: (Header::Hash, Header::Number),
+	authorities_set_id: SetId,
+	authorities_set: &VoterSet<AuthorityId>,
+	justification: &GrandpaJustification<Header>,
+	is_optimizing: bool,
+) -> Result<RedundantItems<Header::Hash>, Error>
 where
 	Header::Number: finality_grandpa::BlockNumberOps,
 {
@@ -95,17 +185,20 @@ where
 		return Err(Error::InvalidJustificationTarget)
 	}
 
+	let threshold = authorities_set.threshold().0.into();
+	let mut redundant_precommits = Vec::new();
 	let mut chain = AncestryChain::new(&justification.votes_ancestries);
 	let mut signature_buffer = Vec::new();
 	let mut votes = BTreeSet::new();
 	let mut cumulative_weight = 0u64;
-	for signed in &justification.commit.precommits {
+	for (precommit_index, signed) in justification.commit.precommits.iter().enumerate() {
 		// authority must be in the set
 		let authority_info = match authorities_set.get(&signed.id) {
 			Some(authority_info) => authority_info,
 			None => {
 				// just ignore precommit from unknown authority as
 				// `finality_grandpa::import_precommit` does
+				redundant_precommits.push(precommit_index);
 				continue
 			},
 		};
@@ -116,6 +209,13 @@ where
 		// `finality-grandpa` crate (mostly related to reporting equivocations). But the only thing
 		// that we care about is that only first vote from the authority is accepted
 		if !votes.insert(signed.id.clone()) {
+			redundant_precommits.push(precommit_index);
+			continue
+		}
+
+		// when optimizing, we don't need votes that are submitted after the threshold is reached
+		if is_optimizing && cumulative_weight >= threshold {
+			redundant_precommits.push(precommit_index);
 			continue
 		}
 
@@ -156,15 +256,14 @@ where
 	}
 
 	// check that there are no extra headers in the justification
-	if !chain.unvisited.is_empty() {
+	if !is_optimizing && !chain.unvisited.is_empty() {
 		return Err(Error::ExtraHeadersInVotesAncestries)
 	}
 
 	// check that the cumulative weight of validators voted for the justification target (or one
 	// of its descendents) is larger than required threshold.
-	let threshold = authorities_set.threshold().0.into();
 	if cumulative_weight >= threshold {
-		Ok(())
+		Ok(RedundantItems { precommits: redundant_precommits, votes_ancestries: chain.unvisited })
 	} else {
 		Err(Error::TooLowCumulativeWeight)
 	}
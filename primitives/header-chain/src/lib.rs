@@ -19,7 +19,9 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use bp_runtime::{BasicOperatingMode, Chain, HashOf, HasherOf, HeaderOf, StorageProofChecker};
+use bp_runtime::{
+	BasicOperatingMode, BlockNumberOf, Chain, HashOf, HasherOf, HeaderOf, StorageProofChecker,
+};
 use codec::{Codec, Decode, Encode, EncodeLike, MaxEncodedLen};
 use core::{clone::Clone, cmp::Eq, default::Default, fmt::Debug};
 use frame_support::PalletError;
@@ -80,6 +82,15 @@ impl<H: HeaderT> StoredHeaderDataBuilder<H::Number, H::Hash> for H {
 pub trait HeaderChain<C: Chain> {
 	/// Returns state (storage) root of given finalized header.
 	fn finalized_header_state_root(header_hash: HashOf<C>) -> Option<HashOf<C>>;
+	/// Returns number of given finalized header.
+	fn finalized_header_number(header_hash: HashOf<C>) -> Option<BlockNumberOf<C>>;
+	/// Returns true if given header is the best finalized header or its ancestor.
+	///
+	/// By default, all finalized headers that are known to the header chain are considered
+	/// ancestors of the best finalized header.
+	fn is_finalized_ancestor(header_hash: HashOf<C>) -> bool {
+		Self::finalized_header_state_root(header_hash).is_some()
+	}
 	/// Parse storage proof using finalized header.
 	fn parse_finalized_storage_proof<R>(
 		header_hash: HashOf<C>,
@@ -95,6 +106,25 @@ pub trait HeaderChain<C: Chain> {
 	}
 }
 
+/// Substrate header chain that allows pinning finalized headers, so that they're not pruned.
+///
+/// It may be used to keep headers that are referenced by not-yet-verified proofs (e.g. by
+/// messages proofs in the transaction pool) until proofs are verified.
+pub trait HeaderChainPinning<C: Chain> {
+	/// Pin given finalized header. Returns `false` if the header is unknown.
+	fn pin_finalized_header(header_hash: HashOf<C>) -> bool;
+	/// Unpin finalized header that has been pinned with `pin_finalized_header`.
+	fn unpin_finalized_header(header_hash: HashOf<C>);
+}
+
+impl<C: Chain> HeaderChainPinning<C> for () {
+	fn pin_finalized_header(_header_hash: HashOf<C>) -> bool {
+		false
+	}
+
+	fn unpin_finalized_header(_header_hash: HashOf<C>) {}
+}
+
 /// A type that can be used as a parameter in a dispatchable function.
 ///
 /// When using `decl_module` all arguments for call functions must implement this trait.
@@ -16,8 +16,13 @@
 
 //! Tests for Grandpa Justification code.
 
-use bp_header_chain::justification::{verify_justification, Error};
+use bp_header_chain::justification::{
+	verify_and_optimize_justification, verify_justification,
+	verify_justification_and_count_precommits, Error,
+};
 use bp_test_utils::*;
+use finality_grandpa::voter_set::VoterSet;
+use sp_finality_grandpa::AuthorityId;
 
 type TestHeader = sp_runtime::testing::Header;
 
@@ -190,3 +195,167 @@ fn justification_is_invalid_if_we_dont_meet_threshold() {
 		Err(Error::TooLowCumulativeWeight),
 	);
 }
+
+#[test]
+fn duplicate_precommits_are_not_counted_as_verified() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	let num_precommits = justification.commit.precommits.len();
+	justification.commit.precommits.push(justification.commit.precommits[0].clone());
+
+	assert_eq!(
+		verify_justification_and_count_precommits::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+		),
+		Ok(num_precommits),
+	);
+}
+
+#[test]
+fn optimizer_does_nothing_with_minimal_justification() {
+	let justification = make_default_justification::<TestHeader>(&test_header(1));
+
+	let num_precommits_before = justification.commit.precommits.len();
+	let justification = verify_and_optimize_justification::<TestHeader>(
+		header_id::<TestHeader>(1),
+		TEST_GRANDPA_SET_ID,
+		&voter_set(),
+		justification,
+	)
+	.unwrap();
+	let num_precommits_after = justification.commit.precommits.len();
+
+	assert_eq!(num_precommits_before, num_precommits_after);
+}
+
+#[test]
+fn unknown_authority_votes_are_removed_by_optimizer() {
+	let params = JustificationGeneratorParams {
+		header: test_header(1),
+		round: TEST_GRANDPA_ROUND,
+		set_id: TEST_GRANDPA_SET_ID,
+		authorities: vec![(ALICE, 1), (BOB, 1), (CHARLIE, 1), (DAVE, 1)],
+		ancestors: 4,
+		forks: 4,
+	};
+	let justification = make_justification_for_header::<TestHeader>(params);
+
+	let justification = verify_and_optimize_justification::<TestHeader>(
+		header_id::<TestHeader>(1),
+		TEST_GRANDPA_SET_ID,
+		&voter_set(),
+		justification,
+	)
+	.unwrap();
+
+	// vote of DAVE and the ancestry header of its fork are removed
+	assert_eq!(justification.commit.precommits.len(), 3);
+	assert_eq!(justification.votes_ancestries.len(), 3);
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+		),
+		Ok(()),
+	);
+}
+
+#[test]
+fn duplicate_authority_votes_are_removed_by_optimizer() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	justification.commit.precommits.push(justification.commit.precommits[0].clone());
+
+	let num_precommits_before = justification.commit.precommits.len();
+	let justification = verify_and_optimize_justification::<TestHeader>(
+		header_id::<TestHeader>(1),
+		TEST_GRANDPA_SET_ID,
+		&voter_set(),
+		justification,
+	)
+	.unwrap();
+	let num_precommits_after = justification.commit.precommits.len();
+
+	assert_eq!(num_precommits_before - 1, num_precommits_after);
+}
+
+#[test]
+fn redundant_authority_votes_are_removed_by_optimizer() {
+	let authorities = vec![(ALICE, 1), (BOB, 1), (CHARLIE, 1), (DAVE, 1)];
+	let voter_set = VoterSet::new(
+		authorities
+			.iter()
+			.map(|(id, weight)| (AuthorityId::from(*id), *weight))
+			.collect::<Vec<_>>(),
+	)
+	.unwrap();
+	let params = JustificationGeneratorParams {
+		header: test_header(1),
+		round: TEST_GRANDPA_ROUND,
+		set_id: TEST_GRANDPA_SET_ID,
+		authorities,
+		ancestors: 4,
+		forks: 4,
+	};
+	let justification = make_justification_for_header::<TestHeader>(params);
+
+	let justification = verify_and_optimize_justification::<TestHeader>(
+		header_id::<TestHeader>(1),
+		TEST_GRANDPA_SET_ID,
+		&voter_set,
+		justification,
+	)
+	.unwrap();
+
+	// threshold of 4 authorities is 3, so the last vote and its ancestry are not required
+	assert_eq!(justification.commit.precommits.len(), 3);
+	assert_eq!(justification.votes_ancestries.len(), 3);
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set,
+			&justification,
+		),
+		Ok(()),
+	);
+}
+
+#[test]
+fn redundant_votes_ancestries_are_removed_by_optimizer() {
+	let mut justification = make_default_justification::<TestHeader>(&test_header(1));
+	justification.votes_ancestries.push(test_header(10));
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+		),
+		Err(Error::ExtraHeadersInVotesAncestries),
+	);
+
+	let num_ancestries_before = justification.votes_ancestries.len();
+	let justification = verify_and_optimize_justification::<TestHeader>(
+		header_id::<TestHeader>(1),
+		TEST_GRANDPA_SET_ID,
+		&voter_set(),
+		justification,
+	)
+	.unwrap();
+	let num_ancestries_after = justification.votes_ancestries.len();
+
+	assert_eq!(num_ancestries_before - 1, num_ancestries_after);
+	assert_eq!(
+		verify_justification::<TestHeader>(
+			header_id::<TestHeader>(1),
+			TEST_GRANDPA_SET_ID,
+			&voter_set(),
+			&justification,
+		),
+		Ok(()),
+	);
+}
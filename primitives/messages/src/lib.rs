@@ -67,6 +67,18 @@ impl OperatingMode for MessagesOperatingMode {
 	}
 }
 
+/// State of the outbound lane.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum LaneState {
+	/// The lane is accepting new outbound messages.
+	Opened,
+	/// The lane is not accepting new outbound messages. Already queued messages are still
+	/// delivered to the bridged chain. Once they are delivered and pruned, the lane is closed.
+	Closing,
+	/// The lane is not accepting new outbound messages and has no queued messages.
+	Closed,
+}
+
 /// Lane id which implements `TypeId`.
 #[derive(
 	Clone, Copy, Decode, Default, Encode, Eq, Ord, PartialOrd, PartialEq, TypeInfo, MaxEncodedLen,
@@ -219,6 +231,88 @@ pub struct InboundMessageDetails {
 	pub dispatch_weight: Weight,
 }
 
+/// Storage keys that need to be read from the runtime storage to prove delivery and dispatch
+/// result of the inbound message, returned by runtime APIs.
+///
+/// The storage proof of these keys may be requested from the node using the
+/// `state_getReadProof` RPC method.
+#[derive(Clone, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
+pub struct InboundMessageDeliveryProofKeys {
+	/// Storage key of the message dispatch result. The value is only stored after the message
+	/// has been delivered, so its proof is also the proof of message delivery.
+	pub dispatch_result_key: sp_core::storage::StorageKey,
+}
+
+/// Dispatch result of the outbound message, proved by the messages delivery proof.
+///
+/// The source chain doesn't know the type of dispatch-level result, used by the message
+/// dispatcher at the bridged chain. So it is stored in the encoded form and may be decoded by
+/// the application that has sent the message.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+pub struct DeliveredMessageDispatchResult {
+	/// Nonce of the message.
+	pub nonce: MessageNonce,
+	/// Dispatch weight of the message that has not been spent by the dispatcher at the bridged
+	/// chain.
+	pub unspent_weight: Weight,
+	/// Encoded dispatch-level result of the message dispatch at the bridged chain.
+	pub encoded_dispatch_level_result: Vec<u8>,
+}
+
+impl DeliveredMessageDispatchResult {
+	/// Decode dispatch-level result of the message dispatch.
+	///
+	/// Returns `None` if the result can't be decoded as `DispatchLevelResult`.
+	pub fn dispatch_level_result<DispatchLevelResult: Decode>(
+		&self,
+	) -> Option<DispatchLevelResult> {
+		DispatchLevelResult::decode(&mut &self.encoded_dispatch_level_result[..]).ok()
+	}
+}
+
+/// Outbound lane details that may be used to find out why the lane is stuck, returned by runtime
+/// APIs.
+#[derive(Clone, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
+pub struct OutboundLaneDiagnostics {
+	/// Outbound lane data.
+	pub lane_data: OutboundLaneData,
+	/// Outbound lane state.
+	pub lane_state: LaneState,
+	/// Lane operating mode.
+	pub operating_mode: MessagesOperatingMode,
+}
+
+impl OutboundLaneDiagnostics {
+	/// Inclusive range of nonces of messages that have been sent, but their delivery is not yet
+	/// confirmed. The range is empty if there are no such messages.
+	pub fn unconfirmed_nonces(&self) -> RangeInclusive<MessageNonce> {
+		self.lane_data.latest_received_nonce.saturating_add(1)..=
+			self.lane_data.latest_generated_nonce
+	}
+}
+
+/// Inbound lane details that may be used to find out why the lane is stuck, returned by runtime
+/// APIs.
+#[derive(Clone, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
+pub struct InboundLaneDiagnostics<RelayerId> {
+	/// Inbound lane data, including all unrewarded relayer entries.
+	pub lane_data: InboundLaneData<RelayerId>,
+	/// Bitmap of messages that have been delivered out of order to the unordered lane. Bit `i`
+	/// is set if message `lane_data.last_delivered_nonce() + 1 + i` has been delivered.
+	pub delivered_ahead: u128,
+	/// Lane operating mode.
+	pub operating_mode: MessagesOperatingMode,
+}
+
+impl<RelayerId> InboundLaneDiagnostics<RelayerId> {
+	/// Inclusive range of nonces of messages that have been delivered, but their delivery is
+	/// not yet confirmed at the bridged chain. The range is empty if there are no such messages.
+	pub fn unconfirmed_nonces(&self) -> RangeInclusive<MessageNonce> {
+		self.lane_data.last_confirmed_nonce.saturating_add(1)..=
+			self.lane_data.last_delivered_nonce()
+	}
+}
+
 /// Unrewarded relayer entry stored in the inbound lane data.
 ///
 /// This struct represents a continuous range of messages that have been delivered by the same
@@ -267,6 +361,9 @@ pub enum ReceivalResult<DispatchLevelResult> {
 	///
 	/// The message dispatch result is also returned.
 	Dispatched(MessageDispatchResult<DispatchLevelResult>),
+	/// Message has been received, but it is too heavy to be dispatched immediately. Its dispatch
+	/// has been deferred.
+	Parked,
 	/// Message has invalid nonce and lane has rejected to accept this message.
 	InvalidNonce,
 	/// There are too many unrewarded relayer entries at the lane.
@@ -16,15 +16,19 @@
 
 //! Primitives of messages module, that are used on the source chain.
 
-use crate::{InboundLaneData, LaneId, MessageNonce, OutboundLaneData};
+use crate::{
+	DeliveredMessageDispatchResult, DeliveredMessages, InboundLaneData, LaneId, MessageNonce,
+	MessagePayload, OutboundLaneData,
+};
 
 use crate::UnrewardedRelayer;
 use bp_runtime::Size;
-use frame_support::{weights::Weight, Parameter, RuntimeDebug};
+use frame_support::{sp_runtime::FixedU128, weights::Weight, Parameter, RuntimeDebug};
 use sp_std::{
 	collections::{btree_map::BTreeMap, vec_deque::VecDeque},
 	fmt::Debug,
 	ops::RangeInclusive,
+	vec::Vec,
 };
 
 /// Number of messages, delivered by relayers.
@@ -60,10 +64,14 @@ pub trait TargetHeaderChain<Payload, AccountId> {
 	/// never be delivered.
 	fn verify_message(payload: &Payload) -> Result<(), Self::Error>;
 
-	/// Verify messages delivery proof and return lane && nonce of the latest received message.
+	/// Verify messages delivery proof and return lane, its inbound state and dispatch results
+	/// of messages that are included in the proof.
 	fn verify_messages_delivery_proof(
 		proof: Self::MessagesDeliveryProof,
-	) -> Result<(LaneId, InboundLaneData<AccountId>), Self::Error>;
+	) -> Result<
+		(LaneId, InboundLaneData<AccountId>, Vec<DeliveredMessageDispatchResult>),
+		Self::Error,
+	>;
 }
 
 /// Lane message verifier.
@@ -88,12 +96,58 @@ pub trait LaneMessageVerifier<SenderOrigin, Payload> {
 	) -> Result<(), Self::Error>;
 }
 
+/// Computes and charges the fee for sending message over the lane.
+///
+/// The fee is charged when the message is accepted by the outbound lane. It is supposed to
+/// scale with the size of message payload, so that sending maximal-size messages costs more than
+/// sending small messages. The `fee_factor` reflects current congestion of the lane - it is
+/// never less than one and grows when the lane is used above its target capacity (similar to
+/// EIP-1559 base fee). So the fee must be multiplied by that factor.
+///
+/// The `outbound_data` is the state of the outbound lane before the message is sent. It may be
+/// used to charge more (or to reject the message) when there are too many messages at the lane,
+/// which delivery is not yet confirmed.
+pub trait MessageDeliveryFeeCalculator<SenderOrigin> {
+	/// Error type.
+	type Error: Debug + Into<&'static str>;
+
+	/// Compute delivery fee of the message with given encoded payload size and charge it from the
+	/// message submitter.
+	fn pay_delivery_fee(
+		submitter: &SenderOrigin,
+		lane: &LaneId,
+		outbound_data: &OutboundLaneData,
+		encoded_payload_size: u32,
+		fee_factor: FixedU128,
+	) -> Result<(), Self::Error>;
+}
+
+impl<SenderOrigin> MessageDeliveryFeeCalculator<SenderOrigin> for () {
+	type Error = &'static str;
+
+	fn pay_delivery_fee(
+		_submitter: &SenderOrigin,
+		_lane: &LaneId,
+		_outbound_data: &OutboundLaneData,
+		_encoded_payload_size: u32,
+		_fee_factor: FixedU128,
+	) -> Result<(), Self::Error> {
+		// this implementation is not charging senders at all
+		Ok(())
+	}
+}
+
 /// Manages payments that are happening at the source chain during delivery confirmation
 /// transaction.
 pub trait DeliveryConfirmationPayments<AccountId> {
 	/// Error type.
 	type Error: Debug + Into<&'static str>;
 
+	/// Weight of the `pay_reward` call, given the number of confirmed messages.
+	///
+	/// It must only include the cost that is not covered by the messages pallet benchmarks.
+	fn pay_reward_weight(messages: MessageNonce) -> Weight;
+
 	/// Pay rewards for delivering messages to the given relayers.
 	///
 	/// The implementation may also choose to pay reward to the `confirmation_relayer`, which is
@@ -109,6 +163,10 @@ pub trait DeliveryConfirmationPayments<AccountId> {
 impl<AccountId> DeliveryConfirmationPayments<AccountId> for () {
 	type Error = &'static str;
 
+	fn pay_reward_weight(_messages: MessageNonce) -> Weight {
+		Weight::zero()
+	}
+
 	fn pay_reward(
 		_lane_id: LaneId,
 		_messages_relayers: VecDeque<UnrewardedRelayer<AccountId>>,
@@ -119,6 +177,91 @@ impl<AccountId> DeliveryConfirmationPayments<AccountId> for () {
 	}
 }
 
+/// Handler for outbound messages that have not been delivered in time.
+///
+/// Please keep in mind that the expired message may still be delivered to the bridged chain if
+/// the delivery transaction has been submitted before the message has expired. So implementation
+/// must not assume that the message payload will never be dispatched.
+pub trait OnMessageExpired {
+	/// Weight of the single `on_message_expired` call.
+	fn on_message_expired_weight() -> Weight;
+
+	/// Called when the outbound message has expired. Its payload is removed from the runtime
+	/// storage after this call.
+	fn on_message_expired(lane: LaneId, nonce: MessageNonce, payload: &MessagePayload);
+}
+
+impl OnMessageExpired for () {
+	fn on_message_expired_weight() -> Weight {
+		Weight::zero()
+	}
+
+	fn on_message_expired(_lane: LaneId, _nonce: MessageNonce, _payload: &MessagePayload) {}
+}
+
+/// Handler for outbound messages that have been cancelled by their senders.
+pub trait OnMessageCancelled<AccountId> {
+	/// Weight of the single `on_message_cancelled` call.
+	fn on_message_cancelled_weight() -> Weight;
+
+	/// Called when delivery of the outbound message, that has been cancelled by its sender, is
+	/// confirmed by the proof that includes the message dispatch result.
+	///
+	/// The message may have been delivered before it has been cancelled, so the implementation
+	/// must only refund the sender if the `dispatch_result` proves that the message has not been
+	/// dispatched at the bridged chain. Senders of cancelled messages, which delivery is
+	/// confirmed without dispatch result, are never refunded.
+	fn on_message_cancelled(
+		lane: LaneId,
+		sender: &AccountId,
+		dispatch_result: &DeliveredMessageDispatchResult,
+	);
+}
+
+impl<AccountId> OnMessageCancelled<AccountId> for () {
+	fn on_message_cancelled_weight() -> Weight {
+		Weight::zero()
+	}
+
+	fn on_message_cancelled(
+		_lane: LaneId,
+		_sender: &AccountId,
+		_dispatch_result: &DeliveredMessageDispatchResult,
+	) {
+	}
+}
+
+/// Handler for confirmed deliveries of outbound messages.
+pub trait OnDeliveryConfirmed {
+	/// Weight of the `on_delivery_confirmed` call, given the number of confirmed messages.
+	fn on_delivery_confirmed_weight(messages: MessageNonce) -> Weight;
+
+	/// Called when delivery of outbound messages has been confirmed by the bridged chain.
+	///
+	/// The `dispatch_results` contains dispatch results of confirmed messages, that have been
+	/// included in the messages delivery proof. The relayer may choose to not include some (or
+	/// all) dispatch results into the proof, so not every confirmed message has its dispatch
+	/// result here.
+	fn on_delivery_confirmed(
+		lane: LaneId,
+		messages: &DeliveredMessages,
+		dispatch_results: &[DeliveredMessageDispatchResult],
+	);
+}
+
+impl OnDeliveryConfirmed for () {
+	fn on_delivery_confirmed_weight(_messages: MessageNonce) -> Weight {
+		Weight::zero()
+	}
+
+	fn on_delivery_confirmed(
+		_lane: LaneId,
+		_messages: &DeliveredMessages,
+		_dispatch_results: &[DeliveredMessageDispatchResult],
+	) {
+	}
+}
+
 /// Send message artifacts.
 #[derive(Eq, RuntimeDebug, PartialEq)]
 pub struct SendMessageArtifacts {
@@ -159,8 +302,9 @@ impl<SenderOrigin, Payload> MessagesBridge<SenderOrigin, Payload> for NoopMessag
 	}
 }
 
-/// Structure that may be used in place of `TargetHeaderChain`, `LaneMessageVerifier` and
-/// `MessageDeliveryAndDispatchPayment` on chains, where outbound messages are forbidden.
+/// Structure that may be used in place of `TargetHeaderChain`, `LaneMessageVerifier`,
+/// `MessageDeliveryFeeCalculator` and `MessageDeliveryAndDispatchPayment` on chains, where
+/// outbound messages are forbidden.
 pub struct ForbidOutboundMessages;
 
 /// Error message that is used in `ForbidOutboundMessages` implementation.
@@ -178,7 +322,10 @@ impl<Payload, AccountId> TargetHeaderChain<Payload, AccountId> for ForbidOutboun
 
 	fn verify_messages_delivery_proof(
 		_proof: Self::MessagesDeliveryProof,
-	) -> Result<(LaneId, InboundLaneData<AccountId>), Self::Error> {
+	) -> Result<
+		(LaneId, InboundLaneData<AccountId>, Vec<DeliveredMessageDispatchResult>),
+		Self::Error,
+	> {
 		Err(ALL_OUTBOUND_MESSAGES_REJECTED)
 	}
 }
@@ -196,9 +343,27 @@ impl<SenderOrigin, Payload> LaneMessageVerifier<SenderOrigin, Payload> for Forbi
 	}
 }
 
+impl<SenderOrigin> MessageDeliveryFeeCalculator<SenderOrigin> for ForbidOutboundMessages {
+	type Error = &'static str;
+
+	fn pay_delivery_fee(
+		_submitter: &SenderOrigin,
+		_lane: &LaneId,
+		_outbound_data: &OutboundLaneData,
+		_encoded_payload_size: u32,
+		_fee_factor: FixedU128,
+	) -> Result<(), Self::Error> {
+		Err(ALL_OUTBOUND_MESSAGES_REJECTED)
+	}
+}
+
 impl<AccountId> DeliveryConfirmationPayments<AccountId> for ForbidOutboundMessages {
 	type Error = &'static str;
 
+	fn pay_reward_weight(_messages: MessageNonce) -> Weight {
+		Weight::zero()
+	}
+
 	fn pay_reward(
 		_lane_id: LaneId,
 		_messages_relayers: VecDeque<UnrewardedRelayer<AccountId>>,
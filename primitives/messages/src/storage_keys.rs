@@ -24,6 +24,8 @@ pub const OUTBOUND_MESSAGES_MAP_NAME: &str = "OutboundMessages";
 pub const OUTBOUND_LANES_MAP_NAME: &str = "OutboundLanes";
 /// Name of the `InboundLanes` storage map.
 pub const INBOUND_LANES_MAP_NAME: &str = "InboundLanes";
+/// Name of the `InboundMessageDispatchResults` storage map.
+pub const INBOUND_MESSAGE_DISPATCH_RESULTS_MAP_NAME: &str = "InboundMessageDispatchResults";
 
 use crate::{LaneId, MessageKey, MessageNonce};
 
@@ -69,6 +71,19 @@ pub fn inbound_lane_data_key(pallet_prefix: &str, lane: &LaneId) -> StorageKey {
 	)
 }
 
+/// Storage key of the inbound message dispatch result in the runtime storage.
+pub fn inbound_message_dispatch_result_key(
+	pallet_prefix: &str,
+	lane: &LaneId,
+	nonce: MessageNonce,
+) -> StorageKey {
+	bp_runtime::storage_map_final_key::<Blake2_128Concat>(
+		pallet_prefix,
+		INBOUND_MESSAGE_DISPATCH_RESULTS_MAP_NAME,
+		&MessageKey { lane_id: *lane, nonce }.encode(),
+	)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -125,4 +140,18 @@ mod tests {
 			hex::encode(&storage_key),
 		);
 	}
+
+	#[test]
+	fn inbound_message_dispatch_result_key_computed_properly() {
+		// If this test fails, then something has been changed in module storage that is breaking
+		// all previously crafted message delivery proofs.
+		let storage_key =
+			inbound_message_dispatch_result_key("BridgeMessages", &LaneId(*b"test"), 42).0;
+		assert_eq!(
+			storage_key,
+			hex!("dd16c784ebd3390a9bc0357c7511ed01c16768d1c4796245eef59265e77b094d9446af0e09063bd4a7874aef8a997cec746573742a00000000000000").to_vec(),
+			"Unexpected storage key: {}",
+			hex::encode(&storage_key),
+		);
+	}
 }
@@ -19,7 +19,7 @@
 use crate::{LaneId, Message, MessageKey, MessageNonce, MessagePayload, OutboundLaneData};
 
 use bp_runtime::{messages::MessageDispatchResult, Size};
-use codec::{Decode, Encode, Error as CodecError};
+use codec::{Decode, Encode, Error as CodecError, MaxEncodedLen};
 use frame_support::{weights::Weight, Parameter, RuntimeDebug};
 use scale_info::TypeInfo;
 use sp_std::{collections::btree_map::BTreeMap, fmt::Debug, marker::PhantomData, prelude::*};
@@ -90,7 +90,15 @@ pub trait MessageDispatch<AccountId> {
 	type DispatchPayload: Decode;
 
 	/// Fine-grained result of single message dispatch (for better diagnostic purposes)
-	type DispatchLevelResult: Clone + sp_std::fmt::Debug + Eq;
+	///
+	/// The result is stored in the runtime storage, so it must be small.
+	type DispatchLevelResult: Clone
+		+ sp_std::fmt::Debug
+		+ Eq
+		+ Encode
+		+ Decode
+		+ TypeInfo
+		+ MaxEncodedLen;
 
 	/// Estimate dispatch weight.
 	///
@@ -60,6 +60,20 @@ pub struct ParaInfo {
 	pub next_imported_hash_position: u32,
 }
 
+/// Configuration of single parachain, tracked by the parachains pallet.
+#[derive(Clone, Copy, Decode, Encode, Eq, MaxEncodedLen, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct ParachainConfig {
+	/// Maximal size (in bytes) of the SCALE-encoded parachain head data
+	/// (`ParaStoredHeaderData`).
+	///
+	/// It must not exceed the `MaxParaHeadDataSize` of the pallet.
+	pub max_head_size: u32,
+	/// Maximal number of parachain heads to keep in the storage.
+	pub heads_to_keep: u32,
+	/// If `false`, all new heads of the parachain are rejected by the pallet.
+	pub is_active: bool,
+}
+
 /// Returns runtime storage key of given parachain head at the source chain.
 ///
 /// The head is stored by the `paras` pallet in the `Heads` map.
@@ -82,6 +96,21 @@ impl StorageMapKeyProvider for ParasInfoKeyProvider {
 	type Value = ParaInfo;
 }
 
+/// Can be use to access the runtime storage key of the parachain head requests at the target
+/// chain.
+///
+/// The requests are stored by the `pallet-bridge-parachains` pallet in the
+/// `RequestedParachainHeads` map. The value is the number of relay chain block, at (or after)
+/// which the head of the parachain is wanted.
+pub struct RequestedParachainHeadsKeyProvider;
+impl StorageMapKeyProvider for RequestedParachainHeadsKeyProvider {
+	const MAP_NAME: &'static str = "RequestedParachainHeads";
+
+	type Hasher = Blake2_128Concat;
+	type Key = ParaId;
+	type Value = RelayBlockNumber;
+}
+
 /// Can be use to access the runtime storage key of the parachain head at the target chain.
 ///
 /// The head is stored by the `pallet-bridge-parachains` pallet in the `ImportedParaHeads` map.
@@ -117,6 +146,8 @@ impl ParaStoredHeaderData {
 pub trait ParaStoredHeaderDataBuilder {
 	/// Try to build head data from self.
 	fn try_build(para_id: ParaId, para_head: &ParaHead) -> Option<ParaStoredHeaderData>;
+	/// Try to decode parachain head as header and return its parent hash.
+	fn try_extract_parent_hash(para_id: ParaId, para_head: &ParaHead) -> Option<ParaHash>;
 }
 
 /// Helper for using single parachain as `ParaStoredHeaderDataBuilder`.
@@ -133,6 +164,14 @@ impl<C: Parachain> ParaStoredHeaderDataBuilder for SingleParaStoredHeaderDataBui
 		}
 		None
 	}
+
+	fn try_extract_parent_hash(para_id: ParaId, para_head: &ParaHead) -> Option<ParaHash> {
+		if para_id == ParaId(C::PARACHAIN_ID) {
+			let header = HeaderOf::<C>::decode(&mut &para_head.0[..]).ok()?;
+			return ParaHash::decode(&mut &header.parent_hash().encode()[..]).ok()
+		}
+		None
+	}
 }
 
 // Tries to build header data from each tuple member, short-circuiting on first successful one.
@@ -149,6 +188,29 @@ impl ParaStoredHeaderDataBuilder for C {
 
 		None
 	}
+
+	fn try_extract_parent_hash(para_id: ParaId, para_head: &ParaHead) -> Option<ParaHash> {
+		for_tuples!( #(
+			let maybe_parent_hash = SingleParaStoredHeaderDataBuilder::<C>::try_extract_parent_hash(para_id, para_head);
+			if let Some(maybe_parent_hash) = maybe_parent_hash {
+				return Some(maybe_parent_hash);
+			}
+		)* );
+
+		None
+	}
+}
+
+sp_api::decl_runtime_apis! {
+	/// API for querying state of the parachains pallet.
+	///
+	/// This API is implemented by runtimes that are bridging with the relay chain, not by the
+	/// relay chain runtime itself.
+	pub trait BridgedParachainsApi {
+		/// Returns hashes of best known heads of all tracked parachains, along with numbers of
+		/// relay chain blocks where these heads have been read.
+		fn best_parachain_heads() -> Vec<(ParaId, ParaHash, RelayBlockNumber)>;
+	}
 }
 
 /// A minimized version of `pallet-bridge-parachains::Call` that can be used without a runtime.
@@ -19,34 +19,117 @@
 #![warn(missing_docs)]
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use bp_messages::LaneId;
+use bp_messages::{LaneId, MessageNonce};
 use bp_runtime::StorageDoubleMapKeyProvider;
-use frame_support::{Blake2_128Concat, Identity};
+use codec::MaxEncodedLen;
+use frame_support::{
+	traits::{fungible, fungibles, Get},
+	Blake2_128Concat, Identity, RuntimeDebug,
+};
+use scale_info::TypeInfo;
 use sp_runtime::{
 	codec::{Codec, Decode, Encode, EncodeLike},
 	traits::AccountIdConversion,
 };
-use sp_std::{fmt::Debug, marker::PhantomData};
+use sp_std::{fmt::Debug, marker::PhantomData, vec::Vec};
+
+/// Maximal number of entries that may be returned by the `BridgeRelayersApi::relayer_rewards`.
+pub const MAX_RELAYER_REWARDS_PER_QUERY: u32 = 128;
+
+/// Kind of the relayer reward.
+///
+/// Rewards for delivering messages and rewards for delivering confirmations are accounted
+/// separately, so that the economics of the two relay legs may be tuned independently.
+#[derive(Clone, Copy, Decode, Encode, Eq, MaxEncodedLen, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum RewardKind {
+	/// Reward for delivering messages (and linked finality proofs) to the bridged chain.
+	MessageDelivery,
+	/// Reward for delivering message delivery confirmations back to the source chain.
+	DeliveryConfirmation,
+}
+
+/// Parameters of the relayer rewards account.
+///
+/// Relayer rewards are registered and paid separately for every lane and reward kind.
+#[derive(Clone, Copy, Decode, Encode, Eq, MaxEncodedLen, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct RewardsAccountParams {
+	/// Lane that the relayer is serving.
+	pub lane_id: LaneId,
+	/// Kind of the reward.
+	pub kind: RewardKind,
+}
+
+impl RewardsAccountParams {
+	/// Create new rewards account parameters.
+	pub const fn new(lane_id: LaneId, kind: RewardKind) -> Self {
+		Self { lane_id, kind }
+	}
+}
+
+/// Statistics of the relayer, collected by the code that refunds relayers.
+///
+/// Statistics are only used for monitoring relayers (e.g. to build relayer leaderboards or to
+/// weight relayer fee quotes by reliability). The counters are never decreased.
+#[derive(Clone, Decode, Default, Encode, Eq, MaxEncodedLen, PartialEq, RuntimeDebug, TypeInfo)]
+pub struct RelayerStatistics<Reward> {
+	/// Number of new messages, delivered by the relayer.
+	pub delivered_messages: MessageNonce,
+	/// Number of new relay chain and parachain headers, submitted by the relayer.
+	pub submitted_headers: u32,
+	/// Total amount of refunds that have been registered for the relayer.
+	pub total_rewards: Reward,
+	/// Number of bridge transactions that have been submitted by the relayer and have not been
+	/// refunded.
+	pub failed_refunds: u32,
+}
 
 /// Reward payment procedure.
 pub trait PaymentProcedure<Relayer, Reward> {
 	/// Error that may be returned by the procedure.
 	type Error: Debug;
 
-	/// Pay reward to the relayer for serving given message lane.
-	fn pay_reward(relayer: &Relayer, lane_id: LaneId, reward: Reward) -> Result<(), Self::Error>;
+	/// Pay reward to the relayer from given rewards account.
+	fn pay_reward(
+		relayer: &Relayer,
+		rewards_account_params: RewardsAccountParams,
+		reward: Reward,
+	) -> Result<(), Self::Error>;
+
+	/// Returns balance of the account (rewards pot) that pays rewards from given rewards
+	/// account.
+	///
+	/// Returns `None` if rewards are not paid from some account (e.g. if they're minted).
+	fn rewards_pot_balance(_rewards_account_params: RewardsAccountParams) -> Option<Reward> {
+		None
+	}
+}
+
+/// Handler that is called when balance of the rewards pot falls below the threshold.
+///
+/// It may be used to notify governance or to top up the rewards pot (e.g. from the treasury
+/// account), so that relayer payouts don't start failing.
+pub trait OnLowRewardsPot<Reward> {
+	/// Called when balance of the account that pays rewards from given rewards account falls
+	/// below the threshold.
+	fn on_low_rewards_pot(rewards_account_params: RewardsAccountParams, balance: Reward);
+}
+
+impl<Reward> OnLowRewardsPot<Reward> for () {
+	fn on_low_rewards_pot(_: RewardsAccountParams, _: Reward) {}
 }
 
 impl<Relayer, Reward> PaymentProcedure<Relayer, Reward> for () {
 	type Error = &'static str;
 
-	fn pay_reward(_: &Relayer, _: LaneId, _: Reward) -> Result<(), Self::Error> {
+	fn pay_reward(_: &Relayer, _: RewardsAccountParams, _: Reward) -> Result<(), Self::Error> {
 		Ok(())
 	}
 }
 
 /// Reward payment procedure that does `balances::transfer` call from the account, derived from
 /// given lane.
+///
+/// Rewards of all kinds are paid from the same lane account.
 pub struct PayLaneRewardFromAccount<T, Relayer>(PhantomData<(T, Relayer)>);
 
 impl<T, Relayer> PayLaneRewardFromAccount<T, Relayer>
@@ -68,10 +151,125 @@ where
 
 	fn pay_reward(
 		relayer: &Relayer,
-		lane_id: LaneId,
+		rewards_account_params: RewardsAccountParams,
+		reward: T::Balance,
+	) -> Result<(), Self::Error> {
+		let rewards_account = Self::lane_rewards_account(rewards_account_params.lane_id);
+		T::transfer(&rewards_account, relayer, reward, false).map(drop)
+	}
+
+	fn rewards_pot_balance(rewards_account_params: RewardsAccountParams) -> Option<T::Balance> {
+		Some(T::balance(&Self::lane_rewards_account(rewards_account_params.lane_id)))
+	}
+}
+
+/// Reward payment procedure that does `balances::transfer` call from the `Account`.
+///
+/// It may be used to pay rewards for serving all lanes from the single pot account. Rewards
+/// may also be paid from the treasury, if the treasury account (e.g. the
+/// `pallet_treasury::Pallet::account_id()`) is returned by the `Account`.
+pub struct PayRewardFromAccount<T, Relayer, Account>(PhantomData<(T, Relayer, Account)>);
+
+impl<T, Relayer, Account> PaymentProcedure<Relayer, T::Balance>
+	for PayRewardFromAccount<T, Relayer, Account>
+where
+	T: fungible::Transfer<Relayer>,
+	Account: Get<Relayer>,
+{
+	type Error = sp_runtime::DispatchError;
+
+	fn pay_reward(
+		relayer: &Relayer,
+		_rewards_account_params: RewardsAccountParams,
 		reward: T::Balance,
 	) -> Result<(), Self::Error> {
-		T::transfer(&Self::lane_rewards_account(lane_id), relayer, reward, false).map(drop)
+		T::transfer(&Account::get(), relayer, reward, false).map(drop)
+	}
+
+	fn rewards_pot_balance(_rewards_account_params: RewardsAccountParams) -> Option<T::Balance> {
+		Some(T::balance(&Account::get()))
+	}
+}
+
+/// Reward payment procedure that mints reward into the relayer account.
+///
+/// Every reward increases the total issuance, so it should only be used by chains that are
+/// ready to inflate their token to pay relayers.
+pub struct PayRewardByMinting<T, Relayer>(PhantomData<(T, Relayer)>);
+
+impl<T, Relayer> PaymentProcedure<Relayer, T::Balance> for PayRewardByMinting<T, Relayer>
+where
+	T: fungible::Mutate<Relayer>,
+{
+	type Error = sp_runtime::DispatchError;
+
+	fn pay_reward(
+		relayer: &Relayer,
+		_rewards_account_params: RewardsAccountParams,
+		reward: T::Balance,
+	) -> Result<(), Self::Error> {
+		T::mint_into(relayer, reward)
+	}
+}
+
+/// Reward payment procedure that mints reward in the `Asset` of `Assets` (e.g. `pallet_assets`)
+/// into the relayer account.
+///
+/// The reward is expected to be computed in the asset units.
+pub struct PayRewardInAsset<Assets, Asset, Relayer>(PhantomData<(Assets, Asset, Relayer)>);
+
+impl<Assets, Asset, Relayer> PaymentProcedure<Relayer, Assets::Balance>
+	for PayRewardInAsset<Assets, Asset, Relayer>
+where
+	Assets: fungibles::Mutate<Relayer>,
+	Asset: Get<Assets::AssetId>,
+{
+	type Error = sp_runtime::DispatchError;
+
+	fn pay_reward(
+		relayer: &Relayer,
+		_rewards_account_params: RewardsAccountParams,
+		reward: Assets::Balance,
+	) -> Result<(), Self::Error> {
+		Assets::mint_into(Asset::get(), relayer, reward)
+	}
+}
+
+/// Reward payment procedure that pays rewards for serving `Lanes` using the `Selected`
+/// procedure and rewards for serving other lanes using the `Other` procedure.
+///
+/// It may be used to select payment procedure for every lane, e.g.
+/// `PayRewardForLanes<AssetLanes, PayRewardInAsset<..>, PayLaneRewardFromAccount<..>>`. Several
+/// selectors may be nested.
+pub struct PayRewardForLanes<Lanes, Selected, Other>(PhantomData<(Lanes, Selected, Other)>);
+
+impl<Relayer, Reward, Lanes, Selected, Other> PaymentProcedure<Relayer, Reward>
+	for PayRewardForLanes<Lanes, Selected, Other>
+where
+	Lanes: Get<Vec<LaneId>>,
+	Selected: PaymentProcedure<Relayer, Reward>,
+	Other: PaymentProcedure<Relayer, Reward, Error = Selected::Error>,
+{
+	type Error = Selected::Error;
+
+	fn pay_reward(
+		relayer: &Relayer,
+		rewards_account_params: RewardsAccountParams,
+		reward: Reward,
+	) -> Result<(), Self::Error> {
+		if Lanes::get().contains(&rewards_account_params.lane_id) {
+			Selected::pay_reward(relayer, rewards_account_params, reward)
+		} else {
+			Other::pay_reward(relayer, rewards_account_params, reward)
+		}
+	}
+
+	fn rewards_pot_balance(rewards_account_params: RewardsAccountParams) -> Option<Reward> {
+		if Lanes::get().contains(&rewards_account_params.lane_id) {
+			Selected::rewards_pot_balance(rewards_account_params)
+		} else {
+			Other::rewards_pot_balance(rewards_account_params)
+		}
 	}
 }
 
@@ -89,15 +287,58 @@ where
 	type Hasher1 = Blake2_128Concat;
 	type Key1 = AccountId;
 	type Hasher2 = Identity;
-	type Key2 = LaneId;
+	type Key2 = RewardsAccountParams;
 	type Value = Reward;
 }
 
+sp_api::decl_runtime_apis! {
+	/// API for querying state of the relayers pallet.
+	pub trait BridgeRelayersApi<AccountId, Reward> where AccountId: Codec, Reward: Codec {
+		/// Returns number of invalid (e.g. obsolete) bridge transactions that have been submitted
+		/// by the relayer.
+		fn invalid_submissions(relayer: AccountId) -> u32;
+		/// Returns non-zero rewards that may be claimed by the relayer from rewards accounts.
+		///
+		/// At most `MAX_RELAYER_REWARDS_PER_QUERY` rewards accounts are returned.
+		fn relayer_rewards(relayer: AccountId) -> Vec<(RewardsAccountParams, Reward)>;
+		/// Returns statistics of the relayer, collected by the code that refunds relayers.
+		fn relayer_statistics(relayer: AccountId) -> RelayerStatistics<Reward>;
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use frame_support::parameter_types;
 	use sp_runtime::testing::H256;
 
+	parameter_types! {
+		pub SelectedLanes: Vec<LaneId> = vec![LaneId([0, 0, 0, 1])];
+	}
+
+	struct FailingPaymentProcedure;
+
+	impl PaymentProcedure<u64, u64> for FailingPaymentProcedure {
+		type Error = &'static str;
+
+		fn pay_reward(_: &u64, _: RewardsAccountParams, _: u64) -> Result<(), Self::Error> {
+			Err("failed")
+		}
+	}
+
+	#[test]
+	fn pay_reward_for_lanes_selects_payment_procedure() {
+		type SelectedFails = PayRewardForLanes<SelectedLanes, FailingPaymentProcedure, ()>;
+		type OtherFails = PayRewardForLanes<SelectedLanes, (), FailingPaymentProcedure>;
+
+		let lane0 = RewardsAccountParams::new(LaneId([0, 0, 0, 0]), RewardKind::MessageDelivery);
+		let lane1 = RewardsAccountParams::new(LaneId([0, 0, 0, 1]), RewardKind::MessageDelivery);
+		assert_eq!(SelectedFails::pay_reward(&1, lane0, 100), Ok(()));
+		assert_eq!(SelectedFails::pay_reward(&1, lane1, 100), Err("failed"));
+		assert_eq!(OtherFails::pay_reward(&1, lane0, 100), Err("failed"));
+		assert_eq!(OtherFails::pay_reward(&1, lane1, 100), Ok(()));
+	}
+
 	#[test]
 	fn lanes_are_using_different_accounts() {
 		assert_eq!(
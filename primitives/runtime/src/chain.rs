@@ -269,8 +269,12 @@ pub type TransactionEraOf<C> = crate::TransactionEra<BlockNumberOf<C>, HashOf<C>
 /// This includes:
 /// - chain-specific bridge runtime APIs:
 ///     - `<ThisChain>FinalityApi`
+///     - `<ThisChain>HeaderAncestryApi`
+///     - `<ThisChain>BridgedHeaderApi`
 /// - constants that are stringified names of runtime API methods:
 ///     - `BEST_FINALIZED_<THIS_CHAIN>_HEADER_METHOD`
+///     - `IS_FINALIZED_<THIS_CHAIN>_ANCESTOR_METHOD`
+///     - `BEST_FINALIZED_<THIS_CHAIN>_HEADER_WITH_STATE_ROOT_METHOD`
 /// The name of the chain has to be specified in snake case (e.g. `rialto_parachain`).
 #[macro_export]
 macro_rules! decl_bridge_finality_runtime_apis {
@@ -283,6 +287,14 @@ macro_rules! decl_bridge_finality_runtime_apis {
 				pub const [<BEST_FINALIZED_ $chain:upper _HEADER_METHOD>]: &str =
 					stringify!([<$chain:camel FinalityApi_best_finalized>]);
 
+				/// Name of the `<ThisChain>HeaderAncestryApi::is_finalized_ancestor` runtime method.
+				pub const [<IS_FINALIZED_ $chain:upper _ANCESTOR_METHOD>]: &str =
+					stringify!([<$chain:camel HeaderAncestryApi_is_finalized_ancestor>]);
+
+				/// Name of the `<ThisChain>BridgedHeaderApi::best_finalized_with_state_root` runtime method.
+				pub const [<BEST_FINALIZED_ $chain:upper _HEADER_WITH_STATE_ROOT_METHOD>]: &str =
+					stringify!([<$chain:camel BridgedHeaderApi_best_finalized_with_state_root>]);
+
 				sp_api::decl_runtime_apis! {
 					/// API for querying information about the finalized chain headers.
 					///
@@ -292,6 +304,26 @@ macro_rules! decl_bridge_finality_runtime_apis {
 						/// Returns number and hash of the best finalized header known to the bridge module.
 						fn best_finalized() -> Option<bp_runtime::HeaderId<Hash, BlockNumber>>;
 					}
+
+					/// API for checking ancestry of the finalized chain headers.
+					///
+					/// This API is implemented by runtimes that are receiving messages from this chain, not by this
+					/// chain's runtime itself.
+					pub trait [<$chain:camel HeaderAncestryApi>] {
+						/// Returns true if the header with given hash is the best finalized header, known to the
+						/// bridge module, or its ancestor. Only headers within the pruning window are checked.
+						fn is_finalized_ancestor(hash: Hash) -> bool;
+					}
+
+					/// API for querying the best finalized chain header along with its state root.
+					///
+					/// This API is implemented by runtimes that are receiving messages from this chain, not by this
+					/// chain's runtime itself.
+					pub trait [<$chain:camel BridgedHeaderApi>] {
+						/// Returns number and hash of the best finalized header known to the bridge module, along
+						/// with the state root of this header.
+						fn best_finalized_with_state_root() -> Option<(bp_runtime::HeaderId<Hash, BlockNumber>, Hash)>;
+					}
 				}
 			}
 
@@ -307,6 +339,7 @@ macro_rules! decl_bridge_finality_runtime_apis {
 ///     - `From<ThisChain>InboundLaneApi`
 /// - constants that are stringified names of runtime API methods:
 ///     - `FROM_<THIS_CHAIN>_MESSAGE_DETAILS_METHOD`,
+///     - `FROM_<THIS_CHAIN>_MESSAGE_DELIVERY_PROOF_KEYS_METHOD`,
 /// The name of the chain has to be specified in snake case (e.g. `rialto_parachain`).
 #[macro_export]
 macro_rules! decl_bridge_messages_runtime_apis {
@@ -319,10 +352,23 @@ macro_rules! decl_bridge_messages_runtime_apis {
 				pub const [<TO_ $chain:upper _MESSAGE_DETAILS_METHOD>]: &str =
 					stringify!([<To $chain:camel OutboundLaneApi_message_details>]);
 
+				/// Name of the `To<ThisChain>OutboundLaneApi::lane_diagnostics` runtime method.
+				pub const [<TO_ $chain:upper _LANE_DIAGNOSTICS_METHOD>]: &str =
+					stringify!([<To $chain:camel OutboundLaneApi_lane_diagnostics>]);
+
 				/// Name of the `From<ThisChain>InboundLaneApi::message_details` runtime method.
 				pub const [<FROM_ $chain:upper _MESSAGE_DETAILS_METHOD>]: &str =
 					stringify!([<From $chain:camel InboundLaneApi_message_details>]);
 
+				/// Name of the `From<ThisChain>InboundLaneApi::message_delivery_proof_keys` runtime
+				/// method.
+				pub const [<FROM_ $chain:upper _MESSAGE_DELIVERY_PROOF_KEYS_METHOD>]: &str =
+					stringify!([<From $chain:camel InboundLaneApi_message_delivery_proof_keys>]);
+
+				/// Name of the `From<ThisChain>InboundLaneApi::lane_diagnostics` runtime method.
+				pub const [<FROM_ $chain:upper _LANE_DIAGNOSTICS_METHOD>]: &str =
+					stringify!([<From $chain:camel InboundLaneApi_lane_diagnostics>]);
+
 				sp_api::decl_runtime_apis! {
 					/// Outbound message lane API for messages that are sent to this chain.
 					///
@@ -339,6 +385,10 @@ macro_rules! decl_bridge_messages_runtime_apis {
 							begin: MessageNonce,
 							end: MessageNonce,
 						) -> Vec<OutboundMessageDetails>;
+
+						/// Returns details of the outbound lane, including the range of messages
+						/// which delivery is not yet confirmed and the lane operating mode.
+						fn lane_diagnostics(lane: LaneId) -> OutboundLaneDiagnostics;
 					}
 
 					/// Inbound message lane API for messages sent by this chain.
@@ -354,6 +404,22 @@ macro_rules! decl_bridge_messages_runtime_apis {
 							lane: LaneId,
 							messages: Vec<(MessagePayload, OutboundMessageDetails)>,
 						) -> Vec<InboundMessageDetails>;
+
+						/// Return storage keys that need to be proved to prove delivery and dispatch
+						/// result of given inbound message.
+						///
+						/// Returns `None` if the message has not been delivered yet or if its dispatch
+						/// result is no longer stored. The storage proof itself may be requested using
+						/// the `state_getReadProof` RPC method.
+						fn message_delivery_proof_keys(
+							lane: LaneId,
+							nonce: MessageNonce,
+						) -> Option<InboundMessageDeliveryProofKeys>;
+
+						/// Returns details of the inbound lane, including unrewarded relayer entries,
+						/// the range of delivered messages which delivery is not yet confirmed and
+						/// the lane operating mode.
+						fn lane_diagnostics(lane: LaneId) -> InboundLaneDiagnostics<AccountId>;
 					}
 				}
 			}
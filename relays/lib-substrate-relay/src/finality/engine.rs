@@ -19,8 +19,10 @@
 use crate::error::Error;
 use async_trait::async_trait;
 use bp_header_chain::{
-	justification::{verify_justification, GrandpaJustification},
-	ConsensusLogReader, FinalityProof, GrandpaConsensusLogReader,
+	justification::{
+		verify_and_optimize_justification, verify_justification, GrandpaJustification,
+	},
+	AuthoritySet, ConsensusLogReader, FinalityProof, GrandpaConsensusLogReader,
 };
 use bp_runtime::{BasicOperatingMode, OperatingMode};
 use codec::{Decode, Encode};
@@ -87,6 +89,16 @@ pub trait Engine<C: Chain>: Send {
 		client.subscribe_finality_justifications::<Self::FinalityClient>().await
 	}
 
+	/// Optimize finality proof before sending it to the target node.
+	///
+	/// The optimized proof is verified using the state of the bridge pallet at the target chain
+	/// and all items, that are not required to verify it, are removed from the proof.
+	async fn optimize_proof<TargetChain: Chain>(
+		target_client: &Client<TargetChain>,
+		header: &C::Header,
+		proof: Self::FinalityProof,
+	) -> Result<Self::FinalityProof, SubstrateError>;
+
 	/// Prepare initialization data for the finality bridge pallet.
 	async fn prepare_initialization_data(
 		client: Client<C>,
@@ -139,6 +151,49 @@ impl<C: ChainWithGrandpa> Engine<C> for Grandpa<C> {
 		bp_header_chain::storage_keys::pallet_operating_mode_key(C::WITH_CHAIN_GRANDPA_PALLET_NAME)
 	}
 
+	async fn optimize_proof<TargetChain: Chain>(
+		target_client: &Client<TargetChain>,
+		header: &C::Header,
+		proof: Self::FinalityProof,
+	) -> Result<Self::FinalityProof, SubstrateError> {
+		let current_authority_set_key = bp_header_chain::storage_keys::current_authority_set_key(
+			C::WITH_CHAIN_GRANDPA_PALLET_NAME,
+		);
+		let authority_set: AuthoritySet = target_client
+			.storage_value(current_authority_set_key, None)
+			.await?
+			.ok_or_else(|| {
+				SubstrateError::Custom(format!(
+					"{} `CurrentAuthoritySet` is missing from the {} state",
+					C::NAME,
+					TargetChain::NAME,
+				))
+			})?;
+		let authority_set_id = authority_set.set_id;
+		let authority_set = VoterSet::new(authority_set.authorities).ok_or_else(|| {
+			SubstrateError::Custom(format!(
+				"{} has invalid {} GRANDPA authorities set",
+				TargetChain::NAME,
+				C::NAME,
+			))
+		})?;
+
+		verify_and_optimize_justification(
+			(header.hash(), *header.number()),
+			authority_set_id,
+			&authority_set,
+			proof,
+		)
+		.map_err(|e| {
+			SubstrateError::Custom(format!(
+				"Failed to optimize {} GRANDPA justification for header {:?}: {:?}",
+				C::NAME,
+				header.hash(),
+				e,
+			))
+		})
+	}
+
 	/// Prepare initialization data for the GRANDPA verifier pallet.
 	async fn prepare_initialization_data(
 		source_client: Client<C>,
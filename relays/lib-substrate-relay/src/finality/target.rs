@@ -110,6 +110,9 @@ where
 		header: SyncHeader<HeaderOf<P::SourceChain>>,
 		proof: SubstrateFinalityProof<P>,
 	) -> Result<Self::TransactionTracker, Error> {
+		// remove redundant items from the finality proof to reduce transaction size and weight
+		let proof = P::FinalityEngine::optimize_proof(&self.client, &header, proof).await?;
+
 		let transaction_params = self.transaction_params.clone();
 		let call =
 			P::SubmitFinalityProofCallBuilder::build_submit_finality_proof_call(header, proof);
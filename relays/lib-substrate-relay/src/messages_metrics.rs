@@ -19,6 +19,7 @@
 use crate::TaggedAccount;
 
 use bp_messages::LaneId;
+use bp_relayers::{RewardKind, RewardsAccountParams};
 use bp_runtime::StorageDoubleMapKeyProvider;
 use codec::Decode;
 use frame_system::AccountInfo;
@@ -81,19 +82,28 @@ where
 		relay_account_balance_metric.register_and_spawn(&metrics.registry)?;
 
 		if let Some(relayers_pallet_name) = BC::WITH_CHAIN_RELAYERS_PALLET_NAME {
+			// reward keys are only valid for the relayers pallet at storage version 1 (see
+			// `pallet_bridge_relayers::migration`). Rewards that have been earned before the
+			// migration live in the message delivery rewards account, so its metric keeps the old
+			// name
 			for lane in lanes {
-				let relay_account_reward_metric = FloatStorageValueMetric::new(
-					AccountBalance::<C> { token_decimals, _phantom: Default::default() },
-					client.clone(),
-					bp_relayers::RelayerRewardsKeyProvider::<AccountIdOf<C>, BalanceOf<C>>::final_key(
-						relayers_pallet_name,
-						account.id(),
-						lane,
-					),
-					format!("at_{}_relay_{}_reward_for_lane_{}_with_{}", C::NAME, account.tag(), hex::encode(lane.as_ref()), BC::NAME),
-					format!("Reward of the {} relay account for serving lane {:?} with {} at the {}", account.tag(), lane, BC::NAME, C::NAME),
-				)?;
-				relay_account_reward_metric.register_and_spawn(&metrics.registry)?;
+				for (reward_kind, metric_name_prefix, reward_kind_name) in [
+					(RewardKind::MessageDelivery, "", "delivery"),
+					(RewardKind::DeliveryConfirmation, "confirmation_", "confirmation"),
+				] {
+					let relay_account_reward_metric = FloatStorageValueMetric::new(
+						AccountBalance::<C> { token_decimals, _phantom: Default::default() },
+						client.clone(),
+						bp_relayers::RelayerRewardsKeyProvider::<AccountIdOf<C>, BalanceOf<C>>::final_key(
+							relayers_pallet_name,
+							account.id(),
+							&RewardsAccountParams::new(*lane, reward_kind),
+						),
+						format!("at_{}_relay_{}_{}reward_for_lane_{}_with_{}", C::NAME, account.tag(), metric_name_prefix, hex::encode(lane.as_ref()), BC::NAME),
+						format!("Reward of the {} relay account for {} over lane {:?} with {} at the {}", account.tag(), reward_kind_name, lane, BC::NAME, C::NAME),
+					)?;
+					relay_account_reward_metric.register_and_spawn(&metrics.registry)?;
+				}
 			}
 		}
 	}
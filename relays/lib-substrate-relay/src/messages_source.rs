@@ -34,8 +34,10 @@ use bp_messages::{
 	InboundMessageDetails, LaneId, MessageNonce, MessagePayload, MessagesOperatingMode,
 	OutboundLaneData, OutboundMessageDetails,
 };
-use bp_runtime::{BasicOperatingMode, HeaderIdProvider};
-use bridge_runtime_common::messages::target::FromBridgedChainMessagesProof;
+use bp_runtime::{BasicOperatingMode, HasherOf, HeaderIdProvider};
+use bridge_runtime_common::messages::target::{
+	FromBridgedChainMessagesProof, MessagesStorageProof,
+};
 use codec::Encode;
 use frame_support::weights::Weight;
 use messages_relay::{
@@ -53,8 +55,12 @@ use relay_substrate_client::{
 };
 use relay_utils::relay_loop::Client as RelayClient;
 use sp_core::Pair;
+use sp_runtime::traits::Header as HeaderT;
 use std::ops::RangeInclusive;
 
+/// Compression level that is used to compress messages storage proofs.
+const STORAGE_PROOF_COMPRESSION_LEVEL: i32 = 19;
+
 /// Intermediate message proof returned by the source Substrate node. Includes everything
 /// required to submit to the target node: cumulative dispatch weight of bundled messages and
 /// the proof itself.
@@ -315,12 +321,16 @@ where
 			.await?
 			.into_iter_nodes()
 			.collect();
+		let state_root = *self.source_client.header_by_hash(id.1).await?.state_root();
+		let proof = MessagesStorageProof::compact::<HasherOf<P::SourceChain>>(proof, state_root)
+			.map_err(|e| SubstrateError::Custom(e.into()))?;
 		let proof = FromBridgedChainMessagesProof {
 			bridged_header_hash: id.1,
-			storage_proof: proof,
+			storage_proof: maybe_compress_storage_proof(proof),
 			lane: self.lane_id,
 			nonces_start: *nonces.start(),
 			nonces_end: *nonces.end(),
+			additional_lanes: Vec::new(),
 		};
 		Ok((id, nonces, (proof_parameters.dispatch_weight, proof)))
 	}
@@ -460,6 +470,37 @@ where
 		.ok_or(SubstrateError::BridgePalletIsNotInitialized)
 }
 
+/// Compresses messages storage proof if it makes the proof smaller.
+fn maybe_compress_storage_proof(storage_proof: MessagesStorageProof) -> MessagesStorageProof {
+	let encoded_compact_proof = match storage_proof {
+		MessagesStorageProof::Compact(ref compact_proof) => compact_proof.encode(),
+		MessagesStorageProof::CompressedCompact { .. } => return storage_proof,
+	};
+
+	let compressed =
+		match zstd::bulk::compress(&encoded_compact_proof, STORAGE_PROOF_COMPRESSION_LEVEL) {
+			Ok(compressed) => compressed,
+			Err(e) => {
+				log::warn!(
+					target: "bridge",
+					"Failed to compress messages storage proof: {:?}. Submitting uncompressed proof",
+					e,
+				);
+				return storage_proof
+			},
+		};
+	let compressed_storage_proof = MessagesStorageProof::CompressedCompact {
+		uncompressed_size: encoded_compact_proof.len() as _,
+		compressed,
+	};
+
+	if compressed_storage_proof.encoded_size() < storage_proof.encoded_size() {
+		compressed_storage_proof
+	} else {
+		storage_proof
+	}
+}
+
 fn validate_out_msgs_details<C: Chain>(
 	out_msgs_details: &[OutboundMessageDetails],
 	nonces: RangeInclusive<MessageNonce>,
@@ -704,4 +745,23 @@ mod tests {
 			Ok(vec![2, 4, 3]),
 		);
 	}
+
+	#[test]
+	fn maybe_compress_storage_proof_compresses_large_proofs() {
+		let proof = MessagesStorageProof::Compact(sp_trie::CompactProof {
+			encoded_nodes: vec![vec![42u8; 1024]; 16],
+		});
+		assert!(matches!(
+			maybe_compress_storage_proof(proof),
+			MessagesStorageProof::CompressedCompact { uncompressed_size, .. }
+				if uncompressed_size > 16 * 1024,
+		));
+	}
+
+	#[test]
+	fn maybe_compress_storage_proof_keeps_proof_if_compression_is_useless() {
+		let proof =
+			MessagesStorageProof::Compact(sp_trie::CompactProof { encoded_nodes: vec![vec![42]] });
+		assert_eq!(maybe_compress_storage_proof(proof.clone()), proof);
+	}
 }
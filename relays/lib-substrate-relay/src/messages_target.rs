@@ -214,9 +214,21 @@ where
 			P::SourceChain::WITH_CHAIN_MESSAGES_PALLET_NAME,
 			&self.lane_id,
 		);
+		// we also prove dispatch results of all unrewarded messages, so that the source chain
+		// applications may react to failed dispatches
+		let dispatch_result_nonces = unrewarded_nonces(&relayers_state).collect::<Vec<_>>();
+		let storage_keys = std::iter::once(inbound_data_key)
+			.chain(dispatch_result_nonces.iter().map(|nonce| {
+				bp_messages::storage_keys::inbound_message_dispatch_result_key(
+					P::SourceChain::WITH_CHAIN_MESSAGES_PALLET_NAME,
+					&self.lane_id,
+					*nonce,
+				)
+			}))
+			.collect();
 		let proof = self
 			.target_client
-			.prove_storage(vec![inbound_data_key], id.1)
+			.prove_storage(storage_keys, id.1)
 			.await?
 			.into_iter_nodes()
 			.collect();
@@ -224,6 +236,7 @@ where
 			bridged_header_hash: id.1,
 			storage_proof: proof,
 			lane: self.lane_id,
+			dispatch_result_nonces,
 		};
 		Ok((id, (relayers_state, proof)))
 	}
@@ -295,3 +308,30 @@ fn make_messages_delivery_call<P: SubstrateMessageLane>(
 		trace_call,
 	)
 }
+
+/// Returns nonces of messages that have been delivered, but not yet confirmed.
+fn unrewarded_nonces(relayers_state: &UnrewardedRelayersState) -> RangeInclusive<MessageNonce> {
+	relayers_state
+		.last_delivered_nonce
+		.saturating_sub(relayers_state.total_messages)
+		.saturating_add(1)..=relayers_state.last_delivered_nonce
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unrewarded_nonces_works() {
+		assert!(unrewarded_nonces(&UnrewardedRelayersState::default()).is_empty());
+		assert_eq!(
+			unrewarded_nonces(&UnrewardedRelayersState {
+				unrewarded_relayer_entries: 2,
+				messages_in_oldest_entry: 1,
+				total_messages: 3,
+				last_delivered_nonce: 10,
+			}),
+			8..=10,
+		);
+	}
+}